@@ -0,0 +1,173 @@
+use std::{
+    cmp::Reverse,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::error::AppError;
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Name of the active log file inside the app log dir
+/// app log 目錄中目前使用中的 log 檔名
+pub(crate) const ACTIVE_LOG_FILE: &str = "app.log";
+
+/// The active file is rotated to `app-<secs>.log` once it reaches this size
+/// 使用中的 log 達到此大小時會輪替為 `app-<secs>.log`
+const MAX_FILE_BYTES: u64 = 50 * 1024;
+
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Serializes writes so rotation never races an append
+/// 序列化寫入，避免輪替與寫入同時發生
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// What `cleanup` removed
+/// `cleanup` 移除的檔案統計
+#[derive(Serialize)]
+pub(crate) struct CleanupResult {
+    removed_files: u32,
+    removed_bytes: u64,
+}
+
+// ───────────────────────────────────────────────
+// App Log / 應用程式 log
+// ───────────────────────────────────────────────
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Set the directory `app.log` is written to; later calls are ignored
+/// 設定 `app.log` 的寫入目錄；之後的呼叫會被忽略
+pub(crate) fn init(log_dir: PathBuf) {
+    let _ = LOG_DIR.set(log_dir);
+}
+
+/// Move a full active file aside so the next append starts a new one
+/// 使用中的 log 已滿時改名保存，下一筆寫入會建立新檔
+fn rotate_if_full(dir: &Path) -> std::io::Result<()> {
+    let active = dir.join(ACTIVE_LOG_FILE);
+    let full = fs::metadata(&active)
+        .map(|meta| meta.len() >= MAX_FILE_BYTES)
+        .unwrap_or(false);
+    if full {
+        fs::rename(&active, dir.join(format!("app-{}.log", now_secs())))?;
+    }
+    Ok(())
+}
+
+/// Append one entry to the app log, falling back to stderr when it can't be written
+/// 寫入一筆 app log；無法寫入時改輸出至 stderr
+pub(crate) fn write(entry: &str) {
+    let line = format!("[{}] {}", now_secs(), entry);
+
+    let Some(dir) = LOG_DIR.get() else {
+        eprintln!("{}", line);
+        return;
+    };
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let written = fs::create_dir_all(dir).and_then(|_| {
+        rotate_if_full(dir)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(ACTIVE_LOG_FILE))?;
+        writeln!(file, "{}", line)
+    });
+    if written.is_err() {
+        eprintln!("{}", line);
+    }
+}
+
+// ───────────────────────────────────────────────
+// Maintenance / 維護
+// ───────────────────────────────────────────────
+
+/// Delete rotated log files beyond the newest `keep_files` or older than `keep_days`
+/// (`0` disables the age limit). The active file is never touched; files that can't be
+/// removed are logged and skipped.
+/// 刪除超過最新 `keep_files` 個或超過 `keep_days` 天的輪替 log（`0` 表示不限天數）；
+/// 不會動到使用中的檔案，無法刪除的檔案會記錄後略過
+pub(crate) fn cleanup(
+    dir: &Path,
+    keep_files: u32,
+    keep_days: u32,
+) -> Result<CleanupResult, AppError> {
+    let mut result = CleanupResult {
+        removed_files: 0,
+        removed_bytes: 0,
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(result),
+        Err(e) => return Err(AppError::io("io.list_app_logs", Some(dir), e)),
+    };
+
+    let mut rotated = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            name != ACTIVE_LOG_FILE && name.ends_with(".log")
+        })
+        .filter_map(|e| {
+            let meta = e.metadata().ok().filter(|m| m.is_file())?;
+            Some((e.path(), meta.len(), meta.modified().unwrap_or(UNIX_EPOCH)))
+        })
+        .collect::<Vec<_>>();
+    // Newest first, so the first `keep_files` are the ones retained
+    // 由新到舊排序，前 `keep_files` 個保留
+    rotated.sort_by_key(|(_, _, modified)| Reverse(*modified));
+
+    let max_age = Duration::from_secs(u64::from(keep_days) * 24 * 60 * 60);
+    let now = SystemTime::now();
+    for (index, (path, size, modified)) in rotated.into_iter().enumerate() {
+        let too_many = index >= keep_files as usize;
+        let too_old = keep_days > 0
+            && now
+                .duration_since(modified)
+                .map(|age| age > max_age)
+                .unwrap_or(false);
+        if !too_many && !too_old {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                result.removed_files += 1;
+                result.removed_bytes += size;
+            }
+            Err(e) => write(&format!(
+                "failed to remove old log {}: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+    Ok(result)
+}
+
+/// Last `lines` lines of the active log file (empty if it doesn't exist yet)
+/// 使用中 log 的最後 `lines` 行（檔案尚不存在時為空）
+pub(crate) fn read_recent(dir: &Path, lines: u32) -> Result<Vec<String>, AppError> {
+    let path = dir.join(ACTIVE_LOG_FILE);
+    let content = match fs::read(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AppError::io("io.read_app_log", Some(&path), e)),
+    };
+    let content = String::from_utf8_lossy(&content);
+    let all = content.lines().collect::<Vec<_>>();
+    let start = all.len().saturating_sub(lines as usize);
+    Ok(all[start..].iter().map(|line| line.to_string()).collect())
+}
@@ -0,0 +1,452 @@
+use std::{
+    fs::File,
+    io::{Read, Seek},
+    path::Path,
+};
+
+use serde::Serialize;
+use zip::{DateTime, ZipArchive};
+
+use crate::{
+    cloud_files,
+    error::AppError,
+    long_path,
+    manifest::{self, ManifestData},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Characters of the archive comment kept by `ZipProvenance::compact`
+/// `ZipProvenance::compact` 保留的壓縮檔註解字元數
+const COMPACT_COMMENT_CHARS: usize = 200;
+
+/// Container format of a downloaded archive
+/// 下載壓縮檔的格式
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ArchiveFormat {
+    Zip,
+    SevenZ,
+    Rar,
+}
+
+/// What a downloaded archive holds, read without extracting it
+/// 不解壓縮即讀出的下載壓縮檔內容
+#[derive(Serialize)]
+pub(crate) struct ArchiveInspection {
+    pub format: ArchiveFormat,
+    /// Parsed manifest.xml, if the archive contains one
+    /// 若壓縮檔內含 manifest.xml 則為解析結果
+    pub manifest: Option<ManifestData>,
+    /// Every file entry, directories excluded
+    /// 所有檔案 entry（不含資料夾）
+    pub entries: Vec<String>,
+    /// Zip-level metadata; None for 7z and rar
+    /// zip 層級的中繼資料；7z 與 rar 為 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<ZipProvenance>,
+}
+
+/// Traces a zip keeps of how it was made, for telling an original release from a re-zipped
+/// copy when manifests and sizes match
+/// zip 保留的製作痕跡，用於在 manifest 與大小皆相同時分辨原始發布版與重新壓縮的複本
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub(crate) struct ZipProvenance {
+    /// Archive comment, some authors stamp their releases with; invalid UTF-8 is replaced,
+    /// None when empty
+    /// 壓縮檔註解，部分作者會在發布時加上；無效的 UTF-8 會被取代，空白時為 None
+    pub comment: Option<String>,
+    /// Oldest and newest file modification time in the central directory as
+    /// `YYYY-MM-DDTHH:MM:SS`, in the zipping machine's local time since DOS times carry no zone;
+    /// files with the default date are left out
+    /// 中央目錄中最舊與最新的檔案修改時間，格式為 `YYYY-MM-DDTHH:MM:SS`；DOS 時間不含時區，因此為壓縮時所用電腦的當地時間；
+    /// 不計入使用預設日期的檔案
+    pub oldest_entry: Option<String>,
+    pub newest_entry: Option<String>,
+    /// Files dated 1980-01-01 00:00:00, the DOS epoch tools write when the original time was
+    /// lost, a sign of re-zipping
+    /// 日期為 1980-01-01 00:00:00 的檔案數；此為工具遺失原始時間時寫入的 DOS 起始時間，代表曾被重新壓縮
+    pub default_dated_entries: usize,
+}
+
+impl ArchiveFormat {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::SevenZ => "7z",
+            ArchiveFormat::Rar => "rar",
+        }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Format Detection / 格式判斷
+// ───────────────────────────────────────────────
+
+/// Tell the format from the magic bytes, falling back to the extension when the file
+/// cannot be read or starts with something else
+/// 依檔頭判斷格式；無法讀取或檔頭不符時改依副檔名判斷
+pub(crate) fn detect_format(path: &Path) -> Option<ArchiveFormat> {
+    let mut magic = [0u8; 6];
+    let read = File::open(long_path::extend(path))
+        .and_then(|mut file| file.read(&mut magic))
+        .unwrap_or(0);
+    match &magic[..read] {
+        [b'P', b'K', 3, 4, ..] | [b'P', b'K', 5, 6, ..] => return Some(ArchiveFormat::Zip),
+        [b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C] => return Some(ArchiveFormat::SevenZ),
+        [b'R', b'a', b'r', b'!', 0x1A, 0x07] => return Some(ArchiveFormat::Rar),
+        _ => {}
+    }
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    match extension.as_str() {
+        "zip" | "zipmod" => Some(ArchiveFormat::Zip),
+        "7z" => Some(ArchiveFormat::SevenZ),
+        "rar" => Some(ArchiveFormat::Rar),
+        _ => None,
+    }
+}
+
+/// The manifest entry nearest the archive root, which is the mod root when several exist
+/// 最接近壓縮檔根目錄的 manifest entry；有多個時其所在即為模組根目錄
+fn shallowest_manifest<'a>(entries: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    entries
+        .filter(|name| manifest::is_manifest_entry(name))
+        .min_by_key(|name| name.trim_matches('/').matches('/').count())
+}
+
+fn parse_found(path: &Path, content: Option<String>) -> Result<Option<ManifestData>, AppError> {
+    content
+        .map(|content| {
+            manifest::parse_manifest(&content)
+                .map_err(|e| e.with_archive_path(&long_path::display(path)))
+        })
+        .transpose()
+}
+
+// ───────────────────────────────────────────────
+// Zip Provenance / Zip 製作痕跡
+// ───────────────────────────────────────────────
+
+fn format_dos_time(time: DateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        time.year(),
+        time.month(),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// Read the comment and the file times from the central directory, decompressing nothing
+/// 從中央目錄讀取註解與檔案時間，不解壓縮任何內容
+fn zip_provenance<R: Read + Seek>(archive: &mut ZipArchive<R>) -> ZipProvenance {
+    let comment = String::from_utf8_lossy(archive.comment())
+        .trim_start_matches('\u{feff}')
+        .trim_end_matches(['\0', ' ', '\r', '\n'])
+        .to_string();
+    let mut times = Vec::new();
+    for index in 0..archive.len() {
+        let Ok(entry) = archive.by_index_raw(index) else {
+            continue;
+        };
+        if let Some(time) = entry.last_modified().filter(|_| !entry.is_dir()) {
+            times.push(time);
+        }
+    }
+    let default_dated_entries = times
+        .iter()
+        .filter(|time| **time == DateTime::default())
+        .count();
+    let dated = times.iter().filter(|time| **time != DateTime::default());
+    ZipProvenance {
+        comment: (!comment.is_empty()).then_some(comment),
+        oldest_entry: dated.clone().min().copied().map(format_dos_time),
+        newest_entry: dated.max().copied().map(format_dos_time),
+        default_dated_entries,
+    }
+}
+
+impl ZipProvenance {
+    /// Copy with the comment cut to `COMPACT_COMMENT_CHARS`, for attaching to a mod entry
+    /// 將註解截斷至 `COMPACT_COMMENT_CHARS` 的複本，供附加到模組項目
+    pub(crate) fn compact(mut self) -> Self {
+        if let Some(comment) = &mut self.comment {
+            if let Some((cut, _)) = comment.char_indices().nth(COMPACT_COMMENT_CHARS) {
+                comment.truncate(cut);
+                comment.push('…');
+            }
+        }
+        self
+    }
+}
+
+fn open_zip(path: &Path) -> Result<ZipArchive<File>, AppError> {
+    let file = File::open(long_path::extend(path))
+        .map_err(|e| AppError::io("io.open_file", Some(path), e))?;
+    ZipArchive::new(file).map_err(|e| AppError::ZipInvalid {
+        path: Some(long_path::display(path)),
+        detail: e.to_string(),
+    })
+}
+
+/// Zip-level metadata of a zipmod or zip download
+/// zipmod 或 zip 下載檔的 zip 層級中繼資料
+pub(crate) fn read_zip_provenance(path: &Path) -> Result<ZipProvenance, AppError> {
+    Ok(zip_provenance(&mut open_zip(path)?))
+}
+
+// ───────────────────────────────────────────────
+// Inspection / 檢視
+// ───────────────────────────────────────────────
+
+fn inspect_zip(path: &Path) -> Result<ArchiveInspection, AppError> {
+    let display = long_path::display(path);
+    let mut archive = open_zip(path)?;
+    let entries = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let content = match shallowest_manifest(entries.iter().map(String::as_str)) {
+        Some(name) => {
+            let mut content = String::new();
+            archive
+                .by_name(name)
+                .map_err(|e| e.to_string())
+                .and_then(|mut entry| {
+                    entry
+                        .read_to_string(&mut content)
+                        .map_err(|e| e.to_string())
+                })
+                .map_err(|detail| AppError::ZipInvalid {
+                    path: Some(display.clone()),
+                    detail,
+                })?;
+            Some(content)
+        }
+        None => None,
+    };
+    Ok(ArchiveInspection {
+        format: ArchiveFormat::Zip,
+        manifest: parse_found(path, content)?,
+        entries,
+        provenance: Some(zip_provenance(&mut archive)),
+    })
+}
+
+/// List a 7z archive from its header and decode only the block holding manifest.xml.
+/// Earlier files of a solid block still have to be decompressed to reach it.
+/// 由標頭列出 7z 內容，只解碼包含 manifest.xml 的區塊；solid 區塊中位於其前的檔案仍須解壓縮才能讀到
+#[cfg(feature = "sevenz")]
+fn inspect_7z(path: &Path) -> Result<ArchiveInspection, AppError> {
+    use sevenz_rust::{Archive, BlockDecoder};
+    use std::io;
+
+    let invalid = |e: sevenz_rust::Error| AppError::ArchiveInvalid {
+        path: long_path::display(path),
+        format: ArchiveFormat::SevenZ.label(),
+        detail: e.to_string(),
+    };
+    let mut file = File::open(long_path::extend(path))
+        .map_err(|e| AppError::io("io.open_file", Some(path), e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| AppError::io("io.open_file", Some(path), e))?
+        .len();
+    let archive = Archive::read(&mut file, len, &[]).map_err(invalid)?;
+
+    let entries = archive
+        .files
+        .iter()
+        .filter(|entry| !entry.is_directory())
+        .map(|entry| entry.name().to_string())
+        .collect::<Vec<_>>();
+    let target = shallowest_manifest(entries.iter().map(String::as_str)).map(str::to_string);
+    let folder = target.as_ref().and_then(|target| {
+        let index = archive
+            .files
+            .iter()
+            .position(|entry| entry.name() == target)?;
+        archive.stream_map.file_folder_index[index]
+    });
+
+    let mut content = None;
+    if let (Some(target), Some(folder)) = (&target, folder) {
+        BlockDecoder::new(folder, &archive, &[], &mut file)
+            .for_each_entries(&mut |entry, reader| {
+                if entry.name() != target {
+                    // Skipped files must still be read through so the next starts in place
+                    // 略過的檔案仍須讀完，下一個檔案才會從正確位置開始
+                    io::copy(reader, &mut io::sink())?;
+                    return Ok(true);
+                }
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                content = Some(text);
+                Ok(false)
+            })
+            .map_err(invalid)?;
+    }
+    Ok(ArchiveInspection {
+        format: ArchiveFormat::SevenZ,
+        manifest: parse_found(path, content)?,
+        entries,
+        provenance: None,
+    })
+}
+
+/// List a rar archive and extract only manifest.xml through the UnRAR library
+/// 透過 UnRAR 函式庫列出 rar 內容，只解出 manifest.xml
+#[cfg(feature = "rar")]
+fn inspect_rar(path: &Path) -> Result<ArchiveInspection, AppError> {
+    use unrar::Archive;
+
+    let invalid = |e: unrar::error::UnrarError| AppError::ArchiveInvalid {
+        path: long_path::display(path),
+        format: ArchiveFormat::Rar.label(),
+        detail: e.to_string(),
+    };
+    let entries = Archive::new(path)
+        .open_for_listing()
+        .map_err(invalid)?
+        .filter_map(|header| match header {
+            Ok(header) if header.is_directory() => None,
+            Ok(header) => Some(Ok(header.filename.to_string_lossy().replace('\\', "/"))),
+            Err(e) => Some(Err(invalid(e))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut content = None;
+    if let Some(target) = shallowest_manifest(entries.iter().map(String::as_str)) {
+        let mut archive = Archive::new(path).open_for_processing().map_err(invalid)?;
+        while let Some(header) = archive.read_header().map_err(invalid)? {
+            let name = header.entry().filename.to_string_lossy().replace('\\', "/");
+            if name != target {
+                archive = header.skip().map_err(invalid)?;
+                continue;
+            }
+            let (data, _) = header.read().map_err(invalid)?;
+            content = Some(String::from_utf8_lossy(&data).into_owned());
+            break;
+        }
+    }
+    Ok(ArchiveInspection {
+        format: ArchiveFormat::Rar,
+        manifest: parse_found(path, content)?,
+        entries,
+        provenance: None,
+    })
+}
+
+/// Fail with `archive_format_unsupported` when `format` was left out of this build
+/// 此版本未包含 `format` 時回傳 `archive_format_unsupported`
+pub(crate) fn ensure_supported(path: &Path, format: ArchiveFormat) -> Result<(), AppError> {
+    let supported = match format {
+        ArchiveFormat::Zip => true,
+        ArchiveFormat::SevenZ => cfg!(feature = "sevenz"),
+        ArchiveFormat::Rar => cfg!(feature = "rar"),
+    };
+    match supported {
+        true => Ok(()),
+        false => Err(AppError::ArchiveFormatUnsupported {
+            path: long_path::display(path),
+            format: format.label(),
+        }),
+    }
+}
+
+/// Identify a zip, 7z or rar download: its file listing and manifest, plus the zip comment
+/// and entry dates, extracting nothing else. Formats left out of this build fail with
+/// `archive_format_unsupported`.
+/// 辨識 zip、7z 或 rar 下載檔：只讀出檔案清單與 manifest，以及 zip 的註解與 entry 日期，不解出其他內容；
+/// 此版本未包含的格式回傳 `archive_format_unsupported`
+pub(crate) fn inspect_archive(path: &Path) -> Result<ArchiveInspection, AppError> {
+    let format = detect_format(path).unwrap_or(ArchiveFormat::Zip);
+    ensure_supported(path, format)?;
+    match format {
+        ArchiveFormat::Zip => inspect_zip(path),
+        #[cfg(feature = "sevenz")]
+        ArchiveFormat::SevenZ => inspect_7z(path),
+        #[cfg(feature = "rar")]
+        ArchiveFormat::Rar => inspect_rar(path),
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("checked by ensure_supported"),
+    }
+}
+
+/// Manifest of a zipmod or of a 7z/rar download, erroring when it has none or is a cloud
+/// placeholder that may not be opened
+/// 讀取 zipmod 或 7z/rar 下載檔的 manifest；沒有 manifest 或為不可開啟的雲端預留位置檔案時回傳錯誤
+pub(crate) fn read_manifest(path: &Path) -> Result<ManifestData, AppError> {
+    cloud_files::ensure_local(path)?;
+    match detect_format(path) {
+        None | Some(ArchiveFormat::Zip) => {
+            manifest::read_manifest_from_path(&path.to_string_lossy())
+        }
+        Some(_) => inspect_archive(path)?
+            .manifest
+            .ok_or(AppError::ManifestMissing {
+                path: Some(long_path::display(path)),
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use zip::DateTime;
+
+    use super::{inspect_archive, ZipProvenance};
+    use crate::test_support::{write_dated_zip, SyntheticInstall};
+
+    #[test]
+    fn zip_provenance_reports_comment_date_range_and_default_dates() {
+        let install = SyntheticInstall::new();
+        let date = |y, m, d, h| DateTime::from_date_and_time(y, m, d, h, 30, 0).unwrap();
+        let original = install.mod_path("original.zipmod");
+        write_dated_zip(
+            &original,
+            Some("Released by Author \u{2605}\0"),
+            &[
+                ("readme.txt", date(2021, 3, 4, 5)),
+                ("abdata/a.unity3d", date(2020, 12, 31, 23)),
+                ("abdata/b.unity3d", date(2022, 1, 2, 3)),
+            ],
+        );
+        let rezipped = install.mod_path("rezipped.zipmod");
+        write_dated_zip(
+            &rezipped,
+            None,
+            &[
+                ("readme.txt", DateTime::default()),
+                ("abdata/a.unity3d", DateTime::default()),
+                ("abdata/b.unity3d", date(2023, 6, 7, 8)),
+            ],
+        );
+
+        let provenance = |path: &Path| inspect_archive(path).unwrap().provenance.unwrap();
+        assert_eq!(
+            provenance(&original),
+            ZipProvenance {
+                comment: Some("Released by Author \u{2605}".to_string()),
+                oldest_entry: Some("2020-12-31T23:30:00".to_string()),
+                newest_entry: Some("2022-01-02T03:30:00".to_string()),
+                default_dated_entries: 0,
+            }
+        );
+        assert_eq!(
+            provenance(&rezipped),
+            ZipProvenance {
+                comment: None,
+                oldest_entry: Some("2023-06-07T08:30:00".to_string()),
+                newest_entry: Some("2023-06-07T08:30:00".to_string()),
+                default_dated_entries: 2,
+            }
+        );
+    }
+}
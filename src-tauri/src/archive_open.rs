@@ -0,0 +1,148 @@
+use std::{fs, path::Path};
+
+use serde::Serialize;
+
+use crate::{error::AppError, long_path, messages::Message};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// How `open_mod_archive` got the archive in front of the user
+/// `open_mod_archive` 以何種方式開啟壓縮檔
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum OpenedWith {
+    /// The program registered for the file's own extension
+    /// 檔案本身副檔名所註冊的程式
+    DefaultHandler,
+    /// The Windows "Open with" dialog, where the user picks the program
+    /// Windows 的「開啟檔案」對話框，由使用者選擇程式
+    OpenWithDialog,
+    /// A temporary `.zip` copy opened by the `.zip` handler; edits to it do not reach the mod
+    /// 以 `.zip` 程式開啟的暫時 `.zip` 複本；對其所做的修改不會寫回模組
+    ZipCopy { copy_path: String },
+}
+
+// ───────────────────────────────────────────────
+// Opening / 開啟
+// ───────────────────────────────────────────────
+
+fn launch_error(path: &Path, e: impl ToString) -> AppError {
+    AppError::io("io.open_archive", Some(path), e.to_string())
+}
+
+/// Whether Windows knows a program for files with `extension` (including the dot)
+/// 判斷 Windows 是否有處理 `extension`（含點）檔案的程式
+#[cfg(windows)]
+fn has_handler(extension: &str) -> bool {
+    use windows_sys::Win32::UI::Shell::{
+        AssocQueryStringW, ASSOCF_INIT_IGNOREUNKNOWN, ASSOCSTR_EXECUTABLE,
+    };
+
+    let extension = extension.encode_utf16().chain([0]).collect::<Vec<_>>();
+    let mut len = 0u32;
+    // SAFETY: `extension` is NUL-terminated and outlives the call; a null output only asks for
+    // the length
+    let result = unsafe {
+        AssocQueryStringW(
+            ASSOCF_INIT_IGNOREUNKNOWN,
+            ASSOCSTR_EXECUTABLE,
+            extension.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null_mut(),
+            &mut len,
+        )
+    };
+    result >= 0 && len > 0
+}
+
+/// Copy `path` into the temp folder under a `.zip` name the `.zip` handler accepts
+/// 將 `path` 以 `.zip` 檔名複製到暫存資料夾，供 `.zip` 程式開啟
+#[cfg(windows)]
+fn zip_copy(path: &Path) -> Result<std::path::PathBuf, AppError> {
+    let dir = std::env::temp_dir().join("kk-mod-manager-open");
+    fs::create_dir_all(&dir).map_err(|e| AppError::io("io.open_archive_copy", Some(&dir), e))?;
+    let stem = path.file_stem().unwrap_or(path.as_os_str());
+    let copy = dir.join(stem).with_extension("zip");
+    fs::copy(long_path::extend(path), &copy)
+        .map_err(|e| AppError::io("io.open_archive_copy", Some(path), e))?;
+    Ok(copy)
+}
+
+/// Open the archive with its own handler. Without one, show the "Open with" dialog, and if
+/// that cannot start, open a temporary `.zip` copy with the `.zip` handler.
+/// 以檔案本身的程式開啟壓縮檔；沒有時顯示「開啟檔案」對話框，無法顯示時改以 `.zip` 程式開啟暫時的 `.zip` 複本
+#[cfg(windows)]
+fn launch(path: &Path) -> Result<OpenedWith, AppError> {
+    use std::{os::windows::process::CommandExt, process::Command};
+
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    if !extension.is_empty() && has_handler(&extension) {
+        tauri_plugin_opener::open_path(path, None::<&str>).map_err(|e| launch_error(path, e))?;
+        return Ok(OpenedWith::DefaultHandler);
+    }
+
+    // rundll32 hands the rest of the command line over as is, so the path must not be quoted
+    // rundll32 會將其餘命令列原樣傳入，因此路徑不可加上引號
+    let dialog = Command::new("rundll32.exe")
+        .arg("shell32.dll,OpenAs_RunDLL")
+        .raw_arg(path.as_os_str())
+        .spawn();
+    if dialog.is_ok() {
+        return Ok(OpenedWith::OpenWithDialog);
+    }
+
+    if !has_handler(".zip") {
+        return Err(AppError::NoArchiveHandler {
+            path: long_path::display(path),
+        });
+    }
+    let copy = zip_copy(path)?;
+    tauri_plugin_opener::open_path(&copy, None::<&str>).map_err(|e| launch_error(&copy, e))?;
+    Ok(OpenedWith::ZipCopy {
+        copy_path: long_path::display(&copy),
+    })
+}
+
+/// Hand the archive to `xdg-open` (`open` on macOS), which exits non-zero when no program
+/// takes the file
+/// 將壓縮檔交給 `xdg-open`（macOS 為 `open`）；沒有程式可開啟時會以非零狀態結束
+#[cfg(not(windows))]
+fn launch(path: &Path) -> Result<OpenedWith, AppError> {
+    use std::{io::ErrorKind, process::Command};
+
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    let no_handler = || AppError::NoArchiveHandler {
+        path: long_path::display(path),
+    };
+    match Command::new(opener).arg(path).status() {
+        Ok(status) if status.success() => Ok(OpenedWith::DefaultHandler),
+        Ok(_) => Err(no_handler()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Err(no_handler()),
+        Err(e) => Err(launch_error(path, e)),
+    }
+}
+
+/// Open a mod archive in the user's archive program, for a look inside. `.zipmod` has no
+/// association on most systems, so this falls back further than the opener plugin does and
+/// fails with `NoArchiveHandler` when nothing can take the file.
+/// 以使用者的壓縮程式開啟模組壓縮檔以查看內容；多數系統的 `.zipmod` 沒有關聯程式，因此比 opener 外掛多了後備方式，
+/// 沒有任何程式可開啟時回傳 `NoArchiveHandler`
+pub(crate) fn open_mod_archive(path: &Path) -> Result<OpenedWith, AppError> {
+    let metadata = fs::metadata(long_path::extend(path))
+        .map_err(|e| AppError::io("io.open_archive", Some(path), e))?;
+    if metadata.is_dir() {
+        return Err(AppError::InvalidInput {
+            message: Message::new("input.open_directory").with("path", long_path::display(path)),
+        });
+    }
+    launch(path)
+}
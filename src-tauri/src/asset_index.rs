@@ -0,0 +1,624 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+#[cfg(feature = "sqlite")]
+use crate::index_db;
+use crate::{
+    app_log, cloud_files,
+    error::AppError,
+    links, long_path,
+    manifest::{read_manifest_from_archive, ManifestData},
+    messages::Message,
+    mod_roots, nested_archives,
+    operations::CancelToken,
+    parser::{build_mod_entry, ModEntry},
+    progress::{self, OnProgress, Progress},
+    timings::Timings,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Bumped whenever `IndexedMod` gains data older indexes lack; mismatched indexes are rebuilt
+/// `IndexedMod` 新增舊索引沒有的資料時遞增；版本不符的索引會重建
+const INDEX_VERSION: u32 = 5;
+
+/// Extensions treated as mod archives when a profile does not set its own
+/// profile 未自訂時視為模組壓縮檔的副檔名
+pub(crate) const DEFAULT_MOD_EXTENSIONS: &[&str] = &["zipmod", "zip"];
+
+/// Cached central-directory listing and manifest of one zipmod
+/// 單一 zipmod 的 central directory 與 manifest 快取
+#[derive(Serialize, Deserialize)]
+pub(crate) struct IndexedMod {
+    pub size: u64,
+    pub modified: Option<u64>,
+    pub manifest: Option<ManifestData>,
+    pub entries: Vec<String>,
+    /// Skipped as a cloud placeholder, so read again on the next refresh
+    /// 因雲端預留位置而略過，下次更新時重新讀取
+    #[serde(default)]
+    pub placeholder: bool,
+}
+
+impl IndexedMod {
+    fn guid(&self) -> Option<&str> {
+        self.manifest
+            .as_ref()
+            .map(|manifest| manifest.guid.as_str())
+    }
+}
+
+/// Persistent asset index, keyed by absolute zipmod path
+/// 持久化的資源索引，以 zipmod 絕對路徑為 key
+#[derive(Serialize, Deserialize, Default)]
+struct AssetIndex {
+    #[serde(default)]
+    version: u32,
+    mods: HashMap<String, IndexedMod>,
+}
+
+/// One archive of the index as handed to the offline scanners
+/// 交給離線掃描器的單一索引壓縮檔
+pub(crate) struct IndexedArchive {
+    pub path: String,
+    pub manifest: Option<ManifestData>,
+    pub inner_zipmods: Vec<String>,
+}
+
+/// Summary returned after (re)building the index
+/// 建立索引後回傳的摘要
+#[derive(Serialize)]
+pub(crate) struct AssetIndexSummary {
+    pub mods_indexed: usize,
+    pub mods_reread: usize,
+    pub entries: usize,
+    /// Cloud placeholders left unread, listed without manifest or entries
+    /// 未讀取的雲端預留位置檔案，列出時沒有 manifest 與 entry
+    pub cloud_placeholders: usize,
+    /// Per-phase milliseconds, only present when requested
+    /// 各階段毫秒數，僅在要求時出現
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timings: Option<BTreeMap<&'static str, f64>>,
+}
+
+/// Files of one GUID providing a colliding asset
+/// 提供同一衝突資源的某個 GUID 及其檔案
+#[derive(Serialize)]
+pub(crate) struct CollisionProvider {
+    guid: String,
+    paths: Vec<String>,
+}
+
+/// An abdata path provided by two or more distinct GUIDs
+/// 被兩個以上不同 GUID 提供的 abdata 路徑
+#[derive(Serialize)]
+pub(crate) struct AssetCollision {
+    asset_path: String,
+    providers: Vec<CollisionProvider>,
+}
+
+/// One zipmod providing the requested asset
+/// 提供指定資源的 zipmod
+#[derive(Serialize)]
+pub(crate) struct AssetProvider {
+    #[serde(rename = "mod")]
+    mod_entry: ModEntry,
+    guid: Option<String>,
+    entries: Vec<String>,
+}
+
+// ───────────────────────────────────────────────
+// Utility Function / 工具函式
+// ───────────────────────────────────────────────
+
+/// Normalize an asset path for comparison (forward slashes, lowercase)
+/// 正規化資源路徑以便比對（統一斜線、轉小寫）
+fn normalize_asset_path(path: &str) -> String {
+    path.replace('\\', "/").trim_matches('/').to_lowercase()
+}
+
+/// Whether a normalized entry name matches the query exactly or as a path suffix
+/// 判斷 entry 名稱是否與查詢完全相符，或以整段路徑結尾相符
+fn entry_matches(entry: &str, query: &str) -> bool {
+    match entry.strip_suffix(query) {
+        Some(prefix) => prefix.is_empty() || prefix.ends_with('/'),
+        None => false,
+    }
+}
+
+/// Whether the file looks like a Sideloader mod archive, i.e. has one of `extensions`.
+/// Extensions compare case-insensitively and may be given with or without the leading dot.
+/// 判斷檔案是否為 Sideloader 模組壓縮檔，即副檔名屬於 `extensions`；不分大小寫，可含或不含開頭的點
+pub(crate) fn is_mod_archive<S: AsRef<str>>(path: &Path, extensions: &[S]) -> bool {
+    path.extension().is_some_and(|ext| {
+        let ext = ext.to_string_lossy();
+        extensions.iter().any(|accepted| {
+            accepted
+                .as_ref()
+                .trim_start_matches('.')
+                .eq_ignore_ascii_case(&ext)
+        })
+    })
+}
+
+/// Read entry names (central directory only) and manifest of a zipmod
+/// 讀取 zipmod 的 entry 清單（僅 central directory）與 manifest
+fn read_index_record(
+    path: &Path,
+    size: u64,
+    modified: Option<u64>,
+    timings: &Timings,
+) -> IndexedMod {
+    let archive = timings.time("archive_ms", || {
+        File::open(long_path::extend(path))
+            .ok()
+            .and_then(|file| ZipArchive::new(file).ok())
+    });
+
+    let Some(mut archive) = archive else {
+        return IndexedMod {
+            size,
+            modified,
+            manifest: None,
+            entries: Vec::new(),
+            placeholder: false,
+        };
+    };
+
+    let entries = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .map(|name| name.to_string())
+        .collect();
+    let manifest = timings
+        .time("manifest_ms", || read_manifest_from_archive(&mut archive))
+        .ok();
+
+    IndexedMod {
+        size,
+        modified,
+        manifest,
+        entries,
+        placeholder: false,
+    }
+}
+
+/// The profile's index database, unless it has none or its JSON index at `index_path` is yet
+/// to be migrated into it
+/// profile 的索引資料庫；profile 沒有資料庫，或 `index_path` 的 JSON 索引尚待遷移時為 None
+#[cfg(feature = "sqlite")]
+fn open_db(index_path: &Path) -> Option<Result<index_db::IndexDb, AppError>> {
+    match index_path.exists() {
+        true => None,
+        false => index_db::IndexDb::existing(index_path),
+    }
+}
+
+/// Log a database that could not be read; it loads as empty, like a damaged JSON index
+/// 記錄無法讀取的資料庫；與損毀的 JSON 索引相同，視為空索引
+#[cfg(feature = "sqlite")]
+fn unreadable_db(error: AppError) {
+    app_log::write(&format!("index database not read: {}", error));
+}
+
+/// The persisted index: from the profile's database when it has one, otherwise from the JSON
+/// file at `index_path`. A missing, damaged or outdated index loads as empty.
+/// 已存的索引：profile 有資料庫時從資料庫讀取，否則讀取 `index_path` 的 JSON 檔案；不存在、損毀或版本過舊的索引視為空索引
+fn load_index(index_path: &Path) -> AssetIndex {
+    #[cfg(feature = "sqlite")]
+    if let Some(db) = open_db(index_path) {
+        return AssetIndex {
+            version: INDEX_VERSION,
+            mods: db
+                .and_then(|db| db.load_mods(INDEX_VERSION))
+                .unwrap_or_else(|e| {
+                    unreadable_db(e);
+                    HashMap::new()
+                }),
+        };
+    }
+    fs::read_to_string(index_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<AssetIndex>(&content).ok())
+        .filter(|index| index.version == INDEX_VERSION)
+        .unwrap_or_else(|| AssetIndex {
+            version: INDEX_VERSION,
+            mods: HashMap::new(),
+        })
+}
+
+/// Store the index in the profile's database when it has one or the index has reached
+/// `index_db::MIN_MODS` archives, retiring the JSON file; otherwise as JSON at `index_path`
+/// profile 有資料庫或索引已達 `index_db::MIN_MODS` 個壓縮檔時存入資料庫並移除 JSON 檔案；否則以 JSON 存於 `index_path`
+fn save_index(index_path: &Path, index: &AssetIndex) -> Result<(), AppError> {
+    #[cfg(feature = "sqlite")]
+    {
+        let db = match index_db::IndexDb::existing(index_path) {
+            Some(db) => Some(db),
+            None if index.mods.len() >= index_db::MIN_MODS => {
+                index_path.parent().map(index_db::IndexDb::create)
+            }
+            None => None,
+        };
+        if let Some(db) = db {
+            db?.save_mods(INDEX_VERSION, &index.mods)?;
+            index_db::retire_json(index_path);
+            return Ok(());
+        }
+    }
+    if let Some(parent) = index_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io("io.create_index_directory", Some(parent), e))?;
+    }
+    let content = serde_json::to_string(index)
+        .map_err(|e| AppError::io("io.serialize_index", Some(index_path), e))?;
+    fs::write(index_path, content).map_err(|e| AppError::io("io.write_index", Some(index_path), e))
+}
+
+/// `save_index`, logging a failure instead of returning it: the index is only a cache, so a
+/// command keeps working from the copy in memory, e.g. when the app data dir is unwritable
+/// `save_index`，失敗時記錄於 log 而不回傳錯誤；索引只是快取，因此指令會以記憶體中的內容繼續運作（例如 app data 資料夾無法寫入時）
+fn keep_index(index_path: &Path, index: &AssetIndex) {
+    if let Err(e) = save_index(index_path, index) {
+        app_log::write(&format!("asset index not saved: {}", e));
+    }
+}
+
+/// List every mod archive under the mods folder with its size and mtime, once per physical file
+/// 列出 mods 資料夾下所有模組壓縮檔及其大小與修改時間，同一實體檔案只列一次
+pub(crate) fn list_mod_archives(
+    mods_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+) -> Vec<(PathBuf, u64, Option<u64>)> {
+    links::walk_files(mods_path, follow_links)
+        .into_iter()
+        .filter(|file| is_mod_archive(&file.path, mod_extensions))
+        .map(|file| {
+            let modified = file
+                .metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|dur| dur.as_secs());
+            (file.path, file.metadata.len(), modified)
+        })
+        .collect()
+}
+
+/// Bring the index up to date with the mods folder, re-reading only changed files. Cloud
+/// placeholders are not opened unless hydrating is on; they get an empty record that is
+/// retried next time. Returns how many files were re-read and how many placeholders were
+/// skipped, or an error if cancelled midway.
+/// 依 mods 資料夾更新索引，只重新讀取有變動的檔案；除非開啟下載，否則不開啟雲端預留位置檔案，改存入下次重試的空紀錄；
+/// 回傳重新讀取的數量與略過的預留位置檔案數，中途取消則回傳錯誤
+fn refresh_index(
+    index: &mut AssetIndex,
+    mods_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+    timings: &Timings,
+) -> Result<(usize, usize), AppError> {
+    let archives = timings.time("list_ms", || {
+        list_mod_archives(mods_path, follow_links, mod_extensions)
+    });
+    let total = archives.len();
+    let mut fresh = HashMap::new();
+    let mut reread = 0;
+    let mut placeholders = 0;
+
+    for (current, (path, size, modified)) in archives.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            // Keep what was indexed so far; untouched records stay for the next run
+            // 保留已完成的部分，未處理的舊紀錄留待下次使用
+            index.mods.extend(fresh);
+            return Err(AppError::OperationCancelled);
+        }
+
+        let key = path.to_string_lossy().to_string();
+        let record = match index.mods.remove(&key) {
+            Some(cached)
+                if cached.size == size && cached.modified == modified && !cached.placeholder =>
+            {
+                cached
+            }
+            _ if cloud_files::skip_content(&path) => {
+                placeholders += 1;
+                IndexedMod {
+                    size,
+                    modified,
+                    manifest: None,
+                    entries: Vec::new(),
+                    placeholder: true,
+                }
+            }
+            _ => {
+                reread += 1;
+                read_index_record(&path, size, modified, timings)
+            }
+        };
+        fresh.insert(key, record);
+        on_progress(Progress::new(current + 1, total));
+    }
+
+    index.mods = fresh;
+    Ok((reread, placeholders))
+}
+
+// ───────────────────────────────────────────────
+// Queries / 查詢
+// ───────────────────────────────────────────────
+
+/// Find every zipmod under `<game_path>/mods` that contains the given asset path
+/// 在 `<game_path>/mods` 中找出包含指定資源路徑的所有 zipmod
+pub(crate) fn find_asset_provider(
+    index_path: &Path,
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    asset_path: &str,
+) -> Result<Vec<AssetProvider>, AppError> {
+    let query = normalize_asset_path(asset_path);
+    if query.is_empty() {
+        return Err(AppError::InvalidInput {
+            message: Message::new("input.asset_path_empty"),
+        });
+    }
+
+    let mut index = load_index(index_path);
+    refresh_index(
+        &mut index,
+        &game_path.join("mods"),
+        follow_links,
+        mod_extensions,
+        &CancelToken::default(),
+        &progress::ignore,
+        &Timings::default(),
+    )?;
+    keep_index(index_path, &index);
+
+    let mut providers = index
+        .mods
+        .iter()
+        .filter_map(|(path, record)| {
+            let entries = record
+                .entries
+                .iter()
+                .filter(|name| entry_matches(&normalize_asset_path(name), &query))
+                .cloned()
+                .collect::<Vec<_>>();
+            if entries.is_empty() {
+                return None;
+            }
+
+            let full_path = Path::new(path);
+            Some(AssetProvider {
+                mod_entry: build_mod_entry(full_path, path, mod_extensions),
+                guid: record.guid().map(str::to_string),
+                entries,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    providers.sort_by(|a, b| a.mod_entry.path.cmp(&b.mod_entry.path));
+    Ok(providers)
+}
+
+/// Build or incrementally update the asset index of the whole mods folder; fails with
+/// `mods_dir_missing` when the game has none
+/// 建立或增量更新整個 mods 資料夾的資源索引；遊戲沒有 mods 資料夾時回傳 `mods_dir_missing`
+pub(crate) fn build_asset_index(
+    index_path: &Path,
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+    timings: Timings,
+) -> Result<AssetIndexSummary, AppError> {
+    mod_roots::ensure_mods_dir(game_path)?;
+    let mut index = timings.time("index_read_ms", || load_index(index_path));
+    let result = refresh_index(
+        &mut index,
+        &game_path.join("mods"),
+        follow_links,
+        mod_extensions,
+        cancel,
+        on_progress,
+        &timings,
+    );
+    timings.time("index_write_ms", || keep_index(index_path, &index));
+    let (mods_reread, cloud_placeholders) = result?;
+
+    Ok(AssetIndexSummary {
+        mods_indexed: index.mods.len(),
+        mods_reread,
+        cloud_placeholders,
+        entries: index.mods.values().map(|record| record.entries.len()).sum(),
+        timings: timings.finish(),
+    })
+}
+
+/// Refresh the index and return every mod archive path with its cached manifest and the
+/// names of any zipmods packed inside it. The index is saved even when cancelled so the
+/// work done is kept. Fails with `mods_dir_missing` when the game has no mods folder.
+/// 更新索引並回傳所有模組壓縮檔路徑、其快取的 manifest 與內含的 zipmod 名稱；即使取消也會儲存索引以保留已完成的部分；
+/// 遊戲沒有 mods 資料夾時回傳 `mods_dir_missing`
+pub(crate) fn indexed_manifests(
+    index_path: &Path,
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<Vec<IndexedArchive>, AppError> {
+    mod_roots::ensure_mods_dir(game_path)?;
+    let mut index = load_index(index_path);
+    let result = refresh_index(
+        &mut index,
+        &game_path.join("mods"),
+        follow_links,
+        mod_extensions,
+        cancel,
+        on_progress,
+        &Timings::default(),
+    );
+    keep_index(index_path, &index);
+    result?;
+
+    Ok(index
+        .mods
+        .into_iter()
+        .map(|(path, record)| IndexedArchive {
+            path,
+            manifest: record.manifest,
+            inner_zipmods: record
+                .entries
+                .into_iter()
+                .filter(|name| nested_archives::is_inner_zipmod(name))
+                .collect(),
+        })
+        .collect())
+}
+
+/// Path and size of every archive the persisted index holds, as of its last refresh; None
+/// before the first index build
+/// 已存索引中每個壓縮檔的路徑與大小（以上次更新時為準）；首次建立索引前為 None
+pub(crate) fn indexed_sizes(index_path: &Path) -> Option<Vec<(PathBuf, u64)>> {
+    #[cfg(feature = "sqlite")]
+    if let Some(db) = open_db(index_path) {
+        let sizes = db
+            .and_then(|db| db.mod_sizes(INDEX_VERSION))
+            .unwrap_or_else(|e| {
+                unreadable_db(e);
+                Vec::new()
+            });
+        return (!sizes.is_empty()).then(|| {
+            sizes
+                .into_iter()
+                .map(|(path, size)| (PathBuf::from(path), size))
+                .collect()
+        });
+    }
+    let index = load_index(index_path);
+    (!index.mods.is_empty()).then(|| {
+        index
+            .mods
+            .into_iter()
+            .map(|(path, record)| (PathBuf::from(path), record.size))
+            .collect()
+    })
+}
+
+/// The persisted records of `paths`, in order: looked up by path when the profile has a
+/// database, otherwise taken out of the loaded JSON index
+/// 依序取得 `paths` 的已存紀錄：profile 有資料庫時依路徑查詢，否則從載入的 JSON 索引中取出
+fn records(index_path: &Path, paths: &[&str]) -> Vec<Option<IndexedMod>> {
+    #[cfg(feature = "sqlite")]
+    if let Some(db) = open_db(index_path) {
+        return db
+            .and_then(|db| db.mod_records(INDEX_VERSION, paths))
+            .unwrap_or_else(|e| {
+                unreadable_db(e);
+                paths.iter().map(|_| None).collect()
+            });
+    }
+    let mut index = load_index(index_path);
+    paths.iter().map(|path| index.mods.remove(*path)).collect()
+}
+
+/// Manifest the persisted index holds for `path`, if its record still matches the file's size
+/// and mtime; the outer None means there is no usable record
+/// 已存索引中 `path` 的 manifest，僅在紀錄與檔案大小、修改時間仍相符時回傳；外層為 None 表示沒有可用紀錄
+pub(crate) fn cached_manifest(
+    index_path: &Path,
+    path: &str,
+    size: u64,
+    modified: Option<u64>,
+) -> Option<Option<ManifestData>> {
+    records(index_path, &[path])
+        .pop()
+        .flatten()
+        .filter(|record| record.size == size && record.modified == modified)
+        .map(|record| record.manifest)
+}
+
+/// Manifests the persisted index holds for each `(path, size, mtime)` of `files`, matched like
+/// `cached_manifest` but loading the index once; None where no record is usable
+/// 已存索引中 `files` 各 `(路徑, 大小, 修改時間)` 的 manifest，比對方式同 `cached_manifest` 但只載入一次索引；
+/// 沒有可用紀錄時為 None
+pub(crate) fn cached_manifests(
+    index_path: &Path,
+    files: &[(&str, u64, Option<u64>)],
+) -> Vec<Option<ManifestData>> {
+    let paths = files.iter().map(|(path, _, _)| *path).collect::<Vec<_>>();
+    records(index_path, &paths)
+        .into_iter()
+        .zip(files)
+        .map(|(record, (_, size, modified))| {
+            record
+                .filter(|record| record.size == *size && record.modified == *modified)
+                .and_then(|record| record.manifest)
+        })
+        .collect()
+}
+
+/// Report abdata paths provided by two or more distinct GUIDs, most colliders first.
+/// Uses the persisted index as-is; run `build_asset_index` first to refresh it.
+/// 回報被兩個以上不同 GUID 提供的 abdata 路徑，衝突數多者排前；直接使用已存的索引
+pub(crate) fn find_global_asset_collisions(index_path: &Path) -> Vec<AssetCollision> {
+    let index = load_index(index_path);
+    let mut by_asset: HashMap<String, BTreeMap<String, Vec<String>>> = HashMap::new();
+
+    for (path, record) in &index.mods {
+        let Some(guid) = record.guid() else { continue };
+        for entry in &record.entries {
+            let asset_path = normalize_asset_path(entry);
+            if !asset_path.starts_with("abdata/") {
+                continue;
+            }
+            let paths = by_asset
+                .entry(asset_path)
+                .or_default()
+                .entry(guid.to_string())
+                .or_default();
+            if !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+    }
+
+    let mut collisions = by_asset
+        .into_iter()
+        .filter(|(_, providers)| providers.len() > 1)
+        .map(|(asset_path, providers)| AssetCollision {
+            asset_path,
+            providers: providers
+                .into_iter()
+                .map(|(guid, mut paths)| {
+                    paths.sort();
+                    CollisionProvider { guid, paths }
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+
+    collisions.sort_by(|a, b| {
+        b.providers
+            .len()
+            .cmp(&a.providers.len())
+            .then_with(|| a.asset_path.cmp(&b.asset_path))
+    });
+    collisions
+}
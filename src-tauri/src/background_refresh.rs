@@ -0,0 +1,173 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError, Sender, TryRecvError},
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use crate::{error::AppError, game_process, long_path, scan, scan_cache::LogIdentity};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Shortest accepted polling interval; anything lower is raised to this
+/// 可接受的最短輪詢間隔；低於此值時提高至此值
+pub(crate) const MIN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// While the game runs it rewrites the log constantly, so polling slows down by this factor
+/// and refreshing waits until the game exits
+/// 遊戲執行中會持續寫入 log，因此輪詢間隔放大此倍數，並延後到遊戲結束後才更新
+const RUNNING_BACKOFF: u32 = 4;
+
+/// Message to the refresh thread
+/// 傳給更新執行緒的訊息
+enum Wake {
+    /// The mods watch saw a change, so refresh without waiting for the next poll
+    /// mods 監看偵測到變動，不必等到下次輪詢即更新
+    ModsChanged,
+    Stop,
+}
+
+/// Cheap snapshot of what a scan depends on: the log identity and the directory mtimes
+/// under `mods`. Equal fingerprints mean a re-scan would give the same totals.
+/// 掃描所依賴狀態的低成本快照：log 識別資訊與 `mods` 底下各資料夾的修改時間；快照相同表示重新掃描結果不變
+#[derive(PartialEq)]
+struct Fingerprint {
+    log: Option<LogIdentity>,
+    mods: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+/// A running background refresh; dropping it stops the thread and waits for it to exit
+/// 進行中的背景更新；drop 時會停止執行緒並等待其結束
+pub(crate) struct BackgroundRefresh {
+    pub game_path: String,
+    wake: Sender<Wake>,
+    thread: Option<JoinHandle<()>>,
+}
+
+// ───────────────────────────────────────────────
+// Fingerprinting / 快照
+// ───────────────────────────────────────────────
+
+/// Modification time of `mods` and every folder below it. Adding, removing or renaming a
+/// file changes its folder's mtime, so files themselves are never stat'ed.
+/// `mods` 及其底下所有資料夾的修改時間；新增、刪除或重新命名檔案會改變所在資料夾的修改時間，因此不需讀取檔案本身
+fn folder_mtimes(mods_root: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut mtimes = Vec::new();
+    let mut pending = vec![mods_root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let extended = long_path::extend(&dir);
+        let modified = fs::metadata(&extended).and_then(|m| m.modified()).ok();
+        if let Ok(entries) = fs::read_dir(&extended) {
+            // Entry file types come with the listing; links are not followed
+            // 檔案類型隨目錄列表取得；不跟隨連結
+            pending.extend(
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+                    .map(|entry| dir.join(entry.file_name())),
+            );
+        }
+        mtimes.push((dir, modified));
+    }
+    mtimes.sort();
+    mtimes
+}
+
+impl Fingerprint {
+    /// Snapshot of `game_path`; the mods folder is skipped when a mods watch reports its changes
+    /// 取得 `game_path` 的快照；若 mods 監看會回報變動則略過 mods 資料夾
+    fn of(game_path: &str, mods_watched: bool) -> Self {
+        let log = scan::find_log_file(game_path)
+            .and_then(|path| LogIdentity::of(&path))
+            .ok();
+        let mods = match mods_watched {
+            true => Vec::new(),
+            false => folder_mtimes(&Path::new(game_path).join("mods")),
+        };
+        Fingerprint { log, mods }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Refreshing / 背景更新
+// ───────────────────────────────────────────────
+
+/// Poll `game_path` every `interval` and call `refresh` when the log or mods folder changed,
+/// plus once at start. While `mods_watched` returns true the mods folder is not polled and
+/// `nudge` is expected instead. While the game runs, polling backs off and refreshes wait.
+/// 每隔 `interval` 檢查 `game_path`，log 或 mods 資料夾有變動時（以及啟動時）呼叫 `refresh`；
+/// `mods_watched` 為 true 時不輪詢 mods 資料夾，改由 `nudge` 通知；遊戲執行中會放慢輪詢並延後更新
+pub(crate) fn start(
+    game_path: &str,
+    interval: Duration,
+    mods_watched: impl Fn() -> bool + Send + 'static,
+    mut refresh: impl FnMut() + Send + 'static,
+) -> Result<BackgroundRefresh, AppError> {
+    let interval = interval.max(MIN_INTERVAL);
+    let (wake, rx) = mpsc::channel::<Wake>();
+    let root = game_path.to_string();
+
+    let thread = thread::Builder::new()
+        .name("background-refresh".to_string())
+        .spawn(move || {
+            let mut last: Option<Fingerprint> = None;
+            let mut pending = true;
+            loop {
+                let running = game_process::running_executable(Path::new(&root)).is_some();
+                if !running {
+                    let fingerprint = Fingerprint::of(&root, mods_watched());
+                    if pending || last.as_ref() != Some(&fingerprint) {
+                        refresh();
+                        last = Some(fingerprint);
+                        pending = false;
+                    }
+                }
+
+                let wait = match running {
+                    true => interval * RUNNING_BACKOFF,
+                    false => interval,
+                };
+                match rx.recv_timeout(wait) {
+                    Ok(Wake::ModsChanged) => pending = true,
+                    Ok(Wake::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+                    Err(RecvTimeoutError::Timeout) => {}
+                }
+                // Collapse a burst of nudges into one refresh
+                // 將連續的多次通知合併為一次更新
+                loop {
+                    match rx.try_recv() {
+                        Ok(Wake::ModsChanged) => pending = true,
+                        Ok(Wake::Stop) | Err(TryRecvError::Disconnected) => return,
+                        Err(TryRecvError::Empty) => break,
+                    }
+                }
+            }
+        })
+        .map_err(|e| AppError::io("io.start_background_refresh", Some(Path::new(game_path)), e))?;
+
+    Ok(BackgroundRefresh {
+        game_path: game_path.to_string(),
+        wake,
+        thread: Some(thread),
+    })
+}
+
+impl BackgroundRefresh {
+    /// Refresh soon, without waiting for the next poll
+    /// 盡快更新，不等待下次輪詢
+    pub(crate) fn nudge(&self) {
+        let _ = self.wake.send(Wake::ModsChanged);
+    }
+}
+
+impl Drop for BackgroundRefresh {
+    fn drop(&mut self) {
+        let _ = self.wake.send(Wake::Stop);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
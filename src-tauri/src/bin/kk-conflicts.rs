@@ -0,0 +1,249 @@
+//! Headless front end for the conflict scanner, printing JSON to stdout.
+//! 衝突掃描的命令列介面，結果以 JSON 輸出至 stdout
+//!
+//! Usage / 用法:
+//!   kk-conflicts scan <game_path> [options]     conflicts parsed from the game log
+//!   kk-conflicts report <game_path> [options]   same, plus each file's manifest.xml
+//!
+//! Options / 選項:
+//!   --format json|text     JSON (default), or a plain list of conflicts for people
+//!   --fail-on-conflicts    exit with 1 when any conflict is found, for release gating
+//!   --read-only            accepted for older scripts; scans never write to game files
+//!
+//! JSON output, schema version 1 / JSON 輸出（schema 版本 1）:
+//!   { "schema_version": 1, "command": "scan" | "report",
+//!     "log_path", "summary", "conflicts": [...], ... }
+//! `scan` adds `from_cache`; `report` gives each file a `manifest` and `manifest_error`.
+//! Conflicts are sorted by log line, then id; skipped files keep Sideloader's order, so
+//! identical input gives byte-identical output. Fields are only added within a version;
+//! renaming or removing one bumps `schema_version`.
+//! `scan` 另含 `from_cache`；`report` 的每個檔案附 `manifest` 與 `manifest_error`。衝突依 log 行號再依 id 排序，
+//! 被略過的檔案維持 Sideloader 的順序，因此相同輸入會得到逐位元組相同的輸出；同一版本內只會新增欄位，
+//! 重新命名或移除欄位時會遞增 `schema_version`
+//!
+//! Exit codes / 結束代碼:
+//!   0  scan ran; no conflicts, or conflicts without `--fail-on-conflicts`
+//!   1  conflicts found with `--fail-on-conflicts`
+//!   2  bad arguments
+//!   3  the scan or the output failed; the error goes to stderr as JSON
+
+use std::{env, process::ExitCode};
+
+use kk_log_based_mod_conflict_manager_lib::{
+    error::AppError,
+    manifest::{read_manifest_from_path, ManifestData},
+    parser::{ModConflict, ModEntry},
+    scan::{scan_conflicts, ScanResult, ScanSummary},
+};
+use serde::Serialize;
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Version of the JSON layout; bumped when a field is renamed or removed
+/// JSON 格式的版本；欄位重新命名或移除時遞增
+const SCHEMA_VERSION: u32 = 1;
+
+/// Exit code for conflicts found under `--fail-on-conflicts`
+/// 指定 `--fail-on-conflicts` 且找到衝突時的結束代碼
+const EXIT_CONFLICTS: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_ERROR: u8 = 3;
+
+enum Format {
+    Json,
+    Text,
+}
+
+/// Parsed command line / 解析後的命令列
+struct Args {
+    report: bool,
+    game_path: String,
+    format: Format,
+    fail_on_conflicts: bool,
+}
+
+/// Every JSON output: the schema header, then the command's own fields
+/// 所有 JSON 輸出：先是 schema 標頭，再接指令本身的欄位
+#[derive(Serialize)]
+struct Output<'a, T: Serialize> {
+    schema_version: u32,
+    command: &'a str,
+    #[serde(flatten)]
+    body: T,
+}
+
+/// One file of a conflict together with its manifest, if readable
+/// 衝突中的單一檔案與其 manifest（若可讀取）
+#[derive(Serialize)]
+struct ReportEntry {
+    #[serde(flatten)]
+    entry: ModEntry,
+    manifest: Option<ManifestData>,
+    manifest_error: Option<AppError>,
+}
+
+#[derive(Serialize)]
+struct ReportConflict {
+    loaded: ReportEntry,
+    skipped: Vec<ReportEntry>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    log_path: String,
+    summary: ScanSummary,
+    conflicts: Vec<ReportConflict>,
+}
+
+// ───────────────────────────────────────────────
+// Subcommands / 子指令
+// ───────────────────────────────────────────────
+
+fn report_entry(entry: ModEntry) -> ReportEntry {
+    let (manifest, manifest_error) = match read_manifest_from_path(&entry.path) {
+        Ok(manifest) => (Some(manifest), None),
+        Err(e) => (None, Some(e)),
+    };
+    ReportEntry {
+        entry,
+        manifest,
+        manifest_error,
+    }
+}
+
+/// Conflicts in log order, ties broken by id, so output never depends on parse scheduling
+/// 依 log 順序排列衝突，相同時依 id，使輸出不受解析排程影響
+fn sort_conflicts(conflicts: &mut [ModConflict]) {
+    conflicts.sort_by(|a, b| a.line.cmp(&b.line).then_with(|| a.id.cmp(&b.id)));
+}
+
+fn build_report(scan: ScanResult) -> Report {
+    Report {
+        log_path: scan.log_path,
+        summary: scan.summary,
+        conflicts: scan
+            .conflicts
+            .into_iter()
+            .map(|conflict| ReportConflict {
+                loaded: report_entry(conflict.loaded),
+                skipped: conflict.skipped.into_iter().map(report_entry).collect(),
+            })
+            .collect(),
+    }
+}
+
+fn print_json<T: Serialize>(command: &str, body: T) -> Result<(), ExitCode> {
+    let output = Output {
+        schema_version: SCHEMA_VERSION,
+        command,
+        body,
+    };
+    match serde_json::to_string_pretty(&output) {
+        Ok(json) => {
+            println!("{}", json);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Failed to serialize output: {}", e);
+            Err(ExitCode::from(EXIT_ERROR))
+        }
+    }
+}
+
+fn print_text(scan: &ScanResult) {
+    let summary = &scan.summary;
+    println!("Log: {}", scan.log_path);
+    println!(
+        "{} conflicts, {} skipped files, {} bytes skipped",
+        summary.conflicts, summary.skipped_files, summary.skipped_bytes
+    );
+    for conflict in &scan.conflicts {
+        println!();
+        println!("loaded:  {}", conflict.loaded.path);
+        for skipped in &conflict.skipped {
+            println!("skipped: {}", skipped.path);
+        }
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!(
+        "Usage: kk-conflicts <scan|report> <game_path> [--format json|text] [--fail-on-conflicts] \
+         [--read-only]"
+    );
+    ExitCode::from(EXIT_USAGE)
+}
+
+fn parse_args(args: &[String]) -> Option<Args> {
+    let mut positional = Vec::new();
+    let mut format = Format::Json;
+    let mut fail_on_conflicts = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.split_once('=') {
+            Some(("--format", value)) => Some(value),
+            _ if arg == "--format" => Some(args.next()?.as_str()),
+            _ => None,
+        };
+        match (arg.as_str(), value) {
+            (_, Some("json")) => format = Format::Json,
+            (_, Some("text")) => format = Format::Text,
+            (_, Some(_)) => return None,
+            ("--fail-on-conflicts", None) => fail_on_conflicts = true,
+            // A scan only reads, so there is nothing for the flag to refuse
+            // 掃描只會讀取，此旗標沒有需要拒絕的動作
+            ("--read-only", None) => {}
+            (flag, None) if flag.starts_with("--") => return None,
+            (_, None) => positional.push(arg.clone()),
+        }
+    }
+    let [command, game_path] = <[String; 2]>::try_from(positional).ok()?;
+    let report = match command.as_str() {
+        "scan" => false,
+        "report" => true,
+        _ => return None,
+    };
+    Some(Args {
+        report,
+        game_path,
+        format,
+        fail_on_conflicts,
+    })
+}
+
+fn main() -> ExitCode {
+    let Some(args) = parse_args(&env::args().skip(1).collect::<Vec<_>>()) else {
+        return usage();
+    };
+
+    let mut scan = match scan_conflicts(&args.game_path) {
+        Ok(scan) => scan,
+        Err(e) => {
+            // Errors go to stderr in the same shape the frontend receives
+            // 錯誤以與前端相同的格式輸出至 stderr
+            eprintln!(
+                "{}",
+                serde_json::to_string(&e).unwrap_or_else(|_| e.to_string())
+            );
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    sort_conflicts(&mut scan.conflicts);
+    let found = !scan.conflicts.is_empty();
+
+    let printed = match (args.format, args.report) {
+        (Format::Text, _) => {
+            print_text(&scan);
+            Ok(())
+        }
+        (Format::Json, true) => print_json("report", build_report(scan)),
+        (Format::Json, false) => print_json("scan", scan),
+    };
+    match (printed, found && args.fail_on_conflicts) {
+        (Err(code), _) => code,
+        (Ok(()), true) => ExitCode::from(EXIT_CONFLICTS),
+        (Ok(()), false) => ExitCode::SUCCESS,
+    }
+}
@@ -0,0 +1,323 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    cloud_files,
+    error::AppError,
+    links, long_path,
+    messages::Message,
+    operations::CancelToken,
+    progress::{OnProgress, Progress},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// First line of every checksum file; the number after `v` is the format version
+/// 每個校驗檔的第一行；`v` 後的數字為格式版本
+const FORMAT_MAGIC: &str = "# koikatsu-mod-checksums v";
+
+/// Format version written by `export_checksums`
+/// `export_checksums` 寫出的格式版本
+const FORMAT_VERSION: u32 = 1;
+
+/// Read size while hashing; cancellation is checked between chunks
+/// 雜湊時每次讀取的大小；每個區塊之間檢查是否取消
+const CHUNK_BYTES: usize = 1 << 20;
+
+/// One file of the mods folder: path relative to `mods` with `/` separators, size and hash
+/// mods 資料夾中的單一檔案：相對於 `mods` 並以 `/` 分隔的路徑、大小與雜湊
+struct ChecksumLine {
+    path: String,
+    size: u64,
+    hash: String,
+}
+
+/// Summary of a written checksum file
+/// 寫出之校驗檔的摘要
+#[derive(Serialize)]
+pub(crate) struct ExportSummary {
+    pub path: String,
+    pub files: usize,
+    pub total_bytes: u64,
+}
+
+/// A file whose size or hash no longer matches the checksum file
+/// 大小或雜湊與校驗檔不符的檔案
+#[derive(Serialize)]
+pub(crate) struct ModifiedFile {
+    path: String,
+    expected_size: u64,
+    actual_size: u64,
+}
+
+/// Outcome of checking a mods folder against a checksum file
+/// 以校驗檔檢查 mods 資料夾的結果
+#[derive(Serialize)]
+pub(crate) struct VerifyResult {
+    /// Listed in the checksum file but absent from the folder
+    /// 校驗檔中有、資料夾中沒有
+    pub missing: Vec<String>,
+    pub modified: Vec<ModifiedFile>,
+    /// Present in the folder but not listed in the checksum file
+    /// 資料夾中有、校驗檔中沒有
+    pub extra: Vec<String>,
+    pub verified: usize,
+    /// Header of the checksum file
+    /// 校驗檔的標頭
+    tool_version: Option<String>,
+    created: Option<u64>,
+}
+
+// ───────────────────────────────────────────────
+// Hashing / 雜湊
+// ───────────────────────────────────────────────
+
+/// Every file under `<game_path>/mods` with its path relative to `mods`, sorted by that path
+/// `<game_path>/mods` 下的所有檔案及其相對於 `mods` 的路徑，依該路徑排序
+fn list_files(game_path: &Path, follow_links: bool) -> Vec<(String, PathBuf, u64)> {
+    let mods_root = game_path.join("mods");
+    let display_root = long_path::display(&mods_root);
+    let mut files = links::walk_files(&mods_root, follow_links)
+        .into_iter()
+        .filter_map(|file| {
+            let relative = file
+                .path
+                .strip_prefix(&display_root)
+                .ok()?
+                .to_string_lossy()
+                .replace('\\', "/");
+            Some((relative, file.path, file.metadata.len()))
+        })
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+/// Stream a file through blake3 without loading it whole; a cloud placeholder fails with
+/// `cloud_placeholder` unless hydrating is on
+/// 以串流方式計算 blake3，不將整個檔案載入記憶體；除非開啟下載，雲端預留位置檔案會以 `cloud_placeholder` 失敗
+pub(crate) fn hash_file(path: &Path, cancel: &CancelToken) -> Result<String, AppError> {
+    cloud_files::ensure_local(path)?;
+    let read_error = |e: io::Error| AppError::io("io.hash_file", Some(path), e);
+    let mut file = File::open(long_path::extend(path)).map_err(read_error)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0u8; CHUNK_BYTES];
+    loop {
+        if cancel.is_cancelled() {
+            return Err(AppError::OperationCancelled);
+        }
+        let read = file.read(&mut buffer).map_err(read_error)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Hash `files` in parallel, reporting one progress step per file
+/// 平行計算 `files` 的雜湊，每完成一個檔案回報一次進度
+fn hash_all<'a>(
+    files: &[(&'a str, &'a Path)],
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<Vec<(&'a str, String)>, AppError> {
+    let total = files.len();
+    let done = AtomicUsize::new(0);
+    files
+        .par_iter()
+        .map(|&(relative, path)| {
+            let hash = hash_file(path, cancel)?;
+            let current = done.fetch_add(1, Ordering::Relaxed) + 1;
+            on_progress(Progress::new(current, total));
+            Ok((relative, hash))
+        })
+        .collect()
+}
+
+// ───────────────────────────────────────────────
+// Checksum File / 校驗檔
+// ───────────────────────────────────────────────
+
+fn malformed(path: &Path, line: usize) -> AppError {
+    AppError::InvalidInput {
+        message: Message::new("input.checksums_malformed")
+            .with("path", long_path::display(path))
+            .with("line", line),
+    }
+}
+
+/// Parse a checksum file: header values, then one `path<TAB>size<TAB>hash` line per file
+/// 解析校驗檔：先讀取標頭，再逐行讀取 `path<TAB>size<TAB>hash`
+fn parse_checksums(
+    path: &Path,
+    content: &str,
+) -> Result<(HashMap<String, String>, Vec<ChecksumLine>), AppError> {
+    let mut lines = content.lines().enumerate();
+    let version = lines
+        .next()
+        .and_then(|(_, first)| first.strip_prefix(FORMAT_MAGIC))
+        .ok_or_else(|| malformed(path, 1))?;
+    if version.trim().parse::<u32>().ok() != Some(FORMAT_VERSION) {
+        return Err(AppError::InvalidInput {
+            message: Message::new("input.checksums_unsupported")
+                .with("path", long_path::display(path))
+                .with("version", version.trim()),
+        });
+    }
+
+    let mut header = HashMap::new();
+    let mut entries = Vec::new();
+    for (index, line) in lines {
+        if let Some(comment) = line.strip_prefix('#') {
+            if let Some((key, value)) = comment.split_once(':') {
+                header.insert(key.trim().to_string(), value.trim().to_string());
+            }
+            continue;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = line.split('\t');
+        let (Some(relative), Some(size), Some(hash), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(malformed(path, index + 1));
+        };
+        entries.push(ChecksumLine {
+            path: relative.to_string(),
+            size: size.parse().map_err(|_| malformed(path, index + 1))?,
+            hash: hash.to_string(),
+        });
+    }
+    Ok((header, entries))
+}
+
+/// Hash every file of the mods folder and write a checksum file to `destination`.
+/// The header records the format version, tool version and creation time.
+/// 計算 mods 資料夾中所有檔案的雜湊並寫出校驗檔至 `destination`；標頭記錄格式版本、工具版本與建立時間
+pub(crate) fn export_checksums(
+    game_path: &Path,
+    destination: &Path,
+    follow_links: bool,
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<ExportSummary, AppError> {
+    let files = list_files(game_path, follow_links);
+    let targets = files
+        .iter()
+        .map(|(relative, path, _)| (relative.as_str(), path.as_path()))
+        .collect::<Vec<_>>();
+    let hashes = hash_all(&targets, cancel, on_progress)?;
+
+    let created = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut content = format!(
+        "{}{}\n# tool_version: {}\n# created: {}\n",
+        FORMAT_MAGIC,
+        FORMAT_VERSION,
+        env!("CARGO_PKG_VERSION"),
+        created
+    );
+    for ((relative, _, size), (_, hash)) in files.iter().zip(&hashes) {
+        content.push_str(&format!("{}\t{}\t{}\n", relative, size, hash));
+    }
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(long_path::extend(parent))
+            .map_err(|e| AppError::io("io.create_checksums_directory", Some(parent), e))?;
+    }
+    fs::write(long_path::extend(destination), content)
+        .map_err(|e| AppError::io("io.write_checksums", Some(destination), e))?;
+
+    Ok(ExportSummary {
+        path: long_path::display(destination),
+        files: files.len(),
+        total_bytes: files.iter().map(|(_, _, size)| size).sum(),
+    })
+}
+
+/// Re-hash the mods folder and compare it with a checksum file. Files whose size already
+/// differs are reported as modified without hashing them.
+/// 重新計算 mods 資料夾的雜湊並與校驗檔比對；大小已不同的檔案直接視為已修改，不再計算雜湊
+pub(crate) fn verify_checksums(
+    game_path: &Path,
+    manifest_path: &Path,
+    follow_links: bool,
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<VerifyResult, AppError> {
+    let content = fs::read_to_string(long_path::extend(manifest_path))
+        .map_err(|e| AppError::io("io.read_checksums", Some(manifest_path), e))?;
+    let (header, expected) = parse_checksums(manifest_path, &content)?;
+
+    // Windows paths are case-insensitive, so match case-folded
+    // Windows 路徑不分大小寫，因此以小寫比對
+    let mut present = list_files(game_path, follow_links)
+        .into_iter()
+        .map(|file| (file.0.to_lowercase(), file))
+        .collect::<HashMap<_, _>>();
+
+    let mut missing = Vec::new();
+    let mut modified = Vec::new();
+    let mut to_hash = Vec::new();
+    for line in &expected {
+        match present.remove(&line.path.to_lowercase()) {
+            None => missing.push(line.path.clone()),
+            Some((_, _, size)) if size != line.size => modified.push(ModifiedFile {
+                path: line.path.clone(),
+                expected_size: line.size,
+                actual_size: size,
+            }),
+            Some((_, path, _)) => to_hash.push((line, path)),
+        }
+    }
+    let mut extra = present
+        .into_values()
+        .map(|(relative, _, _)| relative)
+        .collect::<Vec<_>>();
+    extra.sort();
+
+    let targets = to_hash
+        .iter()
+        .map(|(line, path)| (line.path.as_str(), path.as_path()))
+        .collect::<Vec<_>>();
+    let hashes = hash_all(&targets, cancel, on_progress)?;
+    let mut verified = 0;
+    for ((line, _), (_, hash)) in to_hash.iter().zip(hashes) {
+        if hash.eq_ignore_ascii_case(&line.hash) {
+            verified += 1;
+        } else {
+            modified.push(ModifiedFile {
+                path: line.path.clone(),
+                expected_size: line.size,
+                actual_size: line.size,
+            });
+        }
+    }
+    modified.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(VerifyResult {
+        missing,
+        modified,
+        extra,
+        verified,
+        tool_version: header.get("tool_version").cloned(),
+        created: header
+            .get("created")
+            .and_then(|created| created.parse().ok()),
+    })
+}
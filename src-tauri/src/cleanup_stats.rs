@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Bytes and files removed from the disk
+/// 從磁碟移除的位元組數與檔案數
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+pub(crate) struct CleanupTotals {
+    pub bytes_freed: u64,
+    pub files: usize,
+}
+
+/// What `get_cleanup_stats` returns
+/// `get_cleanup_stats` 的回傳內容
+#[derive(Serialize)]
+pub(crate) struct CleanupStats {
+    /// Since the app was first used / 自首次使用本程式以來
+    pub lifetime: CleanupTotals,
+    /// Since the app was started / 自本次啟動以來
+    pub session: CleanupTotals,
+    /// Seconds since the Unix epoch of the last counted operation
+    /// 最後一次計入之操作的時間（Unix epoch 秒數）
+    pub last_operation: Option<u64>,
+}
+
+/// Contents of the counters file in the app data dir
+/// 應用程式資料夾中計數檔的內容
+#[derive(Serialize, Deserialize, Default)]
+struct CountersFile {
+    #[serde(default)]
+    lifetime: CleanupTotals,
+    #[serde(default)]
+    last_operation: Option<u64>,
+}
+
+/// Session totals, stored in Tauri managed state; lifetime totals live in the counters file.
+/// Only files that left the mods folder for the Recycle Bin count: quarantined and disabled
+/// files are still on the disk.
+/// 工作階段統計，存放於 Tauri managed state；累計統計存於計數檔。只計入移到回收桶的檔案，
+/// 隔離與停用的檔案仍在磁碟上
+#[derive(Default)]
+pub(crate) struct CleanupCounters(Mutex<CleanupTotals>);
+
+// ───────────────────────────────────────────────
+// Counting / 計數
+// ───────────────────────────────────────────────
+
+/// The counters file, or empty counters when it is missing or unreadable
+/// 讀取計數檔；不存在或無法讀取時為空的計數
+fn read_counters(path: &Path) -> CountersFile {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+impl CleanupCounters {
+    /// Add one operation's freed bytes and files to the session and to the counters file at
+    /// `path`; operations that removed nothing are not recorded
+    /// 將單次操作釋放的位元組數與檔案數加入工作階段與 `path` 的計數檔；未移除任何檔案的操作不記錄
+    pub fn record(&self, path: &Path, bytes_freed: u64, files: usize) -> Result<(), AppError> {
+        if files == 0 {
+            return Ok(());
+        }
+        let mut session = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        session.bytes_freed += bytes_freed;
+        session.files += files;
+
+        let mut counters = read_counters(path);
+        counters.lifetime.bytes_freed += bytes_freed;
+        counters.lifetime.files += files;
+        counters.last_operation = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|elapsed| elapsed.as_secs());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| AppError::io("io.write_cleanup_stats", Some(parent), e))?;
+        }
+        let content = serde_json::to_string(&counters)
+            .map_err(|e| AppError::io("io.write_cleanup_stats", Some(path), e))?;
+        fs::write(path, content).map_err(|e| AppError::io("io.write_cleanup_stats", Some(path), e))
+    }
+
+    /// Lifetime totals from the counters file at `path` and this session's totals
+    /// `path` 計數檔中的累計統計與本次工作階段的統計
+    pub fn stats(&self, path: &Path) -> CleanupStats {
+        let counters = read_counters(path);
+        CleanupStats {
+            lifetime: counters.lifetime,
+            session: *self.0.lock().unwrap_or_else(|e| e.into_inner()),
+            last_operation: counters.last_operation,
+        }
+    }
+}
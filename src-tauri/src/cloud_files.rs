@@ -0,0 +1,159 @@
+use std::{
+    env, fs,
+    path::{Component, Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{error::AppError, long_path};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Folder names the sync clients create by default, with the service to name in warnings;
+/// `OneDrive - Company` and `Dropbox (Team)` match by prefix
+/// 同步程式預設建立的資料夾名稱及警告中顯示的服務名稱；`OneDrive - Company` 與 `Dropbox (Team)` 以前綴比對
+const SYNC_FOLDERS: &[(&str, &str)] = &[
+    ("onedrive", "OneDrive"),
+    ("dropbox", "Dropbox"),
+    ("google drive", "Google Drive"),
+    ("my drive", "Google Drive"),
+    ("icloud drive", "iCloud Drive"),
+    ("iclouddrive", "iCloud Drive"),
+];
+
+/// Environment variables OneDrive sets to its folders, which users may have renamed
+/// OneDrive 設定為其資料夾路徑的環境變數；使用者可能已將資料夾改名
+const ONEDRIVE_VARIABLES: &[&str] = &["OneDrive", "OneDriveConsumer", "OneDriveCommercial"];
+
+/// Process-wide opt-in to open cloud placeholders anyway, letting the sync client download
+/// them first
+/// 全域選項：仍然開啟雲端預留位置檔案，由同步程式先行下載
+static HYDRATE_PLACEHOLDERS: AtomicBool = AtomicBool::new(false);
+
+// ───────────────────────────────────────────────
+// Placeholders / 預留位置檔案
+// ───────────────────────────────────────────────
+
+/// The file is a cloud placeholder: its size is known, but its data lives online and opening
+/// it makes the sync client download it, which can take minutes or fail
+/// 檔案為雲端預留位置：大小已知但資料在雲端，開啟時同步程式會先下載，可能需時數分鐘或失敗
+#[cfg(windows)]
+pub(crate) fn is_placeholder(metadata: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    use windows_sys::Win32::Storage::FileSystem::{
+        FILE_ATTRIBUTE_OFFLINE, FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS, FILE_ATTRIBUTE_RECALL_ON_OPEN,
+    };
+
+    metadata.file_attributes()
+        & (FILE_ATTRIBUTE_OFFLINE
+            | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS
+            | FILE_ATTRIBUTE_RECALL_ON_OPEN)
+        != 0
+}
+
+#[cfg(not(windows))]
+pub(crate) fn is_placeholder(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Turn on or off opening placeholders for content reads
+/// 開啟或關閉讀取內容時開啟預留位置檔案
+pub(crate) fn set_hydrate_placeholders(on: bool) {
+    HYDRATE_PLACEHOLDERS.store(on, Ordering::Relaxed);
+}
+
+/// Content reads should pass over the file at `path`: it is a placeholder and hydrating is off
+/// 讀取內容時應略過 `path`：其為預留位置檔案且未開啟下載
+pub(crate) fn skip_content(path: &Path) -> bool {
+    !HYDRATE_PLACEHOLDERS.load(Ordering::Relaxed)
+        && fs::metadata(long_path::extend(path)).is_ok_and(|meta| is_placeholder(&meta))
+}
+
+/// Refuse to read the data of a placeholder unless hydrating is on, so a read fails at once
+/// instead of hanging on a download
+/// 除非已開啟下載，否則拒絕讀取預留位置檔案的資料，使讀取立即失敗而非卡在下載
+pub(crate) fn ensure_local(path: &Path) -> Result<(), AppError> {
+    match skip_content(path) {
+        true => Err(AppError::CloudPlaceholder {
+            path: long_path::display(path),
+        }),
+        false => Ok(()),
+    }
+}
+
+// ───────────────────────────────────────────────
+// Sync Folders / 同步資料夾
+// ───────────────────────────────────────────────
+
+/// Sync service whose default folder name appears in `path`
+/// `path` 中出現其預設資料夾名稱的同步服務
+pub(crate) fn sync_folder_service(path: &Path) -> Option<&'static str> {
+    path.components().find_map(|component| {
+        let Component::Normal(name) = component else {
+            return None;
+        };
+        let name = name.to_string_lossy().to_lowercase();
+        SYNC_FOLDERS.iter().find_map(|(folder, service)| {
+            let rest = name.strip_prefix(folder)?;
+            (rest.is_empty() || rest.starts_with(" - ") || rest.starts_with(" ("))
+                .then_some(*service)
+        })
+    })
+}
+
+/// Cloud sync service keeping `path` in sync: a known folder name in the path, or a OneDrive
+/// folder named by its environment variables
+/// 同步 `path` 的雲端服務：路徑中含已知的資料夾名稱，或位於 OneDrive 環境變數所指的資料夾中
+pub(crate) fn cloud_sync_service(path: &Path) -> Option<&'static str> {
+    let folded = PathBuf::from(path.to_string_lossy().to_lowercase());
+    let onedrive = ONEDRIVE_VARIABLES
+        .iter()
+        .filter_map(env::var_os)
+        .filter(|root| !root.is_empty())
+        .any(|root| folded.starts_with(root.to_string_lossy().to_lowercase()));
+    match onedrive {
+        true => Some("OneDrive"),
+        false => sync_folder_service(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{skip_content, sync_folder_service};
+    use crate::{
+        parser, profiles,
+        test_support::{SyntheticInstall, TestManifest},
+    };
+
+    #[test]
+    fn cloud_sync_folders_are_recognized_by_their_default_names() {
+        let service = |path: &str| sync_folder_service(Path::new(path));
+
+        assert_eq!(
+            service("C:/Users/a/OneDrive/Games/Koikatsu/mods"),
+            Some("OneDrive")
+        );
+        assert_eq!(
+            service("C:/Users/a/OneDrive - Contoso/Koikatsu/mods"),
+            Some("OneDrive")
+        );
+        assert_eq!(
+            service("D:/Dropbox (Personal)/Koikatsu/mods"),
+            Some("Dropbox")
+        );
+        assert_eq!(service("G:/My Drive/Koikatsu/mods"), Some("Google Drive"));
+        assert_eq!(service("D:/Games/OneDriveBackup/Koikatsu/mods"), None);
+        assert_eq!(service("D:/Games/Koikatsu/mods"), None);
+
+        let install = SyntheticInstall::new();
+        let local = install.add_zipmod("hair.zipmod", &TestManifest::new("author.hair"));
+        assert!(
+            !parser::build_mod_entry(&local, "hair.zipmod", &profiles::default_mod_extensions())
+                .cloud_placeholder
+        );
+        assert!(!skip_content(&local));
+    }
+}
@@ -0,0 +1,491 @@
+//! Minimal ECMA-335 metadata reader, just enough to pull string arguments
+//! out of custom attributes (e.g. `[BepInPlugin("guid", "name", "version")]`).
+//! 精簡的 ECMA-335 metadata 解析器，只用來讀取自訂屬性的字串參數
+
+// ───────────────────────────────────────────────
+// Table Layout / 資料表結構
+// ───────────────────────────────────────────────
+
+const MODULE: usize = 0x00;
+const TYPE_REF: usize = 0x01;
+const TYPE_DEF: usize = 0x02;
+const FIELD_PTR: usize = 0x03;
+const FIELD: usize = 0x04;
+const METHOD_PTR: usize = 0x05;
+const METHOD_DEF: usize = 0x06;
+const PARAM_PTR: usize = 0x07;
+const PARAM: usize = 0x08;
+const INTERFACE_IMPL: usize = 0x09;
+const MEMBER_REF: usize = 0x0A;
+const CONSTANT: usize = 0x0B;
+const CUSTOM_ATTRIBUTE: usize = 0x0C;
+const PERMISSION: usize = 0x0E;
+const STAND_ALONE_SIG: usize = 0x11;
+const EVENT: usize = 0x14;
+const PROPERTY: usize = 0x17;
+const MODULE_REF: usize = 0x1A;
+const TYPE_SPEC: usize = 0x1B;
+const ASSEMBLY: usize = 0x20;
+const ASSEMBLY_REF: usize = 0x23;
+const FILE: usize = 0x26;
+const EXPORTED_TYPE: usize = 0x27;
+const MANIFEST_RESOURCE: usize = 0x28;
+const GENERIC_PARAM: usize = 0x2A;
+const METHOD_SPEC: usize = 0x2B;
+const GENERIC_PARAM_CONSTRAINT: usize = 0x2C;
+
+const RESOLUTION_SCOPE: &[usize] = &[MODULE, MODULE_REF, ASSEMBLY_REF, TYPE_REF];
+const TYPE_DEF_OR_REF: &[usize] = &[TYPE_DEF, TYPE_REF, TYPE_SPEC];
+const MEMBER_REF_PARENT: &[usize] = &[TYPE_DEF, TYPE_REF, MODULE_REF, METHOD_DEF, TYPE_SPEC];
+const HAS_CONSTANT: &[usize] = &[FIELD, PARAM, PROPERTY];
+const HAS_CUSTOM_ATTRIBUTE: &[usize] = &[
+    METHOD_DEF,
+    FIELD,
+    TYPE_REF,
+    TYPE_DEF,
+    PARAM,
+    INTERFACE_IMPL,
+    MEMBER_REF,
+    MODULE,
+    PERMISSION,
+    PROPERTY,
+    EVENT,
+    STAND_ALONE_SIG,
+    MODULE_REF,
+    TYPE_SPEC,
+    ASSEMBLY,
+    ASSEMBLY_REF,
+    FILE,
+    EXPORTED_TYPE,
+    MANIFEST_RESOURCE,
+    GENERIC_PARAM,
+    GENERIC_PARAM_CONSTRAINT,
+    METHOD_SPEC,
+];
+// Tags 0, 1 and 4 are unused by the spec; only MethodDef (2) and MemberRef (3) occur
+// 規格中 tag 0、1、4 未使用，只會出現 MethodDef (2) 與 MemberRef (3)
+const CUSTOM_ATTRIBUTE_TYPE: &[usize] =
+    &[usize::MAX, usize::MAX, METHOD_DEF, MEMBER_REF, usize::MAX];
+
+/// Column kinds needed to compute row sizes
+/// 計算每列大小所需的欄位型別
+#[derive(Clone, Copy)]
+enum Col {
+    U16,
+    U32,
+    Str,
+    Guid,
+    Blob,
+    Table(usize),
+    Coded(&'static [usize]),
+}
+
+fn table_columns(table: usize) -> &'static [Col] {
+    use Col::*;
+    match table {
+        MODULE => &[U16, Str, Guid, Guid, Guid],
+        TYPE_REF => &[Coded(RESOLUTION_SCOPE), Str, Str],
+        TYPE_DEF => &[
+            U32,
+            Str,
+            Str,
+            Coded(TYPE_DEF_OR_REF),
+            Table(FIELD),
+            Table(METHOD_DEF),
+        ],
+        FIELD_PTR => &[Table(FIELD)],
+        FIELD => &[U16, Str, Blob],
+        METHOD_PTR => &[Table(METHOD_DEF)],
+        METHOD_DEF => &[U32, U16, U16, Str, Blob, Table(PARAM)],
+        PARAM_PTR => &[Table(PARAM)],
+        PARAM => &[U16, U16, Str],
+        INTERFACE_IMPL => &[Table(TYPE_DEF), Coded(TYPE_DEF_OR_REF)],
+        MEMBER_REF => &[Coded(MEMBER_REF_PARENT), Str, Blob],
+        CONSTANT => &[U16, Coded(HAS_CONSTANT), Blob],
+        CUSTOM_ATTRIBUTE => &[
+            Coded(HAS_CUSTOM_ATTRIBUTE),
+            Coded(CUSTOM_ATTRIBUTE_TYPE),
+            Blob,
+        ],
+        _ => &[],
+    }
+}
+
+// ───────────────────────────────────────────────
+// Byte Reading / 位元組讀取
+// ───────────────────────────────────────────────
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_sized(data: &[u8], offset: usize, size: usize) -> Option<u32> {
+    match size {
+        2 => read_u16(data, offset).map(u32::from),
+        _ => read_u32(data, offset),
+    }
+}
+
+/// Read an ECMA-335 compressed unsigned integer, returning (value, bytes consumed)
+/// 讀取 ECMA-335 壓縮整數，回傳（值, 使用的位元組數）
+fn read_compressed(data: &[u8], offset: usize) -> Option<(usize, usize)> {
+    let first = *data.get(offset)?;
+    if first & 0x80 == 0 {
+        Some((first as usize, 1))
+    } else if first & 0xC0 == 0x80 {
+        let second = *data.get(offset + 1)?;
+        Some(((((first & 0x3F) as usize) << 8) | second as usize, 2))
+    } else if first & 0xE0 == 0xC0 {
+        let rest = data.get(offset + 1..offset + 4)?;
+        let value = (((first & 0x1F) as usize) << 24)
+            | ((rest[0] as usize) << 16)
+            | ((rest[1] as usize) << 8)
+            | rest[2] as usize;
+        Some((value, 4))
+    } else {
+        None
+    }
+}
+
+fn read_c_string(data: &[u8], offset: usize) -> Option<&str> {
+    let bytes = data.get(offset..)?;
+    let end = bytes.iter().position(|&b| b == 0)?;
+    std::str::from_utf8(&bytes[..end]).ok()
+}
+
+// ───────────────────────────────────────────────
+// Metadata / Metadata 解析
+// ───────────────────────────────────────────────
+
+/// Parsed view over the metadata root: heaps and table locations
+/// metadata root 的解析結果：各 heap 與資料表位置
+struct Metadata<'a> {
+    strings: &'a [u8],
+    blob: &'a [u8],
+    tables: &'a [u8],
+    rows: [u32; 64],
+    table_offsets: [usize; 64],
+    row_sizes: [usize; 64],
+    string_index_size: usize,
+    guid_index_size: usize,
+    blob_index_size: usize,
+}
+
+impl<'a> Metadata<'a> {
+    fn parse(metadata: &'a [u8]) -> Option<Self> {
+        if read_u32(metadata, 0)? != 0x424A_5342 {
+            return None;
+        }
+        let version_len = read_u32(metadata, 12)? as usize;
+        let mut offset = 16 + version_len;
+        let stream_count = read_u16(metadata, offset + 2)? as usize;
+        offset += 4;
+
+        let mut strings = None;
+        let mut blob = None;
+        let mut tables = None;
+        for _ in 0..stream_count {
+            let stream_offset = read_u32(metadata, offset)? as usize;
+            let stream_size = read_u32(metadata, offset + 4)? as usize;
+            let name = read_c_string(metadata, offset + 8)?;
+            let body = metadata.get(stream_offset..stream_offset + stream_size)?;
+            match name {
+                "#Strings" => strings = Some(body),
+                "#Blob" => blob = Some(body),
+                "#~" | "#-" => tables = Some(body),
+                _ => {}
+            }
+            // Name is null-terminated and padded to a 4-byte boundary
+            // 名稱以 null 結尾並補齊至 4 位元組
+            offset += 8 + (name.len() + 4) / 4 * 4;
+        }
+
+        let tables = tables?;
+        let heap_sizes = *tables.get(6)?;
+        let valid = u64::from_le_bytes(tables.get(8..16)?.try_into().ok()?);
+
+        let mut rows = [0u32; 64];
+        let mut offset = 24;
+        for (table, count) in rows.iter_mut().enumerate() {
+            if valid & (1 << table) != 0 {
+                *count = read_u32(tables, offset)?;
+                offset += 4;
+            }
+        }
+        if heap_sizes & 0x40 != 0 {
+            offset += 4;
+        }
+
+        let mut metadata = Metadata {
+            strings: strings.unwrap_or(&[]),
+            blob: blob.unwrap_or(&[]),
+            tables,
+            rows,
+            table_offsets: [0; 64],
+            row_sizes: [0; 64],
+            string_index_size: if heap_sizes & 0x01 != 0 { 4 } else { 2 },
+            guid_index_size: if heap_sizes & 0x02 != 0 { 4 } else { 2 },
+            blob_index_size: if heap_sizes & 0x04 != 0 { 4 } else { 2 },
+        };
+
+        // Only tables up to CustomAttribute are needed; stop before unknown layouts
+        // 只需要到 CustomAttribute 為止的資料表
+        for table in 0..=CUSTOM_ATTRIBUTE {
+            metadata.table_offsets[table] = offset;
+            metadata.row_sizes[table] = table_columns(table)
+                .iter()
+                .map(|col| metadata.col_size(*col))
+                .sum();
+            offset += metadata.row_sizes[table] * metadata.rows[table] as usize;
+        }
+        if offset > tables.len() {
+            return None;
+        }
+
+        Some(metadata)
+    }
+
+    fn coded_size(&self, tables: &[usize]) -> usize {
+        let tag_bits = usize::BITS - (tables.len() - 1).leading_zeros();
+        let max_rows = tables
+            .iter()
+            .filter_map(|&t| self.rows.get(t))
+            .copied()
+            .max()
+            .unwrap_or(0);
+        if max_rows < (1 << (16 - tag_bits)) {
+            2
+        } else {
+            4
+        }
+    }
+
+    fn col_size(&self, col: Col) -> usize {
+        match col {
+            Col::U16 => 2,
+            Col::U32 => 4,
+            Col::Str => self.string_index_size,
+            Col::Guid => self.guid_index_size,
+            Col::Blob => self.blob_index_size,
+            Col::Table(t) => {
+                if self.rows[t] < 0x10000 {
+                    2
+                } else {
+                    4
+                }
+            }
+            Col::Coded(tables) => self.coded_size(tables),
+        }
+    }
+
+    /// Read column `col` of 1-based row `row` in `table`
+    /// 讀取指定資料表第 `row` 列（從 1 開始）的第 `col` 欄
+    fn cell(&self, table: usize, row: u32, col: usize) -> Option<u32> {
+        if row == 0 || row > self.rows[table] {
+            return None;
+        }
+        let columns = table_columns(table);
+        let col_offset: usize = columns[..col].iter().map(|c| self.col_size(*c)).sum();
+        let offset =
+            self.table_offsets[table] + (row as usize - 1) * self.row_sizes[table] + col_offset;
+        read_sized(self.tables, offset, self.col_size(columns[col]))
+    }
+
+    fn string(&self, index: u32) -> Option<&'a str> {
+        read_c_string(self.strings, index as usize)
+    }
+
+    fn blob(&self, index: u32) -> Option<&'a [u8]> {
+        let (len, header) = read_compressed(self.blob, index as usize)?;
+        let start = index as usize + header;
+        self.blob.get(start..start + len)
+    }
+
+    /// Resolve the type name of a custom attribute's constructor
+    /// 取得自訂屬性建構子所屬型別的名稱
+    fn attribute_type_name(&self, ctor: u32) -> Option<&'a str> {
+        let tag = ctor & 0x7;
+        let row = ctor >> 3;
+        let parent = match *CUSTOM_ATTRIBUTE_TYPE.get(tag as usize)? {
+            MEMBER_REF => self.cell(MEMBER_REF, row, 0)?,
+            // Attribute declared in the same assembly; its owner type isn't needed here
+            // 在同一組件內宣告的屬性，此處不需要解析
+            _ => return None,
+        };
+        let parent_tag = parent & 0x7;
+        let parent_row = parent >> 3;
+        match *MEMBER_REF_PARENT.get(parent_tag as usize)? {
+            TYPE_REF => self.string(self.cell(TYPE_REF, parent_row, 1)?),
+            TYPE_DEF => self.string(self.cell(TYPE_DEF, parent_row, 1)?),
+            _ => None,
+        }
+    }
+}
+
+/// Decode the fixed string arguments of a custom attribute value blob
+/// 解析自訂屬性 blob 中的固定字串參數
+fn decode_string_args(blob: &[u8], count: usize) -> Option<Vec<Option<String>>> {
+    if read_u16(blob, 0)? != 0x0001 {
+        return None;
+    }
+    let mut offset = 2;
+    let mut args = Vec::with_capacity(count);
+    for _ in 0..count {
+        if *blob.get(offset)? == 0xFF {
+            args.push(None);
+            offset += 1;
+            continue;
+        }
+        let (len, header) = read_compressed(blob, offset)?;
+        offset += header;
+        let bytes = blob.get(offset..offset + len)?;
+        args.push(Some(String::from_utf8_lossy(bytes).to_string()));
+        offset += len;
+    }
+    Some(args)
+}
+
+/// Find the first custom attribute whose type is named `type_name` and return its
+/// first `arg_count` string constructor arguments.
+/// Returns `None` when the metadata is malformed (not a managed assembly) and
+/// `Some(None)` when the metadata is fine but the attribute isn't present.
+/// 找出第一個型別名稱為 `type_name` 的自訂屬性並回傳其字串參數；
+/// metadata 損毀時回傳 `None`，找不到屬性時回傳 `Some(None)`
+pub(crate) fn find_attribute_string_args(
+    metadata: &[u8],
+    type_name: &str,
+    arg_count: usize,
+) -> Option<Option<Vec<Option<String>>>> {
+    let metadata = Metadata::parse(metadata)?;
+
+    let found = (1..=metadata.rows[CUSTOM_ATTRIBUTE]).find_map(|row| {
+        let ctor = metadata.cell(CUSTOM_ATTRIBUTE, row, 1)?;
+        if metadata.attribute_type_name(ctor)? != type_name {
+            return None;
+        }
+        let value = metadata.blob(metadata.cell(CUSTOM_ATTRIBUTE, row, 2)?)?;
+        decode_string_args(value, arg_count)
+    });
+
+    Some(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_attribute_string_args, CUSTOM_ATTRIBUTE, MEMBER_REF, TYPE_REF};
+
+    /// Value blob of `[BepInPlugin(args..)]`: prolog, length-prefixed strings, no named args
+    /// `[BepInPlugin(args..)]` 的值 blob：前導碼、加上長度前綴的字串，沒有具名參數
+    fn attribute_value(args: &[&str]) -> Vec<u8> {
+        let mut value = vec![0x01, 0x00];
+        for arg in args {
+            value.push(arg.len() as u8);
+            value.extend_from_slice(arg.as_bytes());
+        }
+        value.extend_from_slice(&[0x00, 0x00]);
+        value
+    }
+
+    /// Smallest metadata root with one TypeRef named `type_name`, one MemberRef `.ctor` on it
+    /// and one CustomAttribute using that constructor with `args`
+    /// 最小的 metadata root：一個名為 `type_name` 的 TypeRef、其上的 `.ctor` MemberRef，以及以該建構子與
+    /// `args` 建立的 CustomAttribute
+    fn metadata(type_name: &str, args: &[&str]) -> Vec<u8> {
+        let strings = [b"\0", type_name.as_bytes(), b"\0.ctor\0"].concat();
+        let ctor_name = 1 + type_name.len() as u16 + 1;
+        let value = attribute_value(args);
+        let blob = [&[0x00, value.len() as u8][..], &value].concat();
+
+        let mut tables = vec![0, 0, 0, 0, 2, 0, 0, 1];
+        let valid: u64 = (1 << TYPE_REF) | (1 << MEMBER_REF) | (1 << CUSTOM_ATTRIBUTE);
+        tables.extend_from_slice(&valid.to_le_bytes());
+        tables.extend_from_slice(&0u64.to_le_bytes());
+        for _ in 0..3 {
+            tables.extend_from_slice(&1u32.to_le_bytes());
+        }
+        let words = |words: &[u16]| {
+            words
+                .iter()
+                .flat_map(|w| w.to_le_bytes())
+                .collect::<Vec<_>>()
+        };
+        // TypeRef: scope, name, namespace / MemberRef: TypeRef 1, `.ctor`, signature
+        // CustomAttribute: parent, MemberRef 1, value
+        tables.extend(words(&[0, 1, 0]));
+        tables.extend(words(&[(1 << 3) | 1, ctor_name, 0]));
+        tables.extend(words(&[0, (1 << 3) | 3, 1]));
+
+        let streams: [(&str, &[u8]); 3] =
+            [("#~", &tables), ("#Strings", &strings), ("#Blob", &blob)];
+        let header_len = 16
+            + 4
+            + 4
+            + streams
+                .iter()
+                .map(|(name, _)| 8 + (name.len() + 4) / 4 * 4)
+                .sum::<usize>();
+        let mut root = 0x424A_5342u32.to_le_bytes().to_vec();
+        root.extend_from_slice(&[1, 0, 1, 0, 0, 0, 0, 0]);
+        root.extend_from_slice(&4u32.to_le_bytes());
+        root.extend_from_slice(b"v4\0\0");
+        root.extend_from_slice(&[0, 0, streams.len() as u8, 0]);
+        let mut offset = header_len;
+        for (name, body) in &streams {
+            root.extend_from_slice(&(offset as u32).to_le_bytes());
+            root.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            let mut padded = name.as_bytes().to_vec();
+            padded.resize((name.len() + 4) / 4 * 4, 0);
+            root.extend(padded);
+            offset += body.len();
+        }
+        for (_, body) in &streams {
+            root.extend_from_slice(body);
+        }
+        root
+    }
+
+    #[test]
+    fn bepinplugin_arguments_are_decoded() {
+        let data = metadata("BepInPlugin", &["com.example.plugin", "Example", "1.2.3"]);
+
+        let args = find_attribute_string_args(&data, "BepInPlugin", 3);
+
+        assert_eq!(
+            args,
+            Some(Some(vec![
+                Some("com.example.plugin".to_string()),
+                Some("Example".to_string()),
+                Some("1.2.3".to_string()),
+            ]))
+        );
+        assert_eq!(find_attribute_string_args(&data, "Other", 3), Some(None));
+    }
+
+    #[test]
+    fn truncated_or_garbage_metadata_does_not_panic() {
+        let data = metadata("BepInPlugin", &["com.example.plugin", "Example", "1.2.3"]);
+
+        for len in 0..data.len() {
+            let _ = find_attribute_string_args(&data[..len], "BepInPlugin", 3);
+        }
+        for index in 0..data.len() {
+            let mut corrupt = data.clone();
+            corrupt[index] = 0xFF;
+            let _ = find_attribute_string_args(&corrupt, "BepInPlugin", 3);
+        }
+        let garbage = (0..512u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 7) as u8)
+            .collect::<Vec<_>>();
+        assert_eq!(find_attribute_string_args(&garbage, "BepInPlugin", 3), None);
+        assert_eq!(
+            find_attribute_string_args(&data[..40], "BepInPlugin", 3),
+            None
+        );
+    }
+}
@@ -0,0 +1,2928 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    iter,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+    app_log, archive_inspect, archive_open, asset_index,
+    background_refresh::{self, BackgroundRefresh},
+    checksums,
+    cleanup_stats::{self, CleanupCounters},
+    cloud_files, conflict_coupling, conflict_notes,
+    conflict_sort::{self, ConflictSort},
+    corrupt_mods, diagnostics, empty_dirs,
+    error::AppError,
+    events::ProgressReporter,
+    file_snapshot::{self, ExpectedFile, FileSnapshot},
+    first_seen, folder_density, full_analysis, game_tags, grouping, guid_usages, incidents,
+    index_db, install_diff,
+    junk_files::LastJunk,
+    large_results::{self, Guarded},
+    list_ids, long_path,
+    manifest::ManifestData,
+    messages::{self, Message},
+    missing_deps, mod_roots,
+    modpack::{self, ModpackFolders},
+    modpack_integrity,
+    mods_scan::{self, ModFile},
+    mods_watch::{self, ModChange, ModsWatch},
+    name_anomalies, nested_archives,
+    operation_estimates::{self, EstimateKind},
+    operations::{self, Operations},
+    ops,
+    parser::{self, LineRange, ModConflict, ModEntry},
+    path_guard::{AllowedRoots, WriteMode},
+    patterns,
+    pins::{self, PinSet},
+    plugins, priority_folders,
+    profiles::{self, profile_id, AppSettings, ProfileSettings},
+    quarantine, recent_changes, reclaim,
+    resolution::{self, LastConflicts, ResolveAction},
+    resolve_rules, same_name,
+    scan::{self, ScanResult, ScanSummary},
+    scan_cache,
+    scan_pages::{self, Page, ScanStore, SortBy, StoredScan},
+    self_check,
+    session_progress::{self, SessionProgress},
+    stage_installs,
+    storage::{Storage, StorageStatus},
+    superseded, support_fingerprint,
+    timings::Timings,
+    zipmod_convert,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Payload of the `mods-changed` event
+/// `mods-changed` 事件資料
+#[derive(Serialize, Clone)]
+struct ModsChanged {
+    game_path: String,
+    changes: Vec<ModChange>,
+}
+
+/// The mods watch of the active profile, if any
+/// 目前 profile 的 mods 監看（若有）
+#[derive(Default)]
+struct ActiveWatch(Mutex<Option<ModsWatch>>);
+
+/// Payload of the `conflict-count-changed` event
+/// `conflict-count-changed` 事件資料
+#[derive(Serialize, Clone)]
+struct ConflictCountChanged {
+    profile_id: String,
+    game_path: String,
+    #[serde(flatten)]
+    summary: ScanSummary,
+}
+
+/// The background refresh, if any
+/// 背景更新（若有）
+#[derive(Default)]
+struct ActiveRefresh(Mutex<Option<BackgroundRefresh>>);
+
+// ───────────────────────────────────────────────
+// Utility Function / 工具函式
+// ───────────────────────────────────────────────
+
+/// App data dir resolved at startup, or the folder standing in for it; see `storage`
+/// 啟動時解析的 app data 資料夾，或代替它的資料夾；見 `storage`
+fn storage_root(app: &AppHandle) -> PathBuf {
+    app.state::<Storage>().root().to_path_buf()
+}
+
+fn profiles_root(app: &AppHandle) -> PathBuf {
+    app.state::<Storage>().profiles_root()
+}
+
+/// Folder of the result files too large to return over IPC; see `large_results`
+/// 因過大而無法經由 IPC 回傳的結果檔所在資料夾；見 `large_results`
+fn results_dir(app: &AppHandle) -> PathBuf {
+    storage_root(app).join("results")
+}
+
+/// Counters file of `get_cleanup_stats` inside the app data dir
+/// app data 資料夾內 `get_cleanup_stats` 的計數檔
+fn cleanup_stats_path(app: &AppHandle) -> PathBuf {
+    storage_root(app).join("cleanup_stats.json")
+}
+
+/// Count trashed files towards the cleanup stats; failing to save them is logged and never
+/// fails the operation that freed the space
+/// 將丟到回收桶的檔案計入清理統計；儲存失敗只會記錄於 log，不會讓釋放空間的操作失敗
+fn record_cleanup(app: &AppHandle, bytes_freed: u64, files: usize) {
+    let recorded = app.state::<CleanupCounters>().record(
+        &cleanup_stats_path(app),
+        bytes_freed,
+        files,
+    );
+    if let Err(e) = recorded {
+        app_log::write(&format!("cleanup stats not saved: {}", e));
+    }
+}
+
+/// Per-profile data directory inside the app data dir; registers the game path on first use,
+/// for the session only when nothing can be saved
+/// app data 目錄中各 profile 專屬的資料夾；首次使用時登記遊戲路徑，無法儲存時只登記於本次執行
+fn profile_data_dir(app: &AppHandle, game_path: &str) -> Result<PathBuf, AppError> {
+    app.state::<Storage>().profile_dir(game_path)
+}
+
+/// Saved settings of a profile
+/// 取得 profile 的設定
+fn profile_settings(app: &AppHandle, game_path: &str) -> Result<ProfileSettings, AppError> {
+    Ok(profiles::load_settings(&profiles_root(app), game_path))
+}
+
+/// Game paths of every registered profile
+/// 所有已登記 profile 的遊戲路徑
+fn registered_game_paths(app: &AppHandle) -> Result<Vec<String>, AppError> {
+    Ok(app.state::<Storage>().registered_game_paths())
+}
+
+/// Game path of the registered profile with id `profile_id`
+/// 取得 id 為 `profile_id` 之已登記 profile 的遊戲路徑
+fn profile_game_path(app: &AppHandle, profile_id: &str) -> Result<String, AppError> {
+    registered_game_paths(app)?
+        .into_iter()
+        .find(|game_path| profiles::profile_id(game_path) == profile_id)
+        .ok_or_else(|| AppError::InvalidInput {
+            message: Message::new("input.unknown_profile").with("profile_id", profile_id),
+        })
+}
+
+/// Show the conflict count in the main window title, or restore the plain title with `None`
+/// 在主視窗標題顯示衝突數；傳入 `None` 時還原原本的標題
+fn set_conflict_title(app: &AppHandle, conflicts: Option<usize>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let base = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|config| config.label == "main")
+        .map(|config| config.title.clone())
+        .unwrap_or_default();
+    let title = match conflicts {
+        Some(conflicts) => format!("{} ({} conflicts)", base, conflicts),
+        None => base,
+    };
+    let _ = window.set_title(&title);
+}
+
+/// Modpack folders of `game_paths`, each from its own profile settings
+/// `game_paths` 的模組包資料夾，各自依其 profile 設定
+fn modpack_folders<S: AsRef<str>>(
+    app: &AppHandle,
+    game_paths: &[S],
+) -> Result<ModpackFolders, AppError> {
+    let games = game_paths
+        .iter()
+        .map(|game_path| {
+            let settings = profile_settings(app, game_path.as_ref())?;
+            Ok((game_path.as_ref(), settings.modpack_prefixes))
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+    Ok(ModpackFolders::for_games(&games))
+}
+
+/// Tear down the mods watch when the frontend switches to another profile
+/// 前端切換到其他 profile 時停止原本的 mods 監看
+fn stop_watch_for_other_profile(app: &AppHandle, game_path: &str) {
+    let active = app.state::<ActiveWatch>();
+    let mut watch = active.0.lock().unwrap();
+    let other = watch
+        .as_ref()
+        .map(|w| profile_id(&w.game_path) != profile_id(game_path))
+        .unwrap_or(false);
+    if other {
+        *watch = None;
+    }
+}
+
+/// Run blocking work on the blocking thread pool so the IPC handler stays responsive.
+/// A panic inside `work` is caught on the worker thread, recorded as an incident tagged
+/// with `command`, and returned as `AppError::InternalPanic` carrying only the incident id
+/// and a short message; the full report is available via `get_incident_report`.
+/// 在 blocking 執行緒池中執行耗時工作，避免卡住 IPC；panic 會在工作執行緒上被攔截並記錄為事故，
+/// 回傳的 `InternalPanic` 只帶 incident id 與簡短訊息，完整報告可透過 `get_incident_report` 取得
+async fn safe_invoke<T, F>(command: &'static str, work: F) -> Result<T, AppError>
+where
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+    T: Send + 'static,
+{
+    safe_invoke_measured(command, |_| String::new(), work).await
+}
+
+/// `safe_invoke` that also logs `describe(&result)` (e.g. result sizes) next to the duration
+/// 與 `safe_invoke` 相同，並在耗時旁記錄 `describe(&result)`（例如結果數量）
+async fn safe_invoke_measured<T, F, D>(
+    command: &'static str,
+    describe: D,
+    work: F,
+) -> Result<T, AppError>
+where
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+    D: Fn(&T) -> String + Send + 'static,
+    T: Send + 'static,
+{
+    let guarded = move || {
+        let start = Instant::now();
+        let result = incidents::catch(command, work);
+        let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+        let outcome = match &result {
+            Ok(value) => match describe(value) {
+                sizes if sizes.is_empty() => "ok".to_string(),
+                sizes => format!("ok, {}", sizes),
+            },
+            Err(e) => format!("error {}", e.code()),
+        };
+        app_log::write(&format!("{} took {:.1}ms ({})", command, elapsed, outcome));
+        result
+    };
+
+    match tauri::async_runtime::spawn_blocking(guarded).await {
+        Ok(result) => result,
+        Err(e) => Err(AppError::InternalPanic {
+            incident_id: None,
+            detail: e.to_string(),
+        }),
+    }
+}
+
+/// `safe_invoke_measured` for commands whose results can be huge: a result whose JSON is
+/// larger than the app's `max_response_bytes` is written to a result file, and only its path
+/// and `summarize(&result)` go over IPC; see `read_result_chunk`
+/// 用於結果可能極大的指令的 `safe_invoke_measured`：JSON 超過 app 設定 `max_response_bytes` 的結果會寫入結果檔，
+/// 經由 IPC 只傳遞其路徑與 `summarize(&result)`；見 `read_result_chunk`
+async fn safe_invoke_guarded<T, F>(
+    app: &AppHandle,
+    command: &'static str,
+    describe: fn(&T) -> String,
+    summarize: fn(&T) -> serde_json::Value,
+    work: F,
+) -> Result<Guarded<T>, AppError>
+where
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+    T: Serialize + Send + 'static,
+{
+    let dir = results_dir(app);
+    let max_bytes = profiles::load_app_settings(&profiles_root(app)).max_response_bytes;
+    let describe = move |result: &Guarded<T>| match result {
+        Guarded::Inline(value) => describe(value),
+        Guarded::Stored(stored) => format!("{} bytes stored at {}", stored.bytes, stored.stored_at),
+    };
+    safe_invoke_measured(command, describe, move || {
+        large_results::guard(work()?, summarize, &dir, max_bytes)
+    })
+    .await
+}
+
+// ───────────────────────────────────────────────
+// Tauri Commands / 可由前端呼叫的函式
+// ───────────────────────────────────────────────
+
+/// Parse the log and extract all mod conflicts. With `log_modified` (unix seconds of the
+/// log file), entries changed after the log was written get `changed_since_log`.
+/// Entries inside the profile's modpack folders get `managed`. `sort` orders the result by
+/// `wasted_bytes`, `skipped_count`, `name` or `most_recent_activity`; missing keeps log order.
+/// `start_line`/`end_line` (1-based, inclusive, clamped to the log) keep only warnings entirely
+/// inside that range; `line` stays relative to the full log. `lenient` is for text pasted out of
+/// Discord or a forum: curly quotes, chat prefixes and soft line breaks are undone first and
+/// the conflicts are flagged `lenient`. A game without a mods folder fails with
+/// `mods_dir_missing` instead of listing every entry as an empty file. Results too large for
+/// IPC come back as a `stored_at` envelope; see `read_result_chunk`.
+/// 解析 log 字串，抓出所有模組衝突紀錄；提供 `log_modified`（log 檔案的 unix 秒數）時，
+/// 在 log 寫入後變動的項目會標記 `changed_since_log`；位於 profile 模組包資料夾內的項目標記 `managed`；
+/// `sort` 可依 `wasted_bytes`、`skipped_count`、`name` 或 `most_recent_activity` 排序，未指定時維持 log 順序；
+/// `start_line`/`end_line`（從 1 起算、含頭尾，超出時限制在 log 內）只保留完整位於範圍內的警告，`line` 仍以完整 log 為準；
+/// `lenient` 用於從 Discord 或論壇貼上的文字：先還原彎引號、聊天前綴與自動換行，並將衝突標記為 `lenient`；
+/// 遊戲沒有 mods 資料夾時回傳 `mods_dir_missing`，而不是將每個項目列為空檔案；
+/// 過大而不適合 IPC 的結果改以 `stored_at` 封裝回傳，見 `read_result_chunk`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn parse_log(
+    app: AppHandle,
+    log: String,
+    game_path: String,
+    log_modified: Option<u64>,
+    sort: Option<String>,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+    lenient: Option<bool>,
+) -> Result<Guarded<Vec<ModConflict>>, AppError> {
+    let sort = ConflictSort::parse(sort.as_deref())?;
+    let modpacks = modpack_folders(&app, &[&game_path])?;
+    let settings = profile_settings(&app, &game_path)?;
+    let priority_prefixes = settings.priority_folder_prefixes;
+    let mod_extensions = settings.mod_extensions;
+    let notes_dir = profile_data_dir(&app, &game_path)?;
+    let range = (start_line.is_some() || end_line.is_some()).then(|| LineRange {
+        start: start_line.unwrap_or(1),
+        end: end_line.unwrap_or(usize::MAX),
+    });
+    let describe = |conflicts: &Vec<ModConflict>| format!("{} conflicts", conflicts.len());
+    let summarize = |conflicts: &Vec<ModConflict>| {
+        serde_json::json!({
+            "conflicts": conflicts.len(),
+            "skipped_files": conflicts.iter().map(|c| c.skipped.len()).sum::<usize>(),
+        })
+    };
+    let handle = app.clone();
+    safe_invoke_guarded(&handle, "parse_log", describe, summarize, move || {
+        mod_roots::ensure_mods_dir(Path::new(&game_path))?;
+        let mut conflicts = match (lenient.unwrap_or(false), range) {
+            (true, range) => {
+                parser::parse_pasted_conflicts(&log, &game_path, &mod_extensions, range)
+            }
+            (false, Some(range)) => {
+                parser::parse_conflicts_in_range(&log, &game_path, &mod_extensions, range)
+            }
+            (false, None) => parser::parse_conflicts(&log, &game_path, &mod_extensions),
+        };
+        parser::mark_changed_since_log(&mut conflicts, log_modified);
+        modpack::mark_managed(&mut conflicts, &modpacks);
+        priority_folders::mark_priority(&mut conflicts, &priority_prefixes);
+        pins::mark(&notes_dir, &game_path, &mut conflicts);
+        conflict_coupling::mark_coupling(&mut conflicts);
+        conflict_notes::attach(&notes_dir, &mut conflicts);
+        first_seen::observe_conflicts(&notes_dir, &game_path, &mut conflicts);
+        conflict_sort::sort_conflicts(&mut conflicts, sort);
+        app.state::<LastConflicts>().store(&game_path, &conflicts);
+        app.state::<SessionProgress>().track(&game_path, &conflicts);
+        Ok(conflicts)
+    })
+    .await
+}
+
+/// Try to find and read the Koikatsu log file
+/// 嘗試讀取 Koikatsu 的 log 檔案（從常見路徑中找）
+#[tauri::command]
+async fn read_log_from_path(game_path: String) -> Result<String, AppError> {
+    safe_invoke("read_log_from_path", move || scan::find_and_read_log(&game_path)).await
+}
+
+/// Find, read and parse the game log in one call, using the per-profile cache unless `force_rescan`.
+/// Reports metadata reads on `operation-progress` with kind `scan`; modpack entries get `managed`.
+/// `sort` and results too large for IPC work as in `parse_log`.
+/// An earlier scan of the same profile is cancelled only when `cancel_previous` is set.
+/// 一次完成尋找、讀取與解析遊戲 log；除非 `force_rescan`，否則使用 profile 快取；進度以 `scan` 類型送出；
+/// 模組包中的項目標記 `managed`；`sort` 與過大結果的處理與 `parse_log` 相同；
+/// 只有設定 `cancel_previous` 時才會取消同 profile 先前的掃描
+#[tauri::command]
+async fn scan_conflicts(
+    app: AppHandle,
+    game_path: String,
+    force_rescan: Option<bool>,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+    include_timings: Option<bool>,
+    sort: Option<String>,
+) -> Result<Guarded<ScanResult>, AppError> {
+    let sort = ConflictSort::parse(sort.as_deref())?;
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let cache_path = profile_dir.join("scan_cache.json");
+    let modpacks = modpack_folders(&app, &[&game_path])?;
+    let settings = profile_settings(&app, &game_path)?;
+    let priority_prefixes = settings.priority_folder_prefixes;
+    let mod_extensions = settings.mod_extensions;
+    let describe = |scan: &ScanResult| {
+        format!(
+            "{} conflicts, {} skipped files, from_cache={}",
+            scan.summary.conflicts, scan.summary.skipped_files, scan.from_cache
+        )
+    };
+    let summarize = |scan: &ScanResult| serde_json::json!(scan.summary);
+    let handle = app.clone();
+    safe_invoke_guarded(&handle, "scan_conflicts", describe, summarize, move || {
+        stop_watch_for_other_profile(&app, &game_path);
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "scan",
+            operation_id,
+            cancel_previous.unwrap_or(false),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = scan::scan_conflicts_cached(
+            &cache_path,
+            &game_path,
+            &mod_extensions,
+            force_rescan.unwrap_or(false),
+            &|progress| reporter.update(progress),
+            Timings::new(include_timings.unwrap_or(false)),
+        )
+        .map(|mut scan| {
+            modpack::mark_managed(&mut scan.conflicts, &modpacks);
+            priority_folders::mark_priority(&mut scan.conflicts, &priority_prefixes);
+            pins::mark(&profile_dir, &game_path, &mut scan.conflicts);
+            conflict_coupling::mark_coupling(&mut scan.conflicts);
+            conflict_notes::attach(&profile_dir, &mut scan.conflicts);
+            first_seen::observe_conflicts(&profile_dir, &game_path, &mut scan.conflicts);
+            conflict_sort::sort_conflicts(&mut scan.conflicts, sort);
+            app.state::<LastConflicts>().store(&game_path, &scan.conflicts);
+            app.state::<SessionProgress>().track(&game_path, &scan.conflicts);
+            scan
+        });
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Re-stat the loaded and skipped files of the given conflicts of the last scan (all of them
+/// when `conflict_ids` is None), e.g. after files were deleted outside the app, and store the
+/// result for later commands. The log is not read; conflicts keep their ids, notes and order,
+/// and unknown ids are ignored.
+/// 重新讀取最近一次掃描中指定衝突（`conflict_ids` 為 None 時為全部）的 loaded 與 skipped 檔案資訊，
+/// 例如在本程式外刪除檔案之後，並存回供後續指令使用；不讀取 log，衝突保留其 id、筆記與順序，未知的 id 會被忽略
+#[tauri::command]
+async fn refresh_conflict_metadata(
+    app: AppHandle,
+    conflict_ids: Option<Vec<String>>,
+) -> Result<Vec<ModConflict>, AppError> {
+    let Some((game_path, mut conflicts)) = app
+        .state::<LastConflicts>()
+        .select(conflict_ids.as_deref())
+    else {
+        return Ok(Vec::new());
+    };
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let modpacks = modpack_folders(&app, &[&game_path])?;
+    let settings = profile_settings(&app, &game_path)?;
+    let priority_prefixes = settings.priority_folder_prefixes;
+    let mod_extensions = settings.mod_extensions;
+    safe_invoke("refresh_conflict_metadata", move || {
+        parser::restat_conflicts(&mut conflicts, &mod_extensions);
+        modpack::mark_managed(&mut conflicts, &modpacks);
+        priority_folders::mark_priority(&mut conflicts, &priority_prefixes);
+        pins::mark(&profile_dir, &game_path, &mut conflicts);
+        first_seen::observe_conflicts(&profile_dir, &game_path, &mut conflicts);
+        app.state::<LastConflicts>().update(&game_path, &conflicts);
+        Ok(conflicts)
+    })
+    .await
+}
+
+/// Count what a completed run of `kind` covered and fold its duration into the profile's
+/// throughput for `estimate_operation`; the listing is cheap next to the run itself
+/// 計算一次完成的 `kind` 作業所涵蓋的檔案，並將其耗時併入 profile 的處理速度供 `estimate_operation` 使用；
+/// 與作業本身相比，列出檔案的成本很低
+fn record_candidate_run(
+    profile_dir: &Path,
+    kind: EstimateKind,
+    game_path: &str,
+    settings: &ProfileSettings,
+    started: Instant,
+) {
+    let elapsed = started.elapsed();
+    let (_, bytes) = operation_estimates::count_candidates(
+        kind,
+        Path::new(game_path),
+        settings.follow_links,
+        &settings.mod_extensions,
+    );
+    operation_estimates::record_run(profile_dir, kind, bytes, elapsed);
+}
+
+/// Count the files a heavy operation (`asset-index`, `checksum-export`, `checksum-verify` or
+/// `corrupt-scan`) would process and their size from directory listings alone, with a
+/// duration estimated from the throughput of earlier runs of that kind in this profile
+/// 只讀取目錄清單，計算耗時作業（`asset-index`、`checksum-export`、`checksum-verify` 或 `corrupt-scan`）
+/// 會處理的檔案數與總大小，並依此 profile 先前同類作業的處理速度預估所需時間
+#[tauri::command]
+async fn estimate_operation(
+    app: AppHandle,
+    kind: String,
+    game_path: String,
+) -> Result<operation_estimates::OperationEstimate, AppError> {
+    let kind = EstimateKind::parse(&kind)?;
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let settings = profile_settings(&app, &game_path)?;
+    safe_invoke("estimate_operation", move || {
+        Ok(operation_estimates::estimate_operation(
+            kind,
+            &profile_dir,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+        ))
+    })
+    .await
+}
+
+/// Move mod files to Recycle Bin. Every path must lie inside a registered game's mods,
+/// BepInEx/plugins or quarantine folder unless `unsafe_allow_any_path` is set, and paths
+/// inside a modpack folder fail with `modpack_protected` unless `allow_modpack` is set,
+/// pinned files with `pinned` unless `allow_pinned` is set, and folders, such as loose-folder
+/// mods, with `directory_not_mod` unless `allow_directories` is set.
+/// Files whose size or mtime differ from `expected` (or, for paths not listed there, from
+/// the last conflict scan) fail the batch with `file_changed` unless `force` is set.
+/// Files of a profile in safe mode are quarantined instead and come back with
+/// `redirected_to_quarantine`; in safe mode, files outside every game fail with
+/// `safe_mode_refused`. Read-only files fail the batch with `read_only` unless
+/// `clear_read_only` is set, which clears their flag first and notes that in the app log.
+/// Fails with `path_busy` while another operation works on one of the paths. Reports each
+/// file on `operation-progress` with kind `delete`; cancellable between files. After a
+/// successful batch, `prune_empty_dirs` removes the folders it left empty up to the mods
+/// roots, listed in `pruned_dirs`; `prune_junk` lets folders holding only files like
+/// `Thumbs.db` count as empty.
+/// 將指定模組檔案丟進回收桶；除非設定 `unsafe_allow_any_path`，路徑必須位於已登記遊戲的允許資料夾內；
+/// 位於模組包資料夾內的路徑除非設定 `allow_modpack`，否則回傳 `modpack_protected`；
+/// 已釘選的檔案除非設定 `allow_pinned`，否則回傳 `pinned`；資料夾（例如散裝資料夾模組）除非設定
+/// `allow_directories`，否則回傳 `directory_not_mod`。
+/// 除非設定 `force`，若有檔案的大小或修改時間與 `expected`（未列出的路徑則與最近一次衝突掃描）不同，
+/// 整批回傳 `file_changed`。處於安全模式之 profile 的檔案改為隔離，並標記 `redirected_to_quarantine`；
+/// 安全模式下不屬於任何遊戲的檔案回傳 `safe_mode_refused`。含唯讀檔案的批次回傳 `read_only`，除非設定
+/// `clear_read_only`，此時先清除其唯讀屬性並記錄於 app log。有路徑正由其他作業處理時回傳 `path_busy`。
+/// 每個檔案以 `delete` 類型回報進度，可於檔案之間取消。批次成功後，`prune_empty_dirs` 會刪除因此變空的資料夾
+/// （直到 mods 根目錄為止）並列於 `pruned_dirs`；`prune_junk` 讓只含 `Thumbs.db` 等檔案的資料夾也視為空資料夾
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn delete_mods(
+    app: AppHandle,
+    paths: Vec<String>,
+    unsafe_allow_any_path: Option<bool>,
+    allow_modpack: Option<bool>,
+    expected: Option<Vec<ExpectedFile>>,
+    force: Option<bool>,
+    clear_read_only: Option<bool>,
+    prune_empty_dirs: Option<bool>,
+    prune_junk: Option<bool>,
+    allow_pinned: Option<bool>,
+    allow_directories: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<ops::DeleteReport, AppError> {
+    let game_paths = registered_game_paths(&app)?;
+    let root = profiles_root(&app);
+    let modpacks = match allow_modpack.unwrap_or(false) {
+        true => None,
+        false => Some(modpack_folders(&app, &game_paths)?),
+    };
+    let pins = match allow_pinned.unwrap_or(false) {
+        true => Vec::new(),
+        false => game_paths
+            .iter()
+            .map(|game_path| PinSet::load(&profile_data_dir(&app, game_path)?, game_path))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    let expectations = match force.unwrap_or(false) {
+        true => None,
+        false => {
+            let mut expectations = app.state::<LastConflicts>().expectations();
+            file_snapshot::extend(&mut expectations, &expected.unwrap_or_default());
+            Some(expectations)
+        }
+    };
+    let safe_mode_games = game_paths
+        .iter()
+        .filter(|game_path| profiles::load_settings(&root, game_path).safe_mode)
+        .cloned()
+        .collect::<Vec<_>>();
+    safe_invoke("delete_mods", move || {
+        // Sized before the batch runs, while the files still exist
+        // 在批次執行前、檔案仍存在時取得大小
+        let sizes = paths
+            .iter()
+            .filter_map(|path| {
+                FileSnapshot::current(Path::new(path)).map(|file| (path.as_str(), file.size))
+            })
+            .collect::<HashMap<_, _>>();
+        let operations = app.state::<Operations>();
+        let operation = operations.register("", "delete", operation_id, false)?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let mut deleted = Vec::new();
+        let result = operation.lock_paths(&paths).and_then(|()| {
+            ops::delete_mods(
+                &game_paths,
+                &paths,
+                unsafe_allow_any_path.unwrap_or(false),
+                allow_directories.unwrap_or(false),
+                modpacks.as_ref(),
+                &pins,
+                expectations.as_ref(),
+                clear_read_only.unwrap_or(false),
+                &safe_mode_games,
+                operation.write_mode,
+                &|| operation.token.is_cancelled(),
+                &|progress| reporter.update(progress),
+                &mut deleted,
+            )
+        });
+        // A failed or cancelled batch may still have trashed some files; only the ones it moved
+        // itself, and that existed before it ran, are recorded
+        // 失敗或取消的批次仍可能已移除部分檔案；只記錄批次實際搬移、且執行前即存在的檔案
+        let (quarantined, trashed): (Vec<_>, Vec<_>) = deleted
+            .iter()
+            .filter(|file| sizes.contains_key(file.requested.as_str()))
+            .partition(|file| file.redirected_to_quarantine);
+        let paths_of = |files: &[&ops::DeletedFile]| {
+            files
+                .iter()
+                .map(|file| file.requested.clone())
+                .collect::<Vec<_>>()
+        };
+        let session = app.state::<SessionProgress>();
+        session.record(&paths_of(&trashed), ResolveAction::Trash);
+        session.record(&paths_of(&quarantined), ResolveAction::Quarantine);
+        record_cleanup(
+            &app,
+            trashed
+                .iter()
+                .map(|file| sizes[file.requested.as_str()])
+                .sum(),
+            trashed.len(),
+        );
+        reporter.finish(result.as_ref().err());
+        result?;
+        let pruned_dirs = match prune_empty_dirs.unwrap_or(false) {
+            true => {
+                let removed = deleted
+                    .iter()
+                    .map(|file| Path::new(&file.path))
+                    .collect::<Vec<_>>();
+                let roots = game_paths
+                    .iter()
+                    .flat_map(|game_path| mod_roots::mod_roots(Path::new(game_path)))
+                    .collect::<Vec<_>>();
+                empty_dirs::prune_empty_dirs(&removed, &roots, prune_junk.unwrap_or(false))
+            }
+            false => Vec::new(),
+        };
+        Ok(ops::DeleteReport {
+            deleted,
+            pruned_dirs,
+        })
+    })
+    .await
+}
+
+/// Open and extract manifest.xml from mod file; 7z and rar downloads are read too
+/// when this build supports them. `description_limit` caps `description_display` in
+/// characters (default `manifest::DEFAULT_DESCRIPTION_LIMIT`).
+/// 開啟模組檔並解析其中的 manifest.xml；此版本支援時也可讀取 7z 與 rar 下載檔；
+/// `description_limit` 為 `description_display` 的字元數上限（預設 `manifest::DEFAULT_DESCRIPTION_LIMIT`）
+#[tauri::command]
+async fn read_manifest_from_mod_file(
+    path: String,
+    description_limit: Option<usize>,
+) -> Result<ManifestData, AppError> {
+    safe_invoke("read_manifest_from_mod_file", move || {
+        let manifest = archive_inspect::read_manifest(Path::new(&path))?;
+        Ok(match description_limit {
+            Some(limit) => manifest.with_description_limit(limit),
+            None => manifest,
+        })
+    })
+    .await
+}
+
+/// Group conflicts by `manifest_author`, `filename_author`, `guid_prefix` or `priority_folder`,
+/// with the wasted bytes per group; conflicts without a key go into the `unknown` group.
+/// `priority_folder` uses the priority prefixes of `game_path`'s profile, or the defaults.
+/// `sort` orders the conflicts inside each group as in `parse_log`.
+/// 依 `manifest_author`、`filename_author`、`guid_prefix` 或 `priority_folder` 分組衝突並統計各組浪費空間；
+/// 無法分組者歸入 `unknown`；`priority_folder` 使用 `game_path` 所屬 profile 的優先前綴，未指定時使用預設值；
+/// `sort` 依 `parse_log` 的方式排序各組內的衝突
+#[tauri::command]
+async fn group_conflicts(
+    app: AppHandle,
+    conflicts: Vec<ModConflict>,
+    by: String,
+    sort: Option<String>,
+    game_path: Option<String>,
+) -> Result<Vec<grouping::ConflictGroup>, AppError> {
+    let by = grouping::GroupBy::parse(&by)?;
+    let sort = ConflictSort::parse(sort.as_deref())?;
+    let settings = match &game_path {
+        Some(game_path) => profile_settings(&app, game_path)?,
+        None => ProfileSettings::default(),
+    };
+    safe_invoke("group_conflicts", move || {
+        Ok(grouping::group_conflicts(
+            conflicts,
+            by,
+            sort,
+            &settings.priority_folder_prefixes,
+        ))
+    })
+    .await
+}
+
+/// Per-folder conflict counts, skipped files and wasted bytes, bucketed by the first `depth`
+/// (1 or 2, default 1) folders under the mods root and sorted by wasted bytes. Pure over the
+/// given conflicts, so it is instant.
+/// 依 mods 根目錄下前 `depth` 層（1 或 2，預設 1）資料夾統計衝突數、被跳過的檔案數與浪費空間，依浪費空間排序；
+/// 只使用傳入的衝突，因此可立即完成
+#[tauri::command]
+fn conflict_density_by_folder(
+    conflicts: Vec<ModConflict>,
+    depth: Option<usize>,
+) -> Result<Vec<folder_density::FolderStat>, AppError> {
+    let depth = folder_density::parse_depth(depth)?;
+    Ok(folder_density::conflict_density_by_folder(
+        &conflicts, depth,
+    ))
+}
+
+/// Plan which copy of each conflict to keep and which to delete, preferring to delete a
+/// manually installed copy over one in a modpack folder and never deleting a copy in a
+/// priority folder. Each plan carries the scanned size and mtime of its deletions for
+/// `delete_mods`, and in `directory_files` how many files the folders among them hold.
+/// 規劃每個衝突要保留與刪除的檔案；手動安裝與模組包中的檔案衝突時，優先刪除手動安裝的檔案，且不刪除優先資料夾中的檔案；
+/// 每個規劃附帶待刪除檔案掃描時的大小與修改時間，供 `delete_mods` 使用，並於 `directory_files` 列出其中資料夾所含的檔案數
+#[tauri::command]
+async fn plan_conflict_resolution(
+    app: AppHandle,
+    game_path: String,
+    mut conflicts: Vec<ModConflict>,
+) -> Result<Vec<resolution::PlannedResolution>, AppError> {
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let modpacks = modpack_folders(&app, &[&game_path])?;
+    let priority_prefixes = profile_settings(&app, &game_path)?.priority_folder_prefixes;
+    let scanned = app.state::<LastConflicts>().expectations();
+    safe_invoke("plan_conflict_resolution", move || {
+        modpack::mark_managed(&mut conflicts, &modpacks);
+        priority_folders::mark_priority(&mut conflicts, &priority_prefixes);
+        pins::mark(&profile_dir, &game_path, &mut conflicts);
+        Ok(resolution::plan_resolution(&conflicts, &scanned))
+    })
+    .await
+}
+
+/// Trash, quarantine or disable the skipped files of the given conflicts from the last scan,
+/// after checking every file in one pass; returns what was done, what was blocked and why.
+/// Files changed since the scan are blocked as `changed_since_scan` unless `force` is set,
+/// read-only files as `read_only` unless `clear_read_only` is set, and folders as `directory`
+/// unless `allow_directories` is set; `plan_conflict_resolution` tells how many files they
+/// hold. Each conflict is one transaction: when one of its files fails, the others are moved
+/// back and listed in `rolled_back`. When the scanned profile is in safe mode, `trash`
+/// quarantines instead.
+/// With `prune_empty_dirs`, folders that trashed or quarantined files left empty are removed
+/// up to the mods roots and listed in `pruned_dirs`; `prune_junk` is as for `delete_mods`.
+/// 對最近一次掃描中指定衝突的被跳過檔案執行丟到回收桶、隔離或停用；先一次檢查所有檔案，回傳已處理、被阻擋及其原因；
+/// 掃描後已變動的檔案除非設定 `force`，否則以 `changed_since_scan` 阻擋，唯讀檔案除非設定 `clear_read_only`，
+/// 否則以 `read_only` 阻擋，資料夾除非設定 `allow_directories`，否則以 `directory` 阻擋（`plan_conflict_resolution`
+/// 會列出其中的檔案數）；每個衝突為一筆交易，其中一個檔案失敗時
+/// 其他檔案會被移回並列於 `rolled_back`；掃描的 profile 處於安全模式時 `trash` 改為隔離。
+/// 指定 `prune_empty_dirs` 時，刪除因丟到回收桶或隔離而變空的資料夾（直到 mods 根目錄為止）並列於 `pruned_dirs`；
+/// `prune_junk` 同 `delete_mods`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn resolve_conflicts(
+    app: AppHandle,
+    conflict_ids: Vec<String>,
+    action: String,
+    allow_modpack: Option<bool>,
+    force: Option<bool>,
+    clear_read_only: Option<bool>,
+    prune_empty_dirs: Option<bool>,
+    prune_junk: Option<bool>,
+    allow_pinned: Option<bool>,
+    allow_directories: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<resolution::ResolveReport, AppError> {
+    let action = ResolveAction::parse(&action)?;
+    let safe_mode = scanned_safe_mode(&app)?;
+    safe_invoke("resolve_conflicts", move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register("", "resolve", operation_id, false)?;
+        let reporter = ProgressReporter::new(&app, &operation);
+        let options = ResolveOptions {
+            allow_modpack: allow_modpack.unwrap_or(false),
+            force: force.unwrap_or(false),
+            clear_read_only: clear_read_only.unwrap_or(false),
+            prune_empty_dirs: prune_empty_dirs.unwrap_or(false),
+            prune_junk: prune_junk.unwrap_or(false),
+            allow_pinned: allow_pinned.unwrap_or(false),
+            allow_directories: allow_directories.unwrap_or(false),
+            safe_mode,
+        };
+        let result = resolve_selected(
+            &app,
+            &conflict_ids,
+            None,
+            action,
+            &options,
+            &operation,
+            &reporter,
+        );
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Flags shared by `resolve_conflicts` and `resolve_by_rule`
+/// `resolve_conflicts` 與 `resolve_by_rule` 共用的選項
+struct ResolveOptions {
+    allow_modpack: bool,
+    force: bool,
+    clear_read_only: bool,
+    prune_empty_dirs: bool,
+    prune_junk: bool,
+    allow_pinned: bool,
+    allow_directories: bool,
+    safe_mode: bool,
+}
+
+/// Safe mode of the profile the last conflict scan belongs to; off before any scan
+/// 最近一次衝突掃描所屬 profile 的安全模式；尚未掃描時為關閉
+fn scanned_safe_mode(app: &AppHandle) -> Result<bool, AppError> {
+    match app.state::<LastConflicts>().game_path() {
+        Some(game_path) => Ok(profile_settings(app, &game_path)?.safe_mode),
+        None => Ok(false),
+    }
+}
+
+/// Pins of the profile the last conflict scan belongs to, unless `allow_pinned`
+/// 最近一次衝突掃描所屬 profile 的釘選項目；指定 `allow_pinned` 時為 None
+fn scanned_pins(app: &AppHandle, allow_pinned: bool) -> Result<Option<PinSet>, AppError> {
+    match (app.state::<LastConflicts>().game_path(), allow_pinned) {
+        (Some(game_path), false) => Ok(Some(PinSet::load(
+            &profile_data_dir(app, &game_path)?,
+            &game_path,
+        )?)),
+        _ => Ok(None),
+    }
+}
+
+/// Resolve the selected skipped files, then prune emptied folders and record the session
+/// progress and cleanup totals
+/// 處理選取的被跳過檔案，之後清除變空的資料夾並記錄本次進度與清理統計
+fn resolve_selected(
+    app: &AppHandle,
+    conflict_ids: &[String],
+    only: Option<&HashSet<String>>,
+    action: ResolveAction,
+    options: &ResolveOptions,
+    operation: &operations::OperationGuard,
+    reporter: &ProgressReporter,
+) -> Result<resolution::ResolveReport, AppError> {
+    let scanned_game = app.state::<LastConflicts>().game_path();
+    let pins = scanned_pins(app, options.allow_pinned)?;
+    let mut result = resolution::resolve_conflicts(
+        &app.state::<LastConflicts>(),
+        conflict_ids,
+        only,
+        pins.as_ref(),
+        action,
+        options.allow_directories,
+        options.allow_modpack,
+        options.force,
+        options.clear_read_only,
+        options.safe_mode,
+        operation,
+        &|progress| reporter.update(progress),
+    );
+    // Disabled files stay in their folders, which are therefore never empty
+    // 停用的檔案仍留在原資料夾中，因此資料夾不會變空
+    if let (Ok(report), Some(game_path), true) =
+        (&mut result, &scanned_game, options.prune_empty_dirs)
+    {
+        if !matches!(report.action, ResolveAction::Disable) {
+            let removed = report
+                .resolved
+                .iter()
+                .map(|file| Path::new(&file.entry.path))
+                .collect::<Vec<_>>();
+            report.pruned_dirs = empty_dirs::prune_empty_dirs(
+                &removed,
+                &mod_roots::mod_roots(Path::new(game_path)),
+                options.prune_junk,
+            );
+        }
+    }
+    if let Ok(report) = &result {
+        let resolved = report
+            .resolved
+            .iter()
+            .map(|file| file.entry.path.as_str())
+            .collect::<Vec<_>>();
+        app.state::<SessionProgress>().record(&resolved, report.action);
+        if matches!(report.action, ResolveAction::Trash) {
+            record_cleanup(app, report.bytes_reclaimed, report.resolved.len());
+        }
+    }
+    result
+}
+
+/// Pick skipped files of the last scan by a rule instead of by conflict, e.g. every skipped
+/// file under `mods/old` older than a year that is identical to its loaded copy, and trash,
+/// quarantine or disable them. Without `confirm` nothing is touched and the matching files are
+/// returned with a `plan_hash`; with `confirm` and that hash the rule is evaluated again and
+/// carried out only if it still matches exactly the same files, through the same checks as
+/// `resolve_conflicts`. Other arguments are as for `resolve_conflicts`.
+/// 以規則而非衝突挑選最近一次掃描中的被跳過檔案，例如 `mods/old` 下超過一年且與 loaded 檔案完全相同者，
+/// 並丟到回收桶、隔離或停用；未指定 `confirm` 時不做任何變更，只回傳符合的檔案與 `plan_hash`；
+/// 指定 `confirm` 與該雜湊時重新評估規則，僅在符合的檔案完全相同時以與 `resolve_conflicts` 相同的檢查執行；
+/// 其他參數同 `resolve_conflicts`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn resolve_by_rule(
+    app: AppHandle,
+    rule: resolve_rules::ResolveRule,
+    confirm: Option<bool>,
+    plan_hash: Option<String>,
+    allow_modpack: Option<bool>,
+    force: Option<bool>,
+    clear_read_only: Option<bool>,
+    prune_empty_dirs: Option<bool>,
+    prune_junk: Option<bool>,
+    allow_pinned: Option<bool>,
+    allow_directories: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<resolve_rules::RuleOutcome, AppError> {
+    let (game_path, conflicts) =
+        app.state::<LastConflicts>()
+            .select(None)
+            .ok_or_else(|| AppError::InvalidInput {
+                message: Message::new("input.no_conflict_scan"),
+            })?;
+    let index_path = profile_data_dir(&app, &game_path)?.join("asset_index.json");
+    let safe_mode = scanned_safe_mode(&app)?;
+    safe_invoke("resolve_by_rule", move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register("", "resolve", operation_id, false)?;
+        let reporter = ProgressReporter::new(&app, &operation);
+        let result = (|| {
+            let pins = scanned_pins(&app, allow_pinned.unwrap_or(false))?;
+            let plan = resolve_rules::evaluate(
+                &rule,
+                &game_path,
+                &conflicts,
+                pins.as_ref(),
+                &index_path,
+                &operation.token,
+            )?;
+            if !confirm.unwrap_or(false) {
+                return Ok(resolve_rules::RuleOutcome::Preview { plan });
+            }
+            resolve_rules::ensure_confirmed(&plan, plan_hash.as_deref())?;
+            let mut conflict_ids = Vec::new();
+            for found in &plan.matches {
+                if !conflict_ids.contains(&found.conflict_id) {
+                    conflict_ids.push(found.conflict_id.clone());
+                }
+            }
+            let only = plan
+                .matches
+                .iter()
+                .map(|found| found.entry.path.to_lowercase())
+                .collect::<HashSet<_>>();
+            let options = ResolveOptions {
+                allow_modpack: allow_modpack.unwrap_or(false),
+                force: force.unwrap_or(false),
+                clear_read_only: clear_read_only.unwrap_or(false),
+                prune_empty_dirs: prune_empty_dirs.unwrap_or(false),
+                prune_junk: prune_junk.unwrap_or(false),
+                allow_pinned: allow_pinned.unwrap_or(false),
+                allow_directories: allow_directories.unwrap_or(false),
+                safe_mode,
+            };
+            let report = resolve_selected(
+                &app,
+                &conflict_ids,
+                Some(&only),
+                plan.action,
+                &options,
+                &operation,
+                &reporter,
+            )?;
+            Ok(resolve_rules::RuleOutcome::Executed { plan, report })
+        })();
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Bytes and files sent to the Recycle Bin through this app, since it was first used and since
+/// it was started, with the time of the last such operation. Quarantined and disabled files
+/// are still on the disk and do not count.
+/// 透過本程式丟到回收桶的位元組數與檔案數，分為首次使用以來與本次啟動以來，並附最後一次操作的時間；
+/// 隔離與停用的檔案仍在磁碟上，不計入
+#[tauri::command]
+fn get_cleanup_stats(app: AppHandle) -> Result<cleanup_stats::CleanupStats, AppError> {
+    Ok(app
+        .state::<CleanupCounters>()
+        .stats(&cleanup_stats_path(&app)))
+}
+
+/// Forget the first-seen dates of a profile, e.g. after moving files around on purpose; the
+/// next scans record every file as newly seen
+/// 清除 profile 的首次發現日期，例如刻意搬動檔案之後；之後的掃描會將每個檔案記為新發現
+#[tauri::command]
+fn rebuild_first_seen_index(app: AppHandle, game_path: String) -> Result<(), AppError> {
+    first_seen::rebuild(&profile_data_dir(&app, &game_path)?)
+}
+
+/// Reclaim the free space of a registered profile's index database (VACUUM) and report its
+/// size before and after; a profile still on the JSON indices is left as is
+/// 回收已登記 profile 索引資料庫的閒置空間（VACUUM）並回報前後的大小；仍使用 JSON 索引的 profile 不做處理
+#[tauri::command]
+async fn compact_index(
+    app: AppHandle,
+    profile_id: String,
+) -> Result<index_db::CompactReport, AppError> {
+    let game_path = profile_game_path(&app, &profile_id)?;
+    let dir = profile_data_dir(&app, &game_path)?;
+    safe_invoke("compact_index", move || index_db::compact(&dir)).await
+}
+
+/// Which conflicts of the last scans were resolved through this app during the session:
+/// resolved/total counts and each conflict's handled files. Uses the most recently scanned
+/// profile unless `game_path` is given; re-parsing the same log keeps the progress.
+/// 本次工作階段中，最近掃描的衝突有哪些已透過本程式處理：已處理／總數與各衝突已處理的檔案；
+/// 未指定 `game_path` 時使用最近掃描的 profile；重新解析同一份 log 不會遺失進度
+#[tauri::command]
+fn get_session_progress(
+    session: State<SessionProgress>,
+    game_path: Option<String>,
+) -> Result<session_progress::SessionReport, AppError> {
+    session.report(game_path.as_deref())
+}
+
+/// Clear the session progress of `game_path`, or of every profile; returns false if nothing
+/// was tracked
+/// 清除 `game_path`（或所有 profile）的工作階段進度；若沒有追蹤中的內容則回傳 false
+#[tauri::command]
+fn reset_session_progress(session: State<SessionProgress>, game_path: Option<String>) -> bool {
+    session.reset(game_path.as_deref())
+}
+
+/// Save the user's note on a conflict of a registered profile, keyed by the stable conflict
+/// id; a blank note removes it. Notes are returned on the conflicts of later scans.
+/// 儲存使用者對已登記 profile 中某衝突的筆記，以穩定的衝突 id 為 key；空白筆記會將其移除；之後的掃描結果會附上筆記
+#[tauri::command]
+async fn set_conflict_note(
+    app: AppHandle,
+    profile_id: String,
+    conflict_id: String,
+    note: String,
+) -> Result<(), AppError> {
+    let dir = profile_data_dir(&app, &profile_game_path(&app, &profile_id)?)?;
+    safe_invoke("set_conflict_note", move || {
+        conflict_notes::set_note(&dir, &conflict_id, &note)
+    })
+    .await
+}
+
+/// Every note of a registered profile; notes of conflicts the latest scan no longer lists
+/// are returned separately as `orphaned`
+/// 取得已登記 profile 的所有筆記；最近一次掃描已不再列出的衝突之筆記另列為 `orphaned`
+#[tauri::command]
+async fn get_conflict_notes(
+    app: AppHandle,
+    profile_id: String,
+) -> Result<conflict_notes::ConflictNotes, AppError> {
+    let game_path = profile_game_path(&app, &profile_id)?;
+    let dir = profile_data_dir(&app, &game_path)?;
+    let current = app.state::<LastConflicts>().conflict_ids(&game_path);
+    safe_invoke("get_conflict_notes", move || {
+        let current = current.or_else(|| {
+            scan_cache::load_latest(&dir.join("scan_cache.json"))
+                .map(|conflicts| conflicts.into_iter().map(|conflict| conflict.id).collect())
+        });
+        conflict_notes::list(&dir, current.as_ref())
+    })
+    .await
+}
+
+/// Pin files of a registered profile, such as a hand-edited mod, so that `delete_mods`,
+/// `resolve_conflicts`, `resolve_by_rule` and staged installs leave them alone unless told
+/// `allow_pinned`, and plans list them as pinned instead of deleting them. Pins are stored by
+/// path relative to the game folder with the file's hash, so a pinned file keeps its pin when
+/// renamed. Returns every pin of the profile.
+/// 釘選已登記 profile 中的檔案（例如手動修改過的模組），除非指定 `allow_pinned`，否則 `delete_mods`、
+/// `resolve_conflicts`、`resolve_by_rule` 與分段安裝都不會處理，規劃則將其列為已釘選而不刪除；
+/// 釘選項目以相對於遊戲資料夾的路徑與檔案雜湊儲存，因此改名後仍保持釘選；回傳 profile 的所有釘選項目
+#[tauri::command]
+async fn pin_files(
+    app: AppHandle,
+    profile_id: String,
+    paths: Vec<String>,
+) -> Result<Vec<pins::PinnedFile>, AppError> {
+    let game_path = profile_game_path(&app, &profile_id)?;
+    let dir = profile_data_dir(&app, &game_path)?;
+    safe_invoke("pin_files", move || pins::pin(&dir, &game_path, &paths)).await
+}
+
+/// Remove the pins of `paths` in a registered profile; returns the pins left
+/// 移除已登記 profile 中 `paths` 的釘選；回傳剩餘的釘選項目
+#[tauri::command]
+async fn unpin_files(
+    app: AppHandle,
+    profile_id: String,
+    paths: Vec<String>,
+) -> Result<Vec<pins::PinnedFile>, AppError> {
+    let game_path = profile_game_path(&app, &profile_id)?;
+    let dir = profile_data_dir(&app, &game_path)?;
+    safe_invoke("unpin_files", move || pins::unpin(&dir, &game_path, &paths)).await
+}
+
+/// Every pin of a registered profile, with whether its file is still at the pinned path
+/// 已登記 profile 的所有釘選項目，以及其檔案是否仍在釘選時的路徑
+#[tauri::command]
+async fn get_pinned(
+    app: AppHandle,
+    profile_id: String,
+) -> Result<Vec<pins::PinStatus>, AppError> {
+    let game_path = profile_game_path(&app, &profile_id)?;
+    let dir = profile_data_dir(&app, &game_path)?;
+    safe_invoke("get_pinned", move || pins::list(&dir, &game_path)).await
+}
+
+/// Compare the game's modpack folders against a manifest exported from KKManager (XML or
+/// JSON, read from disk) and list missing files, unlisted extra files and size mismatches,
+/// which tell whether modpack conflicts come from manual changes to those folders
+/// 將遊戲的模組包資料夾與由 KKManager 匯出的 manifest（XML 或 JSON，自磁碟讀取）比對，列出缺少的檔案、
+/// 未列出的多餘檔案與大小不符的檔案，藉此判斷模組包內的衝突是否源自手動修改
+#[tauri::command]
+async fn check_modpack_integrity(
+    app: AppHandle,
+    game_path: String,
+    manifest_source: String,
+) -> Result<modpack_integrity::IntegrityReport, AppError> {
+    let follow_links = profile_settings(&app, &game_path)?.follow_links;
+    safe_invoke("check_modpack_integrity", move || {
+        modpack_integrity::check_integrity(&game_path, Path::new(&manifest_source), follow_links)
+    })
+    .await
+}
+
+/// Plan installing every zipmod and zip under `download_dir` without touching the mods folder:
+/// each file is matched by manifest GUID against the mods folder's GUID index and classified as
+/// new install, upgrade, downgrade, same version, exact duplicate, superseded by another download,
+/// or unreadable. Reports on `operation-progress` with kind `stage`.
+/// An earlier `stage` of the same profile is cancelled unless `cancel_previous` is false.
+/// 規劃安裝 `download_dir` 底下每個 zipmod 與 zip，不動到 mods 資料夾：以 manifest GUID 對照 mods 資料夾的 GUID 索引，
+/// 分類為新安裝、升級、降級、相同版本、完全重複、被其他下載檔取代或無法讀取；進度以 `stage` 類型送出；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `stage` 作業
+#[tauri::command]
+async fn stage_installs(
+    app: AppHandle,
+    download_dir: String,
+    game_path: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<Vec<stage_installs::StagePlan>, AppError> {
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let index_path = profile_dir.join("asset_index.json");
+    let settings = profile_settings(&app, &game_path)?;
+    safe_invoke("stage_installs", move || {
+        let pins = PinSet::load(&profile_dir, &game_path)?;
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "stage",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = mods_scan::scan_mods_folder(
+            &index_path,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+            &operation.token,
+            &|progress| reporter.update(progress),
+        )
+        .and_then(|installed| {
+            stage_installs::stage_installs(
+                Path::new(&download_dir),
+                Path::new(&game_path),
+                installed,
+                &settings.mod_extensions,
+                &pins,
+                &operation.token,
+                &|progress| reporter.update(progress),
+            )
+        });
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Apply plans from `stage_installs`, copying downloads into the mods folder and moving each
+/// replaced copy to the Recycle Bin. Blocked plans (downgrades, same-version replacements)
+/// need `allow_blocked`, and plans replacing a pinned copy `allow_pinned`. Fails with
+/// `path_busy` while another operation works on a source, replaced copy or destination.
+/// Returns one result per plan; reports with kind `stage-apply`.
+/// 套用 `stage_installs` 的規劃，將下載檔複製到 mods 資料夾並將被取代的檔案移到回收桶；被阻擋的規劃
+/// （降級、相同版本取代）需指定 `allow_blocked`，取代已釘選檔案的規劃需指定 `allow_pinned`；來源、被取代檔案或目的地正由其他作業處理時回傳 `path_busy`；每個規劃各回傳一筆結果，進度以 `stage-apply` 類型送出
+#[tauri::command]
+async fn apply_stage_plan(
+    app: AppHandle,
+    game_path: String,
+    plan: Vec<stage_installs::StagePlan>,
+    allow_blocked: Option<bool>,
+    allow_pinned: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<stage_installs::StageReport, AppError> {
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    safe_invoke("apply_stage_plan", move || {
+        let pins = match allow_pinned.unwrap_or(false) {
+            true => None,
+            false => Some(PinSet::load(&profile_dir, &game_path)?),
+        };
+        let operations = app.state::<Operations>();
+        let operation =
+            operations.register(&profile_id(&game_path), "stage-apply", operation_id, false)?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let touched = plan
+            .iter()
+            .flat_map(|plan| {
+                iter::once(&plan.source).chain(&plan.existing_path).chain(&plan.destination)
+            })
+            .collect::<Vec<_>>();
+        let result = operation.lock_paths(&touched).and_then(|()| {
+            stage_installs::apply_stage_plan(
+                Path::new(&game_path),
+                &plan,
+                allow_blocked.unwrap_or(false),
+                pins.as_ref(),
+                operation.write_mode,
+                &operation.token,
+                &|progress| reporter.update(progress),
+            )
+        });
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Pre-flight checklist for an install, run before the first scan so permission and
+/// environment problems show up with a reason instead of as a silent failure
+/// 安裝環境的預檢清單；在第一次掃描前執行，讓權限與環境問題以明確原因呈現，而非無聲失敗
+#[tauri::command]
+async fn run_self_check(
+    app: AppHandle,
+    game_path: String,
+) -> Result<Vec<self_check::CheckResult>, AppError> {
+    let write_mode = app.state::<Operations>().write_mode();
+    safe_invoke("run_self_check", move || {
+        Ok(self_check::run_self_check(Path::new(&game_path), write_mode))
+    })
+    .await
+}
+
+/// Mod archives under the mods folders modified in the last `since_days` days, newest first,
+/// flagged as managed, disabled or in a priority folder, with manifests from the asset index
+/// when it is warm. The walk uses the profile's `follow_links` and `mod_extensions`; results
+/// are capped and `truncated` tells when a mass update went over the cap.
+/// 列出 mods 資料夾下最近 `since_days` 天內修改過的模組壓縮檔，由新到舊，標示是否受管理、已停用或位於優先資料夾，
+/// 資源索引有效時附上 manifest；依 profile 的 `follow_links` 與 `mod_extensions` 走訪，結果設有上限，
+/// 大量更新超過上限時 `truncated` 為 true
+#[tauri::command]
+async fn recently_changed_mods(
+    app: AppHandle,
+    game_path: String,
+    since_days: u32,
+) -> Result<recent_changes::RecentChanges, AppError> {
+    let index_path = profile_data_dir(&app, &game_path)?.join("asset_index.json");
+    let settings = profile_settings(&app, &game_path)?;
+    let modpacks = modpack_folders(&app, &[&game_path])?;
+    safe_invoke("recently_changed_mods", move || {
+        Ok(recent_changes::recently_changed_mods(
+            Path::new(&game_path),
+            since_days,
+            &index_path,
+            settings.follow_links,
+            &settings.mod_extensions,
+            &modpacks,
+            &settings.priority_folder_prefixes,
+        ))
+    })
+    .await
+}
+
+/// Folders Sideloader loads mods from, with the resolved target when one is a symlink or
+/// junction and the volume its files are on, so the UI can show where space is freed
+/// Sideloader 載入模組的資料夾；為 symlink 或 junction 時附上解析後的目標與檔案所在磁碟區，讓介面顯示空間實際在哪裡釋放
+#[tauri::command]
+async fn get_mod_directories(
+    game_path: String,
+) -> Result<Vec<mod_roots::ModDirectory>, AppError> {
+    safe_invoke("get_mod_directories", move || {
+        Ok(mod_roots::mod_directories(Path::new(&game_path)))
+    })
+    .await
+}
+
+/// Message keys and their default English templates, for the frontend's translation table
+/// 訊息 key 與預設英文樣板，供前端建立翻譯表
+#[tauri::command]
+fn get_message_catalog() -> BTreeMap<&'static str, &'static str> {
+    messages::MESSAGE_CATALOG.iter().copied().collect()
+}
+
+/// Full text of a panic report, for the UI's "copy error details" button
+/// 取得 panic 報告全文，供前端「複製錯誤詳細資訊」使用
+#[tauri::command]
+fn get_incident_report(incident_id: String) -> Result<String, AppError> {
+    incidents::report(&incident_id).ok_or_else(|| AppError::InvalidInput {
+        message: Message::new("input.unknown_incident").with("incident_id", &incident_id),
+    })
+}
+
+/// Resolve the app log directory
+/// 取得 app log 目錄
+fn app_log_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
+        .app_log_dir()
+        .map_err(|e| AppError::io("io.resolve_app_log_dir", None, e))
+}
+
+/// Directory holding the app's own log files
+/// 應用程式自身 log 檔所在目錄
+#[tauri::command]
+fn get_app_log_dir(app: AppHandle) -> Result<String, AppError> {
+    Ok(long_path::display(&app_log_dir(&app)?))
+}
+
+/// Last `lines` lines of the active app log, for the in-app log viewer
+/// 使用中 app log 的最後 `lines` 行，供程式內的 log 檢視畫面使用
+#[tauri::command]
+async fn read_recent_app_log(app: AppHandle, lines: u32) -> Result<Vec<String>, AppError> {
+    let dir = app_log_dir(&app)?;
+    safe_invoke("read_recent_app_log", move || app_log::read_recent(&dir, lines)).await
+}
+
+/// Delete rotated app logs beyond `keep_files` or older than `keep_days`; never the active file
+/// 刪除超過 `keep_files` 個或 `keep_days` 天的輪替 app log；不會刪除使用中的檔案
+#[tauri::command]
+async fn cleanup_app_logs(
+    app: AppHandle,
+    keep_files: u32,
+    keep_days: u32,
+) -> Result<app_log::CleanupResult, AppError> {
+    let dir = app_log_dir(&app)?;
+    safe_invoke("cleanup_app_logs", move || {
+        app_log::cleanup(&dir, keep_files, keep_days)
+    })
+    .await
+}
+
+/// Zip app logs, the last scan, environment checks and a game log excerpt for bug reports.
+/// Returns the created zip path so the frontend can reveal it.
+/// 打包 app log、最近一次掃描、環境檢查與遊戲 log 摘錄供回報問題；回傳 zip 路徑供前端開啟
+#[tauri::command]
+async fn create_diagnostics_bundle(
+    app: AppHandle,
+    game_path: String,
+    destination: String,
+) -> Result<String, AppError> {
+    let app_log_dir = app_log_dir(&app)?;
+    let scan_cache = profile_data_dir(&app, &game_path)?.join("scan_cache.json");
+    let home_dir = app.path().home_dir().ok();
+    let session = app.state::<SessionProgress>().report(Some(&game_path)).ok();
+    let storage = app.state::<Storage>().status().clone();
+    let write_mode = app.state::<Operations>().write_mode();
+    safe_invoke("create_diagnostics_bundle", move || {
+        let sources = diagnostics::BundleSources {
+            game_path: Path::new(&game_path),
+            app_log_dir: &app_log_dir,
+            scan_cache: &scan_cache,
+            home_dir: home_dir.as_deref(),
+            session: session.as_ref(),
+            storage: &storage,
+            write_mode,
+        };
+        let zip_path = diagnostics::create_bundle(&sources, Path::new(&destination))?;
+        Ok(long_path::display(&zip_path))
+    })
+    .await
+}
+
+/// Short plain-text summary of the install to paste into a help request: app version, game
+/// variant, mods, modpack folders, top conflicts and log age, without absolute paths or the
+/// user name. Uses the asset index and scan cache when they exist; see `support_fingerprint`.
+/// 求助時貼上的安裝簡短純文字摘要：程式版本、遊戲版本、模組、模組包資料夾、主要衝突與 log 經過時間，不含絕對路徑或使用者名稱；
+/// 資源索引與掃描快取存在時取用之，見 `support_fingerprint`
+#[tauri::command]
+async fn generate_support_fingerprint(
+    app: AppHandle,
+    game_path: String,
+) -> Result<String, AppError> {
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let settings = profile_settings(&app, &game_path)?;
+    safe_invoke("generate_support_fingerprint", move || {
+        Ok(support_fingerprint::generate(
+            &support_fingerprint::FingerprintSources {
+                game_path: &game_path,
+                scan_cache_path: &profile_dir.join("scan_cache.json"),
+                index_path: &profile_dir.join("asset_index.json"),
+                modpack_prefixes: &settings.modpack_prefixes,
+                follow_links: settings.follow_links,
+                mod_extensions: &settings.mod_extensions,
+            },
+        ))
+    })
+    .await
+}
+
+/// Find which zipmods provide an asset path (exact or partial suffix)
+/// 查詢哪些 zipmod 提供指定的資源路徑（完整或部分路徑）
+#[tauri::command]
+async fn find_asset_provider(
+    app: AppHandle,
+    game_path: String,
+    asset_path: String,
+) -> Result<Vec<asset_index::AssetProvider>, AppError> {
+    let index_path = profile_data_dir(&app, &game_path)?.join("asset_index.json");
+    let settings = profile_settings(&app, &game_path)?;
+    safe_invoke("find_asset_provider", move || {
+        asset_index::find_asset_provider(
+            &index_path,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+            &asset_path,
+        )
+    })
+    .await
+}
+
+/// Build or incrementally update the asset index.
+/// Reports on `operation-progress` with kind `asset-index`; `cancel_previous` cancels an
+/// earlier build for the same profile.
+/// 建立或增量更新資源索引；進度以 `asset-index` 類型送出，`cancel_previous` 會取消同 profile 的舊作業
+#[tauri::command]
+async fn build_asset_index(
+    app: AppHandle,
+    game_path: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+    include_timings: Option<bool>,
+) -> Result<asset_index::AssetIndexSummary, AppError> {
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let index_path = profile_dir.join("asset_index.json");
+    let settings = profile_settings(&app, &game_path)?;
+    let describe = |summary: &asset_index::AssetIndexSummary| {
+        format!(
+            "{} mods indexed, {} re-read, {} entries",
+            summary.mods_indexed, summary.mods_reread, summary.entries
+        )
+    };
+    safe_invoke_measured("build_asset_index", describe, move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "asset-index",
+            operation_id,
+            cancel_previous.unwrap_or(false),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let started = Instant::now();
+        let result = asset_index::build_asset_index(
+            &index_path,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+            &operation.token,
+            &|progress| reporter.update(progress),
+            Timings::new(include_timings.unwrap_or(false)),
+        );
+        if result.is_ok() {
+            record_candidate_run(
+                &profile_dir,
+                EstimateKind::AssetIndex,
+                &game_path,
+                &settings,
+                started,
+            );
+        }
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Settings of a profile (e.g. whether to follow links in the mods tree)
+/// 取得 profile 設定（例如掃描 mods 目錄樹時是否跟隨連結）
+#[tauri::command]
+fn get_profile_settings(app: AppHandle, game_path: String) -> Result<ProfileSettings, AppError> {
+    profile_settings(&app, &game_path)
+}
+
+/// Save the settings of a profile; turning safe mode on or off is written to the app log
+/// 儲存 profile 設定；開啟或關閉安全模式會寫入應用程式 log
+#[tauri::command]
+fn set_profile_settings(
+    app: AppHandle,
+    game_path: String,
+    settings: ProfileSettings,
+) -> Result<(), AppError> {
+    let root = profiles_root(&app);
+    let safe_mode = settings.safe_mode;
+    let was_safe_mode = profiles::load_settings(&root, &game_path).safe_mode;
+    profiles::save_settings(&root, &game_path, settings)?;
+    if safe_mode != was_safe_mode {
+        app_log::write(&format!(
+            "safe mode turned {} for {}",
+            if safe_mode { "on" } else { "off" },
+            game_path
+        ));
+    }
+    Ok(())
+}
+
+/// Settings shared by every profile (e.g. read-only mode)
+/// 取得所有 profile 共用的設定（例如唯讀模式）
+#[tauri::command]
+fn get_app_settings(app: AppHandle) -> Result<AppSettings, AppError> {
+    Ok(profiles::load_app_settings(&profiles_root(&app)))
+}
+
+/// Save the app-wide settings and apply read-only mode at once; turning it on or off is
+/// written to the app log. While it is on, every command that would change game files fails
+/// with `read_only_mode` before touching anything; scans, exports and diagnostics still run.
+/// `hydrate_cloud_files` also applies at once.
+/// 儲存全域設定並立即套用唯讀模式；開啟或關閉會寫入應用程式 log。開啟期間所有會變更遊戲檔案的指令
+/// 都會在變更任何內容前以 `read_only_mode` 失敗；掃描、匯出與診斷仍可執行；`hydrate_cloud_files` 亦立即套用
+#[tauri::command]
+fn set_app_settings(app: AppHandle, settings: AppSettings) -> Result<(), AppError> {
+    let write_mode = WriteMode::from_read_only(settings.read_only_mode);
+    profiles::save_app_settings(&profiles_root(&app), &settings)?;
+    let operations = app.state::<Operations>();
+    if write_mode != operations.write_mode() {
+        app_log::write(&format!(
+            "read-only mode turned {}",
+            if write_mode.is_read_only() { "on" } else { "off" }
+        ));
+    }
+    operations.set_write_mode(write_mode);
+    cloud_files::set_hydrate_placeholders(settings.hydrate_cloud_files);
+    Ok(())
+}
+
+/// Read part of a result file that a heavy command returned as `{ stored_at, bytes, summary }`
+/// because its JSON exceeded `max_response_bytes`. Start at `offset` 0 and continue from
+/// `next_offset` until `done`; joined, the chunks are the JSON the command would have returned.
+/// `limit` defaults to 1 MiB and chunks never split a character. Only files in the app's
+/// results folder can be read.
+/// 讀取耗時指令因 JSON 超過 `max_response_bytes` 而以 `{ stored_at, bytes, summary }` 回傳的結果檔的一部分；
+/// 從 `offset` 0 開始，依 `next_offset` 繼續讀取直到 `done`，串接後即為指令原本會回傳的 JSON；
+/// `limit` 預設為 1 MiB，且區塊不會切開字元；只能讀取本程式結果資料夾中的檔案
+#[tauri::command]
+async fn read_result_chunk(
+    app: AppHandle,
+    path: String,
+    offset: u64,
+    limit: Option<u64>,
+) -> Result<large_results::ResultChunk, AppError> {
+    let dir = results_dir(&app);
+    safe_invoke("read_result_chunk", move || {
+        large_results::read_chunk(&dir, &path, offset, limit)
+    })
+    .await
+}
+
+/// Delete a result file once it has been read; false when it is already gone. Whatever is
+/// left is deleted when the app exits.
+/// 讀取完畢後刪除結果檔；已不存在時回傳 false；剩下的結果檔會在程式結束時刪除
+#[tauri::command]
+fn discard_result(app: AppHandle, path: String) -> Result<bool, AppError> {
+    large_results::discard(&results_dir(&app), &path)
+}
+
+/// Watch every mod root (plus `extra_dirs`) and emit debounced `mods-changed` events.
+/// Replaces any watch that is already running.
+/// 監看所有模組根目錄（與 `extra_dirs`），去抖動後送出 `mods-changed` 事件；會取代既有的監看
+#[tauri::command]
+fn start_mods_watch(
+    app: AppHandle,
+    active: State<ActiveWatch>,
+    game_path: String,
+    extra_dirs: Option<Vec<String>>,
+) -> Result<(), AppError> {
+    let mut watch = active.0.lock().unwrap();
+    // Drop the old watch first so its thread never emits for the new profile
+    // 先停止舊的監看，避免其執行緒替新 profile 送出事件
+    *watch = None;
+
+    let emit_path = game_path.clone();
+    *watch = Some(mods_watch::start(
+        &game_path,
+        &extra_dirs.unwrap_or_default(),
+        move |changes| {
+            let _ = app.emit(
+                "mods-changed",
+                ModsChanged {
+                    game_path: emit_path.clone(),
+                    changes,
+                },
+            );
+            // The background refresh skips polling a watched mods folder and waits for this
+            // 背景更新不輪詢已監看的 mods 資料夾，改由此處通知
+            if let Some(refresh) = app.state::<ActiveRefresh>().0.lock().unwrap().as_ref() {
+                if profile_id(&refresh.game_path) == profile_id(&emit_path) {
+                    refresh.nudge();
+                }
+            }
+        },
+    )?);
+    Ok(())
+}
+
+/// Stop the mods watch; returns false if none was running
+/// 停止 mods 監看；若沒有進行中的監看則回傳 false
+#[tauri::command]
+fn stop_mods_watch(active: State<ActiveWatch>) -> bool {
+    active.0.lock().unwrap().take().is_some()
+}
+
+/// Poll the profile's log and mods folder every `interval_secs` (at least 2) using mtimes
+/// only, re-scan when something changed, and emit `conflict-count-changed` with the new
+/// totals while showing the count in the window title. A running mods watch of the same
+/// profile replaces polling of the mods folder; while the game runs, polling backs off and
+/// re-scans wait until it exits. Replaces any refresh that is already running.
+/// 每隔 `interval_secs`（至少 2 秒）僅以修改時間檢查 profile 的 log 與 mods 資料夾，有變動時重新掃描，
+/// 並送出含新統計的 `conflict-count-changed` 事件，同時於視窗標題顯示衝突數；同 profile 的 mods 監看執行中時
+/// 改由監看通知 mods 變動；遊戲執行中會放慢輪詢並延後到遊戲結束才重新掃描；會取代既有的背景更新
+#[tauri::command]
+async fn start_background_refresh(
+    app: AppHandle,
+    profile_id: String,
+    interval_secs: u64,
+) -> Result<(), AppError> {
+    let game_path = profile_game_path(&app, &profile_id)?;
+    let cache_path = profile_data_dir(&app, &game_path)?.join("scan_cache.json");
+    let mod_extensions = profile_settings(&app, &game_path)?.mod_extensions;
+    safe_invoke("start_background_refresh", move || {
+        // Stop the old refresh outside the lock; dropping it waits for its thread
+        // 在鎖外停止舊的背景更新；drop 時會等待其執行緒結束
+        let old = app.state::<ActiveRefresh>().0.lock().unwrap().take();
+        drop(old);
+
+        let watch_app = app.clone();
+        let watch_profile = profile_id.clone();
+        let mods_watched = move || {
+            watch_app
+                .state::<ActiveWatch>()
+                .0
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|watch| profiles::profile_id(&watch.game_path) == watch_profile)
+        };
+
+        let emit_app = app.clone();
+        let emit_path = game_path.clone();
+        let mut last: Option<ScanSummary> = None;
+        let refresh = move || {
+            let scan = scan::scan_conflicts_cached(
+                &cache_path,
+                &emit_path,
+                &mod_extensions,
+                false,
+                &|_| {},
+                Timings::new(false),
+            );
+            match scan {
+                Ok(scan) if last.as_ref() != Some(&scan.summary) => {
+                    set_conflict_title(&emit_app, Some(scan.summary.conflicts));
+                    let _ = emit_app.emit(
+                        "conflict-count-changed",
+                        ConflictCountChanged {
+                            profile_id: profile_id.clone(),
+                            game_path: emit_path.clone(),
+                            summary: scan.summary.clone(),
+                        },
+                    );
+                    last = Some(scan.summary);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    app_log::write(&format!("background refresh failed (continuing): {}", e))
+                }
+            }
+        };
+
+        let started = background_refresh::start(
+            &game_path,
+            Duration::from_secs(interval_secs),
+            mods_watched,
+            refresh,
+        )?;
+        *app.state::<ActiveRefresh>().0.lock().unwrap() = Some(started);
+        Ok(())
+    })
+    .await
+}
+
+/// Stop the background refresh and restore the window title; returns false if none was running
+/// 停止背景更新並還原視窗標題；若沒有進行中的背景更新則回傳 false
+#[tauri::command]
+async fn stop_background_refresh(app: AppHandle) -> Result<bool, AppError> {
+    safe_invoke("stop_background_refresh", move || {
+        let stopped = app.state::<ActiveRefresh>().0.lock().unwrap().take();
+        let was_running = stopped.is_some();
+        drop(stopped);
+        if was_running {
+            set_conflict_title(&app, None);
+        }
+        Ok(was_running)
+    })
+    .await
+}
+
+/// Request cancellation of a running operation by id; returns false if it already finished
+/// 依 id 要求取消進行中的作業；若作業已結束則回傳 false
+#[tauri::command]
+fn cancel_operation(operations: State<Operations>, operation_id: String) -> bool {
+    operations.cancel(&operation_id)
+}
+
+/// Report abdata paths provided by two or more distinct GUIDs
+/// 回報被兩個以上不同 GUID 提供的 abdata 路徑
+#[tauri::command]
+async fn find_global_asset_collisions(
+    app: AppHandle,
+    game_path: String,
+) -> Result<Vec<asset_index::AssetCollision>, AppError> {
+    let index_path = profile_data_dir(&app, &game_path)?.join("asset_index.json");
+    safe_invoke("find_global_asset_collisions", move || {
+        Ok(asset_index::find_global_asset_collisions(&index_path))
+    })
+    .await
+}
+
+/// Scan BepInEx/plugins for DLLs sharing a filename and compare their versions
+/// 掃描 BepInEx/plugins 中同名的 DLL 並比較版本
+#[tauri::command]
+async fn scan_plugin_duplicates(
+    app: AppHandle,
+    game_path: String,
+) -> Result<Vec<plugins::PluginConflict>, AppError> {
+    let settings = profile_settings(&app, &game_path)?;
+    safe_invoke("scan_plugin_duplicates", move || {
+        Ok(plugins::scan_plugin_duplicates(
+            Path::new(&game_path),
+            settings.follow_links,
+        ))
+    })
+    .await
+}
+
+/// Read BepInEx plugin name/GUID/version from a DLL
+/// 從 DLL 讀取 BepInEx 插件的名稱、GUID 與版本
+#[tauri::command]
+async fn read_plugin_info(path: String) -> Result<plugins::PluginInfo, AppError> {
+    safe_invoke("read_plugin_info", move || {
+        plugins::read_plugin_info(Path::new(&path))
+    })
+    .await
+}
+
+/// Scan the mods folder offline as a cancellable operation of `kind`, keep the result in
+/// `ScanStore` under the operation id and return its first page.
+/// `cancel_previous` (default true) cancels an earlier scan of `kind` for the profile.
+/// 以 `kind` 類型的可取消作業離線掃描 mods 資料夾，將結果以作業 id 存入 `ScanStore` 並回傳第一頁；
+/// `cancel_previous`（預設為 true）會取消同 profile 先前的 `kind` 掃描
+#[allow(clippy::too_many_arguments)]
+fn run_stored_scan(
+    app: &AppHandle,
+    game_path: &str,
+    kind: &'static str,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    sort_by: SortBy,
+    build: impl FnOnce(Vec<ModFile>) -> StoredScan,
+) -> Result<Page, AppError> {
+    let profile_dir = profile_data_dir(app, game_path)?;
+    let index_path = profile_dir.join("asset_index.json");
+    let settings = profile_settings(app, game_path)?;
+    let operations = app.state::<Operations>();
+    let operation = operations.register(
+        &profile_id(game_path),
+        kind,
+        operation_id,
+        cancel_previous.unwrap_or(true),
+    )?;
+    let reporter = ProgressReporter::new(app, &operation);
+
+    let result = mods_scan::scan_mods_folder(
+        &index_path,
+        Path::new(game_path),
+        settings.follow_links,
+        &settings.mod_extensions,
+        &operation.token,
+        &|progress| reporter.update(progress),
+    );
+    reporter.finish(result.as_ref().err());
+    let mut files = result?;
+    priority_folders::mark_priority_entries(
+        files.iter_mut().map(|file| &mut file.entry),
+        &settings.priority_folder_prefixes,
+    );
+    first_seen::observe(
+        &profile_dir,
+        game_path,
+        files.iter_mut().map(|file| &mut file.entry),
+        first_seen::Coverage::Full,
+    );
+
+    let scan = app.state::<ScanStore>().insert(&operation.id, build(files));
+    Ok(scan_pages::page(&operation.id, &scan, offset, limit, sort_by))
+}
+
+/// List every mod archive with its manifest without needing a log. The full list is kept
+/// under the operation id; this returns the first page and `get_scan_page` the rest.
+/// An earlier `mods-scan` of the same profile is cancelled unless `cancel_previous` is false.
+/// 不需 log 即列出所有模組壓縮檔及其 manifest；完整結果以作業 id 保留，此指令回傳第一頁，其餘以 `get_scan_page` 取得；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `mods-scan` 作業
+#[tauri::command]
+async fn scan_mods_folder(
+    app: AppHandle,
+    game_path: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    sort_by: Option<String>,
+) -> Result<Page, AppError> {
+    let sort_by = SortBy::parse(sort_by.as_deref())?;
+    let describe = |page: &Page| format!("{} total", page.total_count);
+    safe_invoke_measured("scan_mods_folder", describe, move || {
+        run_stored_scan(
+            &app,
+            &game_path,
+            "mods-scan",
+            operation_id,
+            cancel_previous,
+            offset,
+            limit,
+            sort_by,
+            StoredScan::Mods,
+        )
+    })
+    .await
+}
+
+/// Find GUIDs declared by more than one mod archive without needing a log; paged like
+/// `scan_mods_folder`.
+/// An earlier `guid-scan` of the same profile is cancelled unless `cancel_previous` is false.
+/// 不需 log 即找出被多個模組壓縮檔宣告的 GUID；分頁方式同 `scan_mods_folder`；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `guid-scan` 作業
+#[tauri::command]
+async fn scan_guid_groups(
+    app: AppHandle,
+    game_path: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    sort_by: Option<String>,
+) -> Result<Page, AppError> {
+    let sort_by = SortBy::parse(sort_by.as_deref())?;
+    let describe = |page: &Page| format!("{} total", page.total_count);
+    safe_invoke_measured("scan_guid_groups", describe, move || {
+        run_stored_scan(
+            &app,
+            &game_path,
+            "guid-scan",
+            operation_id,
+            cancel_previous,
+            offset,
+            limit,
+            sort_by,
+            |files| StoredScan::GuidGroups(mods_scan::guid_groups(files)),
+        )
+    })
+    .await
+}
+
+/// Another page of a stored scan; fails with `scan_expired` once the result was released
+/// or evicted, after which the frontend should scan again
+/// 取得已存掃描結果的另一頁；結果已釋放或被捨棄時回傳 `scan_expired`，前端應重新掃描
+#[tauri::command]
+fn get_scan_page(
+    store: State<ScanStore>,
+    operation_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    sort_by: Option<String>,
+) -> Result<Page, AppError> {
+    let sort_by = SortBy::parse(sort_by.as_deref())?;
+    let scan = store.get(&operation_id)?;
+    Ok(scan_pages::page(&operation_id, &scan, offset, limit, sort_by))
+}
+
+/// Predict which duplicate Sideloader will load for each GUID of a stored scan, for when
+/// mods were added while the game was closed and no log describes them yet
+/// 依已存的掃描結果預測 Sideloader 對每個 GUID 會載入哪個重複檔案；適用於遊戲關閉時新增模組、尚無 log 的情況
+#[tauri::command]
+fn predict_loaded(
+    store: State<ScanStore>,
+    operation_id: String,
+) -> Result<Vec<mods_scan::Prediction>, AppError> {
+    let groups = match &*store.get(&operation_id)? {
+        StoredScan::Mods(files) => mods_scan::guid_groups(files.clone()),
+        StoredScan::GuidGroups(groups) => groups.clone(),
+    };
+    Ok(mods_scan::predict_loaded(groups))
+}
+
+/// Find manually installed mods that the modpack also ships at an equal or newer version,
+/// using the GUID index. Each result is added to the conflicts of the last scan, with the
+/// modpack copy loaded and the manual copy skipped, so passing the ids of the `safe` results
+/// to `resolve_conflicts` removes exactly those manual copies. Reports on
+/// `operation-progress` with kind `superseded-scan`.
+/// An earlier `superseded-scan` of the same profile is cancelled unless `cancel_previous` is false.
+/// 以 GUID 索引找出模組包也以相同或較新版本提供的手動安裝模組；每筆結果以模組包檔案為 loaded、手動安裝檔案為 skipped
+/// 加入最近一次掃描的衝突，因此將 `safe` 結果的 id 交給 `resolve_conflicts` 即只會移除這些手動安裝的檔案；
+/// 進度以 `superseded-scan` 類型送出；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `superseded-scan` 作業
+#[tauri::command]
+async fn find_superseded_manual_mods(
+    app: AppHandle,
+    game_path: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<Vec<superseded::SupersededMod>, AppError> {
+    let index_path = profile_data_dir(&app, &game_path)?.join("asset_index.json");
+    let settings = profile_settings(&app, &game_path)?;
+    let modpacks = modpack_folders(&app, &[&game_path])?;
+    safe_invoke("find_superseded_manual_mods", move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "superseded-scan",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = mods_scan::scan_mods_folder(
+            &index_path,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+            &operation.token,
+            &|progress| reporter.update(progress),
+        )
+        .and_then(|mut files| {
+            priority_folders::mark_priority_entries(
+                files.iter_mut().map(|file| &mut file.entry),
+                &settings.priority_folder_prefixes,
+            );
+            superseded::find_superseded(files, &modpacks, &operation.token, &|progress| {
+                reporter.update(progress)
+            })
+        });
+        reporter.finish(result.as_ref().err());
+        let superseded = result?;
+        let conflicts = superseded
+            .iter()
+            .map(|found| found.conflict.clone())
+            .collect::<Vec<_>>();
+        app.state::<LastConflicts>().merge(&game_path, &conflicts);
+        Ok(superseded)
+    })
+    .await
+}
+
+/// Mods that cards or scenes of the logged session needed but Sideloader did not have, from its
+/// missing-mod and failed-resolve warnings grouped by GUID with counts and example lines. Each
+/// GUID is checked against the GUID index and the disabled and quarantined files, so one that
+/// is only disabled or quarantined can be restored instead of downloaded. Reads the game's log
+/// unless `log` is given. Reports on `operation-progress` with kind `missing-dependencies`.
+/// The full analysis carries the same list, so `run_full_analysis` exports it.
+/// An earlier `missing-dependencies` of the same profile is cancelled unless `cancel_previous` is false.
+/// 依 Sideloader 的缺少模組與解析失敗警告，找出 log 記錄的遊戲階段中角色卡或場景需要但沒有的模組，依 GUID 分組並附次數與範例行；
+/// 每個 GUID 會與 GUID 索引及已停用、已隔離的檔案比對，僅是停用或隔離者可改為還原而不必重新下載；
+/// 未提供 `log` 時讀取遊戲的 log；進度以 `missing-dependencies` 類型送出；完整分析亦包含此清單，可由 `run_full_analysis` 匯出；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `missing-dependencies` 作業
+#[tauri::command]
+async fn parse_missing_dependencies(
+    app: AppHandle,
+    game_path: String,
+    log: Option<String>,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<Vec<missing_deps::MissingDependency>, AppError> {
+    let index_path = profile_data_dir(&app, &game_path)?.join("asset_index.json");
+    let settings = profile_settings(&app, &game_path)?;
+    safe_invoke("parse_missing_dependencies", move || {
+        let log = match log {
+            Some(log) => log,
+            None => scan::find_and_read_log(&game_path)?,
+        };
+        let mut dependencies = missing_deps::parse_missing_dependencies(&log);
+        if dependencies.is_empty() {
+            return Ok(dependencies);
+        }
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "missing-dependencies",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = mods_scan::scan_mods_folder(
+            &index_path,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+            &operation.token,
+            &|progress| reporter.update(progress),
+        );
+        reporter.finish(result.as_ref().err());
+        missing_deps::cross_reference(
+            &mut dependencies,
+            &result?,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+        );
+        Ok(dependencies)
+    })
+    .await
+}
+
+/// Which mods provide `guid` and which name it in their manifests, e.g. in a custom element
+/// listing required GUIDs, using the GUID index refreshed first. Cards, scenes and coordinates
+/// are not indexed and are listed in `not_searched`. Before the index is first built, answers
+/// `index_not_built` with the estimated time to build it instead of an empty report. Reports
+/// on `operation-progress` with kind `guid-usages`.
+/// An earlier `guid-usages` of the same profile is cancelled unless `cancel_previous` is false.
+/// 以先更新的 GUID 索引找出提供 `guid` 的模組，以及在 manifest 中（例如列出所需 GUID 的自訂元素）提到它的模組；
+/// 角色卡、場景與服裝檔沒有索引，列於 `not_searched`；索引首次建立前回傳 `index_not_built` 與預估的建立時間，
+/// 而非空的報告；進度以 `guid-usages` 類型送出；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `guid-usages` 作業
+#[tauri::command]
+async fn guid_usages(
+    app: AppHandle,
+    game_path: String,
+    guid: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<guid_usages::GuidUsages, AppError> {
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let settings = profile_settings(&app, &game_path)?;
+    safe_invoke("guid_usages", move || {
+        let guid = guid_usages::require_guid(&guid)?;
+        let index_path = profile_dir.join("asset_index.json");
+        if asset_index::indexed_sizes(&index_path).is_none() {
+            mod_roots::ensure_mods_dir(Path::new(&game_path))?;
+            return Ok(guid_usages::GuidUsages::IndexNotBuilt {
+                estimate: operation_estimates::estimate_operation(
+                    EstimateKind::AssetIndex,
+                    &profile_dir,
+                    Path::new(&game_path),
+                    settings.follow_links,
+                    &settings.mod_extensions,
+                ),
+            });
+        }
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "guid-usages",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = mods_scan::scan_mods_folder(
+            &index_path,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+            &operation.token,
+            &|progress| reporter.update(progress),
+        );
+        reporter.finish(result.as_ref().err());
+        Ok(guid_usages::GuidUsages::Found {
+            report: guid_usages::find_usages(&result?, guid),
+        })
+    })
+    .await
+}
+
+/// Create the `mods` folder of a game that has none, e.g. from the `mods_dir_missing` error
+/// state, and return its path; refused in read-only mode
+/// 為沒有 `mods` 資料夾的遊戲建立該資料夾（例如從 `mods_dir_missing` 錯誤畫面呼叫）並回傳其路徑；唯讀模式下拒絕
+#[tauri::command]
+async fn create_mods_dir(app: AppHandle, game_path: String) -> Result<String, AppError> {
+    let write_mode = app.state::<Operations>().write_mode();
+    safe_invoke("create_mods_dir", move || {
+        mod_roots::create_mods_dir(Path::new(&game_path), write_mode)
+            .map(|mods| long_path::display(&mods))
+    })
+    .await
+}
+
+/// Fallback for logs without duplicate warnings: mod archives that share a filename across
+/// folders and are close in size (the largest at most `max_size_ratio` times the smallest,
+/// default `same_name::DEFAULT_MAX_SIZE_RATIO`). Groups whose manifests all declare one GUID
+/// come first as `same_guid`; the rest are low-confidence `same_name` guesses. Unlike scanned
+/// conflicts, the groups are not stored for `resolve_conflicts`. Reports on
+/// `operation-progress` with kind `same-name-scan`.
+/// An earlier `same-name-scan` of the same profile is cancelled unless `cancel_previous` is false.
+/// 供沒有重複警告的 log 使用的備援：跨資料夾同名且大小相近（最大者不超過最小者的 `max_size_ratio` 倍，預設為
+/// `same_name::DEFAULT_MAX_SIZE_RATIO`）的模組壓縮檔；所有 manifest 宣告同一 GUID 的群組以 `same_guid` 排在前面，
+/// 其餘為低把握度的 `same_name` 推測；與掃描出的衝突不同，這些群組不會儲存供 `resolve_conflicts` 使用；進度以 `same-name-scan` 類型送出；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `same-name-scan` 作業
+#[tauri::command]
+async fn find_same_name_candidates(
+    app: AppHandle,
+    game_path: String,
+    max_size_ratio: Option<f64>,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<Vec<same_name::CandidateGroup>, AppError> {
+    let index_path = profile_data_dir(&app, &game_path)?.join("asset_index.json");
+    let settings = profile_settings(&app, &game_path)?;
+    safe_invoke("find_same_name_candidates", move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "same-name-scan",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = mods_scan::scan_mods_folder(
+            &index_path,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+            &operation.token,
+            &|progress| reporter.update(progress),
+        );
+        reporter.finish(result.as_ref().err());
+        let mut files = result?;
+        priority_folders::mark_priority_entries(
+            files.iter_mut().map(|file| &mut file.entry),
+            &settings.priority_folder_prefixes,
+        );
+        Ok(same_name::find_same_name_candidates(
+            files,
+            max_size_ratio.unwrap_or(same_name::DEFAULT_MAX_SIZE_RATIO),
+        ))
+    })
+    .await
+}
+
+/// Compare the mods folders of two installs (e.g. KK and KKS, or desktop and laptop), matching
+/// files by manifest GUID and otherwise by relative path and size. Both trees are scanned at
+/// once with each profile's manifest cache; progress is sent with kind `install-diff`. Results
+/// too large for IPC work as in `parse_log`.
+/// An earlier `install-diff` of the same profile is cancelled unless `cancel_previous` is false.
+/// 比較兩個安裝的 mods 資料夾（例如 KK 與 KKS、桌機與筆電），以 manifest GUID 比對檔案，否則以相對路徑與大小比對；
+/// 兩邊同時掃描並使用各自 profile 的 manifest 快取，進度以 `install-diff` 類型送出；過大結果的處理與 `parse_log` 相同；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `install-diff` 作業
+#[tauri::command]
+async fn diff_installs(
+    app: AppHandle,
+    path_a: String,
+    path_b: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<Guarded<install_diff::InstallDiff>, AppError> {
+    let index_a = profile_data_dir(&app, &path_a)?.join("asset_index.json");
+    let index_b = profile_data_dir(&app, &path_b)?.join("asset_index.json");
+    let settings_a = profile_settings(&app, &path_a)?;
+    let settings_b = profile_settings(&app, &path_b)?;
+    let describe = |diff: &install_diff::InstallDiff| {
+        format!(
+            "{} only in A, {} only in B, {} version differences, {} identical",
+            diff.only_in_a.len(),
+            diff.only_in_b.len(),
+            diff.version_differs.len(),
+            diff.identical.len()
+        )
+    };
+    let summarize = |diff: &install_diff::InstallDiff| {
+        serde_json::json!({
+            "only_in_a": diff.only_in_a.len(),
+            "only_in_b": diff.only_in_b.len(),
+            "version_differs": diff.version_differs.len(),
+            "identical": diff.identical.len(),
+        })
+    };
+    let handle = app.clone();
+    safe_invoke_guarded(&handle, "diff_installs", describe, summarize, move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&path_a),
+            "install-diff",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = install_diff::diff_installs(
+            &install_diff::DiffSource {
+                index_path: &index_a,
+                game_path: Path::new(&path_a),
+                follow_links: settings_a.follow_links,
+                mod_extensions: &settings_a.mod_extensions,
+            },
+            &install_diff::DiffSource {
+                index_path: &index_b,
+                game_path: Path::new(&path_b),
+                follow_links: settings_b.follow_links,
+                mod_extensions: &settings_b.mod_extensions,
+            },
+            &operation.token,
+            &|progress| reporter.update(progress),
+        );
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Hash every file of the mods folder with blake3 and write a checksum file to `destination`,
+/// e.g. next to a backup; progress is sent with kind `checksum-export`.
+/// An earlier `checksum-export` of the same profile is cancelled unless `cancel_previous` is false.
+/// 以 blake3 計算 mods 資料夾所有檔案的雜湊並寫出校驗檔至 `destination`（例如備份旁）；進度以 `checksum-export` 類型送出；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `checksum-export` 作業
+#[tauri::command]
+async fn export_checksums(
+    app: AppHandle,
+    game_path: String,
+    destination: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<checksums::ExportSummary, AppError> {
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let settings = profile_settings(&app, &game_path)?;
+    let describe = |summary: &checksums::ExportSummary| {
+        format!("{} files, {} bytes", summary.files, summary.total_bytes)
+    };
+    safe_invoke_measured("export_checksums", describe, move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "checksum-export",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let started = Instant::now();
+        let result = checksums::export_checksums(
+            Path::new(&game_path),
+            Path::new(&destination),
+            settings.follow_links,
+            &operation.token,
+            &|progress| reporter.update(progress),
+        );
+        if let Ok(summary) = &result {
+            operation_estimates::record_run(
+                &profile_dir,
+                EstimateKind::ChecksumExport,
+                summary.total_bytes,
+                started.elapsed(),
+            );
+        }
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Re-hash the mods folder and report files missing, modified or extra compared with a
+/// checksum file; progress is sent with kind `checksum-verify`.
+/// An earlier `checksum-verify` of the same profile is cancelled unless `cancel_previous` is false.
+/// 重新計算 mods 資料夾的雜湊，回報與校驗檔相比缺少、已修改或多出的檔案；進度以 `checksum-verify` 類型送出；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `checksum-verify` 作業
+#[tauri::command]
+async fn verify_checksums(
+    app: AppHandle,
+    game_path: String,
+    manifest_path: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<checksums::VerifyResult, AppError> {
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let settings = profile_settings(&app, &game_path)?;
+    let describe = |result: &checksums::VerifyResult| {
+        format!(
+            "{} verified, {} missing, {} modified, {} extra",
+            result.verified,
+            result.missing.len(),
+            result.modified.len(),
+            result.extra.len()
+        )
+    };
+    safe_invoke_measured("verify_checksums", describe, move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "checksum-verify",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let started = Instant::now();
+        let result = checksums::verify_checksums(
+            Path::new(&game_path),
+            Path::new(&manifest_path),
+            settings.follow_links,
+            &operation.token,
+            &|progress| reporter.update(progress),
+        );
+        if result.is_ok() {
+            record_candidate_run(
+                &profile_dir,
+                EstimateKind::ChecksumVerify,
+                &game_path,
+                &settings,
+                started,
+            );
+        }
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Find zipmods that fail to open (not zips, truncated central directory, or an entry count
+/// that disagrees with it) without decompressing them; progress is sent with kind `corrupt-scan`.
+/// The findings' paths can be passed to `delete_mods`.
+/// An earlier `corrupt-scan` of the same profile is cancelled unless `cancel_previous` is false.
+/// 不解壓縮即找出無法開啟的 zipmod（不是 zip、central directory 不完整或 entry 數不符）；進度以 `corrupt-scan` 類型送出，
+/// 結果中的路徑可直接交給 `delete_mods`；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `corrupt-scan` 作業
+#[tauri::command]
+async fn scan_for_corrupt_mods(
+    app: AppHandle,
+    game_path: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<Vec<corrupt_mods::CorruptFinding>, AppError> {
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let settings = profile_settings(&app, &game_path)?;
+    let describe = |findings: &Vec<corrupt_mods::CorruptFinding>| format!("{} findings", findings.len());
+    safe_invoke_measured("scan_for_corrupt_mods", describe, move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "corrupt-scan",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let started = Instant::now();
+        let result = corrupt_mods::scan_for_corrupt_mods(
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+            &operation.token,
+            &|progress| reporter.update(progress),
+        );
+        if result.is_ok() {
+            record_candidate_run(
+                &profile_dir,
+                EstimateKind::CorruptScan,
+                &game_path,
+                &settings,
+                started,
+            );
+        }
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Find zipmods whose manifest `<game>` tags exclude the installed game variant, such as KKS-only
+/// mods in a KK install; progress is sent with kind `game-tag-scan`.
+/// An earlier `game-tag-scan` of the same profile is cancelled unless `cancel_previous` is false.
+/// 找出 manifest `<game>` 標籤不包含目前遊戲版本的 zipmod（例如 KK 中的 KKS 專用模組）；進度以 `game-tag-scan` 類型送出；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `game-tag-scan` 作業
+#[tauri::command]
+async fn find_wrong_game_mods(
+    app: AppHandle,
+    game_path: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<Vec<game_tags::WrongGameMod>, AppError> {
+    let index_path = profile_data_dir(&app, &game_path)?.join("asset_index.json");
+    let settings = profile_settings(&app, &game_path)?;
+    let describe = |mods: &Vec<game_tags::WrongGameMod>| format!("{} mods", mods.len());
+    safe_invoke_measured("find_wrong_game_mods", describe, move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "game-tag-scan",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = game_tags::find_wrong_game_mods(
+            &index_path,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+            &operation.token,
+            &|progress| reporter.update(progress),
+        );
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Find file and folder names under `mods` and `BepInEx/plugins` that differ only by case,
+/// have surrounding whitespace, are invalid on Windows or are not NFC; each finding suggests
+/// a free name for `rename_mod`
+/// 找出 `mods` 與 `BepInEx/plugins` 底下僅大小寫不同、前後有空白、Windows 不允許或非 NFC 的檔案與資料夾名稱；
+/// 每筆結果附帶可用於 `rename_mod` 的建議名稱
+#[tauri::command]
+async fn find_name_anomalies(
+    game_path: String,
+) -> Result<Vec<name_anomalies::NameAnomaly>, AppError> {
+    let describe = |anomalies: &Vec<name_anomalies::NameAnomaly>| {
+        format!("{} anomalies", anomalies.len())
+    };
+    safe_invoke_measured("find_name_anomalies", describe, move || {
+        Ok(name_anomalies::find_name_anomalies(&game_path))
+    })
+    .await
+}
+
+/// Open a mod archive in the user's archive program (e.g. 7-Zip), falling back to the "Open
+/// with" dialog or a temporary `.zip` copy on Windows; fails with `no_archive_handler` when
+/// nothing can open it
+/// 以使用者的壓縮程式（例如 7-Zip）開啟模組壓縮檔，Windows 上會改用「開啟檔案」對話框或暫時的 `.zip` 複本；
+/// 沒有任何程式可開啟時回傳 `no_archive_handler`
+#[tauri::command]
+async fn open_mod_archive(path: String) -> Result<archive_open::OpenedWith, AppError> {
+    safe_invoke("open_mod_archive", move || {
+        archive_open::open_mod_archive(Path::new(&path))
+    })
+    .await
+}
+
+/// Rename a mod file or folder inside a registered game's allowed folders to `new_name`,
+/// keeping it in the same folder; returns the new path
+/// 將已登記遊戲允許資料夾內的模組檔案或資料夾改名為 `new_name`（仍在原資料夾），回傳新路徑
+#[tauri::command]
+async fn rename_mod(app: AppHandle, path: String, new_name: String) -> Result<String, AppError> {
+    let game_paths = registered_game_paths(&app)?;
+    let write_mode = app.state::<Operations>().write_mode();
+    safe_invoke("rename_mod", move || {
+        AllowedRoots::for_games(&game_paths).check(&path)?;
+        ops::rename_file(Path::new(&path), &new_name, write_mode)
+            .map(|target| long_path::display(&target))
+    })
+    .await
+}
+
+/// Re-read one mod file after a file operation so the frontend can update its row without a new
+/// scan. A missing file comes back with `exists: false`; `managed` follows the modpack folders of
+/// every registered game, and `with_manifest` attaches the manifest, from the owning game's asset
+/// index when its record is current. `with_provenance` is the deep inspection: it attaches the
+/// zip comment and entry dates in compact form.
+/// 檔案操作後重新讀取單一模組檔，讓前端不需重新掃描即可更新該列；檔案不存在時回傳 `exists: false`；
+/// `managed` 依所有已登記遊戲的模組包資料夾判斷；`with_manifest` 附上 manifest，所屬遊戲的資源索引紀錄仍有效時直接取用；
+/// `with_provenance` 為深入檢視，附上精簡的 zip 註解與 entry 日期
+#[tauri::command]
+async fn get_mod_entry(
+    app: AppHandle,
+    path: String,
+    with_manifest: Option<bool>,
+    with_provenance: Option<bool>,
+) -> Result<mods_scan::RefreshedEntry, AppError> {
+    let game_paths = registered_game_paths(&app)?;
+    let modpacks = modpack_folders(&app, &game_paths)?;
+    let display = long_path::display(Path::new(&path)).to_lowercase();
+    let owner = game_paths.iter().find(|game_path| {
+        let root = long_path::display(Path::new(game_path)).to_lowercase();
+        display
+            .strip_prefix(root.trim_end_matches(['\\', '/']))
+            .is_some_and(|rest| rest.starts_with(['\\', '/']))
+    });
+    let index_path = owner
+        .map(|game_path| profile_data_dir(&app, game_path))
+        .transpose()?
+        .map(|dir| dir.join("asset_index.json"));
+    let mod_extensions = match owner {
+        Some(game_path) => profile_settings(&app, game_path)?.mod_extensions,
+        None => profiles::default_mod_extensions(),
+    };
+    safe_invoke("get_mod_entry", move || {
+        Ok(mods_scan::refresh_entry(
+            Path::new(&path),
+            &modpacks,
+            index_path.as_deref(),
+            &mod_extensions,
+            with_manifest.unwrap_or(false),
+            with_provenance.unwrap_or(false),
+        ))
+    })
+    .await
+}
+
+/// Find item ids that zipmods of different GUIDs both register through their `list/*.csv`
+/// files; list files are cached by mtime in the profile, malformed rows come back as warnings.
+/// Progress is sent with kind `list-id-scan`.
+/// An earlier `list-id-scan` of the same profile is cancelled unless `cancel_previous` is false.
+/// 找出不同 GUID 的 zipmod 透過 `list/*.csv` 重複註冊的項目 id；list 檔依修改時間快取於 profile，
+/// 格式錯誤的列以警告回傳；進度以 `list-id-scan` 類型送出；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `list-id-scan` 作業
+#[tauri::command]
+async fn find_list_id_collisions(
+    app: AppHandle,
+    game_path: String,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+) -> Result<list_ids::ListIdReport, AppError> {
+    let cache_path = profile_data_dir(&app, &game_path)?.join("list_index.json");
+    let settings = profile_settings(&app, &game_path)?;
+    let describe = |report: &list_ids::ListIdReport| {
+        format!(
+            "{} collisions, {} mods, {} re-read",
+            report.collisions.len(),
+            report.mods_scanned,
+            report.mods_reread
+        )
+    };
+    safe_invoke_measured("find_list_id_collisions", describe, move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "list-id-scan",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = list_ids::find_list_id_collisions(
+            &cache_path,
+            Path::new(&game_path),
+            settings.follow_links,
+            &settings.mod_extensions,
+            &operation.token,
+            &|progress| reporter.update(progress),
+        );
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Run self-check, conflict scan, offline GUID scan, corrupt-mod scan, junk-file scan and the
+/// missing-dependency check in one go, then total the wasted bytes. Progress is sent with kind
+/// `full-analysis` and messages like `step 3/7: reading manifests`; stages in `options.skip` are left out and a failing stage only
+/// marks its own section errored. With `options.export_path` the report is also written as JSON.
+/// Reports too large for IPC work as in `parse_log`, summarized by `game_path`, `generated` and `totals`.
+/// An earlier `full-analysis` of the same profile is cancelled unless `cancel_previous` is false.
+/// 一次執行自我檢查、衝突掃描、離線 GUID 掃描、損壞模組掃描、多餘檔案掃描與缺少相依模組檢查，並統計浪費的空間；進度以
+/// `full-analysis` 類型及 `step 3/7: reading manifests` 形式的訊息送出；`options.skip` 中的階段會略過，
+/// 失敗的階段只會將其區段標記為錯誤；提供 `options.export_path` 時另將報告寫成 JSON；
+/// 過大報告的處理與 `parse_log` 相同，摘要為 `game_path`、`generated` 與 `totals`；
+/// 除非 `cancel_previous` 為 false，否則取消同 profile 先前的 `full-analysis` 作業
+#[tauri::command]
+async fn run_full_analysis(
+    app: AppHandle,
+    game_path: String,
+    options: Option<full_analysis::FullAnalysisOptions>,
+    operation_id: Option<String>,
+    cancel_previous: Option<bool>,
+    sort: Option<String>,
+) -> Result<Guarded<full_analysis::FullReport>, AppError> {
+    let options = options.unwrap_or_default();
+    let sort = ConflictSort::parse(sort.as_deref())?;
+    let profile_dir = profile_data_dir(&app, &game_path)?;
+    let settings = profile_settings(&app, &game_path)?;
+    let modpacks = modpack_folders(&app, &[&game_path])?;
+    let describe = |report: &full_analysis::FullReport| {
+        format!("{} bytes wasted", report.totals.total_bytes)
+    };
+    let summarize = |report: &full_analysis::FullReport| {
+        serde_json::json!({
+            "game_path": report.game_path,
+            "generated": report.generated,
+            "totals": report.totals,
+        })
+    };
+    let handle = app.clone();
+    safe_invoke_guarded(&handle, "run_full_analysis", describe, summarize, move || {
+        stop_watch_for_other_profile(&app, &game_path);
+        let operations = app.state::<Operations>();
+        let operation = operations.register(
+            &profile_id(&game_path),
+            "full-analysis",
+            operation_id,
+            cancel_previous.unwrap_or(true),
+        )?;
+        let reporter = ProgressReporter::new(&app, &operation);
+        let context = full_analysis::AnalysisContext {
+            game_path: &game_path,
+            scan_cache_path: profile_dir.join("scan_cache.json"),
+            index_path: profile_dir.join("asset_index.json"),
+            follow_links: settings.follow_links,
+            mod_extensions: &settings.mod_extensions,
+            modpacks: &modpacks,
+            write_mode: operation.write_mode,
+            cancel: &operation.token,
+        };
+
+        let result = full_analysis::run_full_analysis(&context, &options, &|progress| {
+            reporter.update(progress)
+        })
+        .map(|mut report| {
+            if let full_analysis::Section::Ok { result: scan } = &mut report.conflicts {
+                modpack::mark_managed(&mut scan.conflicts, &modpacks);
+                priority_folders::mark_priority(
+                    &mut scan.conflicts,
+                    &settings.priority_folder_prefixes,
+                );
+                pins::mark(&profile_dir, &game_path, &mut scan.conflicts);
+                conflict_coupling::mark_coupling(&mut scan.conflicts);
+                conflict_notes::attach(&profile_dir, &mut scan.conflicts);
+                conflict_sort::sort_conflicts(&mut scan.conflicts, sort);
+                app.state::<LastConflicts>().store(&game_path, &scan.conflicts);
+                app.state::<SessionProgress>().track(&game_path, &scan.conflicts);
+            }
+            if let full_analysis::Section::Ok { result: junk } = &report.junk_files {
+                app.state::<LastJunk>().store(&game_path, junk);
+            }
+            if let Some(export_path) = &options.export_path {
+                full_analysis::export(&mut report, Path::new(export_path));
+            }
+            report
+        });
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Quick estimate of the space resolving everything would free, from the (cached) log parse and
+/// the junk of the last full analysis; never hashes or reads manifests. `confidence` tells
+/// which parts are measured and which are estimates or unavailable.
+/// 以（快取的）log 解析與上次完整分析的多餘檔案快速估算處理所有問題後可釋放的空間；不計算雜湊也不讀取 manifest；
+/// `confidence` 標示哪些部分為實際量測、哪些為估計或無法取得
+#[tauri::command]
+async fn estimate_reclaimable(
+    app: AppHandle,
+    game_path: String,
+) -> Result<reclaim::ReclaimEstimate, AppError> {
+    let cache_path = profile_data_dir(&app, &game_path)?.join("scan_cache.json");
+    let junk_paths = app.state::<LastJunk>().paths(&game_path);
+    let mod_extensions = profile_settings(&app, &game_path)?.mod_extensions;
+    let describe = |estimate: &reclaim::ReclaimEstimate| {
+        format!(
+            "{} bytes, from_cache={}",
+            estimate.total_bytes, estimate.from_cache
+        )
+    };
+    safe_invoke_measured("estimate_reclaimable", describe, move || {
+        reclaim::estimate_reclaimable(
+            &cache_path,
+            &game_path,
+            &mod_extensions,
+            junk_paths.as_deref(),
+        )
+    })
+    .await
+}
+
+/// Extract the zipmods packed inside a downloaded wrapper zip, which Sideloader ignores, into
+/// `destination` (default: next to the wrapper). With `quarantine_wrapper` the wrapper is moved
+/// to its game's quarantine folder afterwards. Progress is sent with kind `extract-zipmods`.
+/// 將下載的外層 zip（Sideloader 會忽略）中的 zipmod 解壓縮至 `destination`（預設為外層 zip 旁）；
+/// 設定 `quarantine_wrapper` 時完成後將外層 zip 移至其遊戲的隔離資料夾；進度以 `extract-zipmods` 類型送出
+#[tauri::command]
+async fn extract_inner_zipmods(
+    app: AppHandle,
+    path: String,
+    destination: Option<String>,
+    quarantine_wrapper: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<Vec<ModEntry>, AppError> {
+    let game_paths = registered_game_paths(&app)?;
+    safe_invoke("extract_inner_zipmods", move || {
+        let wrapper = AllowedRoots::for_games(&game_paths).check(&path)?;
+        let quarantine_game = match quarantine_wrapper.unwrap_or(false) {
+            true => quarantine::owning_game(&game_paths, &wrapper).map(PathBuf::from),
+            false => None,
+        };
+        let operations = app.state::<Operations>();
+        let operation = operations.register("", "extract-zipmods", operation_id, false)?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = operation.lock_paths(&[&wrapper]).and_then(|()| {
+            nested_archives::extract_inner_zipmods(
+                Path::new(&path),
+                destination.as_deref().map(Path::new),
+                quarantine_game.as_deref(),
+                operation.write_mode,
+                &operation.token,
+                &|progress| reporter.update(progress),
+            )
+        });
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// List a zip, 7z or rar download and read its manifest before it is converted,
+/// extracting nothing else
+/// 在轉換前列出 zip、7z 或 rar 下載檔的內容並讀取 manifest，不解出其他檔案
+#[tauri::command]
+async fn inspect_archive(path: String) -> Result<archive_inspect::ArchiveInspection, AppError> {
+    safe_invoke("inspect_archive", move || {
+        archive_inspect::inspect_archive(Path::new(&path))
+    })
+    .await
+}
+
+/// Repack a zip, 7z or rar download into `.zipmod` files in `destination_dir`, one per
+/// mod root it contains
+/// 將 zip、7z 或 rar 下載檔重新打包為 `destination_dir` 中的 `.zipmod`，每個模組根目錄各一個
+#[tauri::command]
+async fn convert_to_zipmod(
+    app: AppHandle,
+    source_path: String,
+    destination_dir: String,
+    operation_id: Option<String>,
+) -> Result<Vec<ModEntry>, AppError> {
+    safe_invoke("convert_to_zipmod", move || {
+        let operations = app.state::<Operations>();
+        let operation = operations.register("", "convert-zipmod", operation_id, false)?;
+        let reporter = ProgressReporter::new(&app, &operation);
+
+        let result = operation.lock_paths(&[&source_path]).and_then(|()| {
+            zipmod_convert::convert_to_zipmod(
+                Path::new(&source_path),
+                Path::new(&destination_dir),
+                &operation.token,
+                &|progress| reporter.update(progress),
+            )
+        });
+        reporter.finish(result.as_ref().err());
+        result
+    })
+    .await
+}
+
+/// Where settings, caches, history and indices are kept this session. When the app data dir
+/// was unwritable at startup, everything is kept in a scratch folder that is removed on exit,
+/// so nothing is saved for the next start; `error` is the original failure.
+/// 本次啟動時設定、快取、歷史紀錄與索引的存放位置；啟動時 app data 資料夾無法寫入時，所有資料改存於結束時刪除的暫存資料夾，
+/// 不會保留到下次啟動；`error` 為原始的錯誤
+#[tauri::command]
+fn get_storage_status(storage: State<Storage>) -> StorageStatus {
+    storage.status().clone()
+}
+
+/// Free a stored scan result; returns false if it was already gone
+/// 釋放已存的掃描結果；若已不存在則回傳 false
+#[tauri::command]
+fn release_scan(store: State<ScanStore>, operation_id: String) -> bool {
+    store.release(&operation_id)
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    patterns::validate();
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .manage(Operations::default())
+        .manage(ActiveWatch::default())
+        .manage(ActiveRefresh::default())
+        .manage(ScanStore::default())
+        .manage(LastConflicts::default())
+        .manage(SessionProgress::default())
+        .manage(LastJunk::default())
+        .manage(CleanupCounters::default())
+        .setup(|app| {
+            app_log::init(app.path().app_log_dir()?);
+            incidents::install();
+            app.manage(Storage::detect(
+                app.path()
+                    .app_data_dir()
+                    .map_err(|e| AppError::io("io.resolve_app_data_dir", None, e)),
+            ));
+            let settings = profiles::load_app_settings(&profiles_root(app.handle()));
+            app.state::<Operations>()
+                .set_write_mode(WriteMode::from_read_only(settings.read_only_mode));
+            cloud_files::set_hydrate_placeholders(settings.hydrate_cloud_files);
+            large_results::discard_all(&results_dir(app.handle()));
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            parse_log,
+            read_log_from_path,
+            scan_conflicts,
+            delete_mods,
+            read_manifest_from_mod_file,
+            find_asset_provider,
+            build_asset_index,
+            cancel_operation,
+            find_global_asset_collisions,
+            scan_plugin_duplicates,
+            read_plugin_info,
+            get_incident_report,
+            get_message_catalog,
+            create_diagnostics_bundle,
+            start_mods_watch,
+            stop_mods_watch,
+            get_app_log_dir,
+            read_recent_app_log,
+            cleanup_app_logs,
+            get_profile_settings,
+            get_app_settings,
+            set_app_settings,
+            set_profile_settings,
+            group_conflicts,
+            scan_mods_folder,
+            scan_guid_groups,
+            get_scan_page,
+            release_scan,
+            predict_loaded,
+            diff_installs,
+            export_checksums,
+            verify_checksums,
+            scan_for_corrupt_mods,
+            find_wrong_game_mods,
+            extract_inner_zipmods,
+            inspect_archive,
+            convert_to_zipmod,
+            plan_conflict_resolution,
+            resolve_conflicts,
+            resolve_by_rule,
+            run_self_check,
+            start_background_refresh,
+            stop_background_refresh,
+            check_modpack_integrity,
+            stage_installs,
+            apply_stage_plan,
+            get_session_progress,
+            reset_session_progress,
+            set_conflict_note,
+            get_conflict_notes,
+            pin_files,
+            unpin_files,
+            get_pinned,
+            compact_index,
+            guid_usages,
+            find_name_anomalies,
+            rename_mod,
+            find_list_id_collisions,
+            run_full_analysis,
+            estimate_reclaimable,
+            get_mod_entry,
+            conflict_density_by_folder,
+            get_cleanup_stats,
+            open_mod_archive,
+            rebuild_first_seen_index,
+            refresh_conflict_metadata,
+            estimate_operation,
+            find_superseded_manual_mods,
+            get_mod_directories,
+            recently_changed_mods,
+            parse_missing_dependencies,
+            find_same_name_candidates,
+            create_mods_dir,
+            read_result_chunk,
+            discard_result,
+            generate_support_fingerprint,
+            get_storage_status
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                large_results::discard_all(&results_dir(app));
+                app.state::<Storage>().discard_session();
+            }
+        });
+}
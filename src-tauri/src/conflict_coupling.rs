@@ -0,0 +1,46 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    iter,
+};
+
+use crate::parser::ModConflict;
+
+// ───────────────────────────────────────────────
+// Coupling / 關聯
+// ───────────────────────────────────────────────
+
+/// Key under which member paths name the same file / 成員路徑指向同一檔案時使用的 key
+fn path_key(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
+
+/// Set `coupled_conflicts` on every member of `conflicts` to the ids of the other conflicts the
+/// same file belongs to, loaded or skipped, sorted. Deleting such a file also changes those
+/// conflicts, e.g. a copy skipped in one warning may be the loaded copy of another. Coupling
+/// only holds within `conflicts`, so pass the whole scan.
+/// 將 `conflicts` 每個成員的 `coupled_conflicts` 設為同一檔案（無論 loaded 或 skipped）所屬的其他衝突 id，並排序；
+/// 刪除這類檔案也會改變那些衝突，例如某警告中被跳過的檔案可能是另一個警告的 loaded 檔案；
+/// 關聯只在 `conflicts` 之內成立，因此須傳入整次掃描
+pub(crate) fn mark_coupling(conflicts: &mut [ModConflict]) {
+    let mut owners: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for conflict in conflicts.iter() {
+        for entry in iter::once(&conflict.loaded).chain(&conflict.skipped) {
+            owners
+                .entry(path_key(&entry.path))
+                .or_default()
+                .insert(conflict.id.clone());
+        }
+    }
+    for conflict in conflicts {
+        let id = conflict.id.clone();
+        for entry in iter::once(&mut conflict.loaded).chain(&mut conflict.skipped) {
+            entry.coupled_conflicts = owners
+                .get(&path_key(&entry.path))
+                .into_iter()
+                .flatten()
+                .filter(|owner| **owner != id)
+                .cloned()
+                .collect();
+        }
+    }
+}
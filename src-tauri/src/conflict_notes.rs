@@ -0,0 +1,129 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{app_log, error::AppError, messages::Message, parser::ModConflict};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Notes file inside each profile directory
+/// 各 profile 資料夾中的筆記檔
+const NOTES_FILE: &str = "conflict_notes.json";
+
+/// Longest accepted note, in characters
+/// 可接受的筆記最大長度（字元數）
+pub(crate) const MAX_NOTE_CHARS: usize = 2000;
+
+/// The user's note on one conflict, keyed by the stable conflict id
+/// 使用者對單一衝突的筆記，以穩定的衝突 id 為 key
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct ConflictNote {
+    pub conflict_id: String,
+    pub note: String,
+    /// Seconds since the Unix epoch when the note was last saved
+    /// 最後儲存筆記的時間（Unix epoch 秒數）
+    pub updated: u64,
+}
+
+/// Notes of a profile, split by whether the latest scan still lists their conflict
+/// profile 的筆記，依最近一次掃描是否仍列出其衝突分開
+#[derive(Serialize)]
+pub(crate) struct ConflictNotes {
+    pub notes: Vec<ConflictNote>,
+    /// Notes whose conflict no longer appears; kept until removed by saving an empty note
+    /// 衝突已不再出現的筆記；保留至以空白筆記移除為止
+    pub orphaned: Vec<ConflictNote>,
+}
+
+// ───────────────────────────────────────────────
+// Storage / 儲存
+// ───────────────────────────────────────────────
+
+/// Notes stored in `profile_dir`; none when the file does not exist yet
+/// `profile_dir` 中儲存的筆記；檔案尚不存在時為空
+fn load(profile_dir: &Path) -> Result<Vec<ConflictNote>, AppError> {
+    let path = profile_dir.join(NOTES_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AppError::io("io.read_conflict_notes", Some(&path), e)),
+    };
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::io("io.read_conflict_notes", Some(&path), e))
+}
+
+fn save(profile_dir: &Path, notes: &[ConflictNote]) -> Result<(), AppError> {
+    let path = profile_dir.join(NOTES_FILE);
+    let content = serde_json::to_string_pretty(notes)
+        .map_err(|e| AppError::io("io.write_conflict_notes", Some(&path), e))?;
+    fs::write(&path, content).map_err(|e| AppError::io("io.write_conflict_notes", Some(&path), e))
+}
+
+/// Save the note of `conflict_id`, replacing any earlier one; a blank note removes it.
+/// Notes longer than `MAX_NOTE_CHARS` are rejected.
+/// 儲存 `conflict_id` 的筆記並取代先前的內容；空白筆記會將其移除；超過 `MAX_NOTE_CHARS` 的筆記會被拒絕
+pub(crate) fn set_note(profile_dir: &Path, conflict_id: &str, note: &str) -> Result<(), AppError> {
+    let note = note.trim();
+    let length = note.chars().count();
+    if length > MAX_NOTE_CHARS {
+        return Err(AppError::InvalidInput {
+            message: Message::new("input.note_too_long")
+                .with("length", length)
+                .with("max", MAX_NOTE_CHARS),
+        });
+    }
+    let mut notes = load(profile_dir)?;
+    notes.retain(|stored| stored.conflict_id != conflict_id);
+    if !note.is_empty() {
+        let updated = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        notes.push(ConflictNote {
+            conflict_id: conflict_id.to_string(),
+            note: note.to_string(),
+            updated,
+        });
+        notes.sort_by(|a, b| a.conflict_id.cmp(&b.conflict_id));
+    }
+    save(profile_dir, &notes)
+}
+
+/// Every note of the profile. With `current_ids` (the conflicts of the latest scan), notes of
+/// other conflicts are listed as orphaned; without a scan nothing is.
+/// profile 的所有筆記；提供 `current_ids`（最近一次掃描的衝突）時，其他衝突的筆記列為孤立筆記；沒有掃描時則不區分
+pub(crate) fn list(
+    profile_dir: &Path,
+    current_ids: Option<&HashSet<String>>,
+) -> Result<ConflictNotes, AppError> {
+    let (notes, orphaned) = load(profile_dir)?
+        .into_iter()
+        .partition(|note| current_ids.is_none_or(|ids| ids.contains(&note.conflict_id)));
+    Ok(ConflictNotes { notes, orphaned })
+}
+
+/// Fill `note` on the conflicts that have one. An unreadable notes file is logged and left
+/// out rather than failing the scan.
+/// 為有筆記的衝突填入 `note`；筆記檔無法讀取時記錄後略過，不讓掃描失敗
+pub(crate) fn attach(profile_dir: &Path, conflicts: &mut [ModConflict]) {
+    let notes = match load(profile_dir) {
+        Ok(notes) => notes,
+        Err(e) => {
+            app_log::write(&format!("conflict notes skipped: {}", e));
+            return;
+        }
+    };
+    for conflict in conflicts {
+        conflict.note = notes
+            .iter()
+            .find(|stored| stored.conflict_id == conflict.id)
+            .map(|stored| stored.note.clone());
+    }
+}
@@ -0,0 +1,71 @@
+use std::cmp::{Ordering, Reverse};
+
+use crate::{error::AppError, messages::Message, parser::ModConflict};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Order of a conflict list, most valuable cleanup first
+/// 衝突清單的排序方式，清理價值高者排前
+#[derive(Clone, Copy)]
+pub(crate) enum ConflictSort {
+    WastedBytes,
+    SkippedCount,
+    Name,
+    MostRecentActivity,
+}
+
+impl ConflictSort {
+    /// Parse the `sort` argument; missing means log order
+    /// 解析 `sort` 參數；未指定時維持 log 順序
+    pub(crate) fn parse(sort: Option<&str>) -> Result<Option<Self>, AppError> {
+        match sort {
+            None => Ok(None),
+            Some("wasted_bytes") => Ok(Some(ConflictSort::WastedBytes)),
+            Some("skipped_count") => Ok(Some(ConflictSort::SkippedCount)),
+            Some("name") => Ok(Some(ConflictSort::Name)),
+            Some("most_recent_activity") => Ok(Some(ConflictSort::MostRecentActivity)),
+            Some(other) => Err(AppError::InvalidInput {
+                message: Message::new("input.unknown_conflict_sort").with("sort", other),
+            }),
+        }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Sorting / 排序
+// ───────────────────────────────────────────────
+
+fn primary(a: &ModConflict, b: &ModConflict, sort: ConflictSort) -> Ordering {
+    match sort {
+        ConflictSort::WastedBytes => b.wasted_bytes.cmp(&a.wasted_bytes),
+        ConflictSort::SkippedCount => b.skipped_count.cmp(&a.skipped_count),
+        ConflictSort::Name => a
+            .loaded
+            .name
+            .to_lowercase()
+            .cmp(&b.loaded.name.to_lowercase()),
+        ConflictSort::MostRecentActivity => {
+            Reverse(a.latest_activity).cmp(&Reverse(b.latest_activity))
+        }
+    }
+}
+
+/// Refresh the rank fields and sort. Counts, sizes and times sort largest or newest first,
+/// names ascending; ties fall back to the loaded path so re-scans keep a stable order.
+/// 更新排序欄位後排序；數量、大小與時間由大到小或由新到舊，名稱遞增；同值時依 loaded 路徑，使重新掃描順序穩定
+pub(crate) fn sort_conflicts(conflicts: &mut [ModConflict], sort: Option<ConflictSort>) {
+    conflicts.iter_mut().for_each(ModConflict::update_rank);
+    let Some(sort) = sort else {
+        return;
+    };
+    conflicts.sort_by(|a, b| {
+        primary(a, b, sort).then_with(|| {
+            a.loaded
+                .path
+                .to_lowercase()
+                .cmp(&b.loaded.path.to_lowercase())
+        })
+    });
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{
+    messages::Message,
+    mods_scan::{self, GuidGroup},
+    parser::{ModConflict, ModEntry},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Which scan reported a conflict member
+/// 回報衝突成員的掃描來源
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Provenance {
+    /// Only the log names it, e.g. its manifest has no GUID or could not be read
+    /// 只有 log 提到，例如 manifest 沒有 GUID 或無法讀取
+    Log,
+    /// Only the offline scan finds it, e.g. a file added since the log was written
+    /// 只有離線掃描找到，例如 log 寫入後才加入的檔案
+    Disk,
+    Both,
+}
+
+/// A conflict member and where it was seen
+/// 衝突成員及其來源
+#[derive(Serialize)]
+pub(crate) struct UnifiedMember {
+    #[serde(flatten)]
+    pub entry: ModEntry,
+    pub provenance: Provenance,
+}
+
+/// One real conflict, merged from the log and the offline GUID scan
+/// 合併 log 與離線 GUID 掃描後的單一實際衝突
+#[derive(Serialize)]
+pub(crate) struct UnifiedConflict {
+    /// GUID of the matching offline group; None when no scanned manifest matches a member
+    /// 對應離線群組的 GUID；沒有任何成員對應到已掃描的 manifest 時為 None
+    pub guid: Option<String>,
+    /// Id of the log conflict, or of the predicted one when the log has none
+    /// log 衝突的 id；log 中沒有時為預測衝突的 id
+    pub id: String,
+    /// Loaded file per the log, or per load-order prediction for predicted conflicts
+    /// log 記錄的載入檔案；預測衝突則依載入順序預測
+    pub loaded: UnifiedMember,
+    pub skipped: Vec<UnifiedMember>,
+    /// Only the offline scan sees this conflict / 只有離線掃描看得到此衝突
+    pub predicted: bool,
+    /// `conflict.predicted` for predicted conflicts / 預測衝突為 `conflict.predicted`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<Message>,
+}
+
+// ───────────────────────────────────────────────
+// Merging / 合併
+// ───────────────────────────────────────────────
+
+fn path_key(entry: &ModEntry) -> String {
+    entry.path.to_lowercase()
+}
+
+impl UnifiedConflict {
+    fn members_mut(&mut self) -> impl Iterator<Item = &mut UnifiedMember> {
+        std::iter::once(&mut self.loaded).chain(&mut self.skipped)
+    }
+
+    /// Add the log members missing from this conflict, e.g. from a second log conflict on
+    /// the same GUID
+    /// 加入此衝突尚未包含的 log 成員，例如同一 GUID 的另一筆 log 衝突
+    fn absorb_log(&mut self, conflict: &ModConflict) {
+        for entry in std::iter::once(&conflict.loaded).chain(&conflict.skipped) {
+            let key = path_key(entry);
+            if !self
+                .members_mut()
+                .any(|member| path_key(&member.entry) == key)
+            {
+                self.skipped.push(UnifiedMember {
+                    entry: entry.clone(),
+                    provenance: Provenance::Log,
+                });
+            }
+        }
+    }
+
+    /// Mark members the offline group also has and append the ones only it has
+    /// 標記離線群組也有的成員，並加入只有離線群組才有的成員
+    fn absorb_disk(&mut self, group: &GuidGroup) {
+        for file in &group.files {
+            let key = path_key(&file.entry);
+            if let Some(member) = self
+                .members_mut()
+                .find(|member| path_key(&member.entry) == key)
+            {
+                member.provenance = Provenance::Both;
+                continue;
+            }
+            self.skipped.push(UnifiedMember {
+                entry: file.entry.clone(),
+                provenance: Provenance::Disk,
+            });
+        }
+    }
+}
+
+/// Merge the log's conflicts with the offline GUID groups so every real conflict is one row.
+/// A log conflict takes the GUID of the group holding any of its members and is keyed by it,
+/// otherwise by its loaded path; log conflicts sharing a key are merged. Groups no log conflict
+/// matched become predicted conflicts, after the log-derived ones and in GUID order.
+/// 合併 log 衝突與離線 GUID 群組，使每個實際衝突只佔一列；log 衝突任一成員所在群組的 GUID 即為其 GUID
+/// 並以此為 key，否則以載入檔案路徑為 key，key 相同的 log 衝突會合併；沒有對應 log 衝突的群組成為預測衝突，
+/// 依 GUID 排序並排在 log 衝突之後
+pub(crate) fn merge_conflict_sources(
+    log_conflicts: &[ModConflict],
+    guid_groups: &[GuidGroup],
+) -> Vec<UnifiedConflict> {
+    let group_of_path = guid_groups
+        .iter()
+        .enumerate()
+        .flat_map(|(index, group)| {
+            group
+                .files
+                .iter()
+                .map(move |file| (path_key(&file.entry), index))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut merged: Vec<UnifiedConflict> = Vec::new();
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+    let mut matched_groups = vec![false; guid_groups.len()];
+    for conflict in log_conflicts {
+        let group = std::iter::once(&conflict.loaded)
+            .chain(&conflict.skipped)
+            .find_map(|entry| group_of_path.get(&path_key(entry)).copied());
+        let key = match group {
+            Some(index) => format!("guid:{}", guid_groups[index].guid),
+            None => format!("path:{}", path_key(&conflict.loaded)),
+        };
+        match by_key.get(&key) {
+            Some(&existing) => merged[existing].absorb_log(conflict),
+            None => {
+                by_key.insert(key, merged.len());
+                let member = |entry: &ModEntry| UnifiedMember {
+                    entry: entry.clone(),
+                    provenance: Provenance::Log,
+                };
+                merged.push(UnifiedConflict {
+                    guid: group.map(|index| guid_groups[index].guid.clone()),
+                    id: conflict.id.clone(),
+                    loaded: member(&conflict.loaded),
+                    skipped: conflict.skipped.iter().map(member).collect(),
+                    predicted: false,
+                    label: None,
+                });
+            }
+        }
+        if let Some(index) = group {
+            matched_groups[index] = true;
+        }
+    }
+
+    for (index, group) in guid_groups.iter().enumerate() {
+        if matched_groups[index] {
+            let existing = by_key[&format!("guid:{}", group.guid)];
+            merged[existing].absorb_disk(group);
+        }
+    }
+
+    let unmatched = guid_groups
+        .iter()
+        .zip(&matched_groups)
+        .filter(|(_, matched)| !**matched)
+        .map(|(group, _)| group.clone())
+        .collect();
+    merged.extend(
+        mods_scan::predict_loaded(unmatched)
+            .into_iter()
+            .map(|prediction| {
+                let member = |entry: ModEntry| UnifiedMember {
+                    entry,
+                    provenance: Provenance::Disk,
+                };
+                UnifiedConflict {
+                    guid: Some(prediction.guid),
+                    id: prediction.conflict.id,
+                    loaded: member(prediction.conflict.loaded),
+                    skipped: prediction
+                        .conflict
+                        .skipped
+                        .into_iter()
+                        .map(member)
+                        .collect(),
+                    predicted: true,
+                    label: Some(Message::new("conflict.predicted")),
+                }
+            }),
+    );
+    merged
+}
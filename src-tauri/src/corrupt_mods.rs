@@ -0,0 +1,294 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    asset_index, cloud_files,
+    error::AppError,
+    links, long_path,
+    operations::CancelToken,
+    parser::{build_mod_entry, ModEntry},
+    progress::{OnProgress, Progress},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const EOCD_LEN: u64 = 22;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const ZIP64_LOCATOR_LEN: u64 = 20;
+const ZIP64_EOCD_SIGNATURE: u32 = 0x0606_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const CENTRAL_HEADER_LEN: usize = 46;
+/// The EOCD record is followed by a comment of at most this many bytes
+/// EOCD 紀錄後的註解最長為此位元組數
+const MAX_COMMENT_LEN: u64 = 0xFFFF;
+
+/// Why a mod archive cannot be loaded
+/// 模組壓縮檔無法載入的原因
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CorruptCategory {
+    /// No end-of-central-directory record: not a zip, or cut off before the end was written
+    /// 沒有 end-of-central-directory 紀錄：不是 zip，或尚未寫到結尾就中斷
+    NotAZip,
+    /// The central directory points past the file or stops partway through a header
+    /// central directory 指向檔案範圍外，或在標頭中途結束
+    TruncatedCentralDirectory,
+    /// The central directory holds a different number of entries than the EOCD record says
+    /// central directory 的 entry 數與 EOCD 紀錄不符
+    EntryCountMismatch,
+    /// The file could not be read at all
+    /// 檔案完全無法讀取
+    Unreadable,
+}
+
+/// A mod archive that will fail to load, ready to hand to the deletion flow
+/// 將會載入失敗的模組壓縮檔，可直接交給刪除流程
+#[derive(Serialize)]
+pub(crate) struct CorruptFinding {
+    pub category: CorruptCategory,
+    #[serde(rename = "mod")]
+    pub mod_entry: ModEntry,
+    pub detail: String,
+}
+
+/// Location of the central directory as declared by the end records
+/// 結尾紀錄所宣告的 central directory 位置
+struct DirectoryRecord {
+    entries: u64,
+    size: u64,
+    offset: u64,
+    /// Where the end records start; the directory must end at or before this
+    /// 結尾紀錄的起始位置；central directory 必須在此之前結束
+    end: u64,
+}
+
+/// A failed check: category plus a short technical detail
+/// 檢查失敗：類別與簡短的技術細節
+struct CheckFailure {
+    category: CorruptCategory,
+    detail: String,
+}
+
+// ───────────────────────────────────────────────
+// Archive Checks / 壓縮檔檢查
+// ───────────────────────────────────────────────
+
+fn u16_at(bytes: &[u8], at: usize) -> u64 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]]) as u64
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+fn read_exact_at(file: &mut File, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+    let mut buffer = vec![0u8; len];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Find the EOCD record in the file tail, following the ZIP64 locator when the
+/// 32-bit fields are saturated
+/// 在檔案結尾尋找 EOCD 紀錄；32 位元欄位已飽和時改讀 ZIP64 紀錄
+fn read_directory_record(file: &mut File, len: u64) -> Result<DirectoryRecord, CheckFailure> {
+    if len < EOCD_LEN {
+        return Err(CheckFailure::new(
+            CorruptCategory::NotAZip,
+            format!("file is only {} bytes", len),
+        ));
+    }
+    let tail_len = len.min(EOCD_LEN + MAX_COMMENT_LEN);
+    let tail_start = len - tail_len;
+    let tail = read_exact_at(file, tail_start, tail_len as usize)?;
+
+    // The last signature whose comment length reaches exactly the end of the file
+    // 取註解長度恰好延伸到檔案結尾的最後一個簽章
+    let at = (0..=tail.len() - EOCD_LEN as usize)
+        .rev()
+        .find(|&at| {
+            u32_at(&tail, at) == EOCD_SIGNATURE
+                && at as u64 + EOCD_LEN + u16_at(&tail, at + 20) == tail_len
+        })
+        .ok_or_else(|| {
+            CheckFailure::new(
+                CorruptCategory::NotAZip,
+                "no end of central directory record".to_string(),
+            )
+        })?;
+    let eocd_pos = tail_start + at as u64;
+    let record = DirectoryRecord {
+        entries: u16_at(&tail, at + 10),
+        size: u32_at(&tail, at + 12) as u64,
+        offset: u32_at(&tail, at + 16) as u64,
+        end: eocd_pos,
+    };
+    let saturated =
+        record.entries == 0xFFFF || record.size == 0xFFFF_FFFF || record.offset == 0xFFFF_FFFF;
+    if !saturated || eocd_pos < ZIP64_LOCATOR_LEN {
+        return Ok(record);
+    }
+
+    let locator = read_exact_at(
+        file,
+        eocd_pos - ZIP64_LOCATOR_LEN,
+        ZIP64_LOCATOR_LEN as usize,
+    )?;
+    if u32_at(&locator, 0) != ZIP64_LOCATOR_SIGNATURE {
+        return Ok(record);
+    }
+    let zip64_pos = u64_at(&locator, 8);
+    let zip64 = match read_exact_at(file, zip64_pos, 56) {
+        Ok(zip64) if u32_at(&zip64, 0) == ZIP64_EOCD_SIGNATURE => zip64,
+        _ => {
+            return Err(CheckFailure::new(
+                CorruptCategory::TruncatedCentralDirectory,
+                format!("ZIP64 end record missing at offset {}", zip64_pos),
+            ))
+        }
+    };
+    Ok(DirectoryRecord {
+        entries: u64_at(&zip64, 32),
+        size: u64_at(&zip64, 40),
+        offset: u64_at(&zip64, 48),
+        end: zip64_pos,
+    })
+}
+
+impl CheckFailure {
+    fn new(category: CorruptCategory, detail: String) -> Self {
+        CheckFailure { category, detail }
+    }
+}
+
+impl From<io::Error> for CheckFailure {
+    fn from(e: io::Error) -> Self {
+        // A short read means the declared structure runs past the end of the file
+        // 讀取不足表示宣告的結構超出檔案結尾
+        let category = match e.kind() {
+            io::ErrorKind::UnexpectedEof => CorruptCategory::TruncatedCentralDirectory,
+            _ => CorruptCategory::Unreadable,
+        };
+        CheckFailure::new(category, e.to_string())
+    }
+}
+
+/// Read the end records and walk the central directory headers without decompressing anything
+/// 讀取結尾紀錄並逐一走訪 central directory 標頭，不解壓縮任何內容
+fn check_archive(path: &Path) -> Result<(), CheckFailure> {
+    let mut file = File::open(long_path::extend(path))?;
+    let len = file.metadata()?.len();
+    let record = read_directory_record(&mut file, len)?;
+
+    let directory_end = record.offset.checked_add(record.size);
+    if directory_end.is_none_or(|end| end > record.end) {
+        return Err(CheckFailure::new(
+            CorruptCategory::TruncatedCentralDirectory,
+            format!(
+                "central directory at {} (+{} bytes) runs past the end records at {}",
+                record.offset, record.size, record.end
+            ),
+        ));
+    }
+    let directory = read_exact_at(&mut file, record.offset, record.size as usize)?;
+
+    let mut at = 0;
+    let mut found = 0u64;
+    while at < directory.len() {
+        if directory.len() - at < CENTRAL_HEADER_LEN
+            || u32_at(&directory, at) != CENTRAL_HEADER_SIGNATURE
+        {
+            return Err(CheckFailure::new(
+                CorruptCategory::TruncatedCentralDirectory,
+                format!("invalid central directory header after {} entries", found),
+            ));
+        }
+        let variable =
+            u16_at(&directory, at + 28) + u16_at(&directory, at + 30) + u16_at(&directory, at + 32);
+        at += CENTRAL_HEADER_LEN + variable as usize;
+        if at > directory.len() {
+            return Err(CheckFailure::new(
+                CorruptCategory::TruncatedCentralDirectory,
+                format!("central directory ends inside entry {}", found + 1),
+            ));
+        }
+        found += 1;
+    }
+
+    if found != record.entries {
+        return Err(CheckFailure::new(
+            CorruptCategory::EntryCountMismatch,
+            format!(
+                "central directory has {} entries, end record says {}",
+                found, record.entries
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// ───────────────────────────────────────────────
+// Folder Scan / 資料夾掃描
+// ───────────────────────────────────────────────
+
+/// Check every mod archive (per `mod_extensions`) under `<game_path>/mods` for a readable
+/// central directory, in parallel. Catches truncated downloads before the game does. Cloud
+/// placeholders are passed over unless hydrating is on, since their bytes are not local.
+/// 平行檢查 `<game_path>/mods` 下每個模組壓縮檔（依 `mod_extensions`）的 central directory 是否可讀，在遊戲之前找出下載不完整的檔案；
+/// 除非開啟下載，否則略過資料不在本機的雲端預留位置檔案
+pub(crate) fn scan_for_corrupt_mods(
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<Vec<CorruptFinding>, AppError> {
+    let archives = links::walk_files(&game_path.join("mods"), follow_links)
+        .into_iter()
+        .filter(|file| asset_index::is_mod_archive(&file.path, mod_extensions))
+        .collect::<Vec<_>>();
+
+    let total = archives.len();
+    let done = AtomicUsize::new(0);
+    let findings = archives
+        .par_iter()
+        .map(|file| {
+            if cancel.is_cancelled() {
+                return Err(AppError::OperationCancelled);
+            }
+            let finding = match cloud_files::skip_content(&file.path) {
+                true => None,
+                false => check_archive(&file.path).err(),
+            };
+            let finding = finding.map(|failure| {
+                let path = file.path.to_string_lossy();
+                CorruptFinding {
+                    category: failure.category,
+                    mod_entry: build_mod_entry(&file.path, &path, mod_extensions),
+                    detail: failure.detail,
+                }
+            });
+            let current = done.fetch_add(1, Ordering::Relaxed) + 1;
+            on_progress(Progress::new(current, total));
+            Ok(finding)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut findings = findings.into_iter().flatten().collect::<Vec<_>>();
+    findings.sort_by(|a, b| a.mod_entry.path.cmp(&b.mod_entry.path));
+    Ok(findings)
+}
@@ -0,0 +1,321 @@
+use std::{
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use regex::RegexBuilder;
+use serde::Serialize;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::{
+    disk_space,
+    error::AppError,
+    long_path,
+    path_guard::WriteMode,
+    scan::find_log_file,
+    self_check::{self, CheckResult},
+    session_progress::SessionReport,
+    storage::StorageStatus,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// How much of the game log to keep from each end
+/// 遊戲 log 頭尾各保留的大小
+const LOG_EXCERPT_BYTES: u64 = 64 * 1024;
+
+/// Paths the bundle is assembled from
+/// 組成診斷包所需的路徑
+pub(crate) struct BundleSources<'a> {
+    pub game_path: &'a Path,
+    pub app_log_dir: &'a Path,
+    pub scan_cache: &'a Path,
+    pub home_dir: Option<&'a Path>,
+    pub session: Option<&'a SessionReport>,
+    pub storage: &'a StorageStatus,
+    /// Passed to the self-check / 傳給自我檢查
+    pub write_mode: WriteMode,
+}
+
+/// Whether an expected game path exists
+/// 預期的遊戲路徑是否存在
+#[derive(Serialize)]
+struct PathCheck {
+    path: String,
+    exists: bool,
+}
+
+/// Summary of the install written as `environment.json`
+/// 寫入 `environment.json` 的安裝環境摘要
+#[derive(Serialize)]
+struct Environment<'a> {
+    app_version: &'static str,
+    os: &'static str,
+    game_variant: Option<&'static str>,
+    game_path: String,
+    log_file: Option<String>,
+    checks: Vec<PathCheck>,
+    self_check: Vec<CheckResult>,
+    /// Includes the error that made the app data dir unusable
+    /// 包含導致 app data 資料夾無法使用的錯誤
+    storage: &'a StorageStatus,
+}
+
+// ───────────────────────────────────────────────
+// Utility Function / 工具函式
+// ───────────────────────────────────────────────
+
+/// Guess the game variant from its `*_Data` folder
+/// 依 `*_Data` 資料夾推測遊戲版本
+pub(crate) fn detect_game_variant(game_path: &Path) -> Option<&'static str> {
+    [
+        ("KoikatsuSunshine_Data", "Koikatsu Sunshine"),
+        ("Koikatsu Party_Data", "Koikatsu Party"),
+        ("Koikatsu_Data", "Koikatsu"),
+        ("CharaStudio_Data", "CharaStudio"),
+    ]
+    .into_iter()
+    .find(|(dir, _)| game_path.join(dir).is_dir())
+    .map(|(_, variant)| variant)
+}
+
+/// Replace the user's home directory with `~` in both slash styles and the JSON-escaped form
+/// of backslashes, ignoring case since Windows paths do
+/// 將使用者家目錄替換成 `~`，涵蓋兩種斜線與 JSON 跳脫後的反斜線，且不分大小寫（Windows 路徑亦不分）
+fn redact(text: &str, home_dir: Option<&Path>) -> String {
+    let Some(home) = home_dir.map(long_path::display) else {
+        return text.to_string();
+    };
+    let home = home.trim_end_matches(['/', '\\']);
+    if home.is_empty() {
+        return text.to_string();
+    }
+    let backslashes = home.replace('/', "\\");
+    // Longest first, so the escaped form is not half replaced by the plain one
+    // 由長到短，避免跳脫形式被一般形式只替換一半
+    let forms = [
+        backslashes.replace('\\', "\\\\"),
+        backslashes.clone(),
+        home.replace('\\', "/"),
+    ];
+    let pattern = forms
+        .iter()
+        .map(|form| regex::escape(form))
+        .collect::<Vec<_>>()
+        .join("|");
+    match RegexBuilder::new(&pattern).case_insensitive(true).build() {
+        Ok(regex) => regex.replace_all(text, "~").into_owned(),
+        Err(_) => text.replace(home, "~"),
+    }
+}
+
+/// Read the first and last `LOG_EXCERPT_BYTES` of a file, marking the gap if any
+/// 讀取檔案頭尾各 `LOG_EXCERPT_BYTES`，中間省略處加上標記
+fn read_excerpt(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(long_path::extend(path))?;
+    let len = file.metadata()?.len();
+    if len <= LOG_EXCERPT_BYTES * 2 {
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        return Ok(String::from_utf8_lossy(&content).to_string());
+    }
+
+    let mut head = vec![0; LOG_EXCERPT_BYTES as usize];
+    file.read_exact(&mut head)?;
+    let mut tail = vec![0; LOG_EXCERPT_BYTES as usize];
+    file.seek(SeekFrom::End(-(LOG_EXCERPT_BYTES as i64)))?;
+    file.read_exact(&mut tail)?;
+    Ok(format!(
+        "{}\n\n... [{} bytes omitted] ...\n\n{}",
+        String::from_utf8_lossy(&head),
+        len - LOG_EXCERPT_BYTES * 2,
+        String::from_utf8_lossy(&tail)
+    ))
+}
+
+fn file_len(path: &Path) -> u64 {
+    fs::metadata(long_path::extend(path))
+        .map(|meta| meta.len())
+        .unwrap_or(0)
+}
+
+/// Upper bound of the bundle size, assuming nothing compresses
+/// 診斷包大小的上限估計（假設完全無法壓縮）
+fn estimate_bundle_size(sources: &BundleSources, log_file: Option<&Path>) -> u64 {
+    let app_logs = fs::read_dir(sources.app_log_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| file_len(&e.path()))
+                .sum::<u64>()
+        })
+        .unwrap_or(0);
+    let excerpt = log_file
+        .map(|path| file_len(path).min(LOG_EXCERPT_BYTES * 2))
+        .unwrap_or(0);
+    // Slack for environment.json, sideloader.cfg and zip headers
+    // 預留 environment.json、sideloader.cfg 與 zip 標頭的空間
+    app_logs + file_len(sources.scan_cache) + excerpt + 64 * 1024
+}
+
+fn bundle_path(destination: &Path) -> PathBuf {
+    let is_zip = destination
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("zip"))
+        .unwrap_or(false);
+    if is_zip {
+        return destination.to_path_buf();
+    }
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    destination.join(format!("kk-mod-manager-diagnostics-{}.zip", stamp))
+}
+
+// ───────────────────────────────────────────────
+// Bundle / 診斷包
+// ───────────────────────────────────────────────
+
+/// Zip app logs, the last scan result, environment checks with the storage status, the
+/// Sideloader config and a game log excerpt into `destination` (a `.zip` path or a folder).
+/// Home dir is redacted as `~`.
+/// 將 app log、最近一次掃描結果、環境檢查與儲存狀態、Sideloader 設定與遊戲 log 摘錄打包至 `destination`
+/// （`.zip` 路徑或資料夾）；家目錄以 `~` 取代
+pub(crate) fn create_bundle(
+    sources: &BundleSources,
+    destination: &Path,
+) -> Result<PathBuf, AppError> {
+    let zip_path = bundle_path(destination);
+    let log_file = find_log_file(&sources.game_path.to_string_lossy()).ok();
+    disk_space::ensure_space(
+        &zip_path,
+        estimate_bundle_size(sources, log_file.as_deref()),
+    )?;
+
+    if let Some(parent) = zip_path.parent() {
+        fs::create_dir_all(long_path::extend(parent))
+            .map_err(|e| AppError::io("io.create_bundle_directory", Some(parent), e))?;
+    }
+    let file = File::create(long_path::extend(&zip_path))
+        .map_err(|e| AppError::io("io.create_diagnostics_bundle", Some(&zip_path), e))?;
+    // Never leave a truncated zip behind
+    // 寫入失敗時不留下不完整的 zip
+    if let Err(e) = write_bundle(sources, log_file.as_deref(), file, &zip_path) {
+        disk_space::remove_partial(&zip_path);
+        return Err(e);
+    }
+    Ok(zip_path)
+}
+
+fn write_bundle(
+    sources: &BundleSources,
+    log_file: Option<&Path>,
+    file: File,
+    zip_path: &Path,
+) -> Result<(), AppError> {
+    let mut zip = ZipWriter::new(file);
+    let home = sources.home_dir;
+
+    let mut add = |name: &str, content: &str| -> Result<(), AppError> {
+        zip.start_file(name, SimpleFileOptions::default())
+            .and_then(|_| Ok(zip.write_all(redact(content, home).as_bytes())?))
+            .map_err(|e| AppError::io("io.write_diagnostics_bundle", Some(zip_path), e))
+    };
+
+    let game_path = sources.game_path;
+    let checks = [
+        game_path.to_path_buf(),
+        game_path.join("mods"),
+        game_path.join("BepInEx"),
+        game_path.join("BepInEx").join("plugins"),
+        game_path.join("BepInEx").join("config"),
+    ]
+    .into_iter()
+    .map(|path| PathCheck {
+        exists: path.exists(),
+        path: long_path::display(&path),
+    })
+    .collect();
+    let environment = Environment {
+        app_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        game_variant: detect_game_variant(game_path),
+        game_path: long_path::display(game_path),
+        log_file: log_file.map(long_path::display),
+        checks,
+        self_check: self_check::run_self_check(game_path, sources.write_mode),
+        storage: sources.storage,
+    };
+    let environment = serde_json::to_string_pretty(&environment)
+        .map_err(|e| AppError::io("io.serialize_environment", None, e))?;
+    add("environment.json", &environment)?;
+
+    if let Ok(entries) = fs::read_dir(sources.app_log_dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_file() {
+                if let Ok(content) = fs::read(&path) {
+                    let name = format!("app-logs/{}", entry.file_name().to_string_lossy());
+                    add(&name, &String::from_utf8_lossy(&content))?;
+                }
+            }
+        }
+    }
+
+    if let Ok(report) = fs::read_to_string(sources.scan_cache) {
+        add("last-scan.json", &report)?;
+    }
+
+    if let Some(session) = sources.session {
+        let session = serde_json::to_string_pretty(session)
+            .map_err(|e| AppError::io("io.serialize_environment", None, e))?;
+        add("session-progress.json", &session)?;
+    }
+
+    let sideloader_config = game_path
+        .join("BepInEx")
+        .join("config")
+        .join("com.bepis.bepinex.sideloader.cfg");
+    if let Ok(config) = fs::read_to_string(&sideloader_config) {
+        add("sideloader.cfg", &config)?;
+    }
+
+    if let Some(log_file) = log_file {
+        if let Ok(excerpt) = read_excerpt(log_file) {
+            add("game-log-excerpt.txt", &excerpt)?;
+        }
+    }
+
+    zip.finish()
+        .map_err(|e| AppError::io("io.finish_diagnostics_bundle", Some(zip_path), e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::redact;
+
+    #[test]
+    fn redact_covers_a_windows_home_inside_json_in_any_case() {
+        let home = Path::new(r"C:\Users\Me");
+        let json = serde_json::json!({
+            "game_path": r"c:\users\me\Koikatsu",
+            "log": "C:/USERS/ME/Koikatsu/output_log.txt",
+        })
+        .to_string();
+
+        let redacted = redact(&json, Some(home));
+
+        let value: serde_json::Value = serde_json::from_str(&redacted).unwrap();
+        assert_eq!(value["game_path"], r"~\Koikatsu");
+        assert_eq!(value["log"], "~/Koikatsu/output_log.txt");
+        assert_eq!(redact(r"at C:\Users\Me\x", Some(home)), r"at ~\x");
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{app_log, error::AppError, long_path};
+
+// ───────────────────────────────────────────────
+// Volume Queries / 磁碟區查詢
+// ───────────────────────────────────────────────
+
+/// Closest ancestor of `path` that exists, since destinations are often created later, with
+/// links resolved so a junction to another drive is measured on that drive
+/// `path` 最接近且存在的上層路徑（目的地通常尚未建立），並解析連結，使指向其他磁碟的 junction 以該磁碟計算
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    let dir = path.ancestors().find(|p| long_path::extend(p).exists())?;
+    Some(fs::canonicalize(long_path::extend(dir)).unwrap_or_else(|_| dir.to_path_buf()))
+}
+
+#[cfg(windows)]
+fn wide(path: &Path) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+    path.as_os_str().encode_wide().chain([0]).collect()
+}
+
+/// Bytes available to the current user on the volume holding `dir`
+/// 目前使用者在 `dir` 所在磁碟區可用的位元組數
+#[cfg(windows)]
+fn free_bytes(dir: &Path) -> io::Result<u64> {
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let name = wide(&long_path::extend(dir));
+    let mut available = 0u64;
+    // SAFETY: `name` is NUL-terminated and outlives the call; unused outputs may be null
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            name.as_ptr(),
+            &mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(available)
+}
+
+#[cfg(unix)]
+fn free_bytes(dir: &Path) -> io::Result<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let name = CString::new(dir.as_os_str().as_bytes())?;
+    // SAFETY: `name` is NUL-terminated and `stat` is a plain C struct
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(name.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Field widths differ between platforms
+    // 各平台欄位寬度不同
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Identity of the volume holding `path`: its mount point on Windows, device id elsewhere
+/// `path` 所在磁碟區的識別：Windows 為掛載點，其他平台為裝置 id
+#[cfg(windows)]
+fn volume_of(path: &Path) -> Option<String> {
+    use windows_sys::Win32::Storage::FileSystem::GetVolumePathNameW;
+
+    let name = wide(&long_path::extend(path));
+    let mut buffer = vec![0u16; 1024];
+    // SAFETY: `name` is NUL-terminated and `buffer` holds the length passed in
+    let ok = unsafe { GetVolumePathNameW(name.as_ptr(), buffer.as_mut_ptr(), buffer.len() as u32) };
+    if ok == 0 {
+        return None;
+    }
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..len]).to_lowercase())
+}
+
+#[cfg(unix)]
+fn volume_of(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|meta| meta.dev().to_string())
+}
+
+/// Whether two paths live on the same volume; unknown counts as different
+/// 兩個路徑是否位於同一磁碟區；無法判斷時視為不同
+pub(crate) fn same_volume(a: &Path, b: &Path) -> bool {
+    let volume = |path| existing_ancestor(path).and_then(|dir| volume_of(&dir));
+    match (volume(a), volume(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Volume holding `path` after following links, for grouping files by drive: the mount point
+/// such as `d:\` on Windows, the device id elsewhere
+/// 解析連結後 `path` 所在的磁碟區，用於依磁碟分組：Windows 為 `d:\` 等掛載點，其他平台為裝置 id
+pub(crate) fn volume_name(path: &Path) -> Option<String> {
+    let volume = volume_of(&existing_ancestor(path)?)?;
+    Some(long_path::display(Path::new(&volume)))
+}
+
+// ───────────────────────────────────────────────
+// Space Checks / 空間檢查
+// ───────────────────────────────────────────────
+
+/// Bytes that writing `sources` under `destination` will consume. A move within one
+/// volume is a rename and needs nothing extra; copies always need the full size.
+/// 將 `sources` 寫入 `destination` 需要的空間；同磁碟區內的搬移只是改名不需額外空間，複製則需完整大小
+pub(crate) fn bytes_needed(sources: &[PathBuf], destination: &Path, moving: bool) -> u64 {
+    sources
+        .iter()
+        .filter(|source| !(moving && same_volume(source, destination)))
+        .filter_map(|source| fs::metadata(long_path::extend(source)).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Bytes free on the volume holding `path`, or `None` when it cannot be determined
+/// `path` 所在磁碟區的可用位元組數；無法取得時為 `None`
+pub(crate) fn available_bytes(path: &Path) -> Option<u64> {
+    free_bytes(&existing_ancestor(path)?).ok()
+}
+
+/// Fail with `InsufficientSpace` if the volume of `destination` has less than `needed`
+/// bytes free. When free space cannot be determined the write is allowed to proceed.
+/// `destination` 所在磁碟區可用空間少於 `needed` 時回傳 `InsufficientSpace`；無法取得可用空間時允許繼續
+pub(crate) fn ensure_space(destination: &Path, needed: u64) -> Result<(), AppError> {
+    if needed == 0 {
+        return Ok(());
+    }
+    let Some(dir) = existing_ancestor(destination) else {
+        return Ok(());
+    };
+    match free_bytes(&dir) {
+        Ok(available) if available < needed => Err(AppError::InsufficientSpace {
+            path: long_path::display(destination),
+            needed,
+            available,
+        }),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            app_log::write(&format!(
+                "free space check skipped for {}: {}",
+                long_path::display(&dir),
+                e
+            ));
+            Ok(())
+        }
+    }
+}
+
+/// Best-effort removal of a file left behind by a failed write
+/// 盡力移除寫入失敗後殘留的檔案
+pub(crate) fn remove_partial(path: &Path) {
+    match fs::remove_file(long_path::extend(path)) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => app_log::write(&format!(
+            "failed to remove partial output {}: {}",
+            long_path::display(path),
+            e
+        )),
+    }
+}
+
+/// Human-readable size for messages, e.g. `1.5 GB`
+/// 訊息用的易讀大小，例如 `1.5 GB`
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{app_log, junk_files, long_path};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// A folder removed because a removal left it empty
+/// 因檔案移除後變空而刪除的資料夾
+#[derive(Serialize)]
+pub(crate) struct PrunedDir {
+    pub path: String,
+    /// OS metadata files such as `Thumbs.db` deleted with it
+    /// 一併刪除的系統產生檔，例如 `Thumbs.db`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub junk_removed: Vec<String>,
+}
+
+// ───────────────────────────────────────────────
+// Pruning / 清除空資料夾
+// ───────────────────────────────────────────────
+
+/// Files left in `dir` that would keep it from counting as empty, or None when it cannot be
+/// read; with `ignore_junk`, OS metadata files are returned instead as files to delete
+/// `dir` 中剩餘的檔案；無法讀取時為 None。指定 `ignore_junk` 時，系統產生檔不算內容，改為回傳待刪除的檔案
+fn removable_junk(dir: &Path, ignore_junk: bool) -> Option<Vec<PathBuf>> {
+    let mut junk = Vec::new();
+    for entry in fs::read_dir(long_path::extend(dir)).ok()? {
+        let entry = entry.ok()?;
+        let path = dir.join(entry.file_name());
+        let is_file = entry.file_type().ok()?.is_file();
+        match is_file && ignore_junk && junk_files::is_os_metadata(&path) {
+            true => junk.push(path),
+            false => return None,
+        }
+    }
+    Some(junk)
+}
+
+/// Remove `dir` and the junk in it, logging a failure
+/// 刪除 `dir` 及其中的系統產生檔，失敗時記錄
+fn remove(dir: &Path, junk: &[PathBuf]) -> bool {
+    let result = junk
+        .iter()
+        .try_for_each(|file| fs::remove_file(long_path::extend(file)))
+        .and_then(|()| fs::remove_dir(long_path::extend(dir)));
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            app_log::write(&format!(
+                "empty folder {} kept: {}",
+                long_path::display(dir),
+                e
+            ));
+            false
+        }
+    }
+}
+
+/// After `removed` files are gone, walk up from each one's folder and delete the folders that
+/// are now empty, stopping at the first that is not. Only folders strictly inside one of
+/// `mod_roots` are touched, compared with links resolved, so a root and anything outside it
+/// always stay. With `ignore_junk`, folders holding nothing but OS metadata such as
+/// `Thumbs.db` count as empty and that junk is deleted with them. Best-effort: a folder that
+/// cannot be removed is logged and ends its chain. Each chain is listed from its deepest folder
+/// up.
+/// `removed` 的檔案移除後，從各檔案所在資料夾往上刪除已變空的資料夾，遇到非空資料夾即停止；
+/// 只處理位於 `mod_roots` 之一內部的資料夾（解析連結後比對），根目錄本身與其外的資料夾一律保留。
+/// 指定 `ignore_junk` 時，只含 `Thumbs.db` 等系統產生檔的資料夾視為空資料夾，並一併刪除這些檔案。
+/// 盡力而為：無法刪除的資料夾會記錄並結束該路徑的處理；每條路徑由最深的資料夾往上列出
+pub(crate) fn prune_empty_dirs(
+    removed: &[&Path],
+    mod_roots: &[PathBuf],
+    ignore_junk: bool,
+) -> Vec<PrunedDir> {
+    let roots = mod_roots
+        .iter()
+        .filter_map(|root| fs::canonicalize(long_path::extend(root)).ok())
+        .collect::<Vec<_>>();
+    let mut starts = removed
+        .iter()
+        .filter_map(|path| path.parent())
+        .filter_map(|parent| fs::canonicalize(long_path::extend(parent)).ok())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    starts.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+
+    let mut pruned = Vec::new();
+    for start in starts {
+        let Some(root) = roots.iter().find(|root| start.starts_with(root)) else {
+            continue;
+        };
+        let mut dir = start.as_path();
+        while dir != root && dir.starts_with(root) {
+            let Some(junk) = removable_junk(dir, ignore_junk) else {
+                break;
+            };
+            if !remove(dir, &junk) {
+                break;
+            }
+            app_log::write(&format!("removed empty folder {}", long_path::display(dir)));
+            pruned.push(PrunedDir {
+                path: long_path::display(dir),
+                junk_removed: junk.iter().map(|file| long_path::display(file)).collect(),
+            });
+            let Some(parent) = dir.parent() else {
+                break;
+            };
+            dir = parent;
+        }
+    }
+    pruned
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::prune_empty_dirs;
+    use crate::test_support::{SyntheticInstall, TestManifest};
+
+    #[test]
+    fn pruning_stops_at_junk_unless_opted_in_and_never_removes_the_root() {
+        let first = SyntheticInstall::new();
+        let second = SyntheticInstall::new();
+        for install in [&first, &second] {
+            let file = install.add_zipmod("old stuff/author x/a.zipmod", &TestManifest::new("a"));
+            install.add_file("mods/old stuff/Thumbs.db", b"cache");
+            fs::remove_file(&file).unwrap();
+        }
+        let removed = |install: &SyntheticInstall| install.mod_path("old stuff/author x/a.zipmod");
+        let roots = |install: &SyntheticInstall| vec![install.mod_path("")];
+
+        let kept = prune_empty_dirs(&[&removed(&first)], &roots(&first), false);
+        let pruned = prune_empty_dirs(&[&removed(&second)], &roots(&second), true);
+
+        assert_eq!(kept.len(), 1);
+        assert!(!first.mod_path("old stuff/author x").exists());
+        assert!(first.mod_path("old stuff/Thumbs.db").exists());
+        assert_eq!(pruned.len(), 2);
+        assert_eq!(pruned[1].junk_removed.len(), 1);
+        assert!(!second.mod_path("old stuff").exists());
+        assert!(second.mod_path("").exists());
+    }
+}
@@ -0,0 +1,621 @@
+use std::{fmt, path::Path};
+
+use serde::{Serialize, Serializer};
+
+use crate::{disk_space::format_bytes, long_path, messages::Message};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Error returned by every command. Serialized as `{ code, key, params, message, path?, detail? }`
+/// (plus `needed`/`available` bytes for `insufficient_space`);
+/// the frontend branches on `code`, translates via `key`/`params`, and falls back to the English `message`.
+/// 所有指令回傳的錯誤；序列化為 `{ code, key, params, message, path?, detail? }`
+/// （`insufficient_space` 另帶 `needed`/`available` 位元組數）。
+/// 前端依 `code` 判斷、以 `key`/`params` 翻譯，無翻譯時顯示英文 `message`
+#[derive(Debug, Clone)]
+pub enum AppError {
+    LogNotFound {
+        game_path: String,
+    },
+    LogUnreadable {
+        path: String,
+        detail: String,
+    },
+    ZipInvalid {
+        path: Option<String>,
+        detail: String,
+    },
+    ManifestMissing {
+        path: Option<String>,
+    },
+    ArchiveInvalid {
+        path: String,
+        format: &'static str,
+        detail: String,
+    },
+    ArchiveFormatUnsupported {
+        path: String,
+        format: &'static str,
+    },
+    ManifestParseFailed {
+        path: Option<String>,
+        detail: String,
+    },
+    DeleteFailed {
+        path: String,
+        detail: String,
+    },
+    PathNotAllowed {
+        path: String,
+    },
+    /// `found` is a `Sideloader Modpack` folder elsewhere under the game folder and
+    /// `suggested` the game folder it implies
+    /// `found` 為遊戲資料夾中其他位置的 `Sideloader Modpack` 資料夾，`suggested` 為據此推測的遊戲資料夾
+    ModsDirMissing {
+        path: String,
+        found: Option<String>,
+        suggested: Option<String>,
+    },
+    ModpackProtected {
+        path: String,
+        count: usize,
+    },
+    /// `count` files of a batch are pinned, `path` being the first
+    /// 批次中有 `count` 個檔案已釘選，`path` 為第一個
+    Pinned {
+        path: String,
+        count: usize,
+    },
+    FileChanged {
+        path: String,
+        count: usize,
+    },
+    ReadOnly {
+        path: String,
+        count: usize,
+    },
+    /// Another process, such as antivirus or the search indexer, still held the file after
+    /// `retries` retries
+    /// 重試 `retries` 次後，檔案仍被防毒軟體或搜尋索引服務等其他程序佔用
+    FileLocked {
+        path: String,
+        retries: u32,
+    },
+    GameRunning {
+        detail: Option<String>,
+    },
+    OperationCancelled,
+    InternalPanic {
+        incident_id: Option<String>,
+        detail: String,
+    },
+    NotManagedPlugin {
+        path: String,
+    },
+    PluginInfoMissing {
+        path: String,
+    },
+    InvalidInput {
+        message: Message,
+    },
+    InsufficientSpace {
+        path: String,
+        needed: u64,
+        available: u64,
+    },
+    ScanExpired {
+        operation_id: String,
+    },
+    SafeModeRefused {
+        path: String,
+    },
+    ReadOnlyMode {
+        path: String,
+    },
+    PathBusy {
+        path: String,
+        operation_id: String,
+    },
+    /// A caller-supplied operation id belongs to an operation that is still running
+    /// 呼叫端指定的作業 id 屬於仍在進行中的作業
+    OperationIdInUse {
+        operation_id: String,
+    },
+    NoArchiveHandler {
+        path: String,
+    },
+    /// The file is a cloud placeholder and hydrating is off; see `cloud_files`
+    /// 檔案為雲端預留位置且未開啟下載；見 `cloud_files`
+    CloudPlaceholder {
+        path: String,
+    },
+    Io {
+        key: &'static str,
+        path: Option<String>,
+        detail: String,
+    },
+}
+
+/// Wire format of AppError
+/// AppError 的序列化格式
+#[derive(Serialize)]
+struct AppErrorPayload<'a> {
+    code: &'static str,
+    #[serde(flatten)]
+    text: Message,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    incident_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    needed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    available: Option<u64>,
+}
+
+// ───────────────────────────────────────────────
+// Construction & Accessors / 建構與存取
+// ───────────────────────────────────────────────
+
+impl AppError {
+    /// Wrap an I/O-style failure; `key` is the `io.*` catalog entry naming what was being done
+    /// 包裝 I/O 類錯誤；`key` 為說明正在進行動作的 `io.*` 訊息 key
+    pub fn io(key: &'static str, path: Option<&Path>, err: impl fmt::Display) -> Self {
+        AppError::Io {
+            key,
+            path: path.map(long_path::display),
+            detail: err.to_string(),
+        }
+    }
+
+    /// Attach the archive path to errors raised before the path was known
+    /// 為尚未帶有路徑的壓縮檔錯誤補上路徑
+    pub fn with_archive_path(self, archive: &str) -> Self {
+        let fill = |path: Option<String>| path.or_else(|| Some(archive.to_string()));
+        match self {
+            AppError::ZipInvalid { path, detail } => AppError::ZipInvalid {
+                path: fill(path),
+                detail,
+            },
+            AppError::ManifestMissing { path } => AppError::ManifestMissing { path: fill(path) },
+            AppError::ManifestParseFailed { path, detail } => AppError::ManifestParseFailed {
+                path: fill(path),
+                detail,
+            },
+            other => other,
+        }
+    }
+
+    /// Stable machine-readable code; part of the frontend contract
+    /// 穩定的錯誤代碼，屬於前端介面約定
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::LogNotFound { .. } => "log_not_found",
+            AppError::LogUnreadable { .. } => "log_unreadable",
+            AppError::ZipInvalid { .. } => "zip_invalid",
+            AppError::ManifestMissing { .. } => "manifest_missing",
+            AppError::ArchiveInvalid { .. } => "archive_invalid",
+            AppError::ArchiveFormatUnsupported { .. } => "archive_format_unsupported",
+            AppError::ManifestParseFailed { .. } => "manifest_parse_failed",
+            AppError::DeleteFailed { .. } => "delete_failed",
+            AppError::PathNotAllowed { .. } => "path_not_allowed",
+            AppError::ModsDirMissing { .. } => "mods_dir_missing",
+            AppError::ModpackProtected { .. } => "modpack_protected",
+            AppError::Pinned { .. } => "pinned",
+            AppError::FileChanged { .. } => "file_changed",
+            AppError::ReadOnly { .. } => "read_only",
+            AppError::FileLocked { .. } => "file_locked",
+            AppError::GameRunning { .. } => "game_running",
+            AppError::OperationCancelled => "operation_cancelled",
+            AppError::InternalPanic { .. } => "internal_panic",
+            AppError::NotManagedPlugin { .. } => "not_managed_plugin",
+            AppError::PluginInfoMissing { .. } => "plugin_info_missing",
+            AppError::InvalidInput { .. } => "invalid_input",
+            AppError::InsufficientSpace { .. } => "insufficient_space",
+            AppError::ScanExpired { .. } => "scan_expired",
+            AppError::SafeModeRefused { .. } => "safe_mode_refused",
+            AppError::ReadOnlyMode { .. } => "read_only_mode",
+            AppError::PathBusy { .. } => "path_busy",
+            AppError::OperationIdInUse { .. } => "operation_id_in_use",
+            AppError::NoArchiveHandler { .. } => "no_archive_handler",
+            AppError::CloudPlaceholder { .. } => "cloud_placeholder",
+            AppError::Io { .. } => "io_failed",
+        }
+    }
+
+    /// Translatable message for this error; every variant goes through the catalog
+    /// 此錯誤的可翻譯訊息；所有 variant 都經由訊息目錄
+    pub fn message(&self) -> Message {
+        match self {
+            AppError::LogNotFound { game_path } => {
+                Message::new("log.not_found").with("path", game_path)
+            }
+            AppError::LogUnreadable { path, detail } => Message::new("log.unreadable")
+                .with("path", path)
+                .with("detail", detail),
+            AppError::ZipInvalid { path, detail } => Message::new("zip.invalid")
+                .with_opt("path", path.as_deref())
+                .with("detail", detail),
+            AppError::ManifestMissing { path } => {
+                Message::new("manifest.missing").with_opt("path", path.as_deref())
+            }
+            AppError::ArchiveInvalid {
+                path,
+                format,
+                detail,
+            } => Message::new("archive.invalid")
+                .with("path", path)
+                .with("format", format)
+                .with("detail", detail),
+            AppError::ArchiveFormatUnsupported { path, format } => {
+                Message::new("archive.format_unsupported")
+                    .with("path", path)
+                    .with("format", format)
+            }
+            AppError::ManifestParseFailed { path, detail } => Message::new("manifest.parse_failed")
+                .with_opt("path", path.as_deref())
+                .with("detail", detail),
+            AppError::DeleteFailed { path, detail } => Message::new("delete.failed")
+                .with("path", path)
+                .with("detail", detail),
+            AppError::PathNotAllowed { path } => {
+                Message::new("path.not_allowed").with("path", path)
+            }
+            AppError::ModsDirMissing {
+                path,
+                found: Some(found),
+                suggested,
+            } => Message::new("path.mods_missing_elsewhere")
+                .with("path", path)
+                .with("found", found)
+                .with_opt("suggested", suggested.as_deref()),
+            AppError::ModsDirMissing { path, .. } => {
+                Message::new("path.mods_missing").with("path", path)
+            }
+            AppError::ModpackProtected { path, count } => Message::new("delete.modpack_protected")
+                .with("path", path)
+                .with("count", count),
+            AppError::Pinned { path, count } => Message::new("delete.pinned")
+                .with("path", path)
+                .with("count", count),
+            AppError::FileChanged { path, count } => Message::new("delete.file_changed")
+                .with("path", path)
+                .with("count", count),
+            AppError::FileLocked { path, retries } => Message::new("delete.file_locked")
+                .with("path", path)
+                .with("retries", retries),
+            AppError::ReadOnly { path, count } => Message::new("delete.read_only")
+                .with("path", path)
+                .with("count", count),
+            AppError::GameRunning { detail } => {
+                Message::new("game.running").with_opt("detail", detail.as_deref())
+            }
+            AppError::OperationCancelled => Message::new("operation.cancelled"),
+            AppError::InternalPanic {
+                incident_id: Some(id),
+                detail,
+            } => Message::new("internal.panic_incident")
+                .with("incident_id", id)
+                .with("detail", detail),
+            AppError::InternalPanic { detail, .. } => {
+                Message::new("internal.panic").with("detail", detail)
+            }
+            AppError::NotManagedPlugin { path } => {
+                Message::new("plugin.not_managed").with("path", path)
+            }
+            AppError::PluginInfoMissing { path } => {
+                Message::new("plugin.info_missing").with("path", path)
+            }
+            AppError::InvalidInput { message } => message.clone(),
+            AppError::InsufficientSpace {
+                path,
+                needed,
+                available,
+            } => Message::new("disk.insufficient_space")
+                .with("path", path)
+                .with("needed", format_bytes(*needed))
+                .with("available", format_bytes(*available)),
+            AppError::ScanExpired { operation_id } => {
+                Message::new("scan.expired").with("operation_id", operation_id)
+            }
+            AppError::SafeModeRefused { path } => {
+                Message::new("delete.safe_mode_refused").with("path", path)
+            }
+            AppError::ReadOnlyMode { path } => {
+                Message::new("operation.read_only_mode").with("path", path)
+            }
+            AppError::PathBusy { path, operation_id } => Message::new("operation.path_busy")
+                .with("path", path)
+                .with("operation_id", operation_id),
+            AppError::OperationIdInUse { operation_id } => {
+                Message::new("operation.id_in_use").with("operation_id", operation_id)
+            }
+            AppError::NoArchiveHandler { path } => {
+                Message::new("open.no_archive_handler").with("path", path)
+            }
+            AppError::CloudPlaceholder { path } => {
+                Message::new("path.cloud_placeholder").with("path", path)
+            }
+            AppError::Io { key, path, detail } => Message::new(key)
+                .with_opt("path", path.as_deref())
+                .with("detail", detail),
+        }
+    }
+
+    fn path(&self) -> Option<&str> {
+        match self {
+            AppError::LogNotFound { game_path: path }
+            | AppError::LogUnreadable { path, .. }
+            | AppError::DeleteFailed { path, .. }
+            | AppError::PathNotAllowed { path }
+            | AppError::ModsDirMissing { path, .. }
+            | AppError::ModpackProtected { path, .. }
+            | AppError::Pinned { path, .. }
+            | AppError::FileChanged { path, .. }
+            | AppError::ReadOnly { path, .. }
+            | AppError::FileLocked { path, .. }
+            | AppError::NotManagedPlugin { path }
+            | AppError::PluginInfoMissing { path }
+            | AppError::SafeModeRefused { path }
+            | AppError::ReadOnlyMode { path }
+            | AppError::PathBusy { path, .. }
+            | AppError::NoArchiveHandler { path }
+            | AppError::CloudPlaceholder { path }
+            | AppError::ArchiveInvalid { path, .. }
+            | AppError::ArchiveFormatUnsupported { path, .. }
+            | AppError::InsufficientSpace { path, .. } => Some(path),
+            AppError::ZipInvalid { path, .. }
+            | AppError::ManifestMissing { path }
+            | AppError::ManifestParseFailed { path, .. }
+            | AppError::Io { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+
+    fn detail(&self) -> Option<&str> {
+        match self {
+            AppError::LogUnreadable { detail, .. }
+            | AppError::ZipInvalid { detail, .. }
+            | AppError::ArchiveInvalid { detail, .. }
+            | AppError::ManifestParseFailed { detail, .. }
+            | AppError::DeleteFailed { detail, .. }
+            | AppError::InternalPanic { detail, .. }
+            | AppError::Io { detail, .. } => Some(detail),
+            AppError::GameRunning { detail } => detail.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message().render())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = self.message();
+        let (needed, available) = match self {
+            AppError::InsufficientSpace {
+                needed, available, ..
+            } => (Some(*needed), Some(*available)),
+            _ => (None, None),
+        };
+        AppErrorPayload {
+            code: self.code(),
+            message: text.render(),
+            text,
+            path: self.path(),
+            detail: self.detail(),
+            incident_id: match self {
+                AppError::InternalPanic { incident_id, .. } => incident_id.as_deref(),
+                _ => None,
+            },
+            needed,
+            available,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::AppError;
+    use crate::messages::Message;
+
+    #[test]
+    fn every_variant_serializes_with_its_code() {
+        let path = || "C:/Game/mods/a.zipmod".to_string();
+        let detail = || "detail".to_string();
+        let cases = [
+            (AppError::LogNotFound { game_path: path() }, "log_not_found"),
+            (
+                AppError::LogUnreadable {
+                    path: path(),
+                    detail: detail(),
+                },
+                "log_unreadable",
+            ),
+            (
+                AppError::ZipInvalid {
+                    path: Some(path()),
+                    detail: detail(),
+                },
+                "zip_invalid",
+            ),
+            (AppError::ManifestMissing { path: None }, "manifest_missing"),
+            (
+                AppError::ArchiveInvalid {
+                    path: path(),
+                    format: "7z",
+                    detail: detail(),
+                },
+                "archive_invalid",
+            ),
+            (
+                AppError::ArchiveFormatUnsupported {
+                    path: path(),
+                    format: "rar",
+                },
+                "archive_format_unsupported",
+            ),
+            (
+                AppError::ManifestParseFailed {
+                    path: Some(path()),
+                    detail: detail(),
+                },
+                "manifest_parse_failed",
+            ),
+            (
+                AppError::DeleteFailed {
+                    path: path(),
+                    detail: detail(),
+                },
+                "delete_failed",
+            ),
+            (
+                AppError::PathNotAllowed { path: path() },
+                "path_not_allowed",
+            ),
+            (
+                AppError::ModsDirMissing {
+                    path: path(),
+                    found: Some(path()),
+                    suggested: Some(path()),
+                },
+                "mods_dir_missing",
+            ),
+            (
+                AppError::ModpackProtected {
+                    path: path(),
+                    count: 2,
+                },
+                "modpack_protected",
+            ),
+            (
+                AppError::Pinned {
+                    path: path(),
+                    count: 1,
+                },
+                "pinned",
+            ),
+            (
+                AppError::FileChanged {
+                    path: path(),
+                    count: 1,
+                },
+                "file_changed",
+            ),
+            (
+                AppError::ReadOnly {
+                    path: path(),
+                    count: 1,
+                },
+                "read_only",
+            ),
+            (
+                AppError::FileLocked {
+                    path: path(),
+                    retries: 3,
+                },
+                "file_locked",
+            ),
+            (
+                AppError::GameRunning {
+                    detail: Some("Koikatu.exe".to_string()),
+                },
+                "game_running",
+            ),
+            (AppError::OperationCancelled, "operation_cancelled"),
+            (
+                AppError::InternalPanic {
+                    incident_id: Some("1-0001".to_string()),
+                    detail: detail(),
+                },
+                "internal_panic",
+            ),
+            (
+                AppError::NotManagedPlugin { path: path() },
+                "not_managed_plugin",
+            ),
+            (
+                AppError::PluginInfoMissing { path: path() },
+                "plugin_info_missing",
+            ),
+            (
+                AppError::InvalidInput {
+                    message: Message::new("input.no_conflict_scan"),
+                },
+                "invalid_input",
+            ),
+            (
+                AppError::InsufficientSpace {
+                    path: path(),
+                    needed: 2048,
+                    available: 1024,
+                },
+                "insufficient_space",
+            ),
+            (
+                AppError::ScanExpired {
+                    operation_id: "op".to_string(),
+                },
+                "scan_expired",
+            ),
+            (
+                AppError::SafeModeRefused { path: path() },
+                "safe_mode_refused",
+            ),
+            (AppError::ReadOnlyMode { path: path() }, "read_only_mode"),
+            (
+                AppError::PathBusy {
+                    path: path(),
+                    operation_id: "op".to_string(),
+                },
+                "path_busy",
+            ),
+            (
+                AppError::OperationIdInUse {
+                    operation_id: "op".to_string(),
+                },
+                "operation_id_in_use",
+            ),
+            (
+                AppError::NoArchiveHandler { path: path() },
+                "no_archive_handler",
+            ),
+            (
+                AppError::CloudPlaceholder { path: path() },
+                "cloud_placeholder",
+            ),
+            (
+                AppError::io(
+                    "io.write_app_data_dir",
+                    Some(Path::new("C:/AppData")),
+                    "denied",
+                ),
+                "io_failed",
+            ),
+        ];
+
+        for (error, code) in cases {
+            let json = serde_json::to_value(&error).unwrap();
+            assert_eq!(json["code"], code);
+            assert!(json["key"].is_string());
+            // Every placeholder of the template was filled in
+            // 範本中的所有佔位符都已填入
+            let message = json["message"].as_str().unwrap();
+            assert!(!message.is_empty() && !message.contains('{'), "{}", message);
+        }
+    }
+}
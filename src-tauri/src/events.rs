@@ -0,0 +1,123 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::{error::AppError, operations::OperationGuard, progress::Progress};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Single channel every long operation reports on
+/// 所有長時間作業共用的事件頻道
+const PROGRESS_EVENT: &str = "operation-progress";
+
+/// Minimum gap between two non-final events of one operation (~10/sec)
+/// 同一作業兩次非最終事件之間的最短間隔（約每秒 10 次）
+const MIN_EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Payload of `operation-progress`; the last event of every operation has `done: true`
+/// `operation-progress` 的事件資料；每個作業的最後一個事件必定為 `done: true`
+#[derive(Serialize, Clone)]
+struct ProgressEvent<'a> {
+    operation_id: &'a str,
+    kind: &'static str,
+    current: usize,
+    total: usize,
+    message: Option<String>,
+    done: bool,
+    cancelled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a AppError>,
+}
+
+struct ReporterState {
+    last_emit: Option<Instant>,
+    latest: Progress,
+}
+
+/// Throttled emitter of `operation-progress` events for one registered operation.
+/// Dropping it without `finish` (e.g. on panic) still sends the final `done` event.
+/// 單一作業的 `operation-progress` 節流發送器；未呼叫 `finish` 就被 drop（例如 panic）時仍會送出最終的 `done` 事件
+pub(crate) struct ProgressReporter<'a> {
+    app: &'a AppHandle,
+    operation: &'a OperationGuard<'a>,
+    state: Mutex<ReporterState>,
+    finished: AtomicBool,
+}
+
+// ───────────────────────────────────────────────
+// Reporting / 進度回報
+// ───────────────────────────────────────────────
+
+impl<'a> ProgressReporter<'a> {
+    pub fn new(app: &'a AppHandle, operation: &'a OperationGuard<'a>) -> Self {
+        ProgressReporter {
+            app,
+            operation,
+            state: Mutex::new(ReporterState {
+                last_emit: None,
+                latest: Progress::new(0, 0),
+            }),
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    fn emit(&self, progress: &Progress, done: bool, error: Option<&AppError>) {
+        let cancelled = self.operation.token.is_cancelled()
+            || matches!(error, Some(AppError::OperationCancelled));
+        let _ = self.app.emit(
+            PROGRESS_EVENT,
+            ProgressEvent {
+                operation_id: &self.operation.id,
+                kind: self.operation.kind,
+                current: progress.current,
+                total: progress.total,
+                message: progress.message.clone(),
+                done,
+                cancelled,
+                error,
+            },
+        );
+    }
+
+    /// Record progress; emitted at most every `MIN_EMIT_INTERVAL`, except when complete
+    /// 記錄進度；除了完成時以外，最多每 `MIN_EMIT_INTERVAL` 送出一次
+    pub fn update(&self, progress: Progress) {
+        let mut state = self.state.lock().unwrap();
+        let due = state
+            .last_emit
+            .map(|last| last.elapsed() >= MIN_EMIT_INTERVAL)
+            .unwrap_or(true);
+        if due || progress.current >= progress.total {
+            self.emit(&progress, false, None);
+            state.last_emit = Some(Instant::now());
+        }
+        state.latest = progress;
+    }
+
+    /// Send the final `done: true` event, carrying the error if the operation failed
+    /// 送出最終的 `done: true` 事件；失敗時附帶錯誤
+    pub fn finish(&self, error: Option<&AppError>) {
+        if self.finished.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        // May run during unwinding, so tolerate a poisoned lock
+        // 可能在 panic 展開時執行，因此容許已 poisoned 的鎖
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.emit(&state.latest, true, error);
+    }
+}
+
+impl Drop for ProgressReporter<'_> {
+    fn drop(&mut self) {
+        self.finish(None);
+    }
+}
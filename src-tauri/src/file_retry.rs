@@ -0,0 +1,179 @@
+use std::{io, path::Path, thread, time::Duration};
+
+use crate::{error::AppError, long_path};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Pauses before each retry, about 1.4 s in all: long enough for Windows Defender or the
+/// search indexer to let go of a freshly written file
+/// 每次重試前的等待時間，合計約 1.4 秒；足以讓 Windows Defender 或搜尋索引服務釋放剛寫入的檔案
+const RETRY_DELAYS_MS: [u64; 3] = [200, 400, 800];
+
+/// Win32 `ERROR_ACCESS_DENIED`, `ERROR_SHARING_VIOLATION` and `ERROR_LOCK_VIOLATION`
+/// Win32 的 `ERROR_ACCESS_DENIED`、`ERROR_SHARING_VIOLATION` 與 `ERROR_LOCK_VIOLATION`
+const WIN32_ACCESS_DENIED: i32 = 5;
+const WIN32_SHARING_VIOLATION: i32 = 32;
+const WIN32_LOCK_VIOLATION: i32 = 33;
+
+/// What one failed attempt says about trying again
+/// 單次失敗對是否重試的意義
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Failure {
+    /// Another process has the file open / 其他程序正開啟該檔案
+    Locked,
+    /// Access denied, which a scanner holding the file also causes
+    /// 存取被拒；掃描程式佔用檔案時也會如此
+    Denied,
+    /// Not worth retrying, e.g. missing or on another volume / 不值得重試，例如檔案不存在或位於其他磁碟區
+    Other,
+}
+
+/// Errors `retry` can tell transient failures of apart
+/// `retry` 能從中分辨暫時性失敗的錯誤
+pub(crate) trait Transient {
+    fn failure(&self) -> Failure;
+}
+
+/// The last error once `retry` gave up, how many retries it made and whether any attempt
+/// saw the file locked
+/// `retry` 放棄時的最後一個錯誤、已重試的次數，以及是否有任何一次遇到檔案被鎖定
+pub(crate) struct Exhausted<E> {
+    pub error: E,
+    pub retries: u32,
+    pub locked: bool,
+}
+
+// ───────────────────────────────────────────────
+// Classification / 分類
+// ───────────────────────────────────────────────
+
+fn win32_failure(code: i32) -> Failure {
+    match code {
+        WIN32_SHARING_VIOLATION | WIN32_LOCK_VIOLATION => Failure::Locked,
+        WIN32_ACCESS_DENIED => Failure::Denied,
+        _ => Failure::Other,
+    }
+}
+
+impl Transient for io::Error {
+    fn failure(&self) -> Failure {
+        #[cfg(windows)]
+        if let Some(code) = self.raw_os_error() {
+            return win32_failure(code);
+        }
+        match self.kind() {
+            io::ErrorKind::PermissionDenied => Failure::Denied,
+            _ => Failure::Other,
+        }
+    }
+}
+
+impl Transient for trash::Error {
+    fn failure(&self) -> Failure {
+        match self {
+            // An HRESULT; `HRESULT_FROM_WIN32` puts Win32 codes under facility 7
+            // 為 HRESULT；`HRESULT_FROM_WIN32` 將 Win32 錯誤碼置於 facility 7
+            trash::Error::Os { code, .. } if (*code as u32) >> 16 == 0x8007 => {
+                win32_failure(code & 0xFFFF)
+            }
+            #[cfg(all(
+                unix,
+                not(target_os = "macos"),
+                not(target_os = "ios"),
+                not(target_os = "android")
+            ))]
+            trash::Error::FileSystem { source, .. } => source.failure(),
+            _ => Failure::Other,
+        }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Retrying / 重試
+// ───────────────────────────────────────────────
+
+/// Run `attempt`, retrying after each pause of `RETRY_DELAYS_MS` while it fails with a locked
+/// file or access denied. Callers check beforehand that the file is writable and not
+/// read-only, so those failures are most likely a scanner briefly holding the file. Returns
+/// the value and the number of retries it took.
+/// 執行 `attempt`；因檔案被鎖定或存取被拒而失敗時，依 `RETRY_DELAYS_MS` 等待後重試。呼叫端已事先確認檔案可寫入且非唯讀，
+/// 因此這類失敗多半是掃描程式短暫佔用檔案；回傳結果與所需的重試次數
+pub(crate) fn retry<T, E: Transient>(
+    mut attempt: impl FnMut() -> Result<T, E>,
+) -> Result<(T, u32), Exhausted<E>> {
+    let mut locked = false;
+    let mut retries = 0;
+    loop {
+        let error = match attempt() {
+            Ok(value) => return Ok((value, retries)),
+            Err(error) => error,
+        };
+        let failure = error.failure();
+        locked |= failure == Failure::Locked;
+        match (failure, RETRY_DELAYS_MS.get(retries as usize)) {
+            (Failure::Locked | Failure::Denied, Some(delay)) => {
+                thread::sleep(Duration::from_millis(*delay));
+                retries += 1;
+            }
+            _ => {
+                return Err(Exhausted {
+                    error,
+                    retries,
+                    locked,
+                })
+            }
+        }
+    }
+}
+
+impl<E> Exhausted<E> {
+    /// `FileLocked` when an attempt saw the file locked by another process, otherwise
+    /// `other` of the last error, as access denied on every attempt is a real permissions
+    /// problem
+    /// 有任何一次遇到檔案被其他程序鎖定時為 `FileLocked`，否則為 `other` 處理最後一個錯誤的結果；
+    /// 每次都存取被拒表示確實是權限問題
+    pub(crate) fn into_error(self, path: &Path, other: impl FnOnce(E) -> AppError) -> AppError {
+        match self.locked {
+            true => AppError::FileLocked {
+                path: long_path::display(path),
+                retries: self.retries,
+            },
+            false => other(self.error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, path::Path};
+
+    use super::retry;
+    use crate::error::AppError;
+
+    #[test]
+    fn retry_counts_retries_and_keeps_denied_access_a_permissions_error() {
+        let denied = || io::Error::from(io::ErrorKind::PermissionDenied);
+        let mut attempts = 0;
+        let succeeded = retry(|| {
+            attempts += 1;
+            match attempts {
+                1 | 2 => Err(denied()),
+                _ => Ok(attempts),
+            }
+        });
+        assert!(matches!(succeeded, Ok((3, 2))));
+
+        let exhausted = retry(|| Err::<(), _>(denied())).err().unwrap();
+        assert_eq!(exhausted.retries, 3);
+        assert!(!exhausted.locked);
+        let error = exhausted.into_error(Path::new("mods/a.zipmod"), |e| {
+            AppError::io("io.rename_mod", None, e)
+        });
+        assert!(matches!(error, AppError::Io { .. }));
+
+        let missing = retry(|| Err::<(), _>(io::Error::from(io::ErrorKind::NotFound)));
+        assert_eq!(missing.err().unwrap().retries, 0);
+    }
+}
@@ -0,0 +1,92 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{folder_size, links, local_time, long_path, parser::ModEntry};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Size and modification time of a file as a scan saw it
+/// 掃描當時檔案的大小與修改時間
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) struct FileSnapshot {
+    pub size: u64,
+    pub modified: Option<u64>,
+}
+
+/// A path plus the snapshot a destructive command expects it to still match
+/// 路徑及破壞性指令預期其仍相符的快照
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct ExpectedFile {
+    pub path: String,
+    #[serde(flatten)]
+    pub snapshot: FileSnapshot,
+}
+
+/// Expected snapshots keyed by lowercase path
+/// 以小寫路徑為 key 的預期快照
+pub(crate) type Expectations = HashMap<String, FileSnapshot>;
+
+// ───────────────────────────────────────────────
+// Verification / 驗證
+// ───────────────────────────────────────────────
+
+impl FileSnapshot {
+    /// Snapshot recorded in a scanned entry
+    /// 掃描項目中記錄的快照
+    pub fn of_entry(entry: &ModEntry) -> Self {
+        FileSnapshot {
+            size: entry.size,
+            modified: entry.modified,
+        }
+    }
+
+    /// Current snapshot of `path`, taken the same way the scan took it: a folder's size is
+    /// that of the files inside; None if it is gone
+    /// 以與掃描相同的方式取得 `path` 目前的快照：資料夾的大小為其中檔案的總大小；檔案不存在時為 None
+    pub fn current(path: &Path) -> Option<Self> {
+        let (metadata, _) = links::stat(path);
+        let metadata = metadata?;
+        Some(FileSnapshot {
+            size: match metadata.is_dir() {
+                true => folder_size::measure(path).bytes,
+                false => metadata.len(),
+            },
+            modified: metadata
+                .modified()
+                .ok()
+                .map(|time| local_time::epoch_seconds(time).0),
+        })
+    }
+}
+
+/// Add `expected` to `expectations`, replacing snapshots of the same paths
+/// 將 `expected` 加入 `expectations`，同一路徑的快照會被取代
+pub(crate) fn extend(expectations: &mut Expectations, expected: &[ExpectedFile]) {
+    expectations.extend(
+        expected
+            .iter()
+            .map(|file| (file.path.to_lowercase(), file.snapshot)),
+    );
+}
+
+/// Snapshot `expectations` recorded for `path`, as given by the caller (not canonicalized)
+/// `expectations` 中 `path`（呼叫端提供的原始路徑，未正規化）的預期快照
+pub(crate) fn expected(expectations: &Expectations, path: &str) -> Option<FileSnapshot> {
+    expectations
+        .get(&path.to_lowercase())
+        .or_else(|| expectations.get(&long_path::display(Path::new(path)).to_lowercase()))
+        .copied()
+}
+
+/// Whether `path` still matches `expected`. Without an expectation it always matches; a path
+/// that no longer exists does not.
+/// `path` 是否仍與 `expected` 相符；沒有預期快照時一律相符，已不存在的路徑則不相符
+pub(crate) fn unchanged(expected: Option<&FileSnapshot>, path: &Path) -> bool {
+    match expected {
+        Some(expected) => FileSnapshot::current(path).as_ref() == Some(expected),
+        None => true,
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "sqlite")]
+use crate::index_db::{self, IndexDb};
+use crate::{
+    app_log,
+    error::AppError,
+    parser::{ModConflict, ModEntry},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// First-seen database inside each profile directory
+/// 各 profile 資料夾中的首次發現資料庫
+const FIRST_SEEN_FILE: &str = "first_seen.json";
+
+/// Full mods-folder scans a file may be absent from before its record is pruned
+/// 檔案可在幾次完整 mods 資料夾掃描中缺席，超過後其紀錄即被清除
+const PRUNE_AFTER_SCANS: u32 = 5;
+
+/// When the app first saw one file
+/// 本程式首次看到單一檔案的時間
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SeenRecord {
+    /// Seconds since the Unix epoch / Unix epoch 秒數
+    pub first_seen: u64,
+    /// Full scans in a row that did not find the file / 連續未找到此檔案的完整掃描次數
+    #[serde(default)]
+    pub missed_scans: u32,
+}
+
+/// Records keyed by `record_key`
+/// 以 `record_key` 為 key 的紀錄
+#[derive(Serialize, Deserialize, Default)]
+struct FirstSeenIndex {
+    #[serde(default)]
+    files: HashMap<String, SeenRecord>,
+}
+
+/// Whether an observation covered every mod of the install, so absent files count as missed
+/// 此次觀察是否涵蓋安裝中的所有模組，缺席的檔案因此計為未找到
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Coverage {
+    /// Only some files, e.g. the members of the log's conflicts / 僅部分檔案，例如 log 衝突的成員
+    Partial,
+    /// An offline scan of the whole mods folder / 整個 mods 資料夾的離線掃描
+    Full,
+}
+
+// ───────────────────────────────────────────────
+// Storage / 儲存
+// ───────────────────────────────────────────────
+
+/// Records from the profile's index database when it has one, otherwise from the JSON file. A
+/// JSON file next to the database has yet to be migrated and is read instead.
+/// profile 有索引資料庫時從資料庫讀取紀錄，否則讀取 JSON 檔案；與資料庫並存的 JSON 檔案尚待遷移，因此改為讀取該檔案
+fn load(profile_dir: &Path) -> Result<FirstSeenIndex, AppError> {
+    let path = profile_dir.join(FIRST_SEEN_FILE);
+    #[cfg(feature = "sqlite")]
+    if !path.exists() {
+        if let Some(db) = IndexDb::existing(&path) {
+            return Ok(FirstSeenIndex {
+                files: db?.load_first_seen()?,
+            });
+        }
+    }
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(FirstSeenIndex::default()),
+        Err(e) => return Err(AppError::io("io.read_first_seen", Some(&path), e)),
+    };
+    // A damaged database only costs the dates; start over rather than fail every scan
+    // 資料庫損毀只會失去日期；重新開始而不是讓每次掃描都失敗
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+/// Store the records in the profile's index database when it has one, retiring the JSON file;
+/// otherwise as JSON
+/// profile 有索引資料庫時存入資料庫並移除 JSON 檔案；否則存為 JSON
+fn save(profile_dir: &Path, index: &FirstSeenIndex) -> Result<(), AppError> {
+    let path = profile_dir.join(FIRST_SEEN_FILE);
+    #[cfg(feature = "sqlite")]
+    if let Some(db) = IndexDb::existing(&path) {
+        db?.save_first_seen(&index.files)?;
+        index_db::retire_json(&path);
+        return Ok(());
+    }
+    fs::create_dir_all(profile_dir)
+        .map_err(|e| AppError::io("io.write_first_seen", Some(profile_dir), e))?;
+    let content = serde_json::to_string(index)
+        .map_err(|e| AppError::io("io.write_first_seen", Some(&path), e))?;
+    fs::write(&path, content).map_err(|e| AppError::io("io.write_first_seen", Some(&path), e))
+}
+
+// ───────────────────────────────────────────────
+// Recording / 記錄
+// ───────────────────────────────────────────────
+
+/// Key of an entry: its path relative to the game root (so copying the install keeps it) and
+/// its size (so a replaced file counts as new). No hash is known at scan time.
+/// 項目的 key：相對於遊戲根目錄的路徑（複製整個安裝後仍相同）與大小（被取代的檔案視為新檔案）；掃描時沒有雜湊可用
+fn record_key(entry: &ModEntry, game_path: &str) -> String {
+    let path = entry.path.replace('\\', "/");
+    let root = game_path.replace('\\', "/");
+    let root = root.trim_end_matches('/');
+    let relative = path
+        .get(..root.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(root))
+        .map(|_| &path[root.len()..])
+        .filter(|rest| rest.starts_with('/'))
+        .unwrap_or(&path);
+    format!(
+        "{}|{}",
+        relative.trim_start_matches('/').to_lowercase(),
+        entry.size
+    )
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+fn record<'a>(
+    profile_dir: &Path,
+    game_path: &str,
+    entries: impl IntoIterator<Item = &'a mut ModEntry>,
+    coverage: Coverage,
+) -> Result<(), AppError> {
+    let mut index = load(profile_dir)?;
+    let now = now();
+    let mut seen = HashSet::new();
+    for entry in entries {
+        if entry.modified.is_none() || entry.is_directory {
+            continue;
+        }
+        let key = record_key(entry, game_path);
+        let record = index.files.entry(key.clone()).or_insert(SeenRecord {
+            first_seen: now,
+            missed_scans: 0,
+        });
+        record.missed_scans = 0;
+        entry.first_seen = Some(record.first_seen);
+        seen.insert(key);
+    }
+
+    if coverage == Coverage::Full {
+        index.files.retain(|key, record| {
+            if !seen.contains(key) {
+                record.missed_scans += 1;
+            }
+            record.missed_scans < PRUNE_AFTER_SCANS
+        });
+    }
+    save(profile_dir, &index)
+}
+
+/// Record the files of `entries` in the profile's first-seen database and fill in their
+/// `first_seen`; missing files and folders are left out. After a `Full` observation, records
+/// of files it did not find are pruned once missed `PRUNE_AFTER_SCANS` times in a row. A
+/// database that cannot be written is logged rather than failing the scan.
+/// 將 `entries` 的檔案記錄到 profile 的首次發現資料庫並填入其 `first_seen`；不存在的檔案與資料夾不記錄；
+/// `Full` 觀察後，連續 `PRUNE_AFTER_SCANS` 次未找到的檔案紀錄會被清除；資料庫無法寫入時記錄後略過，不讓掃描失敗
+pub(crate) fn observe<'a>(
+    profile_dir: &Path,
+    game_path: &str,
+    entries: impl IntoIterator<Item = &'a mut ModEntry>,
+    coverage: Coverage,
+) {
+    if let Err(e) = record(profile_dir, game_path, entries, coverage) {
+        app_log::write(&format!("first-seen dates not saved: {}", e));
+    }
+}
+
+/// `observe` the members of the log's conflicts, which are only part of the install
+/// 對 log 衝突的成員執行 `observe`；這些成員只是安裝的一部分
+pub(crate) fn observe_conflicts(
+    profile_dir: &Path,
+    game_path: &str,
+    conflicts: &mut [ModConflict],
+) {
+    let entries = conflicts
+        .iter_mut()
+        .flat_map(|conflict| std::iter::once(&mut conflict.loaded).chain(&mut conflict.skipped));
+    observe(profile_dir, game_path, entries, Coverage::Partial);
+}
+
+/// Forget every first-seen date of the profile; the next scans start recording afresh
+/// 清除 profile 的所有首次發現日期；之後的掃描會重新開始記錄
+pub(crate) fn rebuild(profile_dir: &Path) -> Result<(), AppError> {
+    save(profile_dir, &FirstSeenIndex::default())
+}
@@ -0,0 +1,856 @@
+use std::{cell::Cell, fs, path::Path};
+
+use crate::{
+    conflict_coupling,
+    error::AppError,
+    file_snapshot::{Expectations, FileSnapshot},
+    log_environment::{self, PatternSet},
+    long_path,
+    modpack::ModpackFolders,
+    ops,
+    parser::{self, ModConflict, ModEntry},
+    path_guard::WriteMode,
+    pins, profiles, progress, resolution, scan,
+    test_support::{write_zip, SyntheticInstall, SyntheticLog, TestManifest},
+};
+
+// ───────────────────────────────────────────────
+// Log Parsing / 解析 Log
+// ───────────────────────────────────────────────
+
+fn display(path: &Path) -> String {
+    long_path::display(path)
+}
+
+/// Mod extensions of a new profile / 新 profile 的模組副檔名
+fn extensions() -> Vec<String> {
+    profiles::default_mod_extensions()
+}
+
+#[test]
+fn scan_resolves_conflict_members_under_mods() {
+    let install = SyntheticInstall::new();
+    let loaded = install.add_zipmod(
+        "Sideloader Modpack/[Author] Hair v2.zipmod",
+        &TestManifest::new("author.hair").with_version("2"),
+    );
+    let skipped = install.add_zipmod(
+        "downloads/[Author] Hair v1.zipmod",
+        &TestManifest::new("author.hair").with_version("1"),
+    );
+    let log = SyntheticLog::new().line("[Info   :   Console] Loading mods");
+    let conflict_line = log.next_line();
+    let log = log.conflict(
+        "Sideloader Modpack/[Author] Hair v2.zipmod",
+        &["downloads/[Author] Hair v1.zipmod"],
+    );
+    install.write_log(&log);
+
+    let scan = scan::scan_conflicts(&install.game_path()).unwrap();
+
+    assert_eq!(scan.conflicts.len(), 1);
+    let conflict = &scan.conflicts[0];
+    assert_eq!(conflict.line, Some(conflict_line));
+    assert_eq!(conflict.loaded.path, display(&loaded));
+    assert_eq!(conflict.loaded.name, "[Author] Hair v2.zipmod");
+    assert_eq!(conflict.skipped.len(), 1);
+    assert_eq!(conflict.skipped[0].path, display(&skipped));
+    assert_eq!(
+        conflict.skipped[0].size,
+        fs::metadata(&skipped).unwrap().len()
+    );
+    assert!(conflict.skipped[0].modified.is_some());
+    assert_eq!(
+        conflict.loaded.mod_root.as_deref(),
+        Some(display(&install.root().join("mods")).as_str())
+    );
+    assert!(!conflict.cross_root);
+    assert_eq!(scan.summary.skipped_files, 1);
+    assert_eq!(scan.summary.skipped_bytes, conflict.skipped[0].size);
+}
+
+#[test]
+fn load_errors_and_other_lines_are_not_conflicts() {
+    let install = SyntheticInstall::new();
+    install.add_zipmod("a.zipmod", &TestManifest::new("a"));
+    install.add_zipmod("b.zipmod", &TestManifest::new("a"));
+    let log = SyntheticLog::new()
+        .load_error(
+            "broken.zipmod",
+            "System.IO.InvalidDataException: bad header",
+        )
+        .line("[Warning:Sideloader] Skipped versions: \"nothing.zipmod\"")
+        .conflict("a.zipmod", &["b.zipmod"])
+        .load_error("other.zipmod", "Zip is corrupted");
+    install.write_log(&log);
+
+    let scan = scan::scan_conflicts(&install.game_path()).unwrap();
+
+    assert_eq!(scan.conflicts.len(), 1);
+    assert_eq!(scan.conflicts[0].loaded.name, "a.zipmod");
+}
+
+#[test]
+fn stale_entries_have_no_metadata() {
+    let install = SyntheticInstall::new();
+    install.add_zipmod("kept.zipmod", &TestManifest::new("kept"));
+    install.write_log(&SyntheticLog::new().conflict("kept.zipmod", &["gone.zipmod"]));
+
+    let scan = scan::scan_conflicts(&install.game_path()).unwrap();
+
+    let stale = &scan.conflicts[0].skipped[0];
+    assert_eq!(stale.path, display(&install.mod_path("gone.zipmod")));
+    assert_eq!(stale.size, 0);
+    assert_eq!(stale.modified, None);
+    assert!(scan.conflicts[0].loaded.modified.is_some());
+}
+
+#[test]
+fn members_resolve_under_the_additional_mods_dir() {
+    let install = SyntheticInstall::new();
+    let extra = install.set_additional_mods_dir("mods_old");
+    install.add_zipmod("shared.zipmod", &TestManifest::new("shared"));
+    write_zip(
+        &extra.join("legacy.zipmod"),
+        &[(
+            "manifest.xml",
+            TestManifest::new("shared").to_xml().as_bytes(),
+        )],
+    );
+    install.write_log(&SyntheticLog::new().conflict("shared.zipmod", &["legacy.zipmod"]));
+
+    let scan = scan::scan_conflicts(&install.game_path()).unwrap();
+
+    let conflict = &scan.conflicts[0];
+    assert_eq!(
+        conflict.skipped[0].path,
+        display(&extra.join("legacy.zipmod"))
+    );
+    assert_eq!(
+        conflict.skipped[0].mod_root.as_deref(),
+        Some(display(&extra).as_str())
+    );
+    assert!(conflict.cross_root);
+}
+
+#[test]
+fn missing_log_is_reported() {
+    let install = SyntheticInstall::new();
+
+    let result = scan::scan_conflicts(&install.game_path());
+
+    assert!(matches!(result, Err(AppError::LogNotFound { .. })));
+}
+
+#[test]
+fn sideloader_version_selects_the_warning_wording() {
+    let install = SyntheticInstall::new();
+    let log = SyntheticLog::new()
+        .conflict("a.zipmod", &["b.zipmod"])
+        .line("[Warning:Sideloader] Skipping \"d.zipmod\" because \"c.zipmod\" is already loaded")
+        .text();
+    let loaded = |log: &str| {
+        parser::parse_conflicts(log, &install.game_path(), &extensions())
+            .iter()
+            .map(|conflict| conflict.loaded.name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let environment = log_environment::detect(&log);
+    assert_eq!(environment.bepinex_version.as_deref(), Some("5.4.22.0"));
+    assert_eq!(environment.process.as_deref(), Some("Koikatu"));
+    assert_eq!(environment.sideloader_version.as_deref(), Some("19.3"));
+    assert_eq!(environment.pattern_set, PatternSet::MultipleVersions);
+    assert_eq!(
+        environment.describe(),
+        "BepInEx 5.4.22.0 (Koikatu), Sideloader 19.3"
+    );
+    assert_eq!(loaded(&log), ["a.zipmod"]);
+
+    let legacy = log.replace("Sideloader 19.3", "Sideloader 12.1");
+    assert_eq!(
+        log_environment::detect(&legacy).pattern_set,
+        PatternSet::Legacy
+    );
+    assert_eq!(loaded(&legacy), ["c.zipmod"]);
+
+    let unknown = log.replace("Loading [Sideloader 19.3]", "Loading plugins");
+    assert_eq!(
+        log_environment::detect(&unknown).pattern_set,
+        PatternSet::All
+    );
+    assert_eq!(loaded(&unknown), ["a.zipmod", "c.zipmod"]);
+}
+
+// ───────────────────────────────────────────────
+// Awkward Game Paths / 特殊遊戲路徑
+// ───────────────────────────────────────────────
+
+/// Scan an install rooted at `folder` through the path `game_path` gives, with one conflict
+/// whose log paths mix separators and full-width names, and check both members resolve
+/// 以 `game_path` 提供的路徑掃描根目錄為 `folder` 的安裝，其唯一衝突的 log 路徑混用分隔符號與全形名稱，
+/// 並確認兩個成員都對應到檔案
+fn assert_scan_resolves_under(folder: &str, game_path: impl Fn(&SyntheticInstall) -> String) {
+    let install = SyntheticInstall::in_folder(folder);
+    let separator = std::path::MAIN_SEPARATOR;
+    let loaded_rel = format!("Sideloader Modpack{}衣装/[作者] 服 v2.zipmod", separator);
+    let skipped_rel = "手動 /服 v1.zipmod".to_string();
+    let loaded = install.add_zipmod(&loaded_rel, &TestManifest::new("author.outfit"));
+    let skipped = install.add_zipmod(&skipped_rel, &TestManifest::new("author.outfit"));
+    install.write_log(&SyntheticLog::new().conflict(&loaded_rel, &[skipped_rel.as_str()]));
+
+    let scan = scan::scan_conflicts(&game_path(&install)).unwrap();
+
+    let conflict = &scan.conflicts[0];
+    assert_eq!(conflict.loaded.name, "[作者] 服 v2.zipmod");
+    assert_eq!(
+        conflict.loaded.size,
+        fs::metadata(long_path::extend(&loaded)).unwrap().len()
+    );
+    assert_eq!(
+        conflict.skipped[0].size,
+        fs::metadata(long_path::extend(&skipped)).unwrap().len()
+    );
+    assert!(conflict.loaded.modified.is_some());
+    assert!(conflict.skipped[0].modified.is_some());
+
+    let entry = parser::build_mod_entry(&skipped, &skipped_rel, &extensions());
+    assert!(entry.modified.is_some());
+    assert_eq!(entry.name, "服 v1.zipmod");
+}
+
+#[test]
+fn game_path_with_trailing_dot_and_full_width_names_resolves() {
+    assert_scan_resolves_under("ゲーム/Koikatsu.", SyntheticInstall::game_path);
+}
+
+#[test]
+fn game_path_with_trailing_space_resolves() {
+    assert_scan_resolves_under("Games/Koikatsu Party ", SyntheticInstall::game_path);
+}
+
+#[test]
+fn game_path_with_forward_slashes_resolves() {
+    assert_scan_resolves_under("ＫＫ　ゲーム/Koikatsu.", |install| {
+        install.game_path().replace('\\', "/")
+    });
+}
+
+// ───────────────────────────────────────────────
+// Deleting / 刪除
+// ───────────────────────────────────────────────
+
+/// Delete `paths` of `install` the way `delete_mods` is called, with safe mode on so files go
+/// to the game's quarantine instead of the Recycle Bin of the machine running the tests
+/// 以呼叫 `delete_mods` 的方式刪除 `install` 中的 `paths`；開啟安全模式，使檔案移至遊戲的隔離區而非執行測試之電腦的回收桶
+fn delete(
+    install: &SyntheticInstall,
+    paths: &[String],
+    modpacks: Option<&ModpackFolders>,
+    expectations: Option<&Expectations>,
+) -> Result<Vec<ops::DeletedFile>, AppError> {
+    let game_paths = [install.game_path()];
+    let mut deleted = Vec::new();
+    ops::delete_mods(
+        &game_paths[..],
+        paths,
+        false,
+        false,
+        modpacks,
+        &[],
+        expectations,
+        false,
+        &game_paths,
+        WriteMode::default(),
+        &|| false,
+        &progress::ignore,
+        &mut deleted,
+    )
+    .map(|()| deleted)
+}
+
+#[test]
+fn delete_moves_scanned_skipped_copy_out_of_mods() {
+    let install = SyntheticInstall::new();
+    install.add_zipmod("keep.zipmod", &TestManifest::new("dup"));
+    let skipped = install.add_zipmod("dl/old.zipmod", &TestManifest::new("dup"));
+    install.write_log(&SyntheticLog::new().conflict("keep.zipmod", &["dl/old.zipmod"]));
+    let scan = scan::scan_conflicts(&install.game_path()).unwrap();
+    let entry = &scan.conflicts[0].skipped[0];
+    let expectations =
+        Expectations::from([(entry.path.to_lowercase(), FileSnapshot::of_entry(entry))]);
+
+    let deleted = delete(
+        &install,
+        std::slice::from_ref(&entry.path),
+        None,
+        Some(&expectations),
+    )
+    .unwrap();
+
+    assert_eq!(deleted.len(), 1);
+    assert!(deleted[0].redirected_to_quarantine);
+    assert!(!skipped.exists());
+    assert!(install.mod_path("keep.zipmod").exists());
+    let destination = deleted[0].destination.as_deref().unwrap();
+    assert!(Path::new(destination).starts_with(install.root().join("quarantine")));
+    assert!(Path::new(destination).exists());
+}
+
+#[test]
+fn cancelled_delete_still_reports_the_files_it_moved() {
+    let install = SyntheticInstall::new();
+    let paths = ["a.zipmod", "b.zipmod"]
+        .map(|name| display(&install.add_zipmod(name, &TestManifest::new(name))));
+    let game_paths = [install.game_path()];
+    let checks = Cell::new(0);
+    let mut deleted = Vec::new();
+
+    let result = ops::delete_mods(
+        &game_paths[..],
+        &paths,
+        false,
+        false,
+        None,
+        &[],
+        None,
+        false,
+        &game_paths,
+        WriteMode::default(),
+        &|| {
+            checks.set(checks.get() + 1);
+            checks.get() > 1
+        },
+        &progress::ignore,
+        &mut deleted,
+    );
+
+    assert!(matches!(result, Err(AppError::OperationCancelled)));
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0].requested, paths[0]);
+    assert!(!Path::new(&paths[0]).exists());
+    assert!(Path::new(&paths[1]).exists());
+}
+
+#[test]
+fn delete_refuses_file_changed_since_scan() {
+    let install = SyntheticInstall::new();
+    let path = install.add_zipmod("a.zipmod", &TestManifest::new("a"));
+    let entry = parser::build_mod_entry(&path, "a.zipmod", &extensions());
+    let expectations =
+        Expectations::from([(entry.path.to_lowercase(), FileSnapshot::of_entry(&entry))]);
+    install.add_zipmod("a.zipmod", &TestManifest::new("a").with_name("Replaced"));
+
+    let result = delete(&install, &[display(&path)], None, Some(&expectations));
+
+    assert!(matches!(
+        result,
+        Err(AppError::FileChanged { count: 1, .. })
+    ));
+    assert!(path.exists());
+}
+
+#[test]
+fn delete_refuses_paths_outside_the_game_and_modpacks() {
+    let install = SyntheticInstall::new();
+    let outside = install.add_file("UserData/chara/card.png", b"png");
+    let modpack = install.add_zipmod("Sideloader Modpack/m.zipmod", &TestManifest::new("m"));
+    let folders = ModpackFolders::for_games(&[(
+        install.game_path(),
+        vec!["mods/Sideloader Modpack".to_string()],
+    )]);
+
+    let outside_result = delete(&install, &[display(&outside)], None, None);
+    let modpack_result = delete(&install, &[display(&modpack)], Some(&folders), None);
+
+    assert!(matches!(
+        outside_result,
+        Err(AppError::PathNotAllowed { .. })
+    ));
+    assert!(matches!(
+        modpack_result,
+        Err(AppError::ModpackProtected { count: 1, .. })
+    ));
+    assert!(outside.exists());
+    assert!(modpack.exists());
+}
+
+#[test]
+fn delete_of_read_only_file_needs_clear_flag() {
+    let install = SyntheticInstall::new();
+    let path = install.add_zipmod("locked.zipmod", &TestManifest::new("locked"));
+    let mut permissions = fs::metadata(&path).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&path, permissions).unwrap();
+    let game_paths = [install.game_path()];
+    let delete = |clear_read_only| {
+        let mut deleted = Vec::new();
+        ops::delete_mods(
+            &game_paths[..],
+            &[display(&path)],
+            false,
+            false,
+            None,
+            &[],
+            None,
+            clear_read_only,
+            &game_paths,
+            WriteMode::default(),
+            &|| false,
+            &progress::ignore,
+            &mut deleted,
+        )
+        .map(|()| deleted)
+    };
+
+    let refused = delete(false);
+    assert!(matches!(refused, Err(AppError::ReadOnly { count: 1, .. })));
+    assert!(path.exists());
+
+    let deleted = delete(true).unwrap();
+    assert_eq!(deleted.len(), 1);
+    assert!(!path.exists());
+}
+
+// ───────────────────────────────────────────────
+// Long Paths / 長路徑
+// ───────────────────────────────────────────────
+
+#[cfg(windows)]
+#[test]
+fn mods_nested_past_max_path_are_read_renamed_and_deleted() {
+    let install = SyntheticInstall::new();
+    let folder = ["長いフォルダ名".repeat(4), "a".repeat(100), "b".repeat(100)].join("/");
+    let rel_path = format!("{}/[Author] Hair v1.zipmod", folder);
+    let path = install.add_zipmod(&rel_path, &TestManifest::new("author.hair"));
+    assert!(display(&path).encode_utf16().count() > 260);
+
+    let entry = parser::build_mod_entry(&path, &rel_path, &extensions());
+    assert_eq!(
+        entry.size,
+        fs::metadata(long_path::extend(&path)).unwrap().len()
+    );
+    assert!(entry.modified.is_some());
+    let manifest = crate::manifest::read_manifest_from_path(&entry.path).unwrap();
+    assert_eq!(manifest.guid, "author.hair");
+
+    let renamed = ops::rename_file(&path, "[Author] Hair v2.zipmod", WriteMode::default()).unwrap();
+    assert!(!long_path::extend(&path).exists());
+    assert!(long_path::extend(&renamed).exists());
+    let deleted = delete(&install, &[display(&renamed)], None, None).unwrap();
+    assert_eq!(deleted.len(), 1);
+    assert!(!long_path::extend(&renamed).exists());
+}
+
+// ───────────────────────────────────────────────
+// Missing Mods Folder / 缺少 mods 資料夾
+// ───────────────────────────────────────────────
+
+#[test]
+fn missing_mods_folder_points_at_a_misplaced_modpack_until_created() {
+    let install = SyntheticInstall::new();
+    fs::remove_dir(install.root().join("mods")).unwrap();
+    install.add_file("Koikatsu/mods/Sideloader Modpack/hair.zipmod", b"zip");
+    install.write_log(&SyntheticLog::new().conflict("a.zipmod", &["b.zipmod"]));
+
+    match scan::scan_conflicts(&install.game_path()) {
+        Err(AppError::ModsDirMissing {
+            path,
+            found,
+            suggested,
+        }) => {
+            assert_eq!(path, display(&install.root().join("mods")));
+            assert!(found.is_some_and(|found| found.ends_with("Sideloader Modpack")));
+            assert_eq!(suggested, Some(display(&install.root().join("Koikatsu"))));
+        }
+        other => panic!("expected mods_dir_missing, got {:?}", other.err()),
+    }
+
+    crate::mod_roots::create_mods_dir(install.root(), WriteMode::default()).unwrap();
+    assert_eq!(
+        scan::scan_conflicts(&install.game_path())
+            .unwrap()
+            .conflicts
+            .len(),
+        1
+    );
+}
+
+// ───────────────────────────────────────────────
+// Pinned Files / 釘選檔案
+// ───────────────────────────────────────────────
+
+fn entry_of(install: &SyntheticInstall, rel_path: &str, content: &[u8]) -> ModEntry {
+    let path = install.add_file(&format!("mods/{}", rel_path), content);
+    parser::build_mod_entry(&path, rel_path, &extensions())
+}
+
+#[test]
+fn delete_and_plans_leave_pinned_files_alone() {
+    let install = SyntheticInstall::new();
+    let profile = tempfile::tempdir().unwrap();
+    let loaded = entry_of(&install, "loaded.zipmod", b"loaded");
+    let skipped = entry_of(&install, "skipped.zipmod", b"skipped");
+    let paths = [skipped.path.clone()];
+    pins::pin(profile.path(), &install.game_path(), &paths).unwrap();
+    let set = pins::PinSet::load(profile.path(), &install.game_path()).unwrap();
+
+    let game_paths = [install.game_path()];
+    let deleted = ops::delete_mods(
+        &game_paths[..],
+        &paths,
+        false,
+        false,
+        None,
+        &[set],
+        None,
+        false,
+        &game_paths,
+        WriteMode::default(),
+        &|| false,
+        &progress::ignore,
+        &mut Vec::new(),
+    );
+    assert!(matches!(deleted, Err(AppError::Pinned { count: 1, .. })));
+    assert!(Path::new(&skipped.path).exists());
+
+    let mut conflicts = [ModConflict::new(loaded, vec![skipped])];
+    pins::mark(profile.path(), &install.game_path(), &mut conflicts);
+    let plans = resolution::plan_resolution(&conflicts, &Expectations::default());
+    assert!(plans[0].delete.is_empty());
+    assert_eq!(plans[0].pinned.len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn pins_hold_when_mods_is_a_link() {
+    let install = SyntheticInstall::new();
+    let profile = tempfile::tempdir().unwrap();
+    let target = install.root().join("elsewhere");
+    fs::create_dir_all(&target).unwrap();
+    fs::remove_dir(install.root().join("mods")).unwrap();
+    std::os::unix::fs::symlink(&target, install.root().join("mods")).unwrap();
+    let skipped = entry_of(&install, "skipped.zipmod", b"skipped");
+    let paths = [skipped.path.clone()];
+    pins::pin(profile.path(), &install.game_path(), &paths).unwrap();
+    let set = pins::PinSet::load(profile.path(), &install.game_path()).unwrap();
+    assert!(set.contains(&fs::canonicalize(&skipped.path).unwrap()));
+
+    let game_paths = [install.game_path()];
+    let deleted = ops::delete_mods(
+        &game_paths[..],
+        &paths,
+        false,
+        false,
+        None,
+        &[set],
+        None,
+        false,
+        &game_paths,
+        WriteMode::default(),
+        &|| false,
+        &progress::ignore,
+        &mut Vec::new(),
+    );
+    assert!(matches!(deleted, Err(AppError::Pinned { count: 1, .. })));
+    assert!(target.join("skipped.zipmod").exists());
+}
+
+#[test]
+fn plans_note_conflicts_sharing_a_deleted_file() {
+    let install = SyntheticInstall::new();
+    let loaded = entry_of(&install, "a.zipmod", b"a");
+    let shared = entry_of(&install, "b.zipmod", b"b");
+    let other = entry_of(&install, "c.zipmod", b"c");
+    let mut conflicts = [
+        ModConflict::new(loaded, vec![shared.clone()]),
+        ModConflict::new(shared, vec![other]),
+    ];
+    conflict_coupling::mark_coupling(&mut conflicts);
+    let (first, second) = (conflicts[0].id.clone(), conflicts[1].id.clone());
+    assert_eq!(conflicts[0].skipped[0].coupled_conflicts, [second.as_str()]);
+    assert_eq!(conflicts[1].loaded.coupled_conflicts, [first.as_str()]);
+    assert!(conflicts[1].skipped[0].coupled_conflicts.is_empty());
+
+    let plans = resolution::plan_resolution(&conflicts, &Expectations::default());
+    assert_eq!(plans[0].also_affects, [second.as_str()]);
+    assert!(plans[0].also_affects_note.is_some());
+    assert!(plans[1].also_affects.is_empty());
+}
+
+// ───────────────────────────────────────────────
+// Folder Mods / 資料夾模組
+// ───────────────────────────────────────────────
+
+#[test]
+fn folder_members_report_their_contents_and_need_allow_directories() {
+    let install = SyntheticInstall::new();
+    let loaded = entry_of(&install, "hair.zipmod", b"zipmod");
+    install.add_file("mods/loose/hair/manifest.xml", b"<manifest/>");
+    install.add_file("mods/loose/hair/abdata/hair.unity3d", b"bundle data");
+    let folder =
+        parser::build_mod_entry(&install.mod_path("loose/hair"), "loose/hair", &extensions());
+    assert!(folder.is_directory);
+    assert_eq!(folder.size, 22);
+    assert_eq!(folder.directory_files, Some(2));
+    assert!(!folder.size_is_estimate);
+
+    let conflicts = [ModConflict::new(loaded, vec![folder.clone()])];
+    let plans = resolution::plan_resolution(&conflicts, &Expectations::default());
+    assert_eq!(plans[0].directory_files, Some(2));
+
+    let game_paths = [install.game_path()];
+    let paths = [folder.path.clone()];
+    let expectations =
+        Expectations::from([(folder.path.to_lowercase(), FileSnapshot::of_entry(&folder))]);
+    let delete = |allow_directories: bool| {
+        let mut deleted = Vec::new();
+        ops::delete_mods(
+            &game_paths[..],
+            &paths,
+            false,
+            allow_directories,
+            None,
+            &[],
+            Some(&expectations),
+            false,
+            &game_paths,
+            WriteMode::default(),
+            &|| false,
+            &progress::ignore,
+            &mut deleted,
+        )
+        .map(|()| deleted)
+    };
+    assert!(matches!(delete(false), Err(AppError::InvalidInput { .. })));
+    assert!(Path::new(&folder.path).exists());
+    let deleted = delete(true).unwrap();
+    assert!(deleted[0].redirected_to_quarantine);
+    assert!(!Path::new(&folder.path).exists());
+}
+
+// ───────────────────────────────────────────────
+// Zip Mods / Zip 模組
+// ───────────────────────────────────────────────
+
+#[test]
+fn zip_mods_scan_resolve_and_rename_like_zipmods() {
+    use crate::{
+        operations::Operations,
+        resolution::{LastConflicts, ResolveAction},
+    };
+
+    let install = SyntheticInstall::new();
+    let loaded = install.add_zipmod("[Author] Hair v2.zip", &TestManifest::new("author.hair"));
+    let skipped = install.add_zipmod(
+        "downloads/[Author] Hair v1.zip",
+        &TestManifest::new("author.hair"),
+    );
+    install.write_log(
+        &SyntheticLog::new().conflict("[Author] Hair v2.zip", &["downloads/[Author] Hair v1.zip"]),
+    );
+
+    let scan = scan::scan_conflicts(&install.game_path()).unwrap();
+    let entry = &scan.conflicts[0].skipped[0];
+    assert_eq!(entry.path, display(&skipped));
+    assert_eq!(entry.name_title.as_deref(), Some("Hair"));
+    assert_eq!(entry.name_version.as_deref(), Some("v1"));
+    let plans = resolution::plan_resolution(&scan.conflicts, &Expectations::default());
+    assert_eq!(plans[0].keep.path, display(&loaded));
+    assert_eq!(plans[0].delete[0].path, display(&skipped));
+
+    let store = LastConflicts::default();
+    store.store(&install.game_path(), &scan.conflicts);
+    let operations = Operations::default();
+    let operation = operations.register("", "test", None, false).unwrap();
+    let report = resolution::resolve_conflicts(
+        &store,
+        &[scan.conflicts[0].id.clone()],
+        None,
+        None,
+        ResolveAction::Quarantine,
+        false,
+        false,
+        false,
+        false,
+        false,
+        &operation,
+        &progress::ignore,
+    )
+    .unwrap();
+    assert_eq!(report.resolved.len(), 1);
+    assert!(!skipped.exists());
+    assert!(install
+        .root()
+        .join("quarantine/mods/downloads/[Author] Hair v1.zip")
+        .exists());
+
+    let renamed = ops::rename_file(&loaded, "[Author] Hair v3.zip", operation.write_mode).unwrap();
+    let entry = parser::build_mod_entry(&renamed, "[Author] Hair v3.zip", &extensions());
+    assert!(!loaded.exists());
+    assert_eq!(entry.name_version.as_deref(), Some("v3"));
+    // A profile that only takes `.zipmod` reads `.zip` as part of the name
+    // 只接受 `.zipmod` 的 profile 會將 `.zip` 視為名稱的一部分
+    let zipmod_only = ["zipmod".to_string()];
+    let entry = parser::build_mod_entry(&renamed, "[Author] Hair v3.zip", &zipmod_only);
+    assert_eq!(entry.name_title.as_deref(), Some("Hair v3.zip"));
+    assert_eq!(entry.name_version, None);
+}
+
+// ───────────────────────────────────────────────
+// Superseded Mods / 被取代的模組
+// ───────────────────────────────────────────────
+
+#[test]
+fn superseded_results_join_the_log_scan_instead_of_replacing_it() {
+    use crate::{
+        mods_scan,
+        operations::{CancelToken, Operations},
+        resolution::{LastConflicts, ResolveAction},
+        superseded,
+    };
+
+    let install = SyntheticInstall::new();
+    install.add_zipmod("a.zipmod", &TestManifest::new("a"));
+    let skipped = install.add_zipmod("dl/a.zipmod", &TestManifest::new("a"));
+    install.write_log(&SyntheticLog::new().conflict("a.zipmod", &["dl/a.zipmod"]));
+    let hair = |version| TestManifest::new("author.hair").with_version(version);
+    install.add_zipmod("Sideloader Modpack/hair.zipmod", &hair("2.0"));
+    install.add_zipmod("hair.zipmod", &hair("1.0"));
+    let profile = tempfile::tempdir().unwrap();
+    let store = LastConflicts::default();
+
+    let scan = scan::scan_conflicts(&install.game_path()).unwrap();
+    store.store(&install.game_path(), &scan.conflicts);
+    let files = mods_scan::scan_mods_folder(
+        &profile.path().join("asset_index.json"),
+        install.root(),
+        false,
+        &extensions(),
+        &CancelToken::default(),
+        &progress::ignore,
+    )
+    .unwrap();
+    let modpacks = ModpackFolders::for_games(&[(
+        install.game_path(),
+        profiles::ProfileSettings::default().modpack_prefixes,
+    )]);
+    let found =
+        superseded::find_superseded(files, &modpacks, &CancelToken::default(), &progress::ignore)
+            .unwrap();
+    assert_eq!(found.len(), 1);
+    let conflicts = found
+        .iter()
+        .map(|found| found.conflict.clone())
+        .collect::<Vec<_>>();
+    store.merge(&install.game_path(), &conflicts);
+    store.merge(&install.game_path(), &conflicts);
+
+    let ids = store.conflict_ids(&install.game_path()).unwrap();
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&scan.conflicts[0].id) && ids.contains(&conflicts[0].id));
+    let operations = Operations::default();
+    let operation = operations.register("", "test", None, false).unwrap();
+    let report = resolution::resolve_conflicts(
+        &store,
+        &[scan.conflicts[0].id.clone()],
+        None,
+        None,
+        ResolveAction::Quarantine,
+        false,
+        false,
+        false,
+        false,
+        false,
+        &operation,
+        &progress::ignore,
+    )
+    .unwrap();
+    assert!(report.unknown_conflict_ids.is_empty());
+    assert_eq!(report.resolved.len(), 1);
+    assert!(!skipped.exists());
+}
+
+// ───────────────────────────────────────────────
+// Read-Only Mode / 唯讀模式
+// ───────────────────────────────────────────────
+
+#[test]
+fn read_only_mode_refuses_changes_but_still_scans() {
+    use crate::{
+        operations::Operations,
+        resolution::{LastConflicts, ResolveAction},
+        stage_installs,
+    };
+
+    let install = SyntheticInstall::new();
+    let loaded = install.add_zipmod("keep.zipmod", &TestManifest::new("dup"));
+    let skipped = install.add_zipmod("dl/old.zipmod", &TestManifest::new("dup"));
+    install.write_log(&SyntheticLog::new().conflict("keep.zipmod", &["dl/old.zipmod"]));
+    let operations = Operations::default();
+    operations.set_write_mode(WriteMode::ReadOnly);
+    let operation = operations.register("", "test", None, false).unwrap();
+    let read_only = |result: Result<(), AppError>| {
+        assert!(matches!(result, Err(AppError::ReadOnlyMode { .. })));
+    };
+
+    let scan = scan::scan_conflicts(&install.game_path()).unwrap();
+    assert_eq!(scan.conflicts.len(), 1);
+    let game_paths = [install.game_path()];
+    read_only(
+        ops::delete_mods(
+            &game_paths[..],
+            &[display(&skipped)],
+            false,
+            false,
+            None,
+            &[],
+            None,
+            false,
+            &game_paths,
+            operation.write_mode,
+            &|| false,
+            &progress::ignore,
+            &mut Vec::new(),
+        )
+        .map(drop),
+    );
+    let store = LastConflicts::default();
+    store.store(&install.game_path(), &scan.conflicts);
+    read_only(
+        resolution::resolve_conflicts(
+            &store,
+            &[scan.conflicts[0].id.clone()],
+            None,
+            None,
+            ResolveAction::Trash,
+            false,
+            false,
+            false,
+            false,
+            true,
+            &operation,
+            &progress::ignore,
+        )
+        .map(drop),
+    );
+    read_only(
+        stage_installs::apply_stage_plan(
+            install.root(),
+            &[],
+            false,
+            None,
+            operation.write_mode,
+            &operation.token,
+            &progress::ignore,
+        )
+        .map(drop),
+    );
+    // Converting a folder locks its source first, which is where it is refused
+    // 轉換資料夾前會先鎖定來源，並於此時被拒絕
+    read_only(operation.lock_paths(&[install.mod_path("dl")]));
+
+    assert!(loaded.exists());
+    assert!(skipped.exists());
+    assert!(!install.root().join("quarantine").exists());
+}
@@ -0,0 +1,148 @@
+use std::{collections::HashMap, iter, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    error::AppError,
+    messages::Message,
+    parser::{ModConflict, ModEntry},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// How many leading folders under the mods root name a bucket when none is given
+/// 未指定時，mods 根目錄下以幾層資料夾作為分組
+const DEFAULT_DEPTH: usize = 1;
+
+/// Conflict load of one folder under a mods root
+/// mods 根目錄下單一資料夾的衝突量
+#[derive(Serialize)]
+pub(crate) struct FolderStat {
+    /// Mods root the folder is under; None when a member's root is unknown
+    /// 資料夾所在的 mods 根目錄；成員的根目錄不明時為 None
+    pub mod_root: Option<String>,
+    /// Leading folders under the root joined with `/`; empty for files directly in it
+    /// 根目錄下的前幾層資料夾，以 `/` 連接；直接位於根目錄的檔案為空字串
+    pub folder: String,
+    /// Conflicts with a loaded or skipped member in the folder
+    /// loaded 或 skipped 成員位於此資料夾的衝突數
+    pub conflicts: usize,
+    pub skipped_files: usize,
+    /// Bytes of the folder's skipped files that deleting would free
+    /// 此資料夾中被跳過檔案刪除後可釋放的位元組數
+    pub wasted_bytes: u64,
+}
+
+// ───────────────────────────────────────────────
+// Density / 密度統計
+// ───────────────────────────────────────────────
+
+/// Parse the `depth` argument of `conflict_density_by_folder`; only 1 and 2 are accepted
+/// 解析 `conflict_density_by_folder` 的 `depth` 參數；僅接受 1 與 2
+pub(crate) fn parse_depth(depth: Option<usize>) -> Result<usize, AppError> {
+    match depth.unwrap_or(DEFAULT_DEPTH) {
+        depth @ (1 | 2) => Ok(depth),
+        depth => Err(AppError::InvalidInput {
+            message: Message::new("input.folder_depth_unsupported").with("depth", depth),
+        }),
+    }
+}
+
+/// Mods root of an entry and the folders between it and the file, outermost first. Entries
+/// without a `mod_root` fall back to what follows the first `mods` folder in their path.
+/// 項目的 mods 根目錄，以及根目錄與檔案之間的資料夾（由外而內）；
+/// 沒有 `mod_root` 的項目改取路徑中第一個 `mods` 資料夾之後的部分
+pub(crate) fn folders_under_root(entry: &ModEntry) -> (Option<String>, Vec<String>) {
+    let path = entry.path.replace('\\', "/");
+    let (root, relative) = match &entry.mod_root {
+        Some(root) => {
+            // The parser builds entry paths by joining onto the root, so the prefix matches as is
+            // parser 以根目錄串接出項目路徑，因此前綴可直接比對
+            let prefix = root.replace('\\', "/");
+            (
+                Some(root.clone()),
+                path.strip_prefix(prefix.as_str()).unwrap_or(&path),
+            )
+        }
+        None => match path.to_lowercase().find("/mods/") {
+            Some(index) => (None, &path[index + "/mods/".len()..]),
+            None => (None, path.as_str()),
+        },
+    };
+    let folders = Path::new(relative.trim_start_matches('/'))
+        .parent()
+        .map(|parent| parent.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_default();
+    let folders = folders
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect();
+    (root, folders)
+}
+
+/// Root and first `depth` folders of an entry
+/// 項目的根目錄與前 `depth` 層資料夾
+fn bucket(entry: &ModEntry, depth: usize) -> (Option<String>, String) {
+    let (root, folders) = folders_under_root(entry);
+    let depth = depth.min(folders.len());
+    (root, folders[..depth].join("/"))
+}
+
+/// Bucket conflict members by their first `depth` folders under the mods root and count, per
+/// folder, the conflicts touching it, the skipped files in it and the bytes those would free.
+/// Pure over the given conflicts; folders are compared case-insensitively and sorted by wasted
+/// bytes, then conflicts.
+/// 依 mods 根目錄下前 `depth` 層資料夾分組衝突成員，統計各資料夾涉及的衝突數、其中被跳過的檔案數與可釋放的位元組數；
+/// 只使用傳入的衝突，資料夾不分大小寫比對，依浪費空間、其次依衝突數排序
+pub(crate) fn conflict_density_by_folder(
+    conflicts: &[ModConflict],
+    depth: usize,
+) -> Vec<FolderStat> {
+    let mut stats: Vec<FolderStat> = Vec::new();
+    let mut index_of: HashMap<(Option<String>, String), usize> = HashMap::new();
+    let mut touched = Vec::new();
+    for conflict in conflicts {
+        touched.clear();
+        let members = iter::once((&conflict.loaded, false))
+            .chain(conflict.skipped.iter().map(|entry| (entry, true)));
+        for (entry, skipped) in members {
+            let (mod_root, folder) = bucket(entry, depth);
+            let key = (
+                mod_root.as_ref().map(|root| root.to_lowercase()),
+                folder.to_lowercase(),
+            );
+            let index = *index_of.entry(key).or_insert_with(|| {
+                stats.push(FolderStat {
+                    mod_root,
+                    folder,
+                    conflicts: 0,
+                    skipped_files: 0,
+                    wasted_bytes: 0,
+                });
+                stats.len() - 1
+            });
+            let stat = &mut stats[index];
+            if skipped {
+                stat.skipped_files += 1;
+                if entry.frees_space() {
+                    stat.wasted_bytes += entry.size;
+                }
+            }
+            if !touched.contains(&index) {
+                touched.push(index);
+                stat.conflicts += 1;
+            }
+        }
+    }
+
+    stats.sort_by(|a, b| {
+        b.wasted_bytes
+            .cmp(&a.wasted_bytes)
+            .then_with(|| b.conflicts.cmp(&a.conflicts))
+            .then_with(|| a.folder.cmp(&b.folder))
+    });
+    stats
+}
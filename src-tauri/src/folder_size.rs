@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use walkdir::WalkDir;
+
+use crate::long_path;
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Files counted in one folder before its size is reported as an estimate, so a conflict
+/// member that turns out to be a huge tree does not stall a scan
+/// 單一資料夾最多計算的檔案數，超過時大小改為估計值，避免衝突成員為巨大目錄樹時拖慢掃描
+pub(crate) const FILE_CAP: u64 = 20_000;
+
+/// What a folder holds, without following links
+/// 資料夾的內容（不跟隨連結）
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FolderSize {
+    pub bytes: u64,
+    pub files: u64,
+    /// `FILE_CAP` was reached, so `bytes` and `files` only cover part of the tree
+    /// 已達 `FILE_CAP`，`bytes` 與 `files` 只涵蓋部分目錄樹
+    pub truncated: bool,
+}
+
+// ───────────────────────────────────────────────
+// Measuring / 計算
+// ───────────────────────────────────────────────
+
+/// Total size and number of the regular files under `dir`, stopping at `FILE_CAP`. Entries
+/// that cannot be read are left out.
+/// `dir` 底下一般檔案的總大小與數量，達 `FILE_CAP` 時停止；無法讀取的項目不計入
+pub(crate) fn measure(dir: &Path) -> FolderSize {
+    let mut size = FolderSize::default();
+    let files = WalkDir::new(long_path::extend(dir))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file());
+    for entry in files {
+        if size.files == FILE_CAP {
+            size.truncated = true;
+            break;
+        }
+        size.bytes += entry.metadata().map_or(0, |metadata| metadata.len());
+        size.files += 1;
+    }
+    size
+}
@@ -0,0 +1,426 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_log,
+    conflict_sources::{self, UnifiedConflict},
+    corrupt_mods::{self, CorruptFinding},
+    error::AppError,
+    junk_files::{self, JunkFile},
+    missing_deps::{self, MissingDependency},
+    modpack::ModpackFolders,
+    mods_scan::{self, GuidCaseCollision, GuidGroup},
+    operations::CancelToken,
+    parser::ModEntry,
+    path_guard::WriteMode,
+    progress::{OnProgress, Progress},
+    scan::{self, ScanResult},
+    self_check::{self, CheckResult},
+    timings::Timings,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Stages reported in progress messages, wasted-bytes totals included
+/// 進度訊息中的階段數，包含浪費空間統計
+const STAGE_COUNT: usize = 7;
+
+/// A stage of the full analysis that callers may skip; the totals always run
+/// 呼叫端可略過的完整分析階段；統計階段一律執行
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AnalysisStage {
+    SelfCheck,
+    /// Log discovery and conflict parse / 尋找 log 並解析衝突
+    Conflicts,
+    /// Offline GUID scan; reads every manifest through the asset index
+    /// 離線 GUID 掃描；透過資產索引讀取所有 manifest
+    GuidScan,
+    /// Reads the end records of every archive / 讀取每個壓縮檔的結尾紀錄
+    CorruptScan,
+    JunkScan,
+    /// Missing-mod warnings of the log, checked against disabled and quarantined files
+    /// log 中的缺少模組警告，並與已停用及已隔離的檔案比對
+    MissingDependencies,
+}
+
+/// What `run_full_analysis` should do besides the default of running every stage
+/// `run_full_analysis` 除預設執行所有階段外的選項
+#[derive(Deserialize, Default)]
+pub(crate) struct FullAnalysisOptions {
+    #[serde(default)]
+    pub skip: Vec<AnalysisStage>,
+    /// Re-parse the log even when the cached result is still valid
+    /// 即使快取結果仍有效也重新解析 log
+    #[serde(default)]
+    pub force_rescan: bool,
+    /// Also write the report as JSON to this path / 另將報告以 JSON 寫入此路徑
+    #[serde(default)]
+    pub export_path: Option<String>,
+}
+
+/// Outcome of one stage: a failure is kept in its section instead of failing the report
+/// 單一階段的結果：失敗會記錄在該區段，而不會讓整份報告失敗
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub(crate) enum Section<T> {
+    Ok { result: T },
+    Skipped,
+    Errored { error: AppError },
+}
+
+/// Bytes the findings take up; `total_bytes` counts a file reported by several stages once
+/// 各項發現所佔的位元組數；`total_bytes` 中被多個階段回報的檔案只計算一次
+#[derive(Serialize, Default)]
+pub(crate) struct WastedTotals {
+    pub conflict_bytes: u64,
+    pub guid_duplicate_bytes: u64,
+    pub corrupt_bytes: u64,
+    pub junk_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// Every analysis of one install in a single report
+/// 單一安裝的所有分析結果合併成的報告
+#[derive(Serialize)]
+pub(crate) struct FullReport {
+    pub game_path: String,
+    /// Seconds since the Unix epoch when the analysis started
+    /// 分析開始的時間（Unix epoch 秒數）
+    pub generated: u64,
+    pub self_check: Section<Vec<CheckResult>>,
+    /// Members carry `coupled_conflicts`, so an export shows which conflicts share files
+    /// 成員附帶 `coupled_conflicts`，匯出結果因此可看出哪些衝突共用檔案
+    pub conflicts: Section<ScanResult>,
+    pub guid_groups: Section<Vec<GuidGroup>>,
+    /// Warnings from the same scan as `guid_groups`; never counted as wasted space
+    /// 與 `guid_groups` 同一次掃描產生的警告；不計入浪費空間
+    pub guid_case_collisions: Section<Vec<GuidCaseCollision>>,
+    /// `conflicts` and `guid_groups` merged into one row per real conflict; only errored or
+    /// skipped when both sources are
+    /// `conflicts` 與 `guid_groups` 合併成每個實際衝突一列；兩個來源皆錯誤或略過時才會錯誤或略過
+    pub unified_conflicts: Section<Vec<UnifiedConflict>>,
+    pub corrupt_mods: Section<Vec<CorruptFinding>>,
+    pub junk_files: Section<Vec<JunkFile>>,
+    /// Never counted as wasted space / 不計入浪費空間
+    pub missing_dependencies: Section<Vec<MissingDependency>>,
+    pub totals: WastedTotals,
+    /// Outcome of writing `export_path`; absent when no export was asked for
+    /// 寫入 `export_path` 的結果；未要求匯出時不出現
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export: Option<Section<String>>,
+}
+
+/// Files and settings of the profile the analysis runs against
+/// 分析所使用的 profile 檔案與設定
+pub(crate) struct AnalysisContext<'a> {
+    pub game_path: &'a str,
+    pub scan_cache_path: PathBuf,
+    pub index_path: PathBuf,
+    pub follow_links: bool,
+    pub mod_extensions: &'a [String],
+    /// Used to mark managed members of `unified_conflicts`
+    /// 用於標記 `unified_conflicts` 中的模組包成員
+    pub modpacks: &'a ModpackFolders,
+    /// Passed to the self-check / 傳給自我檢查
+    pub write_mode: WriteMode,
+    pub cancel: &'a CancelToken,
+}
+
+// ───────────────────────────────────────────────
+// Stages / 分析階段
+// ───────────────────────────────────────────────
+
+/// Run one stage with its progress prefixed by `step n/7: label`. Failures are logged and kept
+/// in the section; only cancellation stops the whole analysis.
+/// 執行單一階段，進度訊息加上 `step n/7: label` 前綴；失敗會記錄並保留在區段中，只有取消會中止整個分析
+fn run_stage<T>(
+    step: usize,
+    label: &str,
+    skipped: bool,
+    on_progress: OnProgress,
+    run: impl FnOnce(OnProgress) -> Result<T, AppError>,
+) -> Result<Section<T>, AppError> {
+    if skipped {
+        return Ok(Section::Skipped);
+    }
+    let prefix = format!("step {}/{}: {}", step, STAGE_COUNT, label);
+    on_progress(Progress::new(0, 0).with_message(prefix.clone()));
+    let stage_progress = |progress: Progress| {
+        let message = match &progress.message {
+            Some(detail) => format!("{} ({})", prefix, detail),
+            None => prefix.clone(),
+        };
+        on_progress(Progress::new(progress.current, progress.total).with_message(message));
+    };
+    match run(&stage_progress) {
+        Ok(result) => Ok(Section::Ok { result }),
+        Err(AppError::OperationCancelled) => Err(AppError::OperationCancelled),
+        Err(error) => {
+            app_log::write(&format!("full analysis: {} failed: {}", label, error));
+            Ok(Section::Errored { error })
+        }
+    }
+}
+
+impl<T> Section<T> {
+    fn result(&self) -> Option<&T> {
+        match self {
+            Section::Ok { result } => Some(result),
+            _ => None,
+        }
+    }
+
+    /// A section derived from this one's result, sharing its status
+    /// 由此區段結果衍生、狀態相同的區段
+    fn derive<U>(&self, derive: impl FnOnce(&T) -> U) -> Section<U> {
+        match self {
+            Section::Ok { result } => Section::Ok {
+                result: derive(result),
+            },
+            Section::Skipped => Section::Skipped,
+            Section::Errored { error } => Section::Errored {
+                error: error.clone(),
+            },
+        }
+    }
+}
+
+/// Merge the log and offline conflicts, using whichever of the two sections succeeded
+/// 合併 log 與離線衝突，使用兩個區段中成功的那些
+fn unified_conflicts(
+    conflicts: &Section<ScanResult>,
+    guid_groups: &Section<Vec<GuidGroup>>,
+    modpacks: &ModpackFolders,
+) -> Section<Vec<UnifiedConflict>> {
+    let (log_conflicts, groups) = match (conflicts.result(), guid_groups.result()) {
+        (None, None) => return conflicts.derive(|_| Vec::new()),
+        (scan, groups) => (
+            scan.map_or(&[][..], |scan| &scan.conflicts),
+            groups.map_or(&[][..], Vec::as_slice),
+        ),
+    };
+    let mut merged = conflict_sources::merge_conflict_sources(log_conflicts, groups);
+    for conflict in &mut merged {
+        for member in std::iter::once(&mut conflict.loaded).chain(&mut conflict.skipped) {
+            member.entry.managed = modpacks.contains(Path::new(&member.entry.path));
+        }
+    }
+    Section::Ok { result: merged }
+}
+
+/// Sum what each stage reports as removable, counting each path once in the grand total
+/// 加總各階段回報可移除的大小，總計中每個路徑只計算一次
+fn wasted_totals(report: &FullReport) -> WastedTotals {
+    let mut files: HashMap<String, u64> = HashMap::new();
+    let mut add = |entries: &mut dyn Iterator<Item = &ModEntry>| {
+        entries
+            .map(|entry| {
+                files.insert(entry.path.to_lowercase(), entry.size);
+                entry.size
+            })
+            .sum::<u64>()
+    };
+
+    let conflict_bytes = report.conflicts.result().map_or(0, |scan| {
+        add(&mut scan
+            .conflicts
+            .iter()
+            .flat_map(|conflict| &conflict.skipped)
+            .filter(|entry| entry.frees_space()))
+    });
+    // Every copy but the largest, as in `GuidGroup::wasted_bytes`; copies with other hardlinks
+    // free nothing
+    // 除最大檔案外的所有複本，與 `GuidGroup::wasted_bytes` 相同；仍有其他硬連結的複本不會釋放空間
+    let guid_duplicate_bytes = report.guid_groups.result().map_or(0, |groups| {
+        add(&mut groups.iter().flat_map(|group| {
+            let largest = (0..group.files.len()).max_by_key(|&index| group.files[index].entry.size);
+            group
+                .files
+                .iter()
+                .enumerate()
+                .filter(move |&(index, _)| Some(index) != largest)
+                .map(|(_, file)| &file.entry)
+                .filter(|entry| entry.frees_space())
+        }))
+    });
+    let corrupt_bytes = report.corrupt_mods.result().map_or(0, |findings| {
+        add(&mut findings.iter().map(|finding| &finding.mod_entry))
+    });
+    let junk_bytes = report
+        .junk_files
+        .result()
+        .map_or(0, |junk| add(&mut junk.iter().map(|file| &file.mod_entry)));
+
+    WastedTotals {
+        conflict_bytes,
+        guid_duplicate_bytes,
+        corrupt_bytes,
+        junk_bytes,
+        total_bytes: files.values().sum(),
+    }
+}
+
+/// Run self-check, conflict scan, offline GUID scan, corrupt-mod scan, junk-file scan and the
+/// missing-dependency check in order, then total the wasted bytes. Progress of every stage goes
+/// to `on_progress` with a `step n/7` prefix. A failing stage is marked errored and the others still run; cancelling
+/// stops the analysis.
+/// 依序執行自我檢查、衝突掃描、離線 GUID 掃描、損壞模組掃描、多餘檔案掃描與缺少相依模組檢查，最後統計浪費的空間；
+/// 各階段的進度都以 `step n/7` 前綴送往 `on_progress`；失敗的階段標記為錯誤，其餘階段照常執行；取消則中止分析
+pub(crate) fn run_full_analysis(
+    context: &AnalysisContext,
+    options: &FullAnalysisOptions,
+    on_progress: OnProgress,
+) -> Result<FullReport, AppError> {
+    let game_path = Path::new(context.game_path);
+    let skipped = |stage: AnalysisStage| options.skip.contains(&stage);
+    let generated = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+
+    let self_check = run_stage(
+        1,
+        "checking the install",
+        skipped(AnalysisStage::SelfCheck),
+        on_progress,
+        |_| Ok(self_check::run_self_check(game_path, context.write_mode)),
+    )?;
+    let conflicts = run_stage(
+        2,
+        "parsing the log",
+        skipped(AnalysisStage::Conflicts),
+        on_progress,
+        |on_progress| {
+            scan::scan_conflicts_cached(
+                &context.scan_cache_path,
+                context.game_path,
+                context.mod_extensions,
+                options.force_rescan,
+                on_progress,
+                Timings::new(false),
+            )
+        },
+    )?;
+    let mod_files = run_stage(
+        3,
+        "reading manifests",
+        skipped(AnalysisStage::GuidScan),
+        on_progress,
+        |on_progress| {
+            mods_scan::scan_mods_folder(
+                &context.index_path,
+                game_path,
+                context.follow_links,
+                context.mod_extensions,
+                context.cancel,
+                on_progress,
+            )
+        },
+    )?;
+    let guid_case_collisions = mod_files.derive(|files| mods_scan::guid_case_collisions(files));
+    let guid_groups = mod_files.derive(|files| mods_scan::guid_groups(files.clone()));
+    let unified_conflicts = unified_conflicts(&conflicts, &guid_groups, context.modpacks);
+    let corrupt_mods = run_stage(
+        4,
+        "checking archives",
+        skipped(AnalysisStage::CorruptScan),
+        on_progress,
+        |on_progress| {
+            corrupt_mods::scan_for_corrupt_mods(
+                game_path,
+                context.follow_links,
+                context.mod_extensions,
+                context.cancel,
+                on_progress,
+            )
+        },
+    )?;
+    let junk_files = run_stage(
+        5,
+        "looking for junk files",
+        skipped(AnalysisStage::JunkScan),
+        on_progress,
+        |on_progress| {
+            junk_files::find_junk_files(
+                game_path,
+                context.follow_links,
+                context.mod_extensions,
+                context.cancel,
+                on_progress,
+            )
+        },
+    )?;
+
+    let missing_dependencies = run_stage(
+        6,
+        "looking for missing dependencies",
+        skipped(AnalysisStage::MissingDependencies),
+        on_progress,
+        |_| {
+            let log = scan::find_and_read_log(context.game_path)?;
+            let mut dependencies = missing_deps::parse_missing_dependencies(&log);
+            missing_deps::cross_reference(
+                &mut dependencies,
+                mod_files.result().map_or(&[][..], Vec::as_slice),
+                game_path,
+                context.follow_links,
+                context.mod_extensions,
+            );
+            Ok(dependencies)
+        },
+    )?;
+
+    on_progress(
+        Progress::new(0, 0)
+            .with_message(format!("step {0}/{0}: totalling wasted space", STAGE_COUNT)),
+    );
+    let mut report = FullReport {
+        game_path: context.game_path.to_string(),
+        generated,
+        self_check,
+        conflicts,
+        guid_groups,
+        guid_case_collisions,
+        unified_conflicts,
+        corrupt_mods,
+        junk_files,
+        missing_dependencies,
+        totals: WastedTotals::default(),
+        export: None,
+    };
+    report.totals = wasted_totals(&report);
+    Ok(report)
+}
+
+// ───────────────────────────────────────────────
+// Export / 匯出
+// ───────────────────────────────────────────────
+
+/// Write `report` to `path` as pretty-printed JSON and record the outcome in `report.export`
+/// 將 `report` 以格式化 JSON 寫入 `path`，並將結果記錄於 `report.export`
+pub(crate) fn export(report: &mut FullReport, path: &Path) {
+    let written = serde_json::to_string_pretty(&*report)
+        .map_err(|e| AppError::io("io.write_full_report", Some(path), e))
+        .and_then(|content| {
+            fs::write(path, content)
+                .map_err(|e| AppError::io("io.write_full_report", Some(path), e))
+        });
+    report.export = Some(match written {
+        Ok(()) => Section::Ok {
+            result: path.to_string_lossy().to_string(),
+        },
+        Err(error) => {
+            app_log::write(&format!("full report export failed: {}", error));
+            Section::Errored { error }
+        }
+    });
+}
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Executables in the game root of Koikatsu, Koikatsu Sunshine and their studio and VR builds
+/// Koikatsu、Koikatsu Sunshine 及其 studio 與 VR 版本位於遊戲根目錄的執行檔
+#[cfg(windows)]
+const GAME_EXECUTABLES: &[&str] = &[
+    "Koikatu.exe",
+    "Koikatsu Party.exe",
+    "KoikatuVR.exe",
+    "Koikatsu Party VR.exe",
+    "KoikatsuSunshine.exe",
+    "KoikatsuSunshine_VR.exe",
+    "CharaStudio.exe",
+];
+
+// ───────────────────────────────────────────────
+// Detection / 偵測
+// ───────────────────────────────────────────────
+
+/// Name of a game executable under `game_path` that is currently running. Windows refuses
+/// write access to the image of a running process, so that sharing violation is the signal;
+/// a missing or read-only executable counts as not running.
+/// 回傳 `game_path` 底下目前正在執行的遊戲執行檔名稱；Windows 不允許以寫入模式開啟執行中的程式映像，
+/// 因此以該共用違規作為判斷依據；執行檔不存在或唯讀時視為未執行
+#[cfg(windows)]
+pub(crate) fn running_executable(game_path: &Path) -> Option<&'static str> {
+    use std::fs::OpenOptions;
+
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    GAME_EXECUTABLES.iter().copied().find(|name| {
+        OpenOptions::new()
+            .write(true)
+            .open(crate::long_path::extend(&game_path.join(name)))
+            .is_err_and(|e| e.raw_os_error() == Some(ERROR_SHARING_VIOLATION))
+    })
+}
+
+/// Elsewhere the game runs under Wine/Proton, where the check does not apply; always none
+/// 其他平台的遊戲透過 Wine/Proton 執行，此檢查不適用，一律回傳無
+#[cfg(not(windows))]
+pub(crate) fn running_executable(_game_path: &Path) -> Option<&'static str> {
+    None
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{
+    diagnostics::detect_game_variant, error::AppError, messages::Message, mods_scan,
+    operations::CancelToken, parser::ModEntry, progress::OnProgress,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+const KOIKATSU_TAGS: &[&str] = &["koikatsu", "koikatu", "コイカツ", "koikatsuparty", "kk"];
+
+/// `<game>` values accepted by each detected variant, compared lowercase without spaces.
+/// Party is the Steam release of Koikatsu and a studio-only install runs Koikatsu mods.
+/// 各遊戲版本接受的 `<game>` 值（轉小寫並去除空白後比對）；Party 為 Koikatsu 的 Steam 版，單獨的 Studio 使用 Koikatsu 模組
+const ACCEPTED_TAGS: &[(&str, &[&str])] = &[
+    ("Koikatsu", KOIKATSU_TAGS),
+    ("Koikatsu Party", KOIKATSU_TAGS),
+    ("CharaStudio", KOIKATSU_TAGS),
+    (
+        "Koikatsu Sunshine",
+        &["koikatsusunshine", "コイカツサンシャイン", "kks"],
+    ),
+];
+
+/// A zipmod whose `<game>` tags do not include the installed game. Such mods are usually
+/// meant for the other variant, so they are better disabled or moved aside than deleted.
+/// `<game>` 標籤不包含目前遊戲的 zipmod；這類模組多半屬於另一版本，較適合停用或移開而非刪除
+#[derive(Serialize)]
+pub(crate) struct WrongGameMod {
+    #[serde(flatten)]
+    pub entry: ModEntry,
+    /// The manifest's `<game>` values as written, for display like "tagged: KoikatsuSunshine"
+    /// manifest 中原樣的 `<game>` 值，供顯示「tagged: KoikatsuSunshine」
+    pub tags: Vec<String>,
+}
+
+// ───────────────────────────────────────────────
+// Detection / 偵測
+// ───────────────────────────────────────────────
+
+fn normalize_tag(tag: &str) -> String {
+    tag.chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Flag zipmods tagged only for other games. Mods without `<game>` tags are universal.
+/// Fails with `input.game_variant_unknown` when the install's variant cannot be detected.
+/// 找出只標示其他遊戲的 zipmod；沒有 `<game>` 標籤的模組視為通用。無法判斷遊戲版本時回傳 `input.game_variant_unknown`
+pub(crate) fn find_wrong_game_mods(
+    index_path: &Path,
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<Vec<WrongGameMod>, AppError> {
+    let accepted = detect_game_variant(game_path)
+        .and_then(|variant| {
+            ACCEPTED_TAGS
+                .iter()
+                .find(|(name, _)| *name == variant)
+                .map(|(_, tags)| *tags)
+        })
+        .ok_or_else(|| AppError::InvalidInput {
+            message: Message::new("input.game_variant_unknown")
+                .with("path", game_path.to_string_lossy()),
+        })?;
+
+    let files = mods_scan::scan_mods_folder(
+        index_path,
+        game_path,
+        follow_links,
+        mod_extensions,
+        cancel,
+        on_progress,
+    )?;
+    Ok(files
+        .into_iter()
+        .filter_map(|file| {
+            let tags = file.manifest?.games;
+            let fits = tags.is_empty()
+                || tags
+                    .iter()
+                    .any(|tag| accepted.contains(&normalize_tag(tag).as_str()));
+            (!fits).then_some(WrongGameMod {
+                entry: file.entry,
+                tags,
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,148 @@
+use std::{collections::HashMap, iter};
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    conflict_sort::{self, ConflictSort},
+    error::AppError,
+    manifest::read_manifest_from_path,
+    messages::Message,
+    parser::{ModConflict, ModEntry, METADATA_POOL},
+    priority_folders,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Label of the bucket holding conflicts with no usable key
+/// 無法分組之衝突所在群組的標籤
+const UNKNOWN_GROUP: &str = "unknown";
+
+/// What conflicts are grouped by
+/// 衝突的分組依據
+#[derive(Clone, Copy)]
+pub(crate) enum GroupBy {
+    ManifestAuthor,
+    FilenameAuthor,
+    GuidPrefix,
+    /// The priority folder a member lies in, e.g. `!!!my overrides`
+    /// 成員所在的優先資料夾，例如 `!!!my overrides`
+    PriorityFolder,
+}
+
+/// Conflicts sharing one group key, with the bytes their skipped copies waste
+/// 同一分組的衝突，以及被跳過檔案所浪費的空間
+#[derive(Serialize)]
+pub(crate) struct ConflictGroup {
+    label: String,
+    conflicts: Vec<ModConflict>,
+    wasted_bytes: u64,
+    is_unknown: bool,
+}
+
+impl GroupBy {
+    /// Parse the `by` argument of `group_conflicts`
+    /// 解析 `group_conflicts` 的 `by` 參數
+    pub(crate) fn parse(by: &str) -> Result<Self, AppError> {
+        match by {
+            "manifest_author" => Ok(GroupBy::ManifestAuthor),
+            "filename_author" => Ok(GroupBy::FilenameAuthor),
+            "guid_prefix" => Ok(GroupBy::GuidPrefix),
+            "priority_folder" => Ok(GroupBy::PriorityFolder),
+            _ => Err(AppError::InvalidInput {
+                message: Message::new("input.unknown_group_by").with("by", by),
+            }),
+        }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Grouping / 分組
+// ───────────────────────────────────────────────
+
+/// GUID without its last dot-separated segment: `com.author.pack.item` → `com.author.pack`
+/// 去掉最後一段的 GUID：`com.author.pack.item` → `com.author.pack`
+fn guid_prefix(guid: &str) -> &str {
+    guid.rsplit_once('.')
+        .map(|(prefix, _)| prefix)
+        .unwrap_or(guid)
+}
+
+fn entry_key(entry: &ModEntry, by: GroupBy, priority_prefixes: &[String]) -> Option<String> {
+    let key = match by {
+        GroupBy::FilenameAuthor => entry.name_author.clone(),
+        GroupBy::ManifestAuthor => read_manifest_from_path(&entry.path).ok()?.author,
+        GroupBy::GuidPrefix => {
+            let manifest = read_manifest_from_path(&entry.path).ok()?;
+            Some(guid_prefix(&manifest.guid).to_string())
+        }
+        GroupBy::PriorityFolder => priority_folders::priority_folder_of(entry, priority_prefixes),
+    };
+    key.map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
+
+/// Key of a conflict: the loaded file's, or the first skipped file that has one
+/// 衝突的分組 key：取 loaded 檔案的，若無則取第一個有 key 的 skipped 檔案
+fn conflict_key(
+    conflict: &ModConflict,
+    by: GroupBy,
+    priority_prefixes: &[String],
+) -> Option<String> {
+    iter::once(&conflict.loaded)
+        .chain(&conflict.skipped)
+        .find_map(|entry| entry_key(entry, by, priority_prefixes))
+}
+
+/// Group conflicts by author, GUID prefix or priority folder (per `priority_prefixes`), largest
+/// waste first and `unknown` last. Keys are compared case-insensitively; the first spelling
+/// seen becomes the label. Conflicts inside a group are ordered by `sort`, or kept in input
+/// order without one.
+/// 依作者、GUID 前綴或優先資料夾（依 `priority_prefixes`）分組，浪費空間大者排前，`unknown` 排最後；
+/// key 不分大小寫，以第一次出現的寫法為標籤；組內衝突依 `sort` 排序，未指定時維持輸入順序
+pub(crate) fn group_conflicts(
+    conflicts: Vec<ModConflict>,
+    by: GroupBy,
+    sort: Option<ConflictSort>,
+    priority_prefixes: &[String],
+) -> Vec<ConflictGroup> {
+    // Manifest lookups open every archive, so run them on the metadata pool
+    // 讀取 manifest 需開啟壓縮檔，因此在 metadata 執行緒池中平行處理
+    let keys = METADATA_POOL.install(|| {
+        conflicts
+            .par_iter()
+            .map(|conflict| conflict_key(conflict, by, priority_prefixes))
+            .collect::<Vec<_>>()
+    });
+
+    let mut groups: Vec<ConflictGroup> = Vec::new();
+    let mut index_of: HashMap<Option<String>, usize> = HashMap::new();
+    for (conflict, key) in conflicts.into_iter().zip(keys) {
+        let folded = key.as_ref().map(|key| key.to_lowercase());
+        let index = *index_of.entry(folded).or_insert_with(|| {
+            groups.push(ConflictGroup {
+                is_unknown: key.is_none(),
+                label: key.unwrap_or_else(|| UNKNOWN_GROUP.to_string()),
+                conflicts: Vec::new(),
+                wasted_bytes: 0,
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[index];
+        group.wasted_bytes += conflict.skipped.iter().map(|e| e.size).sum::<u64>();
+        group.conflicts.push(conflict);
+    }
+
+    for group in &mut groups {
+        conflict_sort::sort_conflicts(&mut group.conflicts, sort);
+    }
+    groups.sort_by(|a, b| {
+        a.is_unknown
+            .cmp(&b.is_unknown)
+            .then_with(|| b.wasted_bytes.cmp(&a.wasted_bytes))
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    groups
+}
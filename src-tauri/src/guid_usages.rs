@@ -0,0 +1,183 @@
+use serde::Serialize;
+
+use crate::{
+    error::AppError, messages::Message, mods_scan::ModFile, operation_estimates::OperationEstimate,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Kinds of files that may use a GUID but that this app keeps no index of, so a lookup
+/// cannot say whether they do
+/// 可能使用某 GUID、但本程式沒有建立索引的檔案類型，因此查詢無法判斷它們是否使用
+const NOT_INDEXED: &[&str] = &["cards", "scenes", "coordinates"];
+
+/// A mod whose manifest names the GUID outside its own `<guid>`
+/// manifest 在自身 `<guid>` 以外提到該 GUID 的模組
+#[derive(Serialize)]
+pub(crate) struct ReferencingMod {
+    pub path: String,
+    /// The referencing mod's own GUID / 提到該 GUID 的模組本身的 GUID
+    pub guid: String,
+    /// Manifest elements naming the GUID / manifest 中提到該 GUID 的元素
+    pub elements: Vec<String>,
+}
+
+/// Everything the indices know to provide or use one GUID
+/// 索引中已知提供或使用某 GUID 的所有項目
+#[derive(Serialize)]
+pub(crate) struct GuidUsageReport {
+    pub guid: String,
+    /// Archives declaring the GUID as their own / 宣告此 GUID 為自身 GUID 的壓縮檔
+    pub provider_count: usize,
+    pub providers: Vec<String>,
+    pub referencing_mod_count: usize,
+    pub referencing_mods: Vec<ReferencingMod>,
+    /// `NOT_INDEXED`: not searched, so absent from the report rather than known not to use it
+    /// `NOT_INDEXED`：未搜尋，因此未出現在報告中不代表未使用
+    pub not_searched: &'static [&'static str],
+}
+
+/// What `guid_usages` returns
+/// `guid_usages` 的回傳內容
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub(crate) enum GuidUsages {
+    Found {
+        report: GuidUsageReport,
+    },
+    /// The asset index has never been built, so an empty report would be meaningless; building
+    /// it should take about `estimate`
+    /// 資源索引從未建立，空的報告沒有意義；建立索引約需 `estimate`
+    IndexNotBuilt {
+        estimate: OperationEstimate,
+    },
+}
+
+// ───────────────────────────────────────────────
+// Lookup / 查詢
+// ───────────────────────────────────────────────
+
+/// Whether a manifest value names `guid`, alone or in a list separated by commas, semicolons
+/// or whitespace
+/// 判斷 manifest 的值是否提到 `guid`，可單獨出現或位於以逗號、分號或空白分隔的清單中
+fn names_guid(value: &str, guid: &str) -> bool {
+    value
+        .split([',', ';'])
+        .flat_map(str::split_whitespace)
+        .any(|token| token.eq_ignore_ascii_case(guid))
+}
+
+/// `guid` without surrounding whitespace; fails with `guid_empty` when nothing is left
+/// 去除前後空白的 `guid`；沒有剩餘內容時回傳 `guid_empty`
+pub(crate) fn require_guid(guid: &str) -> Result<&str, AppError> {
+    match guid.trim() {
+        "" => Err(AppError::InvalidInput {
+            message: Message::new("input.guid_empty"),
+        }),
+        guid => Ok(guid),
+    }
+}
+
+/// The mods of `files` declaring `guid` and those whose manifest `extras` name it, each
+/// sorted by path. GUIDs compare without case, as Sideloader resolves them.
+/// `files` 中宣告 `guid` 的模組，以及 manifest `extras` 提到它的模組，各依路徑排序；
+/// GUID 比對不分大小寫，與 Sideloader 解析方式相同
+pub(crate) fn find_usages(files: &[ModFile], guid: &str) -> GuidUsageReport {
+    let mut providers = Vec::new();
+    let mut referencing_mods = Vec::new();
+    for file in files {
+        let Some(manifest) = &file.manifest else {
+            continue;
+        };
+        if manifest.guid.trim().eq_ignore_ascii_case(guid) {
+            providers.push(file.entry.path.clone());
+            continue;
+        }
+        let elements = manifest
+            .extras
+            .iter()
+            .filter(|(_, values)| values.iter().any(|value| names_guid(value, guid)))
+            .map(|(element, _)| element.clone())
+            .collect::<Vec<_>>();
+        if !elements.is_empty() {
+            referencing_mods.push(ReferencingMod {
+                path: file.entry.path.clone(),
+                guid: manifest.guid.clone(),
+                elements,
+            });
+        }
+    }
+    providers.sort();
+    referencing_mods.sort_by(|a, b| a.path.cmp(&b.path));
+
+    GuidUsageReport {
+        guid: guid.to_string(),
+        provider_count: providers.len(),
+        providers,
+        referencing_mod_count: referencing_mods.len(),
+        referencing_mods,
+        not_searched: NOT_INDEXED,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{find_usages, require_guid};
+    use crate::{
+        long_path, manifest, mods_scan,
+        operations::CancelToken,
+        progress,
+        test_support::{write_zip, SyntheticInstall, TestManifest},
+    };
+
+    #[test]
+    fn guid_usages_finds_manifests_naming_the_guid() {
+        let install = SyntheticInstall::new();
+        let profile = tempfile::tempdir().unwrap();
+        install.add_zipmod("base.zipmod", &TestManifest::new("author.base"));
+        let outfit = r#"<manifest schema-ver="1"><guid>author.outfit</guid>
+            <dependencies>
+                <dependency guid="Author.Base" />
+                <dependency>other.lib; more.lib</dependency>
+            </dependencies>
+            <MaterialEditor><shader>author.base</shader></MaterialEditor>
+        </manifest>"#;
+        let outfit_path = install.root().join("mods/outfit.zipmod");
+        write_zip(&outfit_path, &[("manifest.xml", outfit.as_bytes())]);
+        let shader = r#"<manifest><guid>author.shader</guid><materialeditor guid="author.base"/></manifest>"#;
+        write_zip(
+            &install.root().join("mods/shader.zipmod"),
+            &[("manifest.xml", shader.as_bytes())],
+        );
+
+        let extras = manifest::parse_manifest(outfit).unwrap().extras;
+        let files = mods_scan::scan_mods_folder(
+            &profile.path().join("asset_index.json"),
+            Path::new(&install.game_path()),
+            false,
+            &["zipmod".to_string()],
+            &CancelToken::default(),
+            &progress::ignore,
+        )
+        .unwrap();
+        let report = find_usages(&files, require_guid(" author.base ").unwrap());
+
+        assert_eq!(extras.len(), 1);
+        assert_eq!(
+            extras["dependencies"],
+            ["Author.Base", "other.lib; more.lib"]
+        );
+        assert_eq!(report.provider_count, 1);
+        assert_eq!(report.referencing_mod_count, 1);
+        assert_eq!(
+            report.referencing_mods[0].path,
+            long_path::display(&outfit_path)
+        );
+        assert_eq!(report.referencing_mods[0].elements, ["dependencies"]);
+        assert!(require_guid("  ").is_err());
+    }
+}
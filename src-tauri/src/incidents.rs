@@ -0,0 +1,214 @@
+use std::{
+    backtrace::{Backtrace, BacktraceStatus},
+    cell::RefCell,
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe, PanicHookInfo},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        LazyLock, Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{app_log, error::AppError};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// What the panic hook records about the most recent panic on a thread
+/// panic hook 記錄的該執行緒最近一次 panic 資訊
+struct PanicCapture {
+    message: String,
+    location: Option<String>,
+    backtrace: Backtrace,
+}
+
+/// Summary of a recorded panic, which `catch` turns into `AppError::InternalPanic`
+/// 已記錄之 panic 的摘要，由 `catch` 轉為 `AppError::InternalPanic`
+pub(crate) struct Incident {
+    pub id: String,
+    pub message: String,
+}
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<PanicCapture>> = const { RefCell::new(None) };
+}
+
+/// Full reports of this session, keyed by incident id
+/// 本次執行期間的完整報告，以 incident id 為 key
+static REPORTS: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(Default::default);
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// ───────────────────────────────────────────────
+// Panic Hook / Panic 攔截
+// ───────────────────────────────────────────────
+
+fn payload_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Install a panic hook that remembers message, location and backtrace for the panicking
+/// thread, then defers to the previous hook.
+/// Backtraces are only captured when `RUST_BACKTRACE` is enabled.
+/// 安裝 panic hook，記下該執行緒的訊息、位置與 backtrace 後交給原本的 hook；
+/// 只有在啟用 `RUST_BACKTRACE` 時才會擷取 backtrace
+pub(crate) fn install() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let capture = PanicCapture {
+            message: payload_message(info),
+            location: info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: Backtrace::capture(),
+        };
+        LAST_PANIC.with(|last| *last.borrow_mut() = Some(capture));
+        previous(info);
+    }));
+}
+
+// ───────────────────────────────────────────────
+// Reports / 事故報告
+// ───────────────────────────────────────────────
+
+fn next_incident_id() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{:x}-{:04x}", millis, seq)
+}
+
+/// Turn the panic just caught on this thread into an incident: build the full report,
+/// keep it for `get_incident_report`, and append it to the app log.
+/// Must be called on the thread that panicked.
+/// 將此執行緒剛攔截到的 panic 轉成事故報告：保存供 `get_incident_report` 查詢並寫入 app log；必須在發生 panic 的執行緒呼叫
+fn record(command: &str) -> Incident {
+    let capture = LAST_PANIC.with(|last| last.borrow_mut().take());
+    let id = next_incident_id();
+
+    let (message, location, backtrace) = match &capture {
+        Some(c) => (
+            c.message.clone(),
+            c.location.as_deref().unwrap_or("unknown location"),
+            match c.backtrace.status() {
+                BacktraceStatus::Captured => c.backtrace.to_string(),
+                _ => "disabled (set RUST_BACKTRACE=1 to capture)".to_string(),
+            },
+        ),
+        None => (
+            "unknown panic".to_string(),
+            "unknown location",
+            "unavailable".to_string(),
+        ),
+    };
+
+    let report = format!(
+        "=== Incident {} ===\ncommand: {}\nversion: {}\nmessage: {}\nlocation: {}\nbacktrace:\n{}",
+        id,
+        command,
+        env!("CARGO_PKG_VERSION"),
+        message,
+        location,
+        backtrace
+    );
+    app_log::write(&report);
+    REPORTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id.clone(), report);
+
+    Incident { id, message }
+}
+
+/// Run `work`, turning a panic inside it into an incident tagged with `command` and an
+/// `AppError::InternalPanic` carrying only its id and message; what `safe_invoke` runs on the
+/// worker thread
+/// 執行 `work`，其中的 panic 會被記錄為標記 `command` 的事故，並轉為只帶 id 與訊息的 `AppError::InternalPanic`；
+/// 即 `safe_invoke` 在工作執行緒上執行的內容
+pub(crate) fn catch<T>(
+    command: &str,
+    work: impl FnOnce() -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    match panic::catch_unwind(AssertUnwindSafe(work)) {
+        Ok(result) => result,
+        Err(_) => {
+            let incident = record(command);
+            Err(AppError::InternalPanic {
+                incident_id: Some(incident.id),
+                detail: incident.message,
+            })
+        }
+    }
+}
+
+/// Full text of an incident recorded during this session
+/// 取得本次執行期間記錄的事故完整內容
+pub(crate) fn report(id: &str) -> Option<String> {
+    REPORTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(id)
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{catch, install, report, REPORTS};
+    use crate::error::AppError;
+
+    #[test]
+    fn a_panic_becomes_an_internal_panic_with_its_report() {
+        install();
+
+        let result = catch("test_command", || -> Result<(), AppError> {
+            panic!("boom");
+        });
+
+        let Err(AppError::InternalPanic {
+            incident_id: Some(id),
+            detail,
+        }) = result
+        else {
+            panic!("expected an internal panic");
+        };
+        assert_eq!(detail, "boom");
+        let report = report(&id).unwrap();
+        assert!(report.contains("command: test_command"));
+        assert!(report.contains("message: boom"));
+        assert_eq!(catch("test_command", || Ok(1)).unwrap(), 1);
+    }
+
+    #[test]
+    fn a_poisoned_report_store_still_records_incidents() {
+        install();
+        let _ = thread::spawn(|| {
+            let _guard = REPORTS.lock();
+            panic!("poison the report store");
+        })
+        .join();
+
+        let result = catch("test_command", || -> Result<(), AppError> {
+            panic!("after poison");
+        });
+
+        let Err(AppError::InternalPanic {
+            incident_id: Some(id),
+            ..
+        }) = result
+        else {
+            panic!("expected an internal panic");
+        };
+        assert!(report(&id).unwrap().contains("message: after poison"));
+    }
+}
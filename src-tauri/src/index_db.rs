@@ -0,0 +1,519 @@
+use std::{fs, path::Path};
+
+#[cfg(feature = "sqlite")]
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+#[cfg(feature = "sqlite")]
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::Serialize;
+
+use crate::error::AppError;
+#[cfg(feature = "sqlite")]
+use crate::{app_log, asset_index::IndexedMod, first_seen::SeenRecord, manifest::ManifestData};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Database inside each profile directory holding the indices of a large install
+/// 各 profile 資料夾中存放大型安裝索引的資料庫
+const DB_FILE: &str = "indexes.db";
+
+/// JSON indices of a profile that the database replaces
+/// 資料庫所取代的 profile JSON 索引
+const JSON_FILES: &[&str] = &["asset_index.json", "first_seen.json"];
+
+/// Archives in the asset index from which a profile moves to the database; below it,
+/// rewriting the JSON files whole is quick enough
+/// 資源索引中的壓縮檔達此數量時 profile 改用資料庫；低於此數時整份改寫 JSON 檔案已足夠快
+#[cfg(feature = "sqlite")]
+pub(crate) const MIN_MODS: usize = 5000;
+
+/// How long a connection waits for another one to finish writing
+/// 連線等待另一個連線完成寫入的時間
+#[cfg(feature = "sqlite")]
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Schema changes in order; `PRAGMA user_version` records how many a database has applied
+/// 依序排列的結構變更；`PRAGMA user_version` 記錄資料庫已套用的數量
+#[cfg(feature = "sqlite")]
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE meta (key TEXT PRIMARY KEY, value INTEGER NOT NULL);
+     CREATE TABLE mods (
+         path TEXT PRIMARY KEY,
+         size INTEGER NOT NULL,
+         modified INTEGER,
+         manifest TEXT,
+         placeholder INTEGER NOT NULL
+     );
+     CREATE TABLE mod_entries (path TEXT NOT NULL, name TEXT NOT NULL);
+     CREATE INDEX mod_entries_path ON mod_entries (path);
+     CREATE TABLE first_seen (
+         key TEXT PRIMARY KEY,
+         first_seen INTEGER NOT NULL,
+         missed_scans INTEGER NOT NULL
+     );",
+];
+
+/// `meta` key of the asset index version the `mods` rows were written with
+/// `mods` 資料列寫入時的資源索引版本於 `meta` 中的 key
+#[cfg(feature = "sqlite")]
+const ASSET_INDEX_VERSION: &str = "asset_index_version";
+
+/// The index database of one profile
+/// 單一 profile 的索引資料庫
+#[cfg(feature = "sqlite")]
+pub(crate) struct IndexDb {
+    conn: Connection,
+    path: PathBuf,
+}
+
+/// Where a profile keeps its indices
+/// profile 存放索引的方式
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Backend {
+    Json,
+    Sqlite,
+}
+
+/// Result of `compact`
+/// `compact` 的結果
+#[derive(Serialize)]
+pub(crate) struct CompactReport {
+    pub backend: Backend,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+// ───────────────────────────────────────────────
+// Opening / 開啟
+// ───────────────────────────────────────────────
+
+#[cfg(feature = "sqlite")]
+impl IndexDb {
+    /// The database of the profile `index_file` (one of its JSON indices) belongs to, if the
+    /// profile has moved to one
+    /// `index_file`（profile 的 JSON 索引之一）所屬 profile 的資料庫；profile 尚未改用資料庫時為 None
+    pub fn existing(index_file: &Path) -> Option<Result<Self, AppError>> {
+        let path = index_file.with_file_name(DB_FILE);
+        path.is_file().then(|| Self::open(path))
+    }
+
+    /// Create the database of the profile in `profile_dir`, or open it when it exists
+    /// 建立 `profile_dir` 中 profile 的資料庫；已存在時直接開啟
+    pub fn create(profile_dir: &Path) -> Result<Self, AppError> {
+        fs::create_dir_all(profile_dir)
+            .map_err(|e| AppError::io("io.create_index_directory", Some(profile_dir), e))?;
+        Self::open(profile_dir.join(DB_FILE))
+    }
+
+    fn open(path: PathBuf) -> Result<Self, AppError> {
+        let opened = Connection::open(&path).and_then(|mut conn| {
+            conn.busy_timeout(BUSY_TIMEOUT)?;
+            migrate(&mut conn)?;
+            Ok(conn)
+        });
+        match opened {
+            Ok(conn) => Ok(IndexDb { conn, path }),
+            Err(e) => Err(AppError::io("io.open_index_db", Some(&path), e)),
+        }
+    }
+
+    fn read<T>(
+        &self,
+        query: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, AppError> {
+        query(&self.conn).map_err(|e| AppError::io("io.read_index_db", Some(&self.path), e))
+    }
+
+    fn write(
+        &mut self,
+        change: impl FnOnce(&Transaction) -> rusqlite::Result<()>,
+    ) -> Result<(), AppError> {
+        let written = self.conn.transaction().and_then(|tx| {
+            change(&tx)?;
+            tx.commit()
+        });
+        written.map_err(|e| AppError::io("io.write_index_db", Some(&self.path), e))
+    }
+}
+
+/// Apply the migrations the database has not seen yet, all in one transaction
+/// 在同一個交易中套用資料庫尚未套用的結構變更
+#[cfg(feature = "sqlite")]
+fn migrate(conn: &mut Connection) -> rusqlite::Result<()> {
+    let applied = conn.pragma_query_value(None, "user_version", |row| row.get::<_, usize>(0))?;
+    if applied >= MIGRATIONS.len() {
+        return Ok(());
+    }
+    let tx = conn.transaction()?;
+    for migration in &MIGRATIONS[applied..] {
+        tx.execute_batch(migration)?;
+    }
+    tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+    tx.commit()
+}
+
+/// Delete the JSON index `file` once its content is in the database; a copy that cannot be
+/// deleted is logged, and is migrated again on its next load
+/// 內容已寫入資料庫後刪除 JSON 索引 `file`；無法刪除時記錄，並於下次載入時再次遷移
+#[cfg(feature = "sqlite")]
+pub(crate) fn retire_json(file: &Path) {
+    match fs::remove_file(file) {
+        Ok(()) => app_log::write(&format!("index moved to database: {}", file.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => app_log::write(&format!(
+            "migrated index not deleted: {}: {}",
+            file.display(),
+            e
+        )),
+    }
+}
+
+// ───────────────────────────────────────────────
+// Asset Index / 資源索引
+// ───────────────────────────────────────────────
+
+#[cfg(feature = "sqlite")]
+fn to_sql_error(e: serde_json::Error) -> rusqlite::Error {
+    rusqlite::Error::ToSqlConversionFailure(Box::new(e))
+}
+
+/// A `mods` row without its entries; a manifest that no longer deserializes reads as none
+/// 不含 entry 的 `mods` 資料列；無法再反序列化的 manifest 視為沒有
+#[cfg(feature = "sqlite")]
+fn mod_row(row: &rusqlite::Row) -> rusqlite::Result<(String, IndexedMod)> {
+    let manifest = row.get::<_, Option<String>>(3)?;
+    Ok((
+        row.get(0)?,
+        IndexedMod {
+            size: row.get(1)?,
+            modified: row.get(2)?,
+            manifest: manifest.and_then(|manifest| serde_json::from_str(&manifest).ok()),
+            entries: Vec::new(),
+            placeholder: row.get(4)?,
+        },
+    ))
+}
+
+/// Whether the `mods` rows were written with asset index `version`
+/// `mods` 資料列是否以資源索引版本 `version` 寫入
+#[cfg(feature = "sqlite")]
+fn current_version(conn: &Connection, version: u32) -> rusqlite::Result<bool> {
+    let stored = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = ?1",
+            [ASSET_INDEX_VERSION],
+            |row| row.get::<_, u32>(0),
+        )
+        .optional()?;
+    Ok(stored == Some(version))
+}
+
+#[cfg(feature = "sqlite")]
+impl IndexDb {
+    /// Every record of the asset index with its entries; none when written with another
+    /// `version`, so the index is rebuilt as the JSON one would be
+    /// 資源索引的所有紀錄及其 entry；以其他 `version` 寫入時為空，與 JSON 索引相同會被重建
+    pub fn load_mods(&self, version: u32) -> Result<HashMap<String, IndexedMod>, AppError> {
+        self.read(|conn| {
+            if !current_version(conn, version)? {
+                return Ok(HashMap::new());
+            }
+            let mut mods = conn
+                .prepare("SELECT path, size, modified, manifest, placeholder FROM mods")?
+                .query_map([], mod_row)?
+                .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+            let mut entries = conn.prepare("SELECT path, name FROM mod_entries ORDER BY rowid")?;
+            let mut rows = entries.query([])?;
+            while let Some(row) = rows.next()? {
+                if let Some(record) = mods.get_mut(&row.get::<_, String>(0)?) {
+                    record.entries.push(row.get(1)?);
+                }
+            }
+            Ok(mods)
+        })
+    }
+
+    /// The record of each of `paths` without its entries, in order, through one prepared
+    /// query; None where there is no record of `version`
+    /// 以同一個預先編譯的查詢依序取得 `paths` 各自的紀錄（不含 entry）；沒有 `version` 版本的紀錄時為 None
+    pub fn mod_records(
+        &self,
+        version: u32,
+        paths: &[&str],
+    ) -> Result<Vec<Option<IndexedMod>>, AppError> {
+        self.read(|conn| {
+            if !current_version(conn, version)? {
+                return Ok(paths.iter().map(|_| None).collect());
+            }
+            let mut query = conn.prepare(
+                "SELECT path, size, modified, manifest, placeholder FROM mods WHERE path = ?1",
+            )?;
+            paths
+                .iter()
+                .map(|path| {
+                    query
+                        .query_row([path], mod_row)
+                        .optional()
+                        .map(|row| row.map(|(_, record)| record))
+                })
+                .collect()
+        })
+    }
+
+    /// Path and size of every record of `version`
+    /// `version` 版本所有紀錄的路徑與大小
+    pub fn mod_sizes(&self, version: u32) -> Result<Vec<(String, u64)>, AppError> {
+        self.read(|conn| {
+            if !current_version(conn, version)? {
+                return Ok(Vec::new());
+            }
+            conn.prepare("SELECT path, size FROM mods")?
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect()
+        })
+    }
+
+    /// Store `mods` as the asset index of `version`, writing only the records whose size, mtime
+    /// or placeholder state differ from the stored ones and deleting those no longer present
+    /// 將 `mods` 存為 `version` 版本的資源索引；只寫入大小、修改時間或預留位置狀態與已存紀錄不同者，並刪除已不存在的紀錄
+    pub fn save_mods(
+        &mut self,
+        version: u32,
+        mods: &HashMap<String, IndexedMod>,
+    ) -> Result<(), AppError> {
+        self.write(|tx| {
+            if !current_version(tx, version)? {
+                tx.execute_batch("DELETE FROM mod_entries; DELETE FROM mods;")?;
+                tx.execute(
+                    "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+                    params![ASSET_INDEX_VERSION, version],
+                )?;
+            }
+            let stored = tx
+                .prepare("SELECT path, size, modified, placeholder FROM mods")?
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        (row.get(1)?, row.get(2)?, row.get(3)?),
+                    ))
+                })?
+                .collect::<rusqlite::Result<HashMap<String, (u64, Option<u64>, bool)>>>()?;
+
+            let mut delete_entries = tx.prepare("DELETE FROM mod_entries WHERE path = ?1")?;
+            let mut upsert = tx.prepare(
+                "INSERT OR REPLACE INTO mods (path, size, modified, manifest, placeholder)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            let mut insert_entry =
+                tx.prepare("INSERT INTO mod_entries (path, name) VALUES (?1, ?2)")?;
+            for (path, record) in mods {
+                let state = (record.size, record.modified, record.placeholder);
+                if stored.get(path) == Some(&state) {
+                    continue;
+                }
+                let manifest = record
+                    .manifest
+                    .as_ref()
+                    .map(serde_json::to_string::<ManifestData>)
+                    .transpose()
+                    .map_err(to_sql_error)?;
+                delete_entries.execute([path])?;
+                upsert.execute(params![
+                    path,
+                    record.size,
+                    record.modified,
+                    manifest,
+                    record.placeholder
+                ])?;
+                for name in &record.entries {
+                    insert_entry.execute([path, name])?;
+                }
+            }
+
+            let mut delete = tx.prepare("DELETE FROM mods WHERE path = ?1")?;
+            for path in stored.keys().filter(|path| !mods.contains_key(*path)) {
+                delete_entries.execute([path])?;
+                delete.execute([path])?;
+            }
+            Ok(())
+        })
+    }
+}
+
+// ───────────────────────────────────────────────
+// First-Seen Records / 首次發現紀錄
+// ───────────────────────────────────────────────
+
+#[cfg(feature = "sqlite")]
+impl IndexDb {
+    pub fn load_first_seen(&self) -> Result<HashMap<String, SeenRecord>, AppError> {
+        self.read(|conn| {
+            conn.prepare("SELECT key, first_seen, missed_scans FROM first_seen")?
+                .query_map([], |row| {
+                    Ok((
+                        row.get(0)?,
+                        SeenRecord {
+                            first_seen: row.get(1)?,
+                            missed_scans: row.get(2)?,
+                        },
+                    ))
+                })?
+                .collect()
+        })
+    }
+
+    /// Store `files` as the first-seen records, writing only the records that changed
+    /// 將 `files` 存為首次發現紀錄，只寫入有變動的紀錄
+    pub fn save_first_seen(&mut self, files: &HashMap<String, SeenRecord>) -> Result<(), AppError> {
+        let stored = self.load_first_seen()?;
+        self.write(|tx| {
+            let mut upsert = tx.prepare(
+                "INSERT OR REPLACE INTO first_seen (key, first_seen, missed_scans)
+                 VALUES (?1, ?2, ?3)",
+            )?;
+            for (key, record) in files {
+                let unchanged = stored.get(key).is_some_and(|old| {
+                    old.first_seen == record.first_seen && old.missed_scans == record.missed_scans
+                });
+                if !unchanged {
+                    upsert.execute(params![key, record.first_seen, record.missed_scans])?;
+                }
+            }
+            let mut delete = tx.prepare("DELETE FROM first_seen WHERE key = ?1")?;
+            for key in stored.keys().filter(|key| !files.contains_key(*key)) {
+                delete.execute([key])?;
+            }
+            Ok(())
+        })
+    }
+}
+
+// ───────────────────────────────────────────────
+// Maintenance / 維護
+// ───────────────────────────────────────────────
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+/// Which storage the indices of the profile in `profile_dir` use
+/// `profile_dir` 中 profile 的索引所使用的儲存方式
+pub(crate) fn backend(profile_dir: &Path) -> Backend {
+    match cfg!(feature = "sqlite") && profile_dir.join(DB_FILE).is_file() {
+        true => Backend::Sqlite,
+        false => Backend::Json,
+    }
+}
+
+/// VACUUM the index database of the profile in `profile_dir` and report its size before and
+/// after. A profile on the JSON indices is left as is and reports their combined size, since
+/// they are rewritten whole on every save and never hold free space.
+/// 對 `profile_dir` 中 profile 的索引資料庫執行 VACUUM，並回報前後的大小；使用 JSON 索引的 profile
+/// 不做任何處理並回報其合計大小，因為 JSON 索引每次儲存都整份改寫，不會有閒置空間
+pub(crate) fn compact(profile_dir: &Path) -> Result<CompactReport, AppError> {
+    match backend(profile_dir) {
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => {
+            let path = profile_dir.join(DB_FILE);
+            let bytes_before = file_size(&path);
+            let db = IndexDb::open(path.clone())?;
+            db.conn
+                .execute_batch("VACUUM")
+                .map_err(|e| AppError::io("io.compact_index_db", Some(&path), e))?;
+            drop(db);
+            Ok(CompactReport {
+                backend: Backend::Sqlite,
+                bytes_before,
+                bytes_after: file_size(&path),
+            })
+        }
+        backend => {
+            let bytes = JSON_FILES
+                .iter()
+                .map(|file| file_size(&profile_dir.join(file)))
+                .sum();
+            Ok(CompactReport {
+                backend,
+                bytes_before: bytes,
+                bytes_after: bytes,
+            })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use std::path::Path;
+
+    use super::{compact, Backend, IndexDb};
+    use crate::{
+        asset_index, first_seen,
+        operations::CancelToken,
+        parser::{self, ModEntry},
+        profiles, progress,
+        test_support::{SyntheticInstall, TestManifest},
+        timings::Timings,
+    };
+
+    #[test]
+    fn index_database_takes_over_the_json_indices() {
+        let install = SyntheticInstall::new();
+        let profile = tempfile::tempdir().unwrap();
+        let index_path = profile.path().join("asset_index.json");
+        let build = || {
+            asset_index::build_asset_index(
+                &index_path,
+                Path::new(&install.game_path()),
+                false,
+                &["zipmod".to_string()],
+                &CancelToken::default(),
+                &progress::ignore,
+                Timings::default(),
+            )
+            .unwrap()
+        };
+        let hair = install.add_zipmod("hair.zipmod", &TestManifest::new("author.hair"));
+        let mut entries = [parser::build_mod_entry(
+            &hair,
+            "hair.zipmod",
+            &profiles::default_mod_extensions(),
+        )];
+        let observe = |entries: &mut [ModEntry]| {
+            let coverage = first_seen::Coverage::Partial;
+            first_seen::observe(profile.path(), &install.game_path(), entries, coverage);
+        };
+        build();
+        observe(&mut entries);
+        let seen = entries[0].first_seen;
+        let report = compact(profile.path()).unwrap();
+        assert_eq!(report.backend, Backend::Json);
+        assert_eq!(report.bytes_before, report.bytes_after);
+
+        IndexDb::create(profile.path()).unwrap();
+        build();
+        install.add_zipmod("outfit.zipmod", &TestManifest::new("author.outfit"));
+        let summary = build();
+        observe(&mut entries);
+
+        assert!(!index_path.exists());
+        assert!(!profile.path().join("first_seen.json").exists());
+        assert_eq!((summary.mods_indexed, summary.mods_reread), (2, 1));
+        assert_eq!(entries[0].first_seen, seen);
+        let cached = asset_index::cached_manifest(
+            &index_path,
+            &entries[0].path,
+            entries[0].size,
+            entries[0].modified,
+        );
+        assert_eq!(
+            cached.flatten().map(|manifest| manifest.guid).as_deref(),
+            Some("author.hair")
+        );
+        let report = compact(profile.path()).unwrap();
+        assert_eq!(report.backend, Backend::Sqlite);
+        assert!(report.bytes_after > 0 && report.bytes_after <= report.bytes_before);
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::Mutex,
+    thread,
+};
+
+use serde::Serialize;
+
+use crate::{
+    error::AppError,
+    long_path,
+    mods_scan::{self, ModFile},
+    operations::CancelToken,
+    progress::{OnProgress, Progress},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// One install taking part in a diff, with the manifest cache of its profile
+/// 參與比較的單一安裝，以及其 profile 的 manifest 快取
+pub(crate) struct DiffSource<'a> {
+    pub index_path: &'a Path,
+    pub game_path: &'a Path,
+    pub follow_links: bool,
+    pub mod_extensions: &'a [String],
+}
+
+/// Which install of the diff
+/// 比較中的哪一個安裝
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Side {
+    A,
+    B,
+}
+
+/// The same mod present in both installs
+/// 兩個安裝中都存在的同一模組
+#[derive(Serialize)]
+pub(crate) struct MatchedMod {
+    a: ModFile,
+    b: ModFile,
+}
+
+/// A GUID present in both installs with different manifest versions
+/// 兩個安裝中都存在、但 manifest 版本不同的 GUID
+#[derive(Serialize)]
+pub(crate) struct VersionDifference {
+    guid: String,
+    a: ModFile,
+    b: ModFile,
+    /// Side with the higher version, or `None` when the versions cannot be compared
+    /// 版本較新的一方；版本無法比較時為 `None`
+    newer: Option<Side>,
+}
+
+/// Differences between the mods folders of two installs
+/// 兩個安裝之 mods 資料夾的差異
+#[derive(Serialize)]
+pub(crate) struct InstallDiff {
+    pub only_in_a: Vec<ModFile>,
+    pub only_in_b: Vec<ModFile>,
+    pub version_differs: Vec<VersionDifference>,
+    pub identical: Vec<MatchedMod>,
+}
+
+/// How a file is matched across installs: by manifest GUID, or by relative path and size
+/// when it has no readable manifest
+/// 跨安裝比對檔案的依據：manifest GUID；沒有可讀 manifest 時改用相對路徑與大小
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum MatchKey {
+    Guid(String),
+    Path(String, u64),
+}
+
+// ───────────────────────────────────────────────
+// Matching / 比對
+// ───────────────────────────────────────────────
+
+/// Files of one install by match key. A GUID with several copies is represented by the copy
+/// Sideloader would load, since that is what the install actually uses.
+/// 依比對 key 整理單一安裝的檔案；同一 GUID 有多個複本時，以 Sideloader 會載入的複本為代表
+fn by_match_key(game_path: &Path, files: Vec<ModFile>) -> BTreeMap<MatchKey, ModFile> {
+    let mods_root = long_path::display(&game_path.join("mods"));
+    let mut guids: HashMap<String, Vec<ModFile>> = HashMap::new();
+    let mut keyed = BTreeMap::new();
+
+    for file in files {
+        match file.manifest.as_ref().map(|manifest| manifest.guid.clone()) {
+            Some(guid) => guids.entry(guid).or_default().push(file),
+            None => {
+                let relative = Path::new(&file.entry.path)
+                    .strip_prefix(&mods_root)
+                    .map(|rel| rel.to_string_lossy().replace('\\', "/").to_lowercase())
+                    .unwrap_or_else(|_| file.entry.name.to_lowercase());
+                keyed.insert(MatchKey::Path(relative, file.entry.size), file);
+            }
+        }
+    }
+    for (guid, mut copies) in guids {
+        mods_scan::sort_by_load_order(&mut copies);
+        if let Some(loaded) = copies.into_iter().next() {
+            keyed.insert(MatchKey::Guid(guid), loaded);
+        }
+    }
+    keyed
+}
+
+/// Compare the mods folders of two installs, scanning both at once. Progress is the
+/// combined count of both scans.
+/// 同時掃描兩個安裝並比較其 mods 資料夾；進度為兩邊掃描的合計
+pub(crate) fn diff_installs(
+    a: &DiffSource,
+    b: &DiffSource,
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<InstallDiff, AppError> {
+    let counts = Mutex::new([(0, 0); 2]);
+    let report = |side: usize, progress: Progress| {
+        let mut counts = counts.lock().unwrap();
+        counts[side] = (progress.current, progress.total);
+        let (current, total) = (counts[0].0 + counts[1].0, counts[0].1 + counts[1].1);
+        drop(counts);
+        on_progress(Progress::new(current, total));
+    };
+    let scan = |side: usize, source: &DiffSource| {
+        mods_scan::scan_mods_folder(
+            source.index_path,
+            source.game_path,
+            source.follow_links,
+            source.mod_extensions,
+            cancel,
+            &|progress| report(side, progress),
+        )
+    };
+
+    let (files_a, files_b) = thread::scope(|scope| {
+        let scan_b = scope.spawn(|| scan(1, b));
+        let files_a = scan(0, a);
+        // A panic in the B scan resumes here so `safe_invoke` records it as usual
+        // B 掃描的 panic 會在此重新拋出，交由 `safe_invoke` 照常記錄
+        let files_b = scan_b
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        (files_a, files_b)
+    });
+
+    let mut files_a = by_match_key(a.game_path, files_a?);
+    let files_b = by_match_key(b.game_path, files_b?);
+    let mut diff = InstallDiff {
+        only_in_a: Vec::new(),
+        only_in_b: Vec::new(),
+        version_differs: Vec::new(),
+        identical: Vec::new(),
+    };
+
+    for (key, file_b) in files_b {
+        let Some(file_a) = files_a.remove(&key) else {
+            diff.only_in_b.push(file_b);
+            continue;
+        };
+        let MatchKey::Guid(guid) = key else {
+            diff.identical.push(MatchedMod {
+                a: file_a,
+                b: file_b,
+            });
+            continue;
+        };
+        let version_a = mods_scan::manifest_version(&file_a);
+        let version_b = mods_scan::manifest_version(&file_b);
+        let (ordering, comparable) = mods_scan::compare_versions(version_a, version_b);
+        if version_a == version_b || (comparable && ordering.is_eq()) {
+            diff.identical.push(MatchedMod {
+                a: file_a,
+                b: file_b,
+            });
+            continue;
+        }
+        diff.version_differs.push(VersionDifference {
+            guid,
+            a: file_a,
+            b: file_b,
+            newer: match (comparable, ordering) {
+                (true, Ordering::Greater) => Some(Side::A),
+                (true, Ordering::Less) => Some(Side::B),
+                _ => None,
+            },
+        });
+    }
+    diff.only_in_a = files_a.into_values().collect();
+    Ok(diff)
+}
@@ -0,0 +1,146 @@
+use std::{path::Path, sync::Mutex};
+
+use serde::Serialize;
+
+use crate::{
+    asset_index,
+    error::AppError,
+    links,
+    operations::CancelToken,
+    parser::{build_mod_entry, ModEntry},
+    profiles::profile_id,
+    progress::{OnProgress, Progress},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Files the OS or a file manager drops next to whatever a folder holds
+/// 作業系統或檔案總管在資料夾中自動產生的檔案
+const OS_METADATA_NAMES: &[&str] = &["thumbs.db", "ehthumbs.db", "desktop.ini", ".ds_store"];
+
+/// Extensions browsers and download managers give files they have not finished writing
+/// 瀏覽器與下載工具替尚未下載完成的檔案所加的副檔名
+const PARTIAL_DOWNLOAD_EXTENSIONS: &[&str] = &[
+    "part",
+    "partial",
+    "crdownload",
+    "download",
+    "opdownload",
+    "tmp",
+    "!ut",
+    "!qb",
+];
+
+/// Why a file is considered junk
+/// 檔案被視為多餘檔案的原因
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum JunkKind {
+    /// Thumbnail caches, folder settings and macOS `._` resource forks
+    /// 縮圖快取、資料夾設定檔與 macOS 的 `._` resource fork
+    OsMetadata,
+    /// A download that was never finished / 未下載完成的檔案
+    PartialDownload,
+    /// A mod archive with no content at all / 完全沒有內容的模組壓縮檔
+    EmptyArchive,
+}
+
+/// A file under `mods` that nothing loads, ready to hand to the deletion flow
+/// `mods` 底下不會被載入的檔案，可直接交給刪除流程
+#[derive(Serialize)]
+pub(crate) struct JunkFile {
+    pub kind: JunkKind,
+    #[serde(rename = "mod")]
+    pub mod_entry: ModEntry,
+}
+
+/// Junk found by the most recent junk-file scan, stored in Tauri managed state
+/// 最近一次多餘檔案掃描的結果，存放於 Tauri managed state
+#[derive(Default)]
+pub(crate) struct LastJunk(Mutex<Option<JunkScan>>);
+
+struct JunkScan {
+    game_path: String,
+    paths: Vec<String>,
+}
+
+impl LastJunk {
+    /// Remember the junk of a scan, replacing any earlier scan
+    /// 記住一次掃描的多餘檔案，取代先前的結果
+    pub fn store(&self, game_path: &str, junk: &[JunkFile]) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(JunkScan {
+            game_path: game_path.to_string(),
+            paths: junk
+                .iter()
+                .map(|file| file.mod_entry.path.clone())
+                .collect(),
+        });
+    }
+
+    /// Paths of the stored junk, if the stored scan is of `game_path`
+    /// 若已存掃描屬於 `game_path`，回傳其多餘檔案路徑
+    pub fn paths(&self, game_path: &str) -> Option<Vec<String>> {
+        let guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let scan = guard.as_ref()?;
+        (profile_id(&scan.game_path) == profile_id(game_path)).then(|| scan.paths.clone())
+    }
+}
+
+// ───────────────────────────────────────────────
+// Scanning / 掃描
+// ───────────────────────────────────────────────
+
+/// Whether `path` is a file the OS or a file manager created on its own, such as `Thumbs.db`
+/// 判斷 `path` 是否為作業系統或檔案總管自動產生的檔案，例如 `Thumbs.db`
+pub(crate) fn is_os_metadata(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| {
+        let name = name.to_string_lossy().to_lowercase();
+        OS_METADATA_NAMES.contains(&name.as_str()) || name.starts_with("._")
+    })
+}
+
+fn junk_kind(path: &Path, size: u64, mod_extensions: &[String]) -> Option<JunkKind> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    if is_os_metadata(path) {
+        Some(JunkKind::OsMetadata)
+    } else if extension.is_some_and(|ext| PARTIAL_DOWNLOAD_EXTENSIONS.contains(&ext.as_str())) {
+        Some(JunkKind::PartialDownload)
+    } else if size == 0 && asset_index::is_mod_archive(path, mod_extensions) {
+        Some(JunkKind::EmptyArchive)
+    } else {
+        None
+    }
+}
+
+/// Find OS metadata files, unfinished downloads and zero-byte archives under the game's `mods`
+/// 找出遊戲 `mods` 底下的系統產生檔、未完成的下載與 0 位元組的壓縮檔
+pub(crate) fn find_junk_files(
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<Vec<JunkFile>, AppError> {
+    let files = links::walk_files(&game_path.join("mods"), follow_links);
+    let total = files.len();
+    let mut junk = Vec::new();
+    for (index, file) in files.iter().enumerate() {
+        if cancel.is_cancelled() {
+            return Err(AppError::OperationCancelled);
+        }
+        if let Some(kind) = junk_kind(&file.path, file.metadata.len(), mod_extensions) {
+            let path = file.path.to_string_lossy();
+            junk.push(JunkFile {
+                kind,
+                mod_entry: build_mod_entry(&file.path, &path, mod_extensions),
+            });
+        }
+        on_progress(Progress::new(index + 1, total));
+    }
+    junk.sort_by(|a, b| a.mod_entry.path.cmp(&b.mod_entry.path));
+    Ok(junk)
+}
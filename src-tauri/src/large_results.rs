@@ -0,0 +1,234 @@
+use std::{
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{error::AppError, long_path};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Serialized size above which heavy commands hand their result over through a file
+/// 耗時指令的結果序列化後超過此大小時改以檔案傳遞
+pub(crate) const DEFAULT_MAX_RESPONSE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Chunk size of `read_chunk` when the caller sets none, and its bounds; the lower bound
+/// always fits one UTF-8 character
+/// 呼叫端未指定時 `read_chunk` 的區塊大小與其上下限；下限必能容納一個 UTF-8 字元
+const DEFAULT_CHUNK_BYTES: u64 = 1024 * 1024;
+const MIN_CHUNK_BYTES: u64 = 4;
+const MAX_CHUNK_BYTES: u64 = 8 * 1024 * 1024;
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A command result, either inline or written to a result file because it was too large
+/// to pass over IPC; the frontend tells them apart by `stored_at`
+/// 指令結果：直接回傳，或因過大不適合經由 IPC 傳遞而寫入結果檔；前端依 `stored_at` 區分
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum Guarded<T> {
+    Inline(T),
+    Stored(StoredResult),
+}
+
+/// Envelope returned in place of a result that was written to a file
+/// 結果寫入檔案時改為回傳的封裝
+#[derive(Serialize)]
+pub(crate) struct StoredResult {
+    /// Result file holding the full JSON, for `read_result_chunk` and `discard_result`
+    /// 存放完整 JSON 的結果檔，供 `read_result_chunk` 與 `discard_result` 使用
+    pub stored_at: String,
+    pub bytes: u64,
+    /// Counts the frontend can show before paging in the result
+    /// 前端在讀入結果前即可顯示的數量
+    pub summary: serde_json::Value,
+}
+
+/// One slice of a result file, cut at a character boundary
+/// 結果檔的一段內容，於字元邊界切開
+#[derive(Serialize)]
+pub(crate) struct ResultChunk {
+    pub data: String,
+    pub next_offset: u64,
+    pub total_bytes: u64,
+    pub done: bool,
+}
+
+// ───────────────────────────────────────────────
+// Storing / 儲存
+// ───────────────────────────────────────────────
+
+/// Return `value` inline when its JSON fits in `max_bytes`; otherwise write the JSON to a new
+/// file in `results_dir` and return its envelope with `summarize(&value)`
+/// `value` 的 JSON 不超過 `max_bytes` 時直接回傳；否則將 JSON 寫入 `results_dir` 中的新檔案，並回傳附有 `summarize(&value)` 的封裝
+pub(crate) fn guard<T: Serialize>(
+    value: T,
+    summarize: fn(&T) -> serde_json::Value,
+    results_dir: &Path,
+    max_bytes: u64,
+) -> Result<Guarded<T>, AppError> {
+    let json = serde_json::to_vec(&value).map_err(|e| AppError::io("io.write_result", None, e))?;
+    if json.len() as u64 <= max_bytes {
+        return Ok(Guarded::Inline(value));
+    }
+    fs::create_dir_all(long_path::extend(results_dir))
+        .map_err(|e| AppError::io("io.write_result", Some(results_dir), e))?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let path = results_dir.join(format!(
+        "result-{}-{}.json",
+        millis,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(long_path::extend(&path), &json)
+        .map_err(|e| AppError::io("io.write_result", Some(&path), e))?;
+    Ok(Guarded::Stored(StoredResult {
+        stored_at: long_path::display(&path),
+        bytes: json.len() as u64,
+        summary: summarize(&value),
+    }))
+}
+
+// ───────────────────────────────────────────────
+// Reading & Cleanup / 讀取與清理
+// ───────────────────────────────────────────────
+
+/// `path` when it names a result file directly inside `results_dir`; anything else is refused
+/// so the commands cannot read or delete other files
+/// `path` 為 `results_dir` 中的結果檔時回傳之；其他路徑一律拒絕，使指令無法讀取或刪除其他檔案
+fn result_file(results_dir: &Path, path: &str) -> Result<PathBuf, AppError> {
+    let not_allowed = || AppError::PathNotAllowed {
+        path: path.to_string(),
+    };
+    let file = fs::canonicalize(long_path::extend(Path::new(path))).map_err(|_| not_allowed())?;
+    let dir = fs::canonicalize(long_path::extend(results_dir)).map_err(|_| not_allowed())?;
+    let named = file
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .is_some_and(|name| name.starts_with("result-") && name.ends_with(".json"));
+    match named && file.parent() == Some(dir.as_path()) {
+        true => Ok(file),
+        false => Err(not_allowed()),
+    }
+}
+
+/// Read up to `limit` bytes (default `DEFAULT_CHUNK_BYTES`) of a result file from `offset`,
+/// ending before any character cut in half, which starts the next chunk instead
+/// 從 `offset` 讀取結果檔最多 `limit` 位元組（預設 `DEFAULT_CHUNK_BYTES`），結尾若切到字元則該字元留到下一段
+pub(crate) fn read_chunk(
+    results_dir: &Path,
+    path: &str,
+    offset: u64,
+    limit: Option<u64>,
+) -> Result<ResultChunk, AppError> {
+    let file_path = result_file(results_dir, path)?;
+    let read_error = |e: std::io::Error| AppError::io("io.read_result", Some(&file_path), e);
+    let mut file = File::open(&file_path).map_err(read_error)?;
+    let total_bytes = file.metadata().map_err(read_error)?.len();
+    let offset = offset.min(total_bytes);
+    let limit = limit
+        .unwrap_or(DEFAULT_CHUNK_BYTES)
+        .clamp(MIN_CHUNK_BYTES, MAX_CHUNK_BYTES);
+    let mut buffer = Vec::new();
+    file.seek(SeekFrom::Start(offset)).map_err(read_error)?;
+    file.take(limit)
+        .read_to_end(&mut buffer)
+        .map_err(read_error)?;
+    let valid = match std::str::from_utf8(&buffer) {
+        Ok(_) => buffer.len(),
+        Err(e) => e.valid_up_to(),
+    };
+    buffer.truncate(valid);
+    let next_offset = offset + valid as u64;
+    Ok(ResultChunk {
+        data: String::from_utf8(buffer).unwrap_or_default(),
+        next_offset,
+        total_bytes,
+        done: next_offset >= total_bytes,
+    })
+}
+
+/// Delete one result file; false when it was already gone
+/// 刪除單一結果檔；已不存在時回傳 false
+pub(crate) fn discard(results_dir: &Path, path: &str) -> Result<bool, AppError> {
+    let Ok(file) = result_file(results_dir, path) else {
+        return match long_path::extend(Path::new(path)).exists() {
+            true => Err(AppError::PathNotAllowed {
+                path: path.to_string(),
+            }),
+            false => Ok(false),
+        };
+    };
+    fs::remove_file(&file).map_err(|e| AppError::io("io.discard_result", Some(&file), e))?;
+    Ok(true)
+}
+
+/// Delete every result file, on exit and at startup for ones a crash left behind; files
+/// that cannot be removed are skipped
+/// 刪除所有結果檔；於結束時及啟動時（清除當機遺留者）執行，無法刪除的檔案略過
+pub(crate) fn discard_all(results_dir: &Path) {
+    let Ok(entries) = fs::read_dir(long_path::extend(results_dir)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with("result-") && name.ends_with(".json") {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{discard, guard, read_chunk, Guarded};
+    use crate::{error::AppError, long_path};
+
+    #[test]
+    fn large_results_are_stored_and_read_back_in_whole_characters() {
+        let dir = tempfile::tempdir().unwrap();
+        let results = dir.path().join("results");
+        let names = vec!["髪型 v2.zipmod".to_string(); 8];
+        let json = serde_json::to_string(&names).unwrap();
+        let summarize = |names: &Vec<String>| serde_json::json!({ "names": names.len() });
+
+        let small = guard(names.clone(), summarize, &results, json.len() as u64);
+        assert!(matches!(small, Ok(Guarded::Inline(_))));
+        let stored = match guard(names, summarize, &results, 16).unwrap() {
+            Guarded::Stored(stored) => stored,
+            Guarded::Inline(_) => panic!("expected a stored result"),
+        };
+        assert_eq!(stored.bytes, json.len() as u64);
+        assert_eq!(stored.summary, serde_json::json!({ "names": 8 }));
+
+        let mut read = String::new();
+        let mut offset = 0;
+        loop {
+            let chunk = read_chunk(&results, &stored.stored_at, offset, Some(5)).unwrap();
+            assert!(chunk.data.len() <= 5 && !chunk.data.is_empty());
+            read.push_str(&chunk.data);
+            offset = chunk.next_offset;
+            if chunk.done {
+                break;
+            }
+        }
+        assert_eq!(read, json);
+
+        let outside = dir.path().join("result-1-1.json");
+        fs::write(&outside, "[]").unwrap();
+        assert!(matches!(
+            read_chunk(&results, &long_path::display(&outside), 0, None),
+            Err(AppError::PathNotAllowed { .. })
+        ));
+        assert!(discard(&results, &stored.stored_at).unwrap());
+        assert!(!discard(&results, &stored.stored_at).unwrap());
+    }
+}
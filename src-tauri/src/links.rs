@@ -0,0 +1,171 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use walkdir::WalkDir;
+
+use crate::{app_log, long_path};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// A regular file found under a tree, possibly reached through a link
+/// 在目錄樹中找到的一般檔案，可能經由連結抵達
+pub(crate) struct TreeFile {
+    /// Path as seen under the walked root (through any link), in display form
+    /// 以走訪根目錄為準的路徑（保留連結路徑），已轉為顯示格式
+    pub path: PathBuf,
+    /// Metadata of the file itself, links resolved
+    /// 檔案本身（連結已解析）的資訊
+    pub metadata: fs::Metadata,
+}
+
+/// Identity of a physical file: volume serial and file index on Windows, device and inode
+/// elsewhere. Hardlinks of one file share it.
+/// 實體檔案的識別：Windows 為磁碟區序號與檔案索引，其他平台為裝置與 inode；同一檔案的硬連結共用此值
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct FileId {
+    volume: u64,
+    index: u64,
+}
+
+/// Identity and hardlink count of a file
+/// 檔案的識別與硬連結數
+pub(crate) struct PhysicalFile {
+    pub id: FileId,
+    pub links: u32,
+}
+
+// ───────────────────────────────────────────────
+// Links / 連結處理
+// ───────────────────────────────────────────────
+
+/// Metadata of `path` with links followed, plus the resolved target when `path` is itself
+/// a symlink or junction. Plain files cost a single stat.
+/// 取得 `path` 的檔案資訊（連結已解析），若 `path` 本身為 symlink 或 junction 另回傳其目標；一般檔案只需一次 stat
+pub(crate) fn stat(path: &Path) -> (Option<fs::Metadata>, Option<String>) {
+    let extended = long_path::extend(path);
+    match fs::symlink_metadata(&extended) {
+        Ok(meta) if meta.file_type().is_symlink() => (
+            fs::metadata(&extended).ok(),
+            fs::canonicalize(&extended)
+                .ok()
+                .map(|target| long_path::display(&target)),
+        ),
+        Ok(meta) => (Some(meta), None),
+        Err(_) => (None, None),
+    }
+}
+
+/// Identity and hardlink count of the file at `path`, whose `metadata` is already known.
+/// Windows needs a handle for this, opened without read or write access.
+/// 取得 `path`（已知其 `metadata`）的檔案識別與硬連結數；Windows 需開啟不含讀寫權限的 handle
+#[cfg(windows)]
+pub(crate) fn physical_file(path: &Path, _metadata: &fs::Metadata) -> Option<PhysicalFile> {
+    use std::os::windows::{fs::OpenOptionsExt, io::AsRawHandle};
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS,
+    };
+
+    let file = fs::OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(long_path::extend(path))
+        .ok()?;
+    // SAFETY: the handle stays open for the call and `info` is a plain C struct
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle(), &mut info) } == 0 {
+        return None;
+    }
+    Some(PhysicalFile {
+        id: FileId {
+            volume: info.dwVolumeSerialNumber as u64,
+            index: (info.nFileIndexHigh as u64) << 32 | info.nFileIndexLow as u64,
+        },
+        links: info.nNumberOfLinks,
+    })
+}
+
+#[cfg(unix)]
+pub(crate) fn physical_file(_path: &Path, metadata: &fs::Metadata) -> Option<PhysicalFile> {
+    use std::os::unix::fs::MetadataExt;
+    Some(PhysicalFile {
+        id: FileId {
+            volume: metadata.dev(),
+            index: metadata.ino(),
+        },
+        links: u32::try_from(metadata.nlink()).unwrap_or(u32::MAX),
+    })
+}
+
+/// Every regular file under `root`, each physical file reported once.
+/// Without `follow_links`, directory symlinks and junctions are not descended into
+/// (symlinked files are still listed); with it, link loops are logged and skipped.
+/// 列出 `root` 底下所有一般檔案，同一實體檔案只回報一次。未啟用 `follow_links` 時不進入目錄 symlink 與
+/// junction（檔案 symlink 仍會列出）；啟用時遇到循環連結會記錄並略過
+pub(crate) fn walk_files(root: &Path, follow_links: bool) -> Vec<TreeFile> {
+    let walk_root = long_path::extend(root);
+    let canonical_root = fs::canonicalize(&walk_root).ok();
+    let mut seen = HashMap::new();
+    let mut files = Vec::new();
+
+    for entry in WalkDir::new(&walk_root).follow_links(follow_links) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                if e.loop_ancestor().is_some() {
+                    app_log::write(&format!("skipping link loop: {}", e));
+                }
+                continue;
+            }
+        };
+        let is_link = entry.path_is_symlink();
+        let metadata = if is_link {
+            fs::metadata(entry.path()).ok()
+        } else {
+            entry.metadata().ok()
+        };
+        let Some(metadata) = metadata.filter(|meta| meta.is_file()) else {
+            continue;
+        };
+
+        // Anything reached through a link is keyed by its canonical path; plain files
+        // below the canonical root are keyed without an extra canonicalize per file
+        // 經由連結抵達的檔案以正規路徑為 key；一般檔案直接接在正規根目錄後，避免逐一正規化
+        let direct = canonical_root.as_ref().and_then(|canonical| {
+            entry
+                .path()
+                .strip_prefix(&walk_root)
+                .ok()
+                .map(|rel| canonical.join(rel))
+        });
+        let key = if is_link || follow_links {
+            fs::canonicalize(entry.path()).ok()
+        } else {
+            direct.clone()
+        };
+        let via_link = key != direct;
+        let file = TreeFile {
+            path: PathBuf::from(long_path::display(entry.path())),
+            metadata,
+        };
+        match seen.entry(key.unwrap_or_else(|| entry.path().to_path_buf())) {
+            Entry::Vacant(slot) => {
+                slot.insert((files.len(), via_link));
+                files.push(file);
+            }
+            // Prefer the real location over a link seen earlier in walk order
+            // 若先遇到連結，之後遇到實際位置時改用實際位置
+            Entry::Occupied(mut slot) if slot.get().1 && !via_link => {
+                let index = slot.get().0;
+                slot.insert((index, false));
+                files[index] = file;
+            }
+            Entry::Occupied(_) => {}
+        }
+    }
+    files
+}
@@ -0,0 +1,444 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::{
+    app_log,
+    asset_index::list_mod_archives,
+    error::AppError,
+    long_path,
+    manifest::read_manifest_from_archive,
+    operations::CancelToken,
+    progress::{OnProgress, Progress},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Bumped whenever `ListRecord` changes shape; mismatched caches are rebuilt
+/// `ListRecord` 結構變動時遞增；版本不符的快取會重建
+const CACHE_VERSION: u32 = 1;
+
+/// List files larger than this are skipped; real ones are a few hundred KB at most
+/// 超過此大小的 list 檔會略過；實際檔案最多數百 KB
+const MAX_LIST_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Warnings kept per zipmod, so one broken mod can't flood the report
+/// 每個 zipmod 保留的警告數上限，避免單一損壞模組塞滿報告
+const MAX_WARNINGS_PER_MOD: usize = 20;
+
+/// One item row of a list csv
+/// list csv 中的一列項目
+#[derive(Serialize, Deserialize, Clone)]
+struct ListItem {
+    category: String,
+    id: i64,
+    name: Option<String>,
+    entry: String,
+}
+
+/// A row or file of a list csv that could not be used
+/// list csv 中無法使用的列或檔案
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct ListWarning {
+    pub path: String,
+    pub entry: String,
+    /// 1-based line number; None for problems with the whole file
+    /// 從 1 起算的行號；整個檔案的問題為 None
+    pub line: Option<usize>,
+    pub detail: String,
+}
+
+/// Cached list items of one zipmod
+/// 單一 zipmod 的 list 項目快取
+#[derive(Serialize, Deserialize)]
+struct ListRecord {
+    size: u64,
+    modified: Option<u64>,
+    guid: Option<String>,
+    items: Vec<ListItem>,
+    warnings: Vec<ListWarning>,
+}
+
+/// Persistent list-id cache, keyed by absolute zipmod path
+/// 持久化的 list id 快取，以 zipmod 絕對路徑為 key
+#[derive(Serialize, Deserialize, Default)]
+struct ListCache {
+    #[serde(default)]
+    version: u32,
+    mods: HashMap<String, ListRecord>,
+}
+
+/// Files of one GUID registering a colliding item id
+/// 註冊同一衝突項目 id 的某個 GUID 及其檔案
+#[derive(Serialize)]
+pub(crate) struct ListIdProvider {
+    pub guid: String,
+    pub paths: Vec<String>,
+    /// Item names these files give the id, when their csv has a name column
+    /// 這些檔案為該 id 設定的項目名稱（csv 有名稱欄位時）
+    pub names: Vec<String>,
+}
+
+/// An item id of one category registered by two or more distinct GUIDs
+/// 同一分類中被兩個以上不同 GUID 註冊的項目 id
+#[derive(Serialize)]
+pub(crate) struct ListIdCollision {
+    pub category: String,
+    pub item_id: i64,
+    pub providers: Vec<ListIdProvider>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ListIdReport {
+    pub collisions: Vec<ListIdCollision>,
+    pub mods_scanned: usize,
+    pub mods_reread: usize,
+    pub warnings: Vec<ListWarning>,
+}
+
+// ───────────────────────────────────────────────
+// CSV Parsing / CSV 解析
+// ───────────────────────────────────────────────
+
+/// Whether an archive entry is a csv under a `list/` folder
+/// 判斷壓縮檔 entry 是否為 `list/` 資料夾下的 csv
+fn is_list_entry(name: &str) -> bool {
+    let name = name.replace('\\', "/").to_lowercase();
+    name.ends_with(".csv") && (name.starts_with("list/") || name.contains("/list/"))
+}
+
+fn split_row(line: &str, delimiter: char) -> Vec<String> {
+    line.split(delimiter)
+        .map(|cell| cell.trim().trim_matches('"').trim().to_string())
+        .collect()
+}
+
+/// Category of a list file without a numeric first line: the file name up to its first `_`
+/// 第一行不是數字的 list 檔之分類：檔名中第一個 `_` 之前的部分
+fn fallback_category(entry: &str) -> String {
+    let file = entry.rsplit(['/', '\\']).next().unwrap_or(entry);
+    let stem = file.strip_suffix(".csv").unwrap_or(file);
+    stem.split('_').next().unwrap_or(stem).to_lowercase()
+}
+
+/// Parse one list csv. The usual layout is a category number, a distribution number and the
+/// list name on the first lines, then a header row starting with `ID`, then one item per
+/// row; any of the preamble lines and the header may be missing, cells may be quoted and tab
+/// separated, and a BOM is ignored. Rows without a numeric id are skipped with a warning.
+/// 解析單一 list csv。一般格式為前幾行的分類編號、發布編號與清單名稱，接著是以 `ID` 開頭的標題列，
+/// 之後每列一個項目；前置行與標題列都可能缺少，儲存格可能加上引號或以 tab 分隔，BOM 會被忽略；
+/// 沒有數字 id 的列會略過並產生警告
+fn parse_list(
+    reader: impl BufRead,
+    path: &str,
+    entry: &str,
+    items: &mut Vec<ListItem>,
+    warnings: &mut Vec<ListWarning>,
+) {
+    let mut category = None;
+    let mut delimiter = None;
+    let mut name_column = None;
+    let mut in_rows = false;
+    let warn = |warnings: &mut Vec<ListWarning>, line: usize, detail: String| {
+        warnings.push(ListWarning {
+            path: path.to_string(),
+            entry: entry.to_string(),
+            line: Some(line),
+            detail,
+        });
+    };
+
+    // Split on raw bytes so a stray non-UTF-8 cell only garbles that cell
+    // 以位元組切行，非 UTF-8 的儲存格只會影響該格
+    for (index, line) in reader.split(b'\n').enumerate() {
+        let number = index + 1;
+        let Ok(line) = line else {
+            warn(
+                warnings,
+                number,
+                "unreadable line; rest of file skipped".to_string(),
+            );
+            return;
+        };
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() {
+            continue;
+        }
+        // The first line with more than one cell decides between comma and tab
+        // 第一個多欄的行決定以逗號或 tab 分隔
+        if delimiter.is_none() && (line.contains(',') || line.contains('\t')) {
+            delimiter = Some(match line.contains(',') {
+                true => ',',
+                false => '\t',
+            });
+        }
+        let cells = split_row(line, delimiter.unwrap_or(','));
+        let first = cells[0].as_str();
+
+        if !in_rows {
+            if first.eq_ignore_ascii_case("id") {
+                name_column = cells
+                    .iter()
+                    .position(|cell| cell.eq_ignore_ascii_case("name"));
+                in_rows = true;
+                continue;
+            }
+            // A lone number before the header is the category, then the distribution number
+            // 標題列前單獨的數字依序為分類編號與發布編號
+            if cells.len() == 1 {
+                if category.is_none() && first.parse::<i64>().is_ok() {
+                    category = Some(first.to_string());
+                }
+                continue;
+            }
+            // No header: the first multi-column row is already an item
+            // 沒有標題列：第一個多欄的列即為項目
+            in_rows = true;
+        }
+
+        match first.parse::<i64>() {
+            Ok(id) => items.push(ListItem {
+                category: category.clone().unwrap_or_else(|| fallback_category(entry)),
+                id,
+                name: name_column
+                    .and_then(|column| cells.get(column))
+                    .filter(|name| !name.is_empty())
+                    .cloned(),
+                entry: entry.to_string(),
+            }),
+            Err(_) => warn(
+                warnings,
+                number,
+                format!("row skipped: id \"{}\" is not a number", first),
+            ),
+        }
+    }
+}
+
+/// Read the GUID and every list csv of a zipmod, streaming each entry
+/// 讀取 zipmod 的 GUID 與所有 list csv，逐一以串流方式讀取
+fn read_list_record(path: &Path, size: u64, modified: Option<u64>) -> ListRecord {
+    let display = long_path::display(path);
+    let mut record = ListRecord {
+        size,
+        modified,
+        guid: None,
+        items: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let file_warning = |entry: &str, detail: String| ListWarning {
+        path: display.clone(),
+        entry: entry.to_string(),
+        line: None,
+        detail,
+    };
+
+    let archive = File::open(long_path::extend(path))
+        .map_err(|e| e.to_string())
+        .and_then(|file| ZipArchive::new(file).map_err(|e| e.to_string()));
+    let mut archive = match archive {
+        Ok(archive) => archive,
+        Err(detail) => {
+            record
+                .warnings
+                .push(file_warning("", format!("not a readable zip: {}", detail)));
+            return record;
+        }
+    };
+    record.guid = read_manifest_from_archive(&mut archive)
+        .ok()
+        .map(|manifest| manifest.guid);
+
+    let list_entries = archive
+        .file_names()
+        .filter(|name| is_list_entry(name))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    for name in list_entries {
+        let entry = match archive.by_name(&name) {
+            Ok(entry) if entry.size() > MAX_LIST_BYTES => {
+                let detail = format!(
+                    "skipped: {} bytes is too large for a list file",
+                    entry.size()
+                );
+                record.warnings.push(file_warning(&name, detail));
+                continue;
+            }
+            Ok(entry) => entry,
+            Err(e) => {
+                record.warnings.push(file_warning(&name, e.to_string()));
+                continue;
+            }
+        };
+        let reader = BufReader::new(entry.take(MAX_LIST_BYTES));
+        parse_list(
+            reader,
+            &display,
+            &name,
+            &mut record.items,
+            &mut record.warnings,
+        );
+    }
+    record.warnings.truncate(MAX_WARNINGS_PER_MOD);
+    record
+}
+
+// ───────────────────────────────────────────────
+// Cache / 快取
+// ───────────────────────────────────────────────
+
+fn load_cache(cache_path: &Path) -> ListCache {
+    fs::read_to_string(cache_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<ListCache>(&content).ok())
+        .filter(|cache| cache.version == CACHE_VERSION)
+        .unwrap_or_else(|| ListCache {
+            version: CACHE_VERSION,
+            mods: HashMap::new(),
+        })
+}
+
+fn save_cache(cache_path: &Path, cache: &ListCache) -> Result<(), AppError> {
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io("io.create_cache_directory", Some(parent), e))?;
+    }
+    let content = serde_json::to_string(cache)
+        .map_err(|e| AppError::io("io.serialize_index", Some(cache_path), e))?;
+    fs::write(cache_path, content).map_err(|e| AppError::io("io.write_index", Some(cache_path), e))
+}
+
+/// Bring the cache up to date with the mods folder, re-reading only files whose size or mtime
+/// changed. Returns how many were re-read, or an error if cancelled midway.
+/// 依 mods 資料夾更新快取，只重新讀取大小或修改時間有變動的檔案；回傳重新讀取的數量，中途取消則回傳錯誤
+fn refresh_cache(
+    cache: &mut ListCache,
+    mods_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<usize, AppError> {
+    let archives = list_mod_archives(mods_path, follow_links, mod_extensions);
+    let total = archives.len();
+    let mut fresh = HashMap::new();
+    let mut reread = 0;
+
+    for (current, (path, size, modified)) in archives.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            cache.mods.extend(fresh);
+            return Err(AppError::OperationCancelled);
+        }
+        let key = path.to_string_lossy().to_string();
+        let record = match cache.mods.remove(&key) {
+            Some(cached) if cached.size == size && cached.modified == modified => cached,
+            _ => {
+                reread += 1;
+                read_list_record(&path, size, modified)
+            }
+        };
+        fresh.insert(key, record);
+        on_progress(Progress::new(current + 1, total).with_message(long_path::display(&path)));
+    }
+
+    cache.mods = fresh;
+    Ok(reread)
+}
+
+// ───────────────────────────────────────────────
+// Collisions / 衝突
+// ───────────────────────────────────────────────
+
+/// Find item ids that two or more distinct GUIDs register in the same list category through
+/// the `list/*.csv` files of their zipmods, which makes one item shadow the other in game
+/// without Sideloader logging it. Results are cached per zipmod by size and mtime in
+/// `cache_path`; zipmods without a manifest GUID are left out. Most contested ids come first.
+/// 找出兩個以上不同 GUID 透過其 zipmod 的 `list/*.csv` 在同一 list 分類中註冊的相同項目 id；
+/// 這會讓遊戲中某個項目蓋過另一個，而 Sideloader 不會記錄。結果依 zipmod 的大小與修改時間快取於 `cache_path`；
+/// 沒有 manifest GUID 的 zipmod 不列入；被爭用最多的 id 排前
+pub(crate) fn find_list_id_collisions(
+    cache_path: &Path,
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<ListIdReport, AppError> {
+    let mut cache = load_cache(cache_path);
+    let result = refresh_cache(
+        &mut cache,
+        &game_path.join("mods"),
+        follow_links,
+        mod_extensions,
+        cancel,
+        on_progress,
+    );
+    // Only a cache; the report is built from the copy in memory either way
+    // 只是快取；報告無論如何都以記憶體中的內容產生
+    if let Err(e) = save_cache(cache_path, &cache) {
+        app_log::write(&format!("list id cache not saved: {}", e));
+    }
+    let mods_reread = result?;
+
+    type Providers = BTreeMap<String, (Vec<String>, Vec<String>)>;
+    let mut by_id: HashMap<(String, i64), Providers> = HashMap::new();
+    let mut warnings = Vec::new();
+    for (path, record) in &cache.mods {
+        warnings.extend(record.warnings.iter().cloned());
+        let Some(guid) = &record.guid else { continue };
+        for item in &record.items {
+            let (paths, names) = by_id
+                .entry((item.category.clone(), item.id))
+                .or_default()
+                .entry(guid.clone())
+                .or_default();
+            let display = long_path::display(Path::new(path));
+            if !paths.contains(&display) {
+                paths.push(display);
+            }
+            if let Some(name) = item.name.as_ref().filter(|name| !names.contains(name)) {
+                names.push(name.clone());
+            }
+        }
+    }
+
+    let mut collisions = by_id
+        .into_iter()
+        .filter(|(_, providers)| providers.len() > 1)
+        .map(|((category, item_id), providers)| ListIdCollision {
+            category,
+            item_id,
+            providers: providers
+                .into_iter()
+                .map(|(guid, (mut paths, names))| {
+                    paths.sort();
+                    ListIdProvider { guid, paths, names }
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    collisions.sort_by(|a, b| {
+        b.providers
+            .len()
+            .cmp(&a.providers.len())
+            .then_with(|| a.category.cmp(&b.category))
+            .then_with(|| a.item_id.cmp(&b.item_id))
+    });
+    warnings.sort_by(|a, b| (&a.path, &a.entry, a.line).cmp(&(&b.path, &b.entry, b.line)));
+
+    Ok(ListIdReport {
+        collisions,
+        mods_scanned: cache.mods.len(),
+        mods_reread,
+        warnings,
+    })
+}
@@ -0,0 +1,78 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Local, SecondsFormat};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Latest timestamp kept as is: 9999-12-30T23:59:59Z, a day short so every timezone still
+/// formats it as year 9999. Anything later is a corrupt directory entry (seen on FAT drives).
+/// 保留原值的最晚時間：9999-12-30T23:59:59Z，少一天使任何時區格式化後仍為 9999 年；更晚的值是損壞的目錄項目（FAT 磁碟上可見）
+const MAX_TIMESTAMP: u64 = 253_402_214_399;
+
+// ───────────────────────────────────────────────
+// Conversion / 轉換
+// ───────────────────────────────────────────────
+
+/// Unix seconds of a file time, clamped to `0..=MAX_TIMESTAMP`; the flag tells whether
+/// clamping was needed (pre-1970 or absurdly far in the future)
+/// 檔案時間的 unix 秒數，限制在 `0..=MAX_TIMESTAMP`；旗標表示是否經過限制（1970 年前或過於遙遠的未來）
+pub(crate) fn epoch_seconds(time: SystemTime) -> (u64, bool) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) if elapsed.as_secs() > MAX_TIMESTAMP => (MAX_TIMESTAMP, true),
+        Ok(elapsed) => (elapsed.as_secs(), false),
+        Err(_) => (0, true),
+    }
+}
+
+/// ISO 8601 in the system timezone with its offset, e.g. `2024-05-01T21:30:00+08:00`
+/// 以系統時區表示、含時差的 ISO 8601，例如 `2024-05-01T21:30:00+08:00`
+pub(crate) fn format_local(seconds: u64) -> Option<String> {
+    let utc = DateTime::from_timestamp(i64::try_from(seconds).ok()?, 0)?;
+    Some(
+        utc.with_timezone(&Local)
+            .to_rfc3339_opts(SecondsFormat::Secs, false),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use chrono::DateTime;
+
+    use super::{epoch_seconds, format_local, MAX_TIMESTAMP};
+
+    const DAY: Duration = Duration::from_secs(86_400);
+
+    #[test]
+    fn file_times_are_clamped_to_the_supported_range() {
+        let may_2024 = UNIX_EPOCH + Duration::from_secs(1_714_563_000);
+        let latest = UNIX_EPOCH + Duration::from_secs(MAX_TIMESTAMP);
+
+        assert_eq!(epoch_seconds(may_2024), (1_714_563_000, false));
+        assert_eq!(epoch_seconds(UNIX_EPOCH), (0, false));
+        assert_eq!(epoch_seconds(UNIX_EPOCH - DAY), (0, true));
+        assert_eq!(epoch_seconds(latest), (MAX_TIMESTAMP, false));
+        assert_eq!(epoch_seconds(latest + DAY), (MAX_TIMESTAMP, true));
+    }
+
+    #[test]
+    fn local_time_keeps_the_instant_at_both_ends() {
+        // The local date depends on the timezone the tests run in, the instant does not
+        // 本地日期取決於測試執行時的時區，但時間點本身不變
+        let instant = |text: &str| DateTime::parse_from_rfc3339(text).unwrap().timestamp();
+        let (pre_1970, _) = epoch_seconds(UNIX_EPOCH - DAY);
+        let (far_future, _) = epoch_seconds(UNIX_EPOCH + DAY * 365 * 100_000);
+
+        let earliest = format_local(pre_1970).unwrap();
+        let latest = format_local(far_future).unwrap();
+
+        assert_eq!(instant(&earliest), 0);
+        assert!(earliest.starts_with("1970-01-01T") || earliest.starts_with("1969-12-31T"));
+        assert_eq!(instant(&latest), MAX_TIMESTAMP as i64);
+        assert!(latest.starts_with("9999-12-"), "{}", latest);
+        assert_eq!(format_local(u64::MAX), None);
+    }
+}
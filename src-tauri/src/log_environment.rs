@@ -0,0 +1,132 @@
+use std::{fs::File, io::Read, path::Path};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{long_path, patterns};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// BepInEx and Sideloader announce their versions while the game starts, well within this
+/// many bytes of the log's beginning
+/// BepInEx 與 Sideloader 在遊戲啟動時即記錄版本，遠在 log 開頭的此位元組數之內
+const HEAD_BYTES: usize = 256 * 1024;
+
+/// First Sideloader major version that words duplicates as "Multiple versions detected"
+/// 第一個以「Multiple versions detected」措辭記錄重複模組的 Sideloader 主版本
+const MULTIPLE_VERSIONS_SINCE: u32 = 13;
+
+/// Which wording of Sideloader's duplicate warning the log is parsed for
+/// 解析 log 時所比對的 Sideloader 重複模組警告措辭
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternSet {
+    /// `only "a" will be loaded. Skipped versions: "b"`, since `MULTIPLE_VERSIONS_SINCE`
+    /// 自 `MULTIPLE_VERSIONS_SINCE` 起的措辭
+    MultipleVersions,
+    /// `Skipping "b" because "a" is already loaded`, before `MULTIPLE_VERSIONS_SINCE`
+    /// `MULTIPLE_VERSIONS_SINCE` 之前的措辭
+    Legacy,
+    /// Sideloader's version is unknown, so every wording is tried
+    /// Sideloader 版本不明，因此比對所有措辭
+    #[default]
+    All,
+}
+
+/// The mod loaders that wrote a log, from their startup lines
+/// 由啟動訊息得知的、寫出此 log 的模組載入器
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct LogEnvironment {
+    /// From the banner `BepInEx 5.4.22.0 - Koikatu` / 取自橫幅 `BepInEx 5.4.22.0 - Koikatu`
+    pub bepinex_version: Option<String>,
+    /// Process name after the banner's dash, e.g. `Koikatu` or `CharaStudio`
+    /// 橫幅破折號後的程序名稱，例如 `Koikatu` 或 `CharaStudio`
+    pub process: Option<String>,
+    /// From `Loading [Sideloader 19.3]` / 取自 `Loading [Sideloader 19.3]`
+    pub sideloader_version: Option<String>,
+    /// Chosen from `sideloader_version` / 依 `sideloader_version` 選擇
+    pub pattern_set: PatternSet,
+}
+
+// ───────────────────────────────────────────────
+// Detection / 偵測
+// ───────────────────────────────────────────────
+
+impl PatternSet {
+    fn for_sideloader(version: Option<&str>) -> Self {
+        let major = version.and_then(|version| version.split('.').next()?.parse::<u32>().ok());
+        match major {
+            Some(major) if major >= MULTIPLE_VERSIONS_SINCE => PatternSet::MultipleVersions,
+            Some(_) => PatternSet::Legacy,
+            None => PatternSet::All,
+        }
+    }
+
+    /// Compiled duplicate-warning patterns of this set; each captures `loaded` and `skipped`
+    /// 此組的已編譯重複模組警告樣式；皆擷取 `loaded` 與 `skipped`
+    pub(crate) fn conflict_patterns(self) -> Vec<&'static Regex> {
+        let patterns = patterns::patterns();
+        match self {
+            PatternSet::MultipleVersions => vec![&patterns.sideloader_conflict],
+            PatternSet::Legacy => vec![&patterns.sideloader_conflict_legacy],
+            PatternSet::All => vec![
+                &patterns.sideloader_conflict,
+                &patterns.sideloader_conflict_legacy,
+            ],
+        }
+    }
+}
+
+impl LogEnvironment {
+    /// One line for reports: `BepInEx 5.4.22.0 (Koikatu), Sideloader 19.3`
+    /// 報告用的一行：`BepInEx 5.4.22.0 (Koikatu), Sideloader 19.3`
+    pub(crate) fn describe(&self) -> String {
+        let bepinex = match (&self.bepinex_version, &self.process) {
+            (Some(version), Some(process)) => format!("BepInEx {} ({})", version, process),
+            (Some(version), None) => format!("BepInEx {}", version),
+            (None, _) => "BepInEx unknown".to_string(),
+        };
+        format!(
+            "{}, Sideloader {}",
+            bepinex,
+            self.sideloader_version.as_deref().unwrap_or("unknown")
+        )
+    }
+}
+
+/// Read the loader versions from the first `HEAD_BYTES` of `log` and pick the pattern set
+/// 從 `log` 的前 `HEAD_BYTES` 讀取載入器版本並選擇樣式組
+pub(crate) fn detect(log: &str) -> LogEnvironment {
+    let mut end = log.len().min(HEAD_BYTES);
+    while !log.is_char_boundary(end) {
+        end -= 1;
+    }
+    let head = &log[..end];
+    let patterns = patterns::patterns();
+    let banner = patterns.bepinex_banner.captures(head);
+    let sideloader_version = patterns
+        .sideloader_version
+        .captures(head)
+        .map(|cap| cap[1].to_string());
+    LogEnvironment {
+        bepinex_version: banner.as_ref().map(|cap| cap[1].to_string()),
+        process: banner.as_ref().map(|cap| cap[2].trim().to_string()),
+        pattern_set: PatternSet::for_sideloader(sideloader_version.as_deref()),
+        sideloader_version,
+    }
+}
+
+/// `detect` on the start of the log file at `path`, without reading the rest; a log that
+/// cannot be read has an unknown environment
+/// 只讀取 `path` 處 log 檔案的開頭並執行 `detect`；無法讀取的 log 視為環境不明
+pub(crate) fn read(path: &Path) -> LogEnvironment {
+    let mut head = Vec::new();
+    let read = File::open(long_path::extend(path))
+        .and_then(|file| file.take(HEAD_BYTES as u64).read_to_end(&mut head));
+    match read {
+        Ok(_) => detect(&String::from_utf8_lossy(&head)),
+        Err(_) => LogEnvironment::default(),
+    }
+}
@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+// ───────────────────────────────────────────────
+// Extended-Length Paths / 長路徑處理
+// ───────────────────────────────────────────────
+
+/// Paths at or above this length need the `\\?\` prefix (CreateDirectory's limit is MAX_PATH - 12)
+/// 長度達到此值的路徑需要 `\\?\` 前綴（CreateDirectory 的上限為 MAX_PATH - 12）
+#[cfg(windows)]
+const LONG_PATH_THRESHOLD: usize = 248;
+
+/// Whether a folder or file name of `raw` ends with a dot or space, which Windows strips from
+/// paths without the `\\?\` prefix, as in `D:\ゲーム\Koikatsu.`
+/// 判斷 `raw` 中是否有以點或空白結尾的資料夾或檔案名稱；Windows 會從不含 `\\?\` 前綴的路徑中移除它們，
+/// 例如 `D:\ゲーム\Koikatsu.`
+#[cfg(windows)]
+fn has_trimmed_name(raw: &str) -> bool {
+    raw.split(['/', '\\'])
+        .any(|name| name != "." && name != ".." && name.ends_with(['.', ' ']))
+}
+
+/// Absolute form of `path` with `.` and `..` resolved by hand; `std::path::absolute` asks
+/// Windows, which would strip trailing dots and spaces
+/// 自行處理 `.` 與 `..` 的絕對路徑；`std::path::absolute` 交由 Windows 處理，會移除結尾的點與空白
+#[cfg(windows)]
+fn lexical_absolute(path: &Path) -> Option<PathBuf> {
+    use std::path::Component;
+
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+    let mut absolute = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            other => absolute.push(other.as_os_str()),
+        }
+    }
+    Some(absolute)
+}
+
+/// Convert a path into a form the OS accepts regardless of length. On Windows, long
+/// absolute paths and paths with a name ending in a dot or space get the `\\?\` prefix and
+/// UNC shares get `\\?\UNC\`; elsewhere the path is returned unchanged.
+/// 將路徑轉為不受長度限制的形式；Windows 上長路徑與含有以點或空白結尾之名稱的路徑加上 `\\?\`，
+/// 網路分享加上 `\\?\UNC\`，其他平台原樣回傳
+#[cfg(windows)]
+pub(crate) fn extend(path: &Path) -> PathBuf {
+    let raw = path.as_os_str().to_string_lossy();
+    if raw.starts_with(r"\\?\") {
+        return path.to_path_buf();
+    }
+    let trimmed_name = has_trimmed_name(&raw);
+    if raw.len() < LONG_PATH_THRESHOLD && !trimmed_name {
+        return path.to_path_buf();
+    }
+    // Extended paths are not normalized by the OS, so resolve `.`, `..` and `/` first
+    // 延伸路徑不會被系統正規化，因此先處理 `.`、`..` 與 `/`
+    let absolute = if trimmed_name {
+        lexical_absolute(path)
+    } else {
+        std::path::absolute(path).ok()
+    };
+    let Some(absolute) = absolute else {
+        return path.to_path_buf();
+    };
+    let absolute = absolute.to_string_lossy().to_string();
+    match absolute.strip_prefix(r"\\") {
+        Some(share) => PathBuf::from(format!(r"\\?\UNC\{}", share)),
+        None => PathBuf::from(format!(r"\\?\{}", absolute)),
+    }
+}
+
+#[cfg(not(windows))]
+pub(crate) fn extend(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// Render a path for the frontend, stripping any extended-length prefix
+/// 轉成給前端顯示的字串，移除延伸路徑前綴
+pub(crate) fn display(path: &Path) -> String {
+    let raw = path.to_string_lossy();
+    if let Some(share) = raw.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", share)
+    } else if let Some(local) = raw.strip_prefix(r"\\?\") {
+        local.to_string()
+    } else {
+        raw.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::display;
+
+    #[test]
+    fn display_strips_the_extended_prefix() {
+        assert_eq!(
+            display(Path::new(r"\\?\C:\Games\Koikatsu\mods\a.zipmod")),
+            r"C:\Games\Koikatsu\mods\a.zipmod"
+        );
+        assert_eq!(
+            display(Path::new(r"C:\Games\Koikatsu\mods\a.zipmod")),
+            r"C:\Games\Koikatsu\mods\a.zipmod"
+        );
+    }
+
+    #[test]
+    fn display_turns_extended_unc_back_into_a_share() {
+        assert_eq!(
+            display(Path::new(r"\\?\UNC\server\share\Koikatsu\mods")),
+            r"\\server\share\Koikatsu\mods"
+        );
+        assert_eq!(
+            display(Path::new(r"\\server\share\Koikatsu\mods")),
+            r"\\server\share\Koikatsu\mods"
+        );
+    }
+}
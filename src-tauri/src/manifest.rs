@@ -0,0 +1,584 @@
+use std::{collections::BTreeMap, fs::File, io::Read, path::Path};
+
+use quick_xml::{de::from_str, events::Event, Reader};
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::{error::AppError, long_path};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Parsed manifest.xml inside mod zip
+/// 解析 zip 模組中的 manifest.xml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestData {
+    pub guid: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    /// `<description>` exactly as the manifest has it; untrusted, never render it as HTML
+    /// manifest 中原樣的 `<description>`；內容不可信，切勿以 HTML 呈現
+    #[serde(default, alias = "description")]
+    pub description_raw: Option<String>,
+    /// `description_raw` as plain text for display: line endings normalized, HTML and
+    /// BBCode tags stripped with their text kept, blank lines collapsed, and cut to a limit
+    /// 供顯示的純文字 `description_raw`：統一換行、移除 HTML 與 BBCode 標籤但保留文字、合併多餘空行並截斷至上限
+    #[serde(default)]
+    pub description_display: Option<String>,
+    /// `description_display` was cut at the limit and ends with `…`
+    /// `description_display` 已在上限處截斷並以 `…` 結尾
+    #[serde(default)]
+    pub description_truncated: bool,
+    /// `<game>` tags naming the games the mod is for; empty means any game
+    /// 標示模組適用遊戲的 `<game>` 標籤；為空表示適用所有遊戲
+    #[serde(default, rename = "game")]
+    pub games: Vec<String>,
+    /// Text and attribute values inside each child element of `<manifest>` that no field
+    /// reads, keyed by the element's name; some mods list the GUIDs they need this way
+    /// `<manifest>` 中沒有欄位讀取的各子元素內的文字與屬性值，以元素名稱為 key；部分模組以此列出所需的 GUID
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extras: BTreeMap<String, Vec<String>>,
+}
+
+/// manifest.xml as written, with every field read both as a child element and as an attribute
+/// of `<manifest>`; some older tools wrote `<manifest guid="..." version="...">`
+/// 原樣的 manifest.xml，每個欄位同時以子元素與 `<manifest>` 屬性讀取；部分舊工具會寫成 `<manifest guid="..." version="...">`
+#[derive(Deserialize)]
+struct RawManifest {
+    #[serde(default)]
+    guid: Option<String>,
+    #[serde(default, rename = "@guid")]
+    guid_attr: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default, rename = "@name")]
+    name_attr: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default, rename = "@version")]
+    version_attr: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default, rename = "@author")]
+    author_attr: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default, rename = "@description")]
+    description_attr: Option<String>,
+    #[serde(default, rename = "game")]
+    games: Vec<String>,
+}
+
+/// Characters of `description_display` when the caller sets no limit
+/// 呼叫端未指定上限時 `description_display` 的字元數上限
+pub const DEFAULT_DESCRIPTION_LIMIT: usize = 2000;
+
+/// Elements dropped together with their content; their text is code, not description
+/// 連同內容一併移除的元素；其中的文字為程式碼而非說明
+const DROPPED_ELEMENTS: &[&str] = &["script", "style"];
+
+/// HTML tags that end a line of text / 結束一行文字的 HTML 標籤
+const LINE_BREAK_ELEMENTS: &[&str] = &["br", "p", "div", "li", "tr"];
+
+/// Child elements of `<manifest>` that are read into fields or are known not to name other
+/// mods; every other one goes to `extras`
+/// `<manifest>` 中讀入欄位或已知不會提到其他模組的子元素；其餘子元素皆存入 `extras`
+const KNOWN_ELEMENTS: &[&str] = &[
+    "guid",
+    "name",
+    "version",
+    "author",
+    "description",
+    "website",
+    "game",
+    "materialeditor",
+    "heelsinfo",
+];
+
+/// Values kept in `extras` per manifest; longer values are left out, as no GUID is that long
+/// 每個 manifest 的 `extras` 保留的值數量；超過長度上限的值不保留，因為 GUID 不會那麼長
+const EXTRA_VALUES: usize = 64;
+const EXTRA_VALUE_CHARS: usize = 256;
+
+/// BBCode tags stripped from descriptions; other bracketed text, such as `[Author]`, is kept
+/// 從說明中移除的 BBCode 標籤；其他方括號文字（例如 `[Author]`）會保留
+const BBCODE_TAGS: &[&str] = &[
+    "b", "i", "u", "s", "url", "color", "size", "font", "img", "quote", "code", "list", "center",
+    "left", "right", "spoiler", "*",
+];
+
+// ───────────────────────────────────────────────
+// Description Sanitizing / 說明文字淨化
+// ───────────────────────────────────────────────
+
+/// Name of the HTML tag between `<` and `>` (already lowercased) and whether it closes an
+/// element; comments and declarations have an empty name. None when it is not a tag, as in
+/// `a <3 b`.
+/// 位於 `<` 與 `>` 之間（已轉小寫）的 HTML 標籤名稱及是否為結束標籤；註解與宣告的名稱為空；
+/// 不是標籤時（例如 `a <3 b`）回傳 None
+fn html_tag(inner: &str) -> Option<(&str, bool)> {
+    if inner.starts_with(['!', '?']) {
+        return Some(("", false));
+    }
+    let (closing, rest) = match inner.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (false, inner),
+    };
+    let name = &rest[..rest
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(rest.len())];
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        .then_some((name, closing))
+}
+
+/// The BBCode tag between `[` and `]` (already lowercased), e.g. `url` for `[url=...]`
+/// 位於 `[` 與 `]` 之間（已轉小寫）的 BBCode 標籤，例如 `[url=...]` 為 `url`
+fn bbcode_tag(inner: &str) -> Option<&'static str> {
+    let name = inner
+        .trim_start_matches('/')
+        .split(['=', ' '])
+        .next()
+        .unwrap_or_default();
+    BBCODE_TAGS.iter().find(|tag| **tag == name).copied()
+}
+
+/// Remove HTML and BBCode tags, keeping the text between them. Script and style elements go
+/// with their content, line-breaking tags become newlines and list items start a bullet.
+/// A `<` or `[` that opens no tag is kept as text.
+/// 移除 HTML 與 BBCode 標籤並保留其間文字；script 與 style 元素連同內容移除，換行類標籤轉為換行，
+/// 清單項目以項目符號開頭；未構成標籤的 `<` 或 `[` 保留為文字
+fn strip_markup(text: &str) -> String {
+    let lower = text.to_ascii_lowercase();
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = 0;
+    while let Some(offset) = text[rest..].find(['<', '[']) {
+        let start = rest + offset;
+        stripped.push_str(&text[rest..start]);
+        let html = text[start..].starts_with('<');
+        let end = text[start..]
+            .find(match html {
+                true => '>',
+                false => ']',
+            })
+            .map(|end| start + end);
+        let tag = end.and_then(|end| {
+            let inner = &lower[start + 1..end];
+            match html {
+                true => html_tag(inner),
+                false => bbcode_tag(inner).map(|name| (name, false)),
+            }
+        });
+        let (Some(end), Some((name, closing))) = (end, tag) else {
+            stripped.push_str(&text[start..start + 1]);
+            rest = start + 1;
+            continue;
+        };
+        rest = end + 1;
+        if !html {
+            if name == "*" {
+                stripped.push_str("\n• ");
+            }
+        } else if !closing && DROPPED_ELEMENTS.contains(&name) {
+            rest = lower[rest..]
+                .find(&format!("</{}", name))
+                .and_then(|close| {
+                    lower[rest + close..]
+                        .find('>')
+                        .map(|e| rest + close + e + 1)
+                })
+                .unwrap_or(text.len());
+        } else if LINE_BREAK_ELEMENTS.contains(&name) {
+            stripped.push('\n');
+        }
+    }
+    stripped.push_str(&text[rest..]);
+    stripped
+}
+
+/// Decode the entities left once the XML parser has decoded one level, so an escaped
+/// `&amp;lt;` shows as `&lt;` rather than as a tag
+/// 解碼 XML 解析器解碼一層後仍留下的實體；已跳脫的 `&amp;lt;` 會顯示為 `&lt;` 而非標籤
+fn decode_entities(text: &str) -> String {
+    [
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&quot;", "\""),
+        ("&#39;", "'"),
+        ("&apos;", "'"),
+        ("&nbsp;", " "),
+        ("&amp;", "&"),
+    ]
+    .iter()
+    .fold(text.to_string(), |text, (entity, plain)| {
+        text.replace(entity, plain)
+    })
+}
+
+/// Plain-text form of a manifest description and whether it was cut: CRLF and CR become LF,
+/// tags are stripped, control characters other than newlines and tabs are dropped, lines lose
+/// trailing spaces, runs of blank lines shrink to one, and text longer than `max_chars`
+/// characters is cut and ends with `…`. None when nothing readable is left.
+/// 將 manifest 說明轉為純文字並回傳是否截斷：CRLF 與 CR 轉為 LF、移除標籤、去除換行與 tab 以外的控制字元、
+/// 刪除行尾空白、連續空行合併為一行，超過 `max_chars` 個字元時截斷並以 `…` 結尾；沒有可讀內容時回傳 None
+pub fn sanitize_description(raw: &str, max_chars: usize) -> Option<(String, bool)> {
+    let text = raw.replace("\r\n", "\n").replace('\r', "\n");
+    let text = decode_entities(&strip_markup(&text));
+    let text = text
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+        .collect::<String>();
+
+    let mut lines = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        let blank_run = line.is_empty() && lines.last().is_none_or(|last: &&str| last.is_empty());
+        if !blank_run {
+            lines.push(line);
+        }
+    }
+    let text = lines.join("\n").trim().to_string();
+    if text.is_empty() {
+        return None;
+    }
+    match text.chars().count() > max_chars {
+        true => {
+            let cut = text.chars().take(max_chars).collect::<String>();
+            Some((format!("{}…", cut.trim_end()), true))
+        }
+        false => Some((text, false)),
+    }
+}
+
+impl ManifestData {
+    /// Redo `description_display` with at most `max_chars` characters
+    /// 以最多 `max_chars` 個字元重新產生 `description_display`
+    pub fn with_description_limit(mut self, max_chars: usize) -> Self {
+        let display = self
+            .description_raw
+            .as_deref()
+            .and_then(|raw| sanitize_description(raw, max_chars));
+        self.description_truncated = display.as_ref().is_some_and(|(_, cut)| *cut);
+        self.description_display = display.map(|(text, _)| text);
+        self
+    }
+}
+
+// ───────────────────────────────────────────────
+// Manifest Reading / 讀取 manifest
+// ───────────────────────────────────────────────
+
+/// Whether an archive entry is a manifest.xml, at any depth
+/// 判斷壓縮檔 entry 是否為 manifest.xml（任意層級）
+pub fn is_manifest_entry(name: &str) -> bool {
+    name.to_lowercase().ends_with("manifest.xml")
+}
+
+/// The child element's value, or the attribute's when the element is missing or blank
+/// 子元素的值；子元素不存在或為空白時取屬性的值
+fn prefer_element(element: Option<String>, attribute: Option<String>) -> Option<String> {
+    let present = |value: &String| !value.trim().is_empty();
+    element.filter(present).or(attribute.filter(present))
+}
+
+/// The `extras` of a manifest: values inside the child elements of `<manifest>` not in
+/// `KNOWN_ELEMENTS`, up to `EXTRA_VALUES`. XML errors end the reading with what was found.
+/// manifest 的 `extras`：`<manifest>` 中不在 `KNOWN_ELEMENTS` 的子元素內的值，最多 `EXTRA_VALUES` 個；
+/// 遇到 XML 錯誤時以已讀到的內容為準
+fn read_extras(content: &str) -> BTreeMap<String, Vec<String>> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+    let mut extras = BTreeMap::<String, Vec<String>>::new();
+    let mut kept = 0;
+    let mut depth = 0;
+    let mut element: Option<String> = None;
+    let mut keep = |element: &Option<String>, value: &str| {
+        let value = value.trim();
+        if let Some(element) = element {
+            if !value.is_empty()
+                && value.chars().count() <= EXTRA_VALUE_CHARS
+                && kept < EXTRA_VALUES
+            {
+                extras
+                    .entry(element.clone())
+                    .or_default()
+                    .push(value.to_string());
+                kept += 1;
+            }
+        }
+    };
+    loop {
+        let (start, empty) = match reader.read_event() {
+            Ok(Event::Start(start)) => (Some(start), false),
+            Ok(Event::Empty(start)) => (Some(start), true),
+            Ok(Event::Text(text)) => {
+                if let Ok(text) = text.unescape() {
+                    keep(&element, &text);
+                }
+                (None, false)
+            }
+            Ok(Event::CData(text)) => {
+                keep(&element, &String::from_utf8_lossy(&text));
+                (None, false)
+            }
+            Ok(Event::End(_)) => {
+                depth -= 1;
+                if depth == 1 {
+                    element = None;
+                }
+                (None, false)
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => (None, false),
+        };
+        let Some(start) = start else { continue };
+        if depth == 1 {
+            let name = String::from_utf8_lossy(start.name().as_ref()).to_string();
+            element = (!KNOWN_ELEMENTS.contains(&name.to_lowercase().as_str())).then_some(name);
+        }
+        if depth >= 1 {
+            for attribute in start.attributes().flatten() {
+                if let Ok(value) = attribute.unescape_value() {
+                    keep(&element, &value);
+                }
+            }
+        }
+        match (empty, depth) {
+            (true, 1) => element = None,
+            (true, _) => {}
+            (false, _) => depth += 1,
+        }
+    }
+    extras
+}
+
+/// Parse the text of a manifest.xml, taking fields from child elements or, failing that,
+/// attributes of `<manifest>`. A manifest with no GUID in either form is a parse failure.
+/// The description is sanitized up to `DEFAULT_DESCRIPTION_LIMIT`.
+/// 解析 manifest.xml 的內容，欄位優先取子元素，沒有時取 `<manifest>` 的屬性；兩種形式都沒有 GUID 時視為解析失敗；
+/// 說明文字以 `DEFAULT_DESCRIPTION_LIMIT` 為上限淨化
+pub fn parse_manifest(content: &str) -> Result<ManifestData, AppError> {
+    let raw: RawManifest = from_str(content).map_err(|e| AppError::ManifestParseFailed {
+        path: None,
+        detail: e.to_string(),
+    })?;
+    let guid =
+        prefer_element(raw.guid, raw.guid_attr).ok_or_else(|| AppError::ManifestParseFailed {
+            path: None,
+            detail: "no GUID as element or attribute".to_string(),
+        })?;
+    let manifest = ManifestData {
+        guid,
+        name: prefer_element(raw.name, raw.name_attr),
+        version: prefer_element(raw.version, raw.version_attr),
+        author: prefer_element(raw.author, raw.author_attr),
+        description_raw: prefer_element(raw.description, raw.description_attr),
+        description_display: None,
+        description_truncated: false,
+        games: raw.games,
+        extras: read_extras(content),
+    };
+    Ok(manifest.with_description_limit(DEFAULT_DESCRIPTION_LIMIT))
+}
+
+/// Find and parse manifest.xml inside an opened zip archive
+/// 從已開啟的 zip 中找出並解析 manifest.xml
+pub fn read_manifest_from_archive(
+    archive: &mut ZipArchive<File>,
+) -> Result<ManifestData, AppError> {
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| AppError::ZipInvalid {
+            path: None,
+            detail: e.to_string(),
+        })?;
+        if is_manifest_entry(entry.name()) {
+            let mut content = String::new();
+            entry
+                .read_to_string(&mut content)
+                .map_err(|e| AppError::ZipInvalid {
+                    path: None,
+                    detail: e.to_string(),
+                })?;
+
+            return parse_manifest(&content);
+        }
+    }
+
+    Err(AppError::ManifestMissing { path: None })
+}
+
+/// Open a mod file and parse its manifest.xml
+/// 開啟模組檔並解析其中的 manifest.xml
+pub fn read_manifest_from_path(path: &str) -> Result<ManifestData, AppError> {
+    let file = File::open(long_path::extend(Path::new(path)))
+        .map_err(|e| AppError::io("io.open_file", Some(Path::new(path)), e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| AppError::ZipInvalid {
+        path: Some(path.to_string()),
+        detail: e.to_string(),
+    })?;
+    read_manifest_from_archive(&mut archive).map_err(|e| e.with_archive_path(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_manifest, read_manifest_from_path, ManifestData};
+    use crate::{
+        error::AppError,
+        long_path,
+        test_support::{write_zip, SyntheticInstall, TestManifest},
+    };
+
+    #[test]
+    fn manifest_is_read_from_generated_zipmod() {
+        let install = SyntheticInstall::new();
+        let path = install.add_zipmod(
+            "m.zipmod",
+            &TestManifest::new("com.example.mod")
+                .with_name("Example")
+                .with_version("1.0.3")
+                .with_author("Example Author")
+                .with_game("Koikatsu")
+                .with_game("Koikatsu Sunshine"),
+        );
+
+        let data = read_manifest_from_path(&long_path::display(&path)).unwrap();
+
+        assert_eq!(data.guid, "com.example.mod");
+        assert_eq!(data.name.as_deref(), Some("Example"));
+        assert_eq!(data.version.as_deref(), Some("1.0.3"));
+        assert_eq!(data.author.as_deref(), Some("Example Author"));
+        assert_eq!(data.games, ["Koikatsu", "Koikatsu Sunshine"]);
+    }
+
+    #[test]
+    fn manifest_in_subfolder_is_found() {
+        let install = SyntheticInstall::new();
+        let path = install.mod_path("nested.zipmod");
+        let xml = TestManifest::new("nested.guid").to_xml();
+        write_zip(&path, &[("Some Mod/manifest.xml", xml.as_bytes())]);
+
+        let data = read_manifest_from_path(&long_path::display(&path)).unwrap();
+
+        assert_eq!(data.guid, "nested.guid");
+    }
+
+    fn description_of(xml_description: &str) -> ManifestData {
+        let xml = format!(
+            "<manifest><guid>desc.guid</guid><description>{}</description></manifest>",
+            xml_description
+        );
+        parse_manifest(&xml).unwrap()
+    }
+
+    #[test]
+    fn description_drops_script_and_keeps_tag_text() {
+        let data = description_of(
+            "<![CDATA[<p>Hair <b>v2</b></p><script>alert('x')</script>\
+             <img src=x onerror=alert(1)>a &lt;3 b]]>",
+        );
+
+        assert_eq!(
+            data.description_raw.as_deref(),
+            Some(
+                "<p>Hair <b>v2</b></p><script>alert('x')</script><img src=x onerror=alert(1)>a &lt;3 b"
+            )
+        );
+        assert_eq!(data.description_display.as_deref(), Some("Hair v2\na <3 b"));
+        assert!(!data.description_truncated);
+    }
+
+    #[test]
+    fn description_strips_bbcode_but_not_bracketed_names() {
+        let data = description_of(
+            "[b]Bold[/b] by [Author] [url=https://example.com]site[/url]\
+             [list][*]one[*]two[/list]",
+        );
+
+        assert_eq!(
+            data.description_display.as_deref(),
+            Some("Bold by [Author] site\n• one\n• two")
+        );
+    }
+
+    #[test]
+    fn description_normalizes_crlf_and_blank_lines() {
+        let data = description_of("  First\r\n\r\n\r\n\r\nSecond  \rThird\r\n\r\n");
+
+        assert_eq!(
+            data.description_display.as_deref(),
+            Some("First\n\nSecond\nThird")
+        );
+    }
+
+    #[test]
+    fn description_is_cut_at_the_limit() {
+        let data = description_of("ゲーム用の髪型です").with_description_limit(4);
+        let blank = description_of("&lt;br&gt;&lt;br /&gt;");
+
+        assert_eq!(data.description_display.as_deref(), Some("ゲーム用…"));
+        assert!(data.description_truncated);
+        assert_eq!(blank.description_display, None);
+    }
+
+    #[test]
+    fn archive_without_manifest_or_zip_fails() {
+        let install = SyntheticInstall::new();
+        let no_manifest = install.mod_path("empty.zipmod");
+        write_zip(&no_manifest, &[("abdata/x.unity3d", b"bundle")]);
+        let not_zip = install.add_file("mods/truncated.zipmod", b"PK\x03\x04 truncated");
+
+        let missing = read_manifest_from_path(&long_path::display(&no_manifest));
+        let invalid = read_manifest_from_path(&long_path::display(&not_zip));
+
+        assert!(matches!(missing, Err(AppError::ManifestMissing { .. })));
+        assert!(matches!(invalid, Err(AppError::ZipInvalid { .. })));
+    }
+
+    #[test]
+    fn attribute_only_manifest_is_read() {
+        let install = SyntheticInstall::new();
+        let path = install.mod_path("attributes.zipmod");
+        let xml = r#"<manifest guid="author.attr" version="1.2" name="Attr" author="Someone"/>"#;
+        write_zip(&path, &[("manifest.xml", xml.as_bytes())]);
+
+        let data = read_manifest_from_path(&long_path::display(&path)).unwrap();
+
+        assert_eq!(data.guid, "author.attr");
+        assert_eq!(data.version.as_deref(), Some("1.2"));
+        assert_eq!(data.name.as_deref(), Some("Attr"));
+        assert_eq!(data.author.as_deref(), Some("Someone"));
+    }
+
+    #[test]
+    fn element_wins_over_attribute() {
+        let data = parse_manifest(
+            r#"<manifest guid="attr.guid" version="1.0"><guid>element.guid</guid>
+                <version>2.0</version></manifest>"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.guid, "element.guid");
+        assert_eq!(data.version.as_deref(), Some("2.0"));
+    }
+
+    #[test]
+    fn blank_element_falls_back_to_attribute() {
+        let data = parse_manifest(
+            r#"<manifest guid="attr.guid" version="1.0"><guid>  </guid><version/></manifest>"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.guid, "attr.guid");
+        assert_eq!(data.version.as_deref(), Some("1.0"));
+    }
+
+    #[test]
+    fn manifest_without_guid_fails_to_parse() {
+        let result = parse_manifest(r#"<manifest version="1.0"><name>No Guid</name></manifest>"#);
+
+        assert!(matches!(result, Err(AppError::ManifestParseFailed { .. })));
+    }
+}
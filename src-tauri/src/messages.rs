@@ -0,0 +1,548 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+// ───────────────────────────────────────────────
+// Message Catalog / 訊息目錄
+// ───────────────────────────────────────────────
+
+/// Every message key the backend can emit, with its default English template.
+/// `{name}` is replaced by the parameter of the same name. The frontend mirrors this
+/// table to provide translations; keys must never be renamed.
+/// 後端可能送出的所有訊息 key 與預設英文樣板；`{name}` 會被同名參數取代。
+/// 前端依此表提供翻譯，key 一經發佈不可更名
+pub const MESSAGE_CATALOG: &[(&str, &str)] = &[
+    (
+        "log.not_found",
+        "No known log file found in the specified game path.",
+    ),
+    ("log.unreadable", "Failed to read log file: {detail}"),
+    ("zip.invalid", "Invalid zip file: {detail}"),
+    ("manifest.missing", "manifest.xml not found in zip file"),
+    ("archive.invalid", "Invalid {format} archive: {detail}"),
+    (
+        "archive.format_unsupported",
+        "{format} archives are not supported by this build: {path}",
+    ),
+    ("manifest.parse_failed", "XML parse error: {detail}"),
+    (
+        "disk.insufficient_space",
+        "Insufficient space on {path}: need {needed}, have {available}",
+    ),
+    ("delete.failed", "Failed to delete {path}: {detail}"),
+    (
+        "delete.file_changed",
+        "{count} file(s) changed since scan, e.g. {path}; re-scan or confirm to delete anyway.",
+    ),
+    (
+        "delete.file_locked",
+        "{path} is temporarily locked by another process, such as antivirus or the search indexer, and was still locked after {retries} retries; try again in a moment.",
+    ),
+    (
+        "delete.modpack_protected",
+        "{count} file(s) are in a modpack folder that KKManager re-syncs, e.g. {path}; delete the manual copy instead or confirm to delete anyway.",
+    ),
+    (
+        "delete.pinned",
+        "{count} file(s) are pinned, e.g. {path}; unpin them or confirm to delete anyway.",
+    ),
+    (
+        "delete.read_only",
+        "{count} file(s) are read-only, e.g. {path}; enable 'clear read-only flag' to proceed.",
+    ),
+    (
+        "delete.safe_mode_refused",
+        "Safe mode is on and {path} is outside every registered game, so it cannot be quarantined; nothing was deleted.",
+    ),
+    (
+        "path.cloud_placeholder",
+        "{path} is a cloud placeholder whose data is not on this PC; make it available offline in OneDrive or Dropbox, or turn on hydrating cloud files in settings.",
+    ),
+    (
+        "path.mods_missing",
+        "Mods directory not found at {path}; create it to start adding mods.",
+    ),
+    (
+        "path.mods_missing_elsewhere",
+        "Mods directory not found at {path}, but a Sideloader Modpack folder is at {found}; the game folder may be {suggested}.",
+    ),
+    (
+        "path.not_allowed",
+        "Refusing to modify a path outside the game directories: {path}",
+    ),
+    (
+        "game.running",
+        "The game is running; close it before modifying mods.",
+    ),
+    ("check.game_not_running", "The game is not running."),
+    (
+        "check.game_path_missing",
+        "Game folder not found: {path}",
+    ),
+    ("check.game_path_ok", "Found {variant} at {path}."),
+    (
+        "check.game_path_unknown",
+        "{path} exists but contains no known game data folder.",
+    ),
+    (
+        "check.game_running",
+        "{executable} is running; files it has open cannot be changed until it exits.",
+    ),
+    (
+        "check.log_found",
+        "Log file {path}, last written {age} ago.",
+    ),
+    ("check.log_found_undated", "Log file {path}."),
+    (
+        "check.log_missing",
+        "No log file found yet; start the game once so Sideloader can write one.",
+    ),
+    (
+        "check.logging_defaults",
+        "No BepInEx.cfg yet; BepInEx's defaults log mod conflicts.",
+    ),
+    (
+        "check.logging_disk_disabled",
+        "{setting} is {value} in {path}, so conflicts never reach the log; set it to true.",
+    ),
+    (
+        "check.logging_level_filtered",
+        "{setting} = {value} in {path} hides conflict messages; include {required}.",
+    ),
+    (
+        "check.logging_ok",
+        "BepInEx logs conflict messages to disk ({path}).",
+    ),
+    (
+        "check.mod_root_cloud_sync",
+        "Mods folder {path} is synced by {service}. Files stored online only make reads hang while they download; mark the folder \"Always keep on this device\" or move the game out of {service}.",
+    ),
+    ("check.mod_root_link", "Mods folder {path} is a link to {target}."),
+    (
+        "check.mod_root_other_volume",
+        "Mods folder {path} is on {volume}, not on the game's drive; its free space is checked separately, and the Recycle Bin and quarantine there are those of that drive.",
+    ),
+    ("check.mods_missing", "Mods folder not found: {path}"),
+    (
+        "check.mods_read_only",
+        "Cannot write to the mods folder {path}: {detail}",
+    ),
+    (
+        "check.mods_undeletable",
+        "Could create but not delete {path}: {detail}",
+    ),
+    ("check.mods_writable", "Mods folder {path} is writable."),
+    (
+        "check.mods_write_skipped",
+        "Read-only mode is on, so the write test in {path} was skipped.",
+    ),
+    ("check.space_low", "Only {available} free on the volume of {path}."),
+    ("check.space_ok", "{available} free on the volume of {path}."),
+    (
+        "check.space_unknown",
+        "Could not determine free space for {path}.",
+    ),
+    ("check.trash_ok", "The Recycle Bin is available."),
+    (
+        "check.trash_unavailable",
+        "The Recycle Bin is not available: {detail}",
+    ),
+    (
+        "check.trash_unknown",
+        "The Recycle Bin cannot be checked on this platform.",
+    ),
+    (
+        "open.no_archive_handler",
+        "No program is set up to open {path}; install an archive manager such as 7-Zip.",
+    ),
+    ("operation.cancelled", "Operation cancelled."),
+    (
+        "operation.id_in_use",
+        "Operation {operation_id} is already running; start the new one under another id.",
+    ),
+    (
+        "operation.path_busy",
+        "Operation {operation_id} is still working on {path}; try again once it finishes.",
+    ),
+    (
+        "operation.read_only_mode",
+        "Read-only mode is on, so {path} was left untouched; turn it off in settings to change files.",
+    ),
+    ("conflict.predicted", "predicted (not yet confirmed by log)"),
+    (
+        "resolution.also_affects",
+        "The files this deletes also belong to conflicts {conflict_ids}, which will change too.",
+    ),
+    (
+        "resolution.priority_override",
+        "{path} is in a priority folder, so it looks like an intentional override of the modpack version; it is kept and no priority or modpack copy is deleted.",
+    ),
+    (
+        "scan.expired",
+        "Results of scan {operation_id} are no longer available; run the scan again.",
+    ),
+    ("internal.panic", "Internal error occurred: {detail}"),
+    (
+        "internal.panic_incident",
+        "Internal error occurred (incident {incident_id}): {detail}",
+    ),
+    ("plugin.not_managed", "Not a managed plugin: {path}"),
+    (
+        "plugin.info_missing",
+        "No BepInPlugin attribute or version info found in {path}",
+    ),
+    ("input.asset_path_empty", "Asset path is empty."),
+    (
+        "input.checksums_malformed",
+        "Checksum file {path} is malformed at line {line}.",
+    ),
+    (
+        "input.checksums_unsupported",
+        "Checksum file {path} uses format version {version}, which this version cannot read.",
+    ),
+    (
+        "input.directory_not_mod",
+        "{path} is a folder, not a mod file; folders are only deleted with 'allow folders' turned on.",
+    ),
+    (
+        "input.folder_depth_unsupported",
+        "Folder depth {depth} is not supported; use 1 or 2.",
+    ),
+    (
+        "input.game_variant_unknown",
+        "Could not tell which game is installed at {path}.",
+    ),
+    ("input.guid_empty", "GUID is empty."),
+    (
+        "input.invalid_file_name",
+        "\"{name}\" is not a valid file name on Windows.",
+    ),
+    (
+        "input.modpack_manifest_invalid",
+        "Modpack manifest {path} could not be read: {detail}",
+    ),
+    (
+        "input.no_conflict_scan",
+        "No conflict scan has been run yet; scan first.",
+    ),
+    (
+        "input.note_too_long",
+        "Note is {length} characters long; the limit is {max}.",
+    ),
+    (
+        "input.open_directory",
+        "{path} is a folder, not a mod archive.",
+    ),
+    (
+        "input.pin_not_a_file",
+        "Cannot pin {path}: it is not an existing file.",
+    ),
+    (
+        "input.rename_target_exists",
+        "Cannot rename: {path} already exists.",
+    ),
+    (
+        "input.rule_plan_changed",
+        "The files this rule matches changed since it was previewed (plan {expected}, now {actual}); review the new preview before confirming.",
+    ),
+    (
+        "input.rule_without_conditions",
+        "The rule sets no condition and would match every skipped file; add at least one.",
+    ),
+    (
+        "input.transaction_read_only",
+        "Step {step} cannot run: {path} is read-only; nothing was changed.",
+    ),
+    (
+        "input.transaction_source_missing",
+        "Step {step} cannot run: {path} does not exist; nothing was changed.",
+    ),
+    (
+        "input.transaction_target_exists",
+        "Step {step} cannot run: {path} already exists; nothing was changed.",
+    ),
+    (
+        "input.unknown_conflict_sort",
+        "Unknown sort \"{sort}\"; expected wasted_bytes, skipped_count, name or most_recent_activity.",
+    ),
+    (
+        "input.unknown_estimate_kind",
+        "Unknown operation \"{kind}\"; expected asset-index, checksum-export, checksum-verify or corrupt-scan.",
+    ),
+    (
+        "input.unknown_group_by",
+        "Unknown grouping \"{by}\"; expected manifest_author, filename_author, guid_prefix or priority_folder.",
+    ),
+    (
+        "input.unknown_incident",
+        "Unknown incident id: {incident_id}",
+    ),
+    (
+        "input.unknown_profile",
+        "No registered profile has id {profile_id}.",
+    ),
+    (
+        "input.unknown_resolve_action",
+        "Unknown action \"{action}\"; expected trash, quarantine or disable.",
+    ),
+    (
+        "input.unknown_sort_by",
+        "Unknown sort order \"{sort_by}\"; expected name, size, modified or wasted_bytes.",
+    ),
+    (
+        "io.clear_read_only",
+        "Failed to clear the read-only flag of {path}: {detail}",
+    ),
+    (
+        "io.compact_index_db",
+        "Failed to compact index database {path}: {detail}",
+    ),
+    ("io.copy_file", "Failed to copy {path}: {detail}"),
+    (
+        "io.create_bundle_directory",
+        "Failed to create bundle directory: {detail}",
+    ),
+    (
+        "io.create_cache_directory",
+        "Failed to create cache directory: {detail}",
+    ),
+    (
+        "io.create_checksums_directory",
+        "Failed to create checksum file directory: {detail}",
+    ),
+    (
+        "io.create_diagnostics_bundle",
+        "Failed to create diagnostics bundle: {detail}",
+    ),
+    (
+        "io.create_index_directory",
+        "Failed to create index directory: {detail}",
+    ),
+    (
+        "io.create_mods_dir",
+        "Failed to create mods folder {path}: {detail}",
+    ),
+    (
+        "io.create_profile_directory",
+        "Failed to create profile directory: {detail}",
+    ),
+    (
+        "io.finish_diagnostics_bundle",
+        "Failed to finish diagnostics bundle: {detail}",
+    ),
+    (
+        "io.discard_result",
+        "Failed to delete result file {path}: {detail}",
+    ),
+    (
+        "io.disable_mod",
+        "Failed to disable {path}: {detail}",
+    ),
+    (
+        "io.extract_archive",
+        "Failed to extract {path}: {detail}",
+    ),
+    (
+        "io.extract_zipmod",
+        "Failed to extract zipmod to {path}: {detail}",
+    ),
+    ("io.hash_file", "Failed to hash {path}: {detail}"),
+    ("io.install_mod", "Failed to install {path}: {detail}"),
+    (
+        "io.list_app_logs",
+        "Failed to list app log directory: {detail}",
+    ),
+    ("io.open_archive", "Failed to open {path}: {detail}"),
+    (
+        "io.open_archive_copy",
+        "Failed to copy {path} for opening: {detail}",
+    ),
+    ("io.open_file", "Failed to open file: {detail}"),
+    (
+        "io.open_index_db",
+        "Failed to open index database {path}: {detail}",
+    ),
+    (
+        "io.quarantine_file",
+        "Failed to quarantine {path}: {detail}",
+    ),
+    ("io.read_app_log", "Failed to read app log: {detail}"),
+    (
+        "io.read_checksums",
+        "Failed to read checksum file: {detail}",
+    ),
+    (
+        "io.read_conflict_notes",
+        "Failed to read conflict notes: {detail}",
+    ),
+    (
+        "io.read_first_seen",
+        "Failed to read first-seen dates: {detail}",
+    ),
+    (
+        "io.read_index_db",
+        "Failed to read index database {path}: {detail}",
+    ),
+    (
+        "io.read_modpack_manifest",
+        "Failed to read modpack manifest: {detail}",
+    ),
+    ("io.read_pins", "Failed to read pinned files: {detail}"),
+    ("io.rename_mod", "Failed to rename {path}: {detail}"),
+    ("io.roll_back_step", "Failed to undo the change to {path}: {detail}"),
+    (
+        "io.read_result",
+        "Failed to read result file {path}: {detail}",
+    ),
+    (
+        "io.resolve_app_data_dir",
+        "Failed to resolve app data dir: {detail}",
+    ),
+    (
+        "io.resolve_app_log_dir",
+        "Failed to resolve app log dir: {detail}",
+    ),
+    (
+        "io.serialize_environment",
+        "Failed to serialize environment: {detail}",
+    ),
+    ("io.serialize_index", "Failed to serialize index: {detail}"),
+    (
+        "io.serialize_profile_record",
+        "Failed to serialize profile record: {detail}",
+    ),
+    (
+        "io.serialize_scan_cache",
+        "Failed to serialize scan cache: {detail}",
+    ),
+    (
+        "io.start_background_refresh",
+        "Failed to start background refresh: {detail}",
+    ),
+    (
+        "io.start_mods_watch",
+        "Failed to watch mods folder: {detail}",
+    ),
+    (
+        "io.write_app_data_dir",
+        "App data folder {path} cannot be written, so nothing will be saved this session: {detail}",
+    ),
+    (
+        "io.write_app_settings",
+        "Failed to save app settings: {detail}",
+    ),
+    (
+        "io.write_checksums",
+        "Failed to write checksum file: {detail}",
+    ),
+    (
+        "io.write_cleanup_stats",
+        "Failed to write cleanup stats: {detail}",
+    ),
+    (
+        "io.write_diagnostics_bundle",
+        "Failed to write diagnostics bundle: {detail}",
+    ),
+    (
+        "io.write_conflict_notes",
+        "Failed to write conflict notes: {detail}",
+    ),
+    (
+        "io.write_first_seen",
+        "Failed to write first-seen dates: {detail}",
+    ),
+    (
+        "io.write_full_report",
+        "Failed to write full analysis report: {detail}",
+    ),
+    ("io.write_index", "Failed to write index: {detail}"),
+    (
+        "io.write_index_db",
+        "Failed to write index database {path}: {detail}",
+    ),
+    ("io.write_pins", "Failed to write pinned files: {detail}"),
+    (
+        "io.write_profile_record",
+        "Failed to write profile record: {detail}",
+    ),
+    (
+        "io.write_result",
+        "Failed to write result file: {detail}",
+    ),
+    (
+        "io.write_scan_cache",
+        "Failed to write scan cache: {detail}",
+    ),
+    (
+        "io.write_throughput",
+        "Failed to write measured throughput: {detail}",
+    ),
+    (
+        "io.write_zipmod",
+        "Failed to write zipmod {path}: {detail}",
+    ),
+];
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// A translatable message: stable key plus named parameters
+/// 可翻譯的訊息：穩定的 key 與具名參數
+#[derive(Debug, Clone, Serialize)]
+pub struct Message {
+    key: &'static str,
+    params: BTreeMap<&'static str, String>,
+}
+
+impl Message {
+    /// Start a message for a catalog key
+    /// 以目錄中的 key 建立訊息
+    pub fn new(key: &'static str) -> Self {
+        debug_assert!(
+            template(key).is_some(),
+            "message key missing from catalog: {}",
+            key
+        );
+        Message {
+            key,
+            params: BTreeMap::new(),
+        }
+    }
+
+    /// Add a named parameter
+    /// 加入具名參數
+    pub fn with(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.params.insert(name, value.to_string());
+        self
+    }
+
+    /// Add a parameter only when present
+    /// 僅在有值時加入參數
+    pub fn with_opt(self, name: &'static str, value: Option<&str>) -> Self {
+        match value {
+            Some(value) => self.with(name, value),
+            None => self,
+        }
+    }
+
+    /// Default English rendering; falls back to the key for unknown entries
+    /// 預設的英文內容；未知的 key 直接顯示 key
+    pub fn render(&self) -> String {
+        let Some(template) = template(self.key) else {
+            return self.key.to_string();
+        };
+        self.params
+            .iter()
+            .fold(template.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+    }
+}
+
+fn template(key: &str) -> Option<&'static str> {
+    MESSAGE_CATALOG
+        .iter()
+        .find(|(candidate, _)| *candidate == key)
+        .map(|(_, template)| *template)
+}
@@ -0,0 +1,236 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    app_log, asset_index, links, long_path, manifest, mod_roots, mods_scan::ModFile, ops,
+    patterns::patterns, quarantine,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Log lines kept per GUID as examples / 每個 GUID 保留作為範例的 log 行數
+const EXAMPLE_LIMIT: usize = 3;
+
+/// Whether a GUID the log asked for is on the disk after all
+/// log 所需的 GUID 是否其實仍在磁碟上
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DependencyStatus {
+    /// Not found anywhere; needs a download / 到處都找不到，需要下載
+    Missing,
+    /// In a quarantine folder; restore it instead / 位於隔離資料夾，可改為還原
+    Quarantined,
+    /// Renamed to `.disabled`; re-enable it instead / 已改名為 `.disabled`，可改為重新啟用
+    Disabled,
+    /// Enabled now, likely installed after the log was written
+    /// 目前已啟用，可能在 log 寫入後才安裝
+    Installed,
+}
+
+/// One line of the log mentioning a missing GUID / log 中提到缺少 GUID 的一行
+#[derive(Serialize, Clone)]
+pub(crate) struct LogExample {
+    /// 1-based line in the log / log 中的行號（從 1 起算）
+    pub line: usize,
+    pub text: String,
+}
+
+/// A mod that cards or scenes loaded in the logged session needed and Sideloader did not have
+/// log 記錄的遊戲階段中，角色卡或場景需要但 Sideloader 沒有的模組
+#[derive(Serialize, Clone)]
+pub(crate) struct MissingDependency {
+    /// As first written in the log / log 中第一次出現時的寫法
+    pub guid: String,
+    pub occurrences: usize,
+    /// First `EXAMPLE_LIMIT` lines naming the GUID / 提到此 GUID 的前 `EXAMPLE_LIMIT` 行
+    pub examples: Vec<LogExample>,
+    pub status: DependencyStatus,
+    /// Files declaring the GUID, for every status but `missing`
+    /// 宣告此 GUID 的檔案；`missing` 以外的狀態才有
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub found_at: Vec<String>,
+}
+
+// ───────────────────────────────────────────────
+// Parsing / 解析
+// ───────────────────────────────────────────────
+
+/// Sideloader's missing-mod and failed-resolve warnings grouped by GUID, compared without
+/// case, with an occurrence count and the first few lines as examples. Most occurrences
+/// first, then by GUID. Every result is `missing` until `cross_reference` runs.
+/// 將 Sideloader 的缺少模組與解析失敗警告依 GUID（不分大小寫）分組，附出現次數與前幾行作為範例；
+/// 依出現次數由多到少、再依 GUID 排序；執行 `cross_reference` 前所有結果皆為 `missing`
+pub(crate) fn parse_missing_dependencies(log: &str) -> Vec<MissingDependency> {
+    let pattern = &patterns().sideloader_missing_mod;
+    let mut by_guid: HashMap<String, MissingDependency> = HashMap::new();
+    for (index, line) in log.lines().enumerate() {
+        let Some(guid) = pattern.captures(line).and_then(|captures| captures.get(1)) else {
+            continue;
+        };
+        let dependency = by_guid
+            .entry(guid.as_str().to_lowercase())
+            .or_insert_with(|| MissingDependency {
+                guid: guid.as_str().to_string(),
+                occurrences: 0,
+                examples: Vec::new(),
+                status: DependencyStatus::Missing,
+                found_at: Vec::new(),
+            });
+        dependency.occurrences += 1;
+        if dependency.examples.len() < EXAMPLE_LIMIT {
+            dependency.examples.push(LogExample {
+                line: index + 1,
+                text: line.trim().to_string(),
+            });
+        }
+    }
+    let mut dependencies = by_guid.into_values().collect::<Vec<_>>();
+    dependencies.sort_by(|a, b| {
+        b.occurrences
+            .cmp(&a.occurrences)
+            .then_with(|| a.guid.cmp(&b.guid))
+    });
+    dependencies
+}
+
+// ───────────────────────────────────────────────
+// Cross-Referencing / 交叉比對
+// ───────────────────────────────────────────────
+
+/// Lowercased GUID of the mod archive at `path`, logged and None when unreadable
+/// `path` 模組壓縮檔的小寫 GUID；無法讀取時記錄並回傳 None
+fn read_guid(path: &Path) -> Option<String> {
+    match manifest::read_manifest_from_path(&long_path::display(path)) {
+        Ok(manifest) => Some(manifest.guid.to_lowercase()),
+        Err(e) => {
+            app_log::write(&format!("manifest of {} skipped: {}", path.display(), e));
+            None
+        }
+    }
+}
+
+/// Mark each dependency that is on the disk after all: `installed` when an enabled file of
+/// `enabled` (the GUID index) declares it, else `disabled` for a `.disabled` file under a mod
+/// root, else `quarantined` for a file in the game's quarantine folder. Only disabled and
+/// quarantined mod archives are opened; enabled ones come from the index.
+/// 標記其實仍在磁碟上的相依模組：`enabled`（GUID 索引）中有啟用的檔案宣告時為 `installed`，
+/// 否則模組根目錄下有 `.disabled` 檔案時為 `disabled`，再否則遊戲隔離資料夾中有檔案時為 `quarantined`；
+/// 只會開啟已停用與已隔離的模組壓縮檔，啟用中的檔案取自索引
+pub(crate) fn cross_reference(
+    dependencies: &mut [MissingDependency],
+    enabled: &[ModFile],
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+) {
+    if dependencies.is_empty() {
+        return;
+    }
+    let mut found: HashMap<String, Vec<(DependencyStatus, String)>> = HashMap::new();
+    for file in enabled {
+        if let Some(manifest) = &file.manifest {
+            found
+                .entry(manifest.guid.to_lowercase())
+                .or_default()
+                .push((DependencyStatus::Installed, file.entry.path.clone()));
+        }
+    }
+
+    let disabled = mod_roots::mod_roots(game_path)
+        .into_iter()
+        .flat_map(|root| links::walk_files(&root, follow_links))
+        .filter(|file| {
+            ops::is_disabled(&file.path)
+                && asset_index::is_mod_archive(&file.path.with_extension(""), mod_extensions)
+        })
+        .map(|file| (DependencyStatus::Disabled, file.path));
+    let quarantined = links::walk_files(&quarantine::quarantine_dir(game_path), follow_links)
+        .into_iter()
+        .filter(|file| {
+            let path = match ops::is_disabled(&file.path) {
+                true => file.path.with_extension(""),
+                false => file.path.clone(),
+            };
+            asset_index::is_mod_archive(&path, mod_extensions)
+        })
+        .map(|file| (DependencyStatus::Quarantined, file.path));
+    for (status, path) in disabled.chain(quarantined) {
+        if let Some(guid) = read_guid(&path) {
+            found
+                .entry(guid)
+                .or_default()
+                .push((status, long_path::display(&path)));
+        }
+    }
+
+    for dependency in dependencies {
+        let Some(files) = found.get(&dependency.guid.to_lowercase()) else {
+            continue;
+        };
+        dependency.status = files
+            .iter()
+            .map(|(status, _)| *status)
+            .max()
+            .unwrap_or(DependencyStatus::Missing);
+        dependency.found_at = files
+            .iter()
+            .filter(|(status, _)| *status == dependency.status)
+            .map(|(_, path)| path.clone())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cross_reference, parse_missing_dependencies, DependencyStatus};
+    use crate::test_support::{write_zip, SyntheticInstall, SyntheticLog, TestManifest};
+
+    #[test]
+    fn missing_dependencies_group_by_guid_and_find_restorable_copies() {
+        let install = SyntheticInstall::new();
+        install.add_zipmod("off.zipmod.disabled", &TestManifest::new("author.Disabled"));
+        write_zip(
+            &install.root().join("quarantine/mods/q.zipmod"),
+            &[(
+                "manifest.xml",
+                TestManifest::new("author.quarantined").to_xml().as_bytes(),
+            )],
+        );
+        let log = SyntheticLog::new()
+            .line("[Warning:Sideloader] [UAR] WARNING! Missing mod detected! [author.gone]")
+            .line("[Warning:Sideloader] [UAR] WARNING! Missing mod detected! [author.disabled]")
+            .line("[Info   :Sideloader] [UAR] Compatibility resolving failed for [Author.Gone] slot 12")
+            .line("[Warning:Sideloader] [UAR] WARNING! Missing mod detected! [author.quarantined]")
+            .line("[Info   :Sideloader] Resolving (author.ok) body from slot 1 to slot 2");
+
+        let mut dependencies = parse_missing_dependencies(&log.text());
+        cross_reference(
+            &mut dependencies,
+            &[],
+            install.root(),
+            false,
+            &["zipmod".to_string()],
+        );
+
+        let summary = dependencies
+            .iter()
+            .map(|dependency| (dependency.guid.as_str(), dependency.occurrences))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("author.gone", 2),
+                ("author.disabled", 1),
+                ("author.quarantined", 1)
+            ]
+        );
+        assert_eq!(dependencies[0].examples[1].line, 5);
+        assert!(dependencies[0].status == DependencyStatus::Missing);
+        assert!(dependencies[1].status == DependencyStatus::Disabled);
+        assert!(dependencies[2].status == DependencyStatus::Quarantined);
+        assert_eq!(dependencies[2].found_at.len(), 1);
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::{disk_space, error::AppError, links, long_path, path_guard::WriteMode};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Sideloader's config file, relative to the game root
+/// Sideloader 設定檔，相對於遊戲根目錄
+const SIDELOADER_CONFIG: &[&str] = &["BepInEx", "config", "com.bepis.bepinex.sideloader.cfg"];
+
+/// Setting pointing Sideloader at a second mods folder, as old KK Party guides set up
+/// 讓 Sideloader 額外載入第二個 mods 資料夾的設定，舊版 KK Party 教學常會設定
+const ADDITIONAL_DIR_KEY: &str = "AdditionalModsDirectory";
+
+/// Folder KKManager's modpacks are synced into, looked for when `mods` is missing
+/// KKManager 模組包同步到的資料夾，`mods` 不存在時會搜尋此資料夾
+const MODPACK_DIR: &str = "Sideloader Modpack";
+
+/// How deep under the game folder a misplaced `Sideloader Modpack` is looked for
+/// 在遊戲資料夾下搜尋放錯位置的 `Sideloader Modpack` 的深度
+const MODPACK_SEARCH_DEPTH: usize = 4;
+
+/// One folder Sideloader loads mods from, with where its files really are
+/// Sideloader 載入模組的單一資料夾，以及其中檔案實際所在的位置
+#[derive(Serialize)]
+pub(crate) struct ModDirectory {
+    pub path: String,
+    /// Resolved target when the folder is a symlink or junction
+    /// 資料夾為 symlink 或 junction 時解析後的目標
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_target: Option<String>,
+    /// Volume of the files, links followed; None when the folder is missing
+    /// 檔案所在的磁碟區（已解析連結）；資料夾不存在時為 None
+    pub volume: Option<String>,
+    /// On a different volume than the game folder, so free space, the Recycle Bin and moves
+    /// into quarantine are those of another drive
+    /// 與遊戲資料夾位於不同磁碟區，因此可用空間、回收桶與移入隔離區皆依另一個磁碟而定
+    pub other_volume: bool,
+}
+
+// ───────────────────────────────────────────────
+// Mod Roots / 模組根目錄
+// ───────────────────────────────────────────────
+
+/// Sideloader's additional mods directory of a game, if one is configured and exists;
+/// a relative setting is taken from the game root
+/// 遊戲所設定且存在的 Sideloader 額外 mods 資料夾；相對路徑以遊戲根目錄為準
+pub(crate) fn additional_dir(game_path: &Path) -> Option<PathBuf> {
+    let config_path = SIDELOADER_CONFIG
+        .iter()
+        .fold(game_path.to_path_buf(), |dir, part| dir.join(part));
+    let config = fs::read_to_string(long_path::extend(&config_path)).ok()?;
+    let value = config.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(ADDITIONAL_DIR_KEY)
+            .then(|| value.trim().trim_matches('"'))
+    })?;
+    if value.is_empty() {
+        return None;
+    }
+    let dir = game_path.join(value);
+    let is_dir = links::stat(&dir)
+        .0
+        .is_some_and(|metadata| metadata.is_dir());
+    (is_dir && dir != game_path.join("mods")).then_some(dir)
+}
+
+/// Every folder Sideloader loads mods from: the game's `mods` first, then the additional
+/// directory when configured
+/// Sideloader 載入模組的所有資料夾：先是遊戲的 `mods`，有設定時再加上額外資料夾
+pub(crate) fn mod_roots(game_path: &Path) -> Vec<PathBuf> {
+    std::iter::once(game_path.join("mods"))
+        .chain(additional_dir(game_path))
+        .collect()
+}
+
+/// Every mod root of a game with its link target and volume
+/// 遊戲的每個模組根目錄及其連結目標與磁碟區
+pub(crate) fn mod_directories(game_path: &Path) -> Vec<ModDirectory> {
+    let game_volume = disk_space::volume_name(game_path);
+    mod_roots(game_path)
+        .into_iter()
+        .map(|root| {
+            let (metadata, link_target) = links::stat(&root);
+            let volume = metadata.and_then(|_| disk_space::volume_name(&root));
+            ModDirectory {
+                path: long_path::display(&root),
+                link_target,
+                other_volume: volume.is_some() && game_volume.is_some() && volume != game_volume,
+                volume,
+            }
+        })
+        .collect()
+}
+
+/// The shallowest `Sideloader Modpack` folder under `game_path` and the game folder it
+/// implies: the folder holding its `mods`, or its own parent otherwise
+/// `game_path` 下最淺的 `Sideloader Modpack` 資料夾及據此推測的遊戲資料夾：其 `mods` 所在的資料夾，否則為其上層資料夾
+fn find_misplaced_modpack(game_path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let found = WalkDir::new(long_path::extend(game_path))
+        .max_depth(MODPACK_SEARCH_DEPTH)
+        .into_iter()
+        .filter_map(Result::ok)
+        .find(|entry| {
+            entry.file_type().is_dir()
+                && entry
+                    .file_name()
+                    .to_string_lossy()
+                    .eq_ignore_ascii_case(MODPACK_DIR)
+        })?
+        .into_path();
+    let parent = found.parent()?;
+    let in_mods = parent
+        .file_name()
+        .is_some_and(|name| name.eq_ignore_ascii_case("mods"));
+    let suggested = match in_mods {
+        true => parent.parent()?,
+        false => parent,
+    };
+    Some((suggested.to_path_buf(), found))
+}
+
+/// Fail with `mods_dir_missing` when the game has neither a `mods` folder nor an additional
+/// mods directory, as on a fresh install without a modpack. When a `Sideloader Modpack` folder
+/// lies deeper under `game_path`, the error names it and the game folder it suggests, since
+/// the user most likely picked a folder above the game.
+/// 遊戲既沒有 `mods` 資料夾也沒有額外 mods 資料夾時（例如未安裝模組包的全新安裝）回傳 `mods_dir_missing`；
+/// 若 `game_path` 更深處有 `Sideloader Modpack` 資料夾，錯誤會指出該資料夾與據此推測的遊戲資料夾，因為使用者很可能選到了遊戲的上層資料夾
+pub(crate) fn ensure_mods_dir(game_path: &Path) -> Result<(), AppError> {
+    let mods = game_path.join("mods");
+    let is_dir = links::stat(&mods)
+        .0
+        .is_some_and(|metadata| metadata.is_dir());
+    if is_dir || additional_dir(game_path).is_some() {
+        return Ok(());
+    }
+    let misplaced = find_misplaced_modpack(game_path);
+    Err(AppError::ModsDirMissing {
+        path: long_path::display(&mods),
+        found: misplaced
+            .as_ref()
+            .map(|(_, found)| long_path::display(found)),
+        suggested: misplaced.map(|(suggested, _)| long_path::display(&suggested)),
+    })
+}
+
+/// Create the `mods` folder of a game that has none and return its path
+/// 為沒有 `mods` 資料夾的遊戲建立該資料夾並回傳其路徑
+pub(crate) fn create_mods_dir(
+    game_path: &Path,
+    write_mode: WriteMode,
+) -> Result<PathBuf, AppError> {
+    let mods = game_path.join("mods");
+    write_mode.ensure_writable(&mods)?;
+    if !long_path::extend(game_path).is_dir() {
+        return Err(AppError::io(
+            "io.create_mods_dir",
+            Some(game_path),
+            "game folder not found",
+        ));
+    }
+    fs::create_dir_all(long_path::extend(&mods))
+        .map_err(|e| AppError::io("io.create_mods_dir", Some(&mods), e))?;
+    Ok(mods)
+}
+
+/// Full path of a log-relative mod path and the root it was found under: the first root where
+/// the file exists, or the first root when it exists nowhere; `roots` must not be empty
+/// log 中相對模組路徑的完整路徑及其所在根目錄：取第一個存在該檔案的根目錄，皆不存在時取第一個
+pub(crate) fn resolve<'a>(roots: &'a [PathBuf], rel_path: &str) -> (PathBuf, &'a Path) {
+    if let [only] = roots {
+        return (only.join(rel_path), only);
+    }
+    roots
+        .iter()
+        .map(|root| (root.join(rel_path), root.as_path()))
+        .find(|(path, _)| links::stat(path).0.is_some())
+        .unwrap_or_else(|| (roots[0].join(rel_path), &roots[0]))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::fs;
+
+    use super::mod_directories;
+    use crate::{long_path, test_support::SyntheticInstall};
+
+    #[test]
+    fn linked_mods_folder_reports_its_target() {
+        let install = SyntheticInstall::new();
+        let target = install.root().join("elsewhere");
+        fs::create_dir_all(&target).unwrap();
+        fs::remove_dir(install.root().join("mods")).unwrap();
+        std::os::unix::fs::symlink(&target, install.root().join("mods")).unwrap();
+
+        let directories = mod_directories(install.root());
+
+        assert_eq!(directories.len(), 1);
+        assert_eq!(
+            directories[0].link_target.as_deref(),
+            Some(long_path::display(&fs::canonicalize(&target).unwrap()).as_str())
+        );
+        assert!(directories[0].volume.is_some());
+        assert!(!directories[0].other_volume);
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    fs, iter,
+    path::{Path, PathBuf},
+};
+
+use crate::{long_path, parser::ModConflict};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Folders KKManager re-syncs on its own, relative to the game root. Matching is by
+/// prefix, so `mods/Sideloader Modpack` also covers `mods/Sideloader Modpack - Exclusive KK`.
+/// KKManager 會自動重新同步的資料夾（相對於遊戲根目錄）；以前綴比對，因此 `mods/Sideloader Modpack`
+/// 也涵蓋 `mods/Sideloader Modpack - Exclusive KK`
+pub(crate) const DEFAULT_MODPACK_PREFIXES: &[&str] = &["mods/Sideloader Modpack"];
+
+/// Absolute modpack folder prefixes of one or more games, normalized for comparison
+/// 一個或多個遊戲的模組包資料夾絕對路徑前綴，已正規化以便比對
+pub(crate) struct ModpackFolders {
+    prefixes: Vec<String>,
+}
+
+// ───────────────────────────────────────────────
+// Classification / 分類
+// ───────────────────────────────────────────────
+
+/// Lowercase with `/` separators and no trailing separator, so Windows spellings compare equal
+/// 轉為小寫、以 `/` 分隔且不含結尾分隔符，使 Windows 的不同寫法視為相同
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_end_matches('/').to_lowercase()
+}
+
+fn canonical(path: &Path) -> Option<String> {
+    fs::canonicalize(long_path::extend(path))
+        .ok()
+        .map(|path| normalize(&long_path::display(&path)))
+}
+
+/// Forms of the relative `prefix` under `game_root` that a path can arrive in: as given, under
+/// the canonical parent folder, and the canonical target of every existing folder the prefix
+/// covers, so a `mods` or modpack folder that is a junction still matches canonical targets
+/// 相對前綴 `prefix` 在 `game_root` 下可能出現的各種形式：原始路徑、正規化後的上層資料夾之下，
+/// 以及前綴涵蓋的每個現有資料夾正規化後的目標，使 `mods` 或模組包資料夾為 junction 時仍能比對正規化後的目標
+fn prefix_forms(game_root: &Path, prefix: &str) -> Vec<String> {
+    let prefix = normalize(prefix.trim_start_matches(['/', '\\']));
+    // An empty prefix would cover the whole game folder
+    // 空白前綴會涵蓋整個遊戲資料夾，因此排除
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+    let folder = game_root.join(&prefix);
+    let (parent, name) = match prefix.rsplit_once('/') {
+        Some((parent, name)) => (game_root.join(parent), name),
+        None => (game_root.to_path_buf(), prefix.as_str()),
+    };
+    let covered = fs::read_dir(long_path::extend(&parent))
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .to_lowercase()
+                .starts_with(name)
+        })
+        .map(|entry| parent.join(entry.file_name()))
+        .collect::<Vec<PathBuf>>();
+    iter::once(normalize(&long_path::display(&folder)))
+        .chain(canonical(&parent).map(|parent| format!("{}/{}", parent, name)))
+        .chain(covered.iter().filter_map(|folder| canonical(folder)))
+        .collect()
+}
+
+impl ModpackFolders {
+    /// Prefixes of every `(game_path, relative prefixes)` pair, in every form `prefix_forms`
+    /// lists, since delete targets arrive canonicalized
+    /// 收集每組 `(遊戲路徑, 相對前綴)` 的前綴，包含 `prefix_forms` 列出的所有形式，因為刪除目標已先正規化
+    pub fn for_games<S: AsRef<str>>(games: &[(S, Vec<String>)]) -> Self {
+        let prefixes = games
+            .iter()
+            .flat_map(|(game_path, relative)| {
+                let game_root = Path::new(game_path.as_ref());
+                relative
+                    .iter()
+                    .flat_map(move |prefix| prefix_forms(game_root, prefix))
+            })
+            .collect();
+        ModpackFolders { prefixes }
+    }
+
+    /// Whether `path` lies inside one of the modpack folders
+    /// 判斷 `path` 是否位於某個模組包資料夾內
+    pub fn contains(&self, path: &Path) -> bool {
+        let path = normalize(&long_path::display(path));
+        self.prefixes.iter().any(|prefix| path.starts_with(prefix))
+    }
+}
+
+/// Set `managed` on every loaded and skipped entry that lies in a modpack folder
+/// 將位於模組包資料夾內的 loaded 與 skipped 項目標記為 `managed`
+pub(crate) fn mark_managed(conflicts: &mut [ModConflict], folders: &ModpackFolders) {
+    for conflict in conflicts {
+        for entry in iter::once(&mut conflict.loaded).chain(&mut conflict.skipped) {
+            entry.managed = folders.contains(Path::new(&entry.path));
+        }
+    }
+}
+
+// Both tests create folder links, which Windows only allows with extra privileges
+// 兩個測試皆需建立資料夾連結，Windows 上需額外權限
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{fs, os::unix::fs::symlink};
+
+    use super::ModpackFolders;
+    use crate::test_support::SyntheticInstall;
+
+    fn folders(install: &SyntheticInstall) -> ModpackFolders {
+        ModpackFolders::for_games(&[(
+            install.game_path(),
+            vec!["mods/Sideloader Modpack".to_string()],
+        )])
+    }
+
+    #[test]
+    fn linked_mods_folder_keeps_modpack_targets_covered() {
+        let install = SyntheticInstall::new();
+        let target = install.root().join("elsewhere");
+        fs::create_dir_all(target.join("Sideloader Modpack - Exclusive KK")).unwrap();
+        fs::remove_dir(install.root().join("mods")).unwrap();
+        symlink(&target, install.root().join("mods")).unwrap();
+        let canonical = fs::canonicalize(&target).unwrap();
+
+        let folders = folders(&install);
+
+        assert!(folders.contains(&canonical.join("Sideloader Modpack - Exclusive KK/a.zipmod")));
+        assert!(folders.contains(&canonical.join("Sideloader Modpack/b.zipmod")));
+        assert!(!folders.contains(&canonical.join("Other/c.zipmod")));
+    }
+
+    #[test]
+    fn linked_modpack_folder_covers_its_target() {
+        let install = SyntheticInstall::new();
+        let target = install.root().join("pack store");
+        fs::create_dir_all(&target).unwrap();
+        symlink(&target, install.mod_path("Sideloader Modpack")).unwrap();
+        let canonical = fs::canonicalize(&target).unwrap();
+
+        let folders = folders(&install);
+
+        assert!(folders.contains(&canonical.join("a.zipmod")));
+        assert!(folders.contains(&install.mod_path("Sideloader Modpack/a.zipmod")));
+        assert!(!folders.contains(&install.mod_path("Other/a.zipmod")));
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use quick_xml::{events::Event, Reader};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{error::AppError, links, long_path, messages::Message};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Element names of a file entry in the XML layout
+/// XML 格式中代表檔案項目的元素名稱
+const FILE_TAGS: &[&str] = &["file", "item", "entry"];
+
+/// Attribute, element or key names holding an entry's path, its size, or the folder the
+/// entries below are relative to; compared case-insensitively
+/// 存放項目路徑、大小，或其下項目所在資料夾的屬性、元素或 key 名稱；比對時不分大小寫
+const PATH_KEYS: &[&str] = &["path", "relativepath", "filename"];
+const SIZE_KEYS: &[&str] = &["size", "length", "filesize"];
+const FOLDER_KEYS: &[&str] = &["folder", "directory", "clientpath"];
+
+/// One file the manifest expects, relative to the game root
+/// manifest 預期存在的單一檔案，相對於遊戲根目錄
+struct ExpectedFile {
+    path: String,
+    size: Option<u64>,
+}
+
+/// A file on disk that the manifest does not list
+/// manifest 未列出的磁碟檔案
+#[derive(Serialize)]
+pub(crate) struct ExtraFile {
+    path: String,
+    size: u64,
+}
+
+/// A listed file whose size on disk differs from the manifest
+/// 磁碟大小與 manifest 不符的已列出檔案
+#[derive(Serialize)]
+pub(crate) struct SizeMismatch {
+    path: String,
+    expected: u64,
+    actual: u64,
+}
+
+/// Result of comparing the modpack folders against a manifest. Paths are relative to the
+/// game root with `/` separators; `folders` are the top-level modpack folders compared.
+/// 模組包資料夾與 manifest 的比對結果；路徑相對於遊戲根目錄並以 `/` 分隔，`folders` 為實際比對的模組包資料夾
+#[derive(Serialize)]
+pub(crate) struct IntegrityReport {
+    format: &'static str,
+    folders: Vec<String>,
+    expected_files: usize,
+    matched: usize,
+    missing: Vec<String>,
+    extra: Vec<ExtraFile>,
+    size_mismatches: Vec<SizeMismatch>,
+}
+
+// ───────────────────────────────────────────────
+// Manifest Parsing / 解析 manifest
+// ───────────────────────────────────────────────
+
+fn is_key(name: &str, keys: &[&str]) -> bool {
+    keys.iter().any(|key| name.eq_ignore_ascii_case(key))
+}
+
+fn invalid(source: &Path, detail: impl ToString) -> AppError {
+    AppError::InvalidInput {
+        message: Message::new("input.modpack_manifest_invalid")
+            .with("path", long_path::display(source))
+            .with("detail", detail.to_string()),
+    }
+}
+
+/// Game-root-relative form of a manifest path. Entries name files relative to either the
+/// game root or the mods folder, so paths not starting with `mods/` get it prepended.
+/// Paths that would leave their folder are dropped.
+/// 將 manifest 路徑轉為相對於遊戲根目錄的形式；項目可能相對於遊戲根目錄或 mods 資料夾，
+/// 因此不以 `mods/` 開頭者會補上；會跳出資料夾的路徑則捨棄
+fn game_relative(folder: Option<&str>, path: &str) -> Option<String> {
+    let joined = match folder {
+        Some(folder) => format!("{}/{}", folder, path),
+        None => path.to_string(),
+    };
+    let parts = joined
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>();
+    if parts.len() < 2 || parts.iter().any(|part| *part == ".." || part.contains(':')) {
+        return None;
+    }
+    let relative = parts.join("/");
+    match parts[0].eq_ignore_ascii_case("mods") {
+        true => Some(relative),
+        false => Some(format!("mods/{}", relative)),
+    }
+}
+
+fn expected(folder: Option<&str>, path: &str, size: Option<u64>) -> Option<ExpectedFile> {
+    game_relative(folder, path).map(|path| ExpectedFile { path, size })
+}
+
+/// File entries of the older XML layout: `<File Path=".." Size=".."/>`, or the same with
+/// child elements, optionally inside any element carrying a `Folder` attribute
+/// 較舊的 XML 格式中的檔案項目：`<File Path=".." Size=".."/>` 或以子元素表示，可位於任何帶有 `Folder` 屬性的元素內
+fn parse_xml(content: &str, source: &Path) -> Result<Vec<ExpectedFile>, AppError> {
+    struct OpenFile {
+        depth: usize,
+        folder: Option<String>,
+        path: Option<String>,
+        size: Option<String>,
+    }
+
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+    let mut files = Vec::new();
+    let mut folders: Vec<(usize, String)> = Vec::new();
+    let mut open: Option<OpenFile> = None;
+    let mut child: Option<String> = None;
+    let mut depth = 0;
+
+    // Close the element at `depth`, finishing the file entry it may be
+    // 關閉位於 `depth` 的元素；若其為檔案項目則完成該項目
+    let mut close = |depth: usize, open: &mut Option<OpenFile>, folders: &mut Vec<_>| {
+        if open.as_ref().is_some_and(|file| file.depth == depth) {
+            let file = open.take().unwrap();
+            let size = file.size.and_then(|size| size.trim().parse().ok());
+            files.extend(
+                file.path
+                    .and_then(|path| expected(file.folder.as_deref(), &path, size)),
+            );
+        }
+        folders.retain(|(folder_depth, _): &(usize, String)| *folder_depth < depth);
+    };
+
+    loop {
+        let event = reader.read_event().map_err(|e| invalid(source, e))?;
+        let (element, is_empty) = match &event {
+            Event::Start(element) => (element, false),
+            Event::Empty(element) => (element, true),
+            Event::Text(text) => {
+                if let (Some(file), Some(name)) = (open.as_mut(), child.as_deref()) {
+                    let text = text.unescape().map_err(|e| invalid(source, e))?;
+                    if is_key(name, PATH_KEYS) {
+                        file.path = Some(text.to_string());
+                    } else if is_key(name, SIZE_KEYS) {
+                        file.size = Some(text.to_string());
+                    }
+                }
+                continue;
+            }
+            Event::End(_) => {
+                child = None;
+                close(depth, &mut open, &mut folders);
+                depth -= 1;
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        depth += 1;
+        let name = String::from_utf8_lossy(element.local_name().as_ref()).to_string();
+        let mut attributes = BTreeMap::new();
+        for attribute in element.attributes().flatten() {
+            let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).to_lowercase();
+            let value = attribute.unescape_value().map_err(|e| invalid(source, e))?;
+            attributes.insert(key, value.to_string());
+        }
+        let attribute = |keys: &[&str]| keys.iter().find_map(|key| attributes.get(*key).cloned());
+
+        if open.is_some() {
+            child = Some(name);
+        } else if is_key(&name, FILE_TAGS) {
+            open = Some(OpenFile {
+                depth,
+                folder: folders.last().map(|(_, folder)| folder.clone()),
+                path: attribute(PATH_KEYS),
+                size: attribute(SIZE_KEYS),
+            });
+        } else if let Some(folder) = attribute(FOLDER_KEYS) {
+            folders.push((depth, folder));
+        }
+
+        if is_empty {
+            child = None;
+            close(depth, &mut open, &mut folders);
+            depth -= 1;
+        }
+    }
+    Ok(files)
+}
+
+/// A string or number field of a JSON object, by any of `keys`
+/// 依 `keys` 任一名稱取得 JSON 物件中的字串或數字欄位
+fn json_field(object: &serde_json::Map<String, Value>, keys: &[&str]) -> Option<String> {
+    object
+        .iter()
+        .find(|(key, _)| is_key(key, keys))
+        .and_then(|(_, value)| match value {
+            Value::String(text) => Some(text.clone()),
+            Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        })
+}
+
+/// File entries of the newer JSON layout: any object with a `path`-like string is a file,
+/// and an object with a `folder`-like string makes the entries below it relative to it
+/// 較新的 JSON 格式中的檔案項目：任何帶有類似 `path` 字串的物件即為檔案，帶有類似 `folder` 字串的物件使其下項目相對於該資料夾
+fn collect_json(value: &Value, folder: Option<&str>, files: &mut Vec<ExpectedFile>) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                collect_json(item, folder, files);
+            }
+        }
+        Value::Object(object) => {
+            if let Some(path) = json_field(object, PATH_KEYS) {
+                let size = json_field(object, SIZE_KEYS).and_then(|size| size.parse().ok());
+                files.extend(expected(folder, &path, size));
+                return;
+            }
+            let own_folder = json_field(object, FOLDER_KEYS);
+            let folder = own_folder.as_deref().or(folder);
+            for item in object.values() {
+                collect_json(item, folder, files);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Read a manifest exported from KKManager, telling the layout apart by its first character
+/// 讀取由 KKManager 匯出的 manifest，依第一個字元判斷格式
+fn read_manifest(source: &Path) -> Result<(&'static str, Vec<ExpectedFile>), AppError> {
+    let content = fs::read_to_string(long_path::extend(source))
+        .map_err(|e| AppError::io("io.read_modpack_manifest", Some(source), e))?;
+    let content = content.trim_start_matches('\u{feff}').trim_start();
+    let (format, files) = match content.chars().next() {
+        Some('<') => ("xml", parse_xml(content, source)?),
+        Some('{' | '[') => {
+            let value: Value = serde_json::from_str(content).map_err(|e| invalid(source, e))?;
+            let mut files = Vec::new();
+            collect_json(&value, None, &mut files);
+            ("json", files)
+        }
+        _ => return Err(invalid(source, "neither XML nor JSON")),
+    };
+    if files.is_empty() {
+        return Err(invalid(source, "no file entries found"));
+    }
+    Ok((format, files))
+}
+
+// ───────────────────────────────────────────────
+// Comparison / 比對
+// ───────────────────────────────────────────────
+
+/// `mods/<folder>` part of a game-relative path
+/// 相對路徑中的 `mods/<資料夾>` 部分
+fn top_folder(path: &str) -> &str {
+    let end = path
+        .match_indices('/')
+        .nth(1)
+        .map(|(index, _)| index)
+        .unwrap_or(path.len());
+    &path[..end]
+}
+
+/// Compare the modpack folders named by the manifest at `source` against what is on disk:
+/// files missing, files present but not listed, and files whose size differs. Only folders
+/// the manifest has entries for are walked; paths compare case-insensitively as on Windows.
+/// 將 `source` manifest 所列的模組包資料夾與磁碟內容比對：缺少的檔案、未列出的多餘檔案與大小不符的檔案；
+/// 只走訪 manifest 有項目的資料夾，路徑比對如 Windows 般不分大小寫
+pub(crate) fn check_integrity(
+    game_path: &str,
+    source: &Path,
+    follow_links: bool,
+) -> Result<IntegrityReport, AppError> {
+    let (format, files) = read_manifest(source)?;
+    let game_root = PathBuf::from(long_path::display(Path::new(game_path)));
+
+    let mut expected_files: BTreeMap<String, ExpectedFile> = BTreeMap::new();
+    for file in files {
+        expected_files
+            .entry(file.path.to_lowercase())
+            .or_insert(file);
+    }
+    let mut folders: HashMap<String, String> = HashMap::new();
+    for file in expected_files.values() {
+        let folder = top_folder(&file.path);
+        folders
+            .entry(folder.to_lowercase())
+            .or_insert_with(|| folder.to_string());
+    }
+
+    let mut matched = 0;
+    let mut extra = Vec::new();
+    let mut size_mismatches = Vec::new();
+    let mut seen = BTreeSet::new();
+    for folder in folders.values() {
+        for file in links::walk_files(&game_root.join(folder), follow_links) {
+            let relative = file
+                .path
+                .strip_prefix(&game_root)
+                .unwrap_or(&file.path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let key = relative.to_lowercase();
+            let actual = file.metadata.len();
+            match expected_files.get(&key) {
+                None => extra.push(ExtraFile {
+                    path: relative,
+                    size: actual,
+                }),
+                Some(listed) => {
+                    seen.insert(key);
+                    match listed.size {
+                        Some(size) if size != actual => size_mismatches.push(SizeMismatch {
+                            path: relative,
+                            expected: size,
+                            actual,
+                        }),
+                        _ => matched += 1,
+                    }
+                }
+            }
+        }
+    }
+
+    extra.sort_by(|a, b| a.path.cmp(&b.path));
+    size_mismatches.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut folders = folders.into_values().collect::<Vec<_>>();
+    folders.sort();
+    Ok(IntegrityReport {
+        format,
+        folders,
+        expected_files: expected_files.len(),
+        matched,
+        missing: expected_files
+            .iter()
+            .filter(|(key, _)| !seen.contains(*key))
+            .map(|(_, file)| file.path.clone())
+            .collect(),
+        extra,
+        size_mismatches,
+    })
+}
@@ -0,0 +1,399 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    app_log,
+    archive_inspect::{self, ArchiveFormat, ZipProvenance},
+    asset_index,
+    error::AppError,
+    links,
+    manifest::ManifestData,
+    modpack::ModpackFolders,
+    operations::CancelToken,
+    ops,
+    parser::{build_mod_entry, ModConflict, ModEntry, METADATA_POOL},
+    progress::OnProgress,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// One mod archive in the mods folder with its manifest, if it has a readable one
+/// mods 資料夾中的單一模組壓縮檔及其 manifest（若可讀取）
+#[derive(Clone, Serialize)]
+pub(crate) struct ModFile {
+    #[serde(flatten)]
+    pub entry: ModEntry,
+    pub manifest: Option<ManifestData>,
+    /// A downloaded zip holding other zipmods, which Sideloader ignores until they are extracted
+    /// 內含其他 zipmod 的下載 zip；需先解壓縮，Sideloader 才會載入
+    pub wrapper_archive: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inner_zipmods: Vec<String>,
+}
+
+/// Two or more files declaring the same GUID, found without a log
+/// 不依賴 log 找出的、宣告相同 GUID 的兩個以上檔案
+#[derive(Clone, Serialize)]
+pub(crate) struct GuidGroup {
+    pub guid: String,
+    pub files: Vec<ModFile>,
+    /// Size of every copy but the largest, which is what removing the duplicates frees; copies
+    /// with other hardlinks are left out since deleting them frees nothing
+    /// 除最大檔案外其餘複本的大小，即移除重複檔可釋放的空間；仍有其他硬連結的複本刪除後不會釋放空間，故不計入
+    pub wasted_bytes: u64,
+    /// Members name different mods or authors, so this is likely two forks sharing a GUID
+    /// rather than duplicates; deleting either would lose content
+    /// 成員的模組名稱或作者不同，較可能是共用 GUID 的兩個分支而非重複檔；刪除任一方都會失去內容
+    pub fork_suspected: bool,
+    /// Distinct manifest names and authors as written, filled only when they differ
+    /// 原樣保留的相異 manifest 名稱與作者，僅在不同時填入
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub differing_names: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub differing_authors: Vec<String>,
+}
+
+/// One file re-read on request, for refreshing a single row after a file operation
+/// 依要求重新讀取的單一檔案，供檔案操作後更新單列資料
+#[derive(Serialize)]
+pub(crate) struct RefreshedEntry {
+    #[serde(flatten)]
+    pub entry: ModEntry,
+    /// False once the file has been deleted or moved away / 檔案已刪除或移走時為 false
+    pub exists: bool,
+    /// Renamed to `.disabled` so Sideloader skips it / 已改名為 `.disabled`，Sideloader 會略過
+    pub disabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<ManifestData>,
+    /// Compact zip comment and entry dates, when asked for
+    /// 要求時附上的精簡 zip 註解與 entry 日期
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<ZipProvenance>,
+}
+
+/// The files declaring one spelling of a case-colliding GUID
+/// 宣告大小寫衝突 GUID 其中一種寫法的檔案
+#[derive(Clone, Serialize)]
+pub(crate) struct GuidSpelling {
+    pub guid: String,
+    pub files: Vec<ModEntry>,
+}
+
+/// GUIDs that differ only by case. Sideloader compares GUIDs case-sensitively in some versions
+/// and not in others, so whether both load changes between game updates. This is a warning,
+/// not a resolvable conflict: the fix is usually for the author to republish one of them.
+/// 僅大小寫不同的 GUID；Sideloader 有些版本區分大小寫、有些不區分，因此是否都會載入會隨遊戲更新而改變；
+/// 這是警告而非可自動處理的衝突，通常應由作者重新發布其中一個
+#[derive(Clone, Serialize)]
+pub(crate) struct GuidCaseCollision {
+    /// The GUID in lowercase / 轉為小寫的 GUID
+    pub folded_guid: String,
+    /// Each spelling as written with its files, sorted by spelling
+    /// 各種原始寫法及其檔案，依寫法排序
+    pub spellings: Vec<GuidSpelling>,
+}
+
+// ───────────────────────────────────────────────
+// Offline Scanning / 離線掃描
+// ───────────────────────────────────────────────
+
+/// List every mod archive under `<game_path>/mods` with its manifest.
+/// Manifests come from the per-profile asset index, so unchanged files are not reopened.
+/// 列出 `<game_path>/mods` 下所有模組壓縮檔及其 manifest；manifest 取自 profile 的資源索引，未變動的檔案不會重新開啟
+pub(crate) fn scan_mods_folder(
+    index_path: &Path,
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<Vec<ModFile>, AppError> {
+    let archives = asset_index::indexed_manifests(
+        index_path,
+        game_path,
+        follow_links,
+        mod_extensions,
+        cancel,
+        on_progress,
+    )?;
+
+    // Progress covers the index refresh, which dominates; the stats below are quick
+    // 進度只涵蓋佔大部分時間的索引更新，之後的 stat 很快
+    let mut files = METADATA_POOL.install(|| {
+        archives
+            .into_par_iter()
+            .map(|archive| ModFile {
+                entry: build_mod_entry(Path::new(&archive.path), &archive.path, mod_extensions),
+                manifest: archive.manifest,
+                wrapper_archive: !archive.inner_zipmods.is_empty(),
+                inner_zipmods: archive.inner_zipmods,
+            })
+            .collect::<Vec<_>>()
+    });
+    files.sort_by(|a, b| a.entry.path.cmp(&b.entry.path));
+    Ok(files)
+}
+
+/// Distinct values of one manifest field across `files` when at least two differ after
+/// case-folding and whitespace normalization; files without the field are ignored
+/// `files` 中某個 manifest 欄位在忽略大小寫與空白差異後仍有兩種以上值時，回傳各相異值；沒有該欄位的檔案不列入
+fn differing_values(files: &[ModFile], field: fn(&ManifestData) -> Option<&str>) -> Vec<String> {
+    let mut seen: Vec<(String, String)> = Vec::new();
+    for value in files
+        .iter()
+        .filter_map(|file| file.manifest.as_ref().and_then(field))
+    {
+        let normalized = value
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        if !normalized.is_empty() && !seen.iter().any(|(key, _)| *key == normalized) {
+            seen.push((normalized, value.trim().to_string()));
+        }
+    }
+    if seen.len() < 2 {
+        return Vec::new();
+    }
+    seen.into_iter().map(|(_, value)| value).collect()
+}
+
+/// Re-read one file the way the scanners do, also when it no longer exists. With
+/// `with_manifest`, the manifest comes from `index_path` when its record is current and is
+/// read from the archive otherwise; an unreadable manifest is logged and left out. With
+/// `with_provenance`, a zip's comment and entry dates are attached in compact form.
+/// 以與掃描相同的方式重新讀取單一檔案，檔案已不存在時亦可；指定 `with_manifest` 時，若 `index_path` 的紀錄仍有效
+/// 則取用其 manifest，否則從壓縮檔讀取；無法讀取的 manifest 會記錄後略過；指定 `with_provenance` 時附上精簡的 zip 註解與 entry 日期
+pub(crate) fn refresh_entry(
+    path: &Path,
+    modpacks: &ModpackFolders,
+    index_path: Option<&Path>,
+    mod_extensions: &[String],
+    with_manifest: bool,
+    with_provenance: bool,
+) -> RefreshedEntry {
+    let mut entry = build_mod_entry(path, &path.to_string_lossy(), mod_extensions);
+    entry.managed = modpacks.contains(path);
+    let exists = links::stat(path).0.is_some();
+
+    let manifest = match with_manifest && exists {
+        false => None,
+        true => index_path
+            .and_then(|index_path| {
+                asset_index::cached_manifest(index_path, &entry.path, entry.size, entry.modified)
+            })
+            .unwrap_or_else(|| match archive_inspect::read_manifest(path) {
+                Ok(manifest) => Some(manifest),
+                Err(e) => {
+                    app_log::write(&format!("manifest of {} skipped: {}", entry.path, e));
+                    None
+                }
+            }),
+    };
+    let zip = matches!(
+        archive_inspect::detect_format(path),
+        None | Some(ArchiveFormat::Zip)
+    );
+    let provenance = match with_provenance && exists && zip {
+        false => None,
+        true => match archive_inspect::read_zip_provenance(path) {
+            Ok(provenance) => Some(provenance.compact()),
+            Err(e) => {
+                app_log::write(&format!("provenance of {} skipped: {}", entry.path, e));
+                None
+            }
+        },
+    };
+    RefreshedEntry {
+        exists,
+        disabled: ops::is_disabled(path),
+        manifest,
+        provenance,
+        entry,
+    }
+}
+
+/// Group files by manifest GUID, keeping only GUIDs declared by more than one file
+/// 依 manifest GUID 分組，只保留被多個檔案宣告的 GUID
+pub(crate) fn guid_groups(files: Vec<ModFile>) -> Vec<GuidGroup> {
+    let mut by_guid: HashMap<String, Vec<ModFile>> = HashMap::new();
+    for file in files {
+        if let Some(guid) = file.manifest.as_ref().map(|manifest| manifest.guid.clone()) {
+            by_guid.entry(guid).or_default().push(file);
+        }
+    }
+
+    let mut groups = by_guid
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|(guid, files)| {
+            // Other hardlinks keep a copy's data, so deleting it frees nothing
+            // 仍有其他硬連結時刪除該複本不會釋放空間
+            let largest = (0..files.len()).max_by_key(|&index| files[index].entry.size);
+            let wasted_bytes = files
+                .iter()
+                .enumerate()
+                .filter(|&(index, file)| Some(index) != largest && file.entry.frees_space())
+                .map(|(_, file)| file.entry.size)
+                .sum();
+            let differing_names = differing_values(&files, |manifest| manifest.name.as_deref());
+            let differing_authors = differing_values(&files, |manifest| manifest.author.as_deref());
+            GuidGroup {
+                guid,
+                files,
+                wasted_bytes,
+                fork_suspected: !differing_names.is_empty() || !differing_authors.is_empty(),
+                differing_names,
+                differing_authors,
+            }
+        })
+        .collect::<Vec<_>>();
+    groups.sort_by(|a, b| a.guid.cmp(&b.guid));
+    groups
+}
+
+/// Second pass over the GUIDs: group them case-folded and keep the groups that hold more than one
+/// spelling. Files sharing the exact same GUID are `guid_groups`' business and stay one spelling.
+/// GUID 的第二輪分組：忽略大小寫分組，只保留含多種寫法的群組；GUID 完全相同的檔案屬於 `guid_groups`，算作同一種寫法
+pub(crate) fn guid_case_collisions(files: &[ModFile]) -> Vec<GuidCaseCollision> {
+    let mut by_folded: BTreeMap<String, BTreeMap<&str, Vec<ModEntry>>> = BTreeMap::new();
+    for file in files {
+        if let Some(manifest) = &file.manifest {
+            by_folded
+                .entry(manifest.guid.to_lowercase())
+                .or_default()
+                .entry(&manifest.guid)
+                .or_default()
+                .push(file.entry.clone());
+        }
+    }
+
+    by_folded
+        .into_iter()
+        .filter(|(_, spellings)| spellings.len() > 1)
+        .map(|(folded_guid, spellings)| GuidCaseCollision {
+            folded_guid,
+            spellings: spellings
+                .into_iter()
+                .map(|(guid, files)| GuidSpelling {
+                    guid: guid.to_string(),
+                    files,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+// ───────────────────────────────────────────────
+// Load Prediction / 載入預測
+// ───────────────────────────────────────────────
+
+/// Which file of a GUID group Sideloader is expected to load on the next launch.
+/// The conflict has the same shape as a log-derived one so the conflict view can show both.
+/// 預測下次啟動時 Sideloader 會載入 GUID 群組中的哪個檔案；衝突格式與 log 解析的相同，衝突畫面可共用
+#[derive(Serialize)]
+pub(crate) struct Prediction {
+    pub guid: String,
+    #[serde(flatten)]
+    pub conflict: ModConflict,
+    /// Always true; tells predicted conflicts apart from log-derived ones
+    /// 固定為 true，用以區分預測與 log 解析出的衝突
+    pub predicted: bool,
+    /// False when the pick hinged on a missing or non-numeric version, or on a version
+    /// tie broken by modification time
+    /// 若判斷依賴缺少或非數字的版本，或版本相同而以修改時間決定，則為 false
+    pub confident: bool,
+    /// Copied from the group; a suspected fork's skipped file is not a safe deletion
+    /// 取自所屬群組；疑似分支時被跳過的檔案不宜刪除
+    pub fork_suspected: bool,
+}
+
+/// Numeric parts of a manifest version such as `v1.2.10`, or `None` when a part is not a number
+/// manifest 版本（例如 `v1.2.10`）的數字部分；任一部分非數字時為 `None`
+fn version_parts(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+pub(crate) fn manifest_version(file: &ModFile) -> Option<&str> {
+    file.manifest
+        .as_ref()
+        .and_then(|manifest| manifest.version.as_deref())
+        .map(str::trim)
+        .filter(|version| !version.is_empty())
+}
+
+/// Compare two manifest versions the way Sideloader does: dot-separated numbers part by part,
+/// missing parts counting as zero, and a missing version lowest. Returns whether the result
+/// is trustworthy; non-numeric versions fall back to plain string order.
+/// 依 Sideloader 的方式比較 manifest 版本：逐段比較以點分隔的數字，缺少的段視為 0，沒有版本者最小；
+/// 另回傳結果是否可信，非數字版本改以字串順序比較
+pub(crate) fn compare_versions(a: Option<&str>, b: Option<&str>) -> (Ordering, bool) {
+    let (a, b) = match (a, b) {
+        (Some(a), Some(b)) => (a, b),
+        (a, b) => return (a.is_some().cmp(&b.is_some()), false),
+    };
+    match (version_parts(a), version_parts(b)) {
+        (Some(a), Some(b)) => {
+            let len = a.len().max(b.len());
+            let part = |parts: &[u64], i| parts.get(i).copied().unwrap_or(0);
+            let ordering = (0..len)
+                .map(|i| part(&a, i).cmp(&part(&b, i)))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal);
+            (ordering, true)
+        }
+        _ => (a.cmp(b), false),
+    }
+}
+
+/// Sort files of one GUID into Sideloader's order: highest manifest version first, equal
+/// versions most recently modified first. The first file is the one that loads.
+/// 將同一 GUID 的檔案依 Sideloader 的順序排列：manifest 版本高者在前，版本相同時最近修改者在前；第一個即會被載入者
+pub(crate) fn sort_by_load_order(files: &mut [ModFile]) {
+    files.sort_by(|a, b| {
+        compare_versions(manifest_version(b), manifest_version(a))
+            .0
+            .then_with(|| b.entry.modified.cmp(&a.entry.modified))
+    });
+}
+
+/// Predict the loaded file of every GUID group: highest manifest version wins, and equal
+/// versions go to the most recently modified file, matching Sideloader's ordering.
+/// 預測每個 GUID 群組中會被載入的檔案：manifest 版本最高者優先，版本相同時取最近修改的檔案，與 Sideloader 的排序一致
+pub(crate) fn predict_loaded(guid_groups: Vec<GuidGroup>) -> Vec<Prediction> {
+    guid_groups
+        .into_iter()
+        .filter_map(|group| {
+            let mut files = group.files;
+            sort_by_load_order(&mut files);
+
+            let winner = manifest_version(files.first()?);
+            let confident = files.iter().skip(1).all(|file| {
+                let (ordering, comparable) = compare_versions(winner, manifest_version(file));
+                comparable && ordering.is_gt()
+            });
+
+            let mut entries = files.into_iter().map(|file| file.entry);
+            Some(Prediction {
+                guid: group.guid,
+                conflict: ModConflict::new(entries.next()?, entries.collect()),
+                predicted: true,
+                confident,
+                fork_suspected: group.fork_suspected,
+            })
+        })
+        .collect()
+}
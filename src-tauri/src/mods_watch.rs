@@ -0,0 +1,263 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use notify::{
+    event::{EventKind, ModifyKind},
+    Event, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use serde::Serialize;
+
+use crate::{app_log, error::AppError, long_path, mod_roots};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Quiet period after the last change before a batch is delivered
+/// 最後一次變動後，等待多久沒有新變動才送出整批
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(750);
+
+/// Longest a batch is held back while changes keep arriving, e.g. during a long copy
+/// 變動持續發生時（例如長時間複製）整批最多延後送出的時間
+const MAX_BATCH_WAIT: Duration = Duration::from_secs(5);
+
+/// Kind of filesystem change reported to the frontend
+/// 回報給前端的檔案變動類型
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ModChangeKind {
+    Created,
+    Removed,
+    Renamed,
+}
+
+/// One changed path, relative to the game directory when possible
+/// 單一變動路徑；可能時以相對於遊戲資料夾的路徑表示
+#[derive(Serialize, Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct ModChange {
+    path: String,
+    kind: ModChangeKind,
+}
+
+/// A running watch; dropping it stops the watcher and its debounce thread
+/// 進行中的監看；drop 時會停止監看與去抖動執行緒
+pub(crate) struct ModsWatch {
+    pub game_path: String,
+    _watcher: RecommendedWatcher,
+}
+
+// ───────────────────────────────────────────────
+// Watching / 監看
+// ───────────────────────────────────────────────
+
+fn change_kind(kind: &EventKind) -> Option<ModChangeKind> {
+    match kind {
+        EventKind::Create(_) => Some(ModChangeKind::Created),
+        EventKind::Remove(_) => Some(ModChangeKind::Removed),
+        EventKind::Modify(ModifyKind::Name(_)) => Some(ModChangeKind::Renamed),
+        _ => None,
+    }
+}
+
+fn relative_display(path: &Path, game_root: &Path) -> String {
+    let display = PathBuf::from(long_path::display(path));
+    display
+        .strip_prefix(game_root)
+        .map(|rel| rel.to_string_lossy().to_string())
+        .unwrap_or_else(|_| display.to_string_lossy().to_string())
+}
+
+/// Every folder Sideloader loads mods from, then each of `extra_dirs` not already among them
+/// Sideloader 載入模組的所有資料夾，再加上尚未包含在內的 `extra_dirs`
+fn watch_roots(game_root: &Path, extra_dirs: &[String]) -> Vec<PathBuf> {
+    let mut roots = mod_roots::mod_roots(game_root);
+    for dir in extra_dirs.iter().map(PathBuf::from) {
+        if !roots.contains(&dir) {
+            roots.push(dir);
+        }
+    }
+    roots
+}
+
+/// De-duplicated changes from `first` on, collected until `window` passes without a new one or
+/// `max_wait` passes in all; `None` once the watcher is dropped
+/// 從 `first` 起收集去重後的變動，直到 `window` 內沒有新變動或總計經過 `max_wait` 為止；watcher 被 drop 後回傳 `None`
+fn collect_batch(
+    first: Event,
+    rx: &Receiver<Event>,
+    game_root: &Path,
+    window: Duration,
+    max_wait: Duration,
+) -> Option<Vec<ModChange>> {
+    let deadline = Instant::now() + max_wait;
+    let mut seen = HashSet::new();
+    let mut batch = Vec::new();
+    let mut push = |event: Event| {
+        let Some(kind) = change_kind(&event.kind) else {
+            return;
+        };
+        for path in &event.paths {
+            let change = ModChange {
+                path: relative_display(path, game_root),
+                kind,
+            };
+            if seen.insert(change.clone()) {
+                batch.push(change);
+            }
+        }
+    };
+    push(first);
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Some(batch);
+        }
+        match rx.recv_timeout(window.min(left)) {
+            Ok(event) => push(event),
+            Err(RecvTimeoutError::Timeout) => return Some(batch),
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+}
+
+/// Watch every mod root of `game_path` plus `extra_dirs` recursively and call `on_batch` with the
+/// de-duplicated changes once no new change arrives for `DEBOUNCE_WINDOW`, or after
+/// `MAX_BATCH_WAIT` while changes keep coming.
+/// Watcher errors are written to the app log and watching continues.
+/// 遞迴監看 `game_path` 的所有模組根目錄與 `extra_dirs`，在 `DEBOUNCE_WINDOW` 內沒有新變動後以去重後的清單呼叫 `on_batch`；
+/// 變動持續發生時最遲於 `MAX_BATCH_WAIT` 後送出。監看錯誤會寫入 app log 並持續監看
+pub(crate) fn start(
+    game_path: &str,
+    extra_dirs: &[String],
+    on_batch: impl Fn(Vec<ModChange>) + Send + 'static,
+) -> Result<ModsWatch, AppError> {
+    let game_root = PathBuf::from(game_path);
+    let (tx, rx) = mpsc::channel::<Event>();
+
+    let mut watcher =
+        notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+            Ok(event) => {
+                let _ = tx.send(event);
+            }
+            Err(e) => app_log::write(&format!("mods watch error (continuing): {}", e)),
+        })
+        .map_err(|e| AppError::io("io.start_mods_watch", Some(&game_root), e))?;
+
+    let roots = watch_roots(&game_root, extra_dirs);
+    for root in &roots {
+        watcher
+            .watch(&long_path::extend(root), RecursiveMode::Recursive)
+            .map_err(|e| AppError::io("io.start_mods_watch", Some(root), e))?;
+    }
+
+    let batch_root = game_root.clone();
+    thread::Builder::new()
+        .name("mods-watch".to_string())
+        .spawn(move || {
+            // recv() fails once the watcher (and with it the sender) is dropped
+            // watcher 被 drop 後 sender 隨之釋放，recv() 失敗即結束
+            while let Ok(first) = rx.recv() {
+                let Some(batch) =
+                    collect_batch(first, &rx, &batch_root, DEBOUNCE_WINDOW, MAX_BATCH_WAIT)
+                else {
+                    return;
+                };
+                if !batch.is_empty() {
+                    on_batch(batch);
+                }
+            }
+        })
+        .map_err(|e| AppError::io("io.start_mods_watch", Some(&game_root), e))?;
+
+    Ok(ModsWatch {
+        game_path: game_path.to_string(),
+        _watcher: watcher,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::Path,
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use notify::{
+        event::{CreateKind, EventKind},
+        Event,
+    };
+
+    use super::{collect_batch, watch_roots, ModChangeKind};
+    use crate::test_support::SyntheticInstall;
+
+    fn created(path: &str) -> Event {
+        Event::new(EventKind::Create(CreateKind::File)).add_path(Path::new(path).to_path_buf())
+    }
+
+    #[test]
+    fn steady_changes_are_delivered_after_the_max_wait() {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for _ in 0..200 {
+                if tx.send(created("C:/Game/mods/a.zipmod")).is_err() {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        });
+        let started = Instant::now();
+
+        let batch = collect_batch(
+            created("C:/Game/mods/a.zipmod"),
+            &rx,
+            Path::new("C:/Game"),
+            Duration::from_millis(200),
+            Duration::from_millis(300),
+        )
+        .unwrap();
+
+        assert!(started.elapsed() < Duration::from_millis(1500));
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].kind, ModChangeKind::Created);
+    }
+
+    #[test]
+    fn a_dropped_watcher_ends_the_batch() {
+        let (tx, rx) = mpsc::channel();
+        drop(tx);
+
+        let batch = collect_batch(
+            created("C:/Game/mods/a.zipmod"),
+            &rx,
+            Path::new("C:/Game"),
+            Duration::from_secs(1),
+            Duration::from_secs(5),
+        );
+
+        assert!(batch.is_none());
+    }
+
+    #[test]
+    fn sideloader_additional_directory_is_watched_once() {
+        let install = SyntheticInstall::new();
+        let additional = install.set_additional_mods_dir("more mods");
+        let extra = install.root().join("downloads");
+
+        let roots = watch_roots(
+            install.root(),
+            &[
+                additional.to_string_lossy().to_string(),
+                extra.to_string_lossy().to_string(),
+            ],
+        );
+
+        assert_eq!(roots, vec![install.root().join("mods"), additional, extra]);
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use icu_normalizer::ComposingNormalizerBorrowed;
+use serde::Serialize;
+
+use crate::long_path;
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Folders under the game root whose names are checked
+/// 遊戲根目錄下要檢查名稱的資料夾
+const CHECKED_SUBDIRS: &[&[&str]] = &[&["mods"], &["BepInEx", "plugins"]];
+
+/// Characters Windows refuses in file names, besides control characters
+/// 除控制字元外，Windows 不允許出現在檔名中的字元
+const INVALID_ON_WINDOWS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves whatever the extension
+/// 不論副檔名為何都被 Windows 保留的裝置名稱
+const RESERVED_STEMS: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// What is wrong with a name
+/// 名稱的問題類型
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AnomalyKind {
+    /// Another name in the same folder differs only by case (or by whitespace or normalization
+    /// form on top of that), which Windows treats as the same file
+    /// 同一資料夾中另有名稱僅大小寫不同（或另外僅空白、正規化形式不同），Windows 視為同一檔案
+    CaseCollision,
+    /// Leading or trailing whitespace / 開頭或結尾有空白
+    SurroundingWhitespace,
+    /// Reserved characters, a trailing dot or a reserved device name / 含保留字元、結尾為句點或為保留裝置名稱
+    InvalidOnWindows,
+    /// Not in Unicode NFC form, e.g. decomposed kana from a macOS copy / 非 Unicode NFC 形式，例如自 macOS 複製的分解假名
+    NotNfc,
+}
+
+/// A file or folder name that Windows, Sideloader or the dedupe logic may trip over
+/// 可能讓 Windows、Sideloader 或去重邏輯出錯的檔案或資料夾名稱
+#[derive(Serialize)]
+pub(crate) struct NameAnomaly {
+    pub path: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub kinds: Vec<AnomalyKind>,
+    /// Other names in the same folder this one collides with
+    /// 同一資料夾中與此名稱衝突的其他名稱
+    pub collides_with: Vec<String>,
+    /// Free name in the same folder to pass to `rename_mod`; None when this name can stay,
+    /// e.g. the copy of a collision that is already clean
+    /// 同一資料夾中可用的新名稱，可直接傳給 `rename_mod`；名稱可保留時為 None（例如衝突中本身已正常的那一份）
+    pub suggested_name: Option<String>,
+}
+
+// ───────────────────────────────────────────────
+// Normalization / 正規化
+// ───────────────────────────────────────────────
+
+fn nfc(name: &str) -> String {
+    ComposingNormalizerBorrowed::new_nfc()
+        .normalize(name)
+        .into_owned()
+}
+
+fn is_reserved(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    RESERVED_STEMS
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Whether Windows refuses `name` or silently changes it
+/// Windows 是否會拒絕或自動更改 `name`
+pub(crate) fn invalid_on_windows(name: &str) -> bool {
+    name.chars()
+        .any(|c| c.is_control() || INVALID_ON_WINDOWS.contains(&c))
+        || name.ends_with('.')
+        || is_reserved(name)
+}
+
+/// NFC form with surrounding whitespace and trailing dots removed and Windows-invalid
+/// characters replaced by `_`; reserved device names get a `_` prefix
+/// 轉為 NFC、移除前後空白與結尾句點，並將 Windows 不允許的字元換成 `_`；保留裝置名稱前加上 `_`
+pub(crate) fn clean_name(name: &str) -> String {
+    let replaced = nfc(name)
+        .chars()
+        .map(
+            |c| match c.is_control() || INVALID_ON_WINDOWS.contains(&c) {
+                true => '_',
+                false => c,
+            },
+        )
+        .collect::<String>();
+    let trimmed = replaced.trim().trim_end_matches(['.', ' ']).trim();
+    match trimmed {
+        "" => "_".to_string(),
+        name if is_reserved(name) => format!("_{}", name),
+        name => name.to_string(),
+    }
+}
+
+/// Key under which Windows would see two names as the same file
+/// Windows 會視為同一檔案的名稱比對 key
+fn collision_key(name: &str) -> String {
+    clean_name(name).to_lowercase()
+}
+
+/// `name` itself if its lowercase form is not in `taken`, otherwise the first free
+/// `stem (2).ext`, `stem (3).ext`, ...; the result is added to `taken`
+/// 若 `name` 的小寫形式不在 `taken` 中則直接使用，否則依序嘗試 `stem (2).ext`、`stem (3).ext`...；結果會加入 `taken`
+fn free_name(name: &str, is_dir: bool, taken: &mut HashSet<String>) -> String {
+    let (stem, extension) = match name.rfind('.') {
+        Some(dot) if dot > 0 && !is_dir => name.split_at(dot),
+        _ => (name, ""),
+    };
+    let chosen = std::iter::once(name.to_string())
+        .chain((2..).map(|n| format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !taken.contains(&candidate.to_lowercase()))
+        .expect("unbounded candidate range");
+    taken.insert(chosen.to_lowercase());
+    chosen
+}
+
+// ───────────────────────────────────────────────
+// Scanning / 掃描
+// ───────────────────────────────────────────────
+
+struct Entry {
+    name: String,
+    is_dir: bool,
+}
+
+/// Anomalies among the entries of one folder
+/// 單一資料夾內各項目的名稱問題
+fn check_folder(dir: &Path, entries: &[Entry], anomalies: &mut Vec<NameAnomaly>) {
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        groups
+            .entry(collision_key(&entry.name))
+            .or_default()
+            .push(index);
+    }
+
+    let mut taken = entries
+        .iter()
+        .map(|entry| entry.name.to_lowercase())
+        .collect::<HashSet<_>>();
+    for members in groups.values() {
+        // A collision keeps the member that is already clean, or else the first one
+        // 衝突中保留本身已正常的成員，沒有時保留第一個
+        let keeper = members
+            .iter()
+            .copied()
+            .find(|&index| clean_name(&entries[index].name) == entries[index].name)
+            .unwrap_or(members[0]);
+        let others = members.iter().copied().filter(|&index| index != keeper);
+        for index in std::iter::once(keeper).chain(others) {
+            let entry = &entries[index];
+            let mut kinds = Vec::new();
+            if members.len() > 1 {
+                kinds.push(AnomalyKind::CaseCollision);
+            }
+            if entry.name.trim() != entry.name {
+                kinds.push(AnomalyKind::SurroundingWhitespace);
+            }
+            if invalid_on_windows(&entry.name) {
+                kinds.push(AnomalyKind::InvalidOnWindows);
+            }
+            if !ComposingNormalizerBorrowed::new_nfc().is_normalized(&entry.name) {
+                kinds.push(AnomalyKind::NotNfc);
+            }
+            if kinds.is_empty() {
+                continue;
+            }
+
+            let clean = clean_name(&entry.name);
+            let suggested_name = match (index == keeper, clean == entry.name) {
+                (true, true) => None,
+                // The keeper may take the clean name even though a colliding member holds
+                // its lowercase form; that member is renamed away
+                // 保留者即使與衝突成員的小寫形式相同仍可使用正常名稱，該成員會被改為其他名稱
+                (true, false) => {
+                    taken.insert(clean.to_lowercase());
+                    Some(clean)
+                }
+                (false, _) => Some(free_name(&clean, entry.is_dir, &mut taken)),
+            };
+            let collides_with = members
+                .iter()
+                .filter(|&&other| other != index)
+                .map(|&other| entries[other].name.clone())
+                .collect();
+            anomalies.push(NameAnomaly {
+                path: long_path::display(&dir.join(&entry.name)),
+                name: entry.name.clone(),
+                is_dir: entry.is_dir,
+                kinds,
+                collides_with,
+                suggested_name,
+            });
+        }
+    }
+}
+
+/// Check every file and folder name under the game's `mods` and `BepInEx/plugins` for case-only
+/// collisions, surrounding whitespace, Windows-invalid names and non-NFC forms. Links are not
+/// followed; names that are not valid Unicode are skipped.
+/// 檢查遊戲 `mods` 與 `BepInEx/plugins` 底下所有檔案與資料夾名稱：僅大小寫不同的衝突、前後空白、
+/// Windows 不允許的名稱與非 NFC 形式；不跟隨連結，非有效 Unicode 的名稱略過
+pub(crate) fn find_name_anomalies(game_path: &str) -> Vec<NameAnomaly> {
+    let mut anomalies = Vec::new();
+    let mut pending = CHECKED_SUBDIRS
+        .iter()
+        .map(|parts| {
+            parts
+                .iter()
+                .fold(PathBuf::from(game_path), |dir, part| dir.join(part))
+        })
+        .collect::<Vec<_>>();
+    while let Some(dir) = pending.pop() {
+        let Ok(listing) = fs::read_dir(long_path::extend(&dir)) else {
+            continue;
+        };
+        let entries = listing
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+                Some(Entry { name, is_dir })
+            })
+            .collect::<Vec<_>>();
+        pending.extend(
+            entries
+                .iter()
+                .filter(|entry| entry.is_dir)
+                .map(|entry| dir.join(&entry.name)),
+        );
+        check_folder(&dir, &entries, &mut anomalies);
+    }
+    anomalies.sort_by(|a, b| a.path.cmp(&b.path));
+    anomalies
+}
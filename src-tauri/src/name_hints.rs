@@ -0,0 +1,196 @@
+use std::path::Path;
+
+use crate::{asset_index, patterns};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Game tags that often precede the author bracket, e.g. `[KK][Author] Name.zipmod`
+/// 常出現在作者括號前的遊戲標籤，例如 `[KK][Author] Name.zipmod`
+const GAME_TAGS: &[&str] = &[
+    "kk", "kks", "kkp", "koikatsu", "koikatu", "ec", "ai", "hs2", "kk&kks", "kks&kk", "kk-kks",
+];
+
+/// Characters separating the words of a mod filename
+/// 模組檔名中分隔字詞的字元
+const SEPARATORS: &[char] = &[' ', '_'];
+
+/// Author, title and version guessed from the `[Author] Title v1.2.zipmod` convention.
+/// A field is `None` whenever the name does not say so unambiguously.
+/// 依 `[Author] Title v1.2.zipmod` 慣例從檔名推測的作者、名稱與版本；無法明確判斷的欄位為 `None`
+#[derive(Default, PartialEq, Debug)]
+pub(crate) struct NameHints {
+    pub author: Option<String>,
+    pub title: Option<String>,
+    pub version: Option<String>,
+}
+
+// ───────────────────────────────────────────────
+// Parsing / 解析
+// ───────────────────────────────────────────────
+
+/// Strip one of `mod_extensions` only, so `Mod v1.2` keeps its version
+/// 只移除 `mod_extensions` 中的模組壓縮檔副檔名，避免 `Mod v1.2` 的版本被當成副檔名
+fn strip_mod_extension<'a>(file_name: &'a str, mod_extensions: &[String]) -> &'a str {
+    if !asset_index::is_mod_archive(Path::new(file_name), mod_extensions) {
+        return file_name;
+    }
+    file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem)
+}
+
+/// Split leading `[...]` groups off the name
+/// 拆出檔名開頭的 `[...]` 群組
+fn leading_brackets(mut rest: &str) -> (Vec<&str>, &str) {
+    let mut groups = Vec::new();
+    while let Some(inner) = rest.strip_prefix('[') {
+        let Some(end) = inner.find(']') else {
+            break;
+        };
+        groups.push(inner[..end].trim());
+        rest = inner[end + 1..].trim_start_matches(SEPARATORS);
+    }
+    (groups, rest)
+}
+
+/// The last word if it is a version token such as `v2`, `1.0.3` or `(v1.2b)`.
+/// A bare integer is not a version, since `Outfit 2` is a title.
+/// 若最後一個字詞為版本（例如 `v2`、`1.0.3`、`(v1.2b)`）則回傳；單純的整數不算版本，因為 `Outfit 2` 是名稱
+fn trailing_version(rest: &str) -> Option<(&str, &str)> {
+    let (head, word) = match rest.rfind(SEPARATORS) {
+        Some(split) => (&rest[..split], &rest[split + 1..]),
+        None => ("", rest),
+    };
+    let token = match (word.chars().next(), word.chars().last()) {
+        (Some('('), Some(')')) | (Some('['), Some(']')) => &word[1..word.len() - 1],
+        _ => word,
+    };
+    let is_version = patterns::patterns().name_version.is_match(token)
+        && (token.starts_with(['v', 'V']) || token.contains('.'));
+    is_version.then_some((head, token))
+}
+
+/// Guess author, title and version from a mod filename without opening the archive; an
+/// extension is only dropped when it is one of `mod_extensions`
+/// 不開啟壓縮檔，直接從模組檔名推測作者、名稱與版本；副檔名為 `mod_extensions` 之一時才會移除
+pub(crate) fn parse(file_name: &str, mod_extensions: &[String]) -> NameHints {
+    let stem = strip_mod_extension(file_name, mod_extensions).trim();
+    let (groups, rest) = leading_brackets(stem);
+
+    let mut authors = groups
+        .into_iter()
+        .filter(|group| !group.is_empty() && !GAME_TAGS.contains(&group.to_lowercase().as_str()));
+    // Two non-tag brackets could be either author or tag, so name neither
+    // 出現兩個以上非遊戲標籤的括號時無法判斷何者為作者，因此都不採用
+    let author = match (authors.next(), authors.next()) {
+        (Some(author), None) => Some(author.to_string()),
+        _ => None,
+    };
+
+    let rest = rest.trim_matches(SEPARATORS);
+    let (title, version) = match trailing_version(rest) {
+        Some((head, version)) => (head, Some(version.to_string())),
+        None => (rest, None),
+    };
+    let title = title.trim_matches(|c: char| SEPARATORS.contains(&c) || c == '-');
+    if title.is_empty() {
+        // A name that is only a version (or nothing) says nothing reliable
+        // 只有版本（或空白）的檔名沒有可靠資訊
+        return NameHints {
+            author,
+            ..NameHints::default()
+        };
+    }
+
+    NameHints {
+        author,
+        title: Some(title.to_string()),
+        version,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, NameHints};
+    use crate::profiles::default_mod_extensions;
+
+    #[test]
+    fn real_filenames_give_their_hints() {
+        // Filename, then the author, title and version it should give; `""` wherever the name
+        // is ambiguous
+        // 檔名，以及應推測出的作者、名稱與版本；檔名語意不明確時為 `""`
+        let cases = [
+            ("[Author] Hair v2.zipmod", ["Author", "Hair", "v2"]),
+            ("[Author] Hair V3.zipmod", ["Author", "Hair", "V3"]),
+            ("[Author] Hair v2.zip", ["Author", "Hair", "v2"]),
+            ("[Author] Hair v2.ZIPMOD", ["Author", "Hair", "v2"]),
+            (
+                "[KK][Author] Outfit 1.0.3.zipmod",
+                ["Author", "Outfit", "1.0.3"],
+            ),
+            ("[kk&kks][Author] Hair.zipmod", ["Author", "Hair", ""]),
+            ("[AI][HS2][Author] Hair.zipmod", ["Author", "Hair", ""]),
+            (
+                "[KKS][Mod Maker] Long Coat_v3.zipmod",
+                ["Mod Maker", "Long Coat", "v3"],
+            ),
+            (
+                "[Author Name] 衣装 v1.0.zipmod",
+                ["Author Name", "衣装", "v1.0"],
+            ),
+            ("[Author]Hair.zipmod", ["Author", "Hair", ""]),
+            (
+                "[Author] Skirt (v1.2b).zipmod",
+                ["Author", "Skirt", "v1.2b"],
+            ),
+            ("[Author] Hair [v2].zipmod", ["Author", "Hair", "v2"]),
+            ("[Author] Hair - v2.zipmod", ["Author", "Hair", "v2"]),
+            ("[Author] Outfit 2.0.zipmod", ["Author", "Outfit", "2.0"]),
+            (
+                "[Author] Hair v1.2.3.4.zipmod",
+                ["Author", "Hair", "v1.2.3.4"],
+            ),
+            ("[Author] Mod v1.2", ["Author", "Mod", "v1.2"]),
+            ("Hair_v2.zipmod", ["", "Hair", "v2"]),
+            ("NoBrackets.zipmod", ["", "NoBrackets", ""]),
+            ("[KK] Hair.zipmod", ["", "Hair", ""]),
+            ("[] Hair.zipmod", ["", "Hair", ""]),
+            // A bare number is part of the title, not a version
+            // 單純的數字屬於名稱而非版本
+            ("[Author] Outfit 2.zipmod", ["Author", "Outfit 2", ""]),
+            ("[Author] Hair 2024.zipmod", ["Author", "Hair 2024", ""]),
+            ("[Author] Hair (2).zipmod", ["Author", "Hair (2)", ""]),
+            ("[Author] 2.zipmod", ["Author", "2", ""]),
+            // Only the last word can be a version
+            // 只有最後一個字詞可能是版本
+            (
+                "[Author] Hair v2 final.zipmod",
+                ["Author", "Hair v2 final", ""],
+            ),
+            ("[Author] Hair_v1.2_b.zipmod", ["Author", "Hair_v1.2_b", ""]),
+            // Two brackets that are not game tags: either could be the author
+            // 兩個非遊戲標籤的括號：任一個都可能是作者
+            ("[A][B] Name.zipmod", ["", "Name", ""]),
+            ("[Author][Hair] v2.zipmod", ["", "", ""]),
+            // Nothing but a version or an author says nothing about the title
+            // 只有版本或作者時無法得知名稱
+            ("v1.2.zipmod", ["", "", ""]),
+            ("[Author] v2.zipmod", ["Author", "", ""]),
+            ("[Author].zipmod", ["Author", "", ""]),
+            ("[KK].zipmod", ["", "", ""]),
+        ];
+
+        let mod_extensions = default_mod_extensions();
+        let hint = |expected: &str| (!expected.is_empty()).then(|| expected.to_string());
+        for (file_name, [author, title, version]) in cases {
+            let expected = NameHints {
+                author: hint(author),
+                title: hint(title),
+                version: hint(version),
+            };
+            assert_eq!(parse(file_name, &mod_extensions), expected, "{}", file_name);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use zip::ZipArchive;
+
+use crate::{
+    disk_space,
+    error::AppError,
+    long_path,
+    operations::CancelToken,
+    parser::{build_mod_entry, ModEntry},
+    path_guard::WriteMode,
+    progress::{OnProgress, Progress},
+    quarantine,
+};
+
+// ───────────────────────────────────────────────
+// Detection / 偵測
+// ───────────────────────────────────────────────
+
+/// Whether an archive entry is a zipmod packed inside another archive
+/// 判斷壓縮檔中的 entry 是否為內含的 zipmod
+pub(crate) fn is_inner_zipmod(entry_name: &str) -> bool {
+    !entry_name.ends_with('/') && entry_name.to_lowercase().ends_with(".zipmod")
+}
+
+// ───────────────────────────────────────────────
+// Extraction / 解壓縮
+// ───────────────────────────────────────────────
+
+fn zip_error(path: &Path, e: impl ToString) -> AppError {
+    AppError::ZipInvalid {
+        path: Some(long_path::display(path)),
+        detail: e.to_string(),
+    }
+}
+
+/// Copy entry `index` of `archive` to `target` and make sure the result opens as a zip
+/// 將 `archive` 的第 `index` 個 entry 複製到 `target`，並確認結果可作為 zip 開啟
+fn extract_entry(
+    archive: &mut ZipArchive<File>,
+    index: usize,
+    wrapper: &Path,
+    target: &Path,
+) -> Result<(), AppError> {
+    let mut entry = archive.by_index(index).map_err(|e| zip_error(wrapper, e))?;
+    let mut output = File::create(long_path::extend(target))
+        .map_err(|e| AppError::io("io.extract_zipmod", Some(target), e))?;
+    io::copy(&mut entry, &mut output)
+        .map_err(|e| AppError::io("io.extract_zipmod", Some(target), e))?;
+    drop(output);
+
+    let extracted = File::open(long_path::extend(target))
+        .map_err(|e| AppError::io("io.extract_zipmod", Some(target), e))?;
+    ZipArchive::new(extracted).map_err(|e| zip_error(target, e))?;
+    Ok(())
+}
+
+/// Extract the `.zipmod` files inside a downloaded wrapper zip into `destination`
+/// (default: the wrapper's folder). Names that already exist get a ` (2)` style suffix,
+/// and every extracted file must open as a zip; on any failure the files extracted so far
+/// are removed. With `quarantine_game` the wrapper is quarantined under that game afterwards.
+/// 將下載的外層 zip 中的 `.zipmod` 解壓縮至 `destination`（預設為外層 zip 所在資料夾）；同名時加上 ` (2)` 形式的後綴，
+/// 每個解出的檔案都必須能作為 zip 開啟，失敗時移除已解出的檔案；指定 `quarantine_game` 時，完成後將外層 zip 隔離至該遊戲
+pub(crate) fn extract_inner_zipmods(
+    wrapper: &Path,
+    destination: Option<&Path>,
+    quarantine_game: Option<&Path>,
+    write_mode: WriteMode,
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<Vec<ModEntry>, AppError> {
+    let file = File::open(long_path::extend(wrapper))
+        .map_err(|e| AppError::io("io.open_file", Some(wrapper), e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| zip_error(wrapper, e))?;
+    let destination = destination
+        .map(Path::to_path_buf)
+        .or_else(|| wrapper.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+
+    let inner = (0..archive.len())
+        .filter_map(|index| {
+            let entry = archive.by_index_raw(index).ok()?;
+            is_inner_zipmod(entry.name()).then(|| (index, entry.name().to_string(), entry.size()))
+        })
+        .collect::<Vec<_>>();
+    disk_space::ensure_space(&destination, inner.iter().map(|(_, _, size)| size).sum())?;
+    fs::create_dir_all(long_path::extend(&destination))
+        .map_err(|e| AppError::io("io.extract_zipmod", Some(&destination), e))?;
+
+    let mut extracted: Vec<PathBuf> = Vec::new();
+    let result = inner
+        .iter()
+        .enumerate()
+        .try_for_each(|(current, (index, name, _))| {
+            if cancel.is_cancelled() {
+                return Err(AppError::OperationCancelled);
+            }
+            on_progress(Progress::new(current, inner.len()).with_message(name.clone()));
+            // Only the file name is used, so entries cannot escape `destination`
+            // 只取檔名，entry 無法寫到 `destination` 之外
+            let file_name = Path::new(name.as_str())
+                .file_name()
+                .map(|name| name.to_os_string())
+                .unwrap_or_default();
+            let target = quarantine::unique_path(&destination.join(file_name));
+            extracted.push(target.clone());
+            extract_entry(&mut archive, *index, wrapper, &target)
+        });
+    if let Err(e) = result {
+        extracted
+            .iter()
+            .for_each(|path| disk_space::remove_partial(path));
+        return Err(e);
+    }
+    on_progress(Progress::new(inner.len(), inner.len()));
+    drop(archive);
+
+    if let Some(game_path) = quarantine_game {
+        quarantine::quarantine_file(game_path, wrapper, write_mode)?;
+    }
+    // Every file written is a `.zipmod` / 寫出的檔案皆為 `.zipmod`
+    let mod_extensions = ["zipmod".to_string()];
+    Ok(extracted
+        .iter()
+        .map(|path| build_mod_entry(path, &path.to_string_lossy(), &mod_extensions))
+        .collect())
+}
@@ -0,0 +1,174 @@
+use std::{collections::HashMap, fs, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{app_log, asset_index, error::AppError, links, messages::Message};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Measured throughputs inside each profile directory
+/// 各 profile 資料夾中已量測的處理速度
+const THROUGHPUT_FILE: &str = "throughput.json";
+
+/// Weight of the newest run in the smoothed throughput
+/// 平滑後的處理速度中最新一次執行所佔的權重
+const SMOOTHING: f64 = 0.3;
+
+/// Runs shorter than this say more about start-up costs than about throughput
+/// 短於此時間的執行主要反映啟動成本而非處理速度，因此不計入
+const MIN_MEASURED: Duration = Duration::from_millis(200);
+
+/// Heavy operations that can be estimated, named like their progress kinds
+/// 可預估的耗時作業，名稱與其進度類型相同
+#[derive(Clone, Copy)]
+pub(crate) enum EstimateKind {
+    /// `build_asset_index`: every mod archive / 每個模組壓縮檔
+    AssetIndex,
+    /// `export_checksums`: every file of the mods folder / mods 資料夾中的每個檔案
+    ChecksumExport,
+    /// `verify_checksums`: every file of the mods folder / mods 資料夾中的每個檔案
+    ChecksumVerify,
+    /// `scan_for_corrupt_mods`: every mod archive / 每個模組壓縮檔
+    CorruptScan,
+}
+
+/// What `estimate_operation` returns
+/// `estimate_operation` 的回傳內容
+#[derive(Serialize)]
+pub(crate) struct OperationEstimate {
+    pub files: usize,
+    pub total_bytes: u64,
+    /// Smoothed bytes per second of earlier runs of this kind, None before the first run
+    /// 此類作業先前執行的平滑處理速度（位元組/秒），首次執行前為 None
+    pub bytes_per_second: Option<f64>,
+    /// `total_bytes` at `bytes_per_second`, None before the first run
+    /// 以 `bytes_per_second` 處理 `total_bytes` 所需的秒數，首次執行前為 None
+    pub estimated_seconds: Option<f64>,
+}
+
+/// Smoothed bytes per second keyed by progress kind
+/// 以進度類型為 key 的平滑處理速度（位元組/秒）
+#[derive(Serialize, Deserialize, Default)]
+struct ThroughputFile {
+    #[serde(default)]
+    kinds: HashMap<String, f64>,
+}
+
+impl EstimateKind {
+    pub(crate) fn parse(kind: &str) -> Result<Self, AppError> {
+        match kind {
+            "asset-index" => Ok(EstimateKind::AssetIndex),
+            "checksum-export" => Ok(EstimateKind::ChecksumExport),
+            "checksum-verify" => Ok(EstimateKind::ChecksumVerify),
+            "corrupt-scan" => Ok(EstimateKind::CorruptScan),
+            _ => Err(AppError::InvalidInput {
+                message: Message::new("input.unknown_estimate_kind").with("kind", kind),
+            }),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            EstimateKind::AssetIndex => "asset-index",
+            EstimateKind::ChecksumExport => "checksum-export",
+            EstimateKind::ChecksumVerify => "checksum-verify",
+            EstimateKind::CorruptScan => "corrupt-scan",
+        }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Estimating / 預估
+// ───────────────────────────────────────────────
+
+fn read_throughputs(profile_dir: &Path) -> ThroughputFile {
+    fs::read_to_string(profile_dir.join(THROUGHPUT_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Files `kind` would process under `<game_path>/mods` and their total size, from directory
+/// listings alone
+/// `kind` 在 `<game_path>/mods` 下會處理的檔案數與總大小；只讀取目錄清單
+pub(crate) fn count_candidates(
+    kind: EstimateKind,
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+) -> (usize, u64) {
+    links::walk_files(&game_path.join("mods"), follow_links)
+        .into_iter()
+        .filter(|file| match kind {
+            EstimateKind::AssetIndex | EstimateKind::CorruptScan => {
+                asset_index::is_mod_archive(&file.path, mod_extensions)
+            }
+            EstimateKind::ChecksumExport | EstimateKind::ChecksumVerify => true,
+        })
+        .fold((0, 0), |(files, bytes), file| {
+            (files + 1, bytes + file.metadata.len())
+        })
+}
+
+/// Count what `kind` would process and how long it should take at the throughput measured
+/// on earlier runs of the same kind in this profile
+/// 計算 `kind` 會處理的檔案，並依此 profile 先前同類作業量測到的處理速度推算所需時間
+pub(crate) fn estimate_operation(
+    kind: EstimateKind,
+    profile_dir: &Path,
+    game_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+) -> OperationEstimate {
+    let (files, total_bytes) = count_candidates(kind, game_path, follow_links, mod_extensions);
+    let bytes_per_second = read_throughputs(profile_dir)
+        .kinds
+        .get(kind.name())
+        .copied()
+        .filter(|rate| *rate > 0.0);
+    OperationEstimate {
+        files,
+        total_bytes,
+        bytes_per_second,
+        estimated_seconds: bytes_per_second.map(|rate| total_bytes as f64 / rate),
+    }
+}
+
+// ───────────────────────────────────────────────
+// Measuring / 量測
+// ───────────────────────────────────────────────
+
+fn record(
+    profile_dir: &Path,
+    kind: EstimateKind,
+    bytes: u64,
+    elapsed: Duration,
+) -> Result<(), AppError> {
+    let rate = bytes as f64 / elapsed.as_secs_f64();
+    let mut throughputs = read_throughputs(profile_dir);
+    throughputs
+        .kinds
+        .entry(kind.name().to_string())
+        .and_modify(|smoothed| *smoothed = SMOOTHING * rate + (1.0 - SMOOTHING) * *smoothed)
+        .or_insert(rate);
+
+    let path = profile_dir.join(THROUGHPUT_FILE);
+    let content = serde_json::to_string(&throughputs)
+        .map_err(|e| AppError::io("io.write_throughput", Some(&path), e))?;
+    fs::write(&path, content).map_err(|e| AppError::io("io.write_throughput", Some(&path), e))
+}
+
+/// Fold one completed run of `kind` over `bytes` into the profile's smoothed throughput.
+/// Empty and very short runs are left out; a file that cannot be written is logged.
+/// 將一次完成的 `kind` 作業（處理 `bytes` 位元組）併入 profile 的平滑處理速度；
+/// 不計入空的或極短的執行，無法寫入時記錄後略過
+pub(crate) fn record_run(profile_dir: &Path, kind: EstimateKind, bytes: u64, elapsed: Duration) {
+    if bytes == 0 || elapsed < MIN_MEASURED {
+        return;
+    }
+    if let Err(e) = record(profile_dir, kind, bytes, elapsed) {
+        app_log::write(&format!("{} throughput not saved: {}", kind.name(), e));
+    }
+}
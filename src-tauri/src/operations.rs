@@ -0,0 +1,259 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{error::AppError, long_path, path_guard::WriteMode};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Cancellation flag shared between a running operation and `cancel_operation`
+/// 進行中的作業與 `cancel_operation` 共用的取消旗標
+#[derive(Clone, Default)]
+pub(crate) struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+struct OperationEntry {
+    profile_id: String,
+    kind: &'static str,
+    token: CancelToken,
+}
+
+/// Registry of in-flight long-running operations, stored in Tauri managed state
+/// 進行中長時間作業的登記表，存放於 Tauri managed state
+#[derive(Default)]
+pub(crate) struct Operations {
+    running: Mutex<HashMap<String, OperationEntry>>,
+    /// Canonical, lowercased path → id of the operation working on it
+    /// 正規化並轉小寫的路徑 → 正在處理該路徑的作業 id
+    locked_paths: Mutex<HashMap<String, String>>,
+    counter: AtomicU64,
+    /// `read_only_mode` of the app settings, applied on startup and whenever they are saved
+    /// 全域設定的 `read_only_mode`，於啟動時及每次儲存設定時套用
+    read_only: AtomicBool,
+}
+
+/// Keeps an operation registered, and the paths it locked locked, until dropped, so both are
+/// released on success, error, cancellation and panic paths alike
+/// 在 drop 前保持作業登記與其鎖定的路徑；成功、錯誤、取消或 panic 時都會自動釋放
+pub(crate) struct OperationGuard<'a> {
+    operations: &'a Operations,
+    pub id: String,
+    pub kind: &'static str,
+    pub token: CancelToken,
+    /// Read-only mode as it was when the operation started, so toggling it midway does not
+    /// change a batch halfway through
+    /// 作業開始時的唯讀模式，中途切換不會讓批次只套用一半
+    pub write_mode: WriteMode,
+}
+
+impl Drop for OperationGuard<'_> {
+    fn drop(&mut self) {
+        self.operations
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+        self.operations
+            .locked_paths
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, holder| holder != &self.id);
+    }
+}
+
+/// Lock key of a path: canonical when it exists (a destination may not yet), lowercased
+/// since Windows paths compare case-insensitively
+/// 路徑的鎖定 key：存在時取正規化路徑（目的地可能尚未存在），並轉小寫，因為 Windows 路徑不分大小寫
+fn lock_key(path: &Path) -> String {
+    fs::canonicalize(long_path::extend(path))
+        .map(|canonical| long_path::display(&canonical))
+        .unwrap_or_else(|_| long_path::display(path))
+        .to_lowercase()
+}
+
+// ───────────────────────────────────────────────
+// Registry / 登記表操作
+// ───────────────────────────────────────────────
+
+impl Operations {
+    /// Register a new operation, optionally cancelling earlier ones of the same kind and profile.
+    /// A caller-supplied id lets the frontend know the id before the command returns; one that
+    /// is still running fails with `OperationIdInUse`, since both would share its entry and
+    /// path locks.
+    /// 登記新作業，可選擇取消同 profile 同類型的舊作業；前端可自行指定 id 以便提前得知；
+    /// 指定的 id 仍在進行中時回傳 `OperationIdInUse`，因為兩者會共用其登記與路徑鎖定
+    pub fn register(
+        &self,
+        profile_id: &str,
+        kind: &'static str,
+        operation_id: Option<String>,
+        cancel_previous: bool,
+    ) -> Result<OperationGuard<'_>, AppError> {
+        let id = operation_id.unwrap_or_else(|| {
+            let millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or(0);
+            let seq = self.counter.fetch_add(1, Ordering::Relaxed);
+            format!("{}-{:x}-{}", kind, millis, seq)
+        });
+        let token = CancelToken::default();
+
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if running.contains_key(&id) {
+            return Err(AppError::OperationIdInUse { operation_id: id });
+        }
+        if cancel_previous {
+            running
+                .values()
+                .filter(|op| op.profile_id == profile_id && op.kind == kind)
+                .for_each(|op| op.token.cancel());
+        }
+        running.insert(
+            id.clone(),
+            OperationEntry {
+                profile_id: profile_id.to_string(),
+                kind,
+                token: token.clone(),
+            },
+        );
+
+        Ok(OperationGuard {
+            operations: self,
+            id,
+            kind,
+            token,
+            write_mode: self.write_mode(),
+        })
+    }
+
+    pub fn write_mode(&self) -> WriteMode {
+        WriteMode::from_read_only(self.read_only.load(Ordering::Relaxed))
+    }
+
+    pub fn set_write_mode(&self, write_mode: WriteMode) {
+        self.read_only
+            .store(write_mode.is_read_only(), Ordering::Relaxed);
+    }
+
+    /// Flip the cancellation flag of a running operation; false if the id is unknown
+    /// 將進行中作業的取消旗標設為 true；找不到 id 時回傳 false
+    pub fn cancel(&self, operation_id: &str) -> bool {
+        match self
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(operation_id)
+        {
+            Some(op) => {
+                op.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl OperationGuard<'_> {
+    /// Lock `paths` for this operation until it ends. If another operation holds any of them,
+    /// fails with `PathBusy` naming that operation and locks none. Locking declares that the
+    /// paths will change, so `write_mode` refuses it with `ReadOnlyMode`.
+    /// 鎖定 `paths` 直到此作業結束；若其中任一路徑已被其他作業鎖定，回傳指出該作業的 `PathBusy`，且不鎖定任何路徑；
+    /// 鎖定即表示這些路徑將被變更，因此唯讀模式下以 `ReadOnlyMode` 拒絕
+    pub fn lock_paths<P: AsRef<Path>>(&self, paths: &[P]) -> Result<(), AppError> {
+        paths
+            .iter()
+            .try_for_each(|path| self.write_mode.ensure_writable(path.as_ref()))?;
+        let keys = paths
+            .iter()
+            .map(|path| lock_key(path.as_ref()))
+            .collect::<Vec<_>>();
+        let mut locked = self
+            .operations
+            .locked_paths
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let busy = paths.iter().zip(&keys).find_map(|(path, key)| {
+            locked
+                .get(key)
+                .filter(|holder| *holder != &self.id)
+                .map(|holder| (path, holder))
+        });
+        if let Some((path, holder)) = busy {
+            return Err(AppError::PathBusy {
+                path: long_path::display(path.as_ref()),
+                operation_id: holder.clone(),
+            });
+        }
+        for key in keys {
+            locked.insert(key, self.id.clone());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Operations;
+    use crate::{error::AppError, test_support::SyntheticInstall};
+
+    #[test]
+    fn a_running_operation_id_cannot_be_registered_again() {
+        let operations = Operations::default();
+        let first = operations
+            .register("profile", "scan", Some("op".to_string()), false)
+            .unwrap();
+
+        let second = operations.register("profile", "scan", Some("op".to_string()), false);
+
+        assert!(matches!(
+            second,
+            Err(AppError::OperationIdInUse { operation_id }) if operation_id == "op"
+        ));
+        assert!(operations.cancel("op"));
+        assert!(first.token.is_cancelled());
+        drop(first);
+        assert!(!operations.cancel("op"));
+        assert!(operations
+            .register("profile", "scan", Some("op".to_string()), false)
+            .is_ok());
+    }
+
+    #[test]
+    fn a_locked_path_is_busy_until_its_holder_drops() {
+        let install = SyntheticInstall::new();
+        let path = install.add_file("mods/a.zipmod", b"a");
+        let operations = Operations::default();
+        let first = operations.register("", "delete", None, false).unwrap();
+        let second = operations.register("", "resolve", None, false).unwrap();
+        first.lock_paths(&[&path]).unwrap();
+
+        let busy = second.lock_paths(&[&path]);
+
+        assert!(matches!(
+            busy,
+            Err(AppError::PathBusy { operation_id, .. }) if operation_id == first.id
+        ));
+        first.lock_paths(&[&path]).unwrap();
+        drop(first);
+        second.lock_paths(&[&path]).unwrap();
+    }
+}
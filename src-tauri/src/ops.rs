@@ -0,0 +1,356 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    app_log,
+    empty_dirs::PrunedDir,
+    error::AppError,
+    file_retry,
+    file_snapshot::{self, Expectations, FileSnapshot},
+    links, long_path,
+    messages::Message,
+    modpack::ModpackFolders,
+    name_anomalies,
+    path_guard::{self, WriteMode},
+    pins::PinSet,
+    progress::{OnProgress, Progress},
+    quarantine,
+};
+
+/// Suffix that makes Sideloader ignore a mod file without moving it
+/// 讓 Sideloader 忽略模組檔（不需搬移）所加上的副檔名
+const DISABLED_SUFFIX: &str = ".disabled";
+
+/// A file `delete_mods` removed from the mods folder
+/// `delete_mods` 從 mods 資料夾移除的檔案
+#[derive(Serialize)]
+pub(crate) struct DeletedFile {
+    pub path: String,
+    /// Safe mode of the owning profile moved it to quarantine instead of the Recycle Bin
+    /// 所屬 profile 的安全模式將其移至隔離區而非回收桶
+    pub redirected_to_quarantine: bool,
+    /// Location in quarantine when redirected / 轉為隔離時在隔離區中的位置
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    /// Times the move was retried while another process held the file
+    /// 因其他程序佔用檔案而重試搬移的次數
+    pub retries: u32,
+    /// The path as given in the batch's `paths`, before it was resolved; not sent to the
+    /// frontend
+    /// 批次 `paths` 中解析前的原始路徑；不傳給前端
+    #[serde(skip)]
+    pub(crate) requested: String,
+}
+
+/// What `delete_mods` returns / `delete_mods` 的回傳內容
+#[derive(Serialize)]
+pub(crate) struct DeleteReport {
+    pub deleted: Vec<DeletedFile>,
+    /// Folders the batch left empty, removed when `prune_empty_dirs` is set
+    /// 批次使其變空的資料夾，指定 `prune_empty_dirs` 時刪除
+    pub pruned_dirs: Vec<PrunedDir>,
+}
+
+// ───────────────────────────────────────────────
+// File Operations / 檔案操作
+// ───────────────────────────────────────────────
+
+/// Whether `path` is a mod file disabled by `disable_file`
+/// 判斷 `path` 是否為經 `disable_file` 停用的模組檔
+pub(crate) fn is_disabled(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| {
+        name.to_string_lossy()
+            .to_lowercase()
+            .ends_with(DISABLED_SUFFIX)
+    })
+}
+
+/// Whether `path` carries the read-only attribute (Windows) or has no write bit set (Unix),
+/// which makes deleting it fail with a permission error
+/// `path` 是否帶有唯讀屬性（Windows）或未設定任何寫入權限（Unix）；此時刪除會因權限錯誤而失敗
+pub(crate) fn is_read_only(path: &Path) -> bool {
+    fs::metadata(long_path::extend(path)).is_ok_and(|meta| meta.permissions().readonly())
+}
+
+/// Clear the read-only attribute of `path` (the owner write bit on Unix) and record that in
+/// the app log, so the change can be audited later
+/// 清除 `path` 的唯讀屬性（Unix 上為擁有者寫入權限），並記錄於 app log 以便日後查核
+pub(crate) fn clear_read_only_flag(path: &Path) -> Result<(), AppError> {
+    let extended = long_path::extend(path);
+    let mut permissions = fs::metadata(&extended)
+        .map_err(|e| AppError::io("io.clear_read_only", Some(path), e))?
+        .permissions();
+    #[cfg(windows)]
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    fs::set_permissions(&extended, permissions)
+        .map_err(|e| AppError::io("io.clear_read_only", Some(path), e))?;
+    app_log::write(&format!(
+        "cleared read-only flag: {}",
+        long_path::display(path)
+    ));
+    Ok(())
+}
+
+/// Move one file to the Recycle Bin, retrying while another process holds it; returns the
+/// number of retries
+/// 將單一檔案移到回收桶，其他程序佔用時會重試；回傳重試次數
+pub(crate) fn trash_file(path: &Path, write_mode: WriteMode) -> Result<u32, AppError> {
+    write_mode.ensure_writable(path)?;
+    file_retry::retry(|| trash::delete(long_path::extend(path)))
+        .map(|((), retries)| retries)
+        .map_err(|exhausted| {
+            exhausted.into_error(path, |e| AppError::DeleteFailed {
+                path: long_path::display(path),
+                detail: e.to_string(),
+            })
+        })
+}
+
+/// Rename a mod file to `<name>.disabled` (or a free ` (2)` variant) so Sideloader skips it,
+/// retrying while another process holds it; returns the new path and the number of retries
+/// 將模組檔改名為 `<name>.disabled`（或可用的 ` (2)` 變體）使 Sideloader 略過，其他程序佔用時會重試；
+/// 回傳新路徑與重試次數
+pub(crate) fn disable_file(path: &Path, write_mode: WriteMode) -> Result<(PathBuf, u32), AppError> {
+    write_mode.ensure_writable(path)?;
+    let mut disabled = path.as_os_str().to_owned();
+    disabled.push(DISABLED_SUFFIX);
+    let target = quarantine::unique_path(Path::new(&disabled));
+    let (_, retries) =
+        file_retry::retry(|| fs::rename(long_path::extend(path), long_path::extend(&target)))
+            .map_err(|exhausted| {
+                exhausted.into_error(path, |e| AppError::io("io.disable_mod", Some(path), e))
+            })?;
+    Ok((target, retries))
+}
+
+/// Rename the file or folder at `path` to `new_name` in the same folder and return the new path.
+/// Case-only renames of the same file are allowed; any other existing target is refused.
+/// A rename failing while another process holds the file is retried.
+/// 將 `path` 的檔案或資料夾在原資料夾內改名為 `new_name` 並回傳新路徑；允許同一檔案僅改變大小寫，其他已存在的目標一律拒絕；
+/// 其他程序佔用檔案而改名失敗時會重試
+pub(crate) fn rename_file(
+    path: &Path,
+    new_name: &str,
+    write_mode: WriteMode,
+) -> Result<PathBuf, AppError> {
+    write_mode.ensure_writable(path)?;
+    let invalid = new_name.is_empty()
+        || new_name == ".."
+        || new_name.trim() != new_name
+        || name_anomalies::invalid_on_windows(new_name);
+    if invalid {
+        return Err(AppError::InvalidInput {
+            message: Message::new("input.invalid_file_name").with("name", new_name),
+        });
+    }
+    let target = path.with_file_name(new_name);
+    let (source_extended, target_extended) = (long_path::extend(path), long_path::extend(&target));
+    let same_file = || match (
+        fs::canonicalize(&source_extended),
+        fs::canonicalize(&target_extended),
+    ) {
+        (Ok(source), Ok(existing)) => source == existing,
+        _ => false,
+    };
+    if target_extended.exists() && !same_file() {
+        return Err(AppError::InvalidInput {
+            message: Message::new("input.rename_target_exists")
+                .with("path", long_path::display(&target)),
+        });
+    }
+    file_retry::retry(|| fs::rename(&source_extended, &target_extended)).map_err(|exhausted| {
+        exhausted.into_error(path, |e| AppError::io("io.rename_mod", Some(path), e))
+    })?;
+    Ok(target)
+}
+
+/// Move each path, given as `requested` before it was resolved, to the Recycle Bin, or to its
+/// game's quarantine when the game is one of `safe_mode_games`, stopping at the first failure
+/// or when `is_cancelled` turns true.
+/// Each path is compared with its `expected` snapshot again right before it is trashed; files
+/// changed since the batch was checked are skipped and reported with `FileChanged` at the end.
+/// Each file moved is pushed to `deleted` right away, so a batch that stops still lists them.
+/// 將每個路徑（解析前為 `requested`）移到回收桶；所屬遊戲在 `safe_mode_games` 中時改移至該遊戲的隔離區；遇到錯誤或 `is_cancelled` 為 true 時停止。
+/// 每個路徑在移除前一刻再與對應的 `expected` 快照比對；整批檢查後才變動的檔案會略過，並在最後以 `FileChanged` 回報。
+/// 每個已搬移的檔案立即加入 `deleted`，批次中途停止時仍會列出
+#[allow(clippy::too_many_arguments)]
+fn trash_paths(
+    paths: &[PathBuf],
+    requested: &[String],
+    expected: &[Option<FileSnapshot>],
+    safe_mode_games: &[String],
+    write_mode: WriteMode,
+    is_cancelled: &dyn Fn() -> bool,
+    on_progress: OnProgress,
+    deleted: &mut Vec<DeletedFile>,
+) -> Result<(), AppError> {
+    let mut changed = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        if is_cancelled() {
+            return Err(AppError::OperationCancelled);
+        }
+        on_progress(Progress::new(index, paths.len()).with_message(long_path::display(path)));
+        if !file_snapshot::unchanged(expected.get(index).and_then(Option::as_ref), path) {
+            changed.push(path);
+            continue;
+        }
+        let (destination, retries) = match quarantine::owning_game(safe_mode_games, path) {
+            Some(game_path) => {
+                let (target, retries) =
+                    quarantine::quarantine_file(Path::new(game_path), path, write_mode)?;
+                (Some(target), retries)
+            }
+            None => (None, trash_file(path, write_mode)?),
+        };
+        deleted.push(DeletedFile {
+            path: long_path::display(path),
+            redirected_to_quarantine: destination.is_some(),
+            destination: destination.map(|target| long_path::display(&target)),
+            retries,
+            requested: requested[index].clone(),
+        });
+    }
+    on_progress(Progress::new(paths.len(), paths.len()));
+    match changed.first() {
+        Some(first) => Err(AppError::FileChanged {
+            path: long_path::display(first),
+            count: changed.len(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Move mod files to the Recycle Bin after checking every path against the allowed roots
+/// of `game_paths`; `unsafe_allow_any_path` skips the check. A batch with a folder is
+/// rejected with `directory_not_mod` unless `allow_directories` is set. With
+/// `protected_modpacks`, a batch touching any of those folders is rejected with
+/// `ModpackProtected` before anything is trashed, and a batch with a file in any of `pins`
+/// with `Pinned`. With `expectations`, a batch with files whose size or mtime no longer
+/// match is rejected the same way with `FileChanged`; `None` skips that check.
+/// Files of `safe_mode_games` are quarantined instead; while any game is in safe mode, a batch
+/// with files outside every game is refused with `SafeModeRefused`, as those cannot be.
+/// Read-only files reject the batch with `ReadOnly` unless `clear_read_only` is set, in which
+/// case their flag is cleared first. `write_mode` refuses the whole batch with `ReadOnlyMode`.
+/// Files already trashed stay trashed when `is_cancelled` or an error stops the batch midway;
+/// every file moved is pushed to `deleted` as it goes, so such a batch still reports them.
+/// 檢查所有路徑皆位於 `game_paths` 的允許資料夾後，將模組檔移到回收桶；`unsafe_allow_any_path` 會略過檢查。
+/// 含有資料夾的批次除非設定 `allow_directories`，否則以 `directory_not_mod` 拒絕。
+/// 指定 `protected_modpacks` 時，只要有路徑位於其中的資料夾，整批在刪除前即以 `ModpackProtected` 拒絕；
+/// 含有 `pins` 中任一釘選檔案的批次則以 `Pinned` 拒絕。
+/// 指定 `expectations` 時，若有檔案的大小或修改時間已不符，整批同樣以 `FileChanged` 拒絕；`None` 則不檢查。
+/// 屬於 `safe_mode_games` 的檔案改為隔離；只要有遊戲處於安全模式，含有不屬於任何遊戲之檔案的批次即以 `SafeModeRefused` 拒絕，
+/// 因為這些檔案無法隔離。含唯讀檔案的批次以 `ReadOnly` 拒絕，除非設定 `clear_read_only`，此時先清除其唯讀屬性。
+/// `write_mode` 為唯讀時整批以 `ReadOnlyMode` 拒絕。
+/// 中途取消或發生錯誤時，已移除的檔案不會還原；每個已搬移的檔案會立即加入 `deleted`，因此這類批次仍會回報
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn delete_mods<S: AsRef<str>>(
+    game_paths: &[S],
+    paths: &[String],
+    unsafe_allow_any_path: bool,
+    allow_directories: bool,
+    protected_modpacks: Option<&ModpackFolders>,
+    pins: &[PinSet],
+    expectations: Option<&Expectations>,
+    clear_read_only: bool,
+    safe_mode_games: &[String],
+    write_mode: WriteMode,
+    is_cancelled: &dyn Fn() -> bool,
+    on_progress: OnProgress,
+    deleted: &mut Vec<DeletedFile>,
+) -> Result<(), AppError> {
+    let targets =
+        path_guard::resolve_targets(game_paths, paths, unsafe_allow_any_path, write_mode)?;
+    if let Some(folder) = targets
+        .iter()
+        .find(|target| !allow_directories && links::stat(target).0.is_some_and(|m| m.is_dir()))
+    {
+        return Err(AppError::InvalidInput {
+            message: Message::new("input.directory_not_mod")
+                .with("path", long_path::display(folder)),
+        });
+    }
+    if let Some(modpacks) = protected_modpacks {
+        let protected = targets
+            .iter()
+            .filter(|target| modpacks.contains(target))
+            .collect::<Vec<_>>();
+        if let Some(first) = protected.first() {
+            return Err(AppError::ModpackProtected {
+                path: long_path::display(first),
+                count: protected.len(),
+            });
+        }
+    }
+    let pinned = targets
+        .iter()
+        .filter(|target| pins.iter().any(|set| set.contains(target)))
+        .collect::<Vec<_>>();
+    if let Some(first) = pinned.first() {
+        return Err(AppError::Pinned {
+            path: long_path::display(first),
+            count: pinned.len(),
+        });
+    }
+    if !safe_mode_games.is_empty() {
+        if let Some(outside) = targets
+            .iter()
+            .find(|target| quarantine::owning_game(game_paths, target).is_none())
+        {
+            return Err(AppError::SafeModeRefused {
+                path: long_path::display(outside),
+            });
+        }
+    }
+    let expected = paths
+        .iter()
+        .map(|path| expectations.and_then(|expected| file_snapshot::expected(expected, path)))
+        .collect::<Vec<_>>();
+    let changed = targets
+        .iter()
+        .zip(&expected)
+        .filter(|(target, expected)| !file_snapshot::unchanged(expected.as_ref(), target))
+        .collect::<Vec<_>>();
+    if let Some((first, _)) = changed.first() {
+        return Err(AppError::FileChanged {
+            path: long_path::display(first),
+            count: changed.len(),
+        });
+    }
+    let read_only = targets
+        .iter()
+        .filter(|target| is_read_only(target))
+        .collect::<Vec<_>>();
+    match (read_only.first(), clear_read_only) {
+        (Some(first), false) => {
+            return Err(AppError::ReadOnly {
+                path: long_path::display(first),
+                count: read_only.len(),
+            })
+        }
+        _ => {
+            for target in read_only {
+                clear_read_only_flag(target)?;
+            }
+        }
+    }
+    trash_paths(
+        &targets,
+        paths,
+        &expected,
+        safe_mode_games,
+        write_mode,
+        is_cancelled,
+        on_progress,
+        deleted,
+    )
+}
@@ -0,0 +1,1048 @@
+use std::{
+    collections::{HashMap, HashSet},
+    iter, mem,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        LazyLock,
+    },
+};
+
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cloud_files, disk_space, folder_size, links, local_time, log_environment, long_path, mod_roots,
+    name_hints, paste_normalize, patterns,
+    progress::{self, OnProgress, Progress},
+    timings::Timings,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Entry representing one mod (either loaded or skipped)
+/// 單一模組資料結構（可為 loaded 或 skipped）
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ModEntry {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub created: Option<u64>,
+    #[serde(default)]
+    pub modified: Option<u64>,
+    /// `created`/`modified` in the system timezone as ISO 8601 with offset
+    /// 以系統時區表示、含時差 ISO 8601 格式的 `created`/`modified`
+    #[serde(default)]
+    pub created_local: Option<String>,
+    #[serde(default)]
+    pub modified_local: Option<String>,
+    /// A timestamp was before 1970 or absurdly far in the future and was clamped
+    /// 有時間早於 1970 年或過於遙遠的未來，已被限制在合理範圍
+    #[serde(default)]
+    pub timestamp_clamped: bool,
+    /// File was created or modified after the log was written, so the log may not describe it
+    /// 檔案在 log 寫入後才建立或修改，log 內容可能已不符
+    #[serde(default)]
+    pub changed_since_log: bool,
+    /// Hints parsed from the `[Author] Title v1.2` filename convention
+    /// 依 `[Author] Title v1.2` 檔名慣例解析出的提示
+    #[serde(default)]
+    pub name_author: Option<String>,
+    #[serde(default)]
+    pub name_title: Option<String>,
+    #[serde(default)]
+    pub name_version: Option<String>,
+    /// Resolved target when `path` is a symlink or junction
+    /// `path` 為 symlink 或 junction 時解析後的目標
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_target: Option<String>,
+    /// Lies in a KKManager modpack folder, which is re-synced automatically
+    /// 位於 KKManager 會自動重新同步的模組包資料夾
+    #[serde(default)]
+    pub managed: bool,
+    /// Lies in a priority folder such as `mods/!!!my overrides`, where users put copies meant
+    /// to win over the modpack's
+    /// 位於如 `mods/!!!my overrides` 的優先資料夾；使用者會將要蓋過模組包版本的檔案放在此處
+    #[serde(default)]
+    pub priority_folder: bool,
+    /// Mod folder a log-derived entry was found under: the game's `mods` or Sideloader's
+    /// additional mods directory
+    /// log 解析項目所在的模組資料夾：遊戲的 `mods` 或 Sideloader 的額外 mods 資料夾
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_root: Option<String>,
+    /// Volume of `mod_root` with links followed, e.g. `d:\` when `mods` is a junction to
+    /// another drive; lets the frontend split reclaimable space by drive
+    /// `mod_root` 解析連結後所在的磁碟區，例如 `mods` 為指向其他磁碟的 junction 時為 `d:\`；供前端依磁碟分別計算可釋放空間
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<String>,
+    /// Number of paths, here or in other installs, sharing this file's data; None when unknown
+    /// 共用此檔案資料的路徑數（含其他安裝中的）；無法得知時為 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardlink_count: Option<u32>,
+    /// The path is a folder, e.g. a loose-folder mod or a log entry ending in a separator;
+    /// `size` is then the total of the files inside, and destructive commands leave it alone
+    /// unless given `allow_directories`
+    /// 路徑為資料夾，例如散裝資料夾模組或 log 中以分隔符號結尾的項目；此時 `size` 為其中檔案的總大小，
+    /// 除非指定 `allow_directories`，否則破壞性指令不會處理
+    #[serde(default)]
+    pub is_directory: bool,
+    /// Files inside when `is_directory`, counted up to `folder_size::FILE_CAP`
+    /// `is_directory` 時其中的檔案數，最多計算至 `folder_size::FILE_CAP`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory_files: Option<u64>,
+    /// The folder holds more than `folder_size::FILE_CAP` files, so `size` only covers the
+    /// first of them
+    /// 資料夾中的檔案超過 `folder_size::FILE_CAP` 個，`size` 只涵蓋其中一部分
+    #[serde(default)]
+    pub size_is_estimate: bool,
+    /// OneDrive or Dropbox placeholder whose data is online only; manifest reads and hashing
+    /// skip it unless hydrating cloud files is turned on
+    /// 資料僅在雲端的 OneDrive 或 Dropbox 預留位置檔案；除非開啟下載雲端檔案，否則讀取 manifest 與計算雜湊時略過
+    #[serde(default)]
+    pub cloud_placeholder: bool,
+    /// Pinned by the user, so no automated flow touches it without `allow_pinned`
+    /// 已被使用者釘選，未指定 `allow_pinned` 時任何自動流程都不會處理
+    #[serde(default)]
+    pub pinned: bool,
+    /// Seconds since the Unix epoch when this app first saw the file, from the profile's
+    /// first-seen database; unlike `created`, it survives copying the install elsewhere
+    /// 本程式首次看到此檔案的時間（Unix epoch 秒數），取自 profile 的首次發現資料庫；與 `created` 不同，複製安裝後仍保留
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_seen: Option<u64>,
+    /// Ids of the other conflicts of the scan this file belongs to, so deleting it changes them
+    /// too; see `conflict_coupling`
+    /// 此檔案同時所屬的掃描中其他衝突 id，刪除它也會改變那些衝突；見 `conflict_coupling`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub coupled_conflicts: Vec<String>,
+    /// Physical identity, for telling hardlinks apart; not sent to the frontend
+    /// 實體檔案識別，用以辨識硬連結；不傳給前端
+    #[serde(skip)]
+    pub(crate) file_id: Option<links::FileId>,
+}
+
+/// Conflict block in log: one loaded mod + multiple skipped mods
+/// 衝突項目：一個 loaded mod 與多個被跳過的 mod
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ModConflict {
+    /// Stable id derived from the member paths, used to refer to the conflict in commands
+    /// 由成員路徑推導出的穩定 id，供指令指定衝突
+    #[serde(default)]
+    pub id: String,
+    pub loaded: ModEntry,
+    pub skipped: Vec<ModEntry>,
+    /// Any member changed since the log was written
+    /// 任一成員在 log 寫入後有變動
+    #[serde(default)]
+    pub changed_since_log: bool,
+    /// Bytes freed by removing every skipped copy
+    /// 移除所有 skipped 檔案可釋放的空間
+    #[serde(default)]
+    pub wasted_bytes: u64,
+    #[serde(default)]
+    pub skipped_count: usize,
+    /// Newest `modified` time among all members
+    /// 所有成員中最新的 `modified` 時間
+    #[serde(default)]
+    pub latest_activity: Option<u64>,
+    /// The user's note on this conflict, filled in from the profile's saved notes
+    /// 使用者對此衝突的筆記，取自 profile 已儲存的筆記
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// 1-based line of the full log the warning starts on, for "jump to line"
+    /// 警告在完整 log 中起始的行號（從 1 起算），供「跳至該行」使用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Members were found under different mod folders, e.g. a modpack copy against a copy in
+    /// the legacy additional directory
+    /// 成員位於不同的模組資料夾，例如模組包中的一份與舊額外資料夾中的一份
+    #[serde(default)]
+    pub cross_root: bool,
+    /// Two members are hardlinks of one physical file, so deleting one changes nothing
+    /// 有兩個成員為同一實體檔案的硬連結，刪除其中一個不會有任何改變
+    #[serde(default)]
+    pub shared_file: bool,
+    /// Parsed from pasted text after lenient normalization, so member paths may be wrong
+    /// 由寬鬆正規化後的貼上文字解析而來，成員路徑可能不正確
+    #[serde(default)]
+    pub lenient: bool,
+    /// Log warnings merged into this conflict; Sideloader may log one mod's copies as several
+    /// blocks with the same loaded file
+    /// 合併為此衝突的 log 警告數；Sideloader 可能將同一模組的多個副本記錄為 loaded 檔案相同的數個區塊
+    #[serde(default = "one_block")]
+    pub source_blocks: usize,
+    /// 1-based line of every merged warning, in log order, when there is more than one
+    /// 合併了多個警告時，各警告的行號（從 1 起算，依 log 順序）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_lines: Vec<usize>,
+    /// Where the first warning's text lies in the parsed log, for highlighting it inline
+    /// 第一個警告的文字在所解析 log 中的位置，供在 log 中直接標示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    /// Span of every merged warning, in log order, when there is more than one
+    /// 合併了多個警告時，各警告的位置（依 log 順序）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_spans: Vec<Span>,
+}
+
+/// Half-open range of the parsed log in UTF-16 code units, which is how the frontend indexes
+/// the same string
+/// 所解析 log 中的半開區間，以 UTF-16 code unit 計算，與前端索引同一字串的方式相同
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+fn one_block() -> usize {
+    1
+}
+
+/// Warnings with the same loaded file, merged, before their members are read
+/// 尚未讀取成員資訊、已合併的同一 loaded 檔案的警告
+struct RawBlock {
+    lines: Vec<usize>,
+    spans: Vec<Span>,
+    loaded: String,
+    skipped: Vec<String>,
+}
+
+/// 1-based, inclusive range of log lines to parse; `\r\n` and `\n` both end a line
+/// 要解析的 log 行範圍（從 1 起算、含頭尾）；`\r\n` 與 `\n` 皆視為換行
+#[derive(Clone, Copy, Deserialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+// ───────────────────────────────────────────────
+// Utility Function / 工具函式
+// ───────────────────────────────────────────────
+
+impl ModEntry {
+    /// Whether deleting this path frees its size: not when other hardlinks keep the data
+    /// 刪除此路徑是否會釋放其大小；若仍有其他硬連結保留資料則不會
+    pub fn frees_space(&self) -> bool {
+        self.hardlink_count.is_none_or(|links| links <= 1)
+    }
+}
+
+impl ModConflict {
+    pub fn new(loaded: ModEntry, skipped: Vec<ModEntry>) -> Self {
+        let mut conflict = ModConflict {
+            id: conflict_id(&loaded, &skipped),
+            loaded,
+            skipped,
+            changed_since_log: false,
+            wasted_bytes: 0,
+            skipped_count: 0,
+            latest_activity: None,
+            note: None,
+            line: None,
+            cross_root: false,
+            shared_file: false,
+            lenient: false,
+            source_blocks: 1,
+            source_lines: Vec::new(),
+            span: None,
+            source_spans: Vec::new(),
+        };
+        conflict.update_members();
+        conflict
+    }
+
+    /// Recompute every field derived from the members' metadata
+    /// 依成員的檔案資訊重新計算所有衍生欄位
+    fn update_members(&mut self) {
+        self.cross_root = self
+            .skipped
+            .iter()
+            .any(|entry| entry.mod_root != self.loaded.mod_root);
+        let mut ids = HashSet::new();
+        self.shared_file = iter::once(&self.loaded)
+            .chain(&self.skipped)
+            .filter_map(|entry| entry.file_id)
+            .any(|id| !ids.insert(id));
+        self.update_rank();
+    }
+
+    /// Recompute the rank fields from the current members
+    /// 依目前成員重新計算排序用欄位
+    pub fn update_rank(&mut self) {
+        self.wasted_bytes = self
+            .skipped
+            .iter()
+            .filter(|entry| entry.frees_space())
+            .map(|entry| entry.size)
+            .sum();
+        self.skipped_count = self.skipped.len();
+        self.latest_activity = iter::once(&self.loaded)
+            .chain(&self.skipped)
+            .filter_map(|entry| entry.modified)
+            .max();
+    }
+}
+
+/// FNV-1a over the case-folded member paths, so the same block gets the same id across scans
+/// 以不分大小寫的成員路徑計算 FNV-1a，使同一衝突在不同次掃描中得到相同 id
+fn conflict_id(loaded: &ModEntry, skipped: &[ModEntry]) -> String {
+    let hash = iter::once(loaded)
+        .chain(skipped)
+        .flat_map(|entry| {
+            entry
+                .path
+                .to_lowercase()
+                .into_bytes()
+                .into_iter()
+                .chain([0])
+        })
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{:016x}", hash)
+}
+
+/// Build a ModEntry struct from file metadata; `mod_extensions` are the extensions dropped
+/// before the name hints are read
+/// 從檔案資訊建構 ModEntry 結構；`mod_extensions` 為讀取檔名提示前會移除的副檔名
+pub fn build_mod_entry(
+    full_path: &Path,
+    rel_path_for_name: &str,
+    mod_extensions: &[String],
+) -> ModEntry {
+    // `Path::file_name` gives the parent folder or nothing for `foo/` and `..`, so take the
+    // last non-empty component by hand
+    // `Path::file_name` 對 `foo/` 或 `..` 會回傳上層資料夾或空值，因此自行取最後一個非空的路徑片段
+    let name = rel_path_for_name
+        .split(['/', '\\'])
+        .rfind(|part| !part.trim().is_empty())
+        .unwrap_or(rel_path_for_name)
+        .to_string();
+
+    let path_str = long_path::display(full_path);
+
+    let (metadata, link_target) = links::stat(full_path);
+    let physical = metadata
+        .as_ref()
+        .and_then(|m| links::physical_file(full_path, m));
+    let is_directory = metadata.as_ref().is_some_and(|m| m.is_dir());
+    let folder = is_directory.then(|| folder_size::measure(full_path));
+    let size = match folder {
+        Some(folder) => folder.bytes,
+        None => metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+    };
+    let created = metadata
+        .as_ref()
+        .and_then(|m| m.created().ok())
+        .map(local_time::epoch_seconds);
+    let modified = metadata
+        .as_ref()
+        .and_then(|m| m.modified().ok())
+        .map(local_time::epoch_seconds);
+    let timestamp_clamped =
+        created.is_some_and(|(_, clamped)| clamped) || modified.is_some_and(|(_, clamped)| clamped);
+    let created = created.map(|(seconds, _)| seconds);
+    let modified = modified.map(|(seconds, _)| seconds);
+
+    let hints = name_hints::parse(&name, mod_extensions);
+    ModEntry {
+        name,
+        path: path_str,
+        size,
+        created,
+        modified,
+        created_local: created.and_then(local_time::format_local),
+        modified_local: modified.and_then(local_time::format_local),
+        timestamp_clamped,
+        changed_since_log: false,
+        name_author: hints.author,
+        name_title: hints.title,
+        name_version: hints.version,
+        link_target,
+        managed: false,
+        priority_folder: false,
+        mod_root: None,
+        volume: None,
+        is_directory,
+        directory_files: folder.map(|folder| folder.files),
+        size_is_estimate: folder.is_some_and(|folder| folder.truncated),
+        cloud_placeholder: metadata.as_ref().is_some_and(cloud_files::is_placeholder),
+        pinned: false,
+        first_seen: None,
+        coupled_conflicts: Vec::new(),
+        hardlink_count: physical.as_ref().map(|physical| physical.links),
+        file_id: physical.map(|physical| physical.id),
+    }
+}
+
+// ───────────────────────────────────────────────
+// Core Logic / 核心邏輯
+// ───────────────────────────────────────────────
+
+/// Thread pool for metadata lookups; stat() is latency-bound on network shares
+/// 用於讀取檔案資訊的執行緒池；網路磁碟上 stat() 主要受延遲影響
+pub(crate) static METADATA_POOL: LazyLock<ThreadPool> = LazyLock::new(|| {
+    ThreadPoolBuilder::new()
+        .num_threads(8)
+        .thread_name(|i| format!("mod-metadata-{}", i))
+        .build()
+        .expect("failed to build metadata thread pool")
+});
+
+/// Extract all mod conflicts from log text; `mod_extensions` go to `build_mod_entry`
+/// 從 log 文字中抓出所有模組衝突紀錄；`mod_extensions` 會傳給 `build_mod_entry`
+pub fn parse_conflicts(log: &str, game_path: &str, mod_extensions: &[String]) -> Vec<ModConflict> {
+    parse_conflicts_with_progress(
+        log,
+        game_path,
+        mod_extensions,
+        &progress::ignore,
+        &Timings::default(),
+    )
+}
+
+/// Extract the mod conflicts whose warning lies entirely within `range`; out-of-range bounds are
+/// clamped to the log. Line numbers stay those of the full log.
+/// 只抓出警告完整位於 `range` 內的模組衝突；超出範圍的邊界會限制在 log 內，行號仍以完整 log 為準
+pub fn parse_conflicts_in_range(
+    log: &str,
+    game_path: &str,
+    mod_extensions: &[String],
+    range: LineRange,
+) -> Vec<ModConflict> {
+    parse_matching(
+        log,
+        game_path,
+        mod_extensions,
+        Some(range),
+        &progress::ignore,
+        &Timings::default(),
+    )
+}
+
+/// Extract the mod conflicts from text pasted out of a chat or forum after undoing its
+/// formatting (see `paste_normalize`), flagging each as `lenient`. `range`, `line` and `span`
+/// refer to the text as pasted.
+/// 先還原從聊天軟體或論壇貼上之文字的格式（見 `paste_normalize`）再抓出模組衝突，並全部標記 `lenient`；
+/// `range`、`line` 與 `span` 皆以貼上時的文字為準
+pub fn parse_pasted_conflicts(
+    pasted: &str,
+    game_path: &str,
+    mod_extensions: &[String],
+    range: Option<LineRange>,
+) -> Vec<ModConflict> {
+    let normalized = paste_normalize::normalize_pasted(pasted);
+    let range = match range {
+        Some(range) => match normalized.normalized_range(range) {
+            Some(range) => Some(range),
+            None => return Vec::new(),
+        },
+        None => None,
+    };
+    let mut conflicts = parse_matching(
+        &normalized.text,
+        game_path,
+        mod_extensions,
+        range,
+        &progress::ignore,
+        &Timings::default(),
+    );
+    for conflict in &mut conflicts {
+        conflict.line = conflict.line.map(|line| normalized.original_line(line));
+        for line in &mut conflict.source_lines {
+            *line = normalized.original_line(*line);
+        }
+        conflict.span = conflict.span.map(|span| normalized.original_span(span));
+        for span in &mut conflict.source_spans {
+            *span = normalized.original_span(*span);
+        }
+        conflict.lenient = true;
+    }
+    conflicts
+}
+
+/// Extract all mod conflicts, reporting each file whose metadata has been read.
+/// Records `regex_ms` and `metadata_ms` in `timings`.
+/// 抓出所有模組衝突，並在讀完每個檔案資訊時回報進度；於 `timings` 記錄 `regex_ms` 與 `metadata_ms`
+pub fn parse_conflicts_with_progress(
+    log: &str,
+    game_path: &str,
+    mod_extensions: &[String],
+    on_progress: OnProgress,
+    timings: &Timings,
+) -> Vec<ModConflict> {
+    parse_matching(log, game_path, mod_extensions, None, on_progress, timings)
+}
+
+/// Counts lines up to increasing byte offsets, so a pass over the matches reads the log once
+/// 計算到遞增位元組位置為止的行數，讓逐一處理比對結果時只需讀過 log 一次
+struct LineCounter<'a> {
+    log: &'a str,
+    offset: usize,
+    line: usize,
+}
+
+impl LineCounter<'_> {
+    /// 1-based line holding the byte at `offset`; offsets must not decrease
+    /// `offset` 位置的位元組所在行號（從 1 起算）；位置不可遞減
+    fn line_at(&mut self, offset: usize) -> usize {
+        self.line += self.log.as_bytes()[self.offset..offset]
+            .iter()
+            .filter(|&&byte| byte == b'\n')
+            .count();
+        self.offset = offset;
+        self.line
+    }
+}
+
+/// Counts UTF-16 code units up to increasing byte offsets, like `LineCounter`
+/// 計算到遞增位元組位置為止的 UTF-16 code unit 數，與 `LineCounter` 相同
+struct Utf16Counter<'a> {
+    log: &'a str,
+    offset: usize,
+    units: usize,
+}
+
+impl Utf16Counter<'_> {
+    /// UTF-16 offset of the byte at `offset`, a char boundary; offsets must not decrease
+    /// `offset` 位置（須為字元邊界）的 UTF-16 位置；位置不可遞減
+    fn units_at(&mut self, offset: usize) -> usize {
+        self.units += self.log[self.offset..offset].encode_utf16().count();
+        self.offset = offset;
+        self.units
+    }
+}
+
+/// Key under which log paths name the same file / log 路徑指向同一檔案時使用的 key
+fn rel_key(rel_path: &str) -> String {
+    rel_path.replace('\\', "/").to_lowercase()
+}
+
+/// Merge warnings whose loaded file is the same into the first of them, unioning the skipped
+/// files in log order; a skipped path that repeats or is the loaded file itself is dropped
+/// 將 loaded 檔案相同的警告合併至第一個，並依 log 順序聯集 skipped 檔案；重複或與 loaded 檔案相同的 skipped 路徑會被捨棄
+fn merge_blocks(raw: Vec<(usize, Span, String, Vec<String>)>) -> Vec<RawBlock> {
+    let mut blocks = Vec::<RawBlock>::new();
+    let mut by_loaded = HashMap::new();
+    for (line, span, loaded, skipped) in raw {
+        let index = *by_loaded.entry(rel_key(&loaded)).or_insert_with(|| {
+            blocks.push(RawBlock {
+                lines: Vec::new(),
+                spans: Vec::new(),
+                loaded,
+                skipped: Vec::new(),
+            });
+            blocks.len() - 1
+        });
+        let block = &mut blocks[index];
+        block.lines.push(line);
+        block.spans.push(span);
+        let mut seen = iter::once(&block.loaded)
+            .chain(&block.skipped)
+            .map(|path| rel_key(path))
+            .collect::<HashSet<_>>();
+        block.skipped.extend(
+            skipped
+                .into_iter()
+                .filter(|path| seen.insert(rel_key(path))),
+        );
+    }
+    blocks.retain(|block| !block.skipped.is_empty());
+    blocks
+}
+
+/// Extract the conflicts, keeping only warnings within `range` when one is given. Warnings
+/// with the same loaded file become one conflict (see `merge_blocks`). The warning wording
+/// matched follows the Sideloader version the log announces, every known wording when it
+/// names none. Lines and spans count from the start of `log`, also when `range` is given.
+/// 抓出衝突；提供 `range` 時只保留位於範圍內的警告；loaded 檔案相同的警告合併為一個衝突（見 `merge_blocks`）；
+/// 比對的警告措辭依 log 記錄的 Sideloader 版本而定，未記錄版本時比對所有已知措辭；
+/// 即使提供 `range`，行號與位置仍從 `log` 開頭起算
+fn parse_matching(
+    log: &str,
+    game_path: &str,
+    mod_extensions: &[String],
+    range: Option<LineRange>,
+    on_progress: OnProgress,
+    timings: &Timings,
+) -> Vec<ModConflict> {
+    let patterns = patterns::patterns();
+    let roots = mod_roots::mod_roots(Path::new(game_path));
+    let volumes = roots
+        .iter()
+        .map(|root| disk_space::volume_name(root))
+        .collect::<Vec<_>>();
+    let mut lines = LineCounter {
+        log,
+        offset: 0,
+        line: 1,
+    };
+    let mut units = Utf16Counter {
+        log,
+        offset: 0,
+        units: 0,
+    };
+    let last_line = log.lines().count().max(1);
+    let range = range.map(|range| range.start.clamp(1, last_line)..=range.end.min(last_line));
+
+    let pattern_set = log_environment::detect(log).pattern_set;
+
+    let raw = timings.time("regex_ms", || {
+        let mut captures = pattern_set
+            .conflict_patterns()
+            .into_iter()
+            .flat_map(|pattern| pattern.captures_iter(log))
+            .collect::<Vec<_>>();
+        // Line numbers are counted forward, so matches of several patterns go in log order
+        // 行號為順向計算，因此多個樣式的比對結果須依 log 順序排列
+        captures.sort_by_key(|cap| cap.get(0).map_or(0, |whole| whole.start()));
+        captures
+            .into_iter()
+            .filter_map(|cap| {
+                let whole = cap.get(0)?;
+                // The match may swallow whitespace after a trailing separator; the warning ends
+                // at its last visible character
+                // 比對結果可能包含結尾分隔符號後的空白；警告以最後一個可見字元為結尾
+                let visible_end = whole.start() + whole.as_str().trim_end().len();
+                let line = lines.line_at(whole.start());
+                let end_line = lines.line_at(visible_end.saturating_sub(1).max(whole.start()));
+                if let Some(range) = &range {
+                    if !range.contains(&line) || !range.contains(&end_line) {
+                        return None;
+                    }
+                }
+                let span = Span {
+                    start: units.units_at(whole.start()),
+                    end: units.units_at(visible_end),
+                };
+                Some((line, span, cap))
+            })
+            .map(|(line, span, cap)| {
+                let loaded_rel = cap["loaded"].to_string();
+                // Empty or blank entries come from a trailing separator, not a real file
+                // 空白項目來自結尾多餘的分隔符號，並非實際檔案
+                let skipped_rel = patterns
+                    .quoted_path
+                    .captures_iter(&cap["skipped"])
+                    .map(|path| path[1].trim().to_string())
+                    .filter(|path| !path.is_empty())
+                    .collect::<Vec<_>>();
+                (line, span, loaded_rel, skipped_rel)
+            })
+            .filter(|(_, _, _, skipped_rel)| !skipped_rel.is_empty())
+            .collect::<Vec<_>>()
+    });
+    let raw = merge_blocks(raw);
+
+    // Stat every member of every conflict in parallel; collect() keeps the input order
+    // 平行讀取所有衝突成員的檔案資訊；collect() 會保留原本順序
+    let rel_paths = raw
+        .iter()
+        .flat_map(|block| iter::once(&block.loaded).chain(&block.skipped))
+        .collect::<Vec<_>>();
+    let total = rel_paths.len();
+    let done = AtomicUsize::new(0);
+    let mut entries = timings
+        .time("metadata_ms", || {
+            METADATA_POOL.install(|| {
+                rel_paths
+                    .par_iter()
+                    .map(|rel_path| {
+                        let (path, root) = mod_roots::resolve(&roots, rel_path);
+                        let mut entry = build_mod_entry(&path, rel_path, mod_extensions);
+                        entry.mod_root = Some(long_path::display(root));
+                        entry.volume = roots
+                            .iter()
+                            .position(|candidate| candidate == root)
+                            .and_then(|index| volumes[index].clone());
+                        let current = done.fetch_add(1, Ordering::Relaxed) + 1;
+                        on_progress(Progress::new(current, total));
+                        entry
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .into_iter();
+
+    raw.into_iter()
+        .filter_map(|block| {
+            let loaded = entries.next()?;
+            let skipped = entries.by_ref().take(block.skipped.len()).collect();
+            let mut conflict = ModConflict::new(loaded, skipped);
+            conflict.line = block.lines.first().copied();
+            conflict.span = block.spans.first().copied();
+            conflict.source_blocks = block.lines.len();
+            if block.lines.len() > 1 {
+                conflict.source_lines = block.lines;
+                conflict.source_spans = block.spans;
+            }
+            Some(conflict)
+        })
+        .collect()
+}
+
+/// Re-stat every member of cached conflicts, since files may have changed while the log did not
+/// 重新讀取快取衝突中每個檔案的資訊；log 未變但檔案可能已變動
+pub fn refresh_conflict_entries(
+    conflicts: Vec<ModConflict>,
+    mod_extensions: &[String],
+    on_progress: OnProgress,
+) -> Vec<ModConflict> {
+    let total = conflicts.iter().map(|c| 1 + c.skipped.len()).sum();
+    let done = AtomicUsize::new(0);
+    let refresh = |entry: ModEntry| {
+        let mut refreshed = build_mod_entry(Path::new(&entry.path), &entry.name, mod_extensions);
+        refreshed.mod_root = entry.mod_root;
+        refreshed.volume = entry.volume;
+        let current = done.fetch_add(1, Ordering::Relaxed) + 1;
+        on_progress(Progress::new(current, total));
+        refreshed
+    };
+    METADATA_POOL.install(|| {
+        conflicts
+            .into_par_iter()
+            .map(|conflict| {
+                let mut refreshed = ModConflict::new(
+                    refresh(conflict.loaded),
+                    conflict.skipped.into_iter().map(refresh).collect(),
+                );
+                refreshed.line = conflict.line;
+                refreshed.source_blocks = conflict.source_blocks;
+                refreshed.source_lines = conflict.source_lines;
+                refreshed.span = conflict.span;
+                refreshed.source_spans = conflict.source_spans;
+                refreshed
+            })
+            .collect()
+    })
+}
+
+/// Re-stat every member of `conflicts` in place, in parallel, keeping ids, notes, log lines and
+/// each entry's mod root, volume and coupled conflicts, which depend on paths alone. Entry
+/// annotations such as `managed` are reset and must be applied again.
+/// 平行地就地重新讀取 `conflicts` 中每個成員的檔案資訊，保留 id、筆記、log 行號與各項目的模組資料夾、磁碟區及關聯衝突（只取決於路徑）；
+/// `managed` 等項目標記會被重設，需重新套用
+pub fn restat_conflicts(conflicts: &mut [ModConflict], mod_extensions: &[String]) {
+    let restat = |entry: &mut ModEntry| {
+        let (mod_root, volume) = (entry.mod_root.take(), entry.volume.take());
+        let coupled = mem::take(&mut entry.coupled_conflicts);
+        *entry = build_mod_entry(Path::new(&entry.path), &entry.name, mod_extensions);
+        entry.mod_root = mod_root;
+        entry.volume = volume;
+        entry.coupled_conflicts = coupled;
+    };
+    METADATA_POOL.install(|| {
+        conflicts.par_iter_mut().for_each(|conflict| {
+            restat(&mut conflict.loaded);
+            conflict.skipped.par_iter_mut().for_each(restat);
+            conflict.update_members();
+        })
+    });
+}
+
+/// Flag entries (and their conflicts) whose file was created or modified after `log_modified`.
+/// Copying a file keeps its mtime but not its creation time, so both are checked.
+/// 標記在 `log_modified` 之後才建立或修改的檔案（及其衝突）；複製檔案會保留修改時間但不保留建立時間，因此兩者都檢查
+pub fn mark_changed_since_log(conflicts: &mut [ModConflict], log_modified: Option<u64>) {
+    let Some(log_modified) = log_modified else {
+        return;
+    };
+    let mark = |entry: &mut ModEntry| {
+        entry.changed_since_log = entry.created.max(entry.modified) > Some(log_modified);
+    };
+    for conflict in conflicts {
+        mark(&mut conflict.loaded);
+        conflict.skipped.iter_mut().for_each(mark);
+        conflict.changed_since_log = conflict.loaded.changed_since_log
+            || conflict.skipped.iter().any(|e| e.changed_since_log);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{
+        build_mod_entry, parse_conflicts, parse_conflicts_in_range, parse_pasted_conflicts,
+        LineRange, Span,
+    };
+    use crate::{
+        long_path,
+        profiles::default_mod_extensions,
+        test_support::{SyntheticInstall, SyntheticLog, TestManifest},
+    };
+
+    /// Names of the skipped files of each conflict parsed from `warnings`
+    /// 從 `warnings` 解析出的各衝突中被跳過檔案的名稱
+    fn skipped_names(warnings: &[&str]) -> Vec<Vec<String>> {
+        let log = warnings
+            .iter()
+            .map(|warning| {
+                format!(
+                    "[Warning:Sideloader] Multiple versions detected, {}",
+                    warning
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        parse_conflicts(&log, "C:/Game", &default_mod_extensions())
+            .iter()
+            .map(|conflict| conflict.skipped.iter().map(|e| e.name.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn skipped_list_with_a_single_entry() {
+        let names =
+            skipped_names(&[r#"only "a.zipmod" will be loaded. Skipped versions: "b.zipmod""#]);
+
+        assert_eq!(names, [["b.zipmod"]]);
+    }
+
+    #[test]
+    fn skipped_list_with_a_trailing_comma() {
+        let names = skipped_names(&[
+            r#"only "a.zipmod" will be loaded. Skipped versions: "b.zipmod", "c.zipmod", "#,
+            r#"only "x.zipmod" will be loaded. Skipped versions: "y.zipmod","#,
+        ]);
+
+        assert_eq!(names, [vec!["b.zipmod", "c.zipmod"], vec!["y.zipmod"]]);
+    }
+
+    #[test]
+    fn skipped_list_with_extra_whitespace() {
+        let names = skipped_names(&[
+            "only  \"a.zipmod\"  will be loaded. Skipped versions:   \"b.zipmod\" ,\t\"c.zipmod\"  ",
+            r#"only "x.zipmod" will be loaded. Skipped versions: "y.zipmod",   "",  " z.zipmod ""#,
+        ]);
+
+        assert_eq!(
+            names,
+            [vec!["b.zipmod", "c.zipmod"], vec!["y.zipmod", "z.zipmod"]]
+        );
+    }
+
+    #[test]
+    fn range_drops_a_warning_straddling_its_boundary() {
+        // The second warning's skipped list wraps onto line 4; lines end in `\r\n` as
+        // the game writes them, with one bare `\n` mixed in
+        // 第二個警告的略過清單換行延續至第 4 行；行尾與遊戲寫出的相同為 `\r\n`，並混入一個單獨的 `\n`
+        let log = concat!(
+            "[Message:   BepInEx] BepInEx 5.4.22.0 - Koikatu\r\n",
+            "[Warning:Sideloader] Multiple versions detected, only \"a.zipmod\" will be loaded. ",
+            "Skipped versions: \"b.zipmod\"\n",
+            "[Warning:Sideloader] Multiple versions detected, only \"c.zipmod\" will be loaded. ",
+            "Skipped versions: \"d.zipmod\",\r\n",
+            "  \"e.zipmod\"\r\n",
+            "[Warning:Sideloader] Multiple versions detected, only \"f.zipmod\" will be loaded. ",
+            "Skipped versions: \"g.zipmod\"\r\n",
+        );
+        let loaded = |start, end| {
+            parse_conflicts_in_range(
+                log,
+                "C:/Game",
+                &default_mod_extensions(),
+                LineRange { start, end },
+            )
+            .iter()
+            .map(|conflict| (conflict.loaded.name.clone(), conflict.line))
+            .collect::<Vec<_>>()
+        };
+        let at = |name: &str, line| (name.to_string(), Some(line));
+
+        assert_eq!(loaded(1, 3), [at("a.zipmod", 2)]);
+        assert_eq!(loaded(4, 5), [at("f.zipmod", 5)]);
+        assert_eq!(loaded(3, 4), [at("c.zipmod", 3)]);
+        assert_eq!(
+            loaded(2, 99),
+            [at("a.zipmod", 2), at("c.zipmod", 3), at("f.zipmod", 5)]
+        );
+        let wrapped = parse_conflicts_in_range(
+            log,
+            "C:/Game",
+            &default_mod_extensions(),
+            LineRange { start: 3, end: 4 },
+        );
+        assert_eq!(wrapped[0].skipped.len(), 2);
+    }
+
+    #[test]
+    fn blocks_with_the_same_loaded_file_merge() {
+        let install = SyntheticInstall::new();
+        let log = SyntheticLog::new();
+        let first = log.next_line();
+        let log = log
+            .conflict("a.zipmod", &["b.zipmod"])
+            .conflict("x.zipmod", &["y.zipmod"]);
+        let third = log.next_line();
+        let log = log
+            .conflict("A.zipmod", &["c.zipmod", "b.zipmod"])
+            .conflict("a.zipmod", &["d.zipmod"]);
+
+        let conflicts =
+            parse_conflicts(&log.text(), &install.game_path(), &default_mod_extensions());
+
+        assert_eq!(conflicts.len(), 2);
+        let merged = &conflicts[0];
+        let skipped = merged
+            .skipped
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(skipped, ["b.zipmod", "c.zipmod", "d.zipmod"]);
+        assert_eq!(merged.line, Some(first));
+        assert_eq!(merged.source_blocks, 3);
+        assert_eq!(merged.source_lines, [first, third, third + 1]);
+        assert_eq!(conflicts[1].source_blocks, 1);
+        assert!(conflicts[1].source_lines.is_empty());
+    }
+
+    /// The text of `text` within `span`, indexed in UTF-16 units as the frontend does
+    /// 以前端相同的 UTF-16 unit 索引取出 `text` 中 `span` 範圍的文字
+    fn span_text(text: &str, span: Span) -> String {
+        let units = text.encode_utf16().collect::<Vec<_>>();
+        String::from_utf16(&units[span.start..span.end]).unwrap()
+    }
+
+    #[test]
+    fn conflict_spans_cover_the_matched_warning() {
+        let install = SyntheticInstall::new();
+        let log = SyntheticLog::new()
+            .conflict("衣装/服 🌸.zipmod", &["手動/服.zipmod"])
+            .conflict("x.zipmod", &["y.zipmod", "z.zipmod"])
+            .conflict("衣装/服 🌸.zipmod", &["古い/服.zipmod"]);
+        let text = log.text();
+
+        let conflicts = parse_conflicts(&text, &install.game_path(), &default_mod_extensions());
+
+        let spans = conflicts
+            .iter()
+            .flat_map(|conflict| match conflict.source_spans.is_empty() {
+                true => vec![conflict.span.unwrap()],
+                false => conflict.source_spans.clone(),
+            })
+            .map(|span| span_text(&text, span))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            [
+                r#"only "衣装/服 🌸.zipmod" will be loaded. Skipped versions: "手動/服.zipmod""#,
+                r#"only "衣装/服 🌸.zipmod" will be loaded. Skipped versions: "古い/服.zipmod""#,
+                r#"only "x.zipmod" will be loaded. Skipped versions: "y.zipmod", "z.zipmod""#,
+            ]
+        );
+        assert_eq!(conflicts[0].span, Some(conflicts[0].source_spans[0]));
+    }
+
+    #[test]
+    fn range_keeps_only_warnings_inside_it() {
+        let install = SyntheticInstall::new();
+        let log = SyntheticLog::new().conflict("a.zipmod", &["b.zipmod"]);
+        let second = log.next_line();
+        let log = log.conflict("c.zipmod", &["d.zipmod"]);
+
+        let conflicts = parse_conflicts_in_range(
+            &log.text(),
+            &install.game_path(),
+            &default_mod_extensions(),
+            LineRange {
+                start: second,
+                end: second,
+            },
+        );
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].loaded.name, "c.zipmod");
+        assert_eq!(conflicts[0].line, Some(second));
+    }
+
+    #[test]
+    fn pasted_chat_log_matches_the_original() {
+        let install = SyntheticInstall::new();
+        install.add_zipmod("a.zipmod", &TestManifest::new("a"));
+        install.add_zipmod("b.zipmod", &TestManifest::new("a"));
+        let log = SyntheticLog::new().conflict("a.zipmod", &["b.zipmod"]);
+        let pasted = log
+            .text()
+            .lines()
+            .map(|line| format!("[12:34] user: {}", line.replace('"', "\u{201C}")))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let pasted_conflicts = parse_pasted_conflicts(
+            &pasted,
+            &install.game_path(),
+            &default_mod_extensions(),
+            None,
+        );
+        let conflicts =
+            parse_conflicts(&log.text(), &install.game_path(), &default_mod_extensions());
+
+        assert_eq!(pasted_conflicts.len(), 1);
+        assert_eq!(pasted_conflicts[0].id, conflicts[0].id);
+        assert_eq!(pasted_conflicts[0].line, conflicts[0].line);
+        assert!(pasted_conflicts[0].lenient);
+    }
+
+    #[test]
+    fn pasted_conflict_span_points_into_the_paste() {
+        let install = SyntheticInstall::new();
+        let pasted = [
+            "[12:33] user: here is my log 🌸🌸",
+            "[12:34] user: [Warning:Sideloader] Multiple versions detected, only \u{201C}衣装/服 v2.\
+             zipmod\u{201D} will be loaded. Skipped versions: \u{201C}服 v1.zipmod\u{201D}",
+            "[12:35] user: thanks",
+        ]
+        .join("\r\n");
+
+        let conflicts = parse_pasted_conflicts(
+            &pasted,
+            &install.game_path(),
+            &default_mod_extensions(),
+            None,
+        );
+
+        let span = conflicts[0].span.unwrap();
+        assert_eq!(
+            span_text(&pasted, span),
+            "only \u{201C}衣装/服 v2.zipmod\u{201D} will be loaded. \
+             Skipped versions: \u{201C}服 v1.zipmod\u{201D}"
+        );
+    }
+
+    #[test]
+    fn build_mod_entry_reads_file_and_name() {
+        let install = SyntheticInstall::new();
+        let path = install.add_zipmod(
+            "nested/folder/[Someone] Cool Outfit v1.2.zipmod",
+            &TestManifest::new("someone.outfit"),
+        );
+
+        let entry = build_mod_entry(
+            &path,
+            "nested/folder/[Someone] Cool Outfit v1.2.zipmod",
+            &default_mod_extensions(),
+        );
+
+        assert_eq!(entry.name, "[Someone] Cool Outfit v1.2.zipmod");
+        assert_eq!(entry.path, long_path::display(&path));
+        assert_eq!(entry.size, fs::metadata(&path).unwrap().len());
+        assert!(entry.modified.is_some());
+        assert!(!entry.is_directory);
+        assert_eq!(entry.name_author.as_deref(), Some("Someone"));
+        assert_eq!(entry.name_version.as_deref(), Some("v1.2"));
+    }
+
+    #[test]
+    fn build_mod_entry_of_missing_file_is_empty() {
+        let install = SyntheticInstall::new();
+        let path = install.mod_path("missing.zipmod");
+
+        let entry = build_mod_entry(&path, "missing.zipmod", &default_mod_extensions());
+
+        assert_eq!(entry.name, "missing.zipmod");
+        assert_eq!(entry.size, 0);
+        assert_eq!(entry.modified, None);
+    }
+}
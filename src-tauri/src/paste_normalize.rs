@@ -0,0 +1,184 @@
+use crate::{
+    parser::{LineRange, Span},
+    patterns,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// How far past a line break inside quotes the closing quote may be for the break to count as
+/// a soft wrap; further away, the quote is taken as unbalanced chat text
+/// 引號內換行後，結尾引號需在此距離內才視為自動換行；超過時視為聊天文字中不成對的引號
+const MAX_WRAPPED_QUOTE: usize = 512;
+
+/// Quotation marks chat clients substitute for `"`
+/// 聊天軟體用來取代 `"` 的引號
+const DOUBLE_QUOTES: &[char] = &[
+    '\u{201C}', '\u{201D}', '\u{201E}', '\u{201F}', '\u{2033}', '\u{FF02}',
+];
+
+/// Quotation marks chat clients substitute for `'`
+/// 聊天軟體用來取代 `'` 的引號
+const SINGLE_QUOTES: &[char] = &['\u{2018}', '\u{2019}', '\u{201A}', '\u{201B}'];
+
+/// Pasted text ready for the log parser
+/// 可交給 log 解析器的貼上文字
+pub(crate) struct NormalizedPaste {
+    pub text: String,
+    /// 1-based pasted line each normalized line starts on; index 0 is normalized line 1
+    /// 各正規化行起始於貼上文字的第幾行（從 1 起算）；索引 0 為正規化後的第 1 行
+    original_lines: Vec<usize>,
+    /// Lines of the pasted text / 貼上文字的行數
+    pasted_lines: usize,
+    /// UTF-16 offset in the normalized text and in the paste where each stripped line starts,
+    /// in order; text within a line keeps its offset relative to that start
+    /// 各行移除前綴後，在正規化文字與貼上文字中起始的 UTF-16 位置（依序）；同一行內的文字與起點的相對位置不變
+    segments: Vec<(usize, usize)>,
+}
+
+// ───────────────────────────────────────────────
+// Normalization / 正規化
+// ───────────────────────────────────────────────
+
+/// One pasted line without its chat timestamp, sender name and quote marker
+/// 移除聊天時間戳記、發言者名稱與引用符號後的單行貼上文字
+fn strip_chat_prefix(line: &str) -> &str {
+    let patterns = patterns::patterns();
+    let line = match patterns.paste_timestamp.find(line) {
+        Some(timestamp) => {
+            let rest = &line[timestamp.end()..];
+            patterns
+                .paste_chat_prefix
+                .find(rest)
+                .map_or(rest, |name| &rest[name.end()..])
+        }
+        None => line,
+    };
+    let trimmed = line.trim_start();
+    match trimmed.strip_prefix('>') {
+        Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+        None => line,
+    }
+}
+
+/// Whether a `"` follows within `MAX_WRAPPED_QUOTE` bytes of `rest`
+/// 判斷 `rest` 的 `MAX_WRAPPED_QUOTE` 位元組內是否有 `"`
+fn quote_closes_soon(rest: &str) -> bool {
+    rest.char_indices()
+        .take_while(|(index, _)| *index < MAX_WRAPPED_QUOTE)
+        .any(|(_, c)| c == '"')
+}
+
+/// Undo what copying a log out of Discord or a forum does to it: curly quotes become straight,
+/// chat timestamps and sender names are stripped from each line, and line breaks inside a
+/// quoted string are removed when its closing quote follows shortly. Removed breaks may have
+/// been spaces, so resolved paths are a best guess.
+/// 還原從 Discord 或論壇複製 log 時造成的變形：彎引號改為直引號，移除每行的聊天時間戳記與發言者名稱，
+/// 並在結尾引號緊接在後時移除引號字串中的換行；被移除的換行原本可能是空白，因此解析出的路徑僅為推測
+pub(crate) fn normalize_pasted(pasted: &str) -> NormalizedPaste {
+    let straightened = pasted
+        .replace(DOUBLE_QUOTES, "\"")
+        .replace(SINGLE_QUOTES, "'");
+    let lines = straightened
+        .lines()
+        .map(strip_chat_prefix)
+        .collect::<Vec<_>>();
+
+    let mut text = String::with_capacity(straightened.len());
+    let mut original_lines = vec![1];
+    let mut segments = Vec::with_capacity(lines.len());
+    let (mut units, mut pasted_units, mut pasted_offset) = (0, 0, 0);
+    let mut in_quote = false;
+    for (index, line) in lines.iter().enumerate() {
+        // Every line is a slice of `straightened`, and straightening keeps each character one
+        // UTF-16 unit, so offsets into it are offsets into the paste
+        // 每行皆為 `straightened` 的切片，且改為直引號後每個字元仍為一個 UTF-16 unit，因此其位置即貼上文字中的位置
+        let line_offset = line.as_ptr() as usize - straightened.as_ptr() as usize;
+        pasted_units += straightened[pasted_offset..line_offset]
+            .encode_utf16()
+            .count();
+        pasted_offset = line_offset;
+        segments.push((units, pasted_units));
+        for c in line.chars() {
+            if c == '"' {
+                in_quote = !in_quote;
+            }
+            units += c.len_utf16();
+            text.push(c);
+        }
+        let Some(next) = lines.get(index + 1) else {
+            break;
+        };
+        if in_quote && quote_closes_soon(next) {
+            continue;
+        }
+        // An unclosed quote is chat text, not a wrapped path; start the next line fresh
+        // 未閉合的引號屬於聊天文字而非被折行的路徑；下一行重新計算
+        in_quote = false;
+        units += 1;
+        text.push('\n');
+        original_lines.push(index + 2);
+    }
+
+    NormalizedPaste {
+        text,
+        original_lines,
+        pasted_lines: lines.len().max(1),
+        segments,
+    }
+}
+
+impl NormalizedPaste {
+    /// Pasted line a normalized line starts on
+    /// 正規化行起始於貼上文字的哪一行
+    pub fn original_line(&self, line: usize) -> usize {
+        self.original_lines
+            .get(line.saturating_sub(1))
+            .copied()
+            .unwrap_or(self.pasted_lines)
+    }
+
+    /// `span` of the normalized text as a span of the paste. Both ends are exact; text removed
+    /// in between, such as a chat prefix after a soft wrap, falls inside the mapped span.
+    /// 將正規化文字的 `span` 轉為貼上文字中的位置；兩端皆精確，其間被移除的文字（例如自動換行後的聊天前綴）會包含在轉換後的範圍內
+    pub fn original_span(&self, span: Span) -> Span {
+        // An exclusive end belongs to the segment of the character before it
+        // 不含的結尾屬於其前一個字元所在的區段
+        let map = |offset: usize, exclusive: bool| {
+            let index = self
+                .segments
+                .partition_point(|&(start, _)| start < offset || (!exclusive && start == offset));
+            match index.checked_sub(1).map(|index| self.segments[index]) {
+                Some((start, pasted)) => pasted + offset - start,
+                None => offset,
+            }
+        };
+        Span {
+            start: map(span.start, false),
+            end: map(span.end, true),
+        }
+    }
+
+    /// The normalized lines lying entirely within `range` of the pasted text, or None when
+    /// none do. Bounds are clamped to the paste as `parse_conflicts_in_range` does.
+    /// 完整位於貼上文字 `range` 內的正規化行範圍，沒有任何一行符合時為 None；
+    /// 邊界與 `parse_conflicts_in_range` 相同，會限制在貼上文字內
+    pub fn normalized_range(&self, range: LineRange) -> Option<LineRange> {
+        let start = range.start.clamp(1, self.pasted_lines);
+        let end = range.end.min(self.pasted_lines);
+        let last_of = |index: usize| {
+            self.original_lines
+                .get(index + 1)
+                .map_or(self.pasted_lines, |next| next - 1)
+        };
+        let first = self.original_lines.iter().position(|&line| line >= start)?;
+        let last = (0..self.original_lines.len())
+            .rev()
+            .find(|&index| last_of(index) <= end)?;
+        (first <= last).then_some(LineRange {
+            start: first + 1,
+            end: last + 1,
+        })
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{app_log, error::AppError, long_path, mod_roots};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Game subdirectories that destructive commands may touch
+/// 破壞性指令允許操作的遊戲子資料夾
+const ALLOWED_SUBDIRS: &[&[&str]] = &[&["mods"], &["BepInEx", "plugins"], &["quarantine"]];
+
+/// Whether game files may be changed, from the `read_only_mode` app setting. Commands take it
+/// from `Operations` and pass it down to everything that writes to game files.
+/// 是否允許變更遊戲檔案，來自 `read_only_mode` 全域設定；指令從 `Operations` 取得後傳給所有會寫入遊戲檔案的函式
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub(crate) enum WriteMode {
+    #[default]
+    Writable,
+    /// Nothing under a game folder may be changed / 不得變更遊戲資料夾中的任何內容
+    ReadOnly,
+}
+
+/// Canonicalized directories that destructive commands are allowed to modify
+/// 已正規化、允許破壞性指令修改的資料夾
+pub(crate) struct AllowedRoots {
+    roots: Vec<PathBuf>,
+}
+
+// ───────────────────────────────────────────────
+// Read-Only Mode / 唯讀模式
+// ───────────────────────────────────────────────
+
+impl WriteMode {
+    pub fn from_read_only(read_only: bool) -> Self {
+        match read_only {
+            true => WriteMode::ReadOnly,
+            false => WriteMode::Writable,
+        }
+    }
+
+    pub fn is_read_only(self) -> bool {
+        self == WriteMode::ReadOnly
+    }
+
+    /// Refuse to change `path` in read-only mode. Called by everything that writes to game
+    /// files: `resolve_targets`, `OperationGuard::lock_paths`, the `ops`, `quarantine` and
+    /// `transactions` primitives. A command that locks its paths or goes through them is
+    /// covered without checking itself, and is refused before it touches anything.
+    /// 唯讀模式下拒絕變更 `path`；所有會寫入遊戲檔案的路徑皆會呼叫：`resolve_targets`、`OperationGuard::lock_paths`，
+    /// 以及 `ops`、`quarantine` 與 `transactions` 的基本操作；鎖定路徑或經由這些操作的指令無須自行檢查，且會在變更任何內容前被拒絕
+    pub fn ensure_writable(self, path: &Path) -> Result<(), AppError> {
+        match self {
+            WriteMode::ReadOnly => Err(AppError::ReadOnlyMode {
+                path: long_path::display(path),
+            }),
+            WriteMode::Writable => Ok(()),
+        }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Validation / 路徑檢查
+// ───────────────────────────────────────────────
+
+impl AllowedRoots {
+    /// Collect the allowed directories of every registered game path, including Sideloader's
+    /// additional mods directory; missing ones are skipped
+    /// 收集所有已登記遊戲路徑的允許資料夾（含 Sideloader 額外 mods 資料夾）；不存在的資料夾略過
+    pub fn for_games<S: AsRef<str>>(game_paths: &[S]) -> Self {
+        let roots = game_paths
+            .iter()
+            .flat_map(|game_path| {
+                let game_root = PathBuf::from(game_path.as_ref());
+                let additional = mod_roots::additional_dir(&game_root);
+                ALLOWED_SUBDIRS
+                    .iter()
+                    .map(move |parts| {
+                        parts
+                            .iter()
+                            .fold(game_root.clone(), |dir, part| dir.join(part))
+                    })
+                    .chain(additional)
+                    .filter_map(|dir| fs::canonicalize(long_path::extend(&dir)).ok())
+            })
+            .collect();
+        AllowedRoots { roots }
+    }
+
+    /// Canonicalize `path` (resolving `..` and symlinks) and make sure it lies strictly
+    /// inside one of the allowed roots
+    /// 正規化路徑（解析 `..` 與符號連結），並確認其位於某個允許資料夾之內
+    pub fn check(&self, path: &str) -> Result<PathBuf, AppError> {
+        let not_allowed = || AppError::PathNotAllowed {
+            path: path.to_string(),
+        };
+        let canonical =
+            fs::canonicalize(long_path::extend(Path::new(path))).map_err(|_| not_allowed())?;
+        let inside = self
+            .roots
+            .iter()
+            .any(|root| canonical != *root && canonical.starts_with(root));
+        if inside {
+            Ok(canonical)
+        } else {
+            Err(not_allowed())
+        }
+    }
+
+    /// Check every path up front so a batch is rejected before anything is touched
+    /// 先檢查所有路徑，任何一個不合法就整批拒絕
+    pub fn check_all(&self, paths: &[String]) -> Result<Vec<PathBuf>, AppError> {
+        paths.iter().map(|path| self.check(path)).collect()
+    }
+}
+
+/// Resolve the targets of a destructive command. With `unsafe_allow_any_path` the guard
+/// is bypassed, which is written to the app log every time; `write_mode` is not.
+/// 解析破壞性指令的目標路徑；開啟 `unsafe_allow_any_path` 時略過檢查，且每次都會寫入 app log；`write_mode` 不會被略過
+pub(crate) fn resolve_targets<S: AsRef<str>>(
+    game_paths: &[S],
+    paths: &[String],
+    unsafe_allow_any_path: bool,
+    write_mode: WriteMode,
+) -> Result<Vec<PathBuf>, AppError> {
+    paths
+        .iter()
+        .try_for_each(|path| write_mode.ensure_writable(Path::new(path)))?;
+    if unsafe_allow_any_path {
+        app_log::write(&format!(
+            "WARNING: unsafe_allow_any_path is enabled; skipping path-safety checks for {} path(s): {:?}",
+            paths.len(),
+            paths
+        ));
+        return Ok(paths
+            .iter()
+            .map(|path| Path::new(path).to_path_buf())
+            .collect());
+    }
+    AllowedRoots::for_games(game_paths).check_all(paths)
+}
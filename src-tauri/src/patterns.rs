@@ -0,0 +1,165 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+// ───────────────────────────────────────────────
+// Built-in Patterns / 內建比對樣式
+// ───────────────────────────────────────────────
+
+/// Sideloader duplicate warning:
+/// `only "a.zipmod" will be loaded. Skipped versions: "b.zipmod", "c.zipmod"`
+/// The skipped list may be a single entry or end with a stray `, ` from a Sideloader bug
+/// Sideloader 重複模組警告訊息；略過清單可能只有一項，或因 Sideloader 的錯誤在結尾多出 `, `
+const SIDELOADER_CONFLICT: &str = concat!(
+    r#"only\s+"(?P<loaded>[^"]+)"\s+will be loaded\. "#,
+    r#"Skipped versions:\s*(?P<skipped>(?:"[^"]*"[ \t]*(?:,\s*)?)+)"#
+);
+
+/// Duplicate warning of Sideloader releases before the "Multiple versions detected" wording,
+/// one skipped copy per line: `Skipping "b.zipmod" because "a.zipmod" is already loaded`
+/// 「Multiple versions detected」措辭出現前的 Sideloader 版本的重複模組警告，每行一個被跳過的檔案
+const SIDELOADER_CONFLICT_LEGACY: &str = concat!(
+    r#"Skipping(?: duplicate)?\s+(?P<skipped>"[^"]+")\s+"#,
+    r#"because\s+"(?P<loaded>[^"]+)"\s+(?:is|was) already loaded"#
+);
+
+/// One quoted path inside the skipped list of `SIDELOADER_CONFLICT`
+/// `SIDELOADER_CONFLICT` 略過清單中的單一引號路徑
+const QUOTED_PATH: &str = r#""([^"]*)""#;
+
+/// Sideloader's resolver warning for a card or scene needing a mod that is not installed:
+/// `[UAR] WARNING! Missing mod detected! [com.author.mod]`, also logged as a failed
+/// compatibility resolve with the GUID in brackets
+/// Sideloader 解析器在角色卡或場景需要未安裝的模組時的警告；亦會以相容性解析失敗的形式記錄，GUID 位於方括號中
+const SIDELOADER_MISSING_MOD: &str = r"(?i)(?:missing mod detected|resolv(?:e|ing) failed)[^\[
+]*\[([^\]\s]+)\]";
+
+/// BepInEx's opening banner: `BepInEx 5.4.22.0 - Koikatu`, the process name after the dash
+/// BepInEx 的開頭橫幅：`BepInEx 5.4.22.0 - Koikatu`，破折號後為程序名稱
+const BEPINEX_BANNER: &str = r"(?m)\bBepInEx\s+v?(\d+(?:\.\d+)+)\s+-\s+([^\r\n(]+?)\s*(?:\(|$)";
+
+/// BepInEx loading the Sideloader plugin: `Loading [Sideloader 19.3]`
+/// BepInEx 載入 Sideloader 外掛時的訊息：`Loading [Sideloader 19.3]`
+const SIDELOADER_VERSION: &str = r"(?i)Loading \[Sideloader\s+v?(\d+(?:\.\d+)*)\]";
+
+/// Version word at the end of a mod filename: `v2`, `1.0.3`, `v1.2b`
+/// 模組檔名結尾的版本字詞：`v2`、`1.0.3`、`v1.2b`
+const NAME_VERSION: &str = r"(?i)^v?\d+(?:\.\d+)*[a-z]?$";
+
+/// Timestamp a chat client puts before a pasted line: `[12:34]`, `[2024-05-01 12:34:56]`,
+/// `[5/1/2024 12:34 PM]` or a bare `12:34 PM`. Bracketed BepInEx levels hold no time and stay.
+/// 聊天軟體加在貼上行開頭的時間戳記；BepInEx 的 `[Warning : Sideloader]` 不含時間，不會被移除
+const PASTE_TIMESTAMP: &str =
+    r"^\s*(?:\[[^\]\n]*\d{1,2}:\d{2}[^\]\n]*\]|\d{1,2}:\d{2}(?::\d{2})?(?:\s*[AaPp][Mm])?\s)\s*";
+
+/// Sender name right after a stripped timestamp: `User: ` or `<User> `
+/// 緊接在已移除時間戳記之後的發言者名稱：`User: ` 或 `<User> `
+const PASTE_CHAT_PREFIX: &str = r#"^(?:<[^>\n]{1,32}>|[^\s:"\[\]<>]{1,32}:)\s+"#;
+
+/// Patterns compiled once for the lifetime of the app
+/// 整個程式生命週期只編譯一次的比對樣式
+pub(crate) struct Patterns {
+    pub sideloader_conflict: Regex,
+    pub sideloader_conflict_legacy: Regex,
+    pub quoted_path: Regex,
+    pub bepinex_banner: Regex,
+    pub sideloader_version: Regex,
+    pub sideloader_missing_mod: Regex,
+    pub name_version: Regex,
+    pub paste_timestamp: Regex,
+    pub paste_chat_prefix: Regex,
+}
+
+static PATTERNS: LazyLock<Patterns> = LazyLock::new(|| Patterns {
+    sideloader_conflict: compile("sideloader_conflict", SIDELOADER_CONFLICT),
+    sideloader_conflict_legacy: compile("sideloader_conflict_legacy", SIDELOADER_CONFLICT_LEGACY),
+    quoted_path: compile("quoted_path", QUOTED_PATH),
+    bepinex_banner: compile("bepinex_banner", BEPINEX_BANNER),
+    sideloader_version: compile("sideloader_version", SIDELOADER_VERSION),
+    sideloader_missing_mod: compile("sideloader_missing_mod", SIDELOADER_MISSING_MOD),
+    name_version: compile("name_version", NAME_VERSION),
+    paste_timestamp: compile("paste_timestamp", PASTE_TIMESTAMP),
+    paste_chat_prefix: compile("paste_chat_prefix", PASTE_CHAT_PREFIX),
+});
+
+fn compile(name: &str, pattern: &str) -> Regex {
+    Regex::new(pattern).unwrap_or_else(|e| panic!("Invalid built-in pattern {}: {}", name, e))
+}
+
+/// Shared compiled patterns
+/// 取得共用的已編譯樣式
+pub(crate) fn patterns() -> &'static Patterns {
+    &PATTERNS
+}
+
+/// Compile all built-in patterns now, so a bad pattern fails at startup instead of mid-scan
+/// 在啟動時先編譯所有內建樣式，讓錯誤在啟動時就出現而非掃描途中
+pub(crate) fn validate() {
+    LazyLock::force(&PATTERNS);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::patterns;
+
+    #[test]
+    fn each_pattern_matches_its_sample_line() {
+        let patterns = patterns();
+        let conflict = patterns
+            .sideloader_conflict
+            .captures(
+                r#"[Warning : Sideloader] Multiple versions detected, only "mods/a.zipmod" will be loaded. Skipped versions: "mods/b.zipmod", "mods/c.zipmod""#,
+            )
+            .unwrap();
+        assert_eq!(&conflict["loaded"], "mods/a.zipmod");
+        let skipped = patterns
+            .quoted_path
+            .captures_iter(&conflict["skipped"])
+            .map(|captures| captures[1].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(skipped, ["mods/b.zipmod", "mods/c.zipmod"]);
+
+        let legacy = patterns
+            .sideloader_conflict_legacy
+            .captures(
+                r#"Skipping duplicate "mods/b.zipmod" because "mods/a.zipmod" is already loaded"#,
+            )
+            .unwrap();
+        assert_eq!(&legacy["skipped"], r#""mods/b.zipmod""#);
+        assert_eq!(&legacy["loaded"], "mods/a.zipmod");
+
+        let banner = patterns
+            .bepinex_banner
+            .captures("[Message:   BepInEx] BepInEx 5.4.22.0 - Koikatu (2024/05/01 12:34:56)")
+            .unwrap();
+        assert_eq!((&banner[1], &banner[2]), ("5.4.22.0", "Koikatu"));
+
+        let version = patterns
+            .sideloader_version
+            .captures("[Info   :   BepInEx] Loading [Sideloader 19.3]")
+            .unwrap();
+        assert_eq!(&version[1], "19.3");
+
+        let missing = patterns
+            .sideloader_missing_mod
+            .captures("[Warning :Sideloader] [UAR] WARNING! Missing mod detected! [com.author.mod]")
+            .unwrap();
+        assert_eq!(&missing[1], "com.author.mod");
+
+        for word in ["v2", "1.0.3", "v1.2b"] {
+            assert!(patterns.name_version.is_match(word), "{}", word);
+        }
+        assert!(!patterns.name_version.is_match("hair"));
+
+        let line = "[12:34] User: [Warning : Sideloader] Multiple versions detected";
+        let timestamp = patterns.paste_timestamp.find(line).unwrap();
+        assert_eq!(timestamp.as_str(), "[12:34] ");
+        let rest = &line[timestamp.end()..];
+        let sender = patterns.paste_chat_prefix.find(rest).unwrap();
+        assert_eq!(sender.as_str(), "User: ");
+        assert!(patterns
+            .paste_timestamp
+            .find("[Warning : Sideloader] text")
+            .is_none());
+    }
+}
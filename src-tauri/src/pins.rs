@@ -0,0 +1,346 @@
+use std::{
+    fs, io, iter,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_log, checksums, cloud_files,
+    error::AppError,
+    long_path,
+    messages::Message,
+    operations::CancelToken,
+    parser::{ModConflict, ModEntry},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Pins file inside each profile directory
+/// 各 profile 資料夾中的釘選檔
+const PINS_FILE: &str = "pins.json";
+
+/// A file no automated flow may touch without `allow_pinned`, recognized by its path or, once
+/// renamed or moved, by its size and hash
+/// 未指定 `allow_pinned` 時任何自動流程都不可處理的檔案；以路徑辨識，改名或搬移後則以大小與雜湊辨識
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct PinnedFile {
+    /// Relative to the game folder with `/` separators; absolute for a file outside it, such as
+    /// in an additional mods folder on another drive
+    /// 相對於遊戲資料夾、以 `/` 分隔；位於遊戲資料夾外（例如其他磁碟上的額外 mods 資料夾）時為絕對路徑
+    pub path: String,
+    pub size: u64,
+    /// blake3 of the content when pinned / 釘選時內容的 blake3
+    pub hash: String,
+    /// Seconds since the Unix epoch when the file was pinned
+    /// 釘選的時間（Unix epoch 秒數）
+    pub pinned_at: u64,
+}
+
+/// A pin and whether its file is still at the pinned path
+/// 一個釘選項目，以及其檔案是否仍在釘選時的路徑
+#[derive(Serialize)]
+pub(crate) struct PinStatus {
+    #[serde(flatten)]
+    pub pin: PinnedFile,
+    pub present: bool,
+}
+
+/// The pins of one game, for checking paths against
+/// 單一遊戲的釘選項目，用於比對路徑
+pub(crate) struct PinSet {
+    game_root: PathBuf,
+    pins: Vec<PinnedFile>,
+    /// Canonical path of each pin's file while it exists, so a path reached through a linked
+    /// `mods` folder, as destructive commands pass them, still matches by path
+    /// 各釘選檔案存在時的正規化路徑，使經由連結的 `mods` 資料夾取得的路徑（破壞性指令傳入的形式）仍能以路徑比對
+    canonical: Vec<Option<PathBuf>>,
+}
+
+// ───────────────────────────────────────────────
+// Storage / 儲存
+// ───────────────────────────────────────────────
+
+fn load(profile_dir: &Path) -> Result<Vec<PinnedFile>, AppError> {
+    let path = profile_dir.join(PINS_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AppError::io("io.read_pins", Some(&path), e)),
+    };
+    serde_json::from_str(&content).map_err(|e| AppError::io("io.read_pins", Some(&path), e))
+}
+
+fn save(profile_dir: &Path, pins: &[PinnedFile]) -> Result<(), AppError> {
+    let path = profile_dir.join(PINS_FILE);
+    let content = serde_json::to_string_pretty(pins)
+        .map_err(|e| AppError::io("io.write_pins", Some(&path), e))?;
+    fs::write(&path, content).map_err(|e| AppError::io("io.write_pins", Some(&path), e))
+}
+
+/// `path` as a pin stores it: relative to `game_root` when inside it
+/// 釘選項目儲存 `path` 的形式：位於 `game_root` 內時為相對路徑
+fn pin_path(game_root: &Path, path: &Path) -> String {
+    let path = long_path::display(path).replace('\\', "/");
+    let root = long_path::display(game_root).replace('\\', "/");
+    let root = root.trim_end_matches('/');
+    let inside = path.len() > root.len()
+        && path.is_char_boundary(root.len())
+        && path[..root.len()].eq_ignore_ascii_case(root)
+        && path[root.len()..].starts_with('/');
+    match inside {
+        true => path[root.len() + 1..].to_string(),
+        false => path,
+    }
+}
+
+fn canonical(path: &Path) -> Option<PathBuf> {
+    fs::canonicalize(long_path::extend(path)).ok()
+}
+
+fn hash(path: &Path) -> Result<String, AppError> {
+    checksums::hash_file(path, &CancelToken::default())
+}
+
+// ───────────────────────────────────────────────
+// Matching / 比對
+// ───────────────────────────────────────────────
+
+impl PinSet {
+    /// Pins of `game_path` stored in `profile_dir`; an unreadable pins file fails, so nothing
+    /// is touched on the assumption that no file is pinned
+    /// `profile_dir` 中 `game_path` 的釘選項目；釘選檔無法讀取時回傳錯誤，避免在假設沒有釘選的情況下處理檔案
+    pub fn load(profile_dir: &Path, game_path: &str) -> Result<Self, AppError> {
+        let mut set = PinSet {
+            game_root: PathBuf::from(game_path),
+            pins: load(profile_dir)?,
+            canonical: Vec::new(),
+        };
+        set.canonical = set
+            .pins
+            .iter()
+            .map(|pin| canonical(&set.absolute(pin)))
+            .collect();
+        Ok(set)
+    }
+
+    fn absolute(&self, pin: &PinnedFile) -> PathBuf {
+        match Path::new(&pin.path).is_absolute() {
+            true => PathBuf::from(&pin.path),
+            false => self.game_root.join(&pin.path),
+        }
+    }
+
+    /// Index of the pin `path` falls under, and whether it matched by path rather than by
+    /// content. Content only counts for pins whose path is gone, since a copy next to a pinned
+    /// file that is still there is an ordinary duplicate; it is only hashed for files of such a
+    /// pin's size, and cloud placeholders that may not be downloaded match by path alone.
+    /// `path` 所屬釘選項目的索引，以及是否以路徑（而非內容）比對成功；只有原路徑已不存在的釘選項目才以內容比對，
+    /// 因為釘選檔案仍在時旁邊的複本只是一般的重複檔案；只有大小與這類項目相同的檔案才計算雜湊，
+    /// 不可下載的雲端預留位置檔案只以路徑比對
+    fn find(&self, path: &Path) -> Option<(usize, bool)> {
+        let key = pin_path(&self.game_root, path).to_lowercase();
+        if let Some(index) = self
+            .pins
+            .iter()
+            .position(|pin| pin.path.to_lowercase() == key)
+        {
+            return Some((index, true));
+        }
+        if let Some(index) = canonical(path).and_then(|path| {
+            self.canonical
+                .iter()
+                .position(|pinned| pinned.as_ref() == Some(&path))
+        }) {
+            return Some((index, true));
+        }
+        let size = fs::metadata(long_path::extend(path)).ok()?.len();
+        let moved =
+            |pin: &PinnedFile| pin.size == size && !long_path::extend(&self.absolute(pin)).exists();
+        if !self.pins.iter().any(moved) || cloud_files::skip_content(path) {
+            return None;
+        }
+        let content = hash(path).ok()?;
+        self.pins
+            .iter()
+            .position(|pin| moved(pin) && pin.hash == content)
+            .map(|index| (index, false))
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.find(path).is_some()
+    }
+}
+
+/// Set `pinned` on `entries`. A pin whose file turns up under another name is moved to the new
+/// path, so renaming a pinned file does not orphan its pin. An
+/// unreadable pins file is logged and leaves every entry unpinned for display; destructive
+/// commands load the pins themselves and refuse instead.
+/// 設定 `entries` 的 `pinned`；釘選檔案以其他名稱出現且原路徑已不存在時，將釘選項目移至新路徑，使改名不會讓釘選失效；
+/// 釘選檔無法讀取時記錄後將所有項目顯示為未釘選；破壞性指令會自行載入釘選項目並改為拒絕執行
+pub(crate) fn mark_entries<'a>(
+    profile_dir: &Path,
+    game_path: &str,
+    entries: impl IntoIterator<Item = &'a mut ModEntry>,
+) {
+    let mut set = match PinSet::load(profile_dir, game_path) {
+        Ok(set) => set,
+        Err(e) => {
+            app_log::write(&format!("pins skipped: {}", e));
+            return;
+        }
+    };
+    if set.pins.is_empty() {
+        return;
+    }
+    let mut moved = false;
+    for entry in entries {
+        let path = Path::new(&entry.path);
+        entry.pinned = match set.find(path) {
+            Some((index, by_path)) => {
+                if !by_path {
+                    let new_path = pin_path(&set.game_root, path);
+                    app_log::write(&format!(
+                        "pin moved: {} -> {}",
+                        set.pins[index].path, new_path
+                    ));
+                    set.pins[index].path = new_path;
+                    set.canonical[index] = canonical(path);
+                    moved = true;
+                }
+                true
+            }
+            None => false,
+        };
+    }
+    if moved {
+        if let Err(e) = save(profile_dir, &set.pins) {
+            app_log::write(&format!("moved pins not saved: {}", e));
+        }
+    }
+}
+
+/// `mark_entries` on every loaded and skipped entry of `conflicts`
+/// 對 `conflicts` 的所有 loaded 與 skipped 項目執行 `mark_entries`
+pub(crate) fn mark(profile_dir: &Path, game_path: &str, conflicts: &mut [ModConflict]) {
+    let entries = conflicts
+        .iter_mut()
+        .flat_map(|conflict| iter::once(&mut conflict.loaded).chain(&mut conflict.skipped));
+    mark_entries(profile_dir, game_path, entries);
+}
+
+// ───────────────────────────────────────────────
+// Pinning / 釘選
+// ───────────────────────────────────────────────
+
+/// Pin `paths`, hashing each; pinning a pinned path again refreshes its size and hash.
+/// Every path must be an existing file.
+/// 釘選 `paths` 並計算各自的雜湊；再次釘選已釘選的路徑會更新其大小與雜湊；每個路徑都必須是已存在的檔案
+pub(crate) fn pin(
+    profile_dir: &Path,
+    game_path: &str,
+    paths: &[String],
+) -> Result<Vec<PinnedFile>, AppError> {
+    let game_root = Path::new(game_path);
+    let mut pins = load(profile_dir)?;
+    let pinned_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    for path in paths {
+        let file = Path::new(path);
+        let metadata = fs::metadata(long_path::extend(file))
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .ok_or_else(|| AppError::InvalidInput {
+                message: Message::new("input.pin_not_a_file").with("path", path),
+            })?;
+        let pin = PinnedFile {
+            path: pin_path(game_root, file),
+            size: metadata.len(),
+            hash: hash(file)?,
+            pinned_at,
+        };
+        pins.retain(|stored| stored.path.to_lowercase() != pin.path.to_lowercase());
+        pins.push(pin);
+    }
+    pins.sort_by(|a, b| a.path.cmp(&b.path));
+    save(profile_dir, &pins)?;
+    Ok(pins)
+}
+
+/// Remove the pins of `paths`: those stored under the path, and the pin an existing file at
+/// the path matches by content. Unknown paths are ignored.
+/// 移除 `paths` 的釘選：以該路徑儲存的項目，以及該路徑上現有檔案以內容比對到的項目；未知路徑會被忽略
+pub(crate) fn unpin(
+    profile_dir: &Path,
+    game_path: &str,
+    paths: &[String],
+) -> Result<Vec<PinnedFile>, AppError> {
+    let mut set = PinSet::load(profile_dir, game_path)?;
+    for path in paths {
+        if let Some((index, _)) = set.find(Path::new(path)) {
+            set.pins.remove(index);
+            set.canonical.remove(index);
+        }
+    }
+    save(profile_dir, &set.pins)?;
+    Ok(set.pins)
+}
+
+/// Every pin of the profile with whether its file is still at the pinned path
+/// profile 的所有釘選項目，以及其檔案是否仍在釘選時的路徑
+pub(crate) fn list(profile_dir: &Path, game_path: &str) -> Result<Vec<PinStatus>, AppError> {
+    let set = PinSet::load(profile_dir, game_path)?;
+    Ok(set
+        .pins
+        .iter()
+        .map(|pin| PinStatus {
+            present: long_path::extend(&set.absolute(pin)).exists(),
+            pin: pin.clone(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::{list, mark_entries, pin};
+    use crate::{long_path, parser, profiles, test_support::SyntheticInstall};
+
+    #[test]
+    fn pin_follows_a_renamed_file_but_not_a_copy() {
+        let install = SyntheticInstall::new();
+        let profile = tempfile::tempdir().unwrap();
+        let original = install.add_file("mods/edited.zipmod", b"hand edited");
+        pin(
+            profile.path(),
+            &install.game_path(),
+            &[long_path::display(&original)],
+        )
+        .unwrap();
+        let copy = install.add_file("mods/copy of edited.zipmod", b"hand edited");
+        let entry_of = |path: &Path, rel_path: &str| {
+            parser::build_mod_entry(path, rel_path, &profiles::default_mod_extensions())
+        };
+
+        let mut entries = [entry_of(&copy, "copy of edited.zipmod")];
+        mark_entries(profile.path(), &install.game_path(), &mut entries);
+        assert!(!entries[0].pinned);
+
+        let renamed = install.root().join("mods/edited v2.zipmod");
+        fs::rename(&original, &renamed).unwrap();
+        let mut entries = [entry_of(&renamed, "edited v2.zipmod")];
+        mark_entries(profile.path(), &install.game_path(), &mut entries);
+        assert!(entries[0].pinned);
+
+        let listed = list(profile.path(), &install.game_path()).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].pin.path, "mods/edited v2.zipmod");
+        assert!(listed[0].present);
+    }
+}
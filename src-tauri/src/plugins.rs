@@ -0,0 +1,329 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use pelite::{image::IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR, FileMap, PeFile};
+use serde::Serialize;
+
+use crate::{
+    clr,
+    error::AppError,
+    links, long_path,
+    parser::{build_mod_entry, ModEntry},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// One copy of a plugin DLL, with versions read from its PE resources
+/// 單一插件 DLL，附帶從 PE 資源讀出的版本資訊
+#[derive(Serialize)]
+pub(crate) struct PluginFile {
+    #[serde(flatten)]
+    entry: ModEntry,
+    assembly_version: Option<String>,
+    file_version: Option<String>,
+    is_newest: bool,
+}
+
+/// DLLs sharing the same filename under BepInEx/plugins, newest first
+/// BepInEx/plugins 底下同名的 DLL，依版本由新到舊排序
+#[derive(Serialize)]
+pub(crate) struct PluginConflict {
+    file_name: String,
+    files: Vec<PluginFile>,
+}
+
+/// Versions stored in a DLL's VS_VERSIONINFO resource
+/// DLL 的 VS_VERSIONINFO 資源中的版本資訊
+#[derive(Default)]
+pub(crate) struct PeVersions {
+    pub assembly_version: Option<String>,
+    pub file_version: Option<String>,
+    pub product_name: Option<String>,
+}
+
+/// Where the fields of a PluginInfo came from
+/// PluginInfo 資料的來源
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PluginInfoSource {
+    BepinPluginAttribute,
+    VersionInfo,
+}
+
+/// BepInEx plugin identity read from a DLL
+/// 從 DLL 讀出的 BepInEx 插件資訊
+#[derive(Serialize)]
+pub(crate) struct PluginInfo {
+    name: Option<String>,
+    guid: Option<String>,
+    version: Option<String>,
+    source: PluginInfoSource,
+}
+
+// ───────────────────────────────────────────────
+// Utility Function / 工具函式
+// ───────────────────────────────────────────────
+
+/// Read version strings from an already-parsed PE version resource
+/// 從已解析的 PE 檔讀取版本資源字串
+fn versions_from_pe(file: PeFile) -> PeVersions {
+    let Some(version_info) = file.resources().ok().and_then(|r| r.version_info().ok()) else {
+        return PeVersions::default();
+    };
+
+    let lang = version_info.translation().first().copied();
+    let value = |key: &str| {
+        lang.and_then(|lang| version_info.value(lang, key))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    PeVersions {
+        assembly_version: value("Assembly Version"),
+        file_version: version_info
+            .fixed()
+            .map(|fixed| fixed.dwFileVersion.to_string())
+            .or_else(|| value("FileVersion")),
+        product_name: value("ProductName").or_else(|| value("FileDescription")),
+    }
+}
+
+/// Read version strings from the PE version resource (no .NET runtime needed)
+/// 從 PE 版本資源讀取版本字串（不需要 .NET runtime）
+pub(crate) fn read_pe_versions(path: &Path) -> PeVersions {
+    let Ok(map) = FileMap::open(&long_path::extend(path)) else {
+        return PeVersions::default();
+    };
+    match PeFile::from_bytes(&map) {
+        Ok(file) => versions_from_pe(file),
+        Err(_) => PeVersions::default(),
+    }
+}
+
+/// Locate the CLI metadata root of a managed PE, if any
+/// 取得 .NET 組件的 CLI metadata 區塊
+fn clr_metadata<'a>(file: &PeFile<'a>) -> Option<&'a [u8]> {
+    let dir = file
+        .data_directory()
+        .get(IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR)?;
+    if dir.VirtualAddress == 0 {
+        return None;
+    }
+    let header = file
+        .derva_slice::<u8>(dir.VirtualAddress, dir.Size as usize)
+        .ok()?;
+    let rva = u32::from_le_bytes(header.get(8..12)?.try_into().ok()?);
+    let size = u32::from_le_bytes(header.get(12..16)?.try_into().ok()?);
+    file.derva_slice::<u8>(rva, size as usize).ok()
+}
+
+/// Parse a dotted version ("1.2.0.3") into numeric parts for comparison
+/// 將點分版本字串轉為數字陣列以便比較
+pub(crate) fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split('.')
+        .map(|part| part.trim().parse::<u64>().ok())
+        .collect()
+}
+
+/// Compare two dotted versions, treating missing trailing parts as zero
+/// 比較兩個點分版本，缺少的尾段視為 0
+pub(crate) fn compare_versions(a: &[u64], b: &[u64]) -> Ordering {
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|ord| ord.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Sort key for a plugin copy: assembly version first, then file version
+/// 插件排序依據：先比 assembly version，再比 file version
+fn version_key(file: &PluginFile) -> (Option<Vec<u64>>, Option<Vec<u64>>) {
+    (
+        file.assembly_version.as_deref().and_then(parse_version),
+        file.file_version.as_deref().and_then(parse_version),
+    )
+}
+
+fn compare_plugin_files(a: &PluginFile, b: &PluginFile) -> Ordering {
+    let (a_asm, a_file) = version_key(a);
+    let (b_asm, b_file) = version_key(b);
+    let cmp = |x: Option<Vec<u64>>, y: Option<Vec<u64>>| match (x, y) {
+        (Some(x), Some(y)) => compare_versions(&x, &y),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => Ordering::Equal,
+    };
+    cmp(a_asm, b_asm).then_with(|| cmp(a_file, b_file))
+}
+
+// ───────────────────────────────────────────────
+// Plugin Info / 插件資訊
+// ───────────────────────────────────────────────
+
+/// Read name, GUID and version of a BepInEx plugin DLL.
+/// Prefers the `[BepInPlugin]` attribute and falls back to PE version-info strings.
+/// 讀取 BepInEx 插件 DLL 的名稱、GUID 與版本；優先使用 `[BepInPlugin]`，否則改用 PE 版本資訊
+pub(crate) fn read_plugin_info(path: &Path) -> Result<PluginInfo, AppError> {
+    let map = FileMap::open(&long_path::extend(path))
+        .map_err(|e| AppError::io("io.open_file", Some(path), e))?;
+    let not_managed = || AppError::NotManagedPlugin {
+        path: long_path::display(path),
+    };
+
+    let file = PeFile::from_bytes(&map).map_err(|_| not_managed())?;
+    let metadata = clr_metadata(&file).ok_or_else(not_managed)?;
+    let attribute =
+        clr::find_attribute_string_args(metadata, "BepInPlugin", 3).ok_or_else(not_managed)?;
+
+    if let Some(mut args) = attribute {
+        let version = args.pop().flatten();
+        let name = args.pop().flatten();
+        let guid = args.pop().flatten();
+        return Ok(PluginInfo {
+            name,
+            guid,
+            version,
+            source: PluginInfoSource::BepinPluginAttribute,
+        });
+    }
+
+    let versions = versions_from_pe(file);
+    if versions.product_name.is_none() && versions.file_version.is_none() {
+        return Err(AppError::PluginInfoMissing {
+            path: long_path::display(path),
+        });
+    }
+
+    Ok(PluginInfo {
+        name: versions.product_name,
+        guid: None,
+        version: versions.assembly_version.or(versions.file_version),
+        source: PluginInfoSource::VersionInfo,
+    })
+}
+
+// ───────────────────────────────────────────────
+// Scanner / 掃描
+// ───────────────────────────────────────────────
+
+/// Find plugin DLLs under `<game_path>/BepInEx/plugins` that share a filename (case-insensitive).
+/// A DLL reachable through a link is counted once, so it never duplicates itself.
+/// 找出 `<game_path>/BepInEx/plugins` 底下檔名相同（不分大小寫）的插件 DLL；經由連結可達的同一檔案只算一次
+pub(crate) fn scan_plugin_duplicates(game_path: &Path, follow_links: bool) -> Vec<PluginConflict> {
+    let plugins_path = game_path.join("BepInEx").join("plugins");
+    let mut groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+    for file in links::walk_files(&plugins_path, follow_links) {
+        let Some(file_name) = file.path.file_name() else {
+            continue;
+        };
+        let file_name = file_name.to_string_lossy().to_lowercase();
+        if file_name.ends_with(".dll") {
+            groups.entry(file_name).or_default().push(file.path);
+        }
+    }
+
+    let mut conflicts = groups
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(file_name, paths)| {
+            let mut files = paths
+                .iter()
+                .map(|path| {
+                    let versions = read_pe_versions(path);
+                    let rel_path = path.strip_prefix(&plugins_path).unwrap_or(path);
+                    // A plugin is no mod archive, so its extension stays in the name
+                    // 外掛並非模組壓縮檔，因此副檔名保留於名稱中
+                    PluginFile {
+                        entry: build_mod_entry(path, &rel_path.to_string_lossy(), &[]),
+                        assembly_version: versions.assembly_version,
+                        file_version: versions.file_version,
+                        is_newest: false,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            files.sort_by(|a, b| {
+                compare_plugin_files(b, a).then_with(|| a.entry.path.cmp(&b.entry.path))
+            });
+
+            // Only mark a newest copy when it is strictly newer than the runner-up
+            // 只有在版本確實高於第二名時才標記為最新
+            if let [first, second, ..] = files.as_slice() {
+                if compare_plugin_files(first, second) == Ordering::Greater {
+                    files[0].is_newest = true;
+                }
+            }
+
+            PluginConflict { file_name, files }
+        })
+        .collect::<Vec<_>>();
+
+    conflicts.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::PeFile;
+
+    use super::read_plugin_info;
+    use crate::{error::AppError, test_support::SyntheticInstall};
+
+    /// Headers of a 64-bit DLL with no sections and no CLI header, as a native DLL has
+    /// 沒有區段也沒有 CLI 標頭的 64 位元 DLL 標頭，如同原生 DLL
+    fn native_dll() -> Vec<u8> {
+        let mut image = vec![0u8; 0x200];
+        image[..2].copy_from_slice(b"MZ");
+        image[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        image[0x40..0x44].copy_from_slice(b"PE\0\0");
+        let coff = 0x44;
+        image[coff..coff + 2].copy_from_slice(&0x8664u16.to_le_bytes());
+        image[coff + 16..coff + 18].copy_from_slice(&0xF0u16.to_le_bytes());
+        image[coff + 18..coff + 20].copy_from_slice(&0x2022u16.to_le_bytes());
+        let optional = coff + 20;
+        let mut put = |at: usize, value: &[u8]| {
+            image[optional + at..optional + at + value.len()].copy_from_slice(value)
+        };
+        put(0, &0x20Bu16.to_le_bytes());
+        put(24, &0x1_8000_0000u64.to_le_bytes());
+        put(32, &0x1000u32.to_le_bytes());
+        put(36, &0x200u32.to_le_bytes());
+        put(40, &6u16.to_le_bytes());
+        put(48, &6u16.to_le_bytes());
+        put(56, &0x1000u32.to_le_bytes());
+        put(60, &0x200u32.to_le_bytes());
+        put(68, &3u16.to_le_bytes());
+        put(108, &16u32.to_le_bytes());
+        image
+    }
+
+    #[test]
+    fn native_and_non_pe_dlls_are_not_managed_plugins() {
+        let install = SyntheticInstall::new();
+        assert!(PeFile::from_bytes(&native_dll()).is_ok());
+        let native = install.add_file("BepInEx/plugins/native.dll", &native_dll());
+        let text = install.add_file("BepInEx/plugins/readme.dll", b"not a PE file");
+
+        for path in [native, text] {
+            let result = read_plugin_info(&path);
+            assert!(
+                matches!(result, Err(AppError::NotManagedPlugin { .. })),
+                "{:?}",
+                result.err()
+            );
+        }
+    }
+}
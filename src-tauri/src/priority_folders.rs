@@ -0,0 +1,52 @@
+use std::iter;
+
+use crate::{
+    folder_density,
+    parser::{ModConflict, ModEntry},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Folder-name prefixes that mark a priority folder when a profile does not set its own, as in
+/// `mods/!!!my overrides`
+/// profile 未自訂時標示優先資料夾的資料夾名稱前綴，例如 `mods/!!!my overrides`
+pub(crate) const DEFAULT_PRIORITY_PREFIXES: &[&str] = &["!"];
+
+// ───────────────────────────────────────────────
+// Detection / 偵測
+// ───────────────────────────────────────────────
+
+/// The outermost folder under the mods root whose name starts with one of `prefixes`; blank
+/// prefixes are ignored
+/// mods 根目錄下名稱以 `prefixes` 之一開頭的最外層資料夾；空白前綴會被忽略
+pub(crate) fn priority_folder_of(entry: &ModEntry, prefixes: &[String]) -> Option<String> {
+    let (_, folders) = folder_density::folders_under_root(entry);
+    folders.into_iter().find(|folder| {
+        prefixes
+            .iter()
+            .map(|prefix| prefix.trim())
+            .any(|prefix| !prefix.is_empty() && folder.starts_with(prefix))
+    })
+}
+
+/// Set `priority_folder` on every entry that lies in a priority folder
+/// 將位於優先資料夾內的項目標記為 `priority_folder`
+pub(crate) fn mark_priority_entries<'a>(
+    entries: impl IntoIterator<Item = &'a mut ModEntry>,
+    prefixes: &[String],
+) {
+    for entry in entries {
+        entry.priority_folder = priority_folder_of(entry, prefixes).is_some();
+    }
+}
+
+/// Set `priority_folder` on every loaded and skipped entry that lies in a priority folder
+/// 將位於優先資料夾內的 loaded 與 skipped 項目標記為 `priority_folder`
+pub(crate) fn mark_priority(conflicts: &mut [ModConflict], prefixes: &[String]) {
+    let entries = conflicts
+        .iter_mut()
+        .flat_map(|conflict| iter::once(&mut conflict.loaded).chain(&mut conflict.skipped));
+    mark_priority_entries(entries, prefixes);
+}
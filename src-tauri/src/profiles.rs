@@ -0,0 +1,267 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_index::DEFAULT_MOD_EXTENSIONS, error::AppError, large_results, long_path,
+    modpack::DEFAULT_MODPACK_PREFIXES, priority_folders::DEFAULT_PRIORITY_PREFIXES,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Marker written into each profile directory so destructive commands know the game path
+/// 寫入各 profile 資料夾的紀錄，讓破壞性指令得知已登記的遊戲路徑
+#[derive(Serialize, Deserialize)]
+struct ProfileRecord {
+    game_path: String,
+    #[serde(default)]
+    settings: ProfileSettings,
+}
+
+/// User-adjustable behaviour of one profile, stored in its `profile.json`
+/// 各 profile 可調整的行為設定，儲存於其 `profile.json`
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct ProfileSettings {
+    /// Descend into directory symlinks and junctions when scanning the mods tree
+    /// 掃描 mods 目錄樹時是否進入目錄 symlink 與 junction
+    #[serde(default)]
+    pub follow_links: bool,
+    /// Folders under the game root that KKManager re-syncs; deleting inside them needs
+    /// an explicit `allow_modpack`
+    /// 遊戲根目錄下由 KKManager 重新同步的資料夾；刪除其中的檔案需明確指定 `allow_modpack`
+    #[serde(default = "default_modpack_prefixes")]
+    pub modpack_prefixes: Vec<String>,
+    /// Never let a file leave the disk: `delete_mods` and `resolve_conflicts` quarantine
+    /// instead of trashing, and files that cannot be quarantined are refused
+    /// 絕不讓檔案離開磁碟：`delete_mods` 與 `resolve_conflicts` 改為隔離而非丟到回收桶，無法隔離的檔案一律拒絕
+    #[serde(default)]
+    pub safe_mode: bool,
+    /// File extensions treated as mod archives by every scanner, e.g. `zipmod` and `zip`
+    /// 所有掃描器視為模組壓縮檔的副檔名，例如 `zipmod` 與 `zip`
+    #[serde(default = "default_mod_extensions")]
+    pub mod_extensions: Vec<String>,
+    /// Folder-name prefixes marking priority folders under the mods root, e.g. `!`
+    /// 標示 mods 根目錄下優先資料夾的資料夾名稱前綴，例如 `!`
+    #[serde(default = "default_priority_prefixes")]
+    pub priority_folder_prefixes: Vec<String>,
+}
+
+/// Settings shared by every profile, stored in `app_settings.json` of the profiles root
+/// 所有 profile 共用的設定，儲存於 profiles 根目錄的 `app_settings.json`
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct AppSettings {
+    /// Refuse every command that would change game files, for installs that are only to be
+    /// looked at; see `path_guard::ensure_writable`
+    /// 拒絕所有會變更遊戲檔案的指令，用於只供檢視的安裝；見 `path_guard::ensure_writable`
+    #[serde(default)]
+    pub read_only_mode: bool,
+    /// Open OneDrive and Dropbox placeholders for manifest reads and hashing anyway, letting
+    /// the sync client download each one first; see `cloud_files`
+    /// 讀取 manifest 與計算雜湊時仍開啟 OneDrive 與 Dropbox 預留位置檔案，由同步程式逐一先行下載；見 `cloud_files`
+    #[serde(default)]
+    pub hydrate_cloud_files: bool,
+    /// Serialized size above which heavy commands return their result as a file; see
+    /// `large_results::guard`
+    /// 耗時指令的結果序列化後超過此大小時改以檔案回傳；見 `large_results::guard`
+    #[serde(default = "default_max_response_bytes")]
+    pub max_response_bytes: u64,
+}
+
+fn default_max_response_bytes() -> u64 {
+    large_results::DEFAULT_MAX_RESPONSE_BYTES
+}
+
+fn default_modpack_prefixes() -> Vec<String> {
+    DEFAULT_MODPACK_PREFIXES
+        .iter()
+        .map(|prefix| prefix.to_string())
+        .collect()
+}
+
+/// `DEFAULT_MOD_EXTENSIONS`, for work that has no profile to take `mod_extensions` from
+/// `DEFAULT_MOD_EXTENSIONS`，用於沒有 profile 可取得 `mod_extensions` 的作業
+pub(crate) fn default_mod_extensions() -> Vec<String> {
+    DEFAULT_MOD_EXTENSIONS
+        .iter()
+        .map(|extension| extension.to_string())
+        .collect()
+}
+
+fn default_priority_prefixes() -> Vec<String> {
+    DEFAULT_PRIORITY_PREFIXES
+        .iter()
+        .map(|prefix| prefix.to_string())
+        .collect()
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            read_only_mode: false,
+            hydrate_cloud_files: false,
+            max_response_bytes: default_max_response_bytes(),
+        }
+    }
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        ProfileSettings {
+            follow_links: false,
+            modpack_prefixes: default_modpack_prefixes(),
+            safe_mode: false,
+            mod_extensions: default_mod_extensions(),
+            priority_folder_prefixes: default_priority_prefixes(),
+        }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Profiles / Profile 管理
+// ───────────────────────────────────────────────
+
+/// Stable per-install profile id derived from the game path (FNV-1a)
+/// 由遊戲路徑推導出的穩定 profile id（FNV-1a）
+pub(crate) fn profile_id(game_path: &str) -> String {
+    let normalized = game_path
+        .trim()
+        .trim_end_matches(['/', '\\'])
+        .replace('\\', "/")
+        .to_lowercase();
+    let hash = normalized
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{:016x}", hash)
+}
+
+fn write_record(record_path: &Path, record: &ProfileRecord) -> Result<(), AppError> {
+    let content = serde_json::to_string(record)
+        .map_err(|e| AppError::io("io.serialize_profile_record", Some(record_path), e))?;
+    fs::write(record_path, content)
+        .map_err(|e| AppError::io("io.write_profile_record", Some(record_path), e))
+}
+
+fn read_record(record_path: &Path) -> Option<ProfileRecord> {
+    let content = fs::read_to_string(record_path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// The game path resolved by the file system (`..`, links and letter case), without the
+/// `\\?\` prefix; the path as given when it cannot be resolved, e.g. because it is missing.
+/// `long_path::extend` adds the prefix back where Windows needs it.
+/// 由檔案系統解析後（`..`、連結與大小寫）且不含 `\\?\` 前綴的遊戲路徑；無法解析時（例如路徑不存在）回傳原路徑；
+/// Windows 需要前綴時由 `long_path::extend` 補回
+pub(crate) fn canonical_game_path(game_path: &str) -> String {
+    let path = Path::new(game_path.trim());
+    fs::canonicalize(long_path::extend(path))
+        .map(|canonical| long_path::display(&canonical))
+        .unwrap_or_else(|_| game_path.to_string())
+}
+
+/// Per-profile data directory under `profiles_root`; registers the game path, canonicalized,
+/// on first use
+/// `profiles_root` 底下各 profile 專屬的資料夾；首次使用時登記正規化後的遊戲路徑
+pub(crate) fn profile_dir(profiles_root: &Path, game_path: &str) -> Result<PathBuf, AppError> {
+    let dir = profiles_root.join(profile_id(game_path));
+    let record_path = dir.join("profile.json");
+    if !record_path.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| AppError::io("io.create_profile_directory", Some(&dir), e))?;
+        write_record(
+            &record_path,
+            &ProfileRecord {
+                game_path: canonical_game_path(game_path),
+                settings: ProfileSettings::default(),
+            },
+        )?;
+    }
+    Ok(dir)
+}
+
+/// Settings of a profile; defaults when none were saved yet
+/// 取得 profile 設定；尚未儲存時為預設值
+pub(crate) fn load_settings(profiles_root: &Path, game_path: &str) -> ProfileSettings {
+    let record_path = profiles_root
+        .join(profile_id(game_path))
+        .join("profile.json");
+    read_record(&record_path)
+        .map(|record| record.settings)
+        .unwrap_or_default()
+}
+
+/// Save the settings of a profile, registering it if needed
+/// 儲存 profile 設定，必要時一併登記
+pub(crate) fn save_settings(
+    profiles_root: &Path,
+    game_path: &str,
+    settings: ProfileSettings,
+) -> Result<(), AppError> {
+    let record_path = profile_dir(profiles_root, game_path)?.join("profile.json");
+    write_record(
+        &record_path,
+        &ProfileRecord {
+            game_path: canonical_game_path(game_path),
+            settings,
+        },
+    )
+}
+
+/// Saved app-wide settings; defaults when none were saved or the file is unreadable
+/// 已儲存的全域設定；未曾儲存或檔案無法讀取時使用預設值
+pub(crate) fn load_app_settings(profiles_root: &Path) -> AppSettings {
+    fs::read_to_string(profiles_root.join("app_settings.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn save_app_settings(
+    profiles_root: &Path,
+    settings: &AppSettings,
+) -> Result<(), AppError> {
+    let path = profiles_root.join("app_settings.json");
+    fs::create_dir_all(profiles_root)
+        .map_err(|e| AppError::io("io.write_app_settings", Some(profiles_root), e))?;
+    let content = serde_json::to_string(settings)
+        .map_err(|e| AppError::io("io.write_app_settings", Some(&path), e))?;
+    fs::write(&path, content).map_err(|e| AppError::io("io.write_app_settings", Some(&path), e))
+}
+
+/// Game paths of every profile registered under `profiles_root`
+/// `profiles_root` 底下所有已登記 profile 的遊戲路徑
+pub(crate) fn registered_game_paths(profiles_root: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(profiles_root) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| read_record(&entry.path().join("profile.json")))
+        .map(|record| record.game_path)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::canonical_game_path;
+    use crate::{long_path, test_support::SyntheticInstall};
+
+    #[test]
+    fn canonical_game_path_keeps_trailing_dot() {
+        let install = SyntheticInstall::in_folder("ゲーム/Koikatsu.");
+
+        let canonical = canonical_game_path(&install.game_path());
+
+        assert!(canonical.ends_with("Koikatsu."));
+        assert!(!canonical.starts_with(r"\\?\"));
+        assert!(long_path::extend(&Path::new(&canonical).join("mods")).is_dir());
+    }
+}
@@ -0,0 +1,35 @@
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Progress of a long operation as reported by the core modules
+/// 核心模組回報的長時間作業進度
+#[derive(Debug, Clone)]
+pub struct Progress {
+    pub current: usize,
+    pub total: usize,
+    pub message: Option<String>,
+}
+
+impl Progress {
+    pub fn new(current: usize, total: usize) -> Self {
+        Progress {
+            current,
+            total,
+            message: None,
+        }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// Progress sink shared by parallel workers
+/// 可由平行工作共用的進度回呼
+pub type OnProgress<'a> = &'a (dyn Fn(Progress) + Sync);
+
+/// Sink that drops every update, for callers that don't report progress
+/// 不回報進度時使用的空回呼
+pub fn ignore(_: Progress) {}
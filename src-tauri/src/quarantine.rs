@@ -0,0 +1,113 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{disk_space, error::AppError, file_retry, long_path, path_guard::WriteMode};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Folder under the game root that quarantined files are moved into; `path_guard`
+/// lets destructive commands operate inside it
+/// 遊戲根目錄下存放隔離檔案的資料夾；`path_guard` 允許破壞性指令在其中操作
+const QUARANTINE_DIR: &str = "quarantine";
+
+// ───────────────────────────────────────────────
+// Quarantine / 隔離
+// ───────────────────────────────────────────────
+
+/// Folder a game's quarantined files are kept in / 遊戲隔離檔案存放的資料夾
+pub(crate) fn quarantine_dir(game_path: &Path) -> PathBuf {
+    game_path.join(QUARANTINE_DIR)
+}
+
+/// `path` itself if free, otherwise the first free `name (2).ext`, `name (3).ext`, ...
+/// 若 `path` 不存在則直接使用，否則依序嘗試 `name (2).ext`、`name (3).ext`...
+pub(crate) fn unique_path(path: &Path) -> PathBuf {
+    if !long_path::extend(path).exists() {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !long_path::extend(candidate).exists())
+        .expect("unbounded candidate range")
+}
+
+/// Registered game whose root contains `path`, if any
+/// 根目錄包含 `path` 的已登記遊戲
+pub(crate) fn owning_game<'a, S: AsRef<str>>(game_paths: &'a [S], path: &Path) -> Option<&'a str> {
+    let canonical = fs::canonicalize(long_path::extend(path)).ok()?;
+    game_paths.iter().map(AsRef::as_ref).find(|game_path| {
+        fs::canonicalize(long_path::extend(Path::new(game_path)))
+            .is_ok_and(|root| canonical.starts_with(root))
+    })
+}
+
+/// Move `file` into `<game_path>/quarantine`, keeping its path relative to the game root
+/// so it can be put back. Moves across volumes copy then delete, after a free-space check.
+/// Moves failing while another process holds the file are retried; returns the new path and
+/// the number of retries.
+/// 將 `file` 移入 `<game_path>/quarantine`，保留其相對於遊戲根目錄的路徑以便還原；跨磁碟區時先檢查空間再複製後刪除；
+/// 其他程序佔用檔案而搬移失敗時會重試；回傳新路徑與重試次數
+pub(crate) fn quarantine_file(
+    game_path: &Path,
+    file: &Path,
+    write_mode: WriteMode,
+) -> Result<(PathBuf, u32), AppError> {
+    write_mode.ensure_writable(file)?;
+    let canonical_file = fs::canonicalize(long_path::extend(file))
+        .map_err(|e| AppError::io("io.quarantine_file", Some(file), e))?;
+    let relative = fs::canonicalize(long_path::extend(game_path))
+        .ok()
+        .and_then(|root| {
+            canonical_file
+                .strip_prefix(root)
+                .ok()
+                .map(Path::to_path_buf)
+        })
+        .or_else(|| file.file_name().map(PathBuf::from))
+        .unwrap_or_default();
+    let target = unique_path(&quarantine_dir(game_path).join(relative));
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(long_path::extend(parent))
+            .map_err(|e| AppError::io("io.quarantine_file", Some(parent), e))?;
+    }
+    let renamed =
+        file_retry::retry(|| fs::rename(long_path::extend(file), long_path::extend(&target)));
+    let rename_retries = match renamed {
+        Ok(((), retries)) => return Ok((target, retries)),
+        Err(exhausted) if exhausted.locked => {
+            return Err(
+                exhausted.into_error(file, |e| AppError::io("io.quarantine_file", Some(file), e))
+            )
+        }
+        Err(exhausted) => exhausted.retries,
+    };
+
+    // Rename fails across volumes, e.g. when mods is a junction to another drive
+    // 跨磁碟區時無法改名，例如 mods 為指向其他磁碟的 junction
+    disk_space::ensure_space(
+        &target,
+        disk_space::bytes_needed(&[file.to_path_buf()], &target, true),
+    )?;
+    if let Err(e) = fs::copy(long_path::extend(file), long_path::extend(&target)) {
+        disk_space::remove_partial(&target);
+        return Err(AppError::io("io.quarantine_file", Some(file), e));
+    }
+    let (_, retries) =
+        file_retry::retry(|| fs::remove_file(long_path::extend(file))).map_err(|exhausted| {
+            exhausted.into_error(file, |e| AppError::io("io.quarantine_file", Some(file), e))
+        })?;
+    Ok((target, rename_retries + retries))
+}
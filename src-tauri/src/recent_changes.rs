@@ -0,0 +1,209 @@
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+    asset_index, links, local_time, long_path,
+    manifest::ManifestData,
+    mod_roots,
+    modpack::ModpackFolders,
+    ops,
+    parser::{build_mod_entry, ModEntry},
+    priority_folders,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Most files returned; a full modpack re-download touches tens of thousands
+/// 最多回傳的檔案數；重新下載整個模組包會更動數萬個檔案
+const RESULT_LIMIT: usize = 2000;
+
+/// A mod archive modified within the window
+/// 在時間範圍內被修改的模組壓縮檔
+#[derive(Serialize)]
+pub(crate) struct RecentChange {
+    #[serde(flatten)]
+    pub entry: ModEntry,
+    /// Renamed to `.disabled` so Sideloader skips it / 已改名為 `.disabled`，Sideloader 會略過
+    pub disabled: bool,
+    /// From the asset index when its record is current; archives are not opened
+    /// 資源索引紀錄仍有效時取自索引；不會開啟壓縮檔
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<ManifestData>,
+}
+
+/// What `recently_changed_mods` returns
+/// `recently_changed_mods` 的回傳內容
+#[derive(Serialize)]
+pub(crate) struct RecentChanges {
+    /// Newest first, at most `RESULT_LIMIT` / 由新到舊，最多 `RESULT_LIMIT` 筆
+    pub files: Vec<RecentChange>,
+    /// Files in the window before the cap / 套用上限前範圍內的檔案數
+    pub total: usize,
+    pub truncated: bool,
+}
+
+// ───────────────────────────────────────────────
+// Listing / 列出
+// ───────────────────────────────────────────────
+
+/// Whether the walker should report `path`: a mod archive, or one renamed to `.disabled`
+/// 走訪時是否列出 `path`：模組壓縮檔，或已改名為 `.disabled` 的模組壓縮檔
+fn is_candidate(path: &Path, mod_extensions: &[String]) -> bool {
+    match ops::is_disabled(path) {
+        true => asset_index::is_mod_archive(&path.with_extension(""), mod_extensions),
+        false => asset_index::is_mod_archive(path, mod_extensions),
+    }
+}
+
+/// Mod archives under every mod root modified in the last `since_days` days, enabled or
+/// disabled, newest first. The walk follows the profile's link and extension settings and
+/// reads only directory listings; manifests come from the asset index at `index_path` when
+/// warm. A modification time in the future counts as now and sets `timestamp_clamped`.
+/// 列出所有模組根目錄下最近 `since_days` 天內修改過的模組壓縮檔（含已停用的），由新到舊；依 profile 的連結與副檔名設定走訪，
+/// 只讀取目錄清單，資源索引 `index_path` 有效時附上其 manifest；未來的修改時間視為現在並標記 `timestamp_clamped`
+pub(crate) fn recently_changed_mods(
+    game_path: &Path,
+    since_days: u32,
+    index_path: &Path,
+    follow_links: bool,
+    mod_extensions: &[String],
+    modpacks: &ModpackFolders,
+    priority_prefixes: &[String],
+) -> RecentChanges {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let since = now.saturating_sub(u64::from(since_days) * 24 * 60 * 60);
+
+    let mut recent = Vec::new();
+    for root in mod_roots::mod_roots(game_path) {
+        for file in links::walk_files(&root, follow_links) {
+            if !is_candidate(&file.path, mod_extensions) {
+                continue;
+            }
+            let Some((modified, _)) = file.metadata.modified().ok().map(local_time::epoch_seconds)
+            else {
+                continue;
+            };
+            if modified >= since {
+                recent.push((file.path, root.clone()));
+            }
+        }
+    }
+
+    let mut files = recent
+        .into_iter()
+        .map(|(path, root)| {
+            let mut entry = build_mod_entry(&path, &long_path::display(&path), mod_extensions);
+            entry.mod_root = Some(long_path::display(&root));
+            entry.managed = modpacks.contains(&path);
+            RecentChange {
+                disabled: ops::is_disabled(&path),
+                manifest: None,
+                entry,
+            }
+        })
+        .collect::<Vec<_>>();
+    let keys = files
+        .iter()
+        .map(|file| {
+            (
+                file.entry.path.as_str(),
+                file.entry.size,
+                file.entry.modified,
+            )
+        })
+        .collect::<Vec<_>>();
+    let manifests = asset_index::cached_manifests(index_path, &keys);
+    for (file, manifest) in files.iter_mut().zip(manifests) {
+        file.manifest = manifest;
+        if file.entry.modified.is_some_and(|modified| modified > now) {
+            file.entry.modified = Some(now);
+            file.entry.modified_local = local_time::format_local(now);
+            file.entry.timestamp_clamped = true;
+        }
+    }
+    priority_folders::mark_priority_entries(
+        files.iter_mut().map(|file| &mut file.entry),
+        priority_prefixes,
+    );
+
+    files.sort_by(|a, b| {
+        b.entry
+            .modified
+            .cmp(&a.entry.modified)
+            .then_with(|| a.entry.path.cmp(&b.entry.path))
+    });
+    let total = files.len();
+    files.truncate(RESULT_LIMIT);
+    RecentChanges {
+        truncated: total > files.len(),
+        total,
+        files,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::recently_changed_mods;
+    use crate::{
+        modpack::ModpackFolders,
+        test_support::{SyntheticInstall, TestManifest},
+    };
+
+    #[test]
+    fn recent_changes_are_windowed_newest_first_with_future_clamped() {
+        let install = SyntheticInstall::new();
+        let set_modified = |path: &Path, time: std::time::SystemTime| {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(time)
+                .unwrap();
+        };
+        let now = std::time::SystemTime::now();
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+        let old = install.add_zipmod("old.zipmod", &TestManifest::new("old"));
+        set_modified(&old, now - day * 30);
+        let recent = install.add_zipmod("recent.zipmod", &TestManifest::new("recent"));
+        set_modified(&recent, now - day);
+        let disabled = install.add_zipmod("off.zipmod.disabled", &TestManifest::new("off"));
+        set_modified(&disabled, now - day * 2);
+        let future = install.add_zipmod("future.zipmod", &TestManifest::new("future"));
+        set_modified(&future, now + day * 365);
+        install.add_file("mods/readme.txt", b"not a mod");
+
+        let changes = recently_changed_mods(
+            install.root(),
+            7,
+            &install.root().join("asset_index.json"),
+            false,
+            &["zipmod".to_string()],
+            &ModpackFolders::for_games::<&str>(&[]),
+            &[],
+        );
+
+        let names = changes
+            .files
+            .iter()
+            .map(|file| file.entry.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["future.zipmod", "recent.zipmod", "off.zipmod.disabled"]
+        );
+        assert!(changes.files[0].entry.timestamp_clamped);
+        assert!(changes.files[2].disabled);
+        assert!(!changes.truncated);
+    }
+}
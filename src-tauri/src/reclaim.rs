@@ -0,0 +1,147 @@
+use std::{collections::HashSet, path::Path};
+
+use serde::Serialize;
+
+use crate::{error::AppError, file_snapshot::FileSnapshot, progress, scan, timings::Timings};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Where a part of the estimate comes from
+/// 估計值各部分的來源
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Basis {
+    /// Sizes read from the files just now / 剛剛從檔案讀取的大小
+    Measured,
+    /// Files found by an earlier scan, re-measured now; files added since are not counted
+    /// 先前掃描找到的檔案並重新量測大小；之後新增的檔案不計入
+    LastScan,
+    /// Nothing cheap to go on, so the part is left out of the total
+    /// 沒有可快速取得的資料，此部分不計入總計
+    Unavailable,
+}
+
+/// Bytes and file count of one part of the estimate
+/// 估計值單一部分的位元組數與檔案數
+#[derive(Serialize, Default)]
+pub(crate) struct ReclaimPart {
+    pub bytes: u64,
+    pub files: usize,
+}
+
+/// Which parts of `ReclaimEstimate` are measured and which are estimates
+/// `ReclaimEstimate` 中哪些部分為實際量測、哪些為估計
+#[derive(Serialize)]
+pub(crate) struct Confidence {
+    pub conflict_duplicates: Basis,
+    pub exact_duplicates: Basis,
+    pub junk: Basis,
+}
+
+/// Disk space resolving everything would give back, from cached and cheap data only
+/// 僅以快取與低成本資料估算的、處理所有問題後可釋放的磁碟空間
+#[derive(Serialize)]
+pub(crate) struct ReclaimEstimate {
+    /// Skipped files of the log's conflicts that still exist and are not loaded elsewhere
+    /// log 衝突中仍存在、且未被其他衝突載入的 skipped 檔案
+    pub conflict_duplicates: ReclaimPart,
+    /// Identical copies under different names; needs earlier hashing, None without it
+    /// 不同檔名的相同複本；需先前已計算雜湊，沒有時為 None
+    pub exact_duplicates: Option<ReclaimPart>,
+    /// Junk files of the last junk-file scan that are still there; None without a scan
+    /// 上次多餘檔案掃描中仍存在的檔案；未掃描時為 None
+    pub junk: Option<ReclaimPart>,
+    /// Sum of the available parts, each file counted once
+    /// 可取得部分的總和，每個檔案只計算一次
+    pub total_bytes: u64,
+    /// The log parse was served from the scan cache / 衝突解析沿用了掃描快取
+    pub from_cache: bool,
+    pub confidence: Confidence,
+}
+
+// ───────────────────────────────────────────────
+// Estimation / 估算
+// ───────────────────────────────────────────────
+
+/// Sum the files not counted yet; a size of None means the file is gone
+/// 加總尚未計入的檔案；大小為 None 表示檔案已不存在
+fn measure<'a>(
+    files: impl Iterator<Item = (&'a str, Option<u64>)>,
+    counted: &mut HashSet<String>,
+) -> ReclaimPart {
+    let mut part = ReclaimPart::default();
+    for (path, size) in files {
+        if let Some(size) = size.filter(|_| counted.insert(path.to_lowercase())) {
+            part.bytes += size;
+            part.files += 1;
+        }
+    }
+    part
+}
+
+/// Estimate the reclaimable space of an install. The log parse reuses the scan cache when the
+/// log is unchanged, and `junk_paths` are the junk of an earlier scan if one ran; nothing is
+/// hashed and no manifest is read. There is no hash cache yet, so exact duplicates are always
+/// unavailable.
+/// 估算安裝可釋放的空間；log 未變動時沿用掃描快取，`junk_paths` 為先前掃描的多餘檔案（若有）；
+/// 不計算雜湊也不讀取 manifest；目前沒有雜湊快取，因此完全相同的複本一律無法估算
+pub(crate) fn estimate_reclaimable(
+    cache_path: &Path,
+    game_path: &str,
+    mod_extensions: &[String],
+    junk_paths: Option<&[String]>,
+) -> Result<ReclaimEstimate, AppError> {
+    let scan = scan::scan_conflicts_cached(
+        cache_path,
+        game_path,
+        mod_extensions,
+        false,
+        &progress::ignore,
+        Timings::new(false),
+    )?;
+
+    // A file another conflict loads is kept by resolution and one with other hardlinks keeps its
+    // data, so neither is reclaimable. The scan has just re-read every entry, so their sizes
+    // are current.
+    // 被其他衝突載入的檔案在處理時會保留，仍有其他硬連結的檔案資料也不會釋放，兩者皆不算可釋放；
+    // 掃描剛重新讀取過每個項目，大小為最新值
+    let mut counted = scan
+        .conflicts
+        .iter()
+        .map(|conflict| conflict.loaded.path.to_lowercase())
+        .collect::<HashSet<_>>();
+    let conflict_duplicates = measure(
+        scan.conflicts
+            .iter()
+            .flat_map(|conflict| &conflict.skipped)
+            .filter(|entry| entry.frees_space())
+            .map(|entry| (entry.path.as_str(), entry.modified.map(|_| entry.size))),
+        &mut counted,
+    );
+    let junk = junk_paths.map(|paths| {
+        let files = paths.iter().map(|path| {
+            let size = FileSnapshot::current(Path::new(path)).map(|snapshot| snapshot.size);
+            (path.as_str(), size)
+        });
+        measure(files, &mut counted)
+    });
+
+    let total_bytes = conflict_duplicates.bytes + junk.as_ref().map_or(0, |part| part.bytes);
+    Ok(ReclaimEstimate {
+        conflict_duplicates,
+        exact_duplicates: None,
+        total_bytes,
+        from_cache: scan.from_cache,
+        confidence: Confidence {
+            conflict_duplicates: Basis::Measured,
+            exact_duplicates: Basis::Unavailable,
+            junk: match junk {
+                Some(_) => Basis::LastScan,
+                None => Basis::Unavailable,
+            },
+        },
+        junk,
+    })
+}
@@ -0,0 +1,679 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    iter,
+    path::Path,
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    empty_dirs::PrunedDir,
+    error::AppError,
+    file_snapshot::{self, Expectations, ExpectedFile, FileSnapshot},
+    game_process, long_path,
+    messages::Message,
+    operations::OperationGuard,
+    ops,
+    parser::{ModConflict, ModEntry},
+    path_guard::AllowedRoots,
+    pins::PinSet,
+    profiles::profile_id,
+    progress::{OnProgress, Progress},
+    transactions::{self, Step, StepStatus},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Which copy of a conflict to keep and which to delete
+/// 衝突中要保留與要刪除的檔案
+#[derive(Serialize)]
+pub(crate) struct PlannedResolution {
+    pub keep: ModEntry,
+    pub delete: Vec<ModEntry>,
+    /// The loaded copy is deleted in favour of a modpack copy, which KKManager would
+    /// otherwise re-download after it was removed
+    /// 刪除 loaded 檔案而保留模組包中的檔案；否則被刪除的模組包檔案會由 KKManager 重新下載
+    pub keeps_modpack_copy: bool,
+    /// A copy in a priority folder is kept; other priority and modpack copies are not deleted
+    /// 保留優先資料夾中的檔案；其他優先資料夾與模組包中的檔案不會被刪除
+    pub keeps_priority_copy: bool,
+    /// Why the plan departs from keeping the loaded copy, when it does for a priority folder
+    /// 規劃因優先資料夾而未保留 loaded 檔案等情況的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<Message>,
+    /// Copies the plan would delete but leaves alone because they are pinned
+    /// 規劃原本會刪除、但因已釘選而不處理的檔案
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<ModEntry>,
+    /// Other conflicts a `delete` entry also belongs to, which carrying out the plan changes
+    /// too, sorted; filled from the entries' `coupled_conflicts`
+    /// `delete` 項目同時所屬的其他衝突（已排序），執行此規劃也會改變它們；取自各項目的 `coupled_conflicts`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub also_affects: Vec<String>,
+    /// "This will also affect conflicts …" when `also_affects` is not empty
+    /// `also_affects` 不為空時的「也會影響衝突 …」提示
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub also_affects_note: Option<Message>,
+    /// Files inside the folders among `delete`, which go with them; resolving needs
+    /// `allow_directories` for those
+    /// `delete` 中資料夾內的檔案數，會隨資料夾一併處理；處理這些資料夾需指定 `allow_directories`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directory_files: Option<u64>,
+    /// Size and mtime of each `delete` entry as the last scan saw them; pass as the
+    /// `expected` argument of `delete_mods`
+    /// 最近一次掃描時各 `delete` 項目的大小與修改時間；可直接作為 `delete_mods` 的 `expected` 參數
+    pub expected: Vec<ExpectedFile>,
+}
+
+/// Conflicts of the most recent conflict scan, stored in Tauri managed state
+/// 最近一次衝突掃描的結果，存放於 Tauri managed state
+#[derive(Default)]
+pub(crate) struct LastConflicts(Mutex<Option<ConflictScan>>);
+
+struct ConflictScan {
+    game_path: String,
+    conflicts: Vec<ModConflict>,
+}
+
+/// What `resolve_conflicts` does with each skipped file
+/// `resolve_conflicts` 對每個被跳過檔案的處理方式
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ResolveAction {
+    Trash,
+    Quarantine,
+    Disable,
+}
+
+/// Why a skipped file was left alone
+/// 被跳過的檔案未處理的原因
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum BlockReason {
+    /// File no longer exists / 檔案已不存在
+    Missing,
+    /// Outside the game's mods, plugins and quarantine folders / 位於允許的遊戲資料夾之外
+    PathNotAllowed,
+    /// The same file is what another conflict loads / 同一檔案為其他衝突的 loaded 檔案
+    LoadedElsewhere,
+    /// Pinned and `allow_pinned` was not given / 已釘選且未指定 `allow_pinned`
+    Pinned,
+    /// In a modpack folder and `allow_modpack` was not given / 位於模組包資料夾且未指定 `allow_modpack`
+    Modpack,
+    /// Size or mtime differs from the scan and `force` was not given / 大小或修改時間與掃描時不同且未指定 `force`
+    ChangedSinceScan,
+    /// The game is running and may hold the file open / 遊戲執行中，可能正開啟該檔案
+    GameRunning,
+    /// The path is a folder and `allow_directories` was not given
+    /// 路徑為資料夾且未指定 `allow_directories`
+    Directory,
+    /// Read-only and `clear_read_only` was not given; enable 'clear read-only flag' to proceed
+    /// 為唯讀且未指定 `clear_read_only`；啟用「清除唯讀屬性」即可繼續
+    ReadOnly,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ResolvedFile {
+    #[serde(flatten)]
+    pub entry: ModEntry,
+    /// New location after quarantining or disabling
+    /// 隔離或停用後的新位置
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    /// Safe mode quarantined the file although `trash` was asked for
+    /// 要求 `trash` 但安全模式改為隔離此檔案
+    pub redirected_to_quarantine: bool,
+    /// Times the move was retried while another process held the file
+    /// 因其他程序佔用檔案而重試搬移的次數
+    pub retries: u32,
+}
+
+#[derive(Serialize)]
+pub(crate) struct BlockedFile {
+    #[serde(flatten)]
+    pub entry: ModEntry,
+    pub reason: BlockReason,
+}
+
+#[derive(Serialize)]
+pub(crate) struct FailedFile {
+    #[serde(flatten)]
+    pub entry: ModEntry,
+    pub error: AppError,
+}
+
+/// Another file of a conflict whose resolution failed, and what undoing its step did
+/// 處理失敗的衝突中的其他檔案，以及復原其步驟的結果
+#[derive(Serialize)]
+pub(crate) struct RolledBackFile {
+    #[serde(flatten)]
+    pub entry: ModEntry,
+    /// `rolled_back`, `rollback_failed` (the file stays at `destination`), `not_reversible`
+    /// (already in the Recycle Bin) or `not_run`
+    /// `rolled_back`、`rollback_failed`（檔案留在 `destination`）、`not_reversible`（已在回收桶）或 `not_run`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    #[serde(flatten)]
+    pub status: StepStatus,
+}
+
+/// Outcome of `resolve_conflicts`: every selected skipped file ends up in exactly one list
+/// `resolve_conflicts` 的結果：每個選取的被跳過檔案恰好出現在其中一個清單
+#[derive(Serialize)]
+pub(crate) struct ResolveReport {
+    /// The action applied, `quarantine` when safe mode redirected `trash`
+    /// 實際執行的處理方式；安全模式改寫 `trash` 時為 `quarantine`
+    pub action: ResolveAction,
+    pub resolved: Vec<ResolvedFile>,
+    pub blocked: Vec<BlockedFile>,
+    pub failed: Vec<FailedFile>,
+    /// The other files of conflicts listed in `failed`; each conflict is resolved as a whole
+    /// or rolled back as far as possible
+    /// `failed` 中衝突的其他檔案；每個衝突整體處理，失敗時盡可能復原
+    pub rolled_back: Vec<RolledBackFile>,
+    /// Requested ids that are not in the last scan
+    /// 要求的 id 中不在最近一次掃描結果內者
+    pub unknown_conflict_ids: Vec<String>,
+    /// Size of the resolved files and of rolled-back files already in the Recycle Bin, no
+    /// longer taking space in the mods folder
+    /// 已處理檔案與已在回收桶中無法復原的檔案大小，即 mods 資料夾不再佔用的空間
+    pub bytes_reclaimed: u64,
+    /// Stopped by cancellation; files after that point are in no list
+    /// 因取消而中止；之後的檔案不在任何清單中
+    pub cancelled: bool,
+    /// Folders the trashed or quarantined files left empty, removed when
+    /// `resolve_conflicts` is asked to prune them
+    /// 丟到回收桶或隔離的檔案使其變空的資料夾，`resolve_conflicts` 要求清除時刪除
+    pub pruned_dirs: Vec<PrunedDir>,
+}
+
+impl ResolveAction {
+    /// Parse the `action` argument of `resolve_conflicts`
+    /// 解析 `resolve_conflicts` 的 `action` 參數
+    pub(crate) fn parse(action: &str) -> Result<Self, AppError> {
+        match action {
+            "trash" => Ok(ResolveAction::Trash),
+            "quarantine" => Ok(ResolveAction::Quarantine),
+            "disable" => Ok(ResolveAction::Disable),
+            _ => Err(AppError::InvalidInput {
+                message: Message::new("input.unknown_resolve_action").with("action", action),
+            }),
+        }
+    }
+}
+
+impl LastConflicts {
+    /// Remember the conflicts of a scan, replacing any earlier scan
+    /// 記住一次掃描的衝突，取代先前的結果
+    pub fn store(&self, game_path: &str, conflicts: &[ModConflict]) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(ConflictScan {
+            game_path: game_path.to_string(),
+            conflicts: conflicts.to_vec(),
+        });
+    }
+
+    /// Add `conflicts` to the stored scan of `game_path`, replacing stored ones of the same id;
+    /// stores them as a new scan when the stored one is of another game or there is none
+    /// 將 `conflicts` 加入 `game_path` 的已存掃描，並取代相同 id 者；已存掃描屬於其他遊戲或尚無掃描時改存為新的掃描
+    pub fn merge(&self, game_path: &str, conflicts: &[ModConflict]) {
+        let mut guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let scan = match guard.as_mut() {
+            Some(scan) if profile_id(&scan.game_path) == profile_id(game_path) => scan,
+            _ => {
+                *guard = Some(ConflictScan {
+                    game_path: game_path.to_string(),
+                    conflicts: conflicts.to_vec(),
+                });
+                return;
+            }
+        };
+        let mut added = conflicts
+            .iter()
+            .map(|conflict| (conflict.id.as_str(), conflict))
+            .collect::<HashMap<_, _>>();
+        for stored in &mut scan.conflicts {
+            if let Some(conflict) = added.remove(stored.id.as_str()) {
+                *stored = conflict.clone();
+            }
+        }
+        scan.conflicts.extend(
+            conflicts
+                .iter()
+                .filter(|conflict| added.contains_key(conflict.id.as_str()))
+                .cloned(),
+        );
+    }
+
+    /// Ids of the stored conflicts, if the stored scan is of `game_path`
+    /// 若已存掃描屬於 `game_path`，回傳其衝突 id
+    pub fn conflict_ids(&self, game_path: &str) -> Option<HashSet<String>> {
+        let guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let scan = guard.as_ref()?;
+        (profile_id(&scan.game_path) == profile_id(game_path))
+            .then(|| scan.conflicts.iter().map(|c| c.id.clone()).collect())
+    }
+
+    /// Game path of the stored scan / 已存掃描的遊戲路徑
+    pub fn game_path(&self) -> Option<String> {
+        let guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        guard.as_ref().map(|scan| scan.game_path.clone())
+    }
+
+    /// Size and mtime of every loaded and skipped file of the stored scan
+    /// 已存掃描中所有 loaded 與 skipped 檔案的大小與修改時間
+    pub fn expectations(&self) -> Expectations {
+        let guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        guard
+            .iter()
+            .flat_map(|scan| &scan.conflicts)
+            .flat_map(|conflict| iter::once(&conflict.loaded).chain(&conflict.skipped))
+            .map(|entry| (entry.path.to_lowercase(), FileSnapshot::of_entry(entry)))
+            .collect()
+    }
+
+    /// Game path and copies of the stored conflicts listed in `conflict_ids` (all of them when
+    /// None); unknown ids are ignored
+    /// 已存掃描的遊戲路徑與 `conflict_ids` 所列衝突的複本（None 時為全部）；未知的 id 會被忽略
+    pub fn select(&self, conflict_ids: Option<&[String]>) -> Option<(String, Vec<ModConflict>)> {
+        let guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let scan = guard.as_ref()?;
+        let wanted = conflict_ids.map(|ids| ids.iter().collect::<HashSet<_>>());
+        let conflicts = scan
+            .conflicts
+            .iter()
+            .filter(|conflict| wanted.as_ref().is_none_or(|ids| ids.contains(&conflict.id)))
+            .cloned()
+            .collect();
+        Some((scan.game_path.clone(), conflicts))
+    }
+
+    /// Replace stored conflicts with the given ones of the same id, keeping their order; does
+    /// nothing when the stored scan has since been replaced by one of another game
+    /// 以相同 id 的衝突取代已存的衝突並保留順序；若已存掃描已被其他遊戲的掃描取代則不做任何事
+    pub fn update(&self, game_path: &str, conflicts: &[ModConflict]) {
+        let mut guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(scan) = guard.as_mut() else {
+            return;
+        };
+        if profile_id(&scan.game_path) != profile_id(game_path) {
+            return;
+        }
+        let mut updated = conflicts
+            .iter()
+            .map(|conflict| (conflict.id.as_str(), conflict))
+            .collect::<HashMap<_, _>>();
+        for stored in &mut scan.conflicts {
+            if let Some(conflict) = updated.remove(stored.id.as_str()) {
+                *stored = conflict.clone();
+            }
+        }
+    }
+
+    /// Drop resolved files from the stored conflicts. Emptied conflicts stay, so their loaded
+    /// file is still protected
+    /// 從已存的衝突中移除已處理的檔案；已無 skipped 檔案的衝突仍保留，使其 loaded 檔案持續受保護
+    fn forget(&self, resolved: &HashSet<String>) {
+        let mut guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(scan) = guard.as_mut() else {
+            return;
+        };
+        for conflict in &mut scan.conflicts {
+            conflict
+                .skipped
+                .retain(|entry| !resolved.contains(&entry.path.to_lowercase()));
+        }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Planning / 規劃
+// ───────────────────────────────────────────────
+
+/// Plan one deletion per conflict: keep the loaded copy and delete the skipped ones, except
+/// when a manually installed copy is loaded over a modpack copy. Deleting the modpack copy
+/// would only make KKManager re-download it, so the manual copy is deleted instead. A copy in
+/// a priority folder is an intentional override and is always kept, with a `reason`; then
+/// only copies outside priority and modpack folders are deleted. Pinned copies are never
+/// deleted and are listed in `pinned` instead. Entries must already carry their `managed`,
+/// `priority_folder`, `pinned` and `coupled_conflicts` annotations. Expected snapshots come from `scanned` when it knows
+/// the path, otherwise from the entry itself.
+/// 為每個衝突規劃刪除：保留 loaded 檔案並刪除 skipped 檔案；但若手動安裝的檔案蓋過模組包中的檔案，
+/// 刪除模組包檔案只會讓 KKManager 重新下載，因此改為刪除手動安裝的檔案。優先資料夾中的檔案為刻意的覆蓋，
+/// 一律保留並附上 `reason`，此時只刪除優先資料夾與模組包以外的檔案。已釘選的檔案一律不刪除，改列於 `pinned`。
+/// 項目須已標記 `managed`、`priority_folder`、`pinned` 與 `coupled_conflicts`。
+/// 預期快照優先取自 `scanned`，沒有該路徑時取自項目本身
+pub(crate) fn plan_resolution(
+    conflicts: &[ModConflict],
+    scanned: &Expectations,
+) -> Vec<PlannedResolution> {
+    conflicts
+        .iter()
+        .map(|conflict| {
+            let mut entries = iter::once(&conflict.loaded)
+                .chain(&conflict.skipped)
+                .cloned()
+                .collect::<Vec<_>>();
+            let priority_copy = entries.iter().position(|entry| entry.priority_folder);
+            let modpack_copy = match priority_copy.is_none() && !conflict.loaded.managed {
+                true => conflict.skipped.iter().position(|entry| entry.managed),
+                false => None,
+            };
+            let keep_index = priority_copy.unwrap_or(modpack_copy.map_or(0, |index| index + 1));
+            let keep = entries.remove(keep_index);
+            if priority_copy.is_some() {
+                entries.retain(|entry| !entry.priority_folder && !entry.managed);
+            }
+            let (pinned, entries): (Vec<_>, Vec<_>) =
+                entries.into_iter().partition(|entry| entry.pinned);
+            let reason = priority_copy
+                .map(|_| Message::new("resolution.priority_override").with("path", &keep.path));
+            let also_affects = entries
+                .iter()
+                .flat_map(|entry| &entry.coupled_conflicts)
+                .cloned()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            let also_affects_note = (!also_affects.is_empty()).then(|| {
+                Message::new("resolution.also_affects")
+                    .with("conflict_ids", also_affects.join(", "))
+            });
+            let directory_files = entries
+                .iter()
+                .filter_map(|entry| entry.directory_files)
+                .reduce(|total, files| total + files);
+            let expected = entries
+                .iter()
+                .map(|entry| ExpectedFile {
+                    path: entry.path.clone(),
+                    snapshot: file_snapshot::expected(scanned, &entry.path)
+                        .unwrap_or_else(|| FileSnapshot::of_entry(entry)),
+                })
+                .collect();
+            PlannedResolution {
+                keep,
+                delete: entries,
+                keeps_modpack_copy: modpack_copy.is_some(),
+                keeps_priority_copy: priority_copy.is_some(),
+                reason,
+                pinned,
+                also_affects,
+                also_affects_note,
+                directory_files,
+                expected,
+            }
+        })
+        .collect()
+}
+
+// ───────────────────────────────────────────────
+// Resolution / 處理
+// ───────────────────────────────────────────────
+
+/// The first safety check `entry` fails, in the order the user can act on them
+/// `entry` 未通過的第一項安全檢查，依使用者可處理的順序
+#[allow(clippy::too_many_arguments)]
+fn block_reason(
+    entry: &ModEntry,
+    roots: &AllowedRoots,
+    loaded: &HashSet<String>,
+    pins: Option<&PinSet>,
+    allow_directories: bool,
+    allow_modpack: bool,
+    force: bool,
+    clear_read_only: bool,
+) -> Option<BlockReason> {
+    if !long_path::extend(Path::new(&entry.path)).exists() {
+        return Some(BlockReason::Missing);
+    }
+    if entry.is_directory && !allow_directories {
+        return Some(BlockReason::Directory);
+    }
+    if roots.check(&entry.path).is_err() {
+        return Some(BlockReason::PathNotAllowed);
+    }
+    if loaded.contains(&entry.path.to_lowercase()) {
+        return Some(BlockReason::LoadedElsewhere);
+    }
+    if pins.is_some_and(|pins| pins.contains(Path::new(&entry.path))) {
+        return Some(BlockReason::Pinned);
+    }
+    if entry.managed && !allow_modpack {
+        return Some(BlockReason::Modpack);
+    }
+    if !force && changed_since_scan(entry) {
+        return Some(BlockReason::ChangedSinceScan);
+    }
+    if !clear_read_only && ops::is_read_only(Path::new(&entry.path)) {
+        return Some(BlockReason::ReadOnly);
+    }
+    None
+}
+
+fn changed_since_scan(entry: &ModEntry) -> bool {
+    !file_snapshot::unchanged(Some(&FileSnapshot::of_entry(entry)), Path::new(&entry.path))
+}
+
+fn step(action: ResolveAction, game_path: &Path, path: &Path) -> Step {
+    let path = path.to_path_buf();
+    match action {
+        ResolveAction::Trash => Step::Trash { path },
+        ResolveAction::Quarantine => Step::Quarantine {
+            game_path: game_path.to_path_buf(),
+            path,
+        },
+        ResolveAction::Disable => Step::Disable { path },
+    }
+}
+
+/// Apply `action` to the skipped files of the given conflicts from the last scan. The loaded
+/// files are never touched. Every file is checked first (still present, inside the game
+/// folders, not a folder unless `allow_directories`, not loaded by another conflict, not in
+/// `pins`, not in a modpack folder unless `allow_modpack`, game not running, same size and
+/// mtime as scanned unless `force`) and only
+/// the ones passing all checks are acted on. The size and mtime are compared again right before each conflict
+/// is touched, and the files of one conflict are handled as one `transactions::run`, so a
+/// failure rolls back the conflict's other files. Every selected file is locked for
+/// `operation` first, failing with `PathBusy` if another operation is working on one. A
+/// failed conflict does not stop the batch; cancellation does. Read-only files are blocked
+/// unless `clear_read_only` is set, which clears their flag right before their conflict runs.
+/// With `safe_mode`, `Trash` is carried out as `Quarantine`. With `only`, just the skipped
+/// files whose lowercased path it lists are selected.
+/// 對最近一次掃描中指定衝突的被跳過檔案執行 `action`，loaded 檔案一律不動。每個檔案先經過檢查
+/// （仍存在、位於遊戲資料夾內、除非 `allow_directories` 否則不是資料夾、不是其他衝突的 loaded 檔案、不在 `pins` 中、除非 `allow_modpack` 否則不在模組包資料夾、遊戲未執行、
+/// 除非 `force` 否則大小與修改時間與掃描時相同），全部通過者才會處理，且處理每個衝突前會再比對一次大小與修改時間；
+/// 同一衝突的檔案以一次 `transactions::run` 處理，失敗時會復原該衝突的其他檔案；
+/// 所有選取的檔案會先為 `operation` 鎖定，若有檔案正由其他作業處理則回傳 `PathBusy`；
+/// 單一衝突失敗不會中止整批，取消則會；唯讀檔案會被阻擋，除非設定 `clear_read_only`，此時在處理其衝突前清除唯讀屬性；
+/// `safe_mode` 時 `Trash` 改以 `Quarantine` 執行；指定 `only` 時只選取其所列（小寫）路徑的被跳過檔案
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn resolve_conflicts(
+    store: &LastConflicts,
+    conflict_ids: &[String],
+    only: Option<&HashSet<String>>,
+    pins: Option<&PinSet>,
+    action: ResolveAction,
+    allow_directories: bool,
+    allow_modpack: bool,
+    force: bool,
+    clear_read_only: bool,
+    safe_mode: bool,
+    operation: &OperationGuard,
+    on_progress: OnProgress,
+) -> Result<ResolveReport, AppError> {
+    let redirected = safe_mode && matches!(action, ResolveAction::Trash);
+    let action = match redirected {
+        true => ResolveAction::Quarantine,
+        false => action,
+    };
+    let (game_path, candidates, loaded, unknown_conflict_ids) = {
+        let guard = store.0.lock().unwrap_or_else(|e| e.into_inner());
+        let scan = guard.as_ref().ok_or_else(|| AppError::InvalidInput {
+            message: Message::new("input.no_conflict_scan"),
+        })?;
+        let loaded = scan
+            .conflicts
+            .iter()
+            .map(|conflict| conflict.loaded.path.to_lowercase())
+            .collect::<HashSet<_>>();
+        let mut seen = HashSet::new();
+        let mut unknown = Vec::new();
+        let mut candidates = Vec::new();
+        for id in conflict_ids {
+            match scan.conflicts.iter().find(|conflict| &conflict.id == id) {
+                Some(conflict) => candidates.push(
+                    conflict
+                        .skipped
+                        .iter()
+                        .filter(|entry| {
+                            let key = entry.path.to_lowercase();
+                            only.is_none_or(|only| only.contains(&key)) && seen.insert(key)
+                        })
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                ),
+                None => unknown.push(id.clone()),
+            }
+        }
+        (scan.game_path.clone(), candidates, loaded, unknown)
+    };
+
+    let paths = candidates
+        .iter()
+        .flatten()
+        .map(|entry| Path::new(&entry.path))
+        .collect::<Vec<_>>();
+    operation.lock_paths(&paths)?;
+
+    let game_root = Path::new(&game_path);
+    let running = game_process::running_executable(game_root).is_some();
+    let roots = AllowedRoots::for_games(&[&game_path]);
+    let mut report = ResolveReport {
+        action,
+        resolved: Vec::new(),
+        blocked: Vec::new(),
+        failed: Vec::new(),
+        rolled_back: Vec::new(),
+        unknown_conflict_ids,
+        bytes_reclaimed: 0,
+        cancelled: false,
+        pruned_dirs: Vec::new(),
+    };
+
+    let mut allowed = Vec::new();
+    for group in candidates {
+        let mut files = Vec::new();
+        for entry in group {
+            let reason = match running {
+                true => Some(BlockReason::GameRunning),
+                false => block_reason(
+                    &entry,
+                    &roots,
+                    &loaded,
+                    pins,
+                    allow_directories,
+                    allow_modpack,
+                    force,
+                    clear_read_only,
+                ),
+            };
+            match reason {
+                Some(reason) => report.blocked.push(BlockedFile { entry, reason }),
+                None => files.push(entry),
+            }
+        }
+        allowed.push(files);
+    }
+
+    let total = allowed.iter().map(Vec::len).sum();
+    let mut done = 0;
+    let mut resolved_paths = HashSet::new();
+    for group in allowed {
+        if operation.token.is_cancelled() {
+            report.cancelled = true;
+            break;
+        }
+        if let Some(first) = group.first() {
+            on_progress(Progress::new(done, total).with_message(first.path.clone()));
+        }
+        done += group.len();
+        let (changed, files): (Vec<_>, Vec<_>) = group
+            .into_iter()
+            .partition(|entry| !force && changed_since_scan(entry));
+        report
+            .blocked
+            .extend(changed.into_iter().map(|entry| BlockedFile {
+                entry,
+                reason: BlockReason::ChangedSinceScan,
+            }));
+        // Only reached with `clear_read_only`; other read-only files were blocked above
+        // 僅在指定 `clear_read_only` 時執行；其他唯讀檔案已於先前被阻擋
+        let mut writable = Vec::new();
+        for entry in files {
+            let path = Path::new(&entry.path);
+            let cleared = match ops::is_read_only(path) {
+                true => ops::clear_read_only_flag(path),
+                false => Ok(()),
+            };
+            match cleared {
+                Ok(()) => writable.push(entry),
+                Err(error) => report.failed.push(FailedFile { entry, error }),
+            }
+        }
+        let files = writable;
+        if files.is_empty() {
+            continue;
+        }
+
+        let steps = files
+            .iter()
+            .map(|entry| step(action, game_root, Path::new(&entry.path)))
+            .collect::<Vec<_>>();
+        let steps =
+            match transactions::run("resolve", &steps, operation.write_mode, Some(operation)) {
+                Ok(transaction) => transaction.steps,
+                Err(error) => {
+                    report
+                        .failed
+                        .extend(files.into_iter().map(|entry| FailedFile {
+                            entry,
+                            error: error.clone(),
+                        }));
+                    continue;
+                }
+            };
+        for (entry, step) in files.into_iter().zip(steps) {
+            let gone = matches!(step.status, StepStatus::Done | StepStatus::NotReversible);
+            if gone {
+                resolved_paths.insert(entry.path.to_lowercase());
+                report.bytes_reclaimed += entry.size;
+            }
+            match step.status {
+                StepStatus::Done => report.resolved.push(ResolvedFile {
+                    entry,
+                    destination: step.destination,
+                    redirected_to_quarantine: redirected,
+                    retries: step.retries,
+                }),
+                StepStatus::Failed { error } => report.failed.push(FailedFile { entry, error }),
+                status => {
+                    let destination = match status {
+                        StepStatus::RollbackFailed { .. } => step.destination,
+                        _ => None,
+                    };
+                    report.rolled_back.push(RolledBackFile {
+                        entry,
+                        destination,
+                        status,
+                    })
+                }
+            }
+        }
+    }
+    on_progress(Progress::new(total, total));
+    store.forget(&resolved_paths);
+    Ok(report)
+}
@@ -0,0 +1,444 @@
+use std::{collections::HashSet, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    archive_inspect, asset_index, checksums, cloud_files,
+    error::AppError,
+    long_path,
+    messages::Message,
+    operations::CancelToken,
+    parser::{ModConflict, ModEntry},
+    pins::PinSet,
+    resolution::{ResolveAction, ResolveReport},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Which skipped files of the last scan `resolve_by_rule` acts on, and how. Every condition
+/// that is set must hold; a rule must set at least one.
+/// `resolve_by_rule` 要處理最近一次掃描中哪些被跳過的檔案、以及處理方式；所有設定的條件皆須成立，且至少須設定一項
+#[derive(Deserialize)]
+pub(crate) struct ResolveRule {
+    /// Last modified before this Unix time in seconds / 最後修改時間早於此 Unix 時間（秒）
+    #[serde(default)]
+    pub older_than: Option<u64>,
+    /// Inside this folder, absolute or relative to the game folder
+    /// 位於此資料夾內；可為絕對路徑或相對於遊戲資料夾
+    #[serde(default)]
+    pub under_path: Option<String>,
+    /// Byte-for-byte identical to the loaded copy / 與 loaded 檔案內容完全相同
+    #[serde(default)]
+    pub identical_to_loaded: bool,
+    /// Larger than this many bytes / 大於此位元組數
+    #[serde(default)]
+    pub size_above: Option<u64>,
+    /// Manifest `<author>` equal to this, ignoring case / manifest 的 `<author>` 與此相同（不分大小寫）
+    #[serde(default)]
+    pub manifest_author: Option<String>,
+    pub action: ResolveAction,
+}
+
+/// A skipped file the rule matches, with the conflict it belongs to
+/// 規則符合的被跳過檔案，以及其所屬的衝突
+#[derive(Serialize)]
+pub(crate) struct RuleMatch {
+    pub conflict_id: String,
+    #[serde(flatten)]
+    pub entry: ModEntry,
+}
+
+/// Dry run of a rule. `plan_hash` covers the action and every matched file with its size and
+/// mtime; confirming with it carries out exactly this plan. Pinned files the rule matches are
+/// listed in `pinned` and are not part of the plan.
+/// 規則的試執行結果；`plan_hash` 涵蓋處理方式與每個符合檔案的大小與修改時間，以其確認即執行此規劃；
+/// 規則符合的已釘選檔案列於 `pinned`，不屬於此規劃
+#[derive(Serialize)]
+pub(crate) struct RulePlan {
+    pub action: ResolveAction,
+    pub matches: Vec<RuleMatch>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<RuleMatch>,
+    pub bytes: u64,
+    /// Files inside the matched folders, which go with them; carrying out the plan needs
+    /// `allow_directories` for those
+    /// 符合的資料夾內的檔案數，會隨資料夾一併處理；執行此規劃時處理這些資料夾需指定 `allow_directories`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directory_files: Option<u64>,
+    pub plan_hash: String,
+}
+
+/// What `resolve_by_rule` returns: the dry run, or the confirmed plan and what was done
+/// `resolve_by_rule` 的回傳內容：試執行結果，或已確認的規劃與處理結果
+#[derive(Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub(crate) enum RuleOutcome {
+    Preview {
+        plan: RulePlan,
+    },
+    Executed {
+        plan: RulePlan,
+        report: ResolveReport,
+    },
+}
+
+// ───────────────────────────────────────────────
+// Predicates / 條件
+// ───────────────────────────────────────────────
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_end_matches('/').to_lowercase()
+}
+
+fn is_under(entry: &ModEntry, folder: &str, game_path: &str) -> bool {
+    let folder = normalize(folder);
+    let folder = match Path::new(&folder).is_absolute() {
+        true => folder,
+        false => normalize(&long_path::display(&Path::new(game_path).join(&folder))),
+    };
+    let path = normalize(&entry.path);
+    path.strip_prefix(&folder)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn author_matches(entry: &ModEntry, author: &str, index_path: &Path) -> bool {
+    let manifest =
+        asset_index::cached_manifest(index_path, &entry.path, entry.size, entry.modified)
+            .unwrap_or_else(|| archive_inspect::read_manifest(Path::new(&entry.path)).ok());
+    manifest
+        .and_then(|manifest| manifest.author)
+        .is_some_and(|found| found.trim().eq_ignore_ascii_case(author.trim()))
+}
+
+/// Same size and hash as `loaded`; files that cannot be read, and cloud placeholders that may
+/// not be downloaded, do not match
+/// 與 `loaded` 大小與雜湊相同；無法讀取的檔案與不可下載的雲端預留位置檔案視為不符
+fn identical(entry: &ModEntry, loaded: &ModEntry, cancel: &CancelToken) -> Result<bool, AppError> {
+    let paths = [Path::new(&entry.path), Path::new(&loaded.path)];
+    if entry.size != loaded.size || paths.iter().any(|path| cloud_files::skip_content(path)) {
+        return Ok(false);
+    }
+    let hash = |path: &Path| match checksums::hash_file(path, cancel) {
+        Err(AppError::OperationCancelled) => Err(AppError::OperationCancelled),
+        result => Ok(result.ok()),
+    };
+    let (skipped, loaded) = (hash(paths[0])?, hash(paths[1])?);
+    Ok(skipped.is_some() && skipped == loaded)
+}
+
+/// Whether `entry`, skipped in favour of `loaded`, meets every condition of `rule`. The cheap
+/// conditions are checked first so files are only opened for those still in question.
+/// 被跳過而改載入 `loaded` 的 `entry` 是否符合 `rule` 的所有條件；先檢查成本低的條件，只有仍待判斷的檔案才會被開啟
+pub(crate) fn matches(
+    rule: &ResolveRule,
+    entry: &ModEntry,
+    loaded: &ModEntry,
+    game_path: &str,
+    index_path: &Path,
+    cancel: &CancelToken,
+) -> Result<bool, AppError> {
+    let cheap = rule
+        .older_than
+        .is_none_or(|cutoff| entry.modified.is_some_and(|modified| modified < cutoff))
+        && rule.size_above.is_none_or(|size| entry.size > size)
+        && rule
+            .under_path
+            .as_ref()
+            .is_none_or(|folder| is_under(entry, folder, game_path));
+    if !cheap {
+        return Ok(false);
+    }
+    if let Some(author) = &rule.manifest_author {
+        if !author_matches(entry, author, index_path) {
+            return Ok(false);
+        }
+    }
+    match rule.identical_to_loaded {
+        true => identical(entry, loaded, cancel),
+        false => Ok(true),
+    }
+}
+
+// ───────────────────────────────────────────────
+// Planning / 規劃
+// ───────────────────────────────────────────────
+
+fn plan_hash(action: ResolveAction, matches: &[RuleMatch]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(
+        serde_json::to_string(&action)
+            .unwrap_or_default()
+            .as_bytes(),
+    );
+    for found in matches {
+        let line = format!(
+            "\n{}|{}|{}|{}",
+            found.conflict_id,
+            found.entry.path.to_lowercase(),
+            found.entry.size,
+            found.entry.modified.unwrap_or_default()
+        );
+        hasher.update(line.as_bytes());
+    }
+    hasher.finalize().to_hex()[..16].to_string()
+}
+
+/// Evaluate `rule` against the skipped files of `conflicts`, each file once, in scan order;
+/// matches in `pins` are set aside as `pinned`. Fails with `rule_without_conditions` when the
+/// rule sets no condition, since it would match every skipped file.
+/// 以 `rule` 評估 `conflicts` 的被跳過檔案，每個檔案只評估一次並依掃描順序排列，符合但在 `pins` 中者另列為 `pinned`；
+/// 規則未設定任何條件時會符合所有被跳過的檔案，因此回傳 `rule_without_conditions`
+pub(crate) fn evaluate(
+    rule: &ResolveRule,
+    game_path: &str,
+    conflicts: &[ModConflict],
+    pins: Option<&PinSet>,
+    index_path: &Path,
+    cancel: &CancelToken,
+) -> Result<RulePlan, AppError> {
+    let unconditional = rule.older_than.is_none()
+        && rule.under_path.is_none()
+        && !rule.identical_to_loaded
+        && rule.size_above.is_none()
+        && rule.manifest_author.is_none();
+    if unconditional {
+        return Err(AppError::InvalidInput {
+            message: Message::new("input.rule_without_conditions"),
+        });
+    }
+
+    let mut seen = HashSet::new();
+    let mut matched = Vec::new();
+    let mut pinned = Vec::new();
+    for conflict in conflicts {
+        for entry in &conflict.skipped {
+            if cancel.is_cancelled() {
+                return Err(AppError::OperationCancelled);
+            }
+            if !seen.insert(entry.path.to_lowercase()) {
+                continue;
+            }
+            if matches(rule, entry, &conflict.loaded, game_path, index_path, cancel)? {
+                let found = RuleMatch {
+                    conflict_id: conflict.id.clone(),
+                    entry: entry.clone(),
+                };
+                match pins.is_some_and(|pins| pins.contains(Path::new(&entry.path))) {
+                    true => pinned.push(found),
+                    false => matched.push(found),
+                }
+            }
+        }
+    }
+    Ok(RulePlan {
+        action: rule.action,
+        bytes: matched.iter().map(|found| found.entry.size).sum(),
+        directory_files: matched
+            .iter()
+            .filter_map(|found| found.entry.directory_files)
+            .reduce(|total, files| total + files),
+        plan_hash: plan_hash(rule.action, &matched),
+        matches: matched,
+        pinned,
+    })
+}
+
+/// Fail with `rule_plan_changed` unless `confirmed` is the hash of `plan`, re-evaluated right
+/// before acting
+/// 除非 `confirmed` 等於執行前重新評估的 `plan` 的雜湊，否則回傳 `rule_plan_changed`
+pub(crate) fn ensure_confirmed(plan: &RulePlan, confirmed: Option<&str>) -> Result<(), AppError> {
+    match confirmed == Some(plan.plan_hash.as_str()) {
+        true => Ok(()),
+        false => Err(AppError::InvalidInput {
+            message: Message::new("input.rule_plan_changed")
+                .with("expected", confirmed.unwrap_or("none"))
+                .with("actual", &plan.plan_hash),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{ensure_confirmed, evaluate, matches, ResolveRule};
+    use crate::{
+        error::AppError,
+        operations::CancelToken,
+        parser::{self, ModConflict, ModEntry},
+        profiles,
+        test_support::{SyntheticInstall, TestManifest},
+    };
+
+    fn rule(json: serde_json::Value) -> ResolveRule {
+        serde_json::from_value(json).expect("parse rule")
+    }
+
+    fn rule_matches(
+        install: &SyntheticInstall,
+        rule: &ResolveRule,
+        entry: &ModEntry,
+        loaded: &ModEntry,
+    ) -> bool {
+        let profile = tempfile::tempdir().unwrap();
+        matches(
+            rule,
+            entry,
+            loaded,
+            &install.game_path(),
+            &profile.path().join("asset_index.json"),
+            &CancelToken::default(),
+        )
+        .unwrap()
+    }
+
+    fn entry_of(install: &SyntheticInstall, rel_path: &str, content: &[u8]) -> ModEntry {
+        let path = install.add_file(&format!("mods/{}", rel_path), content);
+        parser::build_mod_entry(&path, rel_path, &profiles::default_mod_extensions())
+    }
+
+    #[test]
+    fn rule_older_than_compares_the_mtime() {
+        let install = SyntheticInstall::new();
+        let loaded = entry_of(&install, "new.zipmod", b"new");
+        let mut skipped = entry_of(&install, "old.zipmod", b"old");
+        skipped.modified = Some(1_600_000_000);
+        let older =
+            |cutoff: u64| rule(serde_json::json!({ "older_than": cutoff, "action": "trash" }));
+
+        assert!(rule_matches(
+            &install,
+            &older(1_700_000_000),
+            &skipped,
+            &loaded
+        ));
+        assert!(!rule_matches(
+            &install,
+            &older(1_600_000_000),
+            &skipped,
+            &loaded
+        ));
+        skipped.modified = None;
+        assert!(!rule_matches(
+            &install,
+            &older(1_700_000_000),
+            &skipped,
+            &loaded
+        ));
+    }
+
+    #[test]
+    fn rule_under_path_matches_whole_folder_names() {
+        let install = SyntheticInstall::new();
+        let loaded = entry_of(&install, "hair.zipmod", b"hair");
+        let inside = entry_of(&install, "old/hair.zipmod", b"hair");
+        let sibling = entry_of(&install, "older/hair.zipmod", b"hair");
+        let relative = rule(serde_json::json!({ "under_path": "mods/old/", "action": "disable" }));
+        let absolute = rule(serde_json::json!({
+            "under_path": format!("{}\\mods\\OLD", install.game_path().replace('/', "\\")),
+            "action": "disable",
+        }));
+
+        for rule in [&relative, &absolute] {
+            assert!(rule_matches(&install, rule, &inside, &loaded));
+            assert!(!rule_matches(&install, rule, &sibling, &loaded));
+        }
+    }
+
+    #[test]
+    fn rule_identical_to_loaded_compares_content() {
+        let install = SyntheticInstall::new();
+        let loaded = entry_of(&install, "a/hair.zipmod", b"same bytes");
+        let copy = entry_of(&install, "b/hair.zipmod", b"same bytes");
+        let edited = entry_of(&install, "c/hair.zipmod", b"diff bytes");
+        let identical = rule(serde_json::json!({ "identical_to_loaded": true, "action": "trash" }));
+
+        assert!(rule_matches(&install, &identical, &copy, &loaded));
+        assert!(!rule_matches(&install, &identical, &edited, &loaded));
+        let missing = entry_of(&install, "d/hair.zipmod", b"same bytes");
+        fs::remove_file(&missing.path).unwrap();
+        assert!(!rule_matches(&install, &identical, &missing, &loaded));
+    }
+
+    #[test]
+    fn rule_size_above_is_strict() {
+        let install = SyntheticInstall::new();
+        let loaded = entry_of(&install, "new.zipmod", b"new");
+        let skipped = entry_of(&install, "old.zipmod", &[0; 100]);
+        let above =
+            |size: u64| rule(serde_json::json!({ "size_above": size, "action": "quarantine" }));
+
+        assert!(rule_matches(&install, &above(99), &skipped, &loaded));
+        assert!(!rule_matches(&install, &above(100), &skipped, &loaded));
+    }
+
+    #[test]
+    fn rule_manifest_author_ignores_case_and_needs_a_manifest() {
+        let install = SyntheticInstall::new();
+        let loaded = entry_of(&install, "new.zipmod", b"new");
+        let by_author = |rel_path: &str, author: &str| {
+            let manifest = TestManifest::new("author.hair").with_author(author);
+            let path = install.add_zipmod(rel_path, &manifest);
+            parser::build_mod_entry(&path, rel_path, &profiles::default_mod_extensions())
+        };
+        let theirs = by_author("theirs.zipmod", "Some Author");
+        let others = by_author("others.zipmod", "Someone Else");
+        let no_manifest = entry_of(&install, "broken.zipmod", b"not a zip");
+        let author =
+            rule(serde_json::json!({ "manifest_author": " some author", "action": "trash" }));
+
+        assert!(rule_matches(&install, &author, &theirs, &loaded));
+        assert!(!rule_matches(&install, &author, &others, &loaded));
+        assert!(!rule_matches(&install, &author, &no_manifest, &loaded));
+    }
+
+    #[test]
+    fn rule_plan_needs_a_condition_and_its_current_hash() {
+        let install = SyntheticInstall::new();
+        let loaded = entry_of(&install, "new.zipmod", b"new");
+        let big = entry_of(&install, "big.zipmod", &[0; 100]);
+        let small = entry_of(&install, "small.zipmod", b"small");
+        let conflicts = [ModConflict::new(loaded, vec![big, small])];
+        let profile = tempfile::tempdir().unwrap();
+        let evaluate = |rule: &ResolveRule| {
+            evaluate(
+                rule,
+                &install.game_path(),
+                &conflicts,
+                None,
+                &profile.path().join("asset_index.json"),
+                &CancelToken::default(),
+            )
+        };
+
+        let empty = rule(serde_json::json!({ "action": "trash" }));
+        assert!(matches!(
+            evaluate(&empty),
+            Err(AppError::InvalidInput { .. })
+        ));
+
+        let plan = evaluate(&rule(
+            serde_json::json!({ "size_above": 50, "action": "trash" }),
+        ))
+        .unwrap();
+        assert_eq!(plan.matches.len(), 1);
+        assert!(plan.matches[0].entry.path.ends_with("big.zipmod"));
+        assert_eq!(plan.bytes, 100);
+        assert!(ensure_confirmed(&plan, Some(&plan.plan_hash)).is_ok());
+        assert!(ensure_confirmed(&plan, None).is_err());
+
+        let wider = evaluate(&rule(
+            serde_json::json!({ "size_above": 1, "action": "trash" }),
+        ))
+        .unwrap();
+        assert_eq!(wider.matches.len(), 2);
+        assert!(ensure_confirmed(&wider, Some(&plan.plan_hash)).is_err());
+        let quarantine = evaluate(&rule(
+            serde_json::json!({ "size_above": 50, "action": "quarantine" }),
+        ))
+        .unwrap();
+        assert_ne!(quarantine.plan_hash, plan.plan_hash);
+    }
+}
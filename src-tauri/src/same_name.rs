@@ -0,0 +1,184 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::Path,
+};
+
+use serde::Serialize;
+
+use crate::mods_scan::ModFile;
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Largest-to-smallest size ratio above which same-named files are taken for different mods
+/// when the caller sets none
+/// 呼叫端未指定時，同名檔案的最大與最小大小比例超過此值即視為不同的模組
+pub(crate) const DEFAULT_MAX_SIZE_RATIO: f64 = 3.0;
+
+/// How sure a same-name group is to be a real conflict
+/// 同名群組為實際衝突的把握程度
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CandidateConfidence {
+    /// Every manifest is readable and declares the same GUID, so Sideloader loads only one
+    /// 所有 manifest 皆可讀取且宣告相同 GUID，因此 Sideloader 只會載入其中一個
+    SameGuid,
+    /// Only the filename matches; check before removing anything
+    /// 只有檔名相同；移除前請先確認
+    SameName,
+}
+
+/// Files sharing one filename in different folders, found without the log
+/// 不依賴 log 找出、位於不同資料夾但檔名相同的檔案
+#[derive(Serialize)]
+pub(crate) struct CandidateGroup {
+    /// The filename as the first file spells it / 依第一個檔案寫法的檔名
+    pub name: String,
+    pub confidence: CandidateConfidence,
+    /// The shared GUID for `same_guid` / `same_guid` 時共用的 GUID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guid: Option<String>,
+    /// Largest size over smallest / 最大檔案與最小檔案的大小比例
+    pub size_ratio: f64,
+    /// Sorted by path / 依路徑排序
+    pub files: Vec<ModFile>,
+}
+
+// ───────────────────────────────────────────────
+// Detection / 偵測
+// ───────────────────────────────────────────────
+
+/// The one GUID every file's manifest declares, compared without case; None when a manifest
+/// is missing or two differ
+/// 所有檔案的 manifest 皆宣告的同一個 GUID（不分大小寫）；有 manifest 缺少或互不相同時為 None
+fn shared_guid(files: &[ModFile]) -> Option<String> {
+    let first = files.first()?.manifest.as_ref()?.guid.clone();
+    files
+        .iter()
+        .all(|file| {
+            file.manifest
+                .as_ref()
+                .is_some_and(|manifest| manifest.guid.eq_ignore_ascii_case(&first))
+        })
+        .then_some(first)
+}
+
+/// Group `files` by filename, compared without case, keeping groups that span two or more
+/// folders and whose largest file is at most `max_size_ratio` times the smallest; empty files
+/// never qualify. For logs from Sideloader versions that wrote no duplicate warnings. A group
+/// whose manifests all declare one GUID is `same_guid`, the rest `same_name`; `same_guid`
+/// groups come first, each kind sorted by name.
+/// 依檔名（不分大小寫）分組 `files`，保留跨越兩個以上資料夾、且最大檔案不超過最小檔案 `max_size_ratio` 倍的群組；
+/// 空檔案一律不列入。用於不會寫出重複警告的舊版 Sideloader；所有 manifest 宣告同一 GUID 的群組為 `same_guid`，
+/// 其餘為 `same_name`；`same_guid` 群組在前，各自依名稱排序
+pub(crate) fn find_same_name_candidates(
+    files: Vec<ModFile>,
+    max_size_ratio: f64,
+) -> Vec<CandidateGroup> {
+    let mut by_name: BTreeMap<String, Vec<ModFile>> = BTreeMap::new();
+    for file in files {
+        by_name
+            .entry(file.entry.name.to_lowercase())
+            .or_default()
+            .push(file);
+    }
+
+    let mut groups = Vec::new();
+    for mut files in by_name.into_values() {
+        let folders = files
+            .iter()
+            .filter_map(|file| Path::new(&file.entry.path).parent())
+            .map(|folder| folder.to_string_lossy().to_lowercase())
+            .collect::<HashSet<_>>();
+        if folders.len() < 2 {
+            continue;
+        }
+        let sizes = files.iter().map(|file| file.entry.size);
+        let (smallest, largest) = (sizes.clone().min().unwrap_or(0), sizes.max().unwrap_or(0));
+        if smallest == 0 {
+            continue;
+        }
+        let size_ratio = largest as f64 / smallest as f64;
+        if size_ratio > max_size_ratio {
+            continue;
+        }
+        files.sort_by(|a, b| a.entry.path.cmp(&b.entry.path));
+        let guid = shared_guid(&files);
+        groups.push(CandidateGroup {
+            name: files[0].entry.name.clone(),
+            confidence: match guid {
+                Some(_) => CandidateConfidence::SameGuid,
+                None => CandidateConfidence::SameName,
+            },
+            guid,
+            size_ratio,
+            files,
+        });
+    }
+    groups.sort_by(|a, b| {
+        a.confidence
+            .cmp(&b.confidence)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_same_name_candidates, CandidateConfidence, DEFAULT_MAX_SIZE_RATIO};
+    use crate::{
+        mods_scan,
+        test_support::{write_zip, SyntheticInstall, TestManifest},
+    };
+
+    #[test]
+    fn same_name_candidates_skip_lopsided_sizes_and_confirm_matching_guids() {
+        let install = SyntheticInstall::new();
+        install.add_zipmod("pack/Hair.zipmod", &TestManifest::new("author.hair"));
+        install.add_zipmod("downloads/hair.zipmod", &TestManifest::new("Author.Hair"));
+        install.add_zipmod("pack/Top.zipmod", &TestManifest::new("author.top"));
+        install.add_zipmod("downloads/Top.zipmod", &TestManifest::new("other.top"));
+        install.add_zipmod("pack/Shoes.zipmod", &TestManifest::new("author.shoes"));
+        // Scrambled so deflate cannot shrink it / 打亂內容使 deflate 無法壓縮
+        let bundle = (0..64 * 1024u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect::<Vec<_>>();
+        write_zip(
+            &install.mod_path("downloads/Shoes.zipmod"),
+            &[
+                (
+                    "manifest.xml",
+                    TestManifest::new("author.shoes").to_xml().as_bytes(),
+                ),
+                ("abdata/shoes.unity3d", &bundle),
+            ],
+        );
+        install.add_zipmod("Unique.zipmod", &TestManifest::new("author.unique"));
+
+        let files = mods_scan::scan_mods_folder(
+            &install.root().join("asset_index.json"),
+            install.root(),
+            false,
+            &["zipmod".to_string()],
+            &Default::default(),
+            &|_| {},
+        )
+        .unwrap();
+        let groups = find_same_name_candidates(files, DEFAULT_MAX_SIZE_RATIO);
+
+        let summary = groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.files.len(), group.confidence))
+            .collect::<Vec<_>>();
+        assert!(
+            summary
+                == [
+                    ("hair.zipmod", 2, CandidateConfidence::SameGuid),
+                    ("Top.zipmod", 2, CandidateConfidence::SameName),
+                ]
+        );
+        assert_eq!(groups[0].guid.as_deref(), Some("Author.Hair"));
+        assert_eq!(groups[1].guid, None);
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs, iter,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    app_log,
+    error::AppError,
+    log_environment::{self, LogEnvironment},
+    long_path, mod_roots,
+    parser::{
+        mark_changed_since_log, parse_conflicts, parse_conflicts_with_progress,
+        refresh_conflict_entries, ModConflict,
+    },
+    profiles,
+    progress::OnProgress,
+    scan_cache,
+    timings::Timings,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Totals over one scan result
+/// 單次掃描結果的統計
+#[derive(Serialize, Clone, PartialEq)]
+pub struct ScanSummary {
+    pub conflicts: usize,
+    pub skipped_files: usize,
+    pub skipped_bytes: u64,
+    /// Files changed after the log was written; relaunching the game refreshes the log
+    /// log 寫入後有變動的檔案數；重新啟動遊戲即可更新 log
+    pub changed_since_log: usize,
+    /// `skipped_bytes` split by the volume of each file's mod root, for when a mod root is a
+    /// link to another drive; files of unknown volume are left out
+    /// 依各檔案模組資料夾所在磁碟區拆分的 `skipped_bytes`，供模組資料夾連結到其他磁碟時使用；磁碟區不明的檔案不列入
+    pub skipped_bytes_by_volume: BTreeMap<String, u64>,
+}
+
+/// Result of `scan_conflicts`: the parsed conflicts plus where they came from
+/// `scan_conflicts` 的結果：衝突清單與其來源
+#[derive(Serialize)]
+pub struct ScanResult {
+    pub log_path: String,
+    pub conflicts: Vec<ModConflict>,
+    pub summary: ScanSummary,
+    /// BepInEx and Sideloader versions that wrote the log
+    /// 寫出此 log 的 BepInEx 與 Sideloader 版本
+    pub environment: LogEnvironment,
+    pub from_cache: bool,
+    /// Per-phase milliseconds, only present when requested
+    /// 各階段毫秒數，僅在要求時出現
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<BTreeMap<&'static str, f64>>,
+}
+
+// ───────────────────────────────────────────────
+// Log Files / Log 檔案
+// ───────────────────────────────────────────────
+
+/// Locate the first known log file under the game path
+/// 在遊戲路徑下找出第一個已知的 log 檔案
+pub fn find_log_file(game_path: &str) -> Result<PathBuf, AppError> {
+    let root = PathBuf::from(game_path);
+    let candidates = vec![
+        root.join("output_log.txt"),
+        root.join("Koikatsu_Data").join("output_log.txt"),
+        root.join("BepInEx").join("LogOutput.log"),
+    ];
+
+    candidates
+        .into_iter()
+        .find(|candidate| long_path::extend(candidate).exists())
+        .ok_or_else(|| AppError::LogNotFound {
+            game_path: game_path.to_string(),
+        })
+}
+
+/// Find and read the first known log file under the game path
+/// 在遊戲路徑下找出並讀取第一個已知的 log 檔案
+pub fn find_and_read_log(game_path: &str) -> Result<String, AppError> {
+    let log_path = find_log_file(game_path)?;
+    read_log_file(&log_path)
+}
+
+pub fn read_log_file(log_path: &Path) -> Result<String, AppError> {
+    fs::read_to_string(long_path::extend(log_path)).map_err(|e| AppError::LogUnreadable {
+        path: long_path::display(log_path),
+        detail: e.to_string(),
+    })
+}
+
+// ───────────────────────────────────────────────
+// Scanning / 掃描
+// ───────────────────────────────────────────────
+
+/// Totals over the skipped files, counting a physical file reached via several links once
+/// 統計被跳過的檔案；經由多個連結指向的同一實體檔案只計一次
+fn summarize(conflicts: &[ModConflict]) -> ScanSummary {
+    let mut seen = HashSet::new();
+    let skipped = conflicts
+        .iter()
+        .flat_map(|c| &c.skipped)
+        .filter(|entry| seen.insert(entry.link_target.as_deref().unwrap_or(&entry.path)))
+        .collect::<Vec<_>>();
+    ScanSummary {
+        conflicts: conflicts.len(),
+        skipped_files: skipped.len(),
+        skipped_bytes: skipped.iter().map(|entry| entry.size).sum(),
+        skipped_bytes_by_volume: skipped.iter().fold(BTreeMap::new(), |mut volumes, entry| {
+            if let Some(volume) = &entry.volume {
+                *volumes.entry(volume.clone()).or_default() += entry.size;
+            }
+            volumes
+        }),
+        changed_since_log: conflicts
+            .iter()
+            .flat_map(|c| iter::once(&c.loaded).chain(&c.skipped))
+            .filter(|entry| entry.changed_since_log)
+            .count(),
+    }
+}
+
+/// Find, read and parse the game log without touching any cache, with the default mod
+/// extensions as no profile applies; fails with `mods_dir_missing` when the game has no mods
+/// folder, whose entries would all be missing
+/// 尋找、讀取並解析遊戲 log，不使用快取，且因沒有 profile 而使用預設的模組副檔名；
+/// 遊戲沒有 mods 資料夾時回傳 `mods_dir_missing`，否則每個項目都會是不存在的檔案
+pub fn scan_conflicts(game_path: &str) -> Result<ScanResult, AppError> {
+    let log_path = find_log_file(game_path)?;
+    mod_roots::ensure_mods_dir(Path::new(game_path))?;
+    let identity = scan_cache::LogIdentity::of(&log_path)?;
+    let log = read_log_file(&log_path)?;
+    let mut conflicts = parse_conflicts(&log, game_path, &profiles::default_mod_extensions());
+    mark_changed_since_log(&mut conflicts, identity.modified);
+    Ok(ScanResult {
+        log_path: long_path::display(&log_path),
+        summary: summarize(&conflicts),
+        environment: log_environment::detect(&log),
+        conflicts,
+        from_cache: false,
+        timings: None,
+    })
+}
+
+/// Read and parse the game log with the profile's `mod_extensions`, reusing the cached result
+/// when the log is unchanged.
+/// Phase durations go into `timings` and are returned with the result. Fails with
+/// `mods_dir_missing` like `scan_conflicts`.
+/// 以 profile 的 `mod_extensions` 讀取並解析遊戲 log；若 log 未變動則沿用快取結果。各階段耗時記錄於 `timings` 並隨結果回傳；
+/// 與 `scan_conflicts` 相同，沒有 mods 資料夾時回傳 `mods_dir_missing`
+pub fn scan_conflicts_cached(
+    cache_path: &Path,
+    game_path: &str,
+    mod_extensions: &[String],
+    force_rescan: bool,
+    on_progress: OnProgress,
+    timings: Timings,
+) -> Result<ScanResult, AppError> {
+    let log_path = find_log_file(game_path)?;
+    mod_roots::ensure_mods_dir(Path::new(game_path))?;
+    let identity = scan_cache::LogIdentity::of(&log_path)?;
+
+    let cached = if force_rescan {
+        None
+    } else {
+        timings.time("cache_read_ms", || scan_cache::load(cache_path, &identity))
+    };
+    let from_cache = cached.is_some();
+
+    let (mut conflicts, environment) = match cached {
+        Some(conflicts) => (
+            timings.time("metadata_ms", || {
+                refresh_conflict_entries(conflicts, mod_extensions, on_progress)
+            }),
+            log_environment::read(&log_path),
+        ),
+        None => {
+            let log = timings.time("log_read_ms", || read_log_file(&log_path))?;
+            let conflicts = parse_conflicts_with_progress(
+                &log,
+                game_path,
+                mod_extensions,
+                on_progress,
+                &timings,
+            );
+            // A cache that cannot be written only costs the next scan its shortcut
+            // 無法寫入的快取只會讓下次掃描無法走捷徑
+            let saved = timings.time("cache_write_ms", || {
+                scan_cache::save(cache_path, &identity, &conflicts)
+            });
+            if let Err(e) = saved {
+                app_log::write(&format!("scan cache not saved: {}", e));
+            }
+            (conflicts, log_environment::detect(&log))
+        }
+    };
+    mark_changed_since_log(&mut conflicts, identity.modified);
+
+    Ok(ScanResult {
+        log_path: identity.path,
+        summary: summarize(&conflicts),
+        environment,
+        conflicts,
+        from_cache,
+        timings: timings.finish(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::scan_conflicts_cached;
+    use crate::{
+        profiles, progress,
+        test_support::{SyntheticInstall, SyntheticLog},
+        timings::Timings,
+    };
+
+    #[test]
+    fn unwritable_scan_cache_still_returns_the_scan() {
+        let install = SyntheticInstall::new();
+        install.add_file("mods/a.zipmod", b"a");
+        install.add_file("mods/b.zipmod", b"b");
+        install.write_log(&SyntheticLog::new().conflict("a.zipmod", &["b.zipmod"]));
+        // A regular file where the cache folder should be / 快取資料夾位置上的一般檔案
+        let blocker = install.root().join("blocker");
+        fs::write(&blocker, b"").unwrap();
+
+        let scan = scan_conflicts_cached(
+            &blocker.join("scan_cache.json"),
+            &install.game_path(),
+            &profiles::default_mod_extensions(),
+            false,
+            &progress::ignore,
+            Timings::default(),
+        );
+
+        assert_eq!(scan.unwrap().conflicts.len(), 1);
+    }
+}
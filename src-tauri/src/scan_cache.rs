@@ -0,0 +1,92 @@
+use std::{fs, path::Path, time::UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::AppError, long_path, parser::ModConflict};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Identity of a log file; the cache is only valid while all fields match
+/// log 檔案的識別資訊；所有欄位相同時快取才有效
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub(crate) struct LogIdentity {
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct CachedScanRef<'a> {
+    log: &'a LogIdentity,
+    conflicts: &'a [ModConflict],
+}
+
+#[derive(Deserialize)]
+struct CachedScan {
+    log: LogIdentity,
+    conflicts: Vec<ModConflict>,
+}
+
+// ───────────────────────────────────────────────
+// Cache Access / 快取存取
+// ───────────────────────────────────────────────
+
+impl LogIdentity {
+    /// Read the identity of the log file at `path`
+    /// 讀取指定 log 檔案的識別資訊
+    pub fn of(path: &Path) -> Result<Self, AppError> {
+        let metadata =
+            fs::metadata(long_path::extend(path)).map_err(|e| AppError::LogUnreadable {
+                path: long_path::display(path),
+                detail: e.to_string(),
+            })?;
+        Ok(LogIdentity {
+            path: long_path::display(path),
+            size: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|dur| dur.as_secs()),
+        })
+    }
+}
+
+/// Load cached conflicts if they were parsed from a log with the same identity
+/// 若快取來自相同識別資訊的 log，則載入快取的衝突結果
+pub(crate) fn load(cache_path: &Path, identity: &LogIdentity) -> Option<Vec<ModConflict>> {
+    let content = fs::read_to_string(cache_path).ok()?;
+    let cached: CachedScan = serde_json::from_str(&content).ok()?;
+    (cached.log == *identity).then_some(cached.conflicts)
+}
+
+/// Conflicts of the cached scan whatever log they came from, e.g. to tell which ids the latest
+/// scan listed
+/// 不論來源 log 為何，取得快取中的衝突，例如用來得知最近一次掃描列出的 id
+pub(crate) fn load_latest(cache_path: &Path) -> Option<Vec<ModConflict>> {
+    let content = fs::read_to_string(cache_path).ok()?;
+    let cached: CachedScan = serde_json::from_str(&content).ok()?;
+    Some(cached.conflicts)
+}
+
+/// Persist parsed conflicts together with the log identity they came from
+/// 將解析結果與其來源 log 的識別資訊一併儲存
+pub(crate) fn save(
+    cache_path: &Path,
+    identity: &LogIdentity,
+    conflicts: &[ModConflict],
+) -> Result<(), AppError> {
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io("io.create_cache_directory", Some(parent), e))?;
+    }
+    let content = serde_json::to_string(&CachedScanRef {
+        log: identity,
+        conflicts,
+    })
+    .map_err(|e| AppError::io("io.serialize_scan_cache", Some(cache_path), e))?;
+    fs::write(cache_path, content)
+        .map_err(|e| AppError::io("io.write_scan_cache", Some(cache_path), e))
+}
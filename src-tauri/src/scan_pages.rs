@@ -0,0 +1,206 @@
+use std::{
+    cmp::Reverse,
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+
+use crate::{
+    error::AppError,
+    messages::Message,
+    mods_scan::{GuidGroup, ModFile},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Completed scans kept at once; the oldest is dropped when another finishes
+/// 同時保留的已完成掃描數；新掃描完成時捨棄最舊的
+const MAX_STORED_SCANS: usize = 4;
+
+/// Page size used when the frontend does not pass `limit`
+/// 前端未指定 `limit` 時的每頁筆數
+const DEFAULT_PAGE_LIMIT: usize = 200;
+
+/// Full result of one scan, computed once and paged from memory
+/// 單次掃描的完整結果，只計算一次並從記憶體分頁
+pub(crate) enum StoredScan {
+    Mods(Vec<ModFile>),
+    GuidGroups(Vec<GuidGroup>),
+}
+
+/// Scan results by operation id, stored in Tauri managed state
+/// 以作業 id 存放的掃描結果，存放於 Tauri managed state
+#[derive(Default)]
+pub(crate) struct ScanStore {
+    scans: Mutex<VecDeque<(String, Arc<StoredScan>)>>,
+}
+
+/// Order of the items in a page
+/// 分頁項目的排序方式
+#[derive(Clone, Copy)]
+pub(crate) enum SortBy {
+    Name,
+    Size,
+    Modified,
+    WastedBytes,
+}
+
+/// Items of one page, matching the kind of scan
+/// 單頁項目，型別依掃描種類而定
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum PageItems {
+    Mods(Vec<ModFile>),
+    GuidGroups(Vec<GuidGroup>),
+}
+
+/// One page of a stored scan; `total_count` is the size of the whole result
+/// 已存掃描結果的一頁；`total_count` 為完整結果的筆數
+#[derive(Serialize)]
+pub(crate) struct Page {
+    operation_id: String,
+    pub total_count: usize,
+    offset: usize,
+    items: PageItems,
+}
+
+impl SortBy {
+    /// Parse the `sort_by` argument; missing means by name
+    /// 解析 `sort_by` 參數；未指定時依名稱排序
+    pub(crate) fn parse(sort_by: Option<&str>) -> Result<Self, AppError> {
+        match sort_by.unwrap_or("name") {
+            "name" => Ok(SortBy::Name),
+            "size" => Ok(SortBy::Size),
+            "modified" => Ok(SortBy::Modified),
+            "wasted_bytes" => Ok(SortBy::WastedBytes),
+            other => Err(AppError::InvalidInput {
+                message: Message::new("input.unknown_sort_by").with("sort_by", other),
+            }),
+        }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Store / 結果存放
+// ───────────────────────────────────────────────
+
+impl ScanStore {
+    /// Keep `scan` under `operation_id`, replacing an earlier scan with the same id
+    /// 以 `operation_id` 存放 `scan`，取代同 id 的舊結果
+    pub(crate) fn insert(&self, operation_id: &str, scan: StoredScan) -> Arc<StoredScan> {
+        let scan = Arc::new(scan);
+        let mut scans = self.scans.lock().unwrap();
+        scans.retain(|(id, _)| id != operation_id);
+        if scans.len() >= MAX_STORED_SCANS {
+            scans.pop_front();
+        }
+        scans.push_back((operation_id.to_string(), scan.clone()));
+        scan
+    }
+
+    /// The stored scan, or `ScanExpired` if it was released, evicted or never existed
+    /// 取得已存的掃描結果；已釋放、被捨棄或不存在時回傳 `ScanExpired`
+    pub(crate) fn get(&self, operation_id: &str) -> Result<Arc<StoredScan>, AppError> {
+        self.scans
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(id, _)| id == operation_id)
+            .map(|(_, scan)| scan.clone())
+            .ok_or_else(|| AppError::ScanExpired {
+                operation_id: operation_id.to_string(),
+            })
+    }
+
+    /// Drop a stored scan; returns whether it was still held
+    /// 釋放已存的掃描結果；回傳是否仍有保留
+    pub(crate) fn release(&self, operation_id: &str) -> bool {
+        let mut scans = self.scans.lock().unwrap();
+        let before = scans.len();
+        scans.retain(|(id, _)| id != operation_id);
+        scans.len() != before
+    }
+}
+
+// ───────────────────────────────────────────────
+// Paging / 分頁
+// ───────────────────────────────────────────────
+
+fn newest(files: &[ModFile]) -> Option<u64> {
+    files.iter().filter_map(|file| file.entry.modified).max()
+}
+
+/// Sorted slice `[offset, offset + limit)` of `items`; ties keep the stored order
+/// `items` 排序後的 `[offset, offset + limit)` 區段；同值時維持原順序
+fn slice_sorted<T: Clone, K: Ord>(
+    items: &[T],
+    offset: usize,
+    limit: usize,
+    key: impl Fn(&T) -> K,
+) -> Vec<T> {
+    let mut order = items.iter().collect::<Vec<_>>();
+    order.sort_by_cached_key(|item| key(item));
+    order
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .cloned()
+        .collect()
+}
+
+/// Cut one page out of a stored scan. Name sorts ascending, everything else largest
+/// or newest first; mod files have no waste of their own, so `wasted_bytes` sorts them by size.
+/// 從已存掃描結果切出一頁；名稱遞增排序，其餘由大到小或由新到舊；單一檔案沒有浪費空間，故 `wasted_bytes` 以大小排序
+pub(crate) fn page(
+    operation_id: &str,
+    scan: &StoredScan,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    sort_by: SortBy,
+) -> Page {
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    let (total_count, items) = match scan {
+        StoredScan::Mods(files) => {
+            let items = match sort_by {
+                SortBy::Name => {
+                    slice_sorted(files, offset, limit, |file| file.entry.name.to_lowercase())
+                }
+                SortBy::Size | SortBy::WastedBytes => {
+                    slice_sorted(files, offset, limit, |file| Reverse(file.entry.size))
+                }
+                SortBy::Modified => {
+                    slice_sorted(files, offset, limit, |file| Reverse(file.entry.modified))
+                }
+            };
+            (files.len(), PageItems::Mods(items))
+        }
+        StoredScan::GuidGroups(groups) => {
+            let items = match sort_by {
+                SortBy::Name => {
+                    slice_sorted(groups, offset, limit, |group| group.guid.to_lowercase())
+                }
+                SortBy::Size => slice_sorted(groups, offset, limit, |group| {
+                    Reverse(group.files.iter().map(|file| file.entry.size).sum::<u64>())
+                }),
+                SortBy::Modified => {
+                    slice_sorted(groups, offset, limit, |group| Reverse(newest(&group.files)))
+                }
+                SortBy::WastedBytes => {
+                    slice_sorted(groups, offset, limit, |group| Reverse(group.wasted_bytes))
+                }
+            };
+            (groups.len(), PageItems::GuidGroups(items))
+        }
+    };
+
+    Page {
+        operation_id: operation_id.to_string(),
+        total_count,
+        offset,
+        items,
+    }
+}
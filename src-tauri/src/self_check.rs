@@ -0,0 +1,360 @@
+use std::{
+    fs,
+    path::Path,
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+    cloud_files, diagnostics::detect_game_variant, disk_space, game_process, long_path,
+    messages::Message, mod_roots, path_guard::WriteMode, scan::find_log_file,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Free space below which writes are likely to fail partway
+/// 可用空間低於此值時，寫入很可能中途失敗
+const LOW_SPACE_FAIL: u64 = 100 * 1024 * 1024;
+
+/// Free space below which quarantine and conversion start to run short
+/// 可用空間低於此值時，隔離與轉換開始可能不足
+const LOW_SPACE_WARN: u64 = 2 * 1024 * 1024 * 1024;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// One line of the pre-flight checklist; `detail` is the English rendering of `key`/`params`
+/// 預檢清單的一項；`detail` 為 `key`/`params` 的英文內容
+#[derive(Serialize)]
+pub(crate) struct CheckResult {
+    pub id: &'static str,
+    pub status: CheckStatus,
+    #[serde(flatten)]
+    pub text: Message,
+    pub detail: String,
+}
+
+fn result(id: &'static str, status: CheckStatus, text: Message) -> CheckResult {
+    CheckResult {
+        id,
+        status,
+        detail: text.render(),
+        text,
+    }
+}
+
+// ───────────────────────────────────────────────
+// Checks / 檢查項目
+// ───────────────────────────────────────────────
+
+/// Coarse age for messages, e.g. `5 minutes`
+/// 訊息用的粗略經過時間，例如 `5 minutes`
+pub(crate) fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..=119 => format!("{} seconds", seconds),
+        120..=7199 => format!("{} minutes", seconds / 60),
+        7200..=172_799 => format!("{} hours", seconds / 3600),
+        _ => format!("{} days", seconds / 86_400),
+    }
+}
+
+fn check_game_path(game_path: &Path) -> CheckResult {
+    let path = long_path::display(game_path);
+    if !long_path::extend(game_path).is_dir() {
+        let text = Message::new("check.game_path_missing").with("path", path);
+        return result("game_path", CheckStatus::Fail, text);
+    }
+    match detect_game_variant(game_path) {
+        Some(variant) => {
+            let text = Message::new("check.game_path_ok")
+                .with("path", path)
+                .with("variant", variant);
+            result("game_path", CheckStatus::Ok, text)
+        }
+        None => {
+            let text = Message::new("check.game_path_unknown").with("path", path);
+            result("game_path", CheckStatus::Warn, text)
+        }
+    }
+}
+
+fn check_log(game_path: &Path) -> CheckResult {
+    let Ok(log_path) = find_log_file(&game_path.to_string_lossy()) else {
+        return result("log", CheckStatus::Warn, Message::new("check.log_missing"));
+    };
+    let path = long_path::display(&log_path);
+    let modified = fs::metadata(long_path::extend(&log_path))
+        .and_then(|meta| meta.modified())
+        .ok();
+    let age = modified.and_then(|modified| SystemTime::now().duration_since(modified).ok());
+    let text = match age {
+        Some(age) => Message::new("check.log_found")
+            .with("path", path)
+            .with("age", format_age(age.as_secs()))
+            .with(
+                "modified",
+                modified
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |d| d.as_secs()),
+            ),
+        None => Message::new("check.log_found_undated").with("path", path),
+    };
+    result("log", CheckStatus::Ok, text)
+}
+
+/// Create, write and delete a scratch file in the mods folder; skipped in read-only mode
+/// 在 mods 資料夾中建立、寫入並刪除暫存檔；唯讀模式下略過
+fn check_mods_writable(game_path: &Path, write_mode: WriteMode) -> CheckResult {
+    let mods = game_path.join("mods");
+    let path = long_path::display(&mods);
+    if !long_path::extend(&mods).is_dir() {
+        let text = Message::new("check.mods_missing").with("path", path);
+        return result("mods_writable", CheckStatus::Fail, text);
+    }
+    if write_mode.is_read_only() {
+        let text = Message::new("check.mods_write_skipped").with("path", path);
+        return result("mods_writable", CheckStatus::Warn, text);
+    }
+    let probe = mods.join(format!(".kk-self-check-{}.tmp", process::id()));
+    if let Err(e) = fs::write(long_path::extend(&probe), b"self-check") {
+        let text = Message::new("check.mods_read_only")
+            .with("path", path)
+            .with("detail", e);
+        return result("mods_writable", CheckStatus::Fail, text);
+    }
+    match fs::remove_file(long_path::extend(&probe)) {
+        Ok(()) => {
+            let text = Message::new("check.mods_writable").with("path", path);
+            result("mods_writable", CheckStatus::Ok, text)
+        }
+        Err(e) => {
+            let text = Message::new("check.mods_undeletable")
+                .with("path", long_path::display(&probe))
+                .with("detail", e);
+            result("mods_writable", CheckStatus::Fail, text)
+        }
+    }
+}
+
+/// Query the Recycle Bin without putting anything into it
+/// 查詢回收桶是否可用，不放入任何檔案
+#[cfg(any(
+    windows,
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+fn check_trash() -> CheckResult {
+    match trash::os_limited::is_empty() {
+        Ok(_) => result("trash", CheckStatus::Ok, Message::new("check.trash_ok")),
+        Err(e) => {
+            // A freedesktop trash folder is only created on first use, so its absence is no failure
+            // freedesktop 的回收桶資料夾在第一次使用時才建立，因此不存在不算失敗
+            let status = match cfg!(windows) {
+                true => CheckStatus::Fail,
+                false => CheckStatus::Warn,
+            };
+            let text = Message::new("check.trash_unavailable").with("detail", e);
+            result("trash", status, text)
+        }
+    }
+}
+
+#[cfg(not(any(
+    windows,
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+fn check_trash() -> CheckResult {
+    result(
+        "trash",
+        CheckStatus::Warn,
+        Message::new("check.trash_unknown"),
+    )
+}
+
+fn check_free_space(game_path: &Path) -> CheckResult {
+    let path = long_path::display(game_path);
+    let Some(available) = disk_space::available_bytes(game_path) else {
+        let text = Message::new("check.space_unknown").with("path", path);
+        return result("free_space", CheckStatus::Warn, text);
+    };
+    let status = if available < LOW_SPACE_FAIL {
+        CheckStatus::Fail
+    } else if available < LOW_SPACE_WARN {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Ok
+    };
+    let key = match status {
+        CheckStatus::Ok => "check.space_ok",
+        _ => "check.space_low",
+    };
+    let text = Message::new(key)
+        .with("path", path)
+        .with("available", disk_space::format_bytes(available));
+    result("free_space", status, text)
+}
+
+/// Mod roots that are links, sit on another volume than the game folder or inside a cloud
+/// sync folder; free space is checked again on each other volume, since that is where moves
+/// and copies land
+/// 為連結、與遊戲資料夾位於不同磁碟區或位於雲端同步資料夾中的模組根目錄；移動與複製實際發生在其他磁碟區，
+/// 因此會另外檢查各磁碟區的可用空間
+fn check_mod_roots(game_path: &Path) -> Vec<CheckResult> {
+    let mut results = Vec::new();
+    for directory in mod_roots::mod_directories(game_path) {
+        let synced = std::iter::once(directory.path.as_str())
+            .chain(directory.link_target.as_deref())
+            .find_map(|path| cloud_files::cloud_sync_service(Path::new(path)));
+        if let Some(service) = synced {
+            let text = Message::new("check.mod_root_cloud_sync")
+                .with("path", directory.path.as_str())
+                .with("service", service);
+            results.push(result("mod_root", CheckStatus::Warn, text));
+        }
+        if let Some(target) = &directory.link_target {
+            let text = Message::new("check.mod_root_link")
+                .with("path", directory.path.as_str())
+                .with("target", target.as_str());
+            results.push(result("mod_root", CheckStatus::Ok, text));
+        }
+        if let (true, Some(volume)) = (directory.other_volume, &directory.volume) {
+            let text = Message::new("check.mod_root_other_volume")
+                .with("path", directory.path.as_str())
+                .with("volume", volume.as_str());
+            results.push(result("mod_root", CheckStatus::Warn, text));
+            results.push(check_free_space(Path::new(&directory.path)));
+        }
+    }
+    results
+}
+
+/// Value of `key` in `[section]` of a BepInEx-style config file, last occurrence winning
+/// BepInEx 格式設定檔中 `[section]` 的 `key` 值，重複時以最後一個為準
+fn config_value<'a>(config: &'a str, section: &str, key: &str) -> Option<&'a str> {
+    let mut current = "";
+    let mut found = None;
+    for line in config.lines().map(str::trim) {
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            current = name.trim();
+        } else if let Some((name, value)) = line.split_once('=') {
+            if !line.starts_with('#')
+                && current.eq_ignore_ascii_case(section)
+                && name.trim().eq_ignore_ascii_case(key)
+            {
+                found = Some(value.trim());
+            }
+        }
+    }
+    found
+}
+
+/// Whether a BepInEx log level list such as `Fatal, Error, Warning` lets warnings through
+/// BepInEx 記錄等級清單（例如 `Fatal, Error, Warning`）是否包含警告
+fn levels_include_warning(levels: &str) -> bool {
+    levels
+        .split([',', '|'])
+        .map(str::trim)
+        .any(|level| level.eq_ignore_ascii_case("warning") || level.eq_ignore_ascii_case("all"))
+}
+
+/// Make sure BepInEx writes Sideloader's duplicate-mod warnings to its disk log: disk logging
+/// must be enabled and its level filter must include Warning. A missing config means BepInEx
+/// defaults, which log them.
+/// 確認 BepInEx 會將 Sideloader 的重複模組警告寫入磁碟 log：須啟用磁碟記錄，且記錄等級須包含 Warning；
+/// 設定檔不存在時為 BepInEx 預設值，會記錄這些警告
+fn check_logging_config(game_path: &Path) -> CheckResult {
+    let config_path = game_path.join("BepInEx").join("config").join("BepInEx.cfg");
+    let Ok(config) = fs::read_to_string(long_path::extend(&config_path)) else {
+        let text = Message::new("check.logging_defaults");
+        return result("logging_config", CheckStatus::Ok, text);
+    };
+    let path = long_path::display(&config_path);
+
+    if let Some(enabled) = config_value(&config, "Logging.Disk", "Enabled") {
+        if enabled.eq_ignore_ascii_case("false") {
+            let text = Message::new("check.logging_disk_disabled")
+                .with("path", path)
+                .with("setting", "[Logging.Disk] Enabled")
+                .with("value", enabled);
+            return result("logging_config", CheckStatus::Warn, text);
+        }
+    }
+    // BepInEx 5 names the filter DisplayedLogLevel, BepInEx 6 LogLevels
+    // BepInEx 5 的過濾設定名為 DisplayedLogLevel，BepInEx 6 則為 LogLevels
+    for key in ["LogLevels", "DisplayedLogLevel"] {
+        if let Some(levels) = config_value(&config, "Logging.Disk", key) {
+            if !levels_include_warning(levels) {
+                let text = Message::new("check.logging_level_filtered")
+                    .with("path", path)
+                    .with("setting", format!("[Logging.Disk] {}", key))
+                    .with("value", levels)
+                    .with("required", "Warning");
+                return result("logging_config", CheckStatus::Warn, text);
+            }
+        }
+    }
+    let text = Message::new("check.logging_ok").with("path", path);
+    result("logging_config", CheckStatus::Ok, text)
+}
+
+fn check_game_running(game_path: &Path) -> CheckResult {
+    match game_process::running_executable(game_path) {
+        Some(executable) => {
+            let text = Message::new("check.game_running").with("executable", executable);
+            result("game_running", CheckStatus::Warn, text)
+        }
+        None => result(
+            "game_running",
+            CheckStatus::Ok,
+            Message::new("check.game_not_running"),
+        ),
+    }
+}
+
+/// Pre-flight checks of an install: game path, log, BepInEx logging settings, mods folder
+/// write access, mod roots linked elsewhere or kept in a cloud sync folder, Recycle Bin, free
+/// space and whether the game is running. Checks that need
+/// the game folder are skipped when it does not exist. The write check is skipped in read-only
+/// `write_mode`.
+/// 安裝環境的預檢：遊戲路徑、log、BepInEx 記錄設定、mods 資料夾寫入權限、連結至他處或位於雲端同步資料夾的模組根目錄、回收桶、可用空間與遊戲是否執行中；
+/// 遊戲資料夾不存在時略過需要它的檢查；`write_mode` 為唯讀時略過寫入檢查
+pub(crate) fn run_self_check(game_path: &Path, write_mode: WriteMode) -> Vec<CheckResult> {
+    let game = check_game_path(game_path);
+    if game.status == CheckStatus::Fail {
+        return vec![game, check_trash()];
+    }
+    let mut results = vec![
+        game,
+        check_log(game_path),
+        check_logging_config(game_path),
+        check_mods_writable(game_path, write_mode),
+    ];
+    results.extend(check_mod_roots(game_path));
+    results.extend([
+        check_trash(),
+        check_free_space(game_path),
+        check_game_running(game_path),
+    ]);
+    results
+}
@@ -0,0 +1,194 @@
+use std::{collections::HashMap, path::Path, sync::Mutex};
+
+use serde::Serialize;
+
+use crate::{
+    error::AppError, long_path, messages::Message, parser::ModConflict, profiles::profile_id,
+    resolution::ResolveAction,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// A skipped file this app acted on during the session
+/// 本次工作階段中由本程式處理過的被跳過檔案
+#[derive(Serialize, Clone)]
+pub(crate) struct HandledFile {
+    path: String,
+    action: ResolveAction,
+}
+
+/// Session status of one conflict. `resolved` means at least one skipped file was handled;
+/// `in_last_scan` turns false once a re-scan no longer lists it (e.g. after a relaunch).
+/// 單一衝突的工作階段狀態；`resolved` 表示至少處理過一個被跳過檔案；重新掃描不再列出時（例如重新啟動遊戲後）
+/// `in_last_scan` 變為 false
+#[derive(Serialize, Clone)]
+pub(crate) struct ConflictStatus {
+    id: String,
+    loaded: String,
+    skipped: Vec<String>,
+    handled: Vec<HandledFile>,
+    resolved: bool,
+    in_last_scan: bool,
+}
+
+/// Progress of one profile's cleanup session
+/// 單一 profile 清理工作階段的進度
+#[derive(Serialize, Clone)]
+pub(crate) struct SessionReport {
+    profile_id: String,
+    game_path: String,
+    total: usize,
+    resolved: usize,
+    conflicts: Vec<ConflictStatus>,
+}
+
+struct ProfileSession {
+    game_path: String,
+    conflicts: Vec<ConflictStatus>,
+}
+
+/// Per-profile session state, stored in Tauri managed state. Kept until the app exits or
+/// `reset` is called.
+/// 各 profile 的工作階段狀態，存放於 Tauri managed state；保留至程式結束或呼叫 `reset`
+#[derive(Default)]
+pub(crate) struct SessionProgress(Mutex<Sessions>);
+
+#[derive(Default)]
+struct Sessions {
+    profiles: HashMap<String, ProfileSession>,
+    last_profile: Option<String>,
+}
+
+// ───────────────────────────────────────────────
+// Tracking / 追蹤
+// ───────────────────────────────────────────────
+
+/// Comparable form of a path: display form, `/` separators, lowercase
+/// 可比較的路徑形式：顯示格式、以 `/` 分隔且為小寫
+fn path_key(path: &str) -> String {
+    long_path::display(Path::new(path))
+        .replace('\\', "/")
+        .to_lowercase()
+}
+
+fn report(profile: &str, session: &ProfileSession) -> SessionReport {
+    SessionReport {
+        profile_id: profile.to_string(),
+        game_path: session.game_path.clone(),
+        total: session.conflicts.len(),
+        resolved: session.conflicts.iter().filter(|c| c.resolved).count(),
+        conflicts: session.conflicts.clone(),
+    }
+}
+
+impl SessionProgress {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Sessions> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Add the conflicts of a scan to the profile's session. Conflicts already tracked keep
+    /// their handled files, matched by id, so re-parsing the same log loses nothing.
+    /// 將一次掃描的衝突加入 profile 的工作階段；已追蹤的衝突依 id 對應並保留已處理的檔案，重新解析同一份 log 不會遺失進度
+    pub fn track(&self, game_path: &str, conflicts: &[ModConflict]) {
+        let profile = profile_id(game_path);
+        let mut sessions = self.lock();
+        sessions.last_profile = Some(profile.clone());
+        let session = sessions
+            .profiles
+            .entry(profile)
+            .or_insert_with(|| ProfileSession {
+                game_path: game_path.to_string(),
+                conflicts: Vec::new(),
+            });
+        for status in &mut session.conflicts {
+            status.in_last_scan = false;
+        }
+        for conflict in conflicts {
+            let skipped = conflict.skipped.iter().map(|e| e.path.clone()).collect();
+            match session.conflicts.iter_mut().find(|s| s.id == conflict.id) {
+                Some(status) => {
+                    status.in_last_scan = true;
+                    status.skipped = skipped;
+                }
+                None => session.conflicts.push(ConflictStatus {
+                    id: conflict.id.clone(),
+                    loaded: conflict.loaded.path.clone(),
+                    skipped,
+                    handled: Vec::new(),
+                    resolved: false,
+                    in_last_scan: true,
+                }),
+            }
+        }
+    }
+
+    /// Mark `paths` as handled with `action` in every tracked conflict listing them as skipped
+    /// 在所有將 `paths` 列為被跳過檔案的已追蹤衝突中，將其標記為以 `action` 處理
+    pub fn record<S: AsRef<str>>(&self, paths: &[S], action: ResolveAction) {
+        let keys = paths
+            .iter()
+            .map(|path| (path_key(path.as_ref()), path.as_ref()))
+            .collect::<HashMap<_, _>>();
+        let mut sessions = self.lock();
+        for session in sessions.profiles.values_mut() {
+            for status in &mut session.conflicts {
+                for skipped in &status.skipped {
+                    let key = path_key(skipped);
+                    let Some(path) = keys.get(&key) else {
+                        continue;
+                    };
+                    if !status.handled.iter().any(|h| path_key(&h.path) == key) {
+                        status.handled.push(HandledFile {
+                            path: path.to_string(),
+                            action,
+                        });
+                    }
+                    status.resolved = true;
+                }
+            }
+        }
+    }
+
+    /// Session progress of `game_path`, or of the most recently scanned profile without one
+    /// `game_path` 的工作階段進度；未指定時取最近掃描的 profile
+    pub fn report(&self, game_path: Option<&str>) -> Result<SessionReport, AppError> {
+        let sessions = self.lock();
+        let profile = match game_path {
+            Some(game_path) => profile_id(game_path),
+            None => sessions
+                .last_profile
+                .clone()
+                .ok_or_else(|| AppError::InvalidInput {
+                    message: Message::new("input.no_conflict_scan"),
+                })?,
+        };
+        Ok(match sessions.profiles.get(&profile) {
+            Some(session) => report(&profile, session),
+            None => report(
+                &profile,
+                &ProfileSession {
+                    game_path: game_path.unwrap_or_default().to_string(),
+                    conflicts: Vec::new(),
+                },
+            ),
+        })
+    }
+
+    /// Forget the session of `game_path`, or of every profile without one; returns whether
+    /// anything was tracked
+    /// 清除 `game_path` 的工作階段，未指定時清除所有 profile；回傳是否有追蹤中的內容
+    pub fn reset(&self, game_path: Option<&str>) -> bool {
+        let mut sessions = self.lock();
+        match game_path {
+            Some(game_path) => sessions.profiles.remove(&profile_id(game_path)).is_some(),
+            None => {
+                let had_any = !sessions.profiles.is_empty();
+                sessions.profiles.clear();
+                sessions.last_profile = None;
+                had_any
+            }
+        }
+    }
+}
@@ -0,0 +1,468 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_log, asset_index, checksums,
+    error::AppError,
+    game_process, links, long_path,
+    manifest::read_manifest_from_path,
+    mods_scan::{self, ModFile},
+    operations::CancelToken,
+    parser::build_mod_entry,
+    path_guard::{AllowedRoots, WriteMode},
+    pins::PinSet,
+    progress::{OnProgress, Progress},
+    quarantine,
+    transactions::{self, Step},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// What installing one downloaded file would do to the mods folder
+/// 安裝單一下載檔對 mods 資料夾的影響
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum StageAction {
+    /// No installed mod has this GUID / 沒有已安裝模組使用此 GUID
+    NewInstall,
+    /// Newer than the installed copy, which it replaces / 比已安裝的版本新，將取代之
+    Upgrade,
+    /// Older than the installed copy; blocked by default / 比已安裝的版本舊；預設不安裝
+    Downgrade,
+    /// Same version but different contents; blocked by default / 版本相同但內容不同；預設不安裝
+    SameVersion,
+    /// Byte-for-byte identical to an installed copy / 與已安裝的檔案完全相同
+    Duplicate,
+    /// Another download of the same GUID loads instead / 同 GUID 的另一個下載檔會優先載入
+    Superseded,
+    /// No readable manifest, so the GUID is unknown / 沒有可讀取的 manifest，無法得知 GUID
+    Unreadable,
+}
+
+/// Plan for one downloaded file. `existing_path` is the installed copy it replaces or
+/// duplicates; `destination` is where it would be copied, absent when nothing is copied.
+/// 單一下載檔的安裝規劃；`existing_path` 為將被取代或重複的已安裝檔案，`destination` 為複製目的地，不複製時為空
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct StagePlan {
+    pub source: String,
+    pub action: StageAction,
+    pub guid: Option<String>,
+    pub version: Option<String>,
+    pub existing_version: Option<String>,
+    pub existing_path: Option<String>,
+    pub destination: Option<String>,
+    /// Needs `allow_blocked` to be applied
+    /// 需指定 `allow_blocked` 才會套用
+    pub blocked: bool,
+    #[serde(default)]
+    pub detail: Option<String>,
+    /// The installed copy it would replace is pinned, so it needs `allow_pinned`
+    /// 將被取代的已安裝檔案已釘選，需指定 `allow_pinned`
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// What applying one plan did
+/// 套用單一規劃的結果
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum StageOutcome {
+    Installed,
+    Replaced,
+    /// Nothing to copy (duplicate, superseded or unreadable) / 沒有需要複製的檔案
+    Skipped,
+    /// Blocked and `allow_blocked` was not given / 被阻擋且未指定 `allow_blocked`
+    Blocked,
+    /// Would replace a pinned copy and `allow_pinned` was not given
+    /// 將取代已釘選的檔案且未指定 `allow_pinned`
+    Pinned,
+    Failed,
+}
+
+#[derive(Serialize)]
+pub(crate) struct StageResult {
+    pub source: String,
+    pub outcome: StageOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    /// Installed copy moved to the Recycle Bin / 移到回收桶的已安裝檔案
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaced: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<AppError>,
+    /// Times trashing the replaced copy was retried while another process held it
+    /// 因其他程序佔用被取代的檔案而重試移到回收桶的次數
+    pub retries: u32,
+}
+
+/// Outcome of `apply_stage_plan`, one result per plan handled
+/// `apply_stage_plan` 的結果，每個已處理的規劃各一筆
+#[derive(Serialize)]
+pub(crate) struct StageReport {
+    pub results: Vec<StageResult>,
+    /// Stopped by cancellation; plans after that point have no result
+    /// 因取消而中止；之後的規劃沒有結果
+    pub cancelled: bool,
+}
+
+// ───────────────────────────────────────────────
+// Planning / 規劃
+// ───────────────────────────────────────────────
+
+/// Whether `a` and `b` have the same bytes; sizes are compared first so hashing is rare
+/// 判斷 `a` 與 `b` 內容是否相同；先比較大小，因此很少需要計算雜湊
+fn identical(a: &ModFile, b: &ModFile, cancel: &CancelToken) -> Result<bool, AppError> {
+    if a.entry.size != b.entry.size {
+        return Ok(false);
+    }
+    let hash = |file: &ModFile| checksums::hash_file(Path::new(&file.entry.path), cancel);
+    Ok(hash(a)? == hash(b)?)
+}
+
+fn with_file_name(dir: &Path, source: &str) -> Option<String> {
+    Path::new(source)
+        .file_name()
+        .map(|name| long_path::display(&dir.join(name)))
+}
+
+/// Plan of `download` with only its own fields filled in
+/// 只填入 `download` 自身欄位的規劃
+fn blank_plan(download: &ModFile, action: StageAction) -> StagePlan {
+    StagePlan {
+        source: download.entry.path.clone(),
+        action,
+        guid: download.manifest.as_ref().map(|m| m.guid.clone()),
+        version: mods_scan::manifest_version(download).map(str::to_string),
+        existing_version: None,
+        existing_path: None,
+        destination: None,
+        blocked: false,
+        detail: None,
+        pinned: false,
+    }
+}
+
+/// Installed copy that applying `plan` moves to the Recycle Bin
+/// 套用 `plan` 時會移到回收桶的已安裝檔案
+fn replaced_path(plan: &StagePlan) -> Option<&str> {
+    match plan.action {
+        StageAction::Upgrade | StageAction::Downgrade | StageAction::SameVersion => {
+            plan.existing_path.as_deref()
+        }
+        _ => None,
+    }
+}
+
+/// Installed copy that `download` is byte-for-byte identical to, if any
+/// 與 `download` 內容完全相同的已安裝檔案（若有）
+fn duplicate_of<'a>(
+    download: &ModFile,
+    installed: Option<&'a Vec<ModFile>>,
+    cancel: &CancelToken,
+) -> Result<Option<&'a ModFile>, AppError> {
+    for copy in installed.into_iter().flatten() {
+        if identical(download, copy, cancel)? {
+            return Ok(Some(copy));
+        }
+    }
+    Ok(None)
+}
+
+/// Plan a download that is not a duplicate against the installed copies of its GUID
+/// 將非重複的下載檔與其 GUID 的已安裝檔案比較並規劃
+fn plan_against(
+    download: &ModFile,
+    installed: Option<&Vec<ModFile>>,
+    mods_dir: &Path,
+) -> StagePlan {
+    let mut plan = blank_plan(download, StageAction::NewInstall);
+    let Some(installed) = installed else {
+        let destination = quarantine::unique_path(
+            &mods_dir.join(
+                Path::new(&download.entry.path)
+                    .file_name()
+                    .unwrap_or_default(),
+            ),
+        );
+        plan.destination = Some(long_path::display(&destination));
+        return plan;
+    };
+
+    // Compare against the copy Sideloader loads now, which is the one this would replace
+    // 與目前會被 Sideloader 載入的檔案比較，即將被取代的檔案
+    let loaded = &installed[0];
+    let existing_version = mods_scan::manifest_version(loaded);
+    plan.action = match mods_scan::compare_versions(plan.version.as_deref(), existing_version).0 {
+        Ordering::Greater => StageAction::Upgrade,
+        Ordering::Less => StageAction::Downgrade,
+        Ordering::Equal => StageAction::SameVersion,
+    };
+    plan.blocked = plan.action != StageAction::Upgrade;
+    plan.existing_version = existing_version.map(str::to_string);
+    plan.existing_path = Some(loaded.entry.path.clone());
+    plan.destination = Path::new(&loaded.entry.path)
+        .parent()
+        .and_then(|dir| with_file_name(dir, &download.entry.path));
+    plan
+}
+
+/// Plan the install of every mod archive (per `mod_extensions`) under `download_dir` against the `installed`
+/// mods (from the GUID index). Downloads are matched to installed copies by manifest GUID;
+/// exact copies of an installed file are duplicates, the rest are classified by version. When
+/// several downloads share a GUID only the one that would load is planned and the rest are
+/// superseded. Plans replacing a copy in `pins` are marked `pinned`. Nothing is written.
+/// 依 GUID 索引中的已安裝模組 `installed`，規劃 `download_dir` 底下每個模組壓縮檔（依 `mod_extensions`）的安裝方式；
+/// 以 manifest GUID 對應已安裝檔案，與已安裝檔案完全相同者為重複，其餘依版本分類；
+/// 多個下載檔共用 GUID 時只規劃會被載入者，其餘標為被取代；取代 `pins` 中檔案的規劃標記 `pinned`。不寫入任何檔案
+pub(crate) fn stage_installs(
+    download_dir: &Path,
+    game_path: &Path,
+    installed: Vec<ModFile>,
+    mod_extensions: &[String],
+    pins: &PinSet,
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<Vec<StagePlan>, AppError> {
+    let mut by_guid: HashMap<String, Vec<ModFile>> = HashMap::new();
+    for file in installed {
+        if let Some(guid) = file.manifest.as_ref().map(|m| m.guid.to_lowercase()) {
+            by_guid.entry(guid).or_default().push(file);
+        }
+    }
+    for copies in by_guid.values_mut() {
+        mods_scan::sort_by_load_order(copies);
+    }
+
+    let sources = links::walk_files(download_dir, false)
+        .into_iter()
+        .map(|file| file.path)
+        .filter(|path| asset_index::is_mod_archive(path, mod_extensions))
+        .collect::<Vec<_>>();
+    let total = sources.len();
+    let mut plans = Vec::new();
+    let mut downloads: HashMap<String, Vec<ModFile>> = HashMap::new();
+    for (index, source) in sources.iter().enumerate() {
+        if cancel.is_cancelled() {
+            return Err(AppError::OperationCancelled);
+        }
+        on_progress(Progress::new(index, total).with_message(long_path::display(source)));
+        let path = long_path::display(source);
+        match read_manifest_from_path(&path) {
+            Ok(manifest) => downloads
+                .entry(manifest.guid.to_lowercase())
+                .or_default()
+                .push(ModFile {
+                    entry: build_mod_entry(source, &path, mod_extensions),
+                    manifest: Some(manifest),
+                    wrapper_archive: false,
+                    inner_zipmods: Vec::new(),
+                }),
+            Err(error) => plans.push(StagePlan {
+                source: path,
+                action: StageAction::Unreadable,
+                guid: None,
+                version: None,
+                existing_version: None,
+                existing_path: None,
+                destination: None,
+                blocked: false,
+                detail: Some(error.to_string()),
+                pinned: false,
+            }),
+        }
+    }
+
+    let mods_dir = game_path.join("mods");
+    for (guid, files) in downloads {
+        let installed = by_guid.get(&guid);
+        let mut fresh = Vec::new();
+        for file in files {
+            match duplicate_of(&file, installed, cancel)? {
+                Some(copy) => plans.push(StagePlan {
+                    existing_version: mods_scan::manifest_version(copy).map(str::to_string),
+                    existing_path: Some(copy.entry.path.clone()),
+                    ..blank_plan(&file, StageAction::Duplicate)
+                }),
+                None => fresh.push(file),
+            }
+        }
+        if fresh.is_empty() {
+            continue;
+        }
+        mods_scan::sort_by_load_order(&mut fresh);
+        let plan = plan_against(&fresh[0], installed, &mods_dir);
+        for other in &fresh[1..] {
+            plans.push(StagePlan {
+                existing_version: plan.version.clone(),
+                existing_path: Some(plan.source.clone()),
+                blocked: true,
+                ..blank_plan(other, StageAction::Superseded)
+            });
+        }
+        plans.push(plan);
+    }
+    on_progress(Progress::new(total, total));
+    for plan in &mut plans {
+        plan.pinned = replaced_path(plan).is_some_and(|path| pins.contains(Path::new(path)));
+    }
+    plans.sort_by(|a, b| a.source.cmp(&b.source));
+    Ok(plans)
+}
+
+// ───────────────────────────────────────────────
+// Applying / 套用
+// ───────────────────────────────────────────────
+
+/// Run `steps` as one transaction, failing with the error of the step that stopped it once
+/// the others are rolled back; returns the retries of all steps
+/// 以一筆交易執行 `steps`；有步驟失敗時於其他步驟復原後回傳該步驟的錯誤；回傳所有步驟的重試次數
+fn run_install(steps: &[Step], write_mode: WriteMode) -> Result<u32, AppError> {
+    let report = transactions::run("staged install", steps, write_mode, None)?;
+    match report.error() {
+        Some(error) => Err(error.clone()),
+        None => Ok(report.steps.iter().map(|step| step.retries).sum()),
+    }
+}
+
+/// Install one plan; returns the installed path, the replaced one and the retries it took.
+/// Destinations are derived here from the game path and the replaced file, not taken from
+/// the plan.
+/// 套用單一規劃，回傳安裝後的路徑、被取代的檔案與重試次數；目的地在此依遊戲路徑與被取代檔案推導，不採用規劃中的值
+fn install(
+    game_path: &Path,
+    roots: &AllowedRoots,
+    plan: &StagePlan,
+    write_mode: WriteMode,
+) -> Result<(PathBuf, Option<PathBuf>, u32), AppError> {
+    let source = Path::new(&plan.source);
+    let name = source.file_name().unwrap_or_default();
+    let Some(existing) = replaced_path(plan) else {
+        let target = quarantine::unique_path(&game_path.join("mods").join(name));
+        let retries = run_install(
+            &[Step::Copy {
+                source: source.to_path_buf(),
+                target: target.clone(),
+            }],
+            write_mode,
+        )?;
+        return Ok((target, None, retries));
+    };
+
+    // Copy first and trash the old copy only once the new one is in place; a failed trash
+    // removes the copy again
+    // 先複製新檔，就位後才將舊檔移到回收桶；移到回收桶失敗時會再移除複本
+    let existing = roots.check(existing)?;
+    let target = existing.with_file_name(name);
+    let staged = quarantine::unique_path(&target);
+    let retries = run_install(
+        &[
+            Step::Copy {
+                source: source.to_path_buf(),
+                target: staged.clone(),
+            },
+            Step::Trash {
+                path: existing.clone(),
+            },
+        ],
+        write_mode,
+    )?;
+    let installed = match staged != target && !long_path::extend(&target).exists() {
+        true => fs::rename(long_path::extend(&staged), long_path::extend(&target))
+            .map(|()| target)
+            .unwrap_or(staged),
+        false => staged,
+    };
+    Ok((installed, Some(existing), retries))
+}
+
+/// Apply plans from `stage_installs`: plans without a destination are skipped, blocked ones
+/// need `allow_blocked`, ones replacing a copy in `pins` are refused as `pinned` (checked
+/// again here, not taken from the plan), and replacements copy the download next to the
+/// installed copy before moving that copy to the Recycle Bin. Refuses to start while the game
+/// runs or in read-only `write_mode`. Every install is written to the app log; failures do not
+/// stop the batch, cancellation does.
+/// 套用 `stage_installs` 的規劃：沒有目的地的規劃略過，被阻擋者需 `allow_blocked`，
+/// 取代 `pins` 中檔案者以 `pinned` 拒絕（在此重新檢查，不採用規劃中的值）；取代時先將下載檔複製到已安裝檔案旁，
+/// 再將舊檔移到回收桶。遊戲執行中或 `write_mode` 為唯讀時拒絕開始。每筆安裝都寫入 app log；失敗不會中止整批，取消則會
+pub(crate) fn apply_stage_plan(
+    game_path: &Path,
+    plans: &[StagePlan],
+    allow_blocked: bool,
+    pins: Option<&PinSet>,
+    write_mode: WriteMode,
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<StageReport, AppError> {
+    write_mode.ensure_writable(&game_path.join("mods"))?;
+    if let Some(executable) = game_process::running_executable(game_path) {
+        return Err(AppError::GameRunning {
+            detail: Some(executable.to_string()),
+        });
+    }
+    let roots = AllowedRoots::for_games(&[long_path::display(game_path)]);
+    let mut report = StageReport {
+        results: Vec::new(),
+        cancelled: false,
+    };
+
+    let total = plans.len();
+    for (index, plan) in plans.iter().enumerate() {
+        if cancel.is_cancelled() {
+            report.cancelled = true;
+            break;
+        }
+        on_progress(Progress::new(index, total).with_message(plan.source.clone()));
+        let result =
+            |outcome, destination: Option<&Path>, replaced: Option<&Path>, error| StageResult {
+                source: plan.source.clone(),
+                outcome,
+                destination: destination.map(long_path::display),
+                replaced: replaced.map(long_path::display),
+                error,
+                retries: 0,
+            };
+        let outcome = if plan.destination.is_none() {
+            result(StageOutcome::Skipped, None, None, None)
+        } else if plan.blocked && !allow_blocked {
+            result(StageOutcome::Blocked, None, None, None)
+        } else if replaced_path(plan)
+            .is_some_and(|path| pins.is_some_and(|pins| pins.contains(Path::new(path))))
+        {
+            result(StageOutcome::Pinned, None, None, None)
+        } else {
+            match install(game_path, &roots, plan, write_mode) {
+                Ok((installed, replaced, retries)) => {
+                    app_log::write(&format!(
+                        "staged install: {} -> {}{}",
+                        plan.source,
+                        long_path::display(&installed),
+                        replaced
+                            .as_ref()
+                            .map(|path| format!(" (replaced {})", long_path::display(path)))
+                            .unwrap_or_default()
+                    ));
+                    let outcome = match replaced {
+                        Some(_) => StageOutcome::Replaced,
+                        None => StageOutcome::Installed,
+                    };
+                    StageResult {
+                        retries,
+                        ..result(outcome, Some(&installed), replaced.as_deref(), None)
+                    }
+                }
+                Err(error) => result(StageOutcome::Failed, None, None, Some(error)),
+            }
+        };
+        report.results.push(outcome);
+    }
+    on_progress(Progress::new(total, total));
+    Ok(report)
+}
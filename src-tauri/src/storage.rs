@@ -0,0 +1,282 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::Mutex,
+};
+
+use serde::Serialize;
+
+use crate::{app_log, error::AppError, long_path, profiles};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// File written and removed again to tell whether a folder takes writes
+/// 寫入後立即刪除、用以判斷資料夾是否可寫入的檔案
+const PROBE_FILE: &str = ".write-probe";
+
+/// Prefix of the scratch folder in the system temp dir that stands in for an unwritable app
+/// data dir; the process id follows, so two instances never share one
+/// 系統暫存資料夾中代替無法寫入之 app data 資料夾的暫存資料夾前綴；其後接程序 id，兩個執行個體不會共用
+const SESSION_DIR_PREFIX: &str = "kk-mod-conflict-manager-session-";
+
+/// Where settings, caches, history and indices are kept
+/// 設定、快取、歷史紀錄與索引的存放位置
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum StorageMode {
+    /// In the app data dir, as usual / 照常存放於 app data 資料夾
+    Persistent,
+    /// The app data dir is unwritable, so everything goes to a scratch folder removed on exit;
+    /// commands work as usual but nothing is saved for the next start
+    /// app data 資料夾無法寫入，所有資料改存於結束時刪除的暫存資料夾；指令照常運作，但不會保留到下次啟動
+    SessionOnly,
+    /// Not even a scratch folder could be created; commands still run, with profiles registered
+    /// in memory and caches rebuilt every time, but saving settings, pins or notes fails
+    /// 連暫存資料夾也無法建立；指令仍會執行，profile 改為登記於記憶體、快取每次重建，但儲存設定、釘選或備註會失敗
+    Unavailable,
+}
+
+/// What `get_storage_status` returns, for a "settings won't be saved" banner
+/// `get_storage_status` 的回傳內容，供顯示「設定不會被儲存」橫幅
+#[derive(Serialize, Clone)]
+pub(crate) struct StorageStatus {
+    pub mode: StorageMode,
+    /// The app data dir, when it could be resolved / app data 資料夾（可解析時）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_data_dir: Option<String>,
+    /// Scratch folder used instead in `session_only` / `session_only` 時改用的暫存資料夾
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_dir: Option<String>,
+    /// Why the app data dir was not used / 未使用 app data 資料夾的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<AppError>,
+    /// Why no scratch folder could be created either, in `unavailable`
+    /// `unavailable` 時暫存資料夾也無法建立的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_error: Option<AppError>,
+}
+
+/// Root every persisted file lives under, chosen once at startup; stored in Tauri managed
+/// state
+/// 所有持久化檔案所在的根目錄，於啟動時決定一次；存放於 Tauri managed state
+pub(crate) struct Storage {
+    root: PathBuf,
+    status: StorageStatus,
+    /// Canonical game paths registered in `unavailable`, where no profile record can be written
+    /// `unavailable` 時無法寫入 profile 紀錄，改於此登記的正規化遊戲路徑
+    session_games: Mutex<Vec<String>>,
+}
+
+// ───────────────────────────────────────────────
+// Detection / 偵測
+// ───────────────────────────────────────────────
+
+/// Create `dir` and write and remove `PROBE_FILE` in it
+/// 建立 `dir`，並在其中寫入後刪除 `PROBE_FILE`
+fn probe(dir: &Path) -> Result<(), AppError> {
+    let path = dir.join(PROBE_FILE);
+    fs::create_dir_all(long_path::extend(dir))
+        .and_then(|_| fs::write(long_path::extend(&path), b"probe"))
+        .and_then(|_| fs::remove_file(long_path::extend(&path)))
+        .map_err(|e| AppError::io("io.write_app_data_dir", Some(dir), e))
+}
+
+impl Storage {
+    /// Use `app_data_dir` when it resolves and takes writes, otherwise a fresh scratch folder
+    /// in the system temp dir. Falling back is logged, never an error, so every command keeps
+    /// working on a restricted account.
+    /// `app_data_dir` 可解析且可寫入時使用之，否則改用系統暫存資料夾中新建的暫存資料夾；
+    /// 改用時只記錄於 log 而不回傳錯誤，讓受限帳戶下所有指令仍可運作
+    pub fn detect(app_data_dir: Result<PathBuf, AppError>) -> Self {
+        Self::detect_in(app_data_dir, &std::env::temp_dir())
+    }
+
+    /// `detect` with the scratch folder under `temp_dir` / 暫存資料夾位於 `temp_dir` 下的 `detect`
+    fn detect_in(app_data_dir: Result<PathBuf, AppError>, temp_dir: &Path) -> Self {
+        let error = match &app_data_dir {
+            Ok(dir) => match probe(dir) {
+                Ok(()) => {
+                    return Storage {
+                        root: dir.clone(),
+                        status: StorageStatus {
+                            mode: StorageMode::Persistent,
+                            app_data_dir: Some(long_path::display(dir)),
+                            session_dir: None,
+                            error: None,
+                            session_error: None,
+                        },
+                        session_games: Mutex::default(),
+                    }
+                }
+                Err(e) => e,
+            },
+            Err(e) => e.clone(),
+        };
+        let session_dir = temp_dir.join(format!("{}{}", SESSION_DIR_PREFIX, process::id()));
+        let _ = fs::remove_dir_all(long_path::extend(&session_dir));
+        let session_error = probe(&session_dir).err();
+        let mode = match session_error {
+            None => StorageMode::SessionOnly,
+            Some(_) => StorageMode::Unavailable,
+        };
+        app_log::write(&format!(
+            "storage degraded to {:?}: {}{}",
+            mode,
+            error,
+            session_error
+                .as_ref()
+                .map(|e| format!("; scratch folder failed too: {}", e))
+                .unwrap_or_default()
+        ));
+        Storage {
+            status: StorageStatus {
+                mode,
+                app_data_dir: app_data_dir.as_deref().ok().map(long_path::display),
+                session_dir: (mode == StorageMode::SessionOnly)
+                    .then(|| long_path::display(&session_dir)),
+                error: Some(error),
+                session_error,
+            },
+            root: match (mode, app_data_dir) {
+                (StorageMode::Unavailable, Ok(dir)) => dir,
+                _ => session_dir,
+            },
+            session_games: Mutex::default(),
+        }
+    }
+
+    /// Folder to keep persisted files under; see `StorageMode`
+    /// 存放持久化檔案的資料夾；見 `StorageMode`
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn status(&self) -> &StorageStatus {
+        &self.status
+    }
+
+    pub fn profiles_root(&self) -> PathBuf {
+        self.root.join("profiles")
+    }
+
+    /// `profiles::profile_dir`, except that in `unavailable` a profile that cannot be
+    /// registered on disk is registered for the session instead. Its folder is returned all
+    /// the same, so reads find nothing saved and each write fails or is skipped on its own.
+    /// 同 `profiles::profile_dir`，但 `unavailable` 時無法於磁碟登記的 profile 改為登記於本次執行；
+    /// 仍回傳其資料夾，讀取時找不到已儲存的內容，各寫入動作則自行失敗或略過
+    pub fn profile_dir(&self, game_path: &str) -> Result<PathBuf, AppError> {
+        let profiles_root = self.profiles_root();
+        match profiles::profile_dir(&profiles_root, game_path) {
+            Err(e) if self.status.mode == StorageMode::Unavailable => {
+                let canonical = profiles::canonical_game_path(game_path);
+                let mut games = self.session_games.lock().unwrap_or_else(|e| e.into_inner());
+                if !games.contains(&canonical) {
+                    app_log::write(&format!("profile registered for this session only: {}", e));
+                    games.push(canonical);
+                }
+                Ok(profiles_root.join(profiles::profile_id(game_path)))
+            }
+            result => result,
+        }
+    }
+
+    /// Game paths of every registered profile, on disk or for this session
+    /// 所有已登記 profile 的遊戲路徑，包含磁碟上與本次執行登記的
+    pub fn registered_game_paths(&self) -> Vec<String> {
+        let mut game_paths = profiles::registered_game_paths(&self.profiles_root());
+        let games = self.session_games.lock().unwrap_or_else(|e| e.into_inner());
+        for game_path in games.iter() {
+            if !game_paths.contains(game_path) {
+                game_paths.push(game_path.clone());
+            }
+        }
+        game_paths
+    }
+
+    /// Remove the scratch folder of `session_only` when the app exits
+    /// 程式結束時刪除 `session_only` 的暫存資料夾
+    pub fn discard_session(&self) {
+        if self.status.mode == StorageMode::SessionOnly {
+            let _ = fs::remove_dir_all(long_path::extend(&self.root));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{Storage, StorageMode};
+    use crate::{
+        asset_index,
+        error::AppError,
+        long_path,
+        path_guard::{self, WriteMode},
+        test_support::{SyntheticInstall, TestManifest},
+    };
+
+    #[test]
+    fn unavailable_storage_registers_profiles_for_the_session() {
+        let dir = tempfile::tempdir().unwrap();
+        // Regular files where both folders should be can never be written into
+        // 兩個資料夾的位置上都是一般檔案，因此永遠無法寫入
+        let blocker = dir.path().join("blocker");
+        fs::write(&blocker, b"").unwrap();
+        let storage = Storage::detect_in(Ok(blocker.join("app")), &blocker);
+        assert_eq!(storage.status().mode, StorageMode::Unavailable);
+        let install = SyntheticInstall::new();
+        let target = install.add_zipmod("a.zipmod", &TestManifest::new("a"));
+
+        let profile_dir = storage.profile_dir(&install.game_path()).unwrap();
+        storage.profile_dir(&install.game_path()).unwrap();
+
+        let game_paths = storage.registered_game_paths();
+        assert_eq!(game_paths.len(), 1);
+        let resolved = path_guard::resolve_targets(
+            &game_paths,
+            &[long_path::display(&target)],
+            false,
+            WriteMode::default(),
+        );
+        assert_eq!(resolved.unwrap(), [fs::canonicalize(&target).unwrap()]);
+        let providers = asset_index::find_asset_provider(
+            &profile_dir.join("asset_index.json"),
+            install.root(),
+            false,
+            &["zipmod".to_string()],
+            "abdata/a.unity3d",
+        );
+        assert_eq!(providers.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn unwritable_app_data_dir_falls_back_to_a_session_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let writable = Storage::detect(Ok(dir.path().join("app")));
+        assert_eq!(writable.status().mode, StorageMode::Persistent);
+        assert_eq!(writable.root(), dir.path().join("app"));
+
+        // A regular file where the folder should be can never be written into, whatever the account
+        // 資料夾位置上的一般檔案無論以何種帳戶都無法寫入
+        let blocker = dir.path().join("blocker");
+        fs::write(&blocker, b"").unwrap();
+        let degraded = Storage::detect(Ok(blocker.join("app")));
+        let status = degraded.status();
+        assert_eq!(status.mode, StorageMode::SessionOnly);
+        assert!(matches!(
+            status.error,
+            Some(AppError::Io {
+                key: "io.write_app_data_dir",
+                ..
+            })
+        ));
+        assert_ne!(degraded.root(), blocker.join("app"));
+        fs::write(degraded.root().join("probe.json"), b"{}").unwrap();
+
+        degraded.discard_session();
+        assert!(!degraded.root().exists());
+    }
+}
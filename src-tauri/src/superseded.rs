@@ -0,0 +1,160 @@
+use std::{cmp::Ordering, collections::HashMap, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    checksums, cloud_files,
+    error::AppError,
+    modpack::ModpackFolders,
+    mods_scan::{self, ModFile},
+    operations::CancelToken,
+    parser::ModConflict,
+    progress::{OnProgress, Progress},
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Same-version copies up to this size are hashed to tell identical ones apart
+/// 不超過此大小的相同版本複本會計算雜湊以判斷是否完全相同
+const HASH_LIMIT: u64 = 64 * 1024 * 1024;
+
+/// How the modpack copy compares to the manual one
+/// 模組包中的檔案與手動安裝檔案的比較結果
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Supersession {
+    /// The modpack ships a newer version / 模組包提供較新的版本
+    Newer,
+    /// Same version and byte-for-byte identical / 版本相同且內容完全相同
+    Identical,
+    /// Same version but different bytes / 版本相同但內容不同
+    SameVersion,
+    /// Same version, too large to hash cheaply or a cloud placeholder
+    /// 版本相同，但檔案過大或為雲端預留位置而未計算雜湊
+    SameVersionUnchecked,
+}
+
+/// A manually installed mod whose GUID the modpack also ships at an equal or newer version.
+/// The conflict has the modpack copy as `loaded` and the manual copy as its only skipped
+/// file, and is stored like a scanned conflict so its id can go to `resolve_conflicts`.
+/// 模組包也以相同或較新版本提供其 GUID 的手動安裝模組；衝突以模組包中的檔案為 `loaded`、手動安裝的檔案為唯一的 skipped，
+/// 並與掃描出的衝突一樣儲存，因此其 id 可直接交給 `resolve_conflicts`
+#[derive(Serialize)]
+pub(crate) struct SupersededMod {
+    pub guid: String,
+    #[serde(flatten)]
+    pub conflict: ModConflict,
+    pub manual_version: Option<String>,
+    pub modpack_version: Option<String>,
+    pub supersession: Supersession,
+    /// Both versions are numeric, so `newer` is trustworthy
+    /// 兩個版本皆為數字，因此 `newer` 可信
+    pub confident: bool,
+    /// Deleting the manual copy loses nothing: a confidently newer or identical modpack copy,
+    /// and the manual copy is not in a priority folder
+    /// 刪除手動安裝的檔案不會失去內容：模組包的版本確定較新或完全相同，且手動安裝的檔案不在優先資料夾中
+    pub safe: bool,
+}
+
+// ───────────────────────────────────────────────
+// Detection / 偵測
+// ───────────────────────────────────────────────
+
+fn classify(
+    manual: &ModFile,
+    modpack: &ModFile,
+    cancel: &CancelToken,
+) -> Result<Option<(Supersession, bool)>, AppError> {
+    let (ordering, confident) = mods_scan::compare_versions(
+        mods_scan::manifest_version(modpack),
+        mods_scan::manifest_version(manual),
+    );
+    let supersession = match ordering {
+        Ordering::Less => return Ok(None),
+        Ordering::Greater => Supersession::Newer,
+        Ordering::Equal if manual.entry.size != modpack.entry.size => Supersession::SameVersion,
+        Ordering::Equal if manual.entry.size > HASH_LIMIT => Supersession::SameVersionUnchecked,
+        Ordering::Equal
+            if [manual, modpack]
+                .iter()
+                .any(|file| cloud_files::skip_content(Path::new(&file.entry.path))) =>
+        {
+            Supersession::SameVersionUnchecked
+        }
+        Ordering::Equal => {
+            let hash = |file: &ModFile| checksums::hash_file(Path::new(&file.entry.path), cancel);
+            match hash(manual)? == hash(modpack)? {
+                true => Supersession::Identical,
+                false => Supersession::SameVersion,
+            }
+        }
+    };
+    Ok(Some((supersession, confident)))
+}
+
+/// Find the files outside the modpack folders whose manifest GUID is also declared inside
+/// them at an equal or newer version. Each manual copy is compared to the modpack copy
+/// Sideloader would load; equal versions are hashed when the sizes match and are at most
+/// `HASH_LIMIT`. Sorted by manual path.
+/// 找出模組包資料夾外、其 manifest GUID 也在模組包資料夾內以相同或較新版本宣告的檔案；每個手動安裝的檔案與
+/// Sideloader 會載入的模組包複本比較，版本相同且大小一致、不超過 `HASH_LIMIT` 時計算雜湊；依手動安裝檔案的路徑排序
+pub(crate) fn find_superseded(
+    files: Vec<ModFile>,
+    modpacks: &ModpackFolders,
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<Vec<SupersededMod>, AppError> {
+    let mut manual = Vec::new();
+    let mut modpack: HashMap<String, Vec<ModFile>> = HashMap::new();
+    for mut file in files {
+        let Some(guid) = file.manifest.as_ref().map(|m| m.guid.to_lowercase()) else {
+            continue;
+        };
+        file.entry.managed = modpacks.contains(Path::new(&file.entry.path));
+        match file.entry.managed {
+            true => modpack.entry(guid).or_default().push(file),
+            false => manual.push((guid, file)),
+        }
+    }
+    for copies in modpack.values_mut() {
+        mods_scan::sort_by_load_order(copies);
+    }
+    manual.retain(|(guid, _)| modpack.contains_key(guid));
+
+    let total = manual.len();
+    let mut superseded = Vec::new();
+    for (index, (guid, file)) in manual.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            return Err(AppError::OperationCancelled);
+        }
+        on_progress(Progress::new(index, total).with_message(file.entry.path.clone()));
+        let packed = &modpack[&guid][0];
+        let Some((supersession, confident)) = classify(&file, packed, cancel)? else {
+            continue;
+        };
+        let safe = !file.entry.priority_folder
+            && match supersession {
+                Supersession::Newer => confident,
+                Supersession::Identical => true,
+                Supersession::SameVersion | Supersession::SameVersionUnchecked => false,
+            };
+        superseded.push(SupersededMod {
+            guid: packed
+                .manifest
+                .as_ref()
+                .map(|m| m.guid.clone())
+                .unwrap_or(guid),
+            manual_version: mods_scan::manifest_version(&file).map(str::to_string),
+            modpack_version: mods_scan::manifest_version(packed).map(str::to_string),
+            conflict: ModConflict::new(packed.entry.clone(), vec![file.entry]),
+            supersession,
+            confident,
+            safe,
+        });
+    }
+    on_progress(Progress::new(total, total));
+    superseded.sort_by(|a, b| a.conflict.skipped[0].path.cmp(&b.conflict.skipped[0].path));
+    Ok(superseded)
+}
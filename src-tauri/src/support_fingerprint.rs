@@ -0,0 +1,263 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    asset_index,
+    diagnostics::detect_game_variant,
+    disk_space::format_bytes,
+    error::AppError,
+    log_environment,
+    parser::ModConflict,
+    progress,
+    scan::{self, find_log_file},
+    scan_cache::{self, LogIdentity},
+    self_check::format_age,
+    timings::Timings,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Conflicts listed by name, largest waste first
+/// 依名稱列出的衝突數，浪費空間最大者優先
+const TOP_CONFLICTS: usize = 5;
+
+/// Modpack folders listed; the rest are summed into one line so the block stays short
+/// 列出的模組包資料夾數；其餘合併為一行，使內容保持簡短
+const MAX_MODPACK_FOLDERS: usize = 8;
+
+/// Files and settings of the profile the fingerprint describes
+/// 指紋所描述的 profile 檔案與設定
+pub(crate) struct FingerprintSources<'a> {
+    pub game_path: &'a str,
+    pub scan_cache_path: &'a Path,
+    pub index_path: &'a Path,
+    pub modpack_prefixes: &'a [String],
+    pub follow_links: bool,
+    pub mod_extensions: &'a [String],
+}
+
+// ───────────────────────────────────────────────
+// Gathering / 收集
+// ───────────────────────────────────────────────
+
+/// Every mod archive under `mods` with its size: from the asset index when one was built,
+/// otherwise from a directory listing; archives are never opened
+/// `mods` 下每個模組壓縮檔及其大小：已建立資源索引時取自索引，否則取自目錄清單；不會開啟任何壓縮檔
+fn mod_archives(sources: &FingerprintSources) -> Vec<(PathBuf, u64)> {
+    asset_index::indexed_sizes(sources.index_path).unwrap_or_else(|| {
+        asset_index::list_mod_archives(
+            &Path::new(sources.game_path).join("mods"),
+            sources.follow_links,
+            sources.mod_extensions,
+        )
+        .into_iter()
+        .map(|(path, size, _)| (path, size))
+        .collect()
+    })
+}
+
+/// Archives per top-level folder of `mods` that a modpack prefix covers, by folder name
+/// 模組包前綴所涵蓋的 `mods` 第一層資料夾中的壓縮檔數，以資料夾名稱為 key
+fn modpack_counts(
+    game_path: &Path,
+    archives: &[(PathBuf, u64)],
+    prefixes: &[String],
+) -> BTreeMap<String, usize> {
+    let prefixes = prefixes
+        .iter()
+        .map(|prefix| prefix.replace('\\', "/").trim_matches('/').to_lowercase())
+        .filter(|prefix| !prefix.is_empty())
+        .collect::<Vec<_>>();
+    let mods = game_path.join("mods");
+    let mut counts = BTreeMap::new();
+    for (path, _) in archives {
+        let Ok(relative) = path.strip_prefix(&mods) else {
+            continue;
+        };
+        let mut components = relative.components();
+        let (Some(Component::Normal(folder)), Some(_)) = (components.next(), components.next())
+        else {
+            continue;
+        };
+        let folder = folder.to_string_lossy();
+        let key = format!("mods/{}", folder.to_lowercase());
+        if prefixes
+            .iter()
+            .any(|prefix| key.starts_with(prefix.as_str()))
+        {
+            *counts.entry(folder.into_owned()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Conflicts of the current log: the cached parse when the log is unchanged, otherwise a
+/// fresh scan, which also refreshes the cache
+/// 目前 log 的衝突：log 未變動時使用快取的解析結果，否則重新掃描並更新快取
+fn current_conflicts(
+    sources: &FingerprintSources,
+    identity: &LogIdentity,
+) -> Result<Vec<ModConflict>, AppError> {
+    if let Some(conflicts) = scan_cache::load(sources.scan_cache_path, identity) {
+        return Ok(conflicts);
+    }
+    scan::scan_conflicts_cached(
+        sources.scan_cache_path,
+        sources.game_path,
+        sources.mod_extensions,
+        false,
+        &progress::ignore,
+        Timings::default(),
+    )
+    .map(|scan| scan.conflicts)
+}
+
+// ───────────────────────────────────────────────
+// Formatting / 格式化
+// ───────────────────────────────────────────────
+
+/// A short plain-text summary of an install to paste when asking for help: app version,
+/// game variant, mod count and size, modpack folders, the BepInEx and Sideloader versions the
+/// log names, conflict count with the five most wasteful conflicts, and log age. Only file and folder names appear, never absolute paths
+/// or the user name; mod counts and conflicts come from the asset index and scan cache when
+/// they exist, so it is usually instant.
+/// 求助時貼上的安裝簡短純文字摘要：程式版本、遊戲版本、模組數量與大小、模組包資料夾、log 記錄的 BepInEx 與 Sideloader 版本、衝突數及浪費空間最多的五個衝突，
+/// 以及 log 的經過時間；只出現檔案與資料夾名稱，不含絕對路徑或使用者名稱；模組數量與衝突在資源索引與掃描快取存在時取自其中，
+/// 因此通常可立即產生
+pub(crate) fn generate(sources: &FingerprintSources) -> String {
+    let game_path = Path::new(sources.game_path);
+    let mut text = String::new();
+    let mut line = |content: String| {
+        let _ = writeln!(text, "{}", content);
+    };
+
+    line(format!(
+        "KK Log-Based Mod Conflict Manager {}",
+        env!("CARGO_PKG_VERSION")
+    ));
+    line(format!(
+        "Game: {}",
+        detect_game_variant(game_path).unwrap_or("unknown")
+    ));
+
+    let archives = mod_archives(sources);
+    let total_bytes = archives.iter().map(|(_, size)| size).sum::<u64>();
+    line(format!(
+        "Mods: {} archives, {}",
+        archives.len(),
+        format_bytes(total_bytes)
+    ));
+    let modpacks = modpack_counts(game_path, &archives, sources.modpack_prefixes);
+    match modpacks.is_empty() {
+        true => line("Modpack folders: none".to_string()),
+        false => {
+            line("Modpack folders:".to_string());
+            for (folder, count) in modpacks.iter().take(MAX_MODPACK_FOLDERS) {
+                line(format!("  {}: {}", folder, count));
+            }
+            if modpacks.len() > MAX_MODPACK_FOLDERS {
+                let rest = modpacks.values().skip(MAX_MODPACK_FOLDERS).sum::<usize>();
+                line(format!(
+                    "  {} more folders: {}",
+                    modpacks.len() - MAX_MODPACK_FOLDERS,
+                    rest
+                ));
+            }
+        }
+    }
+
+    let identity = find_log_file(sources.game_path).and_then(|path| LogIdentity::of(&path));
+    let Ok(identity) = identity else {
+        line("Log: not found".to_string());
+        return text;
+    };
+    line(format!(
+        "Loaders: {}",
+        log_environment::read(Path::new(&identity.path)).describe()
+    ));
+    match current_conflicts(sources, &identity) {
+        Ok(mut conflicts) => {
+            let wasted = conflicts.iter().map(|c| c.wasted_bytes).sum::<u64>();
+            line(format!(
+                "Conflicts: {} ({} wasted)",
+                conflicts.len(),
+                format_bytes(wasted)
+            ));
+            conflicts.sort_by_key(|c| std::cmp::Reverse(c.wasted_bytes));
+            for (rank, conflict) in conflicts.iter().take(TOP_CONFLICTS).enumerate() {
+                line(format!(
+                    "  {}. {} ({}, {} skipped)",
+                    rank + 1,
+                    conflict.loaded.name,
+                    format_bytes(conflict.wasted_bytes),
+                    conflict.skipped.len()
+                ));
+            }
+        }
+        Err(e) => line(format!("Conflicts: not scanned ({})", e.code())),
+    }
+    let age = identity.modified.and_then(|modified| {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        now.checked_sub(modified)
+    });
+    line(match age {
+        Some(age) => format!("Log: written {} ago", format_age(age)),
+        None => "Log: found, age unknown".to_string(),
+    });
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{generate, FingerprintSources};
+    use crate::{
+        profiles::ProfileSettings,
+        test_support::{SyntheticInstall, SyntheticLog, TestManifest},
+    };
+
+    #[test]
+    fn support_fingerprint_summarizes_without_absolute_paths() {
+        let install = SyntheticInstall::in_folder("Users/alice/Koikatsu");
+        fs::create_dir_all(install.root().join("Koikatsu_Data")).unwrap();
+        let hair = TestManifest::new("author.hair");
+        install.add_zipmod("Sideloader Modpack/[Author] Hair v2.zipmod", &hair);
+        install.add_zipmod("Sideloader Modpack - Exclusive KK/Outfit.zipmod", &hair);
+        install.add_zipmod("downloads/[Author] Hair v1.zipmod", &hair);
+        install.write_log(&SyntheticLog::new().conflict(
+            "Sideloader Modpack/[Author] Hair v2.zipmod",
+            &["downloads/[Author] Hair v1.zipmod"],
+        ));
+        let profile = tempfile::tempdir().unwrap();
+
+        let text = generate(&FingerprintSources {
+            game_path: &install.game_path(),
+            scan_cache_path: &profile.path().join("scan_cache.json"),
+            index_path: &profile.path().join("asset_index.json"),
+            modpack_prefixes: &ProfileSettings::default().modpack_prefixes,
+            follow_links: false,
+            mod_extensions: &ProfileSettings::default().mod_extensions,
+        });
+
+        let lines = text.lines().collect::<Vec<_>>();
+        assert!(lines.len() < 30);
+        assert!(lines.contains(&"Game: Koikatsu"));
+        assert!(lines[2].starts_with("Mods: 3 archives, "));
+        assert!(lines.contains(&"  Sideloader Modpack: 1"));
+        assert!(lines.contains(&"  Sideloader Modpack - Exclusive KK: 1"));
+        assert!(lines.contains(&"Loaders: BepInEx 5.4.22.0 (Koikatu), Sideloader 19.3"));
+        assert!(lines.iter().any(|line| line.starts_with("Conflicts: 1 (")));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("  1. [Author] Hair v2.zipmod (")));
+        assert!(lines.last().unwrap().starts_with("Log: written "));
+        assert!(!text.contains("alice") && !text.contains(&install.game_path()));
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use tempfile::TempDir;
+use zip::{write::SimpleFileOptions, DateTime, ZipWriter};
+
+use crate::long_path;
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// manifest.xml of a generated zipmod
+/// 產生的 zipmod 中的 manifest.xml
+pub(crate) struct TestManifest {
+    pub guid: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub author: Option<String>,
+    pub games: Vec<String>,
+}
+
+/// A throwaway game install in the temp folder, removed when dropped: `mods`,
+/// `BepInEx/plugins` and `BepInEx/config`, plus whatever a test adds
+/// 位於暫存資料夾、在 drop 時刪除的遊戲安裝：`mods`、`BepInEx/plugins` 與 `BepInEx/config`，以及測試加入的檔案
+pub(crate) struct SyntheticInstall {
+    _dir: TempDir,
+    /// Game root, the temp folder itself or a folder inside it
+    /// 遊戲根目錄，為暫存資料夾本身或其中的資料夾
+    root: PathBuf,
+}
+
+/// Text of a `LogOutput.log`, built line by line the way BepInEx writes it
+/// 以 BepInEx 的寫法逐行組成的 `LogOutput.log` 內容
+pub(crate) struct SyntheticLog {
+    lines: Vec<String>,
+}
+
+// ───────────────────────────────────────────────
+// Zipmods / Zipmod
+// ───────────────────────────────────────────────
+
+impl TestManifest {
+    pub fn new(guid: &str) -> Self {
+        TestManifest {
+            guid: guid.to_string(),
+            name: None,
+            version: None,
+            author: None,
+            games: Vec::new(),
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+
+    pub fn with_author(mut self, author: &str) -> Self {
+        self.author = Some(author.to_string());
+        self
+    }
+
+    pub fn with_game(mut self, game: &str) -> Self {
+        self.games.push(game.to_string());
+        self
+    }
+
+    /// The manifest as Sideloader expects it, every field a child element of `<manifest>`
+    /// Sideloader 所預期的 manifest，每個欄位皆為 `<manifest>` 的子元素
+    pub fn to_xml(&self) -> String {
+        let element = |tag: &str, value: &str| format!("  <{tag}>{value}</{tag}>\n");
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<manifest>\n");
+        xml.push_str(&element("guid", &self.guid));
+        let optional = [
+            ("name", &self.name),
+            ("version", &self.version),
+            ("author", &self.author),
+        ];
+        for (tag, value) in optional {
+            if let Some(value) = value {
+                xml.push_str(&element(tag, value));
+            }
+        }
+        for game in &self.games {
+            xml.push_str(&element("game", game));
+        }
+        xml.push_str("</manifest>\n");
+        xml
+    }
+}
+
+/// Write a real zip at `path` holding `entries` of `(name, content)`, creating its folders
+/// 在 `path` 寫入包含 `(名稱, 內容)` 各項的實際 zip 檔，並建立所需資料夾
+pub(crate) fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(long_path::extend(parent)).expect("create zip folder");
+    }
+    let mut zip = ZipWriter::new(File::create(long_path::extend(path)).expect("create zip"));
+    for (name, content) in entries {
+        zip.start_file(*name, SimpleFileOptions::default())
+            .expect("start zip entry");
+        zip.write_all(content).expect("write zip entry");
+    }
+    zip.finish().expect("finish zip");
+}
+
+/// Write a zip at `path` whose entries `(name, modified)` hold their own name, with
+/// `comment` as the archive comment
+/// 在 `path` 寫入 zip，各 `(名稱, 修改時間)` entry 的內容為其名稱，並以 `comment` 作為壓縮檔註解
+pub(crate) fn write_dated_zip(path: &Path, comment: Option<&str>, entries: &[(&str, DateTime)]) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(long_path::extend(parent)).expect("create zip folder");
+    }
+    let mut zip = ZipWriter::new(File::create(long_path::extend(path)).expect("create zip"));
+    if let Some(comment) = comment {
+        zip.set_comment(comment);
+    }
+    for (name, modified) in entries {
+        let options = SimpleFileOptions::default().last_modified_time(*modified);
+        zip.start_file(*name, options).expect("start zip entry");
+        zip.write_all(name.as_bytes()).expect("write zip entry");
+    }
+    zip.finish().expect("finish zip");
+}
+
+// ───────────────────────────────────────────────
+// Game Install / 遊戲安裝
+// ───────────────────────────────────────────────
+
+impl SyntheticInstall {
+    pub fn new() -> Self {
+        Self::in_folder("")
+    }
+
+    /// An install whose game root is `folder` inside the temp folder, for awkward names such
+    /// as `ゲーム/Koikatsu.`
+    /// 遊戲根目錄為暫存資料夾中 `folder` 的安裝，用於 `ゲーム/Koikatsu.` 這類特殊名稱
+    pub fn in_folder(folder: &str) -> Self {
+        let dir = tempfile::Builder::new()
+            .prefix("kk-synthetic-install")
+            .tempdir()
+            .expect("create temp dir");
+        let root = match folder {
+            "" => dir.path().to_path_buf(),
+            folder => dir.path().join(folder),
+        };
+        for folder in [
+            &["mods"][..],
+            &["BepInEx", "plugins"],
+            &["BepInEx", "config"],
+        ] {
+            let path = folder.iter().fold(root.clone(), |d, p| d.join(p));
+            fs::create_dir_all(long_path::extend(&path)).expect("create install folder");
+        }
+        SyntheticInstall { _dir: dir, root }
+    }
+
+    /// Game path as the commands take it / 指令所接受的遊戲路徑
+    pub fn game_path(&self) -> String {
+        long_path::display(&self.root)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Full path of `rel_path` under `mods` / `mods` 下 `rel_path` 的完整路徑
+    pub fn mod_path(&self, rel_path: &str) -> PathBuf {
+        self.root.join("mods").join(rel_path)
+    }
+
+    /// Generate a zipmod at `rel_path` under `mods` holding `manifest` and a dummy asset bundle
+    /// 在 `mods` 下的 `rel_path` 產生包含 `manifest` 與一個假資源包的 zipmod
+    pub fn add_zipmod(&self, rel_path: &str, manifest: &TestManifest) -> PathBuf {
+        let path = self.mod_path(rel_path);
+        let bundle = format!("abdata/{}.unity3d", manifest.guid);
+        write_zip(
+            &path,
+            &[
+                ("manifest.xml", manifest.to_xml().as_bytes()),
+                (&bundle, b"UnityFS dummy bundle"),
+            ],
+        );
+        path
+    }
+
+    /// Write `content` at `rel_path` under the game root, e.g. a truncated zipmod
+    /// 在遊戲根目錄下的 `rel_path` 寫入 `content`，例如不完整的 zipmod
+    pub fn add_file(&self, rel_path: &str, content: &[u8]) -> PathBuf {
+        let path = self.root.join(rel_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(long_path::extend(parent)).expect("create file folder");
+        }
+        fs::write(long_path::extend(&path), content).expect("write file");
+        path
+    }
+
+    /// Point Sideloader at a second mods folder `rel_path` under the game root and create it
+    /// 將 Sideloader 的第二個 mods 資料夾設為遊戲根目錄下的 `rel_path` 並建立該資料夾
+    pub fn set_additional_mods_dir(&self, rel_path: &str) -> PathBuf {
+        let dir = self.root.join(rel_path);
+        fs::create_dir_all(long_path::extend(&dir)).expect("create additional mods folder");
+        self.add_file(
+            "BepInEx/config/com.bepis.bepinex.sideloader.cfg",
+            format!("[Settings]\nAdditionalModsDirectory = {}\n", rel_path).as_bytes(),
+        );
+        dir
+    }
+
+    /// Write `log` as `BepInEx/LogOutput.log` / 將 `log` 寫為 `BepInEx/LogOutput.log`
+    pub fn write_log(&self, log: &SyntheticLog) -> PathBuf {
+        self.add_file("BepInEx/LogOutput.log", log.text().as_bytes())
+    }
+}
+
+// ───────────────────────────────────────────────
+// Log Synthesis / 產生 Log
+// ───────────────────────────────────────────────
+
+impl SyntheticLog {
+    /// A log holding only the BepInEx startup lines
+    /// 只有 BepInEx 啟動訊息的 log
+    pub fn new() -> Self {
+        SyntheticLog {
+            lines: vec![
+                "[Message:   BepInEx] BepInEx 5.4.22.0 - Koikatu".to_string(),
+                "[Info   :   BepInEx] Loading [Sideloader 19.3]".to_string(),
+            ],
+        }
+    }
+
+    /// Sideloader's duplicate warning; paths are relative to a mods folder. A path left out
+    /// of the install makes a stale entry.
+    /// Sideloader 的重複模組警告；路徑相對於 mods 資料夾。未放入安裝中的路徑即為過時項目
+    pub fn conflict(mut self, loaded: &str, skipped: &[&str]) -> Self {
+        let skipped = skipped
+            .iter()
+            .map(|path| format!("\"{}\"", path))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.lines.push(format!(
+            "[Warning:Sideloader] Multiple versions detected, only \"{}\" will be loaded. \
+             Skipped versions: {}",
+            loaded, skipped
+        ));
+        self
+    }
+
+    /// Sideloader's error for a mod it could not open
+    /// Sideloader 無法開啟模組時的錯誤訊息
+    pub fn load_error(mut self, path: &str, detail: &str) -> Self {
+        self.lines.push(format!(
+            "[Error  :Sideloader] Failed to load file \"{}\"!\r\n{}",
+            path, detail
+        ));
+        self
+    }
+
+    /// Any other line / 其他任意一行
+    pub fn line(mut self, line: &str) -> Self {
+        self.lines.push(line.to_string());
+        self
+    }
+
+    /// 1-based line the next added line will start on
+    /// 下一個加入的行將起始於第幾行（從 1 起算）
+    pub fn next_line(&self) -> usize {
+        1 + self
+            .lines
+            .iter()
+            .map(|line| line.lines().count())
+            .sum::<usize>()
+    }
+
+    /// The log with Windows line endings, as the game writes it
+    /// 使用 Windows 換行的 log，與遊戲寫出的相同
+    pub fn text(&self) -> String {
+        self.lines.join("\r\n") + "\r\n"
+    }
+}
@@ -0,0 +1,54 @@
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// ───────────────────────────────────────────────
+// Phase Timings / 階段計時
+// ───────────────────────────────────────────────
+
+/// Per-phase durations of one command, keyed like `regex_ms`.
+/// `Timings::default()` is disabled and `time` then just runs the work.
+/// 單一指令各階段的耗時，key 形如 `regex_ms`；`Timings::default()` 為停用狀態，此時 `time` 只會執行工作
+#[derive(Default)]
+pub struct Timings {
+    spans: Option<Mutex<BTreeMap<&'static str, Duration>>>,
+}
+
+impl Timings {
+    pub fn new(enabled: bool) -> Self {
+        Timings {
+            spans: enabled.then(Mutex::default),
+        }
+    }
+
+    /// Run `work`, adding its duration to `name`; repeated names accumulate
+    /// 執行 `work` 並將耗時累加到 `name`；同名階段會累加
+    pub fn time<T>(&self, name: &'static str, work: impl FnOnce() -> T) -> T {
+        let Some(spans) = &self.spans else {
+            return work();
+        };
+        let start = Instant::now();
+        let result = work();
+        let elapsed = start.elapsed();
+        *spans
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(name)
+            .or_default() += elapsed;
+        result
+    }
+
+    /// Milliseconds per phase, or `None` when timing was not requested
+    /// 各階段毫秒數；未要求計時時為 `None`
+    pub fn finish(self) -> Option<BTreeMap<&'static str, f64>> {
+        let spans = self.spans?.into_inner().unwrap_or_else(|e| e.into_inner());
+        Some(
+            spans
+                .into_iter()
+                .map(|(name, elapsed)| (name, elapsed.as_secs_f64() * 1000.0))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,428 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    app_log, disk_space, error::AppError, file_retry, long_path, messages::Message,
+    operations::OperationGuard, ops, path_guard::WriteMode, quarantine,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// One file operation of a transaction
+/// 交易中的單一檔案操作
+#[derive(Clone)]
+pub(crate) enum Step {
+    /// Copy `source` to `target`, which must not exist; undone by removing the copy
+    /// 將 `source` 複製到不存在的 `target`；復原時移除複本
+    Copy { source: PathBuf, target: PathBuf },
+    /// `quarantine::quarantine_file`; undone by moving the file back
+    /// `quarantine::quarantine_file`；復原時將檔案移回原處
+    Quarantine { game_path: PathBuf, path: PathBuf },
+    /// `ops::disable_file`; undone by renaming back / `ops::disable_file`；復原時改回原名
+    Disable { path: PathBuf },
+    /// `ops::trash_file`; cannot be undone, so plan it last
+    /// `ops::trash_file`；無法復原，應排在最後
+    Trash { path: PathBuf },
+}
+
+/// What became of one step
+/// 單一步驟的結果
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub(crate) enum StepStatus {
+    Done,
+    /// The step that stopped the transaction / 使交易中止的步驟
+    Failed {
+        error: AppError,
+    },
+    /// After the failed step, so never started / 位於失敗步驟之後，未曾執行
+    NotRun,
+    /// Done, then undone / 已執行後復原
+    RolledBack,
+    /// Done, and undoing it failed; the file is at `destination`
+    /// 已執行但復原失敗；檔案位於 `destination`
+    RollbackFailed {
+        error: AppError,
+    },
+    /// Done and cannot be undone, e.g. moved to the Recycle Bin
+    /// 已執行且無法復原，例如已移到回收桶
+    NotReversible,
+}
+
+#[derive(Serialize)]
+pub(crate) struct StepReport {
+    /// `copy`, `quarantine`, `disable` or `trash`
+    pub action: &'static str,
+    pub path: String,
+    /// Where the step put the file / 步驟將檔案放置的位置
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    #[serde(flatten)]
+    pub status: StepStatus,
+    /// Times the step was retried before it succeeded while another process held the file
+    /// 步驟成功前因其他程序佔用檔案而重試的次數
+    pub retries: u32,
+}
+
+/// Outcome of `run`: one report per step, in plan order
+/// `run` 的結果：依規劃順序每個步驟各一筆
+#[derive(Serialize)]
+pub(crate) struct TransactionReport {
+    /// Every step is done / 所有步驟皆已完成
+    pub committed: bool,
+    pub steps: Vec<StepReport>,
+}
+
+impl Step {
+    fn action(&self) -> &'static str {
+        match self {
+            Step::Copy { .. } => "copy",
+            Step::Quarantine { .. } => "quarantine",
+            Step::Disable { .. } => "disable",
+            Step::Trash { .. } => "trash",
+        }
+    }
+
+    /// The file the step reads or moves / 步驟讀取或移動的檔案
+    fn source(&self) -> &Path {
+        match self {
+            Step::Copy { source, .. } => source,
+            Step::Quarantine { path, .. } | Step::Disable { path } | Step::Trash { path } => path,
+        }
+    }
+
+    /// Fixed destination; quarantine and disable pick a free name when they run
+    /// 固定的目的地；隔離與停用會在執行時才選擇可用的名稱
+    fn target(&self) -> Option<&Path> {
+        match self {
+            Step::Copy { target, .. } => Some(target),
+            Step::Quarantine { .. } | Step::Disable { .. } | Step::Trash { .. } => None,
+        }
+    }
+}
+
+impl TransactionReport {
+    /// Error of the step that stopped the transaction
+    /// 使交易中止的步驟的錯誤
+    pub(crate) fn error(&self) -> Option<&AppError> {
+        self.steps.iter().find_map(|step| match &step.status {
+            StepStatus::Failed { error } => Some(error),
+            _ => None,
+        })
+    }
+}
+
+// ───────────────────────────────────────────────
+// Validation / 驗證
+// ───────────────────────────────────────────────
+
+fn key(path: &Path) -> String {
+    long_path::display(path).to_lowercase()
+}
+
+fn step_error(key: &'static str, index: usize, path: &Path) -> AppError {
+    AppError::InvalidInput {
+        message: Message::new(key)
+            .with("step", (index + 1).to_string())
+            .with("path", long_path::display(path)),
+    }
+}
+
+/// Check every step against the files as they will be when it runs: its source exists (on
+/// disk or written by an earlier step), a fixed target is free, files that are moved are not
+/// read-only, and the volume of each copy has room for all copies. With `operation`, every
+/// path is locked first; `write_mode` refuses any step in read-only mode.
+/// 依各步驟執行時的檔案狀態檢查：來源存在（於磁碟上或由先前步驟寫入）、固定的目的地未被佔用、
+/// 被移動的檔案不是唯讀，且每個複本所在磁碟區可容納所有複本；指定 `operation` 時先鎖定所有路徑；`write_mode` 為唯讀時拒絕任何步驟
+fn validate(
+    steps: &[Step],
+    write_mode: WriteMode,
+    operation: Option<&OperationGuard>,
+) -> Result<(), AppError> {
+    steps
+        .iter()
+        .flat_map(|step| std::iter::once(step.source()).chain(step.target()))
+        .try_for_each(|path| write_mode.ensure_writable(path))?;
+    if let Some(operation) = operation {
+        let paths = steps
+            .iter()
+            .flat_map(|step| std::iter::once(step.source()).chain(step.target()))
+            .collect::<Vec<_>>();
+        operation.lock_paths(&paths)?;
+    }
+
+    let mut created = HashSet::new();
+    let mut removed = HashSet::new();
+    let mut copied_bytes = 0;
+    for (index, step) in steps.iter().enumerate() {
+        let source = step.source();
+        let on_disk = |path: &Path| {
+            !created.contains(&key(path))
+                && !removed.contains(&key(path))
+                && long_path::extend(path).exists()
+        };
+        let present = |path: &Path| created.contains(&key(path)) || on_disk(path);
+        if !present(source) {
+            return Err(step_error(
+                "input.transaction_source_missing",
+                index,
+                source,
+            ));
+        }
+        if let Some(target) = step.target().filter(|target| present(target)) {
+            return Err(step_error("input.transaction_target_exists", index, target));
+        }
+        let moves_source = !matches!(step, Step::Copy { .. });
+        let read_only = on_disk(source)
+            && fs::metadata(long_path::extend(source))
+                .is_ok_and(|meta| meta.permissions().readonly());
+        if moves_source && read_only {
+            return Err(step_error("input.transaction_read_only", index, source));
+        }
+        if let Step::Copy { target, .. } = step {
+            copied_bytes += disk_space::bytes_needed(&[source.to_path_buf()], target, false);
+            disk_space::ensure_space(target, copied_bytes)?;
+        }
+
+        if moves_source {
+            created.remove(&key(source));
+            removed.insert(key(source));
+        }
+        if let Some(target) = step.target() {
+            removed.remove(&key(target));
+            created.insert(key(target));
+        }
+    }
+    Ok(())
+}
+
+// ───────────────────────────────────────────────
+// Execution / 執行
+// ───────────────────────────────────────────────
+
+/// Carry out one step and return where it put the file and how many retries it took
+/// 執行單一步驟並回傳檔案被放置的位置與所需的重試次數
+fn execute(step: &Step, write_mode: WriteMode) -> Result<(Option<PathBuf>, u32), AppError> {
+    match step {
+        Step::Copy { source, target } => {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(long_path::extend(parent))
+                    .map_err(|e| AppError::io("io.copy_file", Some(parent), e))?;
+            }
+            if let Err(e) = fs::copy(long_path::extend(source), long_path::extend(target)) {
+                disk_space::remove_partial(target);
+                return Err(AppError::io("io.copy_file", Some(source), e));
+            }
+            Ok((Some(target.clone()), 0))
+        }
+        Step::Quarantine { game_path, path } => {
+            quarantine::quarantine_file(game_path, path, write_mode)
+                .map(|(destination, retries)| (Some(destination), retries))
+        }
+        Step::Disable { path } => ops::disable_file(path, write_mode)
+            .map(|(destination, retries)| (Some(destination), retries)),
+        Step::Trash { path } => ops::trash_file(path, write_mode).map(|retries| (None, retries)),
+    }
+}
+
+/// Move `from` back to `original`, retrying while another process holds it and copying when
+/// a rename is not possible (across volumes)
+/// 將 `from` 移回 `original`；其他程序佔用時會重試，無法改名時（跨磁碟區）改為複製
+fn move_back(from: &Path, original: &Path) -> io::Result<()> {
+    let (from, original) = (long_path::extend(from), long_path::extend(original));
+    if original.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "original path is taken",
+        ));
+    }
+    match file_retry::retry(|| fs::rename(&from, &original)) {
+        Ok(_) => return Ok(()),
+        Err(exhausted) if exhausted.locked => return Err(exhausted.error),
+        Err(_) => {}
+    }
+    fs::copy(&from, &original)?;
+    file_retry::retry(|| fs::remove_file(&from))
+        .map(|_| ())
+        .map_err(|exhausted| exhausted.error)
+}
+
+/// Undo one done step / 復原單一已完成的步驟
+fn undo(step: &Step, destination: Option<&Path>) -> StepStatus {
+    let result = match (step, destination) {
+        (Step::Trash { .. }, _) | (_, None) => return StepStatus::NotReversible,
+        (Step::Copy { .. }, Some(copy)) => fs::remove_file(long_path::extend(copy)),
+        (_, Some(moved)) => move_back(moved, step.source()),
+    };
+    match result {
+        Ok(()) => StepStatus::RolledBack,
+        Err(e) => StepStatus::RollbackFailed {
+            error: AppError::io("io.roll_back_step", destination, e),
+        },
+    }
+}
+
+fn log(label: &str, report: &TransactionReport) {
+    let steps = report
+        .steps
+        .iter()
+        .map(|step| {
+            let status = match &step.status {
+                StepStatus::Done => "done".to_string(),
+                StepStatus::Failed { error } => format!("failed: {}", error),
+                StepStatus::NotRun => "not run".to_string(),
+                StepStatus::RolledBack => "rolled back".to_string(),
+                StepStatus::RollbackFailed { error } => format!("rollback failed: {}", error),
+                StepStatus::NotReversible => "not reversible".to_string(),
+            };
+            format!(
+                "{} {}{} ({})",
+                step.action,
+                step.path,
+                step.destination
+                    .as_ref()
+                    .map(|destination| format!(" -> {}", destination))
+                    .unwrap_or_default(),
+                status
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    let outcome = match report.committed {
+        true => "committed",
+        false => "rolled back",
+    };
+    app_log::write(&format!("transaction {} {}: {}", label, outcome, steps));
+}
+
+/// Run `steps` as one unit. Every step is validated before any runs; a validation failure
+/// changes nothing and is returned as the error. The steps then run in order, and when one
+/// fails, those already done are undone in reverse order on a best-effort basis: copies are
+/// removed, quarantined and disabled files are moved back, and trashed files stay in
+/// the Recycle Bin. The report tells what became of each step, and the whole transaction is
+/// written to the app log as one entry under `label`.
+/// 將 `steps` 作為一個整體執行。任何步驟執行前先驗證全部步驟；驗證失敗時不做任何變更並回傳錯誤。
+/// 之後依序執行，若有步驟失敗，已完成的步驟會依相反順序盡力復原：移除複本，將隔離與停用的檔案移回原處，
+/// 已移到回收桶的檔案則留在回收桶。報告列出每個步驟的結果，整筆交易以 `label` 作為單一項目寫入 app log
+pub(crate) fn run(
+    label: &str,
+    steps: &[Step],
+    write_mode: WriteMode,
+    operation: Option<&OperationGuard>,
+) -> Result<TransactionReport, AppError> {
+    validate(steps, write_mode, operation)?;
+
+    let mut done = Vec::new();
+    let mut failure = None;
+    for step in steps {
+        match execute(step, write_mode) {
+            Ok(destination) => done.push(destination),
+            Err(error) => {
+                failure = Some(error);
+                break;
+            }
+        }
+    }
+
+    let committed = failure.is_none();
+    let mut statuses = steps.iter().map(|_| StepStatus::NotRun).collect::<Vec<_>>();
+    for (index, (destination, _)) in done.iter().enumerate().rev() {
+        statuses[index] = match committed {
+            true => StepStatus::Done,
+            false => undo(&steps[index], destination.as_deref()),
+        };
+    }
+    if let Some(error) = failure {
+        statuses[done.len()] = StepStatus::Failed { error };
+    }
+
+    let report = TransactionReport {
+        committed,
+        steps: steps
+            .iter()
+            .zip(statuses)
+            .enumerate()
+            .map(|(index, (step, status))| StepReport {
+                action: step.action(),
+                path: long_path::display(step.source()),
+                destination: done
+                    .get(index)
+                    .and_then(|(destination, _)| destination.as_deref())
+                    .map(long_path::display),
+                status,
+                retries: done.get(index).map_or(0, |(_, retries)| *retries),
+            })
+            .collect(),
+    };
+    log(label, &report);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{run, Step, StepStatus};
+    use crate::{
+        error::AppError,
+        path_guard::WriteMode,
+        test_support::{SyntheticInstall, TestManifest},
+    };
+
+    #[test]
+    fn failed_transaction_moves_quarantined_files_back() {
+        let install = SyntheticInstall::new();
+        let first = install.add_zipmod("dl/a.zipmod", &TestManifest::new("a"));
+        let second = install.add_zipmod("dl/b.zipmod", &TestManifest::new("b"));
+        let quarantine = |path: &Path| Step::Quarantine {
+            game_path: install.root().to_path_buf(),
+            path: path.to_path_buf(),
+        };
+        // Copying a folder passes validation but fails when run
+        // 複製資料夾可通過驗證，但執行時會失敗
+        let steps = [
+            quarantine(&first),
+            quarantine(&second),
+            Step::Copy {
+                source: install.mod_path("dl"),
+                target: install.mod_path("copy"),
+            },
+        ];
+
+        let report = run("test", &steps, WriteMode::default(), None).unwrap();
+
+        assert!(!report.committed);
+        assert!(matches!(report.steps[0].status, StepStatus::RolledBack));
+        assert!(matches!(report.steps[1].status, StepStatus::RolledBack));
+        assert!(matches!(report.steps[2].status, StepStatus::Failed { .. }));
+        assert!(first.exists());
+        assert!(second.exists());
+        assert!(!install.root().join("quarantine/mods/dl/a.zipmod").exists());
+    }
+
+    #[test]
+    fn invalid_transaction_touches_nothing() {
+        let install = SyntheticInstall::new();
+        let present = install.add_zipmod("a.zipmod", &TestManifest::new("a"));
+        let steps = [
+            Step::Disable {
+                path: present.clone(),
+            },
+            Step::Disable {
+                path: install.mod_path("missing.zipmod"),
+            },
+        ];
+
+        let result = run("test", &steps, WriteMode::default(), None);
+
+        assert!(matches!(result, Err(AppError::InvalidInput { .. })));
+        assert!(present.exists());
+    }
+}
@@ -0,0 +1,482 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use walkdir::WalkDir;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    app_log,
+    archive_inspect::{self, ArchiveFormat},
+    disk_space,
+    error::AppError,
+    long_path,
+    manifest::{self, ManifestData},
+    operations::CancelToken,
+    parser::{build_mod_entry, ModEntry},
+    progress::{OnProgress, Progress},
+    quarantine,
+};
+
+// ───────────────────────────────────────────────
+// Data Structures / 資料結構定義
+// ───────────────────────────────────────────────
+
+/// Name of the scratch folder created under the destination while converting
+/// 轉換期間在目的地底下建立的暫存資料夾名稱
+const SCRATCH_DIR: &str = ".zipmod-convert";
+
+/// Characters Windows does not allow in file names
+/// Windows 不允許出現在檔名中的字元
+const INVALID_NAME_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// A folder inside a download that holds manifest.xml next to an abdata folder
+/// 下載檔中同時包含 manifest.xml 與 abdata 資料夾的資料夾
+struct ModRoot {
+    /// Entry name prefix of the folder, `""` for the archive root or e.g. `Pack/`
+    /// 該資料夾的 entry 名稱前綴；壓縮檔根目錄為 `""`，否則如 `Pack/`
+    prefix: String,
+    /// Full entry name of its manifest, keeping the archive's spelling
+    /// 其 manifest 的完整 entry 名稱，保留壓縮檔中的大小寫
+    manifest: String,
+}
+
+/// Extraction state shared by the per-format readers: which entries to keep, where they
+/// go, and how far along the conversion is
+/// 各格式讀取器共用的解壓縮狀態：保留哪些 entry、寫到哪裡，以及轉換進度
+struct Extraction<'a> {
+    source: &'a Path,
+    scratch: &'a Path,
+    destination: &'a Path,
+    roots: Vec<ModRoot>,
+    cancel: &'a CancelToken,
+    on_progress: OnProgress<'a>,
+    done: usize,
+    total: usize,
+}
+
+/// Scratch folder that is removed when dropped, whether converting succeeded or not
+/// 離開作用域時移除的暫存資料夾，無論轉換成功與否
+struct ScratchDir(PathBuf);
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(long_path::extend(&self.0)) {
+            if e.kind() != io::ErrorKind::NotFound {
+                app_log::write(&format!(
+                    "failed to remove conversion scratch folder {}: {}",
+                    long_path::display(&self.0),
+                    e
+                ));
+            }
+        }
+    }
+}
+
+// ───────────────────────────────────────────────
+// Mod Roots / 模組根目錄
+// ───────────────────────────────────────────────
+
+/// Every folder holding manifest.xml and abdata, outermost first. A manifest nested inside
+/// another root belongs to that root's files and does not start a mod of its own.
+/// 找出所有包含 manifest.xml 與 abdata 的資料夾，外層在前；位於其他根目錄內的 manifest 屬於該模組的檔案，不另成模組
+fn find_mod_roots<'a>(names: impl Iterator<Item = &'a str> + Clone) -> Vec<ModRoot> {
+    let mut roots = names
+        .clone()
+        .filter(|name| {
+            let lower = name.to_lowercase();
+            lower == "manifest.xml" || lower.ends_with("/manifest.xml")
+        })
+        .map(|name| {
+            let prefix = &name[..name.len() - "manifest.xml".len()];
+            ModRoot {
+                prefix: prefix.to_string(),
+                manifest: name.to_string(),
+            }
+        })
+        .filter(|root| {
+            let abdata = format!("{}abdata/", root.prefix).to_lowercase();
+            names
+                .clone()
+                .any(|name| name.to_lowercase().starts_with(&abdata))
+        })
+        .collect::<Vec<_>>();
+    roots.sort_by_key(|root| root.prefix.matches('/').count());
+
+    let mut independent: Vec<ModRoot> = Vec::new();
+    for root in roots {
+        let lower = root.prefix.to_lowercase();
+        if !independent
+            .iter()
+            .any(|outer| lower.starts_with(&outer.prefix.to_lowercase()))
+        {
+            independent.push(root);
+        }
+    }
+    independent
+}
+
+/// Relative path of an archive entry, rejecting anything that would leave the target folder
+/// 壓縮檔 entry 的相對路徑；會寫到目標資料夾之外的名稱一律拒絕
+fn safe_relative(name: &str) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for part in name.split(['/', '\\']) {
+        match Path::new(part).components().next() {
+            None | Some(Component::CurDir) => {}
+            Some(Component::Normal(_)) if !part.contains(':') => relative.push(part),
+            _ => return None,
+        }
+    }
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
+
+impl Extraction<'_> {
+    /// Pick the mod roots from the listing and make sure the destination volume can hold
+    /// both the scratch copy and the repacked zipmods
+    /// 從清單中找出模組根目錄，並確認目的地磁碟區能容納暫存檔與重新打包的 zipmod
+    fn plan(&mut self, entries: &[(String, u64)]) -> Result<(), AppError> {
+        self.roots = find_mod_roots(entries.iter().map(|(name, _)| name.as_str()));
+        if self.roots.is_empty() {
+            return Err(AppError::ManifestMissing {
+                path: Some(long_path::display(self.source)),
+            });
+        }
+        let kept = entries
+            .iter()
+            .filter(|(name, _)| self.target(name).is_some())
+            .collect::<Vec<_>>();
+        // Every kept file is written twice: once extracted, once repacked
+        // 每個保留的檔案會寫入兩次：解壓縮一次、重新打包一次
+        let bytes = kept.iter().map(|(_, size)| size).sum::<u64>();
+        disk_space::ensure_space(self.destination, bytes.saturating_mul(2))?;
+        self.total = kept.len() * 2;
+        Ok(())
+    }
+
+    /// Scratch path of an entry, or `None` when it lies outside every mod root
+    /// entry 在暫存資料夾中的路徑；不屬於任何模組根目錄時為 `None`
+    fn target(&self, name: &str) -> Option<PathBuf> {
+        if name.ends_with('/') {
+            return None;
+        }
+        let lower = name.to_lowercase();
+        let (index, root) = self
+            .roots
+            .iter()
+            .enumerate()
+            .find(|(_, root)| lower.starts_with(&root.prefix.to_lowercase()))?;
+        let relative = safe_relative(&name[root.prefix.len()..])?;
+        Some(self.scratch.join(index.to_string()).join(relative))
+    }
+
+    /// Count one file as done, reporting progress and honouring cancellation
+    /// 記錄完成一個檔案，回報進度並檢查是否已取消
+    fn advance(&mut self, name: &str) -> Result<(), AppError> {
+        if self.cancel.is_cancelled() {
+            return Err(AppError::OperationCancelled);
+        }
+        (self.on_progress)(Progress::new(self.done, self.total).with_message(name));
+        self.done += 1;
+        Ok(())
+    }
+}
+
+// ───────────────────────────────────────────────
+// Extraction / 解壓縮
+// ───────────────────────────────────────────────
+
+fn invalid(path: &Path, format: ArchiveFormat, detail: impl ToString) -> AppError {
+    AppError::ArchiveInvalid {
+        path: long_path::display(path),
+        format: format.label(),
+        detail: detail.to_string(),
+    }
+}
+
+/// Stream one entry to its scratch path
+/// 將單一 entry 串流寫入暫存路徑
+fn write_entry(target: &Path, reader: &mut dyn Read) -> Result<(), AppError> {
+    let error = |e| AppError::io("io.extract_archive", Some(target), e);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(long_path::extend(parent)).map_err(error)?;
+    }
+    let mut output = File::create(long_path::extend(target)).map_err(error)?;
+    io::copy(reader, &mut output).map_err(error)?;
+    Ok(())
+}
+
+fn extract_zip(extraction: &mut Extraction) -> Result<(), AppError> {
+    let source = extraction.source;
+    let file = File::open(long_path::extend(source))
+        .map_err(|e| AppError::io("io.open_file", Some(source), e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| AppError::ZipInvalid {
+        path: Some(long_path::display(source)),
+        detail: e.to_string(),
+    })?;
+    let entries = (0..archive.len())
+        .filter_map(|index| {
+            let entry = archive.by_index_raw(index).ok()?;
+            Some((entry.name().to_string(), entry.size()))
+        })
+        .collect::<Vec<_>>();
+    extraction.plan(&entries)?;
+
+    for (index, (name, _)) in entries.iter().enumerate() {
+        let Some(target) = extraction.target(name) else {
+            continue;
+        };
+        extraction.advance(name)?;
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| invalid(source, ArchiveFormat::Zip, e))?;
+        write_entry(&target, &mut entry)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "sevenz")]
+fn extract_7z(extraction: &mut Extraction) -> Result<(), AppError> {
+    use sevenz_rust::{Archive, Password, SevenZReader};
+
+    let source = extraction.source;
+    let mut file = File::open(long_path::extend(source))
+        .map_err(|e| AppError::io("io.open_file", Some(source), e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| AppError::io("io.open_file", Some(source), e))?
+        .len();
+    let archive = Archive::read(&mut file, len, &[])
+        .map_err(|e| invalid(source, ArchiveFormat::SevenZ, e))?;
+    let entries = archive
+        .files
+        .iter()
+        .filter(|entry| !entry.is_directory())
+        .map(|entry| (entry.name().to_string(), entry.size()))
+        .collect::<Vec<_>>();
+    extraction.plan(&entries)?;
+
+    // Errors of our own are carried out of the callback, which can only return 7z errors
+    // 回呼只能回傳 7z 錯誤，因此自身的錯誤另外帶出
+    let mut failure = None;
+    SevenZReader::from_archive(archive, file, Password::empty())
+        .for_each_entries(|entry, reader| {
+            let target = match entry.is_directory() {
+                true => None,
+                false => extraction.target(entry.name()),
+            };
+            let Some(target) = target else {
+                // Skipped files must still be read through so the next starts in place
+                // 略過的檔案仍須讀完，下一個檔案才會從正確位置開始
+                io::copy(reader, &mut io::sink())?;
+                return Ok(true);
+            };
+            let written = extraction
+                .advance(entry.name())
+                .and_then(|()| write_entry(&target, reader));
+            match written {
+                Ok(()) => Ok(true),
+                Err(e) => {
+                    failure = Some(e);
+                    Ok(false)
+                }
+            }
+        })
+        .map_err(|e| invalid(source, ArchiveFormat::SevenZ, e))?;
+    failure.map_or(Ok(()), Err)
+}
+
+#[cfg(feature = "rar")]
+fn extract_rar(extraction: &mut Extraction) -> Result<(), AppError> {
+    use unrar::Archive;
+
+    let source = extraction.source;
+    let rar_error = |e: unrar::error::UnrarError| invalid(source, ArchiveFormat::Rar, e);
+    let entry_name =
+        |header: &unrar::FileHeader| header.filename.to_string_lossy().replace('\\', "/");
+    let entries = Archive::new(source)
+        .open_for_listing()
+        .map_err(rar_error)?
+        .filter_map(|header| match header {
+            Ok(header) if header.is_directory() => None,
+            Ok(header) => Some(Ok((entry_name(&header), header.unpacked_size))),
+            Err(e) => Some(Err(rar_error(e))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    extraction.plan(&entries)?;
+
+    let mut archive = Archive::new(source)
+        .open_for_processing()
+        .map_err(rar_error)?;
+    while let Some(header) = archive.read_header().map_err(rar_error)? {
+        let name = entry_name(header.entry());
+        let target = match header.entry().is_directory() {
+            true => None,
+            false => extraction.target(&name),
+        };
+        archive = match target {
+            Some(target) => {
+                extraction.advance(&name)?;
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(long_path::extend(parent))
+                        .map_err(|e| AppError::io("io.extract_archive", Some(&target), e))?;
+                }
+                header
+                    .extract_to(long_path::extend(&target))
+                    .map_err(rar_error)?
+            }
+            None => header.skip().map_err(rar_error)?,
+        };
+    }
+    Ok(())
+}
+
+// ───────────────────────────────────────────────
+// Repacking / 重新打包
+// ───────────────────────────────────────────────
+
+/// `[Author] Name vVersion.zipmod`, leaving out what the manifest lacks and replacing
+/// characters Windows rejects
+/// 依 manifest 產生 `[Author] Name vVersion.zipmod`，缺少的欄位省略，並替換 Windows 不允許的字元
+fn zipmod_file_name(manifest: &ManifestData) -> String {
+    let present = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let mut name = String::new();
+    if let Some(author) = present(&manifest.author) {
+        name.push_str(&format!("[{}] ", author));
+    }
+    name.push_str(&present(&manifest.name).unwrap_or_else(|| manifest.guid.clone()));
+    if let Some(version) = present(&manifest.version) {
+        name.push_str(&format!(" v{}", version.trim_start_matches(['v', 'V'])));
+    }
+    let name = name
+        .chars()
+        .map(
+            |c| match c.is_control() || INVALID_NAME_CHARS.contains(&c) {
+                true => '_',
+                false => c,
+            },
+        )
+        .collect::<String>();
+    format!("{}.zipmod", name.trim_end_matches(['.', ' ']))
+}
+
+/// Pack the extracted files of one mod root into a stored (uncompressed) zip at `target`
+/// 將單一模組根目錄解出的檔案以不壓縮（Store）方式打包為 `target`
+fn pack_root(root_dir: &Path, target: &Path, extraction: &mut Extraction) -> Result<(), AppError> {
+    let error = |e: io::Error| AppError::io("io.write_zipmod", Some(target), e);
+    let file = File::create(long_path::extend(target)).map_err(error)?;
+    let mut zip = ZipWriter::new(file);
+    let files = WalkDir::new(long_path::extend(root_dir))
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file());
+    let scratch_root = long_path::extend(root_dir);
+    for entry in files {
+        let relative = entry
+            .path()
+            .strip_prefix(&scratch_root)
+            .unwrap_or(entry.path())
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        extraction.advance(&relative)?;
+        let size = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(size >= u32::MAX as u64);
+        zip.start_file(relative, options)
+            .map_err(|e| error(io::Error::other(e)))?;
+        let mut input = File::open(entry.path()).map_err(error)?;
+        io::copy(&mut input, &mut zip).map_err(error)?;
+    }
+    zip.finish().map_err(|e| error(io::Error::other(e)))?;
+    Ok(())
+}
+
+/// Repack every extracted mod root into `destination`, named from its own manifest
+/// 將每個解出的模組根目錄依其 manifest 命名，重新打包至 `destination`
+fn pack_all(extraction: &mut Extraction, outputs: &mut Vec<PathBuf>) -> Result<(), AppError> {
+    for index in 0..extraction.roots.len() {
+        let root_dir = extraction.scratch.join(index.to_string());
+        let root = &extraction.roots[index];
+        let manifest_path = root_dir.join(&root.manifest[root.prefix.len()..]);
+        let content = fs::read_to_string(long_path::extend(&manifest_path))
+            .map_err(|e| AppError::io("io.open_file", Some(&manifest_path), e))?;
+        let manifest = manifest::parse_manifest(&content)
+            .map_err(|e| e.with_archive_path(&long_path::display(extraction.source)))?;
+
+        let target =
+            quarantine::unique_path(&extraction.destination.join(zipmod_file_name(&manifest)));
+        outputs.push(target.clone());
+        pack_root(&root_dir, &target, extraction)?;
+    }
+    Ok(())
+}
+
+// ───────────────────────────────────────────────
+// Conversion / 轉換
+// ───────────────────────────────────────────────
+
+/// Turn a zip, 7z or rar download into proper `.zipmod` files in `destination`, one per mod
+/// root (a folder with manifest.xml and abdata). Files are streamed into a scratch folder
+/// under `destination` that is always removed afterwards; on failure the zipmods written
+/// so far are removed too. Downloads without a mod root fail with `manifest_missing`.
+/// 將 zip、7z 或 rar 下載檔轉換為 `destination` 中正確的 `.zipmod`，每個模組根目錄（含 manifest.xml 與 abdata 的資料夾）各一個；
+/// 檔案串流解出至 `destination` 底下的暫存資料夾，結束後一律移除，失敗時也會移除已寫出的 zipmod；沒有模組根目錄的下載檔回傳 `manifest_missing`
+pub(crate) fn convert_to_zipmod(
+    source: &Path,
+    destination: &Path,
+    cancel: &CancelToken,
+    on_progress: OnProgress,
+) -> Result<Vec<ModEntry>, AppError> {
+    let format = archive_inspect::detect_format(source).unwrap_or(ArchiveFormat::Zip);
+    archive_inspect::ensure_supported(source, format)?;
+    fs::create_dir_all(long_path::extend(destination))
+        .map_err(|e| AppError::io("io.write_zipmod", Some(destination), e))?;
+    let scratch = ScratchDir(quarantine::unique_path(&destination.join(SCRATCH_DIR)));
+
+    let mut extraction = Extraction {
+        source,
+        scratch: &scratch.0,
+        destination,
+        roots: Vec::new(),
+        cancel,
+        on_progress,
+        done: 0,
+        total: 0,
+    };
+    let mut outputs = Vec::new();
+    let result = match format {
+        ArchiveFormat::Zip => extract_zip(&mut extraction),
+        #[cfg(feature = "sevenz")]
+        ArchiveFormat::SevenZ => extract_7z(&mut extraction),
+        #[cfg(feature = "rar")]
+        ArchiveFormat::Rar => extract_rar(&mut extraction),
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("checked by ensure_supported"),
+    }
+    .and_then(|()| pack_all(&mut extraction, &mut outputs));
+    if let Err(e) = result {
+        outputs
+            .iter()
+            .for_each(|path| disk_space::remove_partial(path));
+        return Err(e);
+    }
+    on_progress(Progress::new(extraction.total, extraction.total));
+
+    // Every file written is a `.zipmod` / 寫出的檔案皆為 `.zipmod`
+    let mod_extensions = ["zipmod".to_string()];
+    Ok(outputs
+        .iter()
+        .map(|path| build_mod_entry(path, &path.to_string_lossy(), &mod_extensions))
+        .collect())
+}
@@ -16,7 +16,9 @@ type ManifestData = {
   name?: string;
   version?: string;
   author?: string;
-  description?: string;
+  description_raw?: string;
+  description_display?: string;
+  description_truncated: boolean;
 };
 
 type ModEntry = {
@@ -24,12 +26,126 @@ type ModEntry = {
   path: string;
   size: number;
   created: number | null;
+  modified: number | null;
+  created_local: string | null;
+  modified_local: string | null;
+  timestamp_clamped: boolean;
+  changed_since_log: boolean;
+  name_author: string | null;
+  name_title: string | null;
+  name_version: string | null;
+  link_target?: string;
+  managed: boolean;
+  cloud_placeholder: boolean;
   manifest?: ManifestData;
 };
 
 type ModConflict = {
+  id: string;
   loaded: ModEntry;
   skipped: ModEntry[];
+  changed_since_log: boolean;
+  wasted_bytes: number;
+  skipped_count: number;
+  latest_activity: number | null;
+  note?: string;
+};
+
+// Error shape returned by every Tauri command; branch on `code`, display `message`
+// 所有 Tauri 指令回傳的錯誤格式；依 `code` 判斷，顯示 `message`
+type AppError = {
+  code:
+    | 'log_not_found'
+    | 'log_unreadable'
+    | 'zip_invalid'
+    | 'manifest_missing'
+    | 'manifest_parse_failed'
+    | 'archive_invalid'
+    | 'archive_format_unsupported'
+    | 'delete_failed'
+    | 'path_not_allowed'
+    | 'mods_dir_missing'
+    | 'modpack_protected'
+    | 'pinned'
+    | 'file_changed'
+    | 'file_locked'
+    | 'game_running'
+    | 'operation_cancelled'
+    | 'internal_panic'
+    | 'not_managed_plugin'
+    | 'plugin_info_missing'
+    | 'invalid_input'
+    | 'insufficient_space'
+    | 'scan_expired'
+    | 'safe_mode_refused'
+    | 'read_only_mode'
+    | 'path_busy'
+    | 'operation_id_in_use'
+    | 'no_archive_handler'
+    | 'cloud_placeholder'
+    | 'io_failed';
+  key: string;
+  params: Record<string, string>;
+  message: string;
+  path?: string;
+  detail?: string;
+  incident_id?: string;
+  needed?: number;
+  available?: number;
+};
+
+const isAppError = (err: unknown): err is AppError =>
+  typeof err === 'object' && err !== null && 'code' in err && 'message' in err;
+
+const errorMessage = (err: unknown) =>
+  isAppError(err) ? err.message : String(err);
+
+// Envelope a heavy command returns instead of a result too large for IPC
+// 耗時指令在結果過大而不適合經由 IPC 傳遞時改為回傳的封裝
+type StoredResult = {
+  stored_at: string;
+  bytes: number;
+  summary: Record<string, unknown>;
+};
+
+type ResultChunk = {
+  data: string;
+  next_offset: number;
+  total_bytes: number;
+  done: boolean;
+};
+
+// Invoke a command that may hand its result over as a file, reading the file back in chunks
+// 呼叫可能以檔案傳遞結果的指令，並分段讀回檔案內容
+const invokeGuarded = async <T,>(
+  command: string,
+  args: Record<string, unknown>
+): Promise<T> => {
+  const result = await invoke<T | StoredResult>(command, args);
+  if (
+    typeof result !== 'object' ||
+    result === null ||
+    !('stored_at' in result)
+  ) {
+    return result as T;
+  }
+  const path = (result as StoredResult).stored_at;
+  let json = '';
+  let offset = 0;
+  try {
+    for (;;) {
+      const chunk = await invoke<ResultChunk>('read_result_chunk', {
+        path,
+        offset,
+      });
+      json += chunk.data;
+      offset = chunk.next_offset;
+      if (chunk.done) break;
+    }
+  } finally {
+    await invoke('discard_result', { path }).catch(console.error);
+  }
+  return JSON.parse(json) as T;
 };
 
 const formatSize = (size: number) => {
@@ -51,6 +167,31 @@ const isSizeDuplicate = (conflict: ModConflict, size: number) => {
 
 const isSideloader = (path: string) => path.includes('Sideloader');
 
+// Delete mods, asking before touching KKManager modpack folders (they get re-synced),
+// pinned files or files that changed since the scan
+// 刪除模組；若涉及 KKManager 模組包資料夾（會被重新同步）、已釘選或掃描後已變動的檔案需先詢問
+const deleteMods = async (paths: string[]) => {
+  const flags = { allowModpack: false, allowPinned: false, force: false };
+  for (;;) {
+    try {
+      await invoke('delete_mods', { paths, ...flags });
+      return;
+    } catch (err) {
+      if (!isAppError(err)) throw err;
+      if (err.code === 'modpack_protected' && !flags.allowModpack) {
+        flags.allowModpack = true;
+      } else if (err.code === 'pinned' && !flags.allowPinned) {
+        flags.allowPinned = true;
+      } else if (err.code === 'file_changed' && !flags.force) {
+        flags.force = true;
+      } else {
+        throw err;
+      }
+      if (!window.confirm(`${err.message}\n\nDelete anyway?`)) throw err;
+    }
+  }
+};
+
 // Load manifest lazily only when the component becomes visible
 // 懶載入 manifest：當元件進入畫面時才載入
 const useManifestLoader = (
@@ -116,15 +257,14 @@ const useParseLog = (gamePath: string) => {
     setError('');
     if (!gamePath) return;
     try {
-      const log: string = await invoke('read_log_from_path', { gamePath });
-      const parsed: ModConflict[] = await invoke('parse_log', {
-        log,
-        gamePath,
-      });
-      setResults(parsed);
+      const scan = await invokeGuarded<{ conflicts: ModConflict[] }>(
+        'scan_conflicts',
+        { gamePath, sort: 'wasted_bytes' }
+      );
+      setResults(scan.conflicts);
     } catch (err) {
       console.error(err);
-      setError(String(err));
+      setError(errorMessage(err));
     }
   }, [gamePath]);
 
@@ -137,6 +277,11 @@ const useParseLog = (gamePath: string) => {
           } else {
             const conflict = draft[index];
             conflict.skipped = conflict.skipped.filter(mod => mod.path !== path);
+            conflict.skipped_count = conflict.skipped.length;
+            conflict.wasted_bytes = conflict.skipped.reduce(
+              (sum, mod) => sum + mod.size,
+              0
+            );
             if (conflict.skipped.length === 0) {
               draft.splice(index, 1);
             }
@@ -149,7 +294,7 @@ const useParseLog = (gamePath: string) => {
 
   const removeLoadedMod = useCallback(
     async (index: number, path: string) => {
-      await invoke('delete_mods', { paths: [path] });
+      await deleteMods([path]);
       removeLog(index, 'loaded');
     },
     [removeLog]
@@ -157,7 +302,7 @@ const useParseLog = (gamePath: string) => {
 
   const removeSkippedMod = useCallback(
     async (index: number, path: string) => {
-      await invoke('delete_mods', { paths: [path] });
+      await deleteMods([path]);
       removeLog(index, 'skipped', path);
     },
     [removeLog]
@@ -166,7 +311,7 @@ const useParseLog = (gamePath: string) => {
   const removeOtherMods = useCallback(
     async (index: number, skipped: ModEntry[]) => {
       const paths = skipped.map(mod => mod.path);
-      await invoke('delete_mods', { paths });
+      await deleteMods(paths);
       removeLog(index, 'loaded');
     },
     [removeLog]
@@ -220,11 +365,19 @@ function ModItem({
             Remove this
           </button>
           <span>{mod.name}</span>
+          {mod.managed && (
+            <span
+              className='rounded bg-cyan-900 px-1 text-xs'
+              title='In a KKManager modpack folder; re-synced automatically'
+            >
+              modpack
+            </span>
+          )}
         </div>
 
         <span
           className={cn('text-neutral-500', {
-            'text-cyan-900': isSideloader(mod.path),
+            'text-cyan-900': mod.managed || isSideloader(mod.path),
           })}
         >
           {mod.path}
@@ -239,7 +392,8 @@ function ModItem({
         </span>
 
         <span className='text-neutral-500'>
-          {`Create at: ${formatDate(mod.created)}`}
+          {`Create at: ${mod.created_local ?? formatDate(mod.created)}`}
+          {mod.timestamp_clamped && ' (invalid file date)'}
         </span>
       </div>
 
@@ -264,7 +418,7 @@ function ModItem({
           </div>
           <div className='flex gap-4'>
             <h3 className='w-20'>description</h3>
-            <span>{mod.manifest.description}</span>
+            <span className='whitespace-pre-line'>{mod.manifest.description_display}</span>
           </div>
         </div>
       )}
@@ -368,7 +522,15 @@ function App() {
                   <span className='text-xs text-neutral-500'>
                     (Remove all skipped mods for this conflict)
                   </span>
+                  <span className='rounded bg-neutral-700 px-1 text-xs'>
+                    {`would free ${formatSize(conflict.wasted_bytes)}`}
+                  </span>
                 </div>
+                {conflict.note && (
+                  <div className='mt-1 ml-4 text-xs whitespace-pre-wrap text-yellow-200'>
+                    {`Note: ${conflict.note}`}
+                  </div>
+                )}
               </div>
 
               <div className='mt-2 space-y-2'>